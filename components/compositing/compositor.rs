@@ -20,19 +20,23 @@ use net_traits::image::base::Image;
 use net_traits::image::base::PixelFormat;
 use profile_traits::time::{self, ProfilerCategory, profile};
 use script_traits::{AnimationState, AnimationTickType, ConstellationMsg, LayoutControlMsg};
-use script_traits::{MouseButton, MouseEventType, ScrollState, TouchEventType, TouchId};
-use script_traits::{UntrustedNodeAddress, WindowSizeData, WindowSizeType};
-use script_traits::CompositorEvent::{MouseMoveEvent, MouseButtonEvent, TouchEvent};
+use script_traits::{MouseButton, MouseEventType, ScrollSnapInfo, ScrollState, TouchEventType};
+use script_traits::TouchId;
+use script_traits::{UntrustedNodeAddress, WheelDelta, WheelMode, WindowSizeData, WindowSizeType};
+use script_traits::CompositorEvent::{MouseMoveEvent, MouseButtonEvent, TouchEvent, WheelEvent};
+use scroll::{self, SmoothScroll};
 use servo_config::opts;
 use servo_geometry::DeviceIndependentPixel;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::env;
 use std::fs::{File, create_dir_all};
 use std::io::Write;
+use std::mem;
 use std::num::NonZeroU32;
+use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::mpsc::Sender;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use style_traits::{CSSPixel, DevicePixel, PinchZoomFactor};
 use style_traits::cursor::CursorKind;
 use style_traits::viewport::ViewportConstraints;
@@ -40,7 +44,7 @@ use time::{now, precise_time_ns, precise_time_s};
 use touch::{TouchHandler, TouchAction};
 use webrender;
 use webrender_api::{self, DeviceIntPoint, DevicePoint, HitTestFlags, HitTestResult};
-use webrender_api::{LayoutVector2D, ScrollLocation};
+use webrender_api::{ExternalScrollId, LayoutPoint, LayoutVector2D, ScrollLocation};
 use windowing::{self, EmbedderCoordinates, MouseWindowEvent, WebRenderDebugOption, WindowMethods};
 
 
@@ -57,6 +61,14 @@ enum NotReadyToPaint {
     WaitingOnConstellation,
 }
 
+/// The time without scroll events after which a scroll gesture is considered over, in
+/// milliseconds.
+const SCROLL_GESTURE_TIMEOUT_MS: u64 = 150;
+
+/// The time after which the compositor stops waiting for script to handle a wheel event and
+/// scrolls anyway, in milliseconds.
+const WHEEL_EVENT_TIMEOUT_MS: u64 = 500;
+
 // Default viewport constraints
 const MAX_ZOOM: f32 = 8.0;
 const MIN_ZOOM: f32 = 0.1;
@@ -160,6 +172,9 @@ pub struct IOCompositor<Window: WindowMethods> {
     /// Pending scroll/zoom events.
     pending_scroll_zoom_events: Vec<ScrollZoomEvent>,
 
+    /// Wheel events that scroll once script has handled them, in order.
+    pending_wheel_events: VecDeque<PendingWheelEvent>,
+
     /// Whether we're waiting on a recomposite after dispatching a scroll.
     waiting_for_results_of_scroll: bool,
 
@@ -170,8 +185,18 @@ pub struct IOCompositor<Window: WindowMethods> {
     /// Whether a scroll is in progress; i.e. whether the user's fingers are down.
     scroll_in_progress: bool,
 
+    /// The time of the last scroll event of the current scroll gesture, if any.
     in_scroll_transaction: Option<Instant>,
 
+    /// The scroll positions of the scroll roots when the current scroll gesture began.
+    scroll_positions_at_gesture_start: HashMap<ExternalScrollId, LayoutPoint>,
+
+    /// The scroll roots that are being scrolled in a smooth fashion.
+    smooth_scrolls: HashMap<ExternalScrollId, SmoothScroll>,
+
+    /// The CSS scroll snap points of the scroll containers of each pipeline.
+    scroll_snap_info: HashMap<PipelineId, Vec<ScrollSnapInfo>>,
+
     /// The webrender renderer.
     webrender: webrender::Renderer,
 
@@ -204,6 +229,15 @@ struct ScrollZoomEvent {
     event_count: u32,
 }
 
+/// A wheel event whose scroll waits for script to handle the events before it.
+struct PendingWheelEvent {
+    scroll_location: ScrollLocation,
+    cursor: DeviceIntPoint,
+    phase: TouchEventType,
+    /// When the event was sent to script, if it was.
+    dispatch_time: Option<Instant>,
+}
+
 #[derive(Debug, PartialEq)]
 enum CompositionRequest {
     NoCompositingNecessary,
@@ -308,6 +342,7 @@ impl<Window: WindowMethods> IOCompositor<Window> {
             composition_request: CompositionRequest::NoCompositingNecessary,
             touch_handler: TouchHandler::new(),
             pending_scroll_zoom_events: Vec::new(),
+            pending_wheel_events: VecDeque::new(),
             waiting_for_results_of_scroll: false,
             composite_target,
             shutdown_state: ShutdownState::NotShuttingDown,
@@ -324,6 +359,9 @@ impl<Window: WindowMethods> IOCompositor<Window> {
             ready_to_save_state: ReadyState::Unknown,
             scroll_in_progress: false,
             in_scroll_transaction: None,
+            scroll_positions_at_gesture_start: HashMap::new(),
+            smooth_scrolls: HashMap::new(),
+            scroll_snap_info: HashMap::new(),
             webrender: state.webrender,
             webrender_document: state.webrender_document,
             webrender_api: state.webrender_api,
@@ -411,6 +449,23 @@ impl<Window: WindowMethods> IOCompositor<Window> {
                 self.touch_handler.on_event_processed(result);
             }
 
+            (Msg::WheelEventProcessed(prevented), ShutdownState::NotShuttingDown) => {
+                self.on_wheel_event_processed(prevented);
+            }
+
+            (Msg::SmoothScroll(scroll_state), ShutdownState::NotShuttingDown) => {
+                let offset = scroll_state.scroll_offset;
+                let target = LayoutPoint::new(-offset.x, -offset.y);
+                let start = self.scroll_positions()
+                    .remove(&scroll_state.scroll_id)
+                    .unwrap_or(target);
+                self.start_smooth_scroll(scroll_state.scroll_id, start, target);
+            }
+
+            (Msg::ScrollSnapInfo(pipeline_id, snap_info), ShutdownState::NotShuttingDown) => {
+                self.scroll_snap_info.insert(pipeline_id, snap_info);
+            }
+
             (Msg::CreatePng(reply), ShutdownState::NotShuttingDown) => {
                 let res = self.composite_specific_target(CompositeTarget::WindowAndPng);
                 if let Err(ref e) = res {
@@ -499,6 +554,21 @@ impl<Window: WindowMethods> IOCompositor<Window> {
                 }
             }
 
+            (Msg::DispatchMouseEvent(event_type, button, point), ShutdownState::NotShuttingDown) => {
+                let point = point * self.device_pixels_per_page_px();
+                let event = match event_type {
+                    MouseEventType::Click => MouseWindowEvent::Click(button, point),
+                    MouseEventType::MouseDown => MouseWindowEvent::MouseDown(button, point),
+                    MouseEventType::MouseUp => MouseWindowEvent::MouseUp(button, point),
+                };
+                self.on_mouse_window_event_class(event);
+            }
+
+            (Msg::DispatchMouseMoveEvent(point), ShutdownState::NotShuttingDown) => {
+                let point = point * self.device_pixels_per_page_px();
+                self.on_mouse_window_move_event_class(point);
+            }
+
             // When we are shutting_down, we need to avoid performing operations
             // such as Paint that may crash because we have begun tearing down
             // the rest of our resources.
@@ -584,6 +654,7 @@ impl<Window: WindowMethods> IOCompositor<Window> {
 
     fn remove_pipeline_root_layer(&mut self, pipeline_id: PipelineId) {
         self.pipeline_details.remove(&pipeline_id);
+        self.scroll_snap_info.remove(&pipeline_id);
     }
 
     fn send_window_size(&self, size_type: WindowSizeType) {
@@ -778,6 +849,18 @@ impl<Window: WindowMethods> IOCompositor<Window> {
                     point.cast()
                 )
             }
+            TouchAction::StartPanning(delta) => {
+                // https://w3c.github.io/pointerevents/#the-pointercancel-event
+                for touch_point in self.touch_handler.active_touch_points.clone() {
+                    self.send_touch_event(TouchEventType::Cancel, touch_point.id, touch_point.point);
+                }
+                self.on_scroll_window_event(
+                    ScrollLocation::Delta(
+                        LayoutVector2D::from_untyped(&delta.to_untyped())
+                    ),
+                    point.cast()
+                )
+            }
             TouchAction::Zoom(magnification, scroll_delta) => {
                 let cursor = TypedPoint2D::new(-1, -1);  // Make sure this hits the base layer.
                 self.pending_scroll_zoom_events.push(ScrollZoomEvent {
@@ -796,7 +879,9 @@ impl<Window: WindowMethods> IOCompositor<Window> {
     }
 
     fn on_touch_up(&mut self, identifier: TouchId, point: DevicePoint) {
-        self.send_touch_event(TouchEventType::Up, identifier, point);
+        if !self.touch_handler.is_cancelled(identifier) {
+            self.send_touch_event(TouchEventType::Up, identifier, point);
+        }
 
         if let TouchAction::Click = self.touch_handler.on_touch_up(identifier, point) {
             self.simulate_mouse_click(point);
@@ -804,9 +889,12 @@ impl<Window: WindowMethods> IOCompositor<Window> {
     }
 
     fn on_touch_cancel(&mut self, identifier: TouchId, point: DevicePoint) {
-        // Send the event to script.
+        // Send the event to script, unless it was already cancelled there when it started panning.
+        let cancelled = self.touch_handler.is_cancelled(identifier);
         self.touch_handler.on_touch_cancel(identifier, point);
-        self.send_touch_event(TouchEventType::Cancel, identifier, point);
+        if !cancelled {
+            self.send_touch_event(TouchEventType::Cancel, identifier, point);
+        }
     }
 
     /// <http://w3c.github.io/touch-events/#mouse-events>
@@ -833,10 +921,117 @@ impl<Window: WindowMethods> IOCompositor<Window> {
         }
     }
 
+    /// Sends a wheel event to the content under the cursor, and scrolls once script has handled
+    /// it, unless it prevented the scroll. Scrolls that follow wait for it, to keep them in order.
+    ///
+    /// <https://w3c.github.io/uievents/#events-wheelevents>
+    pub fn on_wheel_event(&mut self,
+                          scroll_location: ScrollLocation,
+                          cursor: DeviceIntPoint,
+                          phase: TouchEventType) {
+        self.flush_timed_out_wheel_events();
+        let mut event = PendingWheelEvent {
+            scroll_location,
+            cursor,
+            phase,
+            dispatch_time: None,
+        };
+
+        let delta = match scroll_location {
+            ScrollLocation::Delta(delta) if delta != LayoutVector2D::zero() => Some(delta),
+            _ => None,
+        };
+        let results = self.hit_test_at_point(cursor.to_f32());
+        if let (Some(delta), Some(item)) = (delta, results.items.first()) {
+            // Wheel deltas are in CSS pixels, and positive towards the bottom right of the page,
+            // unlike scroll deltas.
+            let dppx = self.device_pixels_per_page_px().get();
+            let wheel_delta = WheelDelta {
+                x: -(delta.x / dppx) as f64,
+                y: -(delta.y / dppx) as f64,
+                z: 0.0,
+                mode: WheelMode::DeltaPixel,
+            };
+            let event_to_send = WheelEvent(
+                wheel_delta,
+                item.point_in_viewport.to_untyped(),
+                Some(UntrustedNodeAddress(item.tag.0 as *const c_void)),
+            );
+            let pipeline_id = PipelineId::from_webrender(item.pipeline);
+            let msg = ConstellationMsg::ForwardEvent(pipeline_id, event_to_send);
+            match self.constellation_chan.send(msg) {
+                Ok(()) => event.dispatch_time = Some(Instant::now()),
+                Err(e) => warn!("Sending event to constellation failed ({}).", e),
+            }
+        }
+
+        if event.dispatch_time.is_none() && self.pending_wheel_events.is_empty() {
+            return self.on_scroll_event(scroll_location, cursor, phase);
+        }
+        self.pending_wheel_events.push_back(event);
+    }
+
+    fn on_wheel_event_processed(&mut self, prevented: bool) {
+        let event = match self.pending_wheel_events.pop_front() {
+            Some(event) => event,
+            None => return warn!("Got a wheel event result without a pending wheel event"),
+        };
+        match (prevented, event.phase) {
+            (false, phase) => self.on_scroll_event(event.scroll_location, event.cursor, phase),
+            (true, TouchEventType::Move) => {},
+            // Still start or end the scroll gesture, without scrolling.
+            (true, phase) => {
+                self.on_scroll_event(ScrollLocation::Delta(LayoutVector2D::zero()), event.cursor, phase);
+            },
+        }
+        self.scroll_for_undispatched_wheel_events();
+    }
+
+    /// Scrolls for the wheel events that script hasn't handled in time, for example because the
+    /// pipeline they were sent to has gone away since.
+    fn flush_timed_out_wheel_events(&mut self) {
+        let timeout = Duration::from_millis(WHEEL_EVENT_TIMEOUT_MS);
+        while self.pending_wheel_events.front()
+            .and_then(|event| event.dispatch_time)
+            .map_or(false, |dispatch_time| dispatch_time.elapsed() > timeout)
+        {
+            self.on_wheel_event_processed(false);
+        }
+    }
+
+    /// Scrolls for the wheel events at the front of the queue that weren't sent to script.
+    fn scroll_for_undispatched_wheel_events(&mut self) {
+        while self.pending_wheel_events.front().map_or(false, |event| event.dispatch_time.is_none()) {
+            if let Some(event) = self.pending_wheel_events.pop_front() {
+                self.on_scroll_event(event.scroll_location, event.cursor, event.phase);
+            }
+        }
+    }
+
+    /// Lets the document under the point know about files dropped onto it from outside of the
+    /// window.
+    pub fn on_drop_files_event(&mut self, paths: Vec<PathBuf>, point: DevicePoint) {
+        let results = self.hit_test_at_point(point);
+        if let Some(item) = results.items.first() {
+            let pipeline_id = PipelineId::from_webrender(item.pipeline);
+            let msg = ConstellationMsg::DropFiles(
+                pipeline_id,
+                paths,
+                item.point_in_viewport.to_untyped(),
+                Some(UntrustedNodeAddress(item.tag.0 as *const c_void)),
+            );
+            if let Err(e) = self.constellation_chan.send(msg) {
+                warn!("Sending dropped files to constellation failed ({}).", e);
+            }
+        }
+    }
+
     fn on_scroll_window_event(&mut self,
                               scroll_location: ScrollLocation,
                               cursor: DeviceIntPoint) {
+        self.start_scroll_gesture_if_necessary();
         self.in_scroll_transaction = Some(Instant::now());
+        self.update_animation_state();
         self.pending_scroll_zoom_events.push(ScrollZoomEvent {
             magnification: 1.0,
             scroll_location: scroll_location,
@@ -848,7 +1043,10 @@ impl<Window: WindowMethods> IOCompositor<Window> {
     fn on_scroll_start_window_event(&mut self,
                                     scroll_location: ScrollLocation,
                                     cursor: DeviceIntPoint) {
+        self.start_scroll_gesture_if_necessary();
         self.scroll_in_progress = true;
+        self.in_scroll_transaction = Some(Instant::now());
+        self.update_animation_state();
         self.pending_scroll_zoom_events.push(ScrollZoomEvent {
             magnification: 1.0,
             scroll_location: scroll_location,
@@ -869,6 +1067,116 @@ impl<Window: WindowMethods> IOCompositor<Window> {
         });
     }
 
+    /// Stops any smooth scroll when the user starts scrolling, and remembers where the scroll
+    /// containers that snap were, to snap those that the gesture moves once it is over.
+    fn start_scroll_gesture_if_necessary(&mut self) {
+        if self.in_scroll_transaction.is_some() {
+            return;
+        }
+        if !self.smooth_scrolls.is_empty() {
+            self.smooth_scrolls.clear();
+            self.send_viewport_rects();
+        }
+        self.scroll_positions_at_gesture_start = if self.scroll_snap_info.is_empty() {
+            HashMap::new()
+        } else {
+            self.scroll_positions()
+        };
+    }
+
+    /// Whether the scroll containers that the current scroll gesture moved may have to snap
+    /// once it is over. The window keeps animating until then, so that the compositor notices
+    /// that the gesture is over even if no event arrives after it.
+    fn scroll_snap_pending(&self) -> bool {
+        self.in_scroll_transaction.is_some() && !self.scroll_snap_info.is_empty()
+    }
+
+    /// Snaps the scroll containers that the current scroll gesture moved to their CSS scroll
+    /// snap points once the gesture is over. A gesture is over when no scroll event has been
+    /// received for a while and the fingers are up, as mouse wheels don't report the end of a
+    /// gesture.
+    fn snap_scroll_positions_if_necessary(&mut self) {
+        let gesture_over = self.in_scroll_transaction.map_or(false, |last_scroll_time| {
+            last_scroll_time.elapsed() > Duration::from_millis(SCROLL_GESTURE_TIMEOUT_MS)
+        });
+        if !gesture_over || self.scroll_in_progress || self.waiting_for_results_of_scroll ||
+            !self.pending_scroll_zoom_events.is_empty()
+        {
+            return;
+        }
+        self.in_scroll_transaction = None;
+        self.update_animation_state();
+
+        let start_positions =
+            mem::replace(&mut self.scroll_positions_at_gesture_start, HashMap::new());
+        if self.scroll_snap_info.is_empty() {
+            return;
+        }
+
+        let positions = self.scroll_positions();
+        let mut snaps = vec![];
+        for snap_info in self.scroll_snap_info.values().flat_map(|snap_info| snap_info.iter()) {
+            let position = match positions.get(&snap_info.scroll_id) {
+                Some(position) => *position,
+                None => continue,
+            };
+            if start_positions.get(&snap_info.scroll_id) == Some(&position) {
+                continue;
+            }
+            if let Some(target) = scroll::snap_position(snap_info, position) {
+                snaps.push((snap_info.scroll_id, position, target));
+            }
+        }
+        for (scroll_id, position, target) in snaps {
+            self.start_smooth_scroll(scroll_id, position, target);
+        }
+    }
+
+    /// Returns the scroll position of every scroll root, which is the opposite of its offset.
+    fn scroll_positions(&self) -> HashMap<ExternalScrollId, LayoutPoint> {
+        self.webrender_api
+            .get_scroll_node_state(self.webrender_document)
+            .into_iter()
+            .map(|state| (state.id, LayoutPoint::new(-state.scroll_offset.x, -state.scroll_offset.y)))
+            .collect()
+    }
+
+    fn start_smooth_scroll(&mut self, scroll_id: ExternalScrollId, start: LayoutPoint, target: LayoutPoint) {
+        let smooth_scroll = SmoothScroll::new(start, target, Instant::now());
+        self.smooth_scrolls.insert(scroll_id, smooth_scroll);
+        self.window.set_animation_state(windowing::AnimationState::Animating);
+        self.composite_if_necessary(CompositingReason::SmoothScroll);
+    }
+
+    /// Moves the scroll roots that are scrolled in a smooth fashion to their position for the
+    /// current time. Layout is told about the new scroll offsets once every smooth scroll is
+    /// complete.
+    fn tick_smooth_scrolls(&mut self) {
+        let now = Instant::now();
+        let mut txn = webrender_api::Transaction::new();
+        let mut completed_scrolls = vec![];
+        for (scroll_id, smooth_scroll) in &self.smooth_scrolls {
+            let (position, complete) = smooth_scroll.position_at(now);
+            txn.scroll_node_with_id(position, *scroll_id, webrender_api::ScrollClamping::ToContentBounds);
+            if complete {
+                completed_scrolls.push(*scroll_id);
+            }
+        }
+        txn.generate_frame();
+        self.webrender_api.send_transaction(self.webrender_document, txn);
+
+        for scroll_id in completed_scrolls {
+            self.smooth_scrolls.remove(&scroll_id);
+        }
+        if self.smooth_scrolls.is_empty() {
+            self.send_viewport_rects();
+        }
+
+        // Compositing waits for the next frame, which paces the smooth scrolls. It also updates
+        // the animation state of the window once they are complete.
+        self.composite_if_necessary(CompositingReason::SmoothScroll);
+    }
+
     fn process_pending_scroll_events(&mut self) {
         let had_events = self.pending_scroll_zoom_events.len() > 0;
 
@@ -957,17 +1265,28 @@ impl<Window: WindowMethods> IOCompositor<Window> {
                    pipeline_ids.push(*pipeline_id);
             }
         }
-        let animation_state = if pipeline_ids.is_empty() {
-            windowing::AnimationState::Idle
-        } else {
-            windowing::AnimationState::Animating
-        };
-        self.window.set_animation_state(animation_state);
+        self.update_animation_state();
         for pipeline_id in &pipeline_ids {
             self.tick_animations_for_pipeline(*pipeline_id)
         }
     }
 
+    /// Keeps the window animating while there are animations running in visible pipelines,
+    /// smooth scrolls, or a scroll gesture that may have to snap once it is over.
+    fn update_animation_state(&self) {
+        let animations_running = self.pipeline_details.values().any(|details| {
+            (details.animations_running || details.animation_callbacks_running) && details.visible
+        });
+        let animation_state = if animations_running || !self.smooth_scrolls.is_empty() ||
+            self.scroll_snap_pending()
+        {
+            windowing::AnimationState::Animating
+        } else {
+            windowing::AnimationState::Idle
+        };
+        self.window.set_animation_state(animation_state);
+    }
+
     fn tick_animations_for_pipeline(&mut self, pipeline_id: PipelineId) {
         let animation_callbacks_running = self.pipeline_details(pipeline_id).animation_callbacks_running;
         if animation_callbacks_running {
@@ -1255,6 +1574,7 @@ impl<Window: WindowMethods> IOCompositor<Window> {
                     format: PixelFormat::RGB8,
                     bytes: ipc::IpcSharedMemory::from_bytes(&*img),
                     id: None,
+                    animation: None,
                 })
             }
             #[cfg(feature = "gleam")]
@@ -1351,6 +1671,11 @@ impl<Window: WindowMethods> IOCompositor<Window> {
         if !self.pending_scroll_zoom_events.is_empty() && !self.waiting_for_results_of_scroll {
             self.process_pending_scroll_events()
         }
+
+        self.snap_scroll_positions_if_necessary();
+        if !self.smooth_scrolls.is_empty() {
+            self.tick_smooth_scrolls();
+        }
         self.shutdown_state != ShutdownState::FinishedShuttingDown
     }
 
@@ -1472,4 +1797,6 @@ pub enum CompositingReason {
     NewWebRenderFrame,
     /// WebRender has processed a scroll event and has generated a new frame.
     NewWebRenderScrollFrame,
+    /// A scroll root is being scrolled in a smooth fashion.
+    SmoothScroll,
 }
@@ -13,9 +13,12 @@ use msg::constellation_msg::{PipelineId, TopLevelBrowsingContextId};
 use net_traits::image::base::Image;
 use profile_traits::mem;
 use profile_traits::time;
-use script_traits::{AnimationState, ConstellationMsg, EventResult};
+use euclid::TypedPoint2D;
+use script_traits::{AnimationState, ConstellationMsg, EventResult, MouseButton, MouseEventType};
+use script_traits::{ScrollSnapInfo, ScrollState};
 use std::fmt::{Debug, Error, Formatter};
 use std::sync::mpsc::{Receiver, Sender};
+use style_traits::CSSPixel;
 use style_traits::viewport::ViewportConstraints;
 use webrender;
 use webrender_api::{self, DeviceIntPoint, DeviceUintSize};
@@ -80,6 +83,12 @@ pub enum Msg {
     Recomposite(CompositingReason),
     /// Script has handled a touch event, and either prevented or allowed default actions.
     TouchEventProcessed(EventResult),
+    /// Script has handled a wheel event, and either prevented its default action or not.
+    WheelEventProcessed(bool),
+    /// Scroll a scroll root to the given state in a smooth fashion.
+    SmoothScroll(ScrollState),
+    /// The scroll snap points of the scroll containers of a pipeline have changed.
+    ScrollSnapInfo(PipelineId, Vec<ScrollSnapInfo>),
     /// Composite to a PNG file and return the Image over a passed channel.
     CreatePng(IpcSender<Option<Image>>),
     /// Alerts the compositor that the viewport has been constrained in some manner
@@ -114,6 +123,10 @@ pub enum Msg {
     GetScreenSize(IpcSender<DeviceUintSize>),
     /// Get screen available size.
     GetScreenAvailSize(IpcSender<DeviceUintSize>),
+    /// Handle a mouse button event at the given point as if it came from the window.
+    DispatchMouseEvent(MouseEventType, MouseButton, TypedPoint2D<f32, CSSPixel>),
+    /// Handle the mouse moving to the given point as if it came from the window.
+    DispatchMouseMoveEvent(TypedPoint2D<f32, CSSPixel>),
 }
 
 impl Debug for Msg {
@@ -124,6 +137,9 @@ impl Debug for Msg {
             Msg::SetFrameTree(..) => write!(f, "SetFrameTree"),
             Msg::Recomposite(..) => write!(f, "Recomposite"),
             Msg::TouchEventProcessed(..) => write!(f, "TouchEventProcessed"),
+            Msg::WheelEventProcessed(..) => write!(f, "WheelEventProcessed"),
+            Msg::SmoothScroll(..) => write!(f, "SmoothScroll"),
+            Msg::ScrollSnapInfo(..) => write!(f, "ScrollSnapInfo"),
             Msg::CreatePng(..) => write!(f, "CreatePng"),
             Msg::ViewportConstrained(..) => write!(f, "ViewportConstrained"),
             Msg::IsReadyToSaveImageReply(..) => write!(f, "IsReadyToSaveImageReply"),
@@ -136,6 +152,8 @@ impl Debug for Msg {
             Msg::GetClientWindow(..) => write!(f, "GetClientWindow"),
             Msg::GetScreenSize(..) => write!(f, "GetScreenSize"),
             Msg::GetScreenAvailSize(..) => write!(f, "GetScreenAvailSize"),
+            Msg::DispatchMouseEvent(..) => write!(f, "DispatchMouseEvent"),
+            Msg::DispatchMouseMoveEvent(..) => write!(f, "DispatchMouseMoveEvent"),
         }
     }
 }
@@ -31,6 +31,9 @@ pub use compositor_thread::CompositorProxy;
 pub use compositor::IOCompositor;
 pub use compositor::RenderNotifier;
 pub use compositor::ShutdownState;
+// For unit tests:
+pub use scroll::{SmoothScroll, snap_position};
+pub use touch::{TouchAction, TouchHandler, TouchState};
 use euclid::TypedSize2D;
 use ipc_channel::ipc::IpcSender;
 use msg::constellation_msg::PipelineId;
@@ -42,6 +45,7 @@ mod compositor;
 pub mod compositor_thread;
 #[cfg(feature = "gleam")]
 mod gl;
+mod scroll;
 mod touch;
 pub mod windowing;
 
@@ -0,0 +1,82 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Smooth scrolling and CSS scroll snapping.
+
+use script_traits::{ScrollSnapAxis, ScrollSnapInfo, ScrollSnapStrictness};
+use std::cmp::Ordering;
+use std::time::{Duration, Instant};
+use webrender_api::LayoutPoint;
+
+/// The duration of a smooth scroll, in milliseconds.
+const SMOOTH_SCROLL_DURATION_MS: f32 = 300.0;
+
+/// The distance from a snap position, in CSS pixels, within which a scroll container with
+/// proximity snapping snaps to it.
+const SNAP_PROXIMITY: f32 = 100.0;
+
+/// A scroll root that is being scrolled in a smooth fashion.
+pub struct SmoothScroll {
+    start: LayoutPoint,
+    target: LayoutPoint,
+    start_time: Instant,
+}
+
+impl SmoothScroll {
+    pub fn new(start: LayoutPoint, target: LayoutPoint, start_time: Instant) -> SmoothScroll {
+        SmoothScroll {
+            start,
+            target,
+            start_time,
+        }
+    }
+
+    /// Returns the scroll position at the given time, and whether the scroll is complete.
+    pub fn position_at(&self, now: Instant) -> (LayoutPoint, bool) {
+        let elapsed = duration_to_ms(now.duration_since(self.start_time));
+        let progress = elapsed / SMOOTH_SCROLL_DURATION_MS;
+        if progress >= 1.0 {
+            return (self.target, true);
+        }
+
+        // Ease in and out, so that the scroll neither starts nor stops abruptly.
+        let eased_progress = if progress < 0.5 {
+            4.0 * progress * progress * progress
+        } else {
+            1.0 - (2.0 - 2.0 * progress).powi(3) / 2.0
+        };
+        (self.start.lerp(self.target, eased_progress), false)
+    }
+}
+
+/// Returns the position that a scroll container with the given snap points comes to rest on
+/// when a gesture leaves it at `position`, or `None` if it rests where it is.
+pub fn snap_position(snap_info: &ScrollSnapInfo, position: LayoutPoint) -> Option<LayoutPoint> {
+    let x = snap_info.x.as_ref().and_then(|axis| snap_axis_position(axis, position.x));
+    let y = snap_info.y.as_ref().and_then(|axis| snap_axis_position(axis, position.y));
+    if x.is_none() && y.is_none() {
+        return None;
+    }
+    Some(LayoutPoint::new(x.unwrap_or(position.x), y.unwrap_or(position.y)))
+}
+
+fn snap_axis_position(axis: &ScrollSnapAxis, position: f32) -> Option<f32> {
+    let closest = axis.positions.iter().cloned().min_by(|a, b| {
+        (a - position).abs().partial_cmp(&(b - position).abs()).unwrap_or(Ordering::Equal)
+    })?;
+    if closest == position {
+        return None;
+    }
+    match axis.strictness {
+        ScrollSnapStrictness::Mandatory => Some(closest),
+        ScrollSnapStrictness::Proximity if (closest - position).abs() <= SNAP_PROXIMITY => {
+            Some(closest)
+        },
+        ScrollSnapStrictness::Proximity => None,
+    }
+}
+
+fn duration_to_ms(duration: Duration) -> f32 {
+    duration.as_secs() as f32 * 1000.0 + duration.subsec_nanos() as f32 / 1_000_000.0
+}
@@ -0,0 +1,78 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+extern crate compositing;
+extern crate script_traits;
+extern crate webrender_api;
+
+use compositing::{SmoothScroll, snap_position};
+use script_traits::{ScrollSnapAxis, ScrollSnapInfo, ScrollSnapStrictness};
+use std::time::{Duration, Instant};
+use webrender_api::{ExternalScrollId, LayoutPoint, PipelineId};
+
+fn snap_info(x: Option<ScrollSnapAxis>, y: Option<ScrollSnapAxis>) -> ScrollSnapInfo {
+    ScrollSnapInfo {
+        scroll_id: ExternalScrollId(0, PipelineId(0, 0)),
+        x: x,
+        y: y,
+    }
+}
+
+fn axis(strictness: ScrollSnapStrictness, positions: &[f32]) -> Option<ScrollSnapAxis> {
+    Some(ScrollSnapAxis {
+        strictness: strictness,
+        positions: positions.to_vec(),
+    })
+}
+
+#[test]
+fn test_mandatory_snapping_goes_to_the_closest_position() {
+    let info = snap_info(None, axis(ScrollSnapStrictness::Mandatory, &[0.0, 400.0, 800.0]));
+    assert_eq!(snap_position(&info, LayoutPoint::new(30.0, 250.0)), Some(LayoutPoint::new(30.0, 400.0)));
+    assert_eq!(snap_position(&info, LayoutPoint::new(0.0, 1000.0)), Some(LayoutPoint::new(0.0, 800.0)));
+}
+
+#[test]
+fn test_positions_on_a_snap_position_stay() {
+    let info = snap_info(None, axis(ScrollSnapStrictness::Mandatory, &[0.0, 400.0]));
+    assert_eq!(snap_position(&info, LayoutPoint::new(0.0, 400.0)), None);
+}
+
+#[test]
+fn test_proximity_snapping_only_snaps_nearby() {
+    let info = snap_info(axis(ScrollSnapStrictness::Proximity, &[0.0, 500.0]), None);
+    assert_eq!(snap_position(&info, LayoutPoint::new(450.0, 0.0)), Some(LayoutPoint::new(500.0, 0.0)));
+    assert_eq!(snap_position(&info, LayoutPoint::new(250.0, 0.0)), None);
+}
+
+#[test]
+fn test_snapping_on_both_axes() {
+    let info = snap_info(axis(ScrollSnapStrictness::Mandatory, &[0.0, 100.0]),
+                         axis(ScrollSnapStrictness::Proximity, &[0.0, 1000.0]));
+    // The vertical position is too far from any snap position to snap.
+    assert_eq!(snap_position(&info, LayoutPoint::new(70.0, 500.0)), Some(LayoutPoint::new(100.0, 500.0)));
+    assert_eq!(snap_position(&info, LayoutPoint::new(20.0, 980.0)), Some(LayoutPoint::new(0.0, 1000.0)));
+}
+
+#[test]
+fn test_smooth_scroll_eases_to_its_target() {
+    let start_time = Instant::now();
+    let smooth_scroll = SmoothScroll::new(LayoutPoint::new(0.0, 0.0), LayoutPoint::new(0.0, 100.0), start_time);
+
+    assert_eq!(smooth_scroll.position_at(start_time), (LayoutPoint::new(0.0, 0.0), false));
+
+    // Halfway through, the scroll is halfway to its target.
+    let (position, complete) = smooth_scroll.position_at(start_time + Duration::from_millis(150));
+    assert!((position.y - 50.0).abs() < 0.01, "{:?}", position);
+    assert!(!complete);
+
+    // It starts slowly.
+    let (position, _) = smooth_scroll.position_at(start_time + Duration::from_millis(30));
+    assert!(position.y > 0.0 && position.y < 10.0, "{:?}", position);
+
+    let end = smooth_scroll.position_at(start_time + Duration::from_millis(300));
+    assert_eq!(end, (LayoutPoint::new(0.0, 100.0), true));
+    let after_end = smooth_scroll.position_at(start_time + Duration::from_secs(5));
+    assert_eq!(after_end, (LayoutPoint::new(0.0, 100.0), true));
+}
@@ -0,0 +1,84 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+extern crate compositing;
+extern crate euclid;
+extern crate script_traits;
+
+use compositing::{TouchAction, TouchHandler, TouchState};
+use euclid::{TypedPoint2D, TypedVector2D};
+use script_traits::{AllowedTouchActions, EventResult, TouchId};
+
+const NONE: AllowedTouchActions = AllowedTouchActions { pan_x: false, pan_y: false, zoom: false };
+const PAN_X: AllowedTouchActions = AllowedTouchActions { pan_x: true, pan_y: false, zoom: false };
+
+/// Returns a touch handler with a touch point whose `touchstart` script didn't prevent.
+fn touching(allowed_actions: AllowedTouchActions) -> TouchHandler {
+    let mut handler = TouchHandler::new();
+    handler.on_touch_down(TouchId(0), TypedPoint2D::new(100., 100.));
+    assert_eq!(handler.state, TouchState::WaitingForScript);
+    handler.on_event_processed(EventResult::DefaultAllowed(allowed_actions));
+    assert_eq!(handler.state, TouchState::Touching);
+    handler
+}
+
+fn assert_starts_panning(action: TouchAction, x: f32, y: f32) {
+    match action {
+        TouchAction::StartPanning(delta) => assert_eq!(delta, TypedVector2D::new(x, y)),
+        action => panic!("expected the touch point to start panning, got {:?}", action),
+    }
+}
+
+fn assert_dispatches_event(action: TouchAction) {
+    match action {
+        TouchAction::DispatchEvent => {},
+        action => panic!("expected the touch point to be left to script, got {:?}", action),
+    }
+}
+
+#[test]
+fn test_touch_action_auto_pans() {
+    let mut handler = touching(AllowedTouchActions::all());
+    assert_starts_panning(handler.on_touch_move(TouchId(0), TypedPoint2D::new(100., 150.)), 0., 50.);
+    assert_eq!(handler.state, TouchState::Panning);
+    assert!(handler.is_cancelled(TouchId(0)));
+}
+
+#[test]
+fn test_touch_action_none_suppresses_panning() {
+    let mut handler = touching(NONE);
+    assert_dispatches_event(handler.on_touch_move(TouchId(0), TypedPoint2D::new(100., 150.)));
+    assert_eq!(handler.state, TouchState::DefaultPrevented);
+    assert!(!handler.is_cancelled(TouchId(0)));
+
+    // The rest of the touch sequence stays with script.
+    assert_dispatches_event(handler.on_touch_move(TouchId(0), TypedPoint2D::new(150., 100.)));
+    assert_eq!(handler.state, TouchState::DefaultPrevented);
+}
+
+#[test]
+fn test_touch_action_none_suppresses_zooming() {
+    let mut handler = touching(NONE);
+    handler.on_touch_down(TouchId(1), TypedPoint2D::new(200., 200.));
+    assert_eq!(handler.state, TouchState::MultiTouch);
+}
+
+#[test]
+fn test_touch_action_pan_x_only_pans_horizontally() {
+    let mut handler = touching(PAN_X);
+    assert_starts_panning(handler.on_touch_move(TouchId(0), TypedPoint2D::new(160., 130.)), 60., 0.);
+
+    let mut handler = touching(PAN_X);
+    assert_dispatches_event(handler.on_touch_move(TouchId(0), TypedPoint2D::new(130., 160.)));
+    assert_eq!(handler.state, TouchState::DefaultPrevented);
+}
+
+#[test]
+fn test_prevented_touchstart_suppresses_panning() {
+    let mut handler = TouchHandler::new();
+    handler.on_touch_down(TouchId(0), TypedPoint2D::new(100., 100.));
+    handler.on_event_processed(EventResult::DefaultPrevented);
+    assert_dispatches_event(handler.on_touch_move(TouchId(0), TypedPoint2D::new(100., 150.)));
+    assert_eq!(handler.state, TouchState::DefaultPrevented);
+}
@@ -4,7 +4,7 @@
 
 use euclid::{TypedPoint2D, TypedVector2D};
 use euclid::TypedScale;
-use script_traits::{EventResult, TouchId};
+use script_traits::{AllowedTouchActions, EventResult, TouchId};
 use self::TouchState::*;
 use style_traits::DevicePixel;
 
@@ -14,6 +14,11 @@ const TOUCH_PAN_MIN_SCREEN_PX: f32 = 20.0;
 pub struct TouchHandler {
     pub state: TouchState,
     pub active_touch_points: Vec<TouchPoint>,
+    /// The default touch behaviors that the `touch-action` of the target of the current touch
+    /// sequence allows.
+    allowed_actions: AllowedTouchActions,
+    /// The touch points that content stopped receiving events for when they started panning.
+    cancelled_touch_ids: Vec<TouchId>,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -58,6 +63,9 @@ pub enum TouchAction {
     Click,
     /// Scroll by the provided offset.
     Scroll(TypedVector2D<f32, DevicePixel>),
+    /// Cancel the touch sequence in content, since it started panning, and scroll by the
+    /// provided offset.
+    StartPanning(TypedVector2D<f32, DevicePixel>),
     /// Zoom by a magnification factor and scroll by the provided offset.
     Zoom(f32, TypedVector2D<f32, DevicePixel>),
     /// Send a JavaScript event to content.
@@ -71,6 +79,8 @@ impl TouchHandler {
         TouchHandler {
             state: Nothing,
             active_touch_points: Vec::new(),
+            allowed_actions: AllowedTouchActions::all(),
+            cancelled_touch_ids: Vec::new(),
         }
     }
 
@@ -78,12 +88,15 @@ impl TouchHandler {
         let point = TouchPoint::new(id, point);
         self.active_touch_points.push(point);
 
+        // A second touch point only zooms if `touch-action` allows it.
+        let zoom = self.allowed_actions.zoom;
         self.state = match self.state {
-            Nothing               => WaitingForScript,
-            Touching | Panning    => Pinching,
-            WaitingForScript      => WaitingForScript,
-            DefaultPrevented      => DefaultPrevented,
-            Pinching | MultiTouch => MultiTouch,
+            Nothing                    => WaitingForScript,
+            Touching | Panning if zoom => Pinching,
+            Touching | Panning         => MultiTouch,
+            WaitingForScript           => WaitingForScript,
+            DefaultPrevented           => DefaultPrevented,
+            Pinching | MultiTouch      => MultiTouch,
         };
     }
 
@@ -105,15 +118,29 @@ impl TouchHandler {
                 if delta.x.abs() > TOUCH_PAN_MIN_SCREEN_PX ||
                    delta.y.abs() > TOUCH_PAN_MIN_SCREEN_PX
                 {
-                    self.state = Panning;
-                    TouchAction::Scroll(delta)
+                    // A gesture along a direction that `touch-action` doesn't allow panning in
+                    // is left to script.
+                    let may_pan = if delta.x.abs() > delta.y.abs() {
+                        self.allowed_actions.pan_x
+                    } else {
+                        self.allowed_actions.pan_y
+                    };
+                    if may_pan {
+                        self.state = Panning;
+                        self.cancelled_touch_ids =
+                            self.active_touch_points.iter().map(|t| t.id).collect();
+                        TouchAction::StartPanning(self.pan_delta(delta))
+                    } else {
+                        self.state = DefaultPrevented;
+                        TouchAction::DispatchEvent
+                    }
                 } else {
                     TouchAction::NoAction
                 }
             }
             Panning => {
                 let delta = point - old_point;
-                TouchAction::Scroll(delta)
+                TouchAction::Scroll(self.pan_delta(delta))
             }
             DefaultPrevented => {
                 TouchAction::DispatchEvent
@@ -151,6 +178,7 @@ impl TouchHandler {
                 warn!("Got a touch up event for a non-active touch point");
             }
         }
+        self.cancelled_touch_ids.retain(|&cancelled_id| cancelled_id != id);
         match self.state {
             Touching => {
                 // FIXME: If the duration exceeds some threshold, send a contextmenu event instead.
@@ -185,6 +213,7 @@ impl TouchHandler {
                 return;
             }
         }
+        self.cancelled_touch_ids.retain(|&cancelled_id| cancelled_id != id);
         match self.state {
             Nothing => {}
             Touching | Panning => {
@@ -205,19 +234,35 @@ impl TouchHandler {
         if let WaitingForScript = self.state {
             self.state = match result {
                 EventResult::DefaultPrevented => DefaultPrevented,
-                EventResult::DefaultAllowed => match self.touch_count() {
-                    1 => Touching,
-                    2 => Pinching,
-                    _ => MultiTouch,
+                EventResult::DefaultAllowed(allowed_actions) => {
+                    self.allowed_actions = allowed_actions;
+                    match self.touch_count() {
+                        1 => Touching,
+                        2 if allowed_actions.zoom => Pinching,
+                        _ => MultiTouch,
+                    }
                 }
             }
         }
     }
 
+    /// Whether content stopped receiving the events of a touch point when it started panning.
+    pub fn is_cancelled(&self, id: TouchId) -> bool {
+        self.cancelled_touch_ids.contains(&id)
+    }
+
     fn touch_count(&self) -> usize {
         self.active_touch_points.len()
     }
 
+    /// Restricts a panning offset to the directions that `touch-action` allows panning in.
+    fn pan_delta(&self, delta: TypedVector2D<f32, DevicePixel>) -> TypedVector2D<f32, DevicePixel> {
+        TypedVector2D::new(
+            if self.allowed_actions.pan_x { delta.x } else { 0.0 },
+            if self.allowed_actions.pan_y { delta.y } else { 0.0 },
+        )
+    }
+
     fn pinch_distance_and_center(&self) -> (f32, TypedPoint2D<f32, DevicePixel>) {
         debug_assert_eq!(self.touch_count(), 2);
         let p0 = self.active_touch_points[0].point;
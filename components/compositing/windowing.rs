@@ -5,18 +5,19 @@
 //! Abstract windowing methods. The concrete implementations of these can be found in `platform/`.
 
 use embedder_traits::EventLoopWaker;
-use euclid::TypedScale;
+use euclid::{TypedScale, TypedSize2D};
 #[cfg(feature = "gleam")]
 use gleam::gl;
 use ipc_channel::ipc::IpcSender;
 use msg::constellation_msg::{Key, KeyModifiers, KeyState, TopLevelBrowsingContextId, TraversalDirection};
-use script_traits::{MouseButton, TouchEventType, TouchId};
+use script_traits::{MouseButton, PrintedPage, TouchEventType, TouchId};
 use servo_geometry::{DeviceIndependentPixel, DeviceUintLength};
 use servo_url::ServoUrl;
 use std::fmt::{Debug, Error, Formatter};
+use std::path::PathBuf;
 #[cfg(feature = "gleam")]
 use std::rc::Rc;
-use style_traits::DevicePixel;
+use style_traits::{CSSPixel, DevicePixel};
 use webrender_api::{DeviceIntPoint, DevicePoint, DeviceUintSize, DeviceUintRect, ScrollLocation};
 
 #[derive(Clone)]
@@ -60,6 +61,11 @@ pub enum WindowEvent {
     /// Sent when the user scrolls. The first point is the delta and the second point is the
     /// origin.
     Scroll(ScrollLocation, DeviceIntPoint, TouchEventType),
+    /// Sent when the user rotates the mouse wheel. Unlike `Scroll`, the page can prevent the
+    /// scroll that follows.
+    Wheel(ScrollLocation, DeviceIntPoint, TouchEventType),
+    /// Sent when the user drops files from outside of the window onto a point.
+    DropFiles(Vec<PathBuf>, DevicePoint),
     /// Sent when the user zooms.
     Zoom(f32),
     /// Simulated "pinch zoom" gesture for non-touch platforms (e.g. ctrl-scrollwheel).
@@ -87,6 +93,11 @@ pub enum WindowEvent {
     ToggleWebRenderDebug(WebRenderDebugOption),
     /// Capture current WebRender
     CaptureWebRender,
+    /// Lay a top level browsing context out on pages of the given size, for printing.
+    Print(TopLevelBrowsingContextId, TypedSize2D<f32, CSSPixel>, IpcSender<Vec<PrintedPage>>),
+    /// Sent when the user's preference for reduced motion changes, which keeps animated images
+    /// on their first frame.
+    SetPrefersReducedMotion(bool),
 }
 
 impl Debug for WindowEvent {
@@ -101,6 +112,8 @@ impl Debug for WindowEvent {
             WindowEvent::MouseWindowMoveEventClass(..) => write!(f, "MouseMove"),
             WindowEvent::Touch(..) => write!(f, "Touch"),
             WindowEvent::Scroll(..) => write!(f, "Scroll"),
+            WindowEvent::Wheel(..) => write!(f, "Wheel"),
+            WindowEvent::DropFiles(..) => write!(f, "DropFiles"),
             WindowEvent::Zoom(..) => write!(f, "Zoom"),
             WindowEvent::PinchZoom(..) => write!(f, "PinchZoom"),
             WindowEvent::ResetZoom => write!(f, "ResetZoom"),
@@ -113,6 +126,8 @@ impl Debug for WindowEvent {
             WindowEvent::SelectBrowser(..) => write!(f, "SelectBrowser"),
             WindowEvent::ToggleWebRenderDebug(..) => write!(f, "ToggleWebRenderDebug"),
             WindowEvent::CaptureWebRender => write!(f, "CaptureWebRender"),
+            WindowEvent::Print(..) => write!(f, "Print"),
+            WindowEvent::SetPrefersReducedMotion(..) => write!(f, "SetPrefersReducedMotion"),
         }
     }
 }
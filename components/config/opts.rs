@@ -119,7 +119,7 @@ pub struct Opts {
     pub profile_heartbeats: bool,
 
     /// `None` to disable debugger or `Some` with a port number to start a server to listen to
-    /// remote Firefox debugger connections.
+    /// Chrome DevTools Protocol connections.
     pub debugger_port: Option<u16>,
 
     /// `None` to disable devtools or `Some` with a port number to start a server to listen to
@@ -227,6 +227,9 @@ pub struct Opts {
 
     /// Print Progressive Web Metrics to console.
     pub print_pwm: bool,
+
+    /// Directory to write content process crash reports to.
+    pub crash_reports_dir: Option<PathBuf>,
 }
 
 fn print_usage(app: &str, opts: &Options) {
@@ -552,6 +555,7 @@ pub fn default_opts() -> Opts {
         certificate_path: None,
         unminify_js: false,
         print_pwm: false,
+        crash_reports_dir: None,
     }
 }
 
@@ -581,9 +585,9 @@ pub fn from_cmdline_args(args: &[String]) -> ArgumentParsingResult {
     opts.optopt("", "shaders",
         "Shaders will be loaded from the specified directory instead of using the builtin ones.", "");
     opts.optflag("z", "headless", "Headless mode");
-    opts.optflag("f", "hard-fail", "Exit on thread failure instead of displaying about:failure");
-    opts.optflag("F", "soft-fail", "Display about:failure on thread failure instead of exiting");
-    opts.optflagopt("", "remote-debugging-port", "Start remote debugger server on port", "2794");
+    opts.optflag("f", "hard-fail", "Exit on thread failure instead of displaying a crash page");
+    opts.optflag("F", "soft-fail", "Display a crash page on thread failure instead of exiting");
+    opts.optflagopt("", "remote-debugging-port", "Start Chrome DevTools Protocol server on port", "2794");
     opts.optflagopt("", "devtools", "Start remote devtools server on port", "6000");
     opts.optflagopt("", "webdriver", "Start remote WebDriver server on port", "7000");
     opts.optopt("", "resolution", "Set window resolution.", "1024x740");
@@ -618,6 +622,7 @@ pub fn from_cmdline_args(args: &[String]) -> ArgumentParsingResult {
     opts.optopt("", "profiler-db-pass", "Profiler database password", "");
     opts.optopt("", "profiler-db-name", "Profiler database name", "");
     opts.optflag("", "print-pwm", "Print Progressive Web Metrics");
+    opts.optopt("", "crash-reports-dir", "Directory to write crash reports to", "");
 
     let opt_match = match opts.parse(args) {
         Ok(m) => m,
@@ -853,6 +858,7 @@ pub fn from_cmdline_args(args: &[String]) -> ArgumentParsingResult {
         certificate_path: opt_match.opt_str("certificate-path"),
         unminify_js: opt_match.opt_present("unminify-js"),
         print_pwm: opt_match.opt_present("print-pwm"),
+        crash_reports_dir: opt_match.opt_str("crash-reports-dir").map(Into::into),
     };
 
     set_defaults(opts);
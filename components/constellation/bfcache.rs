@@ -0,0 +1,76 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use msg::constellation_msg::PipelineId;
+use std::collections::VecDeque;
+
+/// The back-forward cache: the pipelines of documents that were navigated away from, but kept
+/// alive and frozen so that traversing the session history back to them doesn't reload them.
+///
+/// The cache is shared by all top-level browsing contexts, and limited to a memory budget.
+/// When it is over budget, the least recently used pipelines are evicted first.
+pub struct BackForwardCache {
+    /// The cached pipelines with the estimated size of their documents in bytes,
+    /// with the least recently used at the front.
+    entries: VecDeque<(PipelineId, usize)>,
+    /// The size in bytes that the cached documents may use in total.
+    budget: usize,
+}
+
+impl BackForwardCache {
+    pub fn new(budget: usize) -> BackForwardCache {
+        BackForwardCache {
+            entries: VecDeque::new(),
+            budget: budget,
+        }
+    }
+
+    /// Add the pipeline that was navigated away from, as the most recently used.
+    /// Its size is unknown until its document has been measured.
+    pub fn insert(&mut self, pipeline_id: PipelineId) {
+        self.remove(pipeline_id);
+        self.entries.push_back((pipeline_id, 0));
+    }
+
+    /// Remove a pipeline, because it was navigated back to or closed.
+    /// Returns whether it was cached.
+    pub fn remove(&mut self, pipeline_id: PipelineId) -> bool {
+        let len = self.entries.len();
+        self.entries.retain(|&(cached_id, _)| cached_id != pipeline_id);
+        self.entries.len() != len
+    }
+
+    pub fn contains(&self, pipeline_id: PipelineId) -> bool {
+        self.entries.iter().any(|&(cached_id, _)| cached_id == pipeline_id)
+    }
+
+    /// Record the measured size of the document of a cached pipeline.
+    pub fn set_size(&mut self, pipeline_id: PipelineId, size: usize) {
+        if let Some(entry) = self.entries.iter_mut().find(|entry| entry.0 == pipeline_id) {
+            entry.1 = size;
+        }
+    }
+
+    /// The estimated size of all the cached documents.
+    pub fn total_size(&self) -> usize {
+        self.entries.iter().map(|&(_, size)| size).sum()
+    }
+
+    /// Remove the least recently used pipelines until the cache is within its budget,
+    /// returning them so that they can be closed.
+    pub fn evict_over_budget(&mut self) -> Vec<PipelineId> {
+        let mut evicted = vec![];
+        let mut total_size = self.total_size();
+        while total_size > self.budget {
+            match self.entries.pop_front() {
+                Some((pipeline_id, size)) => {
+                    total_size -= size;
+                    evicted.push(pipeline_id);
+                },
+                None => break,
+            }
+        }
+        evicted
+    }
+}
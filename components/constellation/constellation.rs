@@ -92,19 +92,19 @@
 use backtrace::Backtrace;
 use bluetooth_traits::BluetoothRequest;
 use browsingcontext::{AllBrowsingContextsIterator, BrowsingContext, FullyActiveBrowsingContextsIterator};
+use bfcache::BackForwardCache;
 use canvas::canvas_paint_thread::CanvasPaintThread;
 use canvas::webgl_thread::WebGLThreads;
 use canvas_traits::canvas::CanvasId;
 use canvas_traits::canvas::CanvasMsg;
-use clipboard::{ClipboardContext, ClipboardProvider};
 use compositing::SendableFrameTree;
 use compositing::compositor_thread::CompositorProxy;
 use compositing::compositor_thread::Msg as ToCompositorMsg;
 use debugger;
 use devtools_traits::{ChromeToDevtoolsControlMsg, DevtoolsControlMsg};
 use embedder_traits::{EmbedderMsg, EmbedderProxy};
-use euclid::{Size2D, TypedSize2D, TypedScale};
-use event_loop::EventLoop;
+use euclid::{Point2D, Size2D, TypedPoint2D, TypedSize2D, TypedScale};
+use event_loop::{EventLoop, Site};
 use gfx::font_cache_thread::FontCacheThread;
 use gfx_traits::Epoch;
 use ipc_channel::{Error as IpcError};
@@ -115,8 +115,9 @@ use log::{Log, Level, LevelFilter, Metadata, Record};
 use msg::constellation_msg::{BrowsingContextId, PipelineId, HistoryStateId, TopLevelBrowsingContextId};
 use msg::constellation_msg::{Key, KeyModifiers, KeyState};
 use msg::constellation_msg::{PipelineNamespace, PipelineNamespaceId, TraversalDirection};
-use net_traits::{self, IpcSend, FetchResponseMsg, ResourceThreads};
-use net_traits::pub_domains::reg_host;
+use net_traits::{self, CoreResourceMsg, IpcSend, FetchResponseMsg, ResourceThreads};
+use net_traits::blob_url_store::get_blob_origin;
+use net_traits::filemanager_thread::FileManagerThreadMsg;
 use net_traits::request::RequestInit;
 use net_traits::storage_thread::{StorageThreadMsg, StorageType};
 use network_listener::NetworkListener;
@@ -128,25 +129,29 @@ use script_traits::{ConstellationControlMsg, ConstellationMsg as FromCompositorM
 use script_traits::{DocumentActivity, DocumentState, LayoutControlMsg, LoadData};
 use script_traits::{IFrameLoadInfo, IFrameLoadInfoWithData, IFrameSandboxState, TimerSchedulerMsg};
 use script_traits::{LayoutMsg as FromLayoutMsg, ScriptMsg as FromScriptMsg, ScriptThreadFactory};
-use script_traits::{LogEntry, ScriptToConstellationChan, ServiceWorkerMsg, webdriver_msg};
+use script_traits::{LogEntry, PrintedPage, ScriptToConstellationChan, ServiceWorkerMsg, webdriver_msg};
 use script_traits::{SWManagerMsg, ScopeThings, UpdatePipelineIdReason, WebDriverCommandMsg};
-use script_traits::{WindowSizeData, WindowSizeType};
+use script_traits::{UntrustedNodeAddress, WindowSizeData, WindowSizeType};
 use serde::{Deserialize, Serialize};
 use servo_config::opts;
 use servo_config::prefs::PREFS;
 use servo_rand::{Rng, SeedableRng, ServoRng, random};
 use servo_remutex::ReentrantMutex;
-use servo_url::{Host, ImmutableOrigin, ServoUrl};
+use servo_url::{ImmutableOrigin, ServoUrl};
 use session_history::{JointSessionHistory, NeedsToReload, SessionHistoryChange, SessionHistoryDiff};
 use std::borrow::ToOwned;
 use std::collections::{HashMap, VecDeque};
+use std::fs::{self, File};
+use std::io::Write;
 use std::marker::PhantomData;
 use std::mem::replace;
+use std::path::PathBuf;
 use std::process;
 use std::rc::{Rc, Weak};
 use std::sync::Arc;
 use std::sync::mpsc::{Receiver, Sender, channel};
 use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
 use style_traits::CSSPixel;
 use style_traits::cursor::CursorKind;
 use style_traits::viewport::ViewportConstraints;
@@ -265,20 +270,24 @@ pub struct Constellation<Message, LTF, STF> {
     webrender_api_sender: webrender_api::RenderApiSender,
 
     /// The set of all event loops in the browser. We generate a new
-    /// event loop for each registered domain name (aka eTLD+1) in
-    /// each top-level browsing context. We store the event loops in a map
+    /// event loop for each site (scheme and registered domain name, aka eTLD+1)
+    /// in each top-level browsing context. We store the event loops in a map
     /// indexed by top-level browsing context id
-    /// (as a `TopLevelBrowsingContextId`) and registered
-    /// domain name (as a `Host`) to event loops. This double
+    /// (as a `TopLevelBrowsingContextId`) and site (as a `Site`)
+    /// to event loops. This double
     /// indirection ensures that separate tabs do not share event
     /// loops, even if the same domain is loaded in each.
     /// It is important that scripts with the same eTLD+1
     /// share an event loop, since they can use `document.domain`
     /// to become same-origin, at which point they can share DOM objects.
-    event_loops: HashMap<TopLevelBrowsingContextId, HashMap<Host, Weak<EventLoop>>>,
+    event_loops: HashMap<TopLevelBrowsingContextId, HashMap<Site, Weak<EventLoop>>>,
 
     joint_session_histories: HashMap<TopLevelBrowsingContextId, JointSessionHistory>,
 
+    /// The pipelines kept alive in the back-forward cache, across all top-level
+    /// browsing contexts.
+    bfcache: BackForwardCache,
+
     /// The set of all the pipelines in the browser.
     /// (See the `pipeline` module for more details.)
     pipelines: HashMap<PipelineId, Pipeline>,
@@ -303,8 +312,9 @@ pub struct Constellation<Message, LTF, STF> {
     /// The size of the top-level window.
     window_size: WindowSizeData,
 
-    /// Means of accessing the clipboard
-    clipboard_ctx: Option<ClipboardContext>,
+    /// Whether the embedder supports the clipboard. If it doesn't, script reads an empty
+    /// clipboard, and its writes are dropped.
+    supports_clipboard: bool,
 
     /// Bits of state used to interact with the webdriver implementation
     webdriver: WebDriverData,
@@ -315,6 +325,10 @@ pub struct Constellation<Message, LTF, STF> {
     /// Are we shutting down?
     shutting_down: bool,
 
+    /// The crash pages shown in place of crashed documents, mapped to the
+    /// load data of the document they replaced, so reloading restores it.
+    crash_placeholders: HashMap<PipelineId, LoadData>,
+
     /// Have we seen any warnings? Hopefully always empty!
     /// The buffer contains `(thread_name, reason)` entries.
     handled_warnings: VecDeque<(Option<String>, String)>,
@@ -334,6 +348,9 @@ pub struct Constellation<Message, LTF, STF> {
 
     /// A channel through which messages can be sent to the canvas paint thread.
     canvas_chan: IpcSender<CanvasMsg>,
+
+    /// Whether the user prefers reduced motion, as last told by the embedder.
+    prefers_reduced_motion: bool,
 }
 
 /// State needed to construct a constellation.
@@ -380,8 +397,7 @@ pub struct InitialConstellationState {
     /// A channel to the webgl thread.
     pub webvr_chan: Option<IpcSender<WebVRMsg>>,
 
-    /// Whether the constellation supports the clipboard.
-    /// TODO: this field is not used, remove it?
+    /// Whether the embedder supports the clipboard.
     pub supports_clipboard: bool,
 }
 
@@ -585,6 +601,11 @@ where
 
                 PipelineNamespace::install(PipelineNamespaceId(0));
 
+                let bfcache_budget = PREFS
+                    .get("session-history.bfcache.max-memory-mb")
+                    .as_u64()
+                    .unwrap_or(64) as usize * 1024 * 1024;
+
                 let mut constellation: Constellation<Message, LTF, STF> = Constellation {
                     script_sender: ipc_script_sender,
                     layout_sender: ipc_layout_sender,
@@ -607,6 +628,7 @@ where
                     swmanager_sender: sw_mgr_clone,
                     event_loops: HashMap::new(),
                     joint_session_histories: HashMap::new(),
+                    bfcache: BackForwardCache::new(bfcache_budget),
                     pipelines: HashMap::new(),
                     browsing_contexts: HashMap::new(),
                     pending_changes: vec![],
@@ -623,23 +645,14 @@ where
                         ),
                     },
                     phantom: PhantomData,
-                    clipboard_ctx: if state.supports_clipboard {
-                        match ClipboardContext::new() {
-                            Ok(c) => Some(c),
-                            Err(e) => {
-                                warn!("Error creating clipboard context ({})", e);
-                                None
-                            },
-                        }
-                    } else {
-                        None
-                    },
+                    supports_clipboard: state.supports_clipboard,
                     webdriver: WebDriverData::new(),
                     scheduler_chan: TimerScheduler::start(),
                     document_states: HashMap::new(),
                     webrender_document: state.webrender_document,
                     webrender_api_sender: state.webrender_api_sender,
                     shutting_down: false,
+                    crash_placeholders: HashMap::new(),
                     handled_warnings: VecDeque::new(),
                     random_pipeline_closure: opts::get().random_pipeline_closure_probability.map(
                         |prob| {
@@ -655,6 +668,10 @@ where
                     webgl_threads: state.webgl_threads,
                     webvr_chan: state.webvr_chan,
                     canvas_chan: CanvasPaintThread::start(),
+                    prefers_reduced_motion: PREFS
+                        .get("ui.prefers-reduced-motion")
+                        .as_boolean()
+                        .unwrap_or(false),
                 };
 
                 constellation.run();
@@ -707,22 +724,22 @@ where
             pipeline_id, browsing_context_id
         );
 
-        let (event_loop, host) = match sandbox {
+        let (event_loop, site) = match sandbox {
             IFrameSandboxState::IFrameSandboxed => (None, None),
             IFrameSandboxState::IFrameUnsandboxed => {
                 // If this is an about:blank load, it must share the creator's event loop.
                 // This must match the logic in the script thread when determining the proper origin.
                 if load_data.url.as_str() != "about:blank" {
-                    match reg_host(&load_data.url) {
+                    match Site::for_url(&load_data.url) {
                         None => (None, None),
-                        Some(host) => {
+                        Some(site) => {
                             let event_loop = self
                                 .event_loops
                                 .get(&top_level_browsing_context_id)
-                                .and_then(|map| map.get(&host))
+                                .and_then(|map| map.get(&site))
                                 .and_then(|weak| weak.upgrade());
                             match event_loop {
-                                None => (None, Some(host)),
+                                None => (None, Some(site)),
                                 Some(event_loop) => (Some(event_loop.clone()), None),
                             }
                         },
@@ -799,15 +816,22 @@ where
             Err(e) => return self.handle_send_error(pipeline_id, e),
         };
 
-        if let Some(host) = host {
+        if self.prefers_reduced_motion {
+            let msg = LayoutControlMsg::SetPrefersReducedMotion(true);
+            if let Err(e) = pipeline.layout_chan.send(msg) {
+                warn!("Sending reduced motion preference to layout failed ({:?}).", e);
+            }
+        }
+
+        if let Some(site) = site {
             debug!(
-                "Adding new host entry {} for top-level browsing context {}.",
-                host, top_level_browsing_context_id
+                "Adding new site entry {} for top-level browsing context {}.",
+                site, top_level_browsing_context_id
             );
             self.event_loops
                 .entry(top_level_browsing_context_id)
                 .or_insert_with(HashMap::new)
-                .insert(host, Rc::downgrade(&pipeline.event_loop));
+                .insert(site, Rc::downgrade(&pipeline.event_loop));
         }
 
         assert!(!self.pipelines.contains_key(&pipeline_id));
@@ -999,7 +1023,8 @@ where
             // If there is already a pending page (self.pending_changes), it will not be overridden;
             // However, if the id is not encompassed by another change, it will be.
             FromCompositorMsg::LoadUrl(top_level_browsing_context_id, url) => {
-                let load_data = LoadData::new(url, None, None, None);
+                let mut load_data = LoadData::new(url, None, None, None);
+                load_data.user_initiated = true;
                 let ctx_id = BrowsingContextId::from(top_level_browsing_context_id);
                 let pipeline_id = match self.browsing_contexts.get(&ctx_id) {
                     Some(ctx) => ctx.pipeline_id,
@@ -1043,7 +1068,7 @@ where
             FromCompositorMsg::SendError(top_level_browsing_context_id, error) => {
                 debug!("constellation got SendError message");
                 if let Some(id) = top_level_browsing_context_id {
-                    self.handle_panic(id, error, None);
+                    self.handle_panic(id, None, error, None);
                 } else {
                     warn!("constellation got a SendError message without top level id");
                 }
@@ -1068,8 +1093,11 @@ where
             FromCompositorMsg::Reload(top_level_browsing_context_id) => {
                 self.handle_reload_msg(top_level_browsing_context_id);
             },
+            FromCompositorMsg::Print(top_level_browsing_context_id, page_size, response_chan) => {
+                self.handle_print_msg(top_level_browsing_context_id, page_size, response_chan);
+            },
             FromCompositorMsg::LogEntry(top_level_browsing_context_id, thread_name, entry) => {
-                self.handle_log_entry(top_level_browsing_context_id, thread_name, entry);
+                self.handle_log_entry(top_level_browsing_context_id, None, thread_name, entry);
             },
             FromCompositorMsg::WebVREvents(pipeline_ids, events) => {
                 self.handle_webvr_events(pipeline_ids, events);
@@ -1077,7 +1105,23 @@ where
             FromCompositorMsg::ForwardEvent(destination_pipeline_id, event) => {
                 self.forward_event(destination_pipeline_id, event);
             },
+            FromCompositorMsg::DropFiles(destination_pipeline_id, paths, point, node_address) => {
+                self.handle_drop_files(destination_pipeline_id, paths, point, node_address);
+            },
             FromCompositorMsg::SetCursor(cursor) => self.handle_set_cursor_msg(cursor),
+            // Synthesized input is hit tested by the compositor like real input.
+            FromCompositorMsg::MouseEvent(event_type, button, point) => {
+                let point = TypedPoint2D::from_untyped(&point);
+                let msg = ToCompositorMsg::DispatchMouseEvent(event_type, button, point);
+                self.compositor_proxy.send(msg);
+            },
+            FromCompositorMsg::MouseMoveEvent(point) => {
+                let point = TypedPoint2D::from_untyped(&point);
+                self.compositor_proxy.send(ToCompositorMsg::DispatchMouseMoveEvent(point));
+            },
+            FromCompositorMsg::SetPrefersReducedMotion(prefers_reduced_motion) => {
+                self.handle_set_prefers_reduced_motion_msg(prefers_reduced_motion);
+            },
         }
     }
 
@@ -1099,8 +1143,7 @@ where
 
         match content {
             FromScriptMsg::ForwardToEmbedder(embedder_msg) => {
-                self.embedder_proxy
-                    .send((Some(source_top_ctx_id), embedder_msg));
+                self.handle_forward_to_embedder(source_top_ctx_id, embedder_msg);
             },
             FromScriptMsg::PipelineExited => {
                 self.handle_pipeline_exited(source_pipeline_id);
@@ -1108,6 +1151,9 @@ where
             FromScriptMsg::DiscardDocument => {
                 self.handle_discard_document(source_top_ctx_id, source_pipeline_id);
             },
+            FromScriptMsg::FrozenDocumentSize(size) => {
+                self.handle_frozen_document_size(source_pipeline_id, size);
+            },
             FromScriptMsg::DiscardTopLevelBrowsingContext => {
                 self.handle_close_top_level_browsing_context(source_top_ctx_id);
             },
@@ -1175,30 +1221,6 @@ where
             FromScriptMsg::Focus => {
                 self.handle_focus_msg(source_pipeline_id);
             },
-            FromScriptMsg::GetClipboardContents(sender) => {
-                let contents = match self.clipboard_ctx {
-                    Some(ref mut ctx) => {
-                        match ctx.get_contents() {
-                            Ok(c) => c,
-                            Err(e) => {
-                                warn!("Error getting clipboard contents ({}), defaulting to empty string", e);
-                                "".to_owned()
-                            },
-                        }
-                    },
-                    None => "".to_owned(),
-                };
-                if let Err(e) = sender.send(contents.to_owned()) {
-                    warn!("Failed to send clipboard ({})", e);
-                }
-            },
-            FromScriptMsg::SetClipboardContents(s) => {
-                if let Some(ref mut ctx) = self.clipboard_ctx {
-                    if let Err(e) = ctx.set_contents(s) {
-                        warn!("Error setting clipboard contents ({})", e);
-                    }
-                }
-            },
             FromScriptMsg::SetVisible(visible) => {
                 self.handle_set_visible_msg(source_pipeline_id, visible);
             },
@@ -1230,11 +1252,22 @@ where
                     .send(ToCompositorMsg::GetScreenAvailSize(send));
             },
             FromScriptMsg::LogEntry(thread_name, entry) => {
-                self.handle_log_entry(Some(source_top_ctx_id), thread_name, entry);
+                self.handle_log_entry(
+                    Some(source_top_ctx_id),
+                    Some(source_pipeline_id),
+                    thread_name,
+                    entry,
+                );
             },
             FromScriptMsg::TouchEventProcessed(result) => self
                 .compositor_proxy
                 .send(ToCompositorMsg::TouchEventProcessed(result)),
+            FromScriptMsg::WheelEventProcessed(prevented) => self
+                .compositor_proxy
+                .send(ToCompositorMsg::WheelEventProcessed(prevented)),
+            FromScriptMsg::SmoothScroll(scroll_state) => self
+                .compositor_proxy
+                .send(ToCompositorMsg::SmoothScroll(scroll_state)),
             FromScriptMsg::GetBrowsingContextId(pipeline_id, sender) => {
                 let result = self
                     .pipelines
@@ -1308,6 +1341,9 @@ where
             FromLayoutMsg::ViewportConstrained(pipeline_id, constraints) => {
                 self.handle_viewport_constrained_msg(pipeline_id, constraints);
             },
+            FromLayoutMsg::ScrollSnapInfo(pipeline_id, snap_info) => self
+                .compositor_proxy
+                .send(ToCompositorMsg::ScrollSnapInfo(pipeline_id, snap_info)),
         }
     }
 
@@ -1508,13 +1544,18 @@ where
             .map(|pipeline| pipeline.top_level_browsing_context_id);
         if let Some(top_level_browsing_context_id) = top_level_browsing_context_id {
             let reason = format!("Send failed ({})", err);
-            self.handle_panic(top_level_browsing_context_id, reason, None);
+            self.handle_panic(top_level_browsing_context_id, Some(pipeline_id), reason, None);
         }
     }
 
+    /// Handle a panic in a pipeline of the given top-level browsing context. If we know
+    /// which pipeline failed, only the browsing contexts whose documents shared its event
+    /// loop (and so its content process) are replaced by a crash page; otherwise the
+    /// whole top-level browsing context is.
     fn handle_panic(
         &mut self,
         top_level_browsing_context_id: TopLevelBrowsingContextId,
+        pipeline_id: Option<PipelineId>,
         reason: String,
         backtrace: Option<String>,
     ) {
@@ -1530,25 +1571,73 @@ where
             top_level_browsing_context_id, reason
         );
 
-        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+        let crashed_event_loop = pipeline_id
+            .and_then(|id| self.pipelines.get(&id))
+            .map(|pipeline| pipeline.event_loop.clone());
+
+        let crashed_url = pipeline_id
+            .and_then(|id| self.pipelines.get(&id))
+            .map(|pipeline| pipeline.url.clone());
+        self.write_crash_report(
+            top_level_browsing_context_id,
+            crashed_url.as_ref(),
+            &reason,
+            backtrace.as_ref(),
+        );
 
         self.embedder_proxy.send((
             Some(top_level_browsing_context_id),
             EmbedderMsg::Panic(reason, backtrace),
         ));
 
-        let (window_size, pipeline_id) = {
-            let browsing_context = self.browsing_contexts.get(&browsing_context_id);
-            let window_size = browsing_context.and_then(|browsing_context| browsing_context.size);
-            let pipeline_id = browsing_context.map(|browsing_context| browsing_context.pipeline_id);
-            (window_size, pipeline_id)
+        let top_level_browsing_context = BrowsingContextId::from(top_level_browsing_context_id);
+        let crashed_browsing_contexts = match crashed_event_loop {
+            Some(ref event_loop) => {
+                // Forget the crashed event loop, so that later loads from its sites
+                // are given a new one.
+                let event_loops = self.event_loops.get_mut(&top_level_browsing_context_id);
+                if let Some(event_loops) = event_loops {
+                    event_loops.retain(|_, weak| {
+                        weak.upgrade().map_or(false, |other| !Rc::ptr_eq(&other, event_loop))
+                    });
+                }
+                self.all_descendant_browsing_contexts_iter(top_level_browsing_context)
+                    .filter(|browsing_context| {
+                        self.pipelines
+                            .get(&browsing_context.pipeline_id)
+                            .map_or(false, |pipeline| Rc::ptr_eq(&pipeline.event_loop, event_loop))
+                    })
+                    .map(|browsing_context| browsing_context.id)
+                    .collect()
+            },
+            None => vec![top_level_browsing_context],
         };
 
-        let (pipeline_url, parent_info) = {
-            let pipeline = pipeline_id.and_then(|id| self.pipelines.get(&id));
-            let pipeline_url = pipeline.map(|pipeline| pipeline.url.clone());
-            let parent_info = pipeline.and_then(|pipeline| pipeline.parent_info);
-            (pipeline_url, parent_info)
+        for browsing_context_id in crashed_browsing_contexts {
+            self.replace_with_crash_page(top_level_browsing_context_id, browsing_context_id);
+        }
+    }
+
+    /// Replace the document of a browsing context whose content process crashed
+    /// by a crash page, running in a new event loop.
+    fn replace_with_crash_page(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        browsing_context_id: BrowsingContextId,
+    ) {
+        // This browsing context may already have been closed along with a crashed ancestor.
+        let (window_size, pipeline_id) = match self.browsing_contexts.get(&browsing_context_id) {
+            Some(browsing_context) => (browsing_context.size, browsing_context.pipeline_id),
+            None => return,
+        };
+
+        let (crashed_load_data, parent_info) = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => {
+                let mut load_data = pipeline.load_data.clone();
+                load_data.url = pipeline.url.clone();
+                (load_data, pipeline.parent_info)
+            },
+            None => return,
         };
 
         self.close_browsing_context_children(
@@ -1557,18 +1646,16 @@ where
             ExitPipelineMode::Force,
         );
 
-        let failure_url = ServoUrl::parse("about:failure").expect("infallible");
+        let crash_url = ServoUrl::parse("about:crash").expect("infallible");
 
-        if let Some(pipeline_url) = pipeline_url {
-            if pipeline_url == failure_url {
-                return error!("about:failure failed");
-            }
+        if crashed_load_data.url == crash_url {
+            return error!("about:crash failed");
         }
 
-        warn!("creating replacement pipeline for about:failure");
+        warn!("creating replacement pipeline for about:crash");
 
         let new_pipeline_id = PipelineId::new();
-        let load_data = LoadData::new(failure_url, None, None, None);
+        let load_data = LoadData::new(crash_url, None, None, None);
         let sandbox = IFrameSandboxState::IFrameSandboxed;
         self.new_pipeline(
             new_pipeline_id,
@@ -1576,28 +1663,74 @@ where
             top_level_browsing_context_id,
             parent_info,
             window_size,
-            load_data.clone(),
+            load_data,
             sandbox,
             false,
         );
+        self.crash_placeholders.insert(new_pipeline_id, crashed_load_data);
         self.add_pending_change(SessionHistoryChange {
             top_level_browsing_context_id: top_level_browsing_context_id,
             browsing_context_id: browsing_context_id,
             new_pipeline_id: new_pipeline_id,
-            replace: None,
+            replace: Some(NeedsToReload::No(pipeline_id)),
         });
     }
 
+    /// Write a crash report to the crash reports directory, if there is one.
+    fn write_crash_report(
+        &self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        url: Option<&ServoUrl>,
+        reason: &str,
+        backtrace: Option<&String>,
+    ) {
+        let dir = match opts::get().crash_reports_dir {
+            Some(ref dir) => dir.clone(),
+            None => return,
+        };
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let path = dir.join(format!(
+            "crash-{}-{:09}.txt",
+            now.as_secs(),
+            now.subsec_nanos()
+        ));
+        let mut report = format!(
+            "Top-level browsing context: {}\nURL: {}\nReason: {}\n",
+            top_level_browsing_context_id,
+            url.map_or("unknown", |url| url.as_str()),
+            reason
+        );
+        if let Some(backtrace) = backtrace {
+            report.push_str("\nBacktrace:\n");
+            report.push_str(backtrace);
+        }
+        let result = fs::create_dir_all(&dir)
+            .and_then(|_| File::create(&path))
+            .and_then(|mut file| file.write_all(report.as_bytes()));
+        match result {
+            Ok(()) => debug!("Wrote crash report to {}.", path.display()),
+            Err(e) => warn!("Failed to write crash report to {} ({}).", path.display(), e),
+        }
+    }
+
     fn handle_log_entry(
         &mut self,
         top_level_browsing_context_id: Option<TopLevelBrowsingContextId>,
+        pipeline_id: Option<PipelineId>,
         thread_name: Option<String>,
         entry: LogEntry,
     ) {
         debug!("Received log entry {:?}.", entry);
         match (entry, top_level_browsing_context_id) {
             (LogEntry::Panic(reason, backtrace), Some(top_level_browsing_context_id)) => {
-                self.handle_panic(top_level_browsing_context_id, reason, Some(backtrace));
+                self.handle_panic(
+                    top_level_browsing_context_id,
+                    pipeline_id,
+                    reason,
+                    Some(backtrace),
+                );
             },
             (LogEntry::Panic(reason, _), _) |
             (LogEntry::Error(reason), _) |
@@ -1642,6 +1775,59 @@ where
         }
     }
 
+    /// Forwards a message from script to the embedder, unless it accesses the clipboard and the
+    /// embedder doesn't support one.
+    fn handle_forward_to_embedder(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        msg: EmbedderMsg,
+    ) {
+        if !self.supports_clipboard {
+            match msg {
+                EmbedderMsg::GetClipboardContents(sender) => {
+                    if let Err(e) = sender.send(String::new()) {
+                        warn!("Failed to send clipboard ({})", e);
+                    }
+                    return;
+                },
+                EmbedderMsg::SetClipboardContents(_) => return,
+                _ => {},
+            }
+        }
+        self.embedder_proxy.send((Some(top_level_browsing_context_id), msg));
+    }
+
+    /// Registers the files that the user dropped onto a document with the file manager, before
+    /// letting script know about them, so that it can only ever read files that it was given.
+    fn handle_drop_files(
+        &mut self,
+        destination_pipeline_id: PipelineId,
+        paths: Vec<PathBuf>,
+        point: Point2D<f32>,
+        node_address: Option<UntrustedNodeAddress>,
+    ) {
+        let (url, is_private) = match self.pipelines.get(&destination_pipeline_id) {
+            Some(pipeline) => (pipeline.url.clone(), pipeline.is_private),
+            None => return debug!("Pipeline {:?} got dropped files after closure.", destination_pipeline_id),
+        };
+        let resource_threads = if is_private {
+            &self.private_resource_threads
+        } else {
+            &self.public_resource_threads
+        };
+        let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+        let msg = FileManagerThreadMsg::AddDroppedFiles(paths, sender, get_blob_origin(&url));
+        if let Err(e) = resource_threads.send(CoreResourceMsg::ToFileManager(msg)) {
+            return warn!("Sending dropped files to the file manager failed ({}).", e);
+        }
+        let files = match receiver.recv() {
+            Ok(files) => files,
+            Err(e) => return warn!("Receiving dropped files from the file manager failed ({:?}).", e),
+        };
+        let event = CompositorEvent::DropFilesEvent(files, point, node_address);
+        self.forward_event(destination_pipeline_id, event);
+    }
+
     fn handle_new_top_level_browsing_context(
         &mut self,
         url: ServoUrl,
@@ -1878,6 +2064,19 @@ where
             .send((None, EmbedderMsg::SetCursor(cursor)))
     }
 
+    fn handle_set_prefers_reduced_motion_msg(&mut self, prefers_reduced_motion: bool) {
+        if prefers_reduced_motion == self.prefers_reduced_motion {
+            return;
+        }
+        self.prefers_reduced_motion = prefers_reduced_motion;
+        for pipeline in self.pipelines.values() {
+            let msg = LayoutControlMsg::SetPrefersReducedMotion(prefers_reduced_motion);
+            if let Err(e) = pipeline.layout_chan.send(msg) {
+                warn!("Sending reduced motion preference to layout failed ({:?}).", e);
+            }
+        }
+    }
+
     fn handle_change_running_animations_state(
         &mut self,
         pipeline_id: PipelineId,
@@ -2372,6 +2571,10 @@ where
             .get(&old_pipeline_id)
             .and_then(|pipeline| pipeline.parent_info);
 
+        // https://html.spec.whatwg.org/multipage/#unload-a-document
+        self.unload_document(old_pipeline_id);
+        self.update_bfcache(old_pipeline_id, new_pipeline_id);
+
         self.update_activity(old_pipeline_id);
         self.update_activity(new_pipeline_id);
 
@@ -2537,6 +2740,11 @@ where
                 )
             },
         };
+        // Reloading a crash page reloads the document that crashed, in a new event loop.
+        if let Some(load_data) = self.crash_placeholders.get(&pipeline_id).cloned() {
+            self.load_url(top_level_browsing_context_id, pipeline_id, load_data, true);
+            return;
+        }
         let msg = ConstellationControlMsg::Reload(pipeline_id);
         let result = match self.pipelines.get(&pipeline_id) {
             None => return warn!("Pipeline {} got reload event after closure.", pipeline_id),
@@ -2547,6 +2755,32 @@ where
         }
     }
 
+    fn handle_print_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        page_size: TypedSize2D<f32, CSSPixel>,
+        response_chan: IpcSender<Vec<PrintedPage>>,
+    ) {
+        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+        let pipeline_id = match self.browsing_contexts.get(&browsing_context_id) {
+            Some(browsing_context) => browsing_context.pipeline_id,
+            None => {
+                return warn!(
+                    "Browsing context {} got print event after closure.",
+                    browsing_context_id
+                )
+            },
+        };
+        let msg = ConstellationControlMsg::Print(pipeline_id, page_size, response_chan);
+        let result = match self.pipelines.get(&pipeline_id) {
+            None => return warn!("Pipeline {} got print event after closure.", pipeline_id),
+            Some(pipeline) => pipeline.event_loop.send(msg),
+        };
+        if let Err(e) = result {
+            self.handle_send_error(pipeline_id, e);
+        }
+    }
+
     fn handle_post_message_msg(
         &mut self,
         browsing_context_id: BrowsingContextId,
@@ -2998,6 +3232,7 @@ where
             Some(old_pipeline_id) => {
                 // https://html.spec.whatwg.org/multipage/#unload-a-document
                 self.unload_document(old_pipeline_id);
+                self.update_bfcache(old_pipeline_id, change.new_pipeline_id);
                 // Deactivate the old pipeline, and activate the new one.
                 let (pipelines_to_close, states_to_close) = if let Some(replace_reloader) =
                     change.replace
@@ -3135,30 +3370,61 @@ where
             pipelines_to_evict
         };
 
-        let mut dead_pipelines = vec![];
         for evicted_id in pipelines_to_evict {
-            let load_data = match self.pipelines.get(&evicted_id) {
-                Some(pipeline) => {
-                    let mut load_data = pipeline.load_data.clone();
-                    load_data.url = pipeline.url.clone();
-                    load_data
-                },
-                None => continue,
-            };
+            self.evict_pipeline(evicted_id);
+        }
 
-            dead_pipelines.push((evicted_id, NeedsToReload::Yes(evicted_id, load_data)));
-            self.close_pipeline(
-                evicted_id,
-                DiscardBrowsingContext::No,
-                ExitPipelineMode::Normal,
-            );
+        self.enforce_bfcache_budget();
+    }
+
+    /// Evict the least recently used pipelines of the back-forward cache until it is
+    /// within its memory budget.
+    fn enforce_bfcache_budget(&mut self) {
+        for evicted_id in self.bfcache.evict_over_budget() {
+            self.evict_pipeline(evicted_id);
         }
+    }
 
-        let session_history = self.get_joint_session_history(top_level_browsing_context_id);
+    /// Close a pipeline that is no longer current in its browsing context,
+    /// leaving a session history entry that reloads it when traversed to.
+    fn evict_pipeline(&mut self, pipeline_id: PipelineId) {
+        let (top_level_id, load_data) = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => {
+                let mut load_data = pipeline.load_data.clone();
+                load_data.url = pipeline.url.clone();
+                (pipeline.top_level_browsing_context_id, load_data)
+            },
+            None => return,
+        };
+
+        self.close_pipeline(
+            pipeline_id,
+            DiscardBrowsingContext::No,
+            ExitPipelineMode::Normal,
+        );
+
+        self.get_joint_session_history(top_level_id).replace_reloader(
+            NeedsToReload::No(pipeline_id),
+            NeedsToReload::Yes(pipeline_id, load_data),
+        );
+    }
 
-        for (alive_id, dead) in dead_pipelines {
-            session_history.replace_reloader(NeedsToReload::No(alive_id), dead);
+    /// Move the pipeline that was navigated away from into the back-forward cache,
+    /// and the pipeline that was navigated to out of it.
+    fn update_bfcache(&mut self, old_pipeline_id: PipelineId, new_pipeline_id: PipelineId) {
+        self.bfcache.remove(new_pipeline_id);
+        self.bfcache.insert(old_pipeline_id);
+        self.enforce_bfcache_budget();
+    }
+
+    /// Record the size of a document that was frozen in the back-forward cache.
+    fn handle_frozen_document_size(&mut self, pipeline_id: PipelineId, size: usize) {
+        if !self.bfcache.contains(pipeline_id) {
+            return;
         }
+        debug!("Pipeline {} was frozen using {} bytes.", pipeline_id, size);
+        self.bfcache.set_size(pipeline_id, size);
+        self.enforce_bfcache_budget();
     }
 
     fn handle_activate_document_msg(&mut self, pipeline_id: PipelineId) {
@@ -3581,6 +3847,9 @@ where
     ) {
         debug!("Closing pipeline {:?}.", pipeline_id);
 
+        self.bfcache.remove(pipeline_id);
+        self.crash_placeholders.remove(&pipeline_id);
+
         // Sever connection to browsing context
         let browsing_context_id = self
             .pipelines
@@ -8,7 +8,10 @@
 
 use ipc_channel::Error;
 use ipc_channel::ipc::IpcSender;
+use net_traits::pub_domains::reg_host;
 use script_traits::ConstellationControlMsg;
+use servo_url::{Host, ServoUrl};
+use std::fmt;
 use std::marker::PhantomData;
 use std::rc::Rc;
 
@@ -45,3 +48,28 @@ impl EventLoop {
         self.script_chan.clone()
     }
 }
+
+/// The key event loops are shared by: documents only share an event loop
+/// (and so a content process) with documents from the same site.
+/// <https://html.spec.whatwg.org/multipage/#site>
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Site {
+    scheme: String,
+    host: Host,
+}
+
+impl Site {
+    /// The site of a URL, if it has a registrable host.
+    pub fn for_url(url: &ServoUrl) -> Option<Site> {
+        reg_host(url).map(|host| Site {
+            scheme: url.scheme().to_owned(),
+            host: host,
+        })
+    }
+}
+
+impl fmt::Display for Site {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}://{}", self.scheme, self.host)
+    }
+}
@@ -10,7 +10,6 @@ extern crate backtrace;
 extern crate bluetooth_traits;
 extern crate canvas;
 extern crate canvas_traits;
-extern crate clipboard;
 extern crate compositing;
 extern crate debugger;
 extern crate devtools_traits;
@@ -42,6 +41,7 @@ extern crate style_traits;
 extern crate webrender_api;
 extern crate webvr_traits;
 
+mod bfcache;
 mod browsingcontext;
 mod constellation;
 mod event_loop;
@@ -52,6 +52,7 @@ mod sandboxing;
 mod session_history;
 mod timer_scheduler;
 
+pub use bfcache::BackForwardCache;
 pub use constellation::{Constellation, FromCompositorLogger, FromScriptLogger, InitialConstellationState};
 pub use pipeline::UnprivilegedPipelineContent;
 #[cfg(all(not(target_os = "windows"), not(target_os = "ios")))]
@@ -0,0 +1,79 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+extern crate constellation;
+extern crate msg;
+
+use constellation::BackForwardCache;
+use msg::constellation_msg::{PipelineId, PipelineIndex, PipelineNamespaceId};
+use std::num::NonZeroU32;
+
+fn pipeline_id(index: u32) -> PipelineId {
+    PipelineId {
+        namespace_id: PipelineNamespaceId(1),
+        index: PipelineIndex(NonZeroU32::new(index).unwrap()),
+    }
+}
+
+#[test]
+fn test_bfcache_evicts_least_recently_used_over_budget() {
+    let mut bfcache = BackForwardCache::new(100);
+    for index in 1..4 {
+        bfcache.insert(pipeline_id(index));
+        bfcache.set_size(pipeline_id(index), 40);
+    }
+
+    assert_eq!(bfcache.total_size(), 120);
+    assert_eq!(bfcache.evict_over_budget(), vec![pipeline_id(1)]);
+    assert!(!bfcache.contains(pipeline_id(1)));
+    assert!(bfcache.contains(pipeline_id(2)));
+    assert!(bfcache.contains(pipeline_id(3)));
+    assert_eq!(bfcache.total_size(), 80);
+    assert!(bfcache.evict_over_budget().is_empty());
+}
+
+#[test]
+fn test_bfcache_keeps_unmeasured_documents_within_budget() {
+    let mut bfcache = BackForwardCache::new(100);
+    bfcache.insert(pipeline_id(1));
+    bfcache.insert(pipeline_id(2));
+
+    assert!(bfcache.evict_over_budget().is_empty());
+
+    bfcache.set_size(pipeline_id(2), 150);
+    assert_eq!(bfcache.evict_over_budget(), vec![pipeline_id(1), pipeline_id(2)]);
+    assert_eq!(bfcache.total_size(), 0);
+}
+
+#[test]
+fn test_bfcache_restore_removes_entry() {
+    let mut bfcache = BackForwardCache::new(100);
+    bfcache.insert(pipeline_id(1));
+    bfcache.set_size(pipeline_id(1), 60);
+
+    assert!(bfcache.remove(pipeline_id(1)));
+    assert!(!bfcache.remove(pipeline_id(1)));
+    assert!(!bfcache.contains(pipeline_id(1)));
+    assert_eq!(bfcache.total_size(), 0);
+}
+
+#[test]
+fn test_bfcache_reinsert_makes_entry_most_recently_used() {
+    let mut bfcache = BackForwardCache::new(100);
+    for index in 1..3 {
+        bfcache.insert(pipeline_id(index));
+        bfcache.set_size(pipeline_id(index), 40);
+    }
+
+    // Traversing back to the first document and away again makes it the most recently used.
+    bfcache.remove(pipeline_id(1));
+    bfcache.insert(pipeline_id(1));
+    bfcache.set_size(pipeline_id(1), 40);
+    bfcache.insert(pipeline_id(3));
+    bfcache.set_size(pipeline_id(3), 40);
+
+    assert_eq!(bfcache.evict_over_budget(), vec![pipeline_id(2)]);
+    assert!(bfcache.contains(pipeline_id(1)));
+    assert!(bfcache.contains(pipeline_id(3)));
+}
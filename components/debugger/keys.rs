@@ -0,0 +1,134 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use msg::constellation_msg::{Key, KeyModifiers};
+
+/// Takes the `code` of an `Input.dispatchKeyEvent` command, which names a physical key
+/// like `KeyboardEvent.code` does, and returns the corresponding key.
+pub fn key_from_code(code: &str) -> Option<Key> {
+    Some(match code {
+        "Space" => Key::Space,
+        "Quote" => Key::Apostrophe,
+        "Comma" => Key::Comma,
+        "Minus" => Key::Minus,
+        "Period" => Key::Period,
+        "Slash" => Key::Slash,
+        "Digit0" => Key::Num0,
+        "Digit1" => Key::Num1,
+        "Digit2" => Key::Num2,
+        "Digit3" => Key::Num3,
+        "Digit4" => Key::Num4,
+        "Digit5" => Key::Num5,
+        "Digit6" => Key::Num6,
+        "Digit7" => Key::Num7,
+        "Digit8" => Key::Num8,
+        "Digit9" => Key::Num9,
+        "Semicolon" => Key::Semicolon,
+        "Equal" => Key::Equal,
+        "KeyA" => Key::A,
+        "KeyB" => Key::B,
+        "KeyC" => Key::C,
+        "KeyD" => Key::D,
+        "KeyE" => Key::E,
+        "KeyF" => Key::F,
+        "KeyG" => Key::G,
+        "KeyH" => Key::H,
+        "KeyI" => Key::I,
+        "KeyJ" => Key::J,
+        "KeyK" => Key::K,
+        "KeyL" => Key::L,
+        "KeyM" => Key::M,
+        "KeyN" => Key::N,
+        "KeyO" => Key::O,
+        "KeyP" => Key::P,
+        "KeyQ" => Key::Q,
+        "KeyR" => Key::R,
+        "KeyS" => Key::S,
+        "KeyT" => Key::T,
+        "KeyU" => Key::U,
+        "KeyV" => Key::V,
+        "KeyW" => Key::W,
+        "KeyX" => Key::X,
+        "KeyY" => Key::Y,
+        "KeyZ" => Key::Z,
+        "BracketLeft" => Key::LeftBracket,
+        "Backslash" => Key::Backslash,
+        "BracketRight" => Key::RightBracket,
+        "Backquote" => Key::GraveAccent,
+        "Escape" => Key::Escape,
+        "Enter" => Key::Enter,
+        "Tab" => Key::Tab,
+        "Backspace" => Key::Backspace,
+        "Insert" => Key::Insert,
+        "Delete" => Key::Delete,
+        "ArrowRight" => Key::Right,
+        "ArrowLeft" => Key::Left,
+        "ArrowDown" => Key::Down,
+        "ArrowUp" => Key::Up,
+        "PageUp" => Key::PageUp,
+        "PageDown" => Key::PageDown,
+        "Home" => Key::Home,
+        "End" => Key::End,
+        "CapsLock" => Key::CapsLock,
+        "F1" => Key::F1,
+        "F2" => Key::F2,
+        "F3" => Key::F3,
+        "F4" => Key::F4,
+        "F5" => Key::F5,
+        "F6" => Key::F6,
+        "F7" => Key::F7,
+        "F8" => Key::F8,
+        "F9" => Key::F9,
+        "F10" => Key::F10,
+        "F11" => Key::F11,
+        "F12" => Key::F12,
+        "NumpadEnter" => Key::KpEnter,
+        "ShiftLeft" => Key::LeftShift,
+        "ControlLeft" => Key::LeftControl,
+        "AltLeft" => Key::LeftAlt,
+        "MetaLeft" => Key::LeftSuper,
+        "ShiftRight" => Key::RightShift,
+        "ControlRight" => Key::RightControl,
+        "AltRight" => Key::RightAlt,
+        "MetaRight" => Key::RightSuper,
+        _ => return None,
+    })
+}
+
+/// Takes the `key` of an `Input.dispatchKeyEvent` command, for clients that do not send
+/// a `code`. Only handles keys whose name does not depend on the keyboard layout.
+pub fn key_from_key_name(name: &str) -> Option<Key> {
+    let mut chars = name.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii_alphabetic() =>
+            key_from_code(&format!("Key{}", c.to_ascii_uppercase())),
+        (Some(c), None) if c.is_ascii_digit() => key_from_code(&format!("Digit{}", c)),
+        (Some(' '), None) => Some(Key::Space),
+        _ => match name {
+            "Shift" => Some(Key::LeftShift),
+            "Control" => Some(Key::LeftControl),
+            "Alt" => Some(Key::LeftAlt),
+            "Meta" => Some(Key::LeftSuper),
+            _ => key_from_code(name),
+        },
+    }
+}
+
+/// Converts the `modifiers` bit field of input commands.
+pub fn modifiers_from_bits(bits: u64) -> KeyModifiers {
+    let mut modifiers = KeyModifiers::empty();
+    if bits & 1 != 0 {
+        modifiers.insert(KeyModifiers::ALT);
+    }
+    if bits & 2 != 0 {
+        modifiers.insert(KeyModifiers::CONTROL);
+    }
+    if bits & 4 != 0 {
+        modifiers.insert(KeyModifiers::SUPER);
+    }
+    if bits & 8 != 0 {
+        modifiers.insert(KeyModifiers::SHIFT);
+    }
+    modifiers
+}
@@ -2,26 +2,156 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+//! A server speaking a subset of the [Chrome DevTools Protocol]
+//! (https://chromedevtools.github.io/devtools-protocol/), so that tools written against it
+//! can drive Servo. Like the WebDriver server, it acts on pages by sending messages to the
+//! constellation.
+//!
+//! Clients discover targets through the `/json/version` and `/json/list` HTTP endpoints,
+//! then send commands over a WebSocket, either to the browser endpoint or to the endpoint
+//! of a single page. Commands for a page can also be sent to the browser endpoint along
+//! with the `sessionId` returned by `Target.attachToTarget`.
+
+extern crate base64;
+extern crate devtools_traits;
+extern crate euclid;
+extern crate image;
+extern crate ipc_channel;
 #[macro_use]
 extern crate log;
+extern crate msg;
+extern crate net_traits;
+extern crate script_traits;
+extern crate serde;
+#[macro_use]
+extern crate serde_json;
+extern crate servo_url;
 extern crate ws;
 
+mod keys;
+
+use devtools_traits::{ChromeToDevtoolsControlMsg, DevtoolsControlMsg, NetworkEvent};
+use euclid::Point2D;
+use image::{DynamicImage, ImageFormat, RgbImage, RgbaImage};
+use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
+use ipc_channel::router::ROUTER;
+use keys::{key_from_code, key_from_key_name, modifiers_from_bits};
+use msg::constellation_msg::{BrowsingContextId, KeyState, TopLevelBrowsingContextId};
+use net_traits::image::base::{Image, PixelFormat};
+use script_traits::{ConstellationMsg, LoadData, MouseButton, MouseEventType};
+use script_traits::WebDriverCommandMsg;
+use script_traits::webdriver_msg::{LoadStatus, WebDriverJSError, WebDriverJSValue};
+use script_traits::webdriver_msg::WebDriverScriptCommand;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use servo_url::ServoUrl;
+use std::collections::HashMap;
 use std::sync::mpsc;
 use std::sync::mpsc::channel;
 use std::thread;
-use ws::{Builder, CloseCode, Handler, Handshake};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use ws::{Builder, CloseCode, Handler, Handshake, Request, Response};
+use ws::util::Token;
+
+/// How long to retry taking a screenshot before giving up, in milliseconds.
+const SCREENSHOT_TIMEOUT: u64 = 30_000;
+
+/// How long to wait for the reply to any other message sent to Servo, in milliseconds.
+/// Commands are handled one at a time, so a page that doesn't reply blocks every client.
+const REPLY_TIMEOUT: u64 = 5_000;
+
+/// The node id of the document, the root of the nodes returned by `DOM` commands.
+const DOCUMENT_NODE_ID: u64 = 1;
 
 enum Message {
     ShutdownServer,
+    SetConstellationChan(mpsc::Sender<ConstellationMsg>),
+    /// A plain HTTP request for one of the `/json` endpoints, and where to send the reply.
+    HttpRequest(String, mpsc::Sender<Option<String>>),
+    /// A command received by a connection, and the target of its endpoint, if any.
+    Command(ws::Sender, Option<String>, String),
+    ConnectionClosed(Token),
+    NetworkEvent(String, NetworkUpdate),
+    /// A navigation of the page with the given target id finished.
+    LoadComplete(String),
 }
 
+#[derive(Clone)]
 pub struct Sender(mpsc::Sender<Message>);
 
+/// The parts of a network event that the `Network` domain reports.
+enum NetworkUpdate {
+    Request {
+        url: String,
+        method: String,
+        headers: Map<String, Value>,
+        is_xhr: bool,
+    },
+    Response {
+        status: u16,
+        status_text: String,
+        headers: Map<String, Value>,
+        length: usize,
+    },
+}
+
+impl<'a> From<&'a NetworkEvent> for NetworkUpdate {
+    fn from(event: &NetworkEvent) -> NetworkUpdate {
+        match *event {
+            NetworkEvent::HttpRequest(ref request) => NetworkUpdate::Request {
+                url: request.url.to_string(),
+                method: request.method.to_string(),
+                headers: request.headers.iter()
+                    .map(|header| (header.name().to_owned(), Value::from(header.value_string())))
+                    .collect(),
+                is_xhr: request.is_xhr,
+            },
+            NetworkEvent::HttpResponse(ref response) => NetworkUpdate::Response {
+                status: response.status.as_ref().map_or(0, |&(code, _)| code),
+                status_text: response.status.as_ref()
+                    .map_or(String::new(), |&(_, ref text)| String::from_utf8_lossy(text).into_owned()),
+                headers: response.headers.iter()
+                    .flat_map(|headers| headers.iter())
+                    .map(|header| (header.name().to_owned(), Value::from(header.value_string())))
+                    .collect(),
+                length: response.body.as_ref().map_or(0, |body| body.len()),
+            },
+        }
+    }
+}
+
 struct Connection {
-    sender: ws::Sender
+    sender: ws::Sender,
+    server: mpsc::Sender<Message>,
+    /// The target of the page endpoint this connection was opened on, if any.
+    target: Option<String>,
 }
 
 impl Handler for Connection {
+    fn on_request(&mut self, request: &Request) -> ws::Result<Response> {
+        let resource = request.resource().to_owned();
+        if resource.starts_with("/json") {
+            let (sender, receiver) = channel();
+            let _ = self.server.send(Message::HttpRequest(resource, sender));
+            // This runs on the thread of the WebSocket event loop, which must not wait for a
+            // busy server for long.
+            return Ok(match receiver.recv_timeout(Duration::from_millis(REPLY_TIMEOUT)) {
+                Ok(Some(body)) => {
+                    let mut response = Response::new(200, "OK", body.into_bytes());
+                    response.headers_mut().push(("Content-Type".to_owned(), b"application/json".to_vec()));
+                    response
+                },
+                Ok(None) => Response::new(404, "Not Found", vec![]),
+                Err(_) => Response::new(503, "Service Unavailable", vec![]),
+            });
+        }
+        let prefix = "/devtools/page/";
+        if resource.starts_with(prefix) {
+            self.target = Some(resource[prefix.len()..].to_owned());
+        }
+        Response::from_request(request)
+    }
+
     fn on_open(&mut self, _: Handshake) -> ws::Result<()> {
         debug!("Connection opened.");
         Ok(())
@@ -29,32 +159,64 @@ impl Handler for Connection {
 
     fn on_close(&mut self, _: CloseCode, _: &str) {
         debug!("Connection closed.");
+        let _ = self.server.send(Message::ConnectionClosed(self.sender.token()));
     }
 
     fn on_message(&mut self, message: ws::Message) -> ws::Result<()> {
-        self.sender.send(message)
+        if let ws::Message::Text(text) = message {
+            let msg = Message::Command(self.sender.clone(), self.target.clone(), text);
+            let _ = self.server.send(msg);
+        }
+        Ok(())
     }
 }
 
 pub fn start_server(port: u16) -> Sender {
     debug!("Starting server.");
     let (sender, receiver) = channel();
+    let server_sender = sender.clone();
+    let reply_sender = sender.clone();
     thread::Builder::new().name("debugger".to_owned()).spawn(move || {
-        let socket = Builder::new().build(|sender: ws::Sender| {
-            Connection { sender: sender }
+        let socket = Builder::new().build(move |sender: ws::Sender| {
+            Connection {
+                sender: sender,
+                server: server_sender.clone(),
+                target: None,
+            }
         }).unwrap();
-        let sender = socket.broadcaster();
+        let broadcaster = socket.broadcaster();
         thread::Builder::new().name("debugger-websocket".to_owned()).spawn(move || {
             socket.listen(("127.0.0.1", port)).unwrap();
         }).expect("Thread spawning failed");
+        let mut server = Server::new(port, reply_sender);
         while let Ok(message) = receiver.recv() {
             match message {
                 Message::ShutdownServer => {
                     break;
-                }
+                },
+                Message::SetConstellationChan(constellation_chan) => {
+                    server.constellation_chan = Some(constellation_chan);
+                },
+                Message::HttpRequest(resource, reply) => {
+                    let _ = reply.send(server.handle_http_request(&resource));
+                },
+                Message::Command(sender, target, text) => {
+                    server.handle_command(sender, target, &text);
+                },
+                Message::ConnectionClosed(token) => {
+                    server.subscriptions.retain(|subscription| subscription.sender.token() != token);
+                },
+                Message::NetworkEvent(request_id, update) => {
+                    server.handle_network_event(request_id, update);
+                },
+                Message::LoadComplete(target_id) => {
+                    let timestamp = json!({ "timestamp": timestamp() });
+                    server.send_page_event(&target_id, "Page.domContentEventFired", timestamp.clone());
+                    server.send_page_event(&target_id, "Page.loadEventFired", timestamp);
+                },
             }
         }
-        sender.shutdown().unwrap();
+        broadcaster.shutdown().unwrap();
     }).expect("Thread spawning failed");
     Sender(sender)
 }
@@ -66,3 +228,709 @@ pub fn shutdown_server(sender: &Sender) {
         warn!("Failed to shut down server.");
     }
 }
+
+/// Give the server the channel through which it drives the constellation. Commands that
+/// act on pages fail until this is called.
+pub fn set_constellation_chan(sender: &Sender, constellation_chan: mpsc::Sender<ConstellationMsg>) {
+    let &Sender(ref sender) = sender;
+    if let Err(_) = sender.send(Message::SetConstellationChan(constellation_chan)) {
+        warn!("Failed to send the constellation channel to the server.");
+    }
+}
+
+/// Return a channel for the resource threads to report network events on, which reports
+/// them to the server and forwards every message to the devtools server, if any.
+pub fn network_event_chan(sender: &Sender,
+                          devtools_chan: Option<mpsc::Sender<DevtoolsControlMsg>>)
+                          -> mpsc::Sender<DevtoolsControlMsg> {
+    let Sender(sender) = sender.clone();
+    let (network_sender, network_receiver) = channel();
+    thread::Builder::new().name("debugger-network".to_owned()).spawn(move || {
+        while let Ok(msg) = network_receiver.recv() {
+            if let DevtoolsControlMsg::FromChrome(
+                    ChromeToDevtoolsControlMsg::NetworkEvent(ref request_id, ref event)) = msg {
+                let update = NetworkUpdate::from(event);
+                if sender.send(Message::NetworkEvent(request_id.clone(), update)).is_err() {
+                    break;
+                }
+            }
+            if let Some(ref devtools_chan) = devtools_chan {
+                let _ = devtools_chan.send(msg);
+            }
+        }
+    }).expect("Thread spawning failed");
+    network_sender
+}
+
+/// An error reply to a command: a JSON-RPC error code and a message.
+type CommandError = (i64, String);
+
+fn invalid_params(message: &str) -> CommandError {
+    (-32602, message.to_owned())
+}
+
+fn server_error(message: &str) -> CommandError {
+    (-32000, message.to_owned())
+}
+
+/// Where to send the reply to a command.
+struct Reply {
+    sender: ws::Sender,
+    id: Value,
+    session_id: Option<String>,
+}
+
+impl Reply {
+    fn send(self, result: Result<Value, CommandError>) {
+        let mut reply = Map::new();
+        reply.insert("id".to_owned(), self.id);
+        match result {
+            Ok(result) => {
+                reply.insert("result".to_owned(), result);
+            },
+            Err((code, message)) => {
+                reply.insert("error".to_owned(), json!({ "code": code, "message": message }));
+            },
+        }
+        if let Some(session_id) = self.session_id {
+            reply.insert("sessionId".to_owned(), Value::from(session_id));
+        }
+        let _ = self.sender.send(Value::Object(reply).to_string());
+    }
+}
+
+/// A connection, or a session of a connection, that enabled the events of a domain.
+struct Subscription {
+    sender: ws::Sender,
+    session_id: Option<String>,
+    domain: String,
+}
+
+struct Server {
+    port: u16,
+    /// The channel of the server itself, for the replies that arrive after a command.
+    sender: mpsc::Sender<Message>,
+    constellation_chan: Option<mpsc::Sender<ConstellationMsg>>,
+    /// The top-level browsing contexts known to clients, by target id.
+    targets: HashMap<String, TopLevelBrowsingContextId>,
+    /// The target of each session, by session id.
+    sessions: HashMap<String, String>,
+    subscriptions: Vec<Subscription>,
+    /// The element ids behind the node ids handed out since the last `DOM.getDocument`.
+    nodes: Vec<String>,
+    /// The URLs of requests that are still waiting for a response, by request id.
+    requests: HashMap<String, String>,
+    next_id: u64,
+}
+
+impl Server {
+    fn new(port: u16, sender: mpsc::Sender<Message>) -> Server {
+        Server {
+            port: port,
+            sender: sender,
+            constellation_chan: None,
+            targets: HashMap::new(),
+            sessions: HashMap::new(),
+            subscriptions: vec![],
+            nodes: vec![],
+            requests: HashMap::new(),
+            next_id: 1,
+        }
+    }
+
+    fn new_id(&mut self, prefix: &str) -> String {
+        let id = self.next_id;
+        self.next_id += 1;
+        format!("{}-{}", prefix, id)
+    }
+
+    fn constellation_chan(&self) -> Result<&mpsc::Sender<ConstellationMsg>, CommandError> {
+        self.constellation_chan.as_ref().ok_or_else(|| server_error("Servo is still starting up"))
+    }
+
+    fn send_constellation_msg(&self, msg: ConstellationMsg) -> Result<(), CommandError> {
+        self.constellation_chan()?.send(msg).map_err(|_| server_error("Servo is shutting down"))
+    }
+
+    fn send_webdriver_command(&self, msg: WebDriverCommandMsg) -> Result<(), CommandError> {
+        self.send_constellation_msg(ConstellationMsg::WebDriverCommand(msg))
+    }
+
+    fn script_command(&self,
+                      target: TopLevelBrowsingContextId,
+                      command: WebDriverScriptCommand)
+                      -> Result<(), CommandError> {
+        let browsing_context_id = BrowsingContextId::from(target);
+        self.send_webdriver_command(WebDriverCommandMsg::ScriptCommand(browsing_context_id, command))
+    }
+
+    /// Return the id of the given browsing context, making it known to clients if needed.
+    fn target_id(&mut self, browsing_context_id: TopLevelBrowsingContextId) -> String {
+        if let Some(id) = self.targets.iter().find(|&(_, &known)| known == browsing_context_id) {
+            return id.0.clone();
+        }
+        let id = self.new_id("page");
+        self.targets.insert(id.clone(), browsing_context_id);
+        id
+    }
+
+    fn focused_target(&mut self) -> Option<String> {
+        let (sender, receiver) = ipc::channel().unwrap();
+        self.constellation_chan.as_ref()?
+            .send(ConstellationMsg::GetFocusTopLevelBrowsingContext(sender)).ok()?;
+        let browsing_context_id = recv_reply(receiver).ok()??;
+        Some(self.target_id(browsing_context_id))
+    }
+
+    fn all_targets(&mut self) -> Vec<String> {
+        self.focused_target();
+        let mut targets: Vec<_> = self.targets.keys().cloned().collect();
+        targets.sort();
+        targets
+    }
+
+    fn target_info(&self, target_id: &str) -> Value {
+        let browsing_context_id = self.targets[target_id];
+        let (url_sender, url_receiver) = ipc::channel().unwrap();
+        let (title_sender, title_receiver) = ipc::channel().unwrap();
+        let (url, title) = if self.script_command(browsing_context_id, WebDriverScriptCommand::GetUrl(url_sender))
+                .and(self.script_command(browsing_context_id, WebDriverScriptCommand::GetTitle(title_sender)))
+                .is_ok() {
+            (recv_reply(url_receiver).map(|url| url.into_string()).unwrap_or_default(),
+             recv_reply(title_receiver).unwrap_or_default())
+        } else {
+            (String::new(), String::new())
+        };
+        let attached = self.sessions.values().any(|target| target == target_id);
+        json!({
+            "targetId": target_id,
+            "type": "page",
+            "title": title,
+            "url": url,
+            "attached": attached,
+        })
+    }
+
+    fn handle_http_request(&mut self, resource: &str) -> Option<String> {
+        let body = match resource.trim_right_matches('/') {
+            "/json/version" => json!({
+                "Browser": "Servo",
+                "Protocol-Version": "1.3",
+                "webSocketDebuggerUrl": format!("ws://127.0.0.1:{}/devtools/browser", self.port),
+            }),
+            "/json" | "/json/list" => {
+                let targets = self.all_targets().iter().map(|target_id| {
+                    let mut info = self.target_info(target_id);
+                    info["id"] = Value::from(target_id.clone());
+                    info["webSocketDebuggerUrl"] =
+                        Value::from(format!("ws://127.0.0.1:{}/devtools/page/{}", self.port, target_id));
+                    info
+                }).collect();
+                Value::Array(targets)
+            },
+            _ => return None,
+        };
+        Some(body.to_string())
+    }
+
+    fn handle_command(&mut self, sender: ws::Sender, endpoint_target: Option<String>, text: &str) {
+        let command: Value = match serde_json::from_str(text) {
+            Ok(command) => command,
+            Err(error) => return warn!("Malformed command {:?}: {}", text, error),
+        };
+        let id = command["id"].clone();
+        let method = command["method"].as_str().unwrap_or("").to_owned();
+        let session_id = command["sessionId"].as_str().map(|session| session.to_owned());
+        let empty = Map::new();
+        let params = command["params"].as_object().unwrap_or(&empty);
+
+        let result = if method == "Page.captureScreenshot" {
+            // Screenshots are retried until the page can be painted, which can take a while,
+            // so they are taken on a thread of their own that replies once done.
+            let reply = Reply { sender: sender.clone(), id: id.clone(), session_id: session_id.clone() };
+            match self.capture_screenshot_later(reply, endpoint_target, session_id.as_ref(), params) {
+                Ok(()) => return,
+                Err(error) => Err(error),
+            }
+        } else {
+            self.handle_method(&sender, endpoint_target, session_id.as_ref(), &method, params)
+        };
+        Reply { sender: sender, id: id, session_id: session_id }.send(result);
+    }
+
+    /// Return the id of the page that commands of the given session or endpoint act on,
+    /// which is either the page of the session, the page of the endpoint, or the focused
+    /// page, with its browsing context.
+    fn page_target(&mut self,
+                   endpoint_target: Option<String>,
+                   session_id: Option<&String>)
+                   -> Result<(String, TopLevelBrowsingContextId), CommandError> {
+        let target_id = match session_id {
+            Some(session_id) => self.sessions.get(session_id).cloned()
+                .ok_or_else(|| server_error("Session with given id not found"))?,
+            None => match endpoint_target.or_else(|| self.focused_target()) {
+                Some(target_id) => target_id,
+                None => return Err(server_error("No page to run the command in")),
+            },
+        };
+        let target = *self.targets.get(&target_id)
+            .ok_or_else(|| server_error("The page has been closed"))?;
+        Ok((target_id, target))
+    }
+
+    fn capture_screenshot_later(&mut self,
+                                reply: Reply,
+                                endpoint_target: Option<String>,
+                                session_id: Option<&String>,
+                                params: &Map<String, Value>)
+                                -> Result<(), CommandError> {
+        match params.get("format").and_then(Value::as_str) {
+            None | Some("png") => {},
+            Some(_) => return Err(invalid_params("Only PNG screenshots are supported")),
+        }
+        let (_, target) = self.page_target(endpoint_target, session_id)?;
+        let constellation_chan = self.constellation_chan()?.clone();
+        thread::Builder::new().name("debugger-screenshot".to_owned()).spawn(move || {
+            let result = capture_screenshot(&constellation_chan, target)
+                .map(|data| json!({ "data": data }));
+            reply.send(result);
+        }).expect("Thread spawning failed");
+        Ok(())
+    }
+
+    fn handle_method(&mut self,
+                     sender: &ws::Sender,
+                     endpoint_target: Option<String>,
+                     session_id: Option<&String>,
+                     method: &str,
+                     params: &Map<String, Value>)
+                     -> Result<Value, CommandError> {
+        let mut parts = method.splitn(2, '.');
+        let domain = parts.next().unwrap_or("");
+
+        // Commands of the browser itself.
+        match method {
+            "Browser.getVersion" => return Ok(json!({
+                "protocolVersion": "1.3",
+                "product": "Servo",
+                "revision": "",
+                "userAgent": "",
+                "jsVersion": "",
+            })),
+            "Browser.close" => {
+                let _ = self.constellation_chan()?.send(ConstellationMsg::Exit);
+                return Ok(json!({}));
+            },
+            "Target.getBrowserContexts" => return Ok(json!({ "browserContextIds": [] })),
+            "Target.getTargets" => {
+                let infos: Vec<_> = self.all_targets().iter().map(|id| self.target_info(id)).collect();
+                return Ok(json!({ "targetInfos": infos }));
+            },
+            "Target.setDiscoverTargets" => {
+                if params.get("discover").and_then(Value::as_bool).unwrap_or(false) {
+                    for target_id in self.all_targets() {
+                        let info = self.target_info(&target_id);
+                        send_event(sender, None, "Target.targetCreated", json!({ "targetInfo": info }));
+                    }
+                }
+                return Ok(json!({}));
+            },
+            "Target.attachToTarget" => {
+                let target_id = string_param(params, "targetId")?;
+                if !self.targets.contains_key(&target_id) {
+                    return Err(invalid_params("No target with given id found"));
+                }
+                let new_session = self.new_id("session");
+                self.sessions.insert(new_session.clone(), target_id.clone());
+                send_event(sender, None, "Target.attachedToTarget", json!({
+                    "sessionId": new_session,
+                    "targetInfo": self.target_info(&target_id),
+                    "waitingForDebugger": false,
+                }));
+                return Ok(json!({ "sessionId": new_session }));
+            },
+            "Target.detachFromTarget" => {
+                let detached = string_param(params, "sessionId")?;
+                self.sessions.remove(&detached);
+                self.subscriptions.retain(|subscription| {
+                    subscription.session_id.as_ref() != Some(&detached)
+                });
+                send_event(sender, None, "Target.detachedFromTarget", json!({ "sessionId": detached }));
+                return Ok(json!({}));
+            },
+            "Target.createTarget" => {
+                let url = string_param(params, "url")?;
+                let url = ServoUrl::parse(&url).map_err(|_| invalid_params("Invalid URL"))?;
+                let (browser_sender, browser_receiver) = ipc::channel().unwrap();
+                self.send_constellation_msg(ConstellationMsg::NewBrowser(url, browser_sender))?;
+                let browsing_context_id = recv_reply(browser_receiver)?;
+                self.send_constellation_msg(ConstellationMsg::SelectBrowser(browsing_context_id))?;
+                let target_id = self.target_id(browsing_context_id);
+                return Ok(json!({ "targetId": target_id }));
+            },
+            "Target.activateTarget" | "Target.closeTarget" => {
+                let target_id = string_param(params, "targetId")?;
+                let browsing_context_id = *self.targets.get(&target_id)
+                    .ok_or_else(|| invalid_params("No target with given id found"))?;
+                return Ok(if method == "Target.activateTarget" {
+                    self.send_constellation_msg(ConstellationMsg::SelectBrowser(browsing_context_id))?;
+                    json!({})
+                } else {
+                    self.send_constellation_msg(ConstellationMsg::CloseBrowser(browsing_context_id))?;
+                    self.targets.remove(&target_id);
+                    self.sessions.retain(|_, target| *target != target_id);
+                    json!({ "success": true })
+                });
+            },
+            _ => {},
+        }
+
+        // Commands of a page.
+        let (target_id, target) = self.page_target(endpoint_target, session_id)?;
+
+        match method {
+            "Page.enable" | "Network.enable" => {
+                let already_enabled = self.subscriptions.iter().any(|subscription| {
+                    subscription.sender.token() == sender.token() &&
+                    subscription.session_id.as_ref() == session_id &&
+                    subscription.domain == domain
+                });
+                if !already_enabled {
+                    self.subscriptions.push(Subscription {
+                        sender: sender.clone(),
+                        session_id: session_id.cloned(),
+                        domain: domain.to_owned(),
+                    });
+                }
+                Ok(json!({}))
+            },
+            "Page.disable" | "Network.disable" => {
+                self.subscriptions.retain(|subscription| {
+                    subscription.sender.token() != sender.token() ||
+                    subscription.session_id.as_ref() != session_id ||
+                    subscription.domain != domain
+                });
+                Ok(json!({}))
+            },
+            "Runtime.enable" | "Runtime.disable" | "DOM.enable" | "DOM.disable" => Ok(json!({})),
+            "Page.navigate" => {
+                let url = string_param(params, "url")?;
+                let url = ServoUrl::parse(&url).map_err(|_| invalid_params("Invalid URL"))?;
+                let load_data = LoadData::new(url, None, None, None);
+                let load_sender = self.load_sender(&target_id);
+                self.send_webdriver_command(WebDriverCommandMsg::LoadUrl(target, load_data, load_sender))?;
+                Ok(json!({ "frameId": target_id, "loaderId": self.new_id("loader") }))
+            },
+            "Page.reload" => {
+                let load_sender = self.load_sender(&target_id);
+                self.send_webdriver_command(WebDriverCommandMsg::Refresh(target, load_sender))?;
+                Ok(json!({}))
+            },
+            "Page.getFrameTree" => {
+                let info = self.target_info(&target_id);
+                Ok(json!({
+                    "frameTree": {
+                        "frame": {
+                            "id": target_id,
+                            "loaderId": "",
+                            "url": info["url"],
+                            "securityOrigin": "",
+                            "mimeType": "text/html",
+                        },
+                    },
+                }))
+            },
+            "Runtime.evaluate" => {
+                let expression = string_param(params, "expression")?;
+                let (result_sender, result_receiver) = ipc::channel().unwrap();
+                self.script_command(target, WebDriverScriptCommand::ExecuteScript(expression, result_sender))?;
+                let result = match recv_reply(result_receiver)? {
+                    Ok(WebDriverJSValue::Undefined) => json!({ "type": "undefined" }),
+                    Ok(WebDriverJSValue::Null) => json!({ "type": "object", "subtype": "null", "value": null }),
+                    Ok(WebDriverJSValue::Boolean(value)) => json!({ "type": "boolean", "value": value }),
+                    Ok(WebDriverJSValue::Number(value)) => json!({
+                        "type": "number",
+                        "value": value,
+                        "description": value.to_string(),
+                    }),
+                    Ok(WebDriverJSValue::String(value)) => json!({ "type": "string", "value": value }),
+                    Err(WebDriverJSError::UnknownType) => json!({ "type": "object", "description": "Object" }),
+                    Err(_) => return Err(server_error("Cannot find context with specified id")),
+                };
+                Ok(json!({ "result": result }))
+            },
+            "DOM.getDocument" => {
+                self.nodes.clear();
+                let (url_sender, url_receiver) = ipc::channel().unwrap();
+                self.script_command(target, WebDriverScriptCommand::GetUrl(url_sender))?;
+                let url = recv_reply(url_receiver)?.into_string();
+                Ok(json!({
+                    "root": {
+                        "nodeId": DOCUMENT_NODE_ID,
+                        "backendNodeId": DOCUMENT_NODE_ID,
+                        "nodeType": 9,
+                        "nodeName": "#document",
+                        "localName": "",
+                        "nodeValue": "",
+                        "documentURL": url,
+                        "baseURL": url,
+                    },
+                }))
+            },
+            "DOM.querySelector" | "DOM.querySelectorAll" => {
+                if params.get("nodeId").and_then(Value::as_u64) != Some(DOCUMENT_NODE_ID) {
+                    return Err(server_error("Only the document can be queried"));
+                }
+                let selector = string_param(params, "selector")?;
+                if method == "DOM.querySelector" {
+                    let (element_sender, element_receiver) = ipc::channel().unwrap();
+                    self.script_command(target, WebDriverScriptCommand::FindElementCSS(selector, element_sender))?;
+                    let element = recv_reply(element_receiver)?
+                        .map_err(|_| server_error("DOM Error while querying"))?;
+                    let node_id = element.map_or(0, |element| self.node_id(element));
+                    Ok(json!({ "nodeId": node_id }))
+                } else {
+                    let (elements_sender, elements_receiver) = ipc::channel().unwrap();
+                    self.script_command(target, WebDriverScriptCommand::FindElementsCSS(selector, elements_sender))?;
+                    let elements = recv_reply(elements_receiver)?
+                        .map_err(|_| server_error("DOM Error while querying"))?;
+                    let node_ids: Vec<_> = elements.into_iter().map(|element| self.node_id(element)).collect();
+                    Ok(json!({ "nodeIds": node_ids }))
+                }
+            },
+            "DOM.getBoxModel" => {
+                let element = self.element(params)?;
+                let (rect_sender, rect_receiver) = ipc::channel().unwrap();
+                self.script_command(target, WebDriverScriptCommand::GetElementRect(element, rect_sender))?;
+                let rect = recv_reply(rect_receiver)?.map_err(|_| server_error("Could not compute box model."))?;
+                let (x, y, width, height) = (rect.origin.x, rect.origin.y, rect.size.width, rect.size.height);
+                let quad = json!([x, y, x + width, y, x + width, y + height, x, y + height]);
+                Ok(json!({
+                    "model": {
+                        "content": quad,
+                        "padding": quad,
+                        "border": quad,
+                        "margin": quad,
+                        "width": width,
+                        "height": height,
+                    },
+                }))
+            },
+            "DOM.focus" => {
+                let element = self.element(params)?;
+                let (focus_sender, focus_receiver) = ipc::channel().unwrap();
+                self.script_command(target, WebDriverScriptCommand::FocusElement(element, focus_sender))?;
+                recv_reply(focus_receiver)?.map_err(|_| server_error("Element is not focusable"))?;
+                Ok(json!({}))
+            },
+            "Input.dispatchMouseEvent" => {
+                let x = params.get("x").and_then(Value::as_f64).ok_or_else(|| invalid_params("Missing x"))?;
+                let y = params.get("y").and_then(Value::as_f64).ok_or_else(|| invalid_params("Missing y"))?;
+                let point = Point2D::new(x as f32, y as f32);
+                let button = match params.get("button").and_then(Value::as_str) {
+                    Some("middle") => MouseButton::Middle,
+                    Some("right") => MouseButton::Right,
+                    _ => MouseButton::Left,
+                };
+                let events = match string_param(params, "type")?.as_str() {
+                    "mouseMoved" => {
+                        self.send_constellation_msg(ConstellationMsg::MouseMoveEvent(point))?;
+                        vec![]
+                    },
+                    "mousePressed" => vec![MouseEventType::MouseDown],
+                    "mouseReleased" => vec![MouseEventType::MouseUp, MouseEventType::Click],
+                    _ => return Err(invalid_params("Unsupported mouse event type")),
+                };
+                for event in events {
+                    self.send_constellation_msg(ConstellationMsg::MouseEvent(event, button, point))?;
+                }
+                Ok(json!({}))
+            },
+            "Input.dispatchKeyEvent" => {
+                let state = match string_param(params, "type")?.as_str() {
+                    "keyDown" | "rawKeyDown" => KeyState::Pressed,
+                    "keyUp" => KeyState::Released,
+                    // The text of the key is delivered with the keyDown event.
+                    "char" => return Ok(json!({})),
+                    _ => return Err(invalid_params("Unsupported key event type")),
+                };
+                let key = params.get("code").and_then(Value::as_str).and_then(key_from_code)
+                    .or_else(|| params.get("key").and_then(Value::as_str).and_then(key_from_key_name))
+                    .ok_or_else(|| invalid_params("Unsupported key"))?;
+                let text = params.get("text").and_then(Value::as_str).and_then(|text| text.chars().next());
+                let modifiers = modifiers_from_bits(params.get("modifiers").and_then(Value::as_u64).unwrap_or(0));
+                self.send_constellation_msg(ConstellationMsg::KeyEvent(text, key, state, modifiers))?;
+                Ok(json!({}))
+            },
+            _ => Err((-32601, format!("'{}' wasn't found", method))),
+        }
+    }
+
+    fn node_id(&mut self, element: String) -> u64 {
+        let index = match self.nodes.iter().position(|known| *known == element) {
+            Some(index) => index,
+            None => {
+                self.nodes.push(element);
+                self.nodes.len() - 1
+            },
+        };
+        index as u64 + DOCUMENT_NODE_ID + 1
+    }
+
+    fn element(&self, params: &Map<String, Value>) -> Result<String, CommandError> {
+        params.get("nodeId").and_then(Value::as_u64)
+            .and_then(|node_id| node_id.checked_sub(DOCUMENT_NODE_ID + 1))
+            .and_then(|index| self.nodes.get(index as usize).cloned())
+            .ok_or_else(|| invalid_params("Could not find node with given id"))
+    }
+
+    /// Return a channel on which Servo reports the end of a navigation of the given page,
+    /// which the server reports to clients as page events. Commands reply as soon as the
+    /// navigation starts, like in Chrome, so that slow pages don't hold up other commands.
+    fn load_sender(&self, target_id: &str) -> IpcSender<LoadStatus> {
+        let (sender, receiver) = ipc::channel().unwrap();
+        let server = self.sender.clone();
+        let target_id = target_id.to_owned();
+        ROUTER.add_route(receiver.to_opaque(), Box::new(move |message| {
+            if let Ok(LoadStatus::LoadComplete) = message.to() {
+                let _ = server.send(Message::LoadComplete(target_id.clone()));
+            }
+        }));
+        sender
+    }
+
+    fn send_page_event(&self, target_id: &str, method: &str, params: Value) {
+        for subscription in &self.subscriptions {
+            if subscription.domain != "Page" {
+                continue;
+            }
+            let subscribed_target = match subscription.session_id {
+                Some(ref session_id) => self.sessions.get(session_id).map(|target| &**target),
+                None => Some(target_id),
+            };
+            if subscribed_target == Some(target_id) {
+                send_event(&subscription.sender, subscription.session_id.as_ref(), method, params.clone());
+            }
+        }
+    }
+
+    fn handle_network_event(&mut self, request_id: String, update: NetworkUpdate) {
+        let timestamp = timestamp();
+        let events = match update {
+            NetworkUpdate::Request { url, method, headers, is_xhr } => {
+                self.requests.insert(request_id.clone(), url.clone());
+                vec![("Network.requestWillBeSent", json!({
+                    "requestId": request_id,
+                    "loaderId": "",
+                    "documentURL": url,
+                    "request": { "url": url, "method": method, "headers": headers },
+                    "timestamp": timestamp,
+                    "wallTime": timestamp,
+                    "initiator": { "type": "other" },
+                    "type": if is_xhr { "XHR" } else { "Other" },
+                }))]
+            },
+            NetworkUpdate::Response { status, status_text, headers, length } => {
+                let url = self.requests.remove(&request_id).unwrap_or_default();
+                vec![
+                    ("Network.responseReceived", json!({
+                        "requestId": request_id,
+                        "loaderId": "",
+                        "timestamp": timestamp,
+                        "type": "Other",
+                        "response": {
+                            "url": url,
+                            "status": status,
+                            "statusText": status_text,
+                            "headers": headers,
+                        },
+                    })),
+                    ("Network.loadingFinished", json!({
+                        "requestId": request_id,
+                        "timestamp": timestamp,
+                        "encodedDataLength": length,
+                    })),
+                ]
+            },
+        };
+        // Network events are not associated with a page yet, so every subscriber gets them.
+        for subscription in self.subscriptions.iter().filter(|subscription| subscription.domain == "Network") {
+            for &(method, ref params) in &events {
+                send_event(&subscription.sender, subscription.session_id.as_ref(), method, params.clone());
+            }
+        }
+    }
+}
+
+fn send_event(sender: &ws::Sender, session_id: Option<&String>, method: &str, params: Value) {
+    let mut event = Map::new();
+    event.insert("method".to_owned(), Value::from(method));
+    event.insert("params".to_owned(), params);
+    if let Some(session_id) = session_id {
+        event.insert("sessionId".to_owned(), Value::from(session_id.clone()));
+    }
+    let _ = sender.send(Value::Object(event).to_string());
+}
+
+/// Wait for the reply to a message sent to Servo, giving up after `REPLY_TIMEOUT`.
+fn recv_reply<T>(receiver: IpcReceiver<T>) -> Result<T, CommandError>
+    where T: for<'de> Deserialize<'de> + Serialize + Send + 'static
+{
+    ROUTER.route_ipc_receiver_to_new_mpsc_receiver(receiver)
+        .recv_timeout(Duration::from_millis(REPLY_TIMEOUT))
+        .map_err(|_| server_error("Servo did not reply in time"))
+}
+
+/// Take a screenshot of the given page, retrying until it can be painted or
+/// `SCREENSHOT_TIMEOUT` passes, and return it as base64 PNG data.
+fn capture_screenshot(constellation_chan: &mpsc::Sender<ConstellationMsg>,
+                      target: TopLevelBrowsingContextId)
+                      -> Result<String, CommandError> {
+    let interval = 1000;
+    let mut img = None;
+    for _ in 0..SCREENSHOT_TIMEOUT / interval {
+        let (sender, receiver) = ipc::channel().unwrap();
+        let msg = ConstellationMsg::WebDriverCommand(WebDriverCommandMsg::TakeScreenshot(target, sender));
+        constellation_chan.send(msg).map_err(|_| server_error("Servo is shutting down"))?;
+        if let Some(x) = recv_reply(receiver)? {
+            img = Some(x);
+            break;
+        }
+        thread::sleep(Duration::from_millis(interval))
+    }
+    let img = img.ok_or_else(|| server_error("Taking screenshot timed out"))?;
+    encode_png(img)
+}
+
+/// Encode a screenshot as base64 PNG data.
+fn encode_png(img: Image) -> Result<String, CommandError> {
+    let (width, height) = (img.width, img.height);
+    let image = match img.format {
+        PixelFormat::RGB8 => RgbImage::from_raw(width, height, img.bytes.to_vec())
+            .map(DynamicImage::ImageRgb8),
+        PixelFormat::BGRA8 => {
+            let mut bytes = img.bytes.to_vec();
+            for pixel in bytes.chunks_mut(4) {
+                pixel.swap(0, 2);
+            }
+            RgbaImage::from_raw(width, height, bytes).map(DynamicImage::ImageRgba8)
+        },
+        PixelFormat::K8 | PixelFormat::KA8 => None,
+    };
+    let image = image.ok_or_else(|| server_error("Unexpected screenshot pixel format"))?;
+    let mut png_data = Vec::new();
+    image.write_to(&mut png_data, ImageFormat::PNG)
+        .map_err(|_| server_error("Could not encode the screenshot"))?;
+    Ok(base64::encode(&png_data))
+}
+
+fn string_param(params: &Map<String, Value>, name: &str) -> Result<String, CommandError> {
+    params.get(name).and_then(Value::as_str).map(|value| value.to_owned())
+        .ok_or_else(|| invalid_params(&format!("Missing or invalid '{}' parameter", name)))
+}
+
+/// The current time, in seconds since the epoch.
+fn timestamp() -> f64 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or(Duration::from_secs(0));
+    now.as_secs() as f64 + now.subsec_nanos() as f64 / 1e9
+}
@@ -0,0 +1,151 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+extern crate base64;
+extern crate debugger;
+extern crate image;
+extern crate ipc_channel;
+extern crate msg;
+extern crate net_traits;
+extern crate script_traits;
+#[macro_use] extern crate serde_json;
+extern crate ws;
+
+use image::GenericImage;
+use ipc_channel::ipc::IpcSharedMemory;
+use msg::constellation_msg::{PipelineNamespace, PipelineNamespaceId, TopLevelBrowsingContextId};
+use net_traits::image::base::{Image, PixelFormat};
+use script_traits::{ConstellationMsg, WebDriverCommandMsg};
+use serde_json::Value;
+use std::net::TcpListener;
+use std::sync::mpsc::{self, channel};
+use std::thread;
+use std::time::{Duration, Instant};
+use ws::{CloseCode, Handler, Handshake};
+
+/// Start a server driving a fake constellation with a single page, whose screenshots are
+/// the given image. Every other message is left unanswered.
+fn start_server(screenshot: Image) -> u16 {
+    let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let server = debugger::start_server(port);
+
+    let (constellation_chan, constellation_port) = channel();
+    thread::spawn(move || {
+        PipelineNamespace::install(PipelineNamespaceId(1));
+        let browser = TopLevelBrowsingContextId::new();
+        let mut unanswered = vec![];
+        while let Ok(msg) = constellation_port.recv() {
+            match msg {
+                ConstellationMsg::GetFocusTopLevelBrowsingContext(reply) => {
+                    reply.send(Some(browser)).unwrap();
+                },
+                ConstellationMsg::WebDriverCommand(WebDriverCommandMsg::TakeScreenshot(_, reply)) => {
+                    reply.send(Some(screenshot.clone())).unwrap();
+                },
+                // Keep the reply channels open, as an unresponsive page would.
+                msg => unanswered.push(msg),
+            }
+        }
+    });
+    debugger::set_constellation_chan(&server, constellation_chan);
+    port
+}
+
+fn image(format: PixelFormat, bytes: &[u8]) -> Image {
+    Image {
+        width: 1,
+        height: 1,
+        format: format,
+        bytes: IpcSharedMemory::from_bytes(bytes),
+        id: None,
+        animation: None,
+    }
+}
+
+struct Client {
+    out: ws::Sender,
+    command: String,
+    reply: mpsc::Sender<Value>,
+}
+
+impl Handler for Client {
+    fn on_open(&mut self, _: Handshake) -> ws::Result<()> {
+        self.out.send(self.command.clone())
+    }
+
+    fn on_message(&mut self, message: ws::Message) -> ws::Result<()> {
+        let message: Value = serde_json::from_str(&message.into_text()?).unwrap();
+        // Skip the events sent before the reply.
+        if message.get("id").is_some() {
+            self.reply.send(message).unwrap();
+            self.out.close(CloseCode::Normal)?;
+        }
+        Ok(())
+    }
+}
+
+/// Send a command to the browser endpoint and return the reply.
+fn command(port: u16, method: &str, params: Value) -> Value {
+    let command = json!({ "id": 1, "method": method, "params": params }).to_string();
+    let url = format!("ws://127.0.0.1:{}/devtools/browser", port);
+    let (sender, receiver) = channel();
+    // The server may not be listening yet.
+    for _ in 0..50 {
+        let _ = ws::connect(url.clone(), |out| Client {
+            out: out,
+            command: command.clone(),
+            reply: sender.clone(),
+        });
+        if let Ok(reply) = receiver.try_recv() {
+            return reply;
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+    panic!("The server did not reply to {}.", method);
+}
+
+#[test]
+fn test_screenshot_converts_bgra_pixels() {
+    let port = start_server(image(PixelFormat::BGRA8, &[0x10, 0x20, 0x30, 0xff]));
+    let reply = command(port, "Page.captureScreenshot", json!({}));
+
+    let png = base64::decode(reply["result"]["data"].as_str().unwrap()).unwrap();
+    let screenshot = image::load_from_memory(&png).unwrap();
+    assert_eq!(screenshot.dimensions(), (1, 1));
+    assert_eq!(screenshot.to_rgba().get_pixel(0, 0).data, [0x30, 0x20, 0x10, 0xff]);
+}
+
+#[test]
+fn test_screenshot_with_unexpected_pixel_format_is_an_error() {
+    let port = start_server(image(PixelFormat::KA8, &[0x80, 0xff]));
+    let reply = command(port, "Page.captureScreenshot", json!({}));
+    assert_eq!(reply["error"]["code"], -32000);
+    assert_eq!(reply["error"]["message"], "Unexpected screenshot pixel format");
+
+    // The server is still running.
+    let reply = command(port, "Browser.getVersion", json!({}));
+    assert_eq!(reply["result"]["product"], "Servo");
+}
+
+#[test]
+fn test_unanswered_command_times_out() {
+    let port = start_server(image(PixelFormat::RGB8, &[0, 0, 0]));
+    let start = Instant::now();
+    let reply = command(port, "Runtime.evaluate", json!({ "expression": "1" }));
+    assert_eq!(reply["error"]["message"], "Servo did not reply in time");
+    assert!(start.elapsed() < Duration::from_secs(30));
+}
+
+#[test]
+fn test_navigation_replies_before_the_load_completes() {
+    let port = start_server(image(PixelFormat::RGB8, &[0, 0, 0]));
+    let start = Instant::now();
+    let reply = command(port, "Page.navigate", json!({ "url": "http://example.com/" }));
+    assert!(reply["result"]["frameId"].is_string());
+
+    // The load never completes, but other commands are still handled.
+    let reply = command(port, "Browser.getVersion", json!({}));
+    assert_eq!(reply["result"]["product"], "Servo");
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
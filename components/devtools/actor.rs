@@ -166,10 +166,7 @@ impl ActorRegistry {
                 }
             }
         }
-        let new_actors = replace(&mut *self.new_actors.borrow_mut(), vec!());
-        for actor in new_actors.into_iter() {
-            self.actors.insert(actor.name().to_owned(), actor);
-        }
+        self.register_pending_actors();
 
         let old_actors = replace(&mut *self.old_actors.borrow_mut(), vec!());
         for name in old_actors {
@@ -178,6 +175,14 @@ impl ActorRegistry {
         Ok(())
     }
 
+    /// Register the actors created through `register_later`.
+    pub fn register_pending_actors(&mut self) {
+        let new_actors = replace(&mut *self.new_actors.borrow_mut(), vec!());
+        for actor in new_actors.into_iter() {
+            self.actors.insert(actor.name().to_owned(), actor);
+        }
+    }
+
     pub fn drop_actor(&mut self, name: String) {
         self.actors.remove(&name);
     }
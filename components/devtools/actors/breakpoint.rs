@@ -0,0 +1,67 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Represents a breakpoint set through a source actor.
+
+use actor::{Actor, ActorMessageStatus, ActorRegistry};
+use actors::thread::ThreadActor;
+use devtools_traits::DevtoolScriptControlMsg::{self, RemoveBreakpoint};
+use ipc_channel::ipc::IpcSender;
+use msg::constellation_msg::PipelineId;
+use protocol::JsonPacketStream;
+use serde_json::{Map, Value};
+use std::net::TcpStream;
+
+#[derive(Serialize)]
+struct DeleteReply {
+    from: String,
+}
+
+pub struct BreakpointActor {
+    name: String,
+    thread: String,
+    id: u32,
+    pipeline: PipelineId,
+    script_chan: IpcSender<DevtoolScriptControlMsg>,
+}
+
+impl BreakpointActor {
+    pub fn new(name: String,
+               thread: String,
+               id: u32,
+               pipeline: PipelineId,
+               script_chan: IpcSender<DevtoolScriptControlMsg>) -> BreakpointActor {
+        BreakpointActor {
+            name: name,
+            thread: thread,
+            id: id,
+            pipeline: pipeline,
+            script_chan: script_chan,
+        }
+    }
+}
+
+impl Actor for BreakpointActor {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn handle_message(&self,
+                      registry: &ActorRegistry,
+                      msg_type: &str,
+                      _msg: &Map<String, Value>,
+                      stream: &mut TcpStream) -> Result<ActorMessageStatus, ()> {
+        Ok(match msg_type {
+            "delete" => {
+                self.script_chan.send(RemoveBreakpoint(self.pipeline, self.id)).unwrap();
+                registry.find::<ThreadActor>(&self.thread).remove_breakpoint(self.id);
+                registry.drop_actor_later(self.name());
+                stream.write_json_packet(&DeleteReply { from: self.name() });
+                ActorMessageStatus::Processed
+            },
+
+            _ => ActorMessageStatus::Ignored,
+        })
+    }
+}
@@ -0,0 +1,298 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Liberally derived from the [Firefox JS implementation]
+//! (https://searchfox.org/mozilla-central/source/devtools/server/actors/frame.js).
+//! Represents a frame of the stack of a paused thread, the scopes it can see and the
+//! objects reachable from them. These actors only live until the thread resumes.
+
+use actor::{Actor, ActorMessageStatus, ActorRegistry};
+use actors::object::encode_value;
+use actors::thread::ThreadActor;
+use devtools_traits::{DebuggerValue, ScopeInfo};
+use devtools_traits::DevtoolScriptControlMsg::{self, GetObjectProperties, GetScopes};
+use ipc_channel::ipc::{self, IpcSender};
+use msg::constellation_msg::PipelineId;
+use protocol::JsonPacketStream;
+use serde_json::{Map, Value};
+use std::net::TcpStream;
+
+#[derive(Clone, Serialize)]
+struct EnvironmentForm {
+    actor: String,
+    #[serde(rename = "type")]
+    type_: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    function: Option<FunctionForm>,
+    bindings: BindingsForm,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent: Option<Box<EnvironmentForm>>,
+}
+
+#[derive(Clone, Serialize)]
+struct FunctionForm {
+    #[serde(rename = "type")]
+    type_: String,
+    class: String,
+    name: String,
+}
+
+#[derive(Clone, Serialize)]
+struct BindingsForm {
+    arguments: Vec<Map<String, Value>>,
+    variables: Map<String, Value>,
+}
+
+#[derive(Serialize)]
+struct EnvironmentReply {
+    from: String,
+    #[serde(flatten)]
+    environment: EnvironmentForm,
+}
+
+#[derive(Serialize)]
+struct ErrorReply {
+    from: String,
+    error: String,
+    message: String,
+}
+
+#[derive(Serialize)]
+struct BindingsReply {
+    from: String,
+    bindings: BindingsForm,
+}
+
+#[derive(Serialize)]
+struct PrototypeAndPropertiesReply {
+    from: String,
+    prototype: Value,
+    ownProperties: Map<String, Value>,
+}
+
+/// Encode a value of a paused frame as the grip that represents it in the protocol,
+/// creating an actor of the current pause for it if it is an object.
+fn encode_debugger_value(registry: &ActorRegistry,
+                         thread: &ThreadActor,
+                         value: DebuggerValue) -> Value {
+    match value {
+        DebuggerValue::Primitive(value) => encode_value(registry, value),
+        DebuggerValue::SpecialNumber(type_) => {
+            let mut m = Map::new();
+            m.insert("type".to_owned(), Value::String(type_));
+            Value::Object(m)
+        },
+        DebuggerValue::Object { class, id } => {
+            let mut m = Map::new();
+            m.insert("type".to_owned(), Value::String("object".to_owned()));
+            m.insert("class".to_owned(), Value::String(class));
+            m.insert("actor".to_owned(), Value::String(thread.object_actor(registry, id)));
+            m.insert("extensible".to_owned(), Value::Bool(true));
+            m.insert("frozen".to_owned(), Value::Bool(false));
+            m.insert("sealed".to_owned(), Value::Bool(false));
+            Value::Object(m)
+        },
+    }
+}
+
+/// The descriptors of the given properties, which only have a value.
+fn encode_properties(registry: &ActorRegistry,
+                     thread: &ThreadActor,
+                     properties: Vec<(String, DebuggerValue)>) -> Map<String, Value> {
+    properties.into_iter().map(|(name, value)| {
+        let mut descriptor = Map::new();
+        descriptor.insert("value".to_owned(), encode_debugger_value(registry, thread, value));
+        (name, Value::Object(descriptor))
+    }).collect()
+}
+
+fn not_paused(name: String, message: &str, stream: &mut TcpStream) {
+    stream.write_json_packet(&ErrorReply {
+        from: name,
+        error: "notPaused".to_owned(),
+        message: message.to_owned(),
+    });
+}
+
+pub struct FrameActor {
+    name: String,
+    thread: String,
+    depth: u32,
+    pipeline: PipelineId,
+    script_chan: IpcSender<DevtoolScriptControlMsg>,
+}
+
+impl FrameActor {
+    pub fn new(name: String,
+               thread: String,
+               depth: u32,
+               pipeline: PipelineId,
+               script_chan: IpcSender<DevtoolScriptControlMsg>) -> FrameActor {
+        FrameActor {
+            name: name,
+            thread: thread,
+            depth: depth,
+            pipeline: pipeline,
+            script_chan: script_chan,
+        }
+    }
+
+    /// Create the environment actors of the given scopes, returning the form of the
+    /// innermost one, which links to the others through its parents.
+    fn environment_form(&self,
+                        registry: &ActorRegistry,
+                        scopes: Vec<ScopeInfo>) -> Option<EnvironmentForm> {
+        let thread = registry.find::<ThreadActor>(&self.thread);
+        let mut environment = None;
+        for scope in scopes.into_iter().rev() {
+            let variables = encode_properties(registry, thread, scope.bindings);
+            let bindings = BindingsForm { arguments: vec![], variables: variables };
+            let actor = EnvironmentActor {
+                name: registry.new_name("environment"),
+                bindings: bindings.clone(),
+            };
+            let type_ = match &*scope.kind {
+                "declarative" if scope.function_name.is_some() => "function".to_owned(),
+                "declarative" => "block".to_owned(),
+                _ => scope.kind.clone(),
+            };
+            thread.add_pause_actor(actor.name());
+            environment = Some(EnvironmentForm {
+                actor: actor.name(),
+                type_: type_,
+                function: scope.function_name.map(|name| FunctionForm {
+                    type_: "object".to_owned(),
+                    class: "Function".to_owned(),
+                    name: name,
+                }),
+                bindings: bindings,
+                parent: environment.map(Box::new),
+            });
+            registry.register_later(Box::new(actor));
+        }
+        environment
+    }
+}
+
+impl Actor for FrameActor {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn handle_message(&self,
+                      registry: &ActorRegistry,
+                      msg_type: &str,
+                      _msg: &Map<String, Value>,
+                      stream: &mut TcpStream) -> Result<ActorMessageStatus, ()> {
+        Ok(match msg_type {
+            "getEnvironment" => {
+                let (tx, rx) = ipc::channel().unwrap();
+                self.script_chan.send(GetScopes(self.pipeline, self.depth, tx)).unwrap();
+                let scopes = rx.recv().map_err(|_| ())?.unwrap_or_default();
+                let environment = match self.environment_form(registry, scopes) {
+                    Some(environment) => environment,
+                    None => {
+                        not_paused(self.name(), "The frame is no longer on the stack.", stream);
+                        return Ok(ActorMessageStatus::Processed);
+                    },
+                };
+                stream.write_json_packet(&EnvironmentReply {
+                    from: self.name(),
+                    environment: environment,
+                });
+                ActorMessageStatus::Processed
+            },
+
+            _ => ActorMessageStatus::Ignored,
+        })
+    }
+}
+
+pub struct EnvironmentActor {
+    name: String,
+    bindings: BindingsForm,
+}
+
+impl Actor for EnvironmentActor {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn handle_message(&self,
+                      _registry: &ActorRegistry,
+                      msg_type: &str,
+                      _msg: &Map<String, Value>,
+                      stream: &mut TcpStream) -> Result<ActorMessageStatus, ()> {
+        Ok(match msg_type {
+            "bindings" => {
+                stream.write_json_packet(&BindingsReply {
+                    from: self.name(),
+                    bindings: self.bindings.clone(),
+                });
+                ActorMessageStatus::Processed
+            },
+
+            _ => ActorMessageStatus::Ignored,
+        })
+    }
+}
+
+pub struct PauseObjectActor {
+    name: String,
+    thread: String,
+    id: u32,
+    pipeline: PipelineId,
+    script_chan: IpcSender<DevtoolScriptControlMsg>,
+}
+
+impl PauseObjectActor {
+    pub fn new(name: String,
+               thread: String,
+               id: u32,
+               pipeline: PipelineId,
+               script_chan: IpcSender<DevtoolScriptControlMsg>) -> PauseObjectActor {
+        PauseObjectActor {
+            name: name,
+            thread: thread,
+            id: id,
+            pipeline: pipeline,
+            script_chan: script_chan,
+        }
+    }
+}
+
+impl Actor for PauseObjectActor {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn handle_message(&self,
+                      registry: &ActorRegistry,
+                      msg_type: &str,
+                      _msg: &Map<String, Value>,
+                      stream: &mut TcpStream) -> Result<ActorMessageStatus, ()> {
+        Ok(match msg_type {
+            "prototypeAndProperties" => {
+                let (tx, rx) = ipc::channel().unwrap();
+                self.script_chan.send(GetObjectProperties(self.pipeline, self.id, tx)).unwrap();
+                let info = match rx.recv().map_err(|_| ())? {
+                    Some(info) => info,
+                    None => {
+                        not_paused(self.name(), "The thread resumed since the object was seen.", stream);
+                        return Ok(ActorMessageStatus::Processed);
+                    },
+                };
+                let thread = registry.find::<ThreadActor>(&self.thread);
+                stream.write_json_packet(&PrototypeAndPropertiesReply {
+                    from: self.name(),
+                    prototype: encode_debugger_value(registry, thread, info.prototype),
+                    ownProperties: encode_properties(registry, thread, info.properties),
+                });
+                ActorMessageStatus::Processed
+            },
+
+            _ => ActorMessageStatus::Ignored,
+        })
+    }
+}
@@ -6,14 +6,18 @@
 //! (http://mxr.mozilla.org/mozilla-central/source/toolkit/devtools/server/actors/inspector.js).
 
 use actor::{Actor, ActorMessageStatus, ActorRegistry};
+use actors::stylesheets::{StyleSheetForm, StyleSheetsActor};
 use devtools_traits::{ComputedNodeLayout, DevtoolScriptControlMsg, NodeInfo};
-use devtools_traits::DevtoolScriptControlMsg::{GetChildren, GetDocumentElement, GetRootNode};
-use devtools_traits::DevtoolScriptControlMsg::{GetLayout, ModifyAttribute};
+use devtools_traits::{PropertyModification, StyleRuleId, StyleRuleInfo};
+use devtools_traits::DevtoolScriptControlMsg::{GetAppliedRules, GetChildren, GetDocumentElement};
+use devtools_traits::DevtoolScriptControlMsg::{GetLayout, GetRootNode, ModifyAttribute};
+use devtools_traits::DevtoolScriptControlMsg::{ModifyRule, SetRuleText};
 use ipc_channel::ipc::{self, IpcSender};
 use msg::constellation_msg::PipelineId;
 use protocol::JsonPacketStream;
 use serde_json::{self, Map, Value};
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::net::TcpStream;
 
 pub struct InspectorActor {
@@ -21,6 +25,7 @@ pub struct InspectorActor {
     pub walker: RefCell<Option<String>>,
     pub pageStyle: RefCell<Option<String>>,
     pub highlighter: RefCell<Option<String>>,
+    pub style_sheets: String,
     pub script_chan: IpcSender<DevtoolScriptControlMsg>,
     pub pipeline: PipelineId,
 }
@@ -347,15 +352,18 @@ struct PageStyleMsg {
 
 struct PageStyleActor {
     name: String,
+    style_sheets: String,
     script_chan: IpcSender<DevtoolScriptControlMsg>,
     pipeline: PipelineId,
+    /// The style rule actors created so far, by rule.
+    rules: RefCell<HashMap<StyleRuleId, String>>,
 }
 
 #[derive(Serialize)]
 struct GetAppliedReply {
     entries: Vec<AppliedEntry>,
     rules: Vec<AppliedRule>,
-    sheets: Vec<AppliedSheet>,
+    sheets: Vec<StyleSheetForm>,
     from: String,
 }
 
@@ -377,24 +385,113 @@ struct AppliedEntry {
 struct AppliedRule {
     actor: String,
     #[serde(rename = "type")]
-    type_: String,
-    href: String,
+    type_: u16,
+    href: Option<String>,
     cssText: String,
     line: u32,
     column: u32,
-    parentStyleSheet: String,
+    parentStyleSheet: Option<String>,
+    selectors: Vec<String>,
 }
 
 #[derive(Serialize)]
-struct AppliedSheet {
-    actor: String,
-    href: String,
-    nodeHref: String,
-    disabled: bool,
-    title: String,
-    system: bool,
-    styleSheetIndex: isize,
-    ruleCount: usize,
+struct ModifyRuleReply {
+    from: String,
+    rule: AppliedRule,
+}
+
+/// The `CSSRule.type` of style rules.
+const STYLE_RULE: u16 = 1;
+/// The rule type used by the protocol for the declarations of `style` attributes.
+const ELEMENT_STYLE: u16 = 100;
+
+/// Edits the declarations of a style rule or of a `style` attribute.
+struct StyleRuleActor {
+    name: String,
+    script_chan: IpcSender<DevtoolScriptControlMsg>,
+    pipeline: PipelineId,
+    /// The actor and URL of the stylesheet containing the rule, if any.
+    parent_style_sheet: Option<StyleSheetForm>,
+    info: RefCell<StyleRuleInfo>,
+}
+
+impl StyleRuleActor {
+    fn encodable(&self) -> AppliedRule {
+        let info = self.info.borrow();
+        AppliedRule {
+            actor: self.name(),
+            type_: match info.id {
+                StyleRuleId::Element(..) => ELEMENT_STYLE,
+                StyleRuleId::Sheet(..) => STYLE_RULE,
+            },
+            href: self.parent_style_sheet.as_ref().and_then(|sheet| sheet.href.clone()),
+            cssText: info.declarations.clone(),
+            line: info.line,
+            column: info.column,
+            parentStyleSheet: self.parent_style_sheet.as_ref().map(|sheet| sheet.actor.clone()),
+            selectors: info.selectors.clone(),
+        }
+    }
+
+    fn modify_reply(&self, declarations: String) -> ModifyRuleReply {
+        self.info.borrow_mut().declarations = declarations;
+        ModifyRuleReply {
+            from: self.name(),
+            rule: self.encodable(),
+        }
+    }
+}
+
+impl Actor for StyleRuleActor {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn handle_message(&self,
+                      _registry: &ActorRegistry,
+                      msg_type: &str,
+                      msg: &Map<String, Value>,
+                      stream: &mut TcpStream) -> Result<ActorMessageStatus, ()> {
+        Ok(match msg_type {
+            "modifyProperties" => {
+                let mods = msg.get("modifications").and_then(|mods| mods.as_array()).ok_or(())?;
+                let modifications = mods.iter().filter_map(|json_mod| {
+                    let value = match json_mod.get("type")?.as_str()? {
+                        "set" => Some(json_mod.get("value")?.as_str()?.to_owned()),
+                        "remove" => None,
+                        _ => return None,
+                    };
+                    Some(PropertyModification {
+                        name: json_mod.get("name")?.as_str()?.to_owned(),
+                        value: value,
+                        priority: json_mod.get("priority")
+                            .and_then(|priority| priority.as_str())
+                            .unwrap_or("")
+                            .to_owned(),
+                    })
+                }).collect();
+
+                let rule = self.info.borrow().id.clone();
+                let (tx, rx) = ipc::channel().unwrap();
+                self.script_chan.send(ModifyRule(self.pipeline, rule, modifications, tx)).unwrap();
+                let declarations = rx.recv().unwrap().ok_or(())?;
+                stream.write_json_packet(&self.modify_reply(declarations));
+                ActorMessageStatus::Processed
+            }
+
+            "setRuleText" => {
+                let text = msg.get("newText").and_then(|text| text.as_str()).ok_or(())?;
+                let rule = self.info.borrow().id.clone();
+                let (tx, rx) = ipc::channel().unwrap();
+                self.script_chan.send(SetRuleText(self.pipeline, rule, text.to_owned(), tx)).unwrap();
+                let declarations = rx.recv().unwrap().ok_or(())?;
+                stream.write_json_packet(&self.modify_reply(declarations));
+                ActorMessageStatus::Processed
+            }
+
+            _ => ActorMessageStatus::Ignored,
+        })
+    }
 }
 
 #[derive(Serialize)]
@@ -454,11 +551,66 @@ impl Actor for PageStyleActor {
                       stream: &mut TcpStream) -> Result<ActorMessageStatus, ()> {
         Ok(match msg_type {
             "getApplied" => {
-                //TODO: query script for relevant applied styles to node (msg.node)
+                let target = msg.get("node").and_then(|node| node.as_str()).ok_or(())?;
+                let (tx, rx) = ipc::channel().unwrap();
+                self.script_chan.send(GetAppliedRules(self.pipeline,
+                                                      registry.actor_to_script(target.to_owned()),
+                                                      tx))
+                                .unwrap();
+                let applied = rx.recv().unwrap().ok_or(())?;
+                let style_sheets = registry.find::<StyleSheetsActor>(&self.style_sheets)
+                                           .style_sheets(registry);
+
+                let mut entries = vec![];
+                let mut rules = vec![];
+                let mut sheets: Vec<StyleSheetForm> = vec![];
+                let mut rule_actors = self.rules.borrow_mut();
+                for info in applied {
+                    let parent_style_sheet = match info.id {
+                        StyleRuleId::Sheet(index, _) => style_sheets.iter()
+                            .find(|sheet| sheet.styleSheetIndex == index)
+                            .cloned(),
+                        StyleRuleId::Element(..) => None,
+                    };
+                    if let Some(ref sheet) = parent_style_sheet {
+                        if !sheets.iter().any(|known| known.actor == sheet.actor) {
+                            sheets.push(sheet.clone());
+                        }
+                    }
+                    let matched_selectors = info.matched_selectors.clone();
+                    let rule = match rule_actors.get(&info.id) {
+                        Some(name) => {
+                            let actor = registry.find::<StyleRuleActor>(name);
+                            *actor.info.borrow_mut() = info;
+                            actor.encodable()
+                        },
+                        None => {
+                            let actor = StyleRuleActor {
+                                name: registry.new_name("domstylerule"),
+                                script_chan: self.script_chan.clone(),
+                                pipeline: self.pipeline,
+                                parent_style_sheet: parent_style_sheet,
+                                info: RefCell::new(info.clone()),
+                            };
+                            let rule = actor.encodable();
+                            rule_actors.insert(info.id, actor.name());
+                            registry.register_later(Box::new(actor));
+                            rule
+                        },
+                    };
+                    entries.push(AppliedEntry {
+                        rule: rule.actor.clone(),
+                        pseudoElement: Value::Null,
+                        isSystem: false,
+                        matchedSelectors: matched_selectors,
+                    });
+                    rules.push(rule);
+                }
+
                 let msg = GetAppliedReply {
-                    entries: vec!(),
-                    rules: vec!(),
-                    sheets: vec!(),
+                    entries: entries,
+                    rules: rules,
+                    sheets: sheets,
                     from: self.name(),
                 };
                 stream.write_json_packet(&msg);
@@ -582,8 +734,10 @@ impl Actor for InspectorActor {
                 if self.pageStyle.borrow().is_none() {
                     let style = PageStyleActor {
                         name: registry.new_name("pageStyle"),
+                        style_sheets: self.style_sheets.clone(),
                         script_chan: self.script_chan.clone(),
                         pipeline: self.pipeline,
+                        rules: RefCell::new(HashMap::new()),
                     };
                     let mut pageStyle = self.pageStyle.borrow_mut();
                     *pageStyle = Some(style.name());
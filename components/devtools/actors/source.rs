@@ -0,0 +1,160 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Liberally derived from the [Firefox JS implementation]
+//! (https://searchfox.org/mozilla-central/source/devtools/server/actors/source.js).
+//! Represents a script loaded by a debugged global, and sets breakpoints in it.
+
+use actor::{Actor, ActorMessageStatus, ActorRegistry};
+use actors::breakpoint::BreakpointActor;
+use actors::thread::{LocationForm, ThreadActor};
+use devtools_traits::{DevtoolScriptControlMsg, SourceLocation};
+use devtools_traits::DevtoolScriptControlMsg::{GetSourceText, SetBreakpoint};
+use ipc_channel::ipc::{self, IpcSender};
+use msg::constellation_msg::PipelineId;
+use protocol::JsonPacketStream;
+use serde_json::{Map, Value};
+use std::net::TcpStream;
+
+#[derive(Clone, Serialize)]
+pub struct SourceForm {
+    pub actor: String,
+    pub url: String,
+    isBlackBoxed: bool,
+    isPrettyPrinted: bool,
+}
+
+#[derive(Serialize)]
+struct SourceReply {
+    from: String,
+    source: String,
+    contentType: String,
+}
+
+#[derive(Serialize)]
+struct SetBreakpointReply {
+    from: String,
+    actor: String,
+    actualLocation: LocationForm,
+}
+
+#[derive(Serialize)]
+struct ErrorReply {
+    from: String,
+    error: String,
+    message: String,
+}
+
+pub struct SourceActor {
+    name: String,
+    thread: String,
+    id: u32,
+    pipeline: PipelineId,
+    script_chan: IpcSender<DevtoolScriptControlMsg>,
+}
+
+impl SourceActor {
+    pub fn new(name: String,
+               thread: String,
+               id: u32,
+               pipeline: PipelineId,
+               script_chan: IpcSender<DevtoolScriptControlMsg>) -> SourceActor {
+        SourceActor {
+            name: name,
+            thread: thread,
+            id: id,
+            pipeline: pipeline,
+            script_chan: script_chan,
+        }
+    }
+
+    pub fn encodable(&self, url: String) -> SourceForm {
+        SourceForm {
+            actor: self.name(),
+            url: url,
+            isBlackBoxed: false,
+            isPrettyPrinted: false,
+        }
+    }
+}
+
+impl Actor for SourceActor {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn handle_message(&self,
+                      registry: &ActorRegistry,
+                      msg_type: &str,
+                      msg: &Map<String, Value>,
+                      stream: &mut TcpStream) -> Result<ActorMessageStatus, ()> {
+        Ok(match msg_type {
+            "source" => {
+                let (tx, rx) = ipc::channel().unwrap();
+                self.script_chan.send(GetSourceText(self.pipeline, self.id, tx)).unwrap();
+                let msg = SourceReply {
+                    from: self.name(),
+                    source: rx.recv().unwrap().unwrap_or_default(),
+                    contentType: "text/javascript".to_owned(),
+                };
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            "setBreakpoint" => {
+                let location = msg.get("location");
+                let line = location.and_then(|location| location.get("line"))
+                    .and_then(|line| line.as_u64());
+                let column = location.and_then(|location| location.get("column"))
+                    .and_then(|column| column.as_u64())
+                    .unwrap_or(0);
+                let line = match line {
+                    Some(line) => line,
+                    None => return Err(()),
+                };
+                let location = SourceLocation {
+                    source: self.id,
+                    line: line as u32,
+                    column: column as u32,
+                };
+                let (tx, rx) = ipc::channel().unwrap();
+                self.script_chan.send(SetBreakpoint(self.pipeline, location, tx)).unwrap();
+                let breakpoint = match rx.recv().unwrap() {
+                    Some(breakpoint) => breakpoint,
+                    None => {
+                        let msg = ErrorReply {
+                            from: self.name(),
+                            error: "noCodeAtLineColumn".to_owned(),
+                            message: format!("No code at line {}.", line),
+                        };
+                        stream.write_json_packet(&msg);
+                        return Ok(ActorMessageStatus::Processed);
+                    },
+                };
+
+                let thread = registry.find::<ThreadActor>(&self.thread);
+                let actor = BreakpointActor::new(registry.new_name("breakpoint"),
+                                                 self.thread.clone(),
+                                                 breakpoint.id,
+                                                 self.pipeline,
+                                                 self.script_chan.clone());
+                thread.add_breakpoint(breakpoint.id, actor.name());
+                let msg = SetBreakpointReply {
+                    from: self.name(),
+                    actor: actor.name(),
+                    actualLocation: LocationForm {
+                        source: thread.source_form(registry, self.id).unwrap(),
+                        line: breakpoint.location.line,
+                        column: breakpoint.location.column,
+                    },
+                };
+                registry.register_later(Box::new(actor));
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            _ => ActorMessageStatus::Ignored,
+        })
+    }
+}
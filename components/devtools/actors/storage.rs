@@ -0,0 +1,375 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Liberally derived from the [Firefox JS implementation]
+//! (https://searchfox.org/mozilla-central/source/devtools/server/actors/storage.js).
+//! Lists and edits the cookies, web storage and cache entries of the origin of a tab.
+
+use actor::{Actor, ActorMessageStatus, ActorRegistry};
+use devtools_traits::{CookieAttributes, DevtoolScriptControlMsg, StoreChange, StoreEntry, StoreType};
+use devtools_traits::DevtoolScriptControlMsg::{ClearStore, GetStorageHost, GetStoreEntries};
+use devtools_traits::DevtoolScriptControlMsg::{RemoveStoreEntry, SetStoreEntry};
+use ipc_channel::ipc::{self, IpcSender};
+use msg::constellation_msg::PipelineId;
+use protocol::JsonPacketStream;
+use serde_json::{self, Map, Value};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::net::TcpStream;
+
+const STORE_TYPES: [StoreType; 4] = [
+    StoreType::Cookies,
+    StoreType::LocalStorage,
+    StoreType::SessionStorage,
+    StoreType::Cache,
+];
+
+/// The name of a store type in the protocol.
+fn store_name(store: StoreType) -> &'static str {
+    match store {
+        StoreType::Cookies => "cookies",
+        StoreType::LocalStorage => "localStorage",
+        StoreType::SessionStorage => "sessionStorage",
+        StoreType::Cache => "Cache",
+    }
+}
+
+/// The data of a `storesUpdate` event for a single entry.
+fn update_data(action: &str, store: StoreType, host: String, name: String) -> Map<String, Value> {
+    let mut hosts = Map::new();
+    hosts.insert(host, Value::Array(vec![Value::String(name)]));
+    let mut stores = Map::new();
+    stores.insert(store_name(store).to_owned(), Value::Object(hosts));
+    let mut data = Map::new();
+    data.insert(action.to_owned(), Value::Object(stores));
+    data
+}
+
+#[derive(Serialize)]
+struct StoreForm {
+    actor: String,
+    hosts: HashMap<String, Vec<String>>,
+}
+
+#[derive(Serialize)]
+struct StoresUpdateReply {
+    from: String,
+    #[serde(rename = "type")]
+    type_: String,
+    data: Value,
+}
+
+pub struct StorageActor {
+    name: String,
+    pipeline: PipelineId,
+    script_chan: IpcSender<DevtoolScriptControlMsg>,
+    /// The clients that listed the stores, which are told about changes.
+    streams: RefCell<Vec<TcpStream>>,
+    /// The store actors, by store type.
+    stores: RefCell<HashMap<StoreType, String>>,
+}
+
+impl StorageActor {
+    pub fn new(name: String,
+               pipeline: PipelineId,
+               script_chan: IpcSender<DevtoolScriptControlMsg>) -> StorageActor {
+        StorageActor {
+            name: name,
+            pipeline: pipeline,
+            script_chan: script_chan,
+            streams: RefCell::new(Vec::new()),
+            stores: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Notify the clients that a store of the given origin changed.
+    pub fn store_changed(&self, store: StoreType, host: String, change: StoreChange) {
+        let (type_, data) = match change {
+            StoreChange::Added(name) => ("storesUpdate", update_data("added", store, host, name)),
+            StoreChange::Changed(name) => ("storesUpdate", update_data("changed", store, host, name)),
+            StoreChange::Deleted(name) => ("storesUpdate", update_data("deleted", store, host, name)),
+            StoreChange::Cleared => {
+                let mut data = Map::new();
+                data.insert(store_name(store).to_owned(), Value::Array(vec![Value::String(host)]));
+                ("storesCleared", data)
+            },
+        };
+        let msg = StoresUpdateReply {
+            from: self.name(),
+            type_: type_.to_owned(),
+            data: Value::Object(data),
+        };
+        for stream in &mut *self.streams.borrow_mut() {
+            stream.write_json_packet(&msg);
+        }
+    }
+}
+
+impl Actor for StorageActor {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn handle_message(&self,
+                      registry: &ActorRegistry,
+                      msg_type: &str,
+                      _msg: &Map<String, Value>,
+                      stream: &mut TcpStream) -> Result<ActorMessageStatus, ()> {
+        Ok(match msg_type {
+            "listStores" => {
+                let (tx, rx) = ipc::channel().unwrap();
+                self.script_chan.send(GetStorageHost(self.pipeline, tx)).unwrap();
+                let host = rx.recv().unwrap().ok_or(())?;
+
+                let mut stores = self.stores.borrow_mut();
+                let mut msg = Map::new();
+                msg.insert("from".to_owned(), Value::String(self.name()));
+                for store in STORE_TYPES.iter() {
+                    let actor = stores.entry(*store).or_insert_with(|| {
+                        let actor = StoreActor {
+                            name: registry.new_name(store_name(*store)),
+                            store: *store,
+                            pipeline: self.pipeline,
+                            script_chan: self.script_chan.clone(),
+                        };
+                        let name = actor.name();
+                        registry.register_later(Box::new(actor));
+                        name
+                    });
+                    let mut hosts = HashMap::new();
+                    hosts.insert(host.clone(), vec![]);
+                    let form = StoreForm {
+                        actor: actor.clone(),
+                        hosts: hosts,
+                    };
+                    msg.insert(store_name(*store).to_owned(), serde_json::to_value(&form).unwrap());
+                }
+                self.streams.borrow_mut().push(stream.try_clone().unwrap());
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            }
+
+            _ => ActorMessageStatus::Ignored,
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct CookieObject {
+    uniqueKey: String,
+    name: String,
+    value: String,
+    host: String,
+    path: String,
+    /// Milliseconds since the epoch, or 0 for session cookies.
+    expires: u64,
+    isHttpOnly: bool,
+    isSecure: bool,
+}
+
+#[derive(Serialize)]
+struct ItemObject {
+    name: String,
+    value: String,
+}
+
+#[derive(Serialize)]
+struct CacheObject {
+    url: String,
+    status: String,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum StoreObject {
+    Cookie(CookieObject),
+    Item(ItemObject),
+    Cache(CacheObject),
+}
+
+#[derive(Serialize)]
+struct GetStoreObjectsReply {
+    from: String,
+    offset: u64,
+    total: usize,
+    data: Vec<StoreObject>,
+}
+
+#[derive(Serialize)]
+struct FieldForm {
+    name: String,
+    editable: bool,
+}
+
+#[derive(Serialize)]
+struct GetFieldsReply {
+    from: String,
+    value: Vec<FieldForm>,
+}
+
+#[derive(Serialize)]
+struct EditReply {
+    from: String,
+}
+
+/// Lists and edits the entries of one type of store.
+struct StoreActor {
+    name: String,
+    store: StoreType,
+    pipeline: PipelineId,
+    script_chan: IpcSender<DevtoolScriptControlMsg>,
+}
+
+impl StoreActor {
+    fn encodable(&self, entry: StoreEntry) -> StoreObject {
+        match (self.store, entry.cookie) {
+            (StoreType::Cookies, Some(cookie)) => StoreObject::Cookie(CookieObject {
+                uniqueKey: format!("{}{}{}", entry.name, cookie.domain, cookie.path),
+                name: entry.name,
+                value: entry.value,
+                host: cookie.domain,
+                path: cookie.path,
+                expires: cookie.expires.unwrap_or(0),
+                isHttpOnly: cookie.http_only,
+                isSecure: cookie.secure,
+            }),
+            (StoreType::Cache, _) => StoreObject::Cache(CacheObject {
+                url: entry.name,
+                status: entry.value,
+            }),
+            _ => StoreObject::Item(ItemObject {
+                name: entry.name,
+                value: entry.value,
+            }),
+        }
+    }
+
+    /// Build the entry described by the `items` of an `editItem` request.
+    fn entry_from_items(&self, items: &Map<String, Value>) -> Option<StoreEntry> {
+        let string = |name: &str| items.get(name).and_then(|value| value.as_str()).map(str::to_owned);
+        let flag = |name: &str| items.get(name).and_then(|value| value.as_bool()).unwrap_or(false);
+        let cookie = match self.store {
+            StoreType::Cookies => Some(CookieAttributes {
+                domain: string("host").unwrap_or_default(),
+                path: string("path").unwrap_or_else(|| "/".to_owned()),
+                expires: items.get("expires")
+                    .and_then(|expires| expires.as_u64())
+                    .and_then(|expires| if expires == 0 { None } else { Some(expires) }),
+                http_only: flag("isHttpOnly"),
+                secure: flag("isSecure"),
+            }),
+            _ => None,
+        };
+        Some(StoreEntry {
+            name: string("name")?,
+            value: string("value").unwrap_or_default(),
+            cookie: cookie,
+        })
+    }
+}
+
+impl Actor for StoreActor {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn handle_message(&self,
+                      _registry: &ActorRegistry,
+                      msg_type: &str,
+                      msg: &Map<String, Value>,
+                      stream: &mut TcpStream) -> Result<ActorMessageStatus, ()> {
+        Ok(match msg_type {
+            "getStoreObjects" => {
+                let names = msg.get("names").and_then(|names| names.as_array()).map(|names| {
+                    names.iter().filter_map(|name| name.as_str()).map(str::to_owned).collect::<Vec<_>>()
+                });
+                let options = msg.get("options");
+                let offset = options.and_then(|options| options.get("offset"))
+                    .and_then(|offset| offset.as_u64())
+                    .unwrap_or(0);
+                let size = options.and_then(|options| options.get("size"))
+                    .and_then(|size| size.as_u64());
+
+                let (tx, rx) = ipc::channel().unwrap();
+                self.script_chan.send(GetStoreEntries(self.pipeline, self.store, tx)).unwrap();
+                let entries: Vec<_> = rx.recv().unwrap().into_iter().filter(|entry| {
+                    names.as_ref().map_or(true, |names| names.contains(&entry.name))
+                }).collect();
+                let total = entries.len();
+                let msg = GetStoreObjectsReply {
+                    from: self.name(),
+                    offset: offset,
+                    total: total,
+                    data: entries.into_iter()
+                        .skip(offset as usize)
+                        .take(size.map_or(usize::max_value(), |size| size as usize))
+                        .map(|entry| self.encodable(entry))
+                        .collect(),
+                };
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            }
+
+            "getFields" => {
+                let fields: &[&str] = match self.store {
+                    StoreType::Cookies => &["uniqueKey", "name", "value", "host", "path", "expires",
+                                            "isHttpOnly", "isSecure"],
+                    StoreType::LocalStorage | StoreType::SessionStorage => &["name", "value"],
+                    StoreType::Cache => &["url", "status"],
+                };
+                let msg = GetFieldsReply {
+                    from: self.name(),
+                    value: fields.iter().map(|field| FieldForm {
+                        name: (*field).to_owned(),
+                        editable: self.store != StoreType::Cache && *field != "uniqueKey",
+                    }).collect(),
+                };
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            }
+
+            "editItem" => {
+                let data = msg.get("data").and_then(|data| data.as_object()).ok_or(())?;
+                let items = data.get("items").and_then(|items| items.as_object()).ok_or(())?;
+                let entry = self.entry_from_items(items).ok_or(())?;
+                let field = data.get("field").and_then(|field| field.as_str());
+                let old_value = data.get("oldValue").and_then(|value| value.as_str());
+                if let (Some("name"), Some(old_name)) = (field, old_value) {
+                    if old_name != entry.name {
+                        self.script_chan.send(RemoveStoreEntry(self.pipeline, self.store, old_name.to_owned()))
+                                        .unwrap();
+                    }
+                }
+                self.script_chan.send(SetStoreEntry(self.pipeline, self.store, entry)).unwrap();
+                stream.write_json_packet(&EditReply { from: self.name() });
+                ActorMessageStatus::Processed
+            }
+
+            "addItem" => {
+                let name = msg.get("guid").and_then(|guid| guid.as_str()).ok_or(())?;
+                let mut items = Map::new();
+                items.insert("name".to_owned(), Value::String(name.to_owned()));
+                let entry = self.entry_from_items(&items).ok_or(())?;
+                self.script_chan.send(SetStoreEntry(self.pipeline, self.store, entry)).unwrap();
+                stream.write_json_packet(&EditReply { from: self.name() });
+                ActorMessageStatus::Processed
+            }
+
+            "removeItem" => {
+                let name = msg.get("name").and_then(|name| name.as_str()).ok_or(())?;
+                self.script_chan.send(RemoveStoreEntry(self.pipeline, self.store, name.to_owned())).unwrap();
+                stream.write_json_packet(&EditReply { from: self.name() });
+                ActorMessageStatus::Processed
+            }
+
+            "removeAll" => {
+                self.script_chan.send(ClearStore(self.pipeline, self.store)).unwrap();
+                stream.write_json_packet(&EditReply { from: self.name() });
+                ActorMessageStatus::Processed
+            }
+
+            _ => ActorMessageStatus::Ignored,
+        })
+    }
+}
@@ -0,0 +1,202 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Liberally derived from the [Firefox JS implementation]
+//! (https://searchfox.org/mozilla-central/source/devtools/server/actors/stylesheets.js).
+//! Lists the stylesheets of a document and lets the style editor replace their contents.
+
+use actor::{Actor, ActorMessageStatus, ActorRegistry};
+use devtools_traits::{DevtoolScriptControlMsg, StyleSheetInfo};
+use devtools_traits::DevtoolScriptControlMsg::{GetStyleSheetText, GetStyleSheets, UpdateStyleSheet};
+use ipc_channel::ipc::{self, IpcSender};
+use msg::constellation_msg::PipelineId;
+use protocol::JsonPacketStream;
+use serde_json::{Map, Value};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::net::TcpStream;
+
+#[derive(Clone, Serialize)]
+pub struct StyleSheetForm {
+    pub actor: String,
+    pub href: Option<String>,
+    nodeHref: String,
+    disabled: bool,
+    title: String,
+    system: bool,
+    pub styleSheetIndex: u32,
+    ruleCount: u32,
+}
+
+#[derive(Serialize)]
+struct GetStyleSheetsReply {
+    from: String,
+    styleSheets: Vec<StyleSheetForm>,
+}
+
+#[derive(Serialize)]
+struct GetTextReply {
+    from: String,
+    text: String,
+}
+
+#[derive(Serialize)]
+struct StyleAppliedReply {
+    from: String,
+    #[serde(rename = "type")]
+    type_: String,
+}
+
+#[derive(Serialize)]
+struct UpdateReply {
+    from: String,
+}
+
+#[derive(Serialize)]
+struct ErrorReply {
+    from: String,
+    error: String,
+    message: String,
+}
+
+pub struct StyleSheetsActor {
+    name: String,
+    url: String,
+    script_chan: IpcSender<DevtoolScriptControlMsg>,
+    pipeline: PipelineId,
+    /// The stylesheet actors of the document, by stylesheet index.
+    sheets: RefCell<HashMap<u32, String>>,
+}
+
+impl StyleSheetsActor {
+    pub fn new(name: String,
+               url: String,
+               pipeline: PipelineId,
+               script_chan: IpcSender<DevtoolScriptControlMsg>) -> StyleSheetsActor {
+        StyleSheetsActor {
+            name: name,
+            url: url,
+            script_chan: script_chan,
+            pipeline: pipeline,
+            sheets: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Return the forms of the current stylesheets of the document, creating actors for
+    /// the stylesheets that were not seen before.
+    pub fn style_sheets(&self, registry: &ActorRegistry) -> Vec<StyleSheetForm> {
+        let (tx, rx) = ipc::channel().unwrap();
+        self.script_chan.send(GetStyleSheets(self.pipeline, tx)).unwrap();
+        let mut sheets = self.sheets.borrow_mut();
+        rx.recv().unwrap().into_iter().map(|info| {
+            let name = sheets.entry(info.index).or_insert_with(|| {
+                let actor = StyleSheetActor {
+                    name: registry.new_name("stylesheet"),
+                    index: info.index,
+                    script_chan: self.script_chan.clone(),
+                    pipeline: self.pipeline,
+                };
+                let name = actor.name();
+                registry.register_later(Box::new(actor));
+                name
+            }).clone();
+            self.encodable(name, info)
+        }).collect()
+    }
+
+    fn encodable(&self, actor: String, info: StyleSheetInfo) -> StyleSheetForm {
+        StyleSheetForm {
+            actor: actor,
+            href: info.href,
+            nodeHref: self.url.clone(),
+            disabled: info.disabled,
+            title: info.title.unwrap_or_default(),
+            system: false,
+            styleSheetIndex: info.index,
+            ruleCount: info.rule_count,
+        }
+    }
+}
+
+impl Actor for StyleSheetsActor {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn handle_message(&self,
+                      registry: &ActorRegistry,
+                      msg_type: &str,
+                      _msg: &Map<String, Value>,
+                      stream: &mut TcpStream) -> Result<ActorMessageStatus, ()> {
+        Ok(match msg_type {
+            "getStyleSheets" => {
+                let msg = GetStyleSheetsReply {
+                    from: self.name(),
+                    styleSheets: self.style_sheets(registry),
+                };
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            }
+
+            _ => ActorMessageStatus::Ignored,
+        })
+    }
+}
+
+struct StyleSheetActor {
+    name: String,
+    index: u32,
+    script_chan: IpcSender<DevtoolScriptControlMsg>,
+    pipeline: PipelineId,
+}
+
+impl Actor for StyleSheetActor {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn handle_message(&self,
+                      _registry: &ActorRegistry,
+                      msg_type: &str,
+                      msg: &Map<String, Value>,
+                      stream: &mut TcpStream) -> Result<ActorMessageStatus, ()> {
+        Ok(match msg_type {
+            "getText" => {
+                let (tx, rx) = ipc::channel().unwrap();
+                self.script_chan.send(GetStyleSheetText(self.pipeline, self.index, tx)).unwrap();
+                let msg = GetTextReply {
+                    from: self.name(),
+                    text: rx.recv().unwrap().ok_or(())?,
+                };
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            }
+
+            "update" => {
+                let text = msg.get("text").and_then(|text| text.as_str()).ok_or(())?;
+                let (tx, rx) = ipc::channel().unwrap();
+                self.script_chan.send(UpdateStyleSheet(self.pipeline, self.index, text.to_owned(), tx))
+                                .unwrap();
+                if !rx.recv().unwrap() {
+                    let msg = ErrorReply {
+                        from: self.name(),
+                        error: "invalidText".to_owned(),
+                        message: "The stylesheet text has parse errors.".to_owned(),
+                    };
+                    stream.write_json_packet(&msg);
+                    return Ok(ActorMessageStatus::Processed);
+                }
+                let event = StyleAppliedReply {
+                    from: self.name(),
+                    type_: "styleApplied".to_owned(),
+                };
+                stream.write_json_packet(&event);
+                stream.write_json_packet(&UpdateReply { from: self.name() });
+                ActorMessageStatus::Processed
+            }
+
+            _ => ActorMessageStatus::Ignored,
+        })
+    }
+}
@@ -62,6 +62,8 @@ pub struct TabActorMsg {
     outerWindowID: u32,
     consoleActor: String,
     inspectorActor: String,
+    styleSheetsActor: String,
+    storageActor: String,
     timelineActor: String,
     profilerActor: String,
     performanceActor: String,
@@ -73,6 +75,8 @@ pub struct TabActor {
     pub url: String,
     pub console: String,
     pub inspector: String,
+    pub style_sheets: String,
+    pub storage: String,
     pub timeline: String,
     pub profiler: String,
     pub performance: String,
@@ -161,6 +165,8 @@ impl TabActor {
             outerWindowID: 0, //FIXME: this should probably be the pipeline id
             consoleActor: self.console.clone(),
             inspectorActor: self.inspector.clone(),
+            styleSheetsActor: self.style_sheets.clone(),
+            storageActor: self.storage.clone(),
             timelineActor: self.timeline.clone(),
             profilerActor: self.profiler.clone(),
             performanceActor: self.performance.clone(),
@@ -2,10 +2,24 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+//! Liberally derived from the [Firefox JS implementation]
+//! (https://searchfox.org/mozilla-central/source/devtools/server/actors/thread.js).
+//! Controls the JavaScript debugger of a single global: pausing, resuming, stepping and
+//! inspecting the stack. The debugger itself runs in the script thread.
+
 use actor::{Actor, ActorMessageStatus, ActorRegistry};
+use actors::frame::{FrameActor, PauseObjectActor};
+use actors::source::{SourceActor, SourceForm};
+use devtools_traits::{FrameInfo, PauseInfo, PauseReason, StepKind};
+use devtools_traits::DevtoolScriptControlMsg::{self, AttachDebugger, DetachDebugger};
+use devtools_traits::DevtoolScriptControlMsg::{GetFrames, GetSources, Interrupt, Resume};
+use ipc_channel::ipc::{self, IpcSender};
+use msg::constellation_msg::PipelineId;
 use protocol::JsonPacketStream;
 use serde_json::{Map, Value};
-use std::net::TcpStream;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::net::{SocketAddr, TcpStream};
 
 #[derive(Serialize)]
 struct ThreadAttachedReply {
@@ -24,6 +38,19 @@ enum PoppedFrameMsg {}
 struct WhyMsg {
     #[serde(rename = "type")]
     type_: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    actors: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct ThreadPausedReply {
+    from: String,
+    #[serde(rename = "type")]
+    type_: String,
+    actor: String,
+    frame: FrameForm,
+    poppedFrames: Vec<PoppedFrameMsg>,
+    why: WhyMsg,
 }
 
 #[derive(Serialize)]
@@ -33,6 +60,13 @@ struct ThreadResumedReply {
     type_: String,
 }
 
+#[derive(Serialize)]
+struct ThreadDetachedReply {
+    from: String,
+    #[serde(rename = "type")]
+    type_: String,
+}
+
 #[derive(Serialize)]
 struct ReconfigureReply {
     from: String
@@ -41,20 +75,207 @@ struct ReconfigureReply {
 #[derive(Serialize)]
 struct SourcesReply {
     from: String,
-    sources: Vec<Source>,
+    sources: Vec<SourceForm>,
+}
+
+#[derive(Serialize)]
+struct FramesReply {
+    from: String,
+    frames: Vec<FrameForm>,
 }
 
 #[derive(Serialize)]
-enum Source {}
+struct FrameForm {
+    actor: String,
+    depth: u32,
+    #[serde(rename = "type")]
+    type_: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    callee: Option<CalleeForm>,
+    #[serde(rename = "where")]
+    where_: LocationForm,
+}
+
+#[derive(Serialize)]
+struct CalleeForm {
+    #[serde(rename = "type")]
+    type_: String,
+    class: String,
+    name: String,
+}
+
+#[derive(Serialize)]
+pub struct LocationForm {
+    pub source: SourceForm,
+    pub line: u32,
+    pub column: u32,
+}
 
 pub struct ThreadActor {
     name: String,
+    script_chan: IpcSender<DevtoolScriptControlMsg>,
+    pipeline: PipelineId,
+    /// The streams of the attached clients, by the address of the client.
+    streams: RefCell<HashMap<SocketAddr, TcpStream>>,
+    /// The source actors of the scripts seen so far, by source id.
+    sources: RefCell<HashMap<u32, SourceForm>>,
+    /// The breakpoint actors of this thread, by breakpoint id.
+    breakpoints: RefCell<HashMap<u32, String>>,
+    /// The frame actors of the current pause, by depth.
+    frames: RefCell<HashMap<u32, String>>,
+    /// The object actors of the current pause, by the id of their object.
+    objects: RefCell<HashMap<u32, String>>,
+    /// The actors that only live until the thread resumes, other than the frame and object
+    /// actors.
+    pause_actors: RefCell<Vec<String>>,
 }
 
 impl ThreadActor {
-    pub fn new(name: String) -> ThreadActor {
+    pub fn new(name: String,
+               pipeline: PipelineId,
+               script_chan: IpcSender<DevtoolScriptControlMsg>) -> ThreadActor {
         ThreadActor {
             name: name,
+            script_chan: script_chan,
+            pipeline: pipeline,
+            streams: RefCell::new(HashMap::new()),
+            sources: RefCell::new(HashMap::new()),
+            breakpoints: RefCell::new(HashMap::new()),
+            frames: RefCell::new(HashMap::new()),
+            objects: RefCell::new(HashMap::new()),
+            pause_actors: RefCell::new(vec![]),
+        }
+    }
+
+    pub fn add_breakpoint(&self, id: u32, actor: String) {
+        self.breakpoints.borrow_mut().insert(id, actor);
+    }
+
+    pub fn remove_breakpoint(&self, id: u32) {
+        self.breakpoints.borrow_mut().remove(&id);
+    }
+
+    /// Drop the given actor when the thread resumes.
+    pub fn add_pause_actor(&self, name: String) {
+        self.pause_actors.borrow_mut().push(name);
+    }
+
+    /// Drop the actors of the current pause, whose frames are gone once the thread resumes.
+    fn clear_pause_actors(&self, registry: &ActorRegistry) {
+        for (_, name) in self.frames.borrow_mut().drain() {
+            registry.drop_actor_later(name);
+        }
+        for (_, name) in self.objects.borrow_mut().drain() {
+            registry.drop_actor_later(name);
+        }
+        for name in self.pause_actors.borrow_mut().drain(..) {
+            registry.drop_actor_later(name);
+        }
+    }
+
+    /// Return the name of the actor of the frame at the given depth of the current pause.
+    fn frame_actor(&self, registry: &ActorRegistry, depth: u32) -> String {
+        let mut frames = self.frames.borrow_mut();
+        frames.entry(depth).or_insert_with(|| {
+            let actor = FrameActor::new(registry.new_name("frame"),
+                                        self.name(),
+                                        depth,
+                                        self.pipeline,
+                                        self.script_chan.clone());
+            let name = actor.name();
+            registry.register_later(Box::new(actor));
+            name
+        }).clone()
+    }
+
+    /// Return the name of the actor of the object with the given id in the current pause.
+    pub fn object_actor(&self, registry: &ActorRegistry, id: u32) -> String {
+        let mut objects = self.objects.borrow_mut();
+        objects.entry(id).or_insert_with(|| {
+            let actor = PauseObjectActor::new(registry.new_name("pauseobject"),
+                                              self.name(),
+                                              id,
+                                              self.pipeline,
+                                              self.script_chan.clone());
+            let name = actor.name();
+            registry.register_later(Box::new(actor));
+            name
+        }).clone()
+    }
+
+    /// Return the form of the given source, creating actors for any scripts loaded since
+    /// the last time the sources were listed.
+    pub fn source_form(&self, registry: &ActorRegistry, id: u32) -> Option<SourceForm> {
+        if !self.sources.borrow().contains_key(&id) {
+            self.update_sources(registry);
+        }
+        self.sources.borrow().get(&id).cloned()
+    }
+
+    fn update_sources(&self, registry: &ActorRegistry) {
+        let (tx, rx) = ipc::channel().unwrap();
+        self.script_chan.send(GetSources(self.pipeline, tx)).unwrap();
+        let mut sources = self.sources.borrow_mut();
+        for info in rx.recv().unwrap() {
+            if sources.contains_key(&info.id) {
+                continue;
+            }
+            let actor = SourceActor::new(registry.new_name("source"),
+                                         self.name(),
+                                         info.id,
+                                         self.pipeline,
+                                         self.script_chan.clone());
+            sources.insert(info.id, actor.encodable(info.url));
+            registry.register_later(Box::new(actor));
+        }
+    }
+
+    fn frame_form(&self, registry: &ActorRegistry, frame: FrameInfo) -> Option<FrameForm> {
+        let source = self.source_form(registry, frame.location.source)?;
+        Some(FrameForm {
+            actor: self.frame_actor(registry, frame.depth),
+            depth: frame.depth,
+            type_: frame.kind,
+            callee: frame.function_name.map(|name| CalleeForm {
+                type_: "object".to_owned(),
+                class: "Function".to_owned(),
+                name: name,
+            }),
+            where_: LocationForm {
+                source: source,
+                line: frame.location.line,
+                column: frame.location.column,
+            },
+        })
+    }
+
+    /// Notify the attached clients that the debugger paused.
+    pub fn paused(&self, registry: &ActorRegistry, info: PauseInfo) {
+        let frame = match self.frame_form(registry, info.frame) {
+            Some(frame) => frame,
+            None => return warn!("Paused in an unknown source."),
+        };
+        let breakpoints = self.breakpoints.borrow();
+        let msg = ThreadPausedReply {
+            from: self.name(),
+            type_: "paused".to_owned(),
+            actor: registry.new_name("pause"),
+            frame: frame,
+            poppedFrames: vec![],
+            why: WhyMsg {
+                type_: match info.reason {
+                    PauseReason::Breakpoint => "breakpoint",
+                    PauseReason::Interrupted => "interrupted",
+                    PauseReason::ResumeLimit => "resumeLimit",
+                    PauseReason::DebuggerStatement => "debuggerStatement",
+                }.to_owned(),
+                actors: info.breakpoints.iter()
+                    .filter_map(|id| breakpoints.get(id).cloned())
+                    .collect(),
+            },
+        };
+        for stream in self.streams.borrow_mut().values_mut() {
+            stream.write_json_packet(&msg);
         }
     }
 }
@@ -67,7 +288,7 @@ impl Actor for ThreadActor {
     fn handle_message(&self,
                       registry: &ActorRegistry,
                       msg_type: &str,
-                      _msg: &Map<String, Value>,
+                      msg: &Map<String, Value>,
                       stream: &mut TcpStream) -> Result<ActorMessageStatus, ()> {
         Ok(match msg_type {
             "attach" => {
@@ -76,17 +297,68 @@ impl Actor for ThreadActor {
                     type_: "paused".to_owned(),
                     actor: registry.new_name("pause"),
                     poppedFrames: vec![],
-                    why: WhyMsg { type_: "attached".to_owned() },
+                    why: WhyMsg { type_: "attached".to_owned(), actors: vec![] },
                 };
+                let client = stream.peer_addr().map_err(|_| ())?;
+                self.streams.borrow_mut().insert(client, stream.try_clone().unwrap());
+                self.script_chan.send(AttachDebugger(self.pipeline)).unwrap();
                 stream.write_json_packet(&msg);
                 ActorMessageStatus::Processed
             },
 
             "resume" => {
+                let step = msg.get("resumeLimit")
+                    .and_then(|limit| limit.get("type"))
+                    .and_then(|type_| type_.as_str())
+                    .and_then(|type_| match type_ {
+                        "next" => Some(StepKind::Next),
+                        "step" => Some(StepKind::Step),
+                        "finish" => Some(StepKind::Finish),
+                        _ => None,
+                    });
                 let msg = ThreadResumedReply {
                     from: self.name(),
                     type_: "resumed".to_owned(),
                 };
+                self.clear_pause_actors(registry);
+                self.script_chan.send(Resume(self.pipeline, step)).unwrap();
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            // The reply is the "paused" packet sent once the script thread actually pauses.
+            "interrupt" => {
+                self.script_chan.send(Interrupt(self.pipeline)).unwrap();
+                ActorMessageStatus::Processed
+            },
+
+            "frames" => {
+                let start = msg.get("start").and_then(|start| start.as_u64()).unwrap_or(0);
+                let count = msg.get("count").and_then(|count| count.as_u64());
+                let (tx, rx) = ipc::channel().unwrap();
+                self.script_chan.send(GetFrames(self.pipeline, tx)).unwrap();
+                let frames = rx.recv().unwrap().into_iter()
+                    .skip(start as usize)
+                    .take(count.map_or(usize::max_value(), |count| count as usize))
+                    .filter_map(|frame| self.frame_form(registry, frame))
+                    .collect();
+                stream.write_json_packet(&FramesReply { from: self.name(), frames: frames });
+                ActorMessageStatus::Processed
+            },
+
+            "detach" => {
+                let msg = ThreadDetachedReply {
+                    from: self.name(),
+                    type_: "detached".to_owned(),
+                };
+                if let Ok(client) = stream.peer_addr() {
+                    self.streams.borrow_mut().remove(&client);
+                }
+                if self.streams.borrow().is_empty() {
+                    self.clear_pause_actors(registry);
+                    self.breakpoints.borrow_mut().clear();
+                    self.script_chan.send(DetachDebugger(self.pipeline)).unwrap();
+                }
                 stream.write_json_packet(&msg);
                 ActorMessageStatus::Processed
             },
@@ -97,9 +369,13 @@ impl Actor for ThreadActor {
             }
 
             "sources" => {
+                self.update_sources(registry);
+                let sources = self.sources.borrow();
+                let mut ids: Vec<_> = sources.keys().cloned().collect();
+                ids.sort();
                 let msg = SourcesReply {
                     from: self.name(),
-                    sources: vec![],
+                    sources: ids.iter().map(|id| sources[id].clone()).collect(),
                 };
                 stream.write_json_packet(&msg);
                 ActorMessageStatus::Processed
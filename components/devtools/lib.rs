@@ -31,12 +31,15 @@ use actors::network_event::{EventActor, NetworkEventActor, ResponseStartMsg};
 use actors::performance::PerformanceActor;
 use actors::profiler::ProfilerActor;
 use actors::root::RootActor;
+use actors::storage::StorageActor;
+use actors::stylesheets::StyleSheetsActor;
 use actors::tab::TabActor;
 use actors::thread::ThreadActor;
 use actors::timeline::TimelineActor;
 use actors::worker::WorkerActor;
 use devtools_traits::{ChromeToDevtoolsControlMsg, ConsoleMessage, DevtoolsControlMsg};
 use devtools_traits::{DevtoolScriptControlMsg, DevtoolsPageInfo, LogLevel, NetworkEvent};
+use devtools_traits::{PauseInfo, StoreChange, StoreType};
 use devtools_traits::{ScriptToDevtoolsControlMsg, WorkerId};
 use ipc_channel::ipc::IpcSender;
 use msg::constellation_msg::PipelineId;
@@ -54,7 +57,9 @@ use time::precise_time_ns;
 mod actor;
 /// Corresponds to http://mxr.mozilla.org/mozilla-central/source/toolkit/devtools/server/actors/
 mod actors {
+    pub mod breakpoint;
     pub mod console;
+    pub mod frame;
     pub mod framerate;
     pub mod inspector;
     pub mod memory;
@@ -63,6 +68,9 @@ mod actors {
     pub mod performance;
     pub mod profiler;
     pub mod root;
+    pub mod source;
+    pub mod storage;
+    pub mod stylesheets;
     pub mod tab;
     pub mod thread;
     pub mod timeline;
@@ -210,22 +218,36 @@ fn run_server(sender: Sender<DevtoolsControlMsg>,
         let (pipeline, worker_id) = ids;
 
         //TODO: move all this actor creation into a constructor method on TabActor
-        let (tab, console, inspector, timeline, profiler, performance, thread) = {
+        let (tab, console, inspector, style_sheets, storage, timeline, profiler, performance, thread) = {
             let console = ConsoleActor {
                 name: actors.new_name("console"),
                 script_chan: script_sender.clone(),
                 pipeline: pipeline,
                 streams: RefCell::new(Vec::new()),
             };
+            let DevtoolsPageInfo { title, url } = page_info;
+            let style_sheets = StyleSheetsActor::new(actors.new_name("styleSheets"),
+                                                     url.as_str().to_owned(),
+                                                     pipeline,
+                                                     script_sender.clone());
             let inspector = InspectorActor {
                 name: actors.new_name("inspector"),
                 walker: RefCell::new(None),
                 pageStyle: RefCell::new(None),
                 highlighter: RefCell::new(None),
+                style_sheets: style_sheets.name(),
                 script_chan: script_sender.clone(),
                 pipeline: pipeline,
             };
 
+            let storage = StorageActor::new(actors.new_name("storage"),
+                                            pipeline,
+                                            script_sender.clone());
+
+            let thread = ThreadActor::new(actors.new_name("context"),
+                                          pipeline,
+                                          script_sender.clone());
+
             let timeline = TimelineActor::new(actors.new_name("timeline"),
                                               pipeline,
                                               script_sender);
@@ -233,15 +255,14 @@ fn run_server(sender: Sender<DevtoolsControlMsg>,
             let profiler = ProfilerActor::new(actors.new_name("profiler"));
             let performance = PerformanceActor::new(actors.new_name("performance"));
 
-            let thread = ThreadActor::new(actors.new_name("context"));
-
-            let DevtoolsPageInfo { title, url } = page_info;
             let tab = TabActor {
                 name: actors.new_name("tab"),
                 title: String::from(title),
                 url: url.into_string(),
                 console: console.name(),
                 inspector: inspector.name(),
+                style_sheets: style_sheets.name(),
+                storage: storage.name(),
                 timeline: timeline.name(),
                 profiler: profiler.name(),
                 performance: performance.name(),
@@ -251,7 +272,7 @@ fn run_server(sender: Sender<DevtoolsControlMsg>,
             let root = actors.find_mut::<RootActor>("root");
             root.tabs.push(tab.name.clone());
 
-            (tab, console, inspector, timeline, profiler, performance, thread)
+            (tab, console, inspector, style_sheets, storage, timeline, profiler, performance, thread)
         };
 
         if let Some(id) = worker_id {
@@ -268,6 +289,8 @@ fn run_server(sender: Sender<DevtoolsControlMsg>,
         actors.register(Box::new(tab));
         actors.register(Box::new(console));
         actors.register(Box::new(inspector));
+        actors.register(Box::new(style_sheets));
+        actors.register(Box::new(storage));
         actors.register(Box::new(timeline));
         actors.register(Box::new(profiler));
         actors.register(Box::new(performance));
@@ -310,6 +333,38 @@ fn run_server(sender: Sender<DevtoolsControlMsg>,
         }
     }
 
+    fn handle_debugger_paused(actors: Arc<Mutex<ActorRegistry>>,
+                              id: PipelineId,
+                              info: PauseInfo,
+                              actor_pipelines: &HashMap<PipelineId, String>) {
+        let mut actors = actors.lock().unwrap();
+        {
+            let tab_actor = match actor_pipelines.get(&id) {
+                Some(name) => actors.find::<TabActor>(name),
+                None => return,
+            };
+            let thread_actor = actors.find::<ThreadActor>(&tab_actor.thread);
+            thread_actor.paused(&actors, info);
+        }
+        // The paused packet can refer to sources the client has not seen yet.
+        actors.register_pending_actors();
+    }
+
+    fn handle_store_changed(actors: Arc<Mutex<ActorRegistry>>,
+                            id: PipelineId,
+                            store: StoreType,
+                            host: String,
+                            change: StoreChange,
+                            actor_pipelines: &HashMap<PipelineId, String>) {
+        let actors = actors.lock().unwrap();
+        let tab_actor = match actor_pipelines.get(&id) {
+            Some(name) => actors.find::<TabActor>(name),
+            None => return,
+        };
+        let storage_actor = actors.find::<StorageActor>(&tab_actor.storage);
+        storage_actor.store_changed(store, host, change);
+    }
+
     fn find_console_actor(actors: Arc<Mutex<ActorRegistry>>,
                           id: PipelineId,
                           worker_id: Option<WorkerId>,
@@ -511,6 +566,16 @@ fn run_server(sender: Sender<DevtoolsControlMsg>,
                 handle_console_message(actors.clone(), id, None, console_message,
                                        &actor_pipelines, &actor_workers)
             },
+            DevtoolsControlMsg::FromScript(ScriptToDevtoolsControlMsg::DebuggerPaused(
+                        id,
+                        info)) =>
+                handle_debugger_paused(actors.clone(), id, info, &actor_pipelines),
+            DevtoolsControlMsg::FromScript(ScriptToDevtoolsControlMsg::StoreChanged(
+                        id,
+                        store,
+                        host,
+                        change)) =>
+                handle_store_changed(actors.clone(), id, store, host, change, &actor_pipelines),
             DevtoolsControlMsg::FromChrome(ChromeToDevtoolsControlMsg::NetworkEvent(
                         request_id, network_event)) => {
                 // copy the accepted_connections vector
@@ -0,0 +1,98 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! A devtools server with a single page, whose script thread is played by the test, and a
+//! client speaking the remote debugging protocol to it.
+
+// Not every test uses every helper.
+#![allow(dead_code)]
+
+use devtools;
+use devtools_traits::{DevtoolScriptControlMsg, DevtoolsControlMsg, DevtoolsPageInfo};
+use devtools_traits::ScriptToDevtoolsControlMsg;
+use ipc_channel::ipc::IpcSender;
+use msg::constellation_msg::{PipelineId, PipelineIndex, PipelineNamespaceId};
+use serde_json::{self, Value};
+use servo_url::ServoUrl;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::num::NonZeroU32;
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::Duration;
+
+pub fn pipeline_id() -> PipelineId {
+    PipelineId {
+        namespace_id: PipelineNamespaceId(1),
+        index: PipelineIndex(NonZeroU32::new(1).unwrap()),
+    }
+}
+
+/// Start a devtools server with a single page, whose script thread receives its messages
+/// on `script_chan`. Returns the server and the port it listens on.
+pub fn start_server(script_chan: IpcSender<DevtoolScriptControlMsg>) -> (Sender<DevtoolsControlMsg>, u16) {
+    let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let server = devtools::start_server(port);
+    let page_info = DevtoolsPageInfo {
+        title: "Devtools test".to_owned(),
+        url: ServoUrl::parse("http://example.com/").unwrap(),
+    };
+    let msg = ScriptToDevtoolsControlMsg::NewGlobal((pipeline_id(), None), script_chan, page_info);
+    server.send(DevtoolsControlMsg::FromScript(msg)).unwrap();
+    (server, port)
+}
+
+pub struct Client {
+    stream: TcpStream,
+}
+
+impl Client {
+    /// Connect to the server, waiting for it to start listening.
+    pub fn connect(port: u16) -> Client {
+        let stream = (0..50).filter_map(|_| {
+            TcpStream::connect(("127.0.0.1", port)).map_err(|_| {
+                thread::sleep(Duration::from_millis(100));
+            }).ok()
+        }).next().expect("The devtools server did not start.");
+        stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let mut client = Client { stream: stream };
+        assert_eq!(client.recv()["from"], "root");
+        client
+    }
+
+    /// Attach to the tab of the page, returning the form of the tab and the reply to
+    /// attaching, which names the thread actor.
+    pub fn attach_tab(&mut self) -> (Value, Value) {
+        let tabs = self.request(json!({ "to": "root", "type": "listTabs" }));
+        let tab = tabs["tabs"][0].clone();
+        let attached = self.request(json!({ "to": tab["actor"], "type": "attach" }));
+        assert_eq!(attached["type"], "tabAttached");
+        (tab, attached)
+    }
+
+    pub fn send(&mut self, packet: Value) {
+        let packet = packet.to_string();
+        write!(self.stream, "{}:{}", packet.len(), packet).unwrap();
+    }
+
+    pub fn recv(&mut self) -> Value {
+        let mut length = String::new();
+        loop {
+            let mut byte = [0];
+            self.stream.read_exact(&mut byte).unwrap();
+            if byte[0] == b':' {
+                break;
+            }
+            length.push(byte[0] as char);
+        }
+        let mut packet = vec![0; length.parse().unwrap()];
+        self.stream.read_exact(&mut packet).unwrap();
+        serde_json::from_slice(&packet).unwrap()
+    }
+
+    pub fn request(&mut self, packet: Value) -> Value {
+        self.send(packet);
+        self.recv()
+    }
+}
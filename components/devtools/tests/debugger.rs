@@ -0,0 +1,197 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+extern crate devtools;
+extern crate devtools_traits;
+extern crate ipc_channel;
+extern crate msg;
+#[macro_use] extern crate serde_json;
+extern crate servo_url;
+
+mod common;
+
+use common::{Client, pipeline_id};
+use devtools_traits::{DebuggerValue, DevtoolScriptControlMsg, DevtoolsControlMsg};
+use devtools_traits::{FrameInfo, ObjectPropertiesInfo, PauseInfo, PauseReason, ScopeInfo};
+use devtools_traits::{ScriptToDevtoolsControlMsg, SourceInfo, SourceLocation};
+use devtools_traits::EvaluateJSReply::{NullValue, NumberValue};
+use ipc_channel::ipc;
+use std::sync::mpsc::Sender;
+use std::thread;
+
+fn location(line: u32) -> SourceLocation {
+    SourceLocation { source: 1, line: line, column: 0 }
+}
+
+fn frame(depth: u32, function_name: &str, line: u32) -> FrameInfo {
+    FrameInfo {
+        depth: depth,
+        kind: "call".to_owned(),
+        function_name: Some(function_name.to_owned()),
+        location: location(line),
+    }
+}
+
+/// Start a devtools server with a single page, whose script thread is paused in `inner`,
+/// called by `outer`, which both see an object with id 1 in their `o` variable. Returns
+/// the server and the port it listens on.
+fn start_server() -> (Sender<DevtoolsControlMsg>, u16) {
+    let (script_chan, script_port) = ipc::channel().unwrap();
+    thread::spawn(move || {
+        while let Ok(msg) = script_port.recv() {
+            match msg {
+                DevtoolScriptControlMsg::GetSources(_, reply) => {
+                    let url = "http://example.com/script.js".to_owned();
+                    reply.send(vec![SourceInfo { id: 1, url: url }]).unwrap();
+                },
+                DevtoolScriptControlMsg::GetFrames(_, reply) => {
+                    reply.send(vec![frame(0, "inner", 3), frame(1, "outer", 7)]).unwrap();
+                },
+                DevtoolScriptControlMsg::GetScopes(_, depth, reply) => {
+                    let function_scope = ScopeInfo {
+                        kind: "declarative".to_owned(),
+                        function_name: Some(if depth == 0 { "inner" } else { "outer" }.to_owned()),
+                        bindings: vec![
+                            ("x".to_owned(), DebuggerValue::Primitive(NumberValue(depth as f64 + 1.))),
+                            ("y".to_owned(), DebuggerValue::SpecialNumber("NaN".to_owned())),
+                            ("o".to_owned(), DebuggerValue::Object { class: "Object".to_owned(), id: 1 }),
+                        ],
+                    };
+                    let global_scope = ScopeInfo {
+                        kind: "object".to_owned(),
+                        function_name: None,
+                        bindings: vec![],
+                    };
+                    reply.send(Some(vec![function_scope, global_scope])).unwrap();
+                },
+                DevtoolScriptControlMsg::GetObjectProperties(_, id, reply) => {
+                    let properties = if id == 1 {
+                        Some(ObjectPropertiesInfo {
+                            prototype: DebuggerValue::Primitive(NullValue),
+                            properties: vec![
+                                ("z".to_owned(), DebuggerValue::SpecialNumber("-Infinity".to_owned())),
+                            ],
+                        })
+                    } else {
+                        None
+                    };
+                    reply.send(properties).unwrap();
+                },
+                _ => {},
+            }
+        }
+    });
+    common::start_server(script_chan)
+}
+
+fn pause(server: &Sender<DevtoolsControlMsg>) {
+    let info = PauseInfo {
+        reason: PauseReason::DebuggerStatement,
+        breakpoints: vec![],
+        frame: frame(0, "inner", 3),
+    };
+    let msg = ScriptToDevtoolsControlMsg::DebuggerPaused(pipeline_id(), info);
+    server.send(DevtoolsControlMsg::FromScript(msg)).unwrap();
+}
+
+/// Connect to the server and attach to the thread of its page, returning the name of the
+/// thread actor.
+fn attach(port: u16) -> (Client, String) {
+    let mut client = Client::connect(port);
+    let (_, tab) = client.attach_tab();
+    let thread = tab["threadActor"].as_str().unwrap().to_owned();
+    let attached = client.request(json!({ "to": thread, "type": "attach" }));
+    assert_eq!(attached["why"]["type"], "attached");
+    (client, thread)
+}
+
+#[test]
+fn test_detach_keeps_other_clients_attached() {
+    let (server, port) = start_server();
+    let (mut first, thread) = attach(port);
+    let (mut second, _) = attach(port);
+
+    let detached = first.request(json!({ "to": thread, "type": "detach" }));
+    assert_eq!(detached["type"], "detached");
+
+    pause(&server);
+    let paused = second.recv();
+    assert_eq!(paused["type"], "paused");
+    assert_eq!(paused["from"], thread);
+    assert_eq!(paused["why"]["type"], "debuggerStatement");
+}
+
+#[test]
+fn test_frame_actors_resolve_environments() {
+    let (server, port) = start_server();
+    let (mut client, thread) = attach(port);
+
+    pause(&server);
+    let paused = client.recv();
+    assert_eq!(paused["type"], "paused");
+
+    let frames = client.request(json!({ "to": thread, "type": "frames" }));
+    let frames = frames["frames"].as_array().unwrap().clone();
+    assert_eq!(frames.len(), 2);
+    // The pause and the stack refer to the same actor for the same frame.
+    assert_eq!(frames[0]["actor"], paused["frame"]["actor"]);
+    assert_eq!(frames[1]["callee"]["name"], "outer");
+    assert_eq!(frames[1]["where"]["line"], 7);
+
+    let frame_actor = frames[1]["actor"].clone();
+    let environment = client.request(json!({ "to": frame_actor, "type": "getEnvironment" }));
+    assert_eq!(environment["from"], frame_actor);
+    assert_eq!(environment["type"], "function");
+    assert_eq!(environment["function"]["name"], "outer");
+    assert_eq!(environment["bindings"]["variables"]["x"]["value"], 2.0);
+    assert_eq!(environment["parent"]["type"], "object");
+    assert!(environment["parent"].get("parent").is_none());
+
+    let actor = environment["actor"].clone();
+    let bindings = client.request(json!({ "to": actor, "type": "bindings" }));
+    assert_eq!(bindings["from"], actor);
+    assert_eq!(bindings["bindings"]["variables"]["x"]["value"], 2.0);
+
+    let resumed = client.request(json!({ "to": thread, "type": "resume" }));
+    assert_eq!(resumed["type"], "resumed");
+
+    // The frame actors of the previous pause are gone, the new pause has its own.
+    pause(&server);
+    let paused_again = client.recv();
+    assert_ne!(paused_again["frame"]["actor"], paused["frame"]["actor"]);
+}
+
+#[test]
+fn test_object_actors_resolve_until_resume() {
+    let (server, port) = start_server();
+    let (mut client, thread) = attach(port);
+
+    pause(&server);
+    let paused = client.recv();
+    let frame_actor = paused["frame"]["actor"].clone();
+    let environment = client.request(json!({ "to": frame_actor, "type": "getEnvironment" }));
+    let variables = environment["bindings"]["variables"].clone();
+    assert_eq!(variables["y"]["value"], json!({ "type": "NaN" }));
+    assert_eq!(variables["o"]["value"]["type"], "object");
+    assert_eq!(variables["o"]["value"]["class"], "Object");
+
+    let object = variables["o"]["value"]["actor"].clone();
+    let properties = client.request(json!({ "to": object, "type": "prototypeAndProperties" }));
+    assert_eq!(properties["from"], object);
+    assert_eq!(properties["prototype"], json!({ "type": "null" }));
+    assert_eq!(properties["ownProperties"]["z"]["value"], json!({ "type": "-Infinity" }));
+
+    // The same object gets the same actor within a pause.
+    let outer = client.request(json!({ "to": thread, "type": "frames" }))["frames"][1]["actor"].clone();
+    let environment = client.request(json!({ "to": outer, "type": "getEnvironment" }));
+    assert_eq!(environment["bindings"]["variables"]["o"]["value"]["actor"], object);
+
+    let resumed = client.request(json!({ "to": thread, "type": "resume" }));
+    assert_eq!(resumed["type"], "resumed");
+
+    // The object actor is gone with the pause, so the next reply is for a new one.
+    client.send(json!({ "to": object, "type": "prototypeAndProperties" }));
+    pause(&server);
+    assert_eq!(client.recv()["type"], "paused");
+}
@@ -0,0 +1,165 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+extern crate devtools;
+extern crate devtools_traits;
+extern crate ipc_channel;
+extern crate msg;
+#[macro_use] extern crate serde_json;
+extern crate servo_url;
+
+mod common;
+
+use common::Client;
+use devtools_traits::{DevtoolScriptControlMsg, NodeInfo, StyleRuleId, StyleRuleInfo};
+use devtools_traits::StyleSheetInfo;
+use ipc_channel::ipc;
+use std::thread;
+
+fn root_node() -> NodeInfo {
+    NodeInfo {
+        uniqueId: "root".to_owned(),
+        baseURI: "http://example.com/".to_owned(),
+        parent: "".to_owned(),
+        nodeType: 9,
+        namespaceURI: "".to_owned(),
+        nodeName: "#document".to_owned(),
+        numChildren: 1,
+        name: "".to_owned(),
+        publicId: "".to_owned(),
+        systemId: "".to_owned(),
+        attrs: vec![],
+        isDocumentElement: false,
+        shortValue: "".to_owned(),
+        incompleteValue: false,
+    }
+}
+
+/// Start a devtools server with a single page, with one stylesheet holding a single
+/// `p { color: red; }` rule, which matches every node. Text containing `!!` has parse
+/// errors. Returns the port the server listens on.
+fn start_server() -> u16 {
+    let (script_chan, script_port) = ipc::channel().unwrap();
+    thread::spawn(move || {
+        let mut declarations = "color: red;".to_owned();
+        let mut text = format!("p {{ {} }}", declarations);
+        while let Ok(msg) = script_port.recv() {
+            match msg {
+                DevtoolScriptControlMsg::GetRootNode(_, reply) => {
+                    reply.send(Some(root_node())).unwrap();
+                },
+                DevtoolScriptControlMsg::GetStyleSheets(_, reply) => {
+                    reply.send(vec![StyleSheetInfo {
+                        index: 0,
+                        href: Some("http://example.com/style.css".to_owned()),
+                        title: None,
+                        disabled: false,
+                        rule_count: 1,
+                    }]).unwrap();
+                },
+                DevtoolScriptControlMsg::GetStyleSheetText(_, 0, reply) => {
+                    reply.send(Some(text.clone())).unwrap();
+                },
+                DevtoolScriptControlMsg::UpdateStyleSheet(_, 0, new_text, reply) => {
+                    let valid = !new_text.contains("!!");
+                    if valid {
+                        text = new_text;
+                    }
+                    reply.send(valid).unwrap();
+                },
+                DevtoolScriptControlMsg::GetAppliedRules(_, node, reply) => {
+                    assert_eq!(node, "root");
+                    reply.send(Some(vec![StyleRuleInfo {
+                        id: StyleRuleId::Sheet(0, vec![0]),
+                        selectors: vec!["p".to_owned()],
+                        matched_selectors: vec!["p".to_owned()],
+                        declarations: declarations.clone(),
+                        line: 1,
+                        column: 0,
+                    }])).unwrap();
+                },
+                DevtoolScriptControlMsg::ModifyRule(_, StyleRuleId::Sheet(0, path), modifications, reply) => {
+                    assert_eq!(path, vec![0]);
+                    declarations = modifications.iter().filter_map(|modification| {
+                        let value = modification.value.as_ref()?;
+                        Some(format!("{}: {};", modification.name, value))
+                    }).collect::<Vec<_>>().join(" ");
+                    reply.send(Some(declarations.clone())).unwrap();
+                },
+                DevtoolScriptControlMsg::SetRuleText(_, StyleRuleId::Sheet(0, _), new_text, reply) => {
+                    declarations = new_text;
+                    reply.send(Some(declarations.clone())).unwrap();
+                },
+                _ => {},
+            }
+        }
+    });
+    common::start_server(script_chan).1
+}
+
+#[test]
+fn test_update_style_sheet_text() {
+    let port = start_server();
+    let mut client = Client::connect(port);
+    let (tab, _) = client.attach_tab();
+
+    let style_sheets = client.request(json!({ "to": tab["styleSheetsActor"], "type": "getStyleSheets" }));
+    let sheet = style_sheets["styleSheets"][0].clone();
+    assert_eq!(sheet["href"], "http://example.com/style.css");
+    assert_eq!(sheet["ruleCount"], 1);
+
+    let text = client.request(json!({ "to": sheet["actor"], "type": "getText" }));
+    assert_eq!(text["text"], "p { color: red; }");
+
+    client.send(json!({ "to": sheet["actor"], "type": "update", "text": "p { color: blue; }" }));
+    assert_eq!(client.recv()["type"], "styleApplied");
+    assert_eq!(client.recv()["from"], sheet["actor"]);
+    let text = client.request(json!({ "to": sheet["actor"], "type": "getText" }));
+    assert_eq!(text["text"], "p { color: blue; }");
+
+    // Text with parse errors is rejected, and the stylesheet keeps its rules.
+    let error = client.request(json!({ "to": sheet["actor"], "type": "update", "text": "p { !! }" }));
+    assert_eq!(error["error"], "invalidText");
+    let text = client.request(json!({ "to": sheet["actor"], "type": "getText" }));
+    assert_eq!(text["text"], "p { color: blue; }");
+}
+
+#[test]
+fn test_edit_applied_rule() {
+    let port = start_server();
+    let mut client = Client::connect(port);
+    let (tab, _) = client.attach_tab();
+    let inspector = tab["inspectorActor"].clone();
+
+    let walker = client.request(json!({ "to": inspector, "type": "getWalker" }));
+    let root = walker["walker"]["root"]["actor"].clone();
+    let page_style = client.request(json!({ "to": inspector, "type": "getPageStyle" }));
+    let page_style = page_style["pageStyle"]["actor"].clone();
+
+    let applied = client.request(json!({ "to": page_style, "type": "getApplied", "node": root }));
+    let rule = applied["rules"][0].clone();
+    assert_eq!(rule["cssText"], "color: red;");
+    assert_eq!(rule["selectors"], json!(["p"]));
+    assert_eq!(applied["entries"][0]["matchedSelectors"], json!(["p"]));
+    assert_eq!(applied["sheets"][0]["actor"], rule["parentStyleSheet"]);
+
+    let modified = client.request(json!({
+        "to": rule["actor"],
+        "type": "modifyProperties",
+        "modifications": [{ "type": "set", "name": "color", "value": "green" }],
+    }));
+    assert_eq!(modified["rule"]["cssText"], "color: green;");
+
+    let modified = client.request(json!({
+        "to": rule["actor"],
+        "type": "setRuleText",
+        "newText": "margin: 0;",
+    }));
+    assert_eq!(modified["rule"]["cssText"], "margin: 0;");
+
+    // The rule keeps its actor, and reflects the edits.
+    let applied = client.request(json!({ "to": page_style, "type": "getApplied", "node": root }));
+    assert_eq!(applied["rules"][0]["actor"], rule["actor"]);
+    assert_eq!(applied["rules"][0]["cssText"], "margin: 0;");
+}
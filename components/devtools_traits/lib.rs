@@ -86,6 +86,12 @@ pub enum ScriptToDevtoolsControlMsg {
 
     /// Report a CSS parse error for the given pipeline
     ReportCSSError(PipelineId, CSSError),
+
+    /// The JavaScript debugger paused the scripts of the given pipeline.
+    DebuggerPaused(PipelineId, PauseInfo),
+
+    /// A store of the given origin was modified by the given pipeline.
+    StoreChanged(PipelineId, StoreType, String, StoreChange),
 }
 
 /// Serialized JS return values
@@ -215,6 +221,222 @@ pub enum DevtoolScriptControlMsg {
     RequestAnimationFrame(PipelineId, String),
     /// Direct the given pipeline to reload the current page.
     Reload(PipelineId),
+    /// Start debugging the scripts of the given pipeline.
+    AttachDebugger(PipelineId),
+    /// Stop debugging the scripts of the given pipeline, removing its breakpoints.
+    DetachDebugger(PipelineId),
+    /// Retrieve the scripts loaded by the given pipeline.
+    GetSources(PipelineId, IpcSender<Vec<SourceInfo>>),
+    /// Retrieve the text of the given script of the given pipeline.
+    GetSourceText(PipelineId, u32, IpcSender<Option<String>>),
+    /// Set a breakpoint at the given location, replying with the breakpoint if there is
+    /// code to break on at or after that location.
+    SetBreakpoint(PipelineId, SourceLocation, IpcSender<Option<BreakpointInfo>>),
+    /// Remove the breakpoint with the given id.
+    RemoveBreakpoint(PipelineId, u32),
+    /// Pause before the next statement run by the given pipeline.
+    Interrupt(PipelineId),
+    /// Retrieve the stack of the given paused pipeline, innermost frame first.
+    GetFrames(PipelineId, IpcSender<Vec<FrameInfo>>),
+    /// Retrieve the scopes of the frame at the given depth of the stack of the given paused
+    /// pipeline, innermost first.
+    GetScopes(PipelineId, u32, IpcSender<Option<Vec<ScopeInfo>>>),
+    /// Retrieve the prototype and own properties of the object with the given id in the
+    /// current pause of the given pipeline.
+    GetObjectProperties(PipelineId, u32, IpcSender<Option<ObjectPropertiesInfo>>),
+    /// Resume the given paused pipeline, optionally pausing again after a step.
+    Resume(PipelineId, Option<StepKind>),
+    /// Retrieve the author stylesheets of the document of the given pipeline.
+    GetStyleSheets(PipelineId, IpcSender<Vec<StyleSheetInfo>>),
+    /// Retrieve the serialized rules of the stylesheet with the given index.
+    GetStyleSheetText(PipelineId, u32, IpcSender<Option<String>>),
+    /// Replace the rules of the stylesheet with the given index with the ones parsed from
+    /// the given text, replying whether the text parsed without errors and was applied.
+    UpdateStyleSheet(PipelineId, u32, String, IpcSender<bool>),
+    /// Retrieve the style rules matching the given node, most specific first.
+    GetAppliedRules(PipelineId, String, IpcSender<Option<Vec<StyleRuleInfo>>>),
+    /// Apply a list of property modifications to the given style rule, replying with the
+    /// serialization of its new declarations.
+    ModifyRule(PipelineId, StyleRuleId, Vec<PropertyModification>, IpcSender<Option<String>>),
+    /// Replace the declarations of the given style rule, replying with the serialization
+    /// of its new declarations.
+    SetRuleText(PipelineId, StyleRuleId, String, IpcSender<Option<String>>),
+    /// Retrieve the serialization of the origin of the given pipeline, which names its stores.
+    GetStorageHost(PipelineId, IpcSender<Option<String>>),
+    /// Retrieve the entries of the given store of the origin of the given pipeline.
+    GetStoreEntries(PipelineId, StoreType, IpcSender<Vec<StoreEntry>>),
+    /// Add an entry to the given store, replacing any entry with the same name.
+    SetStoreEntry(PipelineId, StoreType, StoreEntry),
+    /// Remove the entries with the given name from the given store.
+    RemoveStoreEntry(PipelineId, StoreType, String),
+    /// Remove all the entries of the given store.
+    ClearStore(PipelineId, StoreType),
+}
+
+/// The kinds of storage shown by the storage inspector.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum StoreType {
+    Cookies,
+    LocalStorage,
+    SessionStorage,
+    /// The HTTP cache.
+    Cache,
+}
+
+/// An entry of a store. Cache entries are named after their URL and valued with their status.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct StoreEntry {
+    pub name: String,
+    pub value: String,
+    /// The attributes of the entry, if it is a cookie.
+    pub cookie: Option<CookieAttributes>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CookieAttributes {
+    pub domain: String,
+    pub path: String,
+    /// The expiry time in milliseconds since the epoch, or `None` for session cookies.
+    pub expires: Option<u64>,
+    pub http_only: bool,
+    pub secure: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum StoreChange {
+    Added(String),
+    Changed(String),
+    Deleted(String),
+    Cleared,
+}
+
+/// An author stylesheet of a document.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct StyleSheetInfo {
+    /// The index of the stylesheet in the document's list of stylesheets.
+    pub index: u32,
+    pub href: Option<String>,
+    pub title: Option<String>,
+    pub disabled: bool,
+    pub rule_count: u32,
+}
+
+/// Identifies the declarations edited through a style rule actor.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum StyleRuleId {
+    /// The `style` attribute of the node with the given id.
+    Element(String),
+    /// A style rule of a stylesheet, given by the index of the stylesheet and the indices of
+    /// the rule and of the grouping rules containing it.
+    Sheet(u32, Vec<u32>),
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct StyleRuleInfo {
+    pub id: StyleRuleId,
+    pub selectors: Vec<String>,
+    /// The selectors of the rule that match the node the rule was looked up for.
+    pub matched_selectors: Vec<String>,
+    /// The serialization of the declarations of the rule.
+    pub declarations: String,
+    pub line: u32,
+    pub column: u32,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PropertyModification {
+    pub name: String,
+    /// The new value of the property, or `None` to remove it.
+    pub value: Option<String>,
+    pub priority: String,
+}
+
+/// A script known to the JavaScript debugger.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SourceInfo {
+    pub id: u32,
+    pub url: String,
+}
+
+/// A position in a script known to the JavaScript debugger.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SourceLocation {
+    pub source: u32,
+    pub line: u32,
+    pub column: u32,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BreakpointInfo {
+    pub id: u32,
+    pub location: SourceLocation,
+}
+
+/// A frame of a paused stack.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FrameInfo {
+    pub depth: u32,
+    /// The frame type, as in `Debugger.Frame.prototype.type`.
+    pub kind: String,
+    pub function_name: Option<String>,
+    pub location: SourceLocation,
+}
+
+/// A scope of a paused frame.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ScopeInfo {
+    /// The scope type, as in `Debugger.Environment.prototype.type`.
+    pub kind: String,
+    /// The name of the function whose call created this scope, if any.
+    pub function_name: Option<String>,
+    /// The variables of this scope with their values. Empty for the global scope.
+    pub bindings: Vec<(String, DebuggerValue)>,
+}
+
+/// A value seen by the debugger while paused.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum DebuggerValue {
+    Primitive(EvaluateJSReply),
+    /// A number that JSON cannot represent: `NaN`, `Infinity`, `-Infinity` or `-0`.
+    SpecialNumber(String),
+    /// An object, identified by an id that is only valid until the pipeline resumes.
+    Object { class: String, id: u32 },
+}
+
+/// The prototype and own data properties of an object of a paused pipeline.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ObjectPropertiesInfo {
+    pub prototype: DebuggerValue,
+    pub properties: Vec<(String, DebuggerValue)>,
+}
+
+/// The ways of resuming execution from a pause, named after the protocol's resume limits.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StepKind {
+    /// Pause at the next statement of the current frame or its callers.
+    Next,
+    /// Pause at the next statement run, including in called functions.
+    Step,
+    /// Pause when the current frame returns.
+    Finish,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PauseReason {
+    Breakpoint,
+    Interrupted,
+    ResumeLimit,
+    DebuggerStatement,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PauseInfo {
+    pub reason: PauseReason,
+    /// The breakpoints that were hit, if any.
+    pub breakpoints: Vec<u32>,
+    pub frame: FrameInfo,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
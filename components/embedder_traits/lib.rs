@@ -114,6 +114,12 @@ pub enum EmbedderMsg {
     ShowIME(InputMethodType),
     /// Request to hide the IME when the editable element is blurred.
     HideIME,
+    /// The security state of the current page has changed.
+    SecurityStateChanged(SecurityState),
+    /// Get the text contents of the system clipboard, or an empty string if there are none.
+    GetClipboardContents(IpcSender<String>),
+    /// Set the text contents of the system clipboard.
+    SetClipboardContents(String),
     /// Servo has shut down
     Shutdown,
 }
@@ -142,11 +148,29 @@ impl Debug for EmbedderMsg {
             EmbedderMsg::SelectFiles(..) => write!(f, "SelectFiles"),
             EmbedderMsg::ShowIME(..) => write!(f, "ShowIME"),
             EmbedderMsg::HideIME => write!(f, "HideIME"),
+            EmbedderMsg::SecurityStateChanged(..) => write!(f, "SecurityStateChanged"),
+            EmbedderMsg::GetClipboardContents(..) => write!(f, "GetClipboardContents"),
+            EmbedderMsg::SetClipboardContents(..) => write!(f, "SetClipboardContents"),
             EmbedderMsg::Shutdown => write!(f, "Shutdown"),
         }
     }
 }
 
+/// The security state of the document displayed in a browser, as shown by
+/// the embedder's UI.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum SecurityState {
+    /// The document was not loaded over a secure connection.
+    Insecure,
+    /// The document and all of its subresources were loaded securely.
+    Secure,
+    /// The document was loaded securely, but insecure display content
+    /// (images, audio or video) was loaded into it.
+    MixedContentDisplayed,
+    /// The document was loaded securely, and insecure content was blocked.
+    MixedContentBlocked,
+}
+
 /// Filter for file selection;
 /// the `String` content is expected to be extension (e.g, "doc", without the prefixing ".")
 #[derive(Clone, Debug, Deserialize, Serialize)]
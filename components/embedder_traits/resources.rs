@@ -44,11 +44,15 @@ pub enum Resource {
     SSLCertificates,
     BadCertHTML,
     NetErrorHTML,
+    CrashHTML,
+    DebuggerJS,
     UserAgentCSS,
     ServoCSS,
     PresentationalHintsCSS,
     QuirksModeCSS,
     RippyPNG,
+    EnglishHyphenationPatterns,
+    GermanHyphenationPatterns,
 }
 
 pub trait ResourceReaderMethods {
@@ -75,11 +79,15 @@ fn resources_for_tests() -> Box<ResourceReaderMethods + Sync + Send> {
                 Resource::SSLCertificates => "certs",
                 Resource::BadCertHTML => "badcert.html",
                 Resource::NetErrorHTML => "neterror.html",
+                Resource::CrashHTML => "crash.html",
+                Resource::DebuggerJS => "debugger.js",
                 Resource::UserAgentCSS => "user-agent.css",
                 Resource::ServoCSS => "servo.css",
                 Resource::PresentationalHintsCSS => "presentational-hints.css",
                 Resource::QuirksModeCSS => "quirks-mode.css",
                 Resource::RippyPNG => "rippy.png",
+                Resource::EnglishHyphenationPatterns => "hyphenation/hyph-en-us.pat.txt",
+                Resource::GermanHyphenationPatterns => "hyphenation/hyph-de-1996.pat.txt",
             };
             let mut path = env::current_exe().unwrap();
             path = path.canonicalize().unwrap();
@@ -4,7 +4,7 @@
 
 use app_units::Au;
 use euclid::{Point2D, Rect, Size2D};
-use font_context::{FontContext, FontSource};
+use font_context::{FontContext, FontSource, WebFont};
 use font_template::FontTemplateDescriptor;
 use ordered_float::NotNan;
 use platform::font::{FontHandle, FontTable};
@@ -21,10 +21,13 @@ use std::rc::Rc;
 use std::str;
 use std::sync::Arc;
 use std::sync::atomic::{ATOMIC_USIZE_INIT, AtomicUsize, Ordering};
-use style::computed_values::{font_stretch, font_style, font_variant_caps, font_weight};
+use style::computed_values::{font_kerning, font_stretch, font_style, font_variant_caps, font_weight};
+use style::font_face::FontFaceDescriptors;
 use style::properties::style_structs::Font as FontStyleStruct;
 use style::values::computed::font::SingleFontFamily;
+use style::values::specified::font::VariantLigatures;
 use text::Shaper;
+use text::color_glyphs::ColorGlyphTable;
 use text::glyph::{ByteIndex, GlyphData, GlyphId, GlyphStore};
 use text::shaping::ShaperMethods;
 use time;
@@ -37,9 +40,18 @@ macro_rules! ot_tag {
     );
 }
 
+pub const CBDT: u32 = ot_tag!('C', 'B', 'D', 'T');
+pub const COLR: u32 = ot_tag!('C', 'O', 'L', 'R');
+pub const CPAL: u32 = ot_tag!('C', 'P', 'A', 'L');
 pub const GPOS: u32 = ot_tag!('G', 'P', 'O', 'S');
 pub const GSUB: u32 = ot_tag!('G', 'S', 'U', 'B');
 pub const KERN: u32 = ot_tag!('k', 'e', 'r', 'n');
+pub const SBIX: u32 = ot_tag!('s', 'b', 'i', 'x');
+const CALT: u32 = ot_tag!('c', 'a', 'l', 't');
+const CLIG: u32 = ot_tag!('c', 'l', 'i', 'g');
+const DLIG: u32 = ot_tag!('d', 'l', 'i', 'g');
+const HLIG: u32 = ot_tag!('h', 'l', 'i', 'g');
+const LIGA: u32 = ot_tag!('l', 'i', 'g', 'a');
 
 static TEXT_SHAPING_PERFORMANCE_COUNTER: AtomicUsize = ATOMIC_USIZE_INIT;
 
@@ -65,12 +77,20 @@ pub trait FontHandleMethods: Sized {
 
     fn glyph_index(&self, codepoint: char) -> Option<GlyphId>;
     fn glyph_h_advance(&self, GlyphId) -> Option<FractionalPixel>;
+    /// The advance of a glyph in vertical text, synthesized if the font has no vertical metrics.
+    fn glyph_v_advance(&self, GlyphId) -> Option<FractionalPixel>;
     fn glyph_h_kerning(&self, glyph0: GlyphId, glyph1: GlyphId) -> FractionalPixel;
 
     /// Can this font do basic horizontal LTR shaping without Harfbuzz?
     fn can_do_fast_shaping(&self) -> bool;
+    /// Sets the values of the variation axes of the font. Values for axes the font does not have
+    /// are ignored.
+    fn set_variations(&mut self, variations: &[webrender_api::FontVariation]);
     fn metrics(&self) -> FontMetrics;
     fn table_for_tag(&self, FontTableTag) -> Option<FontTable>;
+    /// Whether the font has color glyphs, either bitmaps (`CBDT`, `sbix`) or layered outlines
+    /// (`COLR`).
+    fn has_color_glyphs(&self) -> bool;
 
     /// A unique identifier for the font, allowing comparison.
     fn identifier(&self) -> Atom;
@@ -124,6 +144,10 @@ pub struct FontDescriptor {
     pub template_descriptor: FontTemplateDescriptor,
     pub variant: font_variant_caps::T,
     pub pt_size: Au,
+    /// The OpenType features to enable or disable, as tags and values.
+    pub features: Vec<(u32, u32)>,
+    /// The values of the variation axes of the font.
+    pub variations: Vec<webrender_api::FontVariation>,
 }
 
 impl<'a> From<&'a FontStyleStruct> for FontDescriptor {
@@ -132,10 +156,51 @@ impl<'a> From<&'a FontStyleStruct> for FontDescriptor {
             template_descriptor: FontTemplateDescriptor::from(style),
             variant: style.font_variant_caps,
             pt_size: style.font_size.size(),
+            features: features_from_style(style),
+            variations: style.font_variation_settings.0.iter().map(|variation| {
+                webrender_api::FontVariation {
+                    tag: variation.tag.0,
+                    value: variation.value,
+                }
+            }).collect(),
         }
     }
 }
 
+/// Returns the OpenType features set by `font-kerning`, `font-variant-ligatures` and
+/// `font-feature-settings`, the last ones taking precedence.
+///
+/// https://drafts.csswg.org/css-fonts-3/#feature-precedence
+fn features_from_style(style: &FontStyleStruct) -> Vec<(u32, u32)> {
+    let mut features = vec![];
+    match style.font_kerning {
+        font_kerning::T::Auto => {}
+        font_kerning::T::Normal => features.push((KERN, 1)),
+        font_kerning::T::None => features.push((KERN, 0)),
+    }
+
+    let ligatures = style.font_variant_ligatures;
+    let ligature_features: [(VariantLigatures, VariantLigatures, &[u32]); 4] = [
+        (VariantLigatures::COMMON_LIGATURES, VariantLigatures::NO_COMMON_LIGATURES, &[LIGA, CLIG]),
+        (VariantLigatures::DISCRETIONARY_LIGATURES, VariantLigatures::NO_DISCRETIONARY_LIGATURES, &[DLIG]),
+        (VariantLigatures::HISTORICAL_LIGATURES, VariantLigatures::NO_HISTORICAL_LIGATURES, &[HLIG]),
+        (VariantLigatures::CONTEXTUAL, VariantLigatures::NO_CONTEXTUAL, &[CALT]),
+    ];
+    for &(enabled, disabled, tags) in &ligature_features {
+        let value = if ligatures.intersects(VariantLigatures::NONE | disabled) {
+            0
+        } else if ligatures.contains(enabled) {
+            1
+        } else {
+            continue
+        };
+        features.extend(tags.iter().map(|&tag| (tag, value)));
+    }
+
+    features.extend(style.font_feature_settings.0.iter().map(|feature| (feature.tag.0, feature.value as u32)));
+    features
+}
+
 #[derive(Debug)]
 pub struct Font {
     pub handle: FontHandle,
@@ -145,7 +210,20 @@ pub struct Font {
     shaper: Option<Shaper>,
     shape_cache: RefCell<HashMap<ShapeCacheEntry, Arc<GlyphStore>>>,
     glyph_advance_cache: RefCell<HashMap<u32, FractionalPixel>>,
+    glyph_v_advance_cache: RefCell<HashMap<u32, FractionalPixel>>,
     pub font_key: webrender_api::FontInstanceKey,
+    /// The instance of the font whose glyphs are rotated a quarter turn clockwise, for sideways
+    /// text in vertical writing modes. Created on demand by the font context.
+    pub sideways_font_key: Option<webrender_api::FontInstanceKey>,
+    /// The descriptors of the `@font-face` rule this font comes from, if any.
+    pub face_descriptors: Option<FontFaceDescriptors>,
+    /// Set for a fallback font used while a web font is in its block period: text laid out with
+    /// it is not painted.
+    pub invisible: bool,
+    /// Whether the font has color glyphs, which makes it suitable for emoji presentation.
+    pub has_color_glyphs: bool,
+    /// The layered color glyphs of the font, which are painted by layout as their layers.
+    pub color_glyphs: Option<Arc<ColorGlyphTable>>,
 }
 
 impl Font {
@@ -154,6 +232,15 @@ impl Font {
                actual_pt_size: Au,
                font_key: webrender_api::FontInstanceKey) -> Font {
         let metrics = handle.metrics();
+        let has_color_glyphs = handle.has_color_glyphs();
+        let color_glyphs = if has_color_glyphs {
+            match (handle.table_for_tag(COLR), handle.table_for_tag(CPAL)) {
+                (Some(colr), Some(cpal)) => ColorGlyphTable::new(colr.buffer(), cpal.buffer()).map(Arc::new),
+                _ => None,
+            }
+        } else {
+            None
+        };
 
         Font {
             handle: handle,
@@ -163,7 +250,13 @@ impl Font {
             metrics,
             shape_cache: RefCell::new(HashMap::new()),
             glyph_advance_cache: RefCell::new(HashMap::new()),
+            glyph_v_advance_cache: RefCell::new(HashMap::new()),
             font_key,
+            sideways_font_key: None,
+            face_descriptors: None,
+            invisible: false,
+            has_color_glyphs,
+            color_glyphs,
         }
     }
 
@@ -185,6 +278,10 @@ bitflags! {
         const RTL_FLAG = 0x08;
         #[doc = "Set if word-break is set to keep-all."]
         const KEEP_ALL_FLAG = 0x10;
+        #[doc = "Text is laid out vertically, with upright glyphs."]
+        const VERTICAL_FLAG = 0x20;
+        #[doc = "Text is laid out vertically, with glyphs rotated a quarter turn clockwise."]
+        const SIDEWAYS_FLAG = 0x40;
     }
 }
 
@@ -246,8 +343,11 @@ impl Font {
 
     fn can_do_fast_shaping(&self, text: &str, options: &ShapingOptions) -> bool {
         options.script == Script::Latin &&
-            !options.flags.contains(ShapingFlags::RTL_FLAG) &&
+            !options.flags.intersects(ShapingFlags::RTL_FLAG | ShapingFlags::VERTICAL_FLAG) &&
             self.handle.can_do_fast_shaping() &&
+            self.descriptor.features.is_empty() &&
+            self.descriptor.variations.is_empty() &&
+            self.face_descriptors.as_ref().map_or(true, |descriptors| descriptors.feature_settings.is_empty()) &&
             text.is_ascii()
     }
 
@@ -303,8 +403,11 @@ impl Font {
         self.handle.glyph_index(codepoint)
     }
 
+    /// Returns true if the font has a glyph for `codepoint` and may be used for it, that is the
+    /// character is in the `unicode-range` of its `@font-face` rule.
     pub fn has_glyph_for(&self, codepoint: char) -> bool {
-        self.glyph_index(codepoint).is_some()
+        self.face_descriptors.as_ref().map_or(true, |descriptors| descriptors.covers(codepoint)) &&
+            self.glyph_index(codepoint).is_some()
     }
 
     pub fn glyph_h_kerning(&self, first_glyph: GlyphId, second_glyph: GlyphId)
@@ -320,6 +423,15 @@ impl Font {
             }
         })
     }
+
+    pub fn glyph_v_advance(&self, glyph: GlyphId) -> FractionalPixel {
+        *self.glyph_v_advance_cache.borrow_mut().entry(glyph).or_insert_with(|| {
+            match self.handle.glyph_v_advance(glyph) {
+                Some(advance) => advance,
+                None => (self.metrics.ascent + self.metrics.descent).to_f64_px(),
+            }
+        })
+    }
 }
 
 pub type FontRef = Rc<RefCell<Font>>;
@@ -354,16 +466,69 @@ impl FontGroup {
     /// `codepoint`. If no such font is found, returns the first available font or fallback font
     /// (which will cause a "glyph not found" character to be rendered). If no font at all can be
     /// found, returns None.
+    ///
+    /// If a web font of the group covering `codepoint` is in the block period of its loading, the
+    /// font found instead is an invisible one.
     pub fn find_by_codepoint<S: FontSource>(
         &mut self,
         mut font_context: &mut FontContext<S>,
         codepoint: char
+    ) -> Option<FontRef> {
+        let mut blocked = false;
+        let font = self.find_visible_by_codepoint(&mut font_context, codepoint, &mut blocked);
+        if blocked {
+            return font.map(|font| font_context.invisible_font(&font))
+        }
+        font
+    }
+
+    /// Like `find_by_codepoint`, for a character presented as an emoji: a font with color glyphs
+    /// is preferred, first among the families of the group and then among the fallback fonts.
+    ///
+    /// <https://unicode.org/reports/tr51/#Presentation_Style>
+    pub fn find_emoji_by_codepoint<S: FontSource>(
+        &mut self,
+        mut font_context: &mut FontContext<S>,
+        codepoint: char
+    ) -> Option<FontRef> {
+        let has_color_glyph = |font: &FontRef| {
+            let font = font.borrow();
+            font.has_color_glyphs && font.has_glyph_for(codepoint)
+        };
+
+        let mut blocked = false;
+        for family in &mut self.families {
+            match family.font_for_codepoint(&mut font_context, codepoint) {
+                WebFont::Available(ref font) if has_color_glyph(font) => return Some(font.clone()),
+                WebFont::Blocked => blocked = true,
+                WebFont::Available(_) | WebFont::NoSuchFamily | WebFont::Unavailable => {}
+            }
+        }
+
+        // Text waiting for a web font is not painted anyway.
+        if !blocked {
+            if let Some(font) = self.find_fallback(&mut font_context, Some(codepoint), has_color_glyph) {
+                return Some(font)
+            }
+        }
+
+        self.find_by_codepoint(font_context, codepoint)
+    }
+
+    fn find_visible_by_codepoint<S: FontSource>(
+        &mut self,
+        mut font_context: &mut FontContext<S>,
+        codepoint: char,
+        blocked: &mut bool,
     ) -> Option<FontRef> {
         let has_glyph = |font: &FontRef| font.borrow().has_glyph_for(codepoint);
 
-        let font = self.find(&mut font_context, |font| has_glyph(font));
-        if font.is_some() {
-            return font
+        for family in &mut self.families {
+            match family.font_for_codepoint(&mut font_context, codepoint) {
+                WebFont::Available(font) => return Some(font),
+                WebFont::Blocked => *blocked = true,
+                WebFont::NoSuchFamily | WebFont::Unavailable => {}
+            }
         }
 
         if let Some(ref fallback) = self.last_matching_fallback {
@@ -443,6 +608,13 @@ struct FontGroupFamily {
     family_descriptor: FontFamilyDescriptor,
     loaded: bool,
     font: Option<FontRef>,
+    /// Whether `@font-face` rules may define faces of this family besides `font`.
+    has_web_fonts: bool,
+    /// The web fonts of this family loaded for characters `font` cannot be used for.
+    web_fonts: Vec<FontRef>,
+    /// The characters no font of this family can be used for, and whether they wait for a web
+    /// font in its block period.
+    web_font_misses: HashMap<char, bool>,
 }
 
 impl FontGroupFamily {
@@ -457,6 +629,9 @@ impl FontGroupFamily {
             family_descriptor,
             loaded: false,
             font: None,
+            has_web_fonts: true,
+            web_fonts: vec![],
+            web_font_misses: HashMap::new(),
         }
     }
 
@@ -471,6 +646,48 @@ impl FontGroupFamily {
 
         self.font.clone()
     }
+
+    /// Returns the font within this family which matches the style and has a glyph for
+    /// `codepoint`, looking among the faces of the family covering different characters if it
+    /// comes from `@font-face` rules.
+    fn font_for_codepoint<S: FontSource>(
+        &mut self,
+        font_context: &mut FontContext<S>,
+        codepoint: char,
+    ) -> WebFont {
+        let has_glyph = |font: &FontRef| font.borrow().has_glyph_for(codepoint);
+
+        let font = self.font(font_context);
+        if let Some(font) = font.into_iter().chain(self.web_fonts.iter().cloned()).find(has_glyph) {
+            return WebFont::Available(font)
+        }
+        if !self.has_web_fonts {
+            return WebFont::NoSuchFamily
+        }
+        if let Some(&blocked) = self.web_font_misses.get(&codepoint) {
+            return if blocked { WebFont::Blocked } else { WebFont::Unavailable }
+        }
+
+        let web_font = font_context.web_font(&self.font_descriptor, &self.family_descriptor, codepoint);
+        let blocked = match web_font {
+            WebFont::NoSuchFamily => {
+                self.has_web_fonts = false;
+                return WebFont::NoSuchFamily
+            }
+            WebFont::Available(ref font) if has_glyph(font) => {
+                self.web_fonts.push(font.clone());
+                return WebFont::Available(font.clone())
+            }
+            WebFont::Available(ref font) => {
+                self.web_fonts.push(font.clone());
+                false
+            }
+            WebFont::Blocked => true,
+            WebFont::Unavailable => false,
+        };
+        self.web_font_misses.insert(codepoint, blocked);
+        if blocked { WebFont::Blocked } else { WebFont::Unavailable }
+    }
 }
 
 pub struct RunMetrics {
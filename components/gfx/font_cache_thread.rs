@@ -23,7 +23,8 @@ use std::borrow::ToOwned;
 use std::collections::HashMap;
 use std::ops::Deref;
 use std::sync::{Arc, Mutex};
-use style::font_face::{EffectiveSources, Source};
+use std::time::{Duration, Instant};
+use style::font_face::{EffectiveSources, FontDisplay, FontFaceDescriptors, ScriptFontFaceSource, Source};
 use style::values::computed::font::FamilyName;
 use webrender_api;
 
@@ -36,6 +37,8 @@ pub struct FontTemplates {
 pub struct FontTemplateInfo {
     pub font_template: Arc<FontTemplateData>,
     pub font_key: webrender_api::FontKey,
+    /// The descriptors of the `@font-face` rule the template comes from, if any.
+    pub face_descriptors: Option<FontFaceDescriptors>,
 }
 
 impl FontTemplates {
@@ -48,43 +51,7 @@ impl FontTemplates {
     /// Find a font in this family that matches a given descriptor.
     pub fn find_font_for_style(&mut self, desc: &FontTemplateDescriptor, fctx: &FontContextHandle)
                                -> Option<Arc<FontTemplateData>> {
-        // TODO(Issue #189): optimize lookup for
-        // regular/bold/italic/bolditalic with fixed offsets and a
-        // static decision table for fallback between these values.
-        for template in &mut self.templates {
-            let maybe_template = template.data_for_descriptor(fctx, desc);
-            if maybe_template.is_some() {
-                return maybe_template;
-            }
-        }
-
-        // We didn't find an exact match. Do more expensive fuzzy matching.
-        // TODO(#190): Do a better job.
-        let (mut best_template_data, mut best_distance) = (None, f32::MAX);
-        for template in &mut self.templates {
-            if let Some((template_data, distance)) =
-                    template.data_for_approximate_descriptor(fctx, desc) {
-                if distance < best_distance {
-                    best_template_data = Some(template_data);
-                    best_distance = distance
-                }
-            }
-        }
-        if best_template_data.is_some() {
-            return best_template_data
-        }
-
-        // If a request is made for a font family that exists,
-        // pick the first valid font in the family if we failed
-        // to find an exact match for the descriptor.
-        for template in &mut self.templates {
-            let maybe_template = template.get();
-            if maybe_template.is_some() {
-                return maybe_template;
-            }
-        }
-
-        None
+        find_font_for_style(self.templates.iter_mut().collect(), desc, fctx)
     }
 
     pub fn add_template(&mut self, identifier: Atom, maybe_data: Option<Vec<u8>>) {
@@ -100,13 +67,158 @@ impl FontTemplates {
     }
 }
 
+/// Find the template among `templates` that best matches a given descriptor.
+fn find_font_for_style(mut templates: Vec<&mut FontTemplate>,
+                       desc: &FontTemplateDescriptor,
+                       fctx: &FontContextHandle)
+                       -> Option<Arc<FontTemplateData>> {
+    // TODO(Issue #189): optimize lookup for
+    // regular/bold/italic/bolditalic with fixed offsets and a
+    // static decision table for fallback between these values.
+    for template in &mut templates {
+        let maybe_template = template.data_for_descriptor(fctx, desc);
+        if maybe_template.is_some() {
+            return maybe_template;
+        }
+    }
+
+    // We didn't find an exact match. Do more expensive fuzzy matching.
+    // TODO(#190): Do a better job.
+    let (mut best_template_data, mut best_distance) = (None, f32::MAX);
+    for template in &mut templates {
+        if let Some((template_data, distance)) =
+                template.data_for_approximate_descriptor(fctx, desc) {
+            if distance < best_distance {
+                best_template_data = Some(template_data);
+                best_distance = distance
+            }
+        }
+    }
+    if best_template_data.is_some() {
+        return best_template_data
+    }
+
+    // If a request is made for a font family that exists,
+    // pick the first valid font in the family if we failed
+    // to find an exact match for the descriptor.
+    for template in &mut templates {
+        let maybe_template = template.get();
+        if maybe_template.is_some() {
+            return maybe_template;
+        }
+    }
+
+    None
+}
+
+/// A font face added by an `@font-face` rule, or by script.
+struct WebFontFace {
+    descriptors: FontFaceDescriptors,
+    /// The sources of the face that were not tried yet.
+    sources: EffectiveSources,
+    state: WebFontState,
+    /// Whether the face can be used to render text. Faces added by script are only used while
+    /// they belong to the fonts of their document.
+    enabled: bool,
+    /// Where to send the `WebFontEvent`s of this face.
+    sender: IpcSender<WebFontEvent>,
+}
+
+enum WebFontState {
+    /// Not loaded until some text needs a character of its `unicode-range`.
+    Deferred,
+    /// Loading since the given instant.
+    Loading(Instant),
+    /// Loaded soon enough to be used.
+    Loaded(FontTemplates),
+    /// Failed to load, or loaded after the end of its swap period.
+    Failed,
+}
+
+/// Returns the lengths of the block period and of the swap period of the font display timeline
+/// for the given `font-display`, `None` standing for an infinite swap period.
+///
+/// https://drafts.csswg.org/css-fonts-4/#font-display-timeline
+fn font_display_periods(display: FontDisplay) -> (Duration, Option<Duration>) {
+    match display {
+        FontDisplay::Auto | FontDisplay::Block => (Duration::from_secs(3), None),
+        FontDisplay::Swap => (Duration::from_secs(0), None),
+        FontDisplay::Fallback => (Duration::from_millis(100), Some(Duration::from_secs(3))),
+        FontDisplay::Optional => (Duration::from_millis(100), Some(Duration::from_secs(0))),
+    }
+}
+
+/// Events the font cache thread sends to layout about the web fonts it was given.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum WebFontEvent {
+    /// The face will only be loaded once some text needs a character of its `unicode-range`.
+    Deferred,
+    /// A deferred face started loading.
+    LoadStarted,
+    /// The block period of a loading face ended, so the text waiting for it must be shown.
+    BlockPeriodEnded,
+    /// A face finished loading, and can be used if true.
+    LoadFinished(bool),
+}
+
+/// Identifies a web font face, to load, enable, disable or remove it later.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct WebFontId {
+    family_name: LowercaseString,
+    index: usize,
+}
+
+/// The result of looking up the web font faces of a family for a character.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum WebFontTemplate {
+    /// No `@font-face` rule defines the family.
+    NoSuchFamily,
+    /// No face covering the character can be used, so fallback fonts must be used instead.
+    Unavailable,
+    /// A face covering the character is in the block period of its loading: fallback fonts are
+    /// used to lay out the text, but it must not be painted.
+    Blocked,
+    Available(FontTemplateInfo),
+}
+
 /// Commands that the FontContext sends to the font cache thread.
 #[derive(Debug, Deserialize, Serialize)]
 pub enum Command {
     GetFontTemplate(FontTemplateDescriptor, FontFamilyDescriptor, IpcSender<Reply>),
-    GetFontInstance(webrender_api::FontKey, Au, IpcSender<webrender_api::FontInstanceKey>),
-    AddWebFont(LowercaseString, EffectiveSources, IpcSender<()>),
-    AddDownloadedWebFont(LowercaseString, ServoUrl, Vec<u8>, IpcSender<()>),
+    GetWebFontTemplate(FontTemplateDescriptor, FontFamilyDescriptor, char, IpcSender<Reply>),
+    GetFontInstance(
+        webrender_api::FontKey,
+        Au,
+        Vec<webrender_api::FontVariation>,
+        IpcSender<webrender_api::FontInstanceKey>,
+    ),
+    /// Gets the instance of the same font, size and variations as the given instance whose glyphs
+    /// are rotated for sideways text in vertical writing modes.
+    GetSidewaysFontInstance(webrender_api::FontInstanceKey, IpcSender<webrender_api::FontInstanceKey>),
+    AddWebFont(
+        LowercaseString,
+        EffectiveSources,
+        FontFaceDescriptors,
+        IpcSender<WebFontEvent>,
+        IpcSender<WebFontId>,
+    ),
+    /// Adds a disabled web font face created by script and starts loading it right away.
+    AddScriptWebFont(
+        LowercaseString,
+        ScriptFontFaceSource,
+        FontFaceDescriptors,
+        IpcSender<WebFontEvent>,
+        IpcSender<WebFontId>,
+    ),
+    SetWebFontEnabled(WebFontId, bool),
+    /// Starts loading a web font face if it is deferred.
+    LoadDeferredWebFont(WebFontId),
+    /// Forgets a web font face created by script which is not used anymore.
+    RemoveWebFont(WebFontId),
+    /// Tries the next source of the face at the given index of a web font family.
+    LoadWebFont(LowercaseString, usize),
+    AddDownloadedWebFont(LowercaseString, usize, ServoUrl, Vec<u8>),
+    BlockPeriodEnded(LowercaseString, usize),
     Exit(IpcSender<()>),
     Ping,
 }
@@ -115,6 +227,7 @@ pub enum Command {
 #[derive(Debug, Deserialize, Serialize)]
 pub enum Reply {
     GetFontTemplateReply(Option<FontTemplateInfo>),
+    GetWebFontTemplateReply(WebFontTemplate),
 }
 
 /// The font cache thread itself. It maintains a list of reference counted
@@ -124,12 +237,15 @@ struct FontCache {
     channel_to_self: IpcSender<Command>,
     generic_fonts: HashMap<FontFamilyName, LowercaseString>,
     local_families: HashMap<LowercaseString, FontTemplates>,
-    web_families: HashMap<LowercaseString, FontTemplates>,
+    web_families: HashMap<LowercaseString, Vec<WebFontFace>>,
     font_context: FontContextHandle,
     core_resource_thread: CoreResourceThread,
     webrender_api: webrender_api::RenderApi,
     webrender_fonts: HashMap<Atom, webrender_api::FontKey>,
-    font_instances: HashMap<(webrender_api::FontKey, Au), webrender_api::FontInstanceKey>,
+    /// The font instances, keyed by font, size, variations and whether glyphs are rotated for
+    /// sideways text.
+    font_instances: HashMap<(webrender_api::FontKey, Au, Vec<webrender_api::FontVariation>, bool),
+                            webrender_api::FontInstanceKey>,
 }
 
 fn populate_generic_fonts() -> HashMap<FontFamilyName, LowercaseString> {
@@ -170,33 +286,61 @@ impl FontCache {
                     let maybe_font_template = self.find_font_template(&template_descriptor, &family_descriptor);
                     let _ = result.send(Reply::GetFontTemplateReply(maybe_font_template));
                 }
-                Command::GetFontInstance(font_key, size, result) => {
-                    let webrender_api = &self.webrender_api;
-
-                    let instance_key = *self.font_instances
-                                            .entry((font_key, size))
-                                            .or_insert_with(|| {
-                                                let key = webrender_api.generate_font_instance_key();
-                                                let mut txn = webrender_api::Transaction::new();
-                                                txn.add_font_instance(key,
-                                                                          font_key,
-                                                                          size,
-                                                                          None,
-                                                                          None,
-                                                                          Vec::new());
-                                                webrender_api.update_resources(txn.resource_updates);
-                                                key
-                                            });
-
+                Command::GetWebFontTemplate(template_descriptor, family_descriptor, codepoint, result) => {
+                    let web_font_template =
+                        self.find_web_font_template(&template_descriptor, &family_descriptor, codepoint);
+                    let _ = result.send(Reply::GetWebFontTemplateReply(web_font_template));
+                }
+                Command::GetFontInstance(font_key, size, variations, result) => {
+                    let instance_key = self.font_instance(font_key, size, variations, false);
                     let _ = result.send(instance_key);
                 }
-                Command::AddWebFont(family_name, sources, result) => {
-                    self.handle_add_web_font(family_name, sources, result);
+                Command::GetSidewaysFontInstance(instance_key, result) => {
+                    let upright = self.font_instances.iter()
+                        .find(|&(&(_, _, _, sideways), &key)| !sideways && key == instance_key)
+                        .map(|(&(font_key, size, ref variations, _), _)| (font_key, size, variations.clone()));
+                    let sideways_key = match upright {
+                        Some((font_key, size, variations)) => self.font_instance(font_key, size, variations, true),
+                        None => instance_key,
+                    };
+                    let _ = result.send(sideways_key);
+                }
+                Command::AddWebFont(family_name, sources, descriptors, sender, result) => {
+                    let id = self.handle_add_web_font(family_name, sources, descriptors, sender);
+                    let _ = result.send(id);
+                }
+                Command::AddScriptWebFont(family_name, source, descriptors, sender, result) => {
+                    let id = self.handle_add_script_web_font(family_name, source, descriptors, sender);
+                    let _ = result.send(id);
+                }
+                Command::SetWebFontEnabled(id, enabled) => {
+                    self.web_families.get_mut(&id.family_name).unwrap()[id.index].enabled = enabled;
                 }
-                Command::AddDownloadedWebFont(family_name, url, bytes, result) => {
-                    let templates = &mut self.web_families.get_mut(&family_name).unwrap();
+                Command::LoadDeferredWebFont(id) => {
+                    let deferred = match self.web_families[&id.family_name][id.index].state {
+                        WebFontState::Deferred => true,
+                        _ => false,
+                    };
+                    if deferred {
+                        self.start_loading_deferred_web_font(id.family_name, id.index);
+                    }
+                }
+                Command::RemoveWebFont(id) => {
+                    self.remove_web_font(id);
+                }
+                Command::LoadWebFont(family_name, index) => {
+                    self.load_web_font(family_name, index);
+                }
+                Command::AddDownloadedWebFont(family_name, index, url, bytes) => {
+                    let mut templates = FontTemplates::new();
                     templates.add_template(Atom::from(url.to_string()), Some(bytes));
-                    drop(result.send(()));
+                    self.finish_loading_web_font(&family_name, index, Some(templates));
+                }
+                Command::BlockPeriodEnded(family_name, index) => {
+                    let face = &self.web_families[&family_name][index];
+                    if let WebFontState::Loading(_) = face.state {
+                        let _ = face.sender.send(WebFontEvent::BlockPeriodEnded);
+                    }
                 }
                 Command::Ping => (),
                 Command::Exit(result) => {
@@ -207,28 +351,198 @@ impl FontCache {
         }
     }
 
+    fn font_instance(&mut self,
+                     font_key: webrender_api::FontKey,
+                     size: Au,
+                     variations: Vec<webrender_api::FontVariation>,
+                     sideways: bool)
+                     -> webrender_api::FontInstanceKey {
+        let webrender_api = &self.webrender_api;
+
+        *self.font_instances.entry((font_key, size, variations.clone(), sideways)).or_insert_with(|| {
+            let key = webrender_api.generate_font_instance_key();
+            // FreeType only renders the color bitmaps of emoji fonts when asked to.
+            let mut options = webrender_api::FontInstanceOptions::default();
+            options.flags |= webrender_api::FontInstanceFlags::EMBEDDED_BITMAPS;
+            if sideways {
+                // Glyphs are rotated a quarter turn clockwise.
+                options.flags |= webrender_api::FontInstanceFlags::TRANSPOSE |
+                                 webrender_api::FontInstanceFlags::FLIP_X;
+            }
+            let mut txn = webrender_api::Transaction::new();
+            txn.add_font_instance(key, font_key, size, Some(options), None, variations);
+            webrender_api.update_resources(txn.resource_updates);
+            key
+        })
+    }
+
     fn handle_add_web_font(&mut self,
                            family_name: LowercaseString,
-                           mut sources: EffectiveSources,
-                           sender: IpcSender<()>) {
-        let src = if let Some(src) = sources.next() {
-            src
-        } else {
-            sender.send(()).unwrap();
-            return;
+                           sources: EffectiveSources,
+                           descriptors: FontFaceDescriptors,
+                           sender: IpcSender<WebFontEvent>)
+                           -> WebFontId {
+        // Faces restricted to some characters are only fetched once a character needs them.
+        let eager = descriptors.covers_all();
+        if !eager {
+            let _ = sender.send(WebFontEvent::Deferred);
+        }
+
+        let index = {
+            let faces = self.web_families.entry(family_name.clone()).or_insert_with(|| vec![]);
+            faces.push(WebFontFace {
+                descriptors,
+                sources,
+                state: WebFontState::Deferred,
+                enabled: true,
+                sender,
+            });
+            faces.len() - 1
+        };
+        if eager {
+            self.start_loading_web_font(family_name.clone(), index);
+        }
+
+        WebFontId { family_name, index }
+    }
+
+    /// Adds a face created by script, which is loaded whatever its `unicode-range` as script
+    /// asked for it, but is not used until it is enabled.
+    fn handle_add_script_web_font(&mut self,
+                                  family_name: LowercaseString,
+                                  source: ScriptFontFaceSource,
+                                  descriptors: FontFaceDescriptors,
+                                  sender: IpcSender<WebFontEvent>)
+                                  -> WebFontId {
+        let (sources, data) = match source {
+            ScriptFontFaceSource::Sources(sources) => (sources, None),
+            ScriptFontFaceSource::Data(bytes) => (EffectiveSources::new(&[]), Some(bytes)),
+        };
+        let index = {
+            let faces = self.web_families.entry(family_name.clone()).or_insert_with(|| vec![]);
+            faces.push(WebFontFace {
+                descriptors,
+                sources,
+                state: WebFontState::Deferred,
+                enabled: false,
+                sender,
+            });
+            faces.len() - 1
+        };
+
+        match data {
+            Some(bytes) => {
+                // The data is at hand, so there is no block period to wait for.
+                self.web_families.get_mut(&family_name).unwrap()[index].state =
+                    WebFontState::Loading(Instant::now());
+                let templates = fontsan::process(&bytes).ok().map(|bytes| {
+                    let mut templates = FontTemplates::new();
+                    let identifier = format!("font-face-data:{}:{}", family_name, index);
+                    templates.add_template(Atom::from(identifier), Some(bytes));
+                    templates
+                });
+                self.finish_loading_web_font(&family_name, index, templates);
+            }
+            None => self.start_loading_web_font(family_name.clone(), index),
+        }
+
+        WebFontId { family_name, index }
+    }
+
+    /// Starts loading a deferred web font face, telling its owner about it.
+    fn start_loading_deferred_web_font(&mut self, family_name: LowercaseString, index: usize) {
+        let _ = self.web_families[&family_name][index].sender.send(WebFontEvent::LoadStarted);
+        self.start_loading_web_font(family_name, index);
+    }
+
+    /// Forgets a web font face created by script, deleting the fonts WebRender was given for it.
+    fn remove_web_font(&mut self, id: WebFontId) {
+        let face = &mut self.web_families.get_mut(&id.family_name).unwrap()[id.index];
+        face.enabled = false;
+        face.sources = EffectiveSources::new(&[]);
+        // A fetch in progress will find the face failed, and still report the end of its loading.
+        let templates = match mem::replace(&mut face.state, WebFontState::Failed) {
+            WebFontState::Loaded(templates) => templates,
+            _ => return,
+        };
+
+        let mut txn = webrender_api::Transaction::new();
+        for template in &templates.templates {
+            let font_key = match self.webrender_fonts.remove(template.identifier()) {
+                Some(font_key) => font_key,
+                None => continue,
+            };
+            self.font_instances.retain(|&(instance_font_key, ..), &mut instance_key| {
+                if instance_font_key == font_key {
+                    txn.delete_font_instance(instance_key);
+                    return false
+                }
+                true
+            });
+            txn.delete_font(font_key);
+        }
+        self.webrender_api.update_resources(txn.resource_updates);
+    }
+
+    /// Starts the font display timeline of a web font face and fetches its first source.
+    fn start_loading_web_font(&mut self, family_name: LowercaseString, index: usize) {
+        let block_period = {
+            let face = &mut self.web_families.get_mut(&family_name).unwrap()[index];
+            face.state = WebFontState::Loading(Instant::now());
+            font_display_periods(face.descriptors.display).0
         };
 
-        if !self.web_families.contains_key(&family_name) {
-            let templates = FontTemplates::new();
-            self.web_families.insert(family_name.clone(), templates);
+        if block_period > Duration::from_secs(0) {
+            let channel_to_self = self.channel_to_self.clone();
+            let family_name = family_name.clone();
+            thread::Builder::new().name("WebFontBlockPeriod".to_owned()).spawn(move || {
+                thread::sleep(block_period);
+                let _ = channel_to_self.send(Command::BlockPeriodEnded(family_name, index));
+            }).expect("Thread spawning failed");
         }
 
+        self.load_web_font(family_name, index);
+    }
+
+    /// Ends the loading of a web font face, with the templates of its source if one could be
+    /// loaded. The face is only used if its swap period has not ended yet.
+    fn finish_loading_web_font(&mut self,
+                               family_name: &LowercaseString,
+                               index: usize,
+                               templates: Option<FontTemplates>) {
+        let face = &mut self.web_families.get_mut(family_name).unwrap()[index];
+        let in_time = match face.state {
+            WebFontState::Loading(start) => {
+                let (block_period, swap_period) = font_display_periods(face.descriptors.display);
+                swap_period.map_or(true, |swap_period| start.elapsed() <= block_period + swap_period)
+            }
+            _ => false,
+        };
+        face.state = match templates {
+            Some(templates) if in_time => WebFontState::Loaded(templates),
+            _ => WebFontState::Failed,
+        };
+        let loaded = match face.state {
+            WebFontState::Loaded(_) => true,
+            _ => false,
+        };
+        let _ = face.sender.send(WebFontEvent::LoadFinished(loaded));
+    }
+
+    /// Tries the next source of a web font face.
+    fn load_web_font(&mut self, family_name: LowercaseString, index: usize) {
+        let src = self.web_families.get_mut(&family_name).unwrap()[index].sources.next();
+        let src = match src {
+            Some(src) => src,
+            None => return self.finish_loading_web_font(&family_name, index, None),
+        };
+
         match src {
             Source::Url(url_source) => {
                 // https://drafts.csswg.org/css-fonts/#font-fetching-requirements
                 let url = match url_source.url.url() {
                     Some(url) => url.clone(),
-                    None => return,
+                    None => return self.load_web_font(family_name, index),
                 };
 
                 let request = RequestInit {
@@ -260,7 +574,7 @@ impl FontCache {
                         FetchResponseMsg::ProcessResponseEOF(response) => {
                             trace!("@font-face {} EOF={:?}", family_name, response);
                             if response.is_err() || !*response_valid.lock().unwrap() {
-                                let msg = Command::AddWebFont(family_name.clone(), sources.clone(), sender.clone());
+                                let msg = Command::LoadWebFont(family_name.clone(), index);
                                 channel_to_self.send(msg).unwrap();
                                 return;
                             }
@@ -272,16 +586,16 @@ impl FontCache {
                                     // FIXME(servo/fontsan#1): get an error message
                                     debug!("Sanitiser rejected web font: \
                                             family={} url={:?}", family_name, url);
-                                    let msg = Command::AddWebFont(family_name.clone(), sources.clone(), sender.clone());
+                                    let msg = Command::LoadWebFont(family_name.clone(), index);
                                     channel_to_self.send(msg).unwrap();
                                     return;
                                 },
                             };
                             let command =
                                 Command::AddDownloadedWebFont(family_name.clone(),
+                                                              index,
                                                               url.clone(),
-                                                              bytes,
-                                                              sender.clone());
+                                                              bytes);
                             channel_to_self.send(command).unwrap();
                         }
                     }
@@ -289,16 +603,16 @@ impl FontCache {
             }
             Source::Local(ref font) => {
                 let font_face_name = LowercaseString::new(&font.name);
-                let templates = &mut self.web_families.get_mut(&family_name).unwrap();
+                let mut templates = FontTemplates::new();
                 let mut found = false;
                 for_each_variation(&font_face_name, |path| {
                     found = true;
                     templates.add_template(Atom::from(&*path), None);
                 });
                 if found {
-                    sender.send(()).unwrap();
+                    self.finish_loading_web_font(&family_name, index, Some(templates));
                 } else {
-                    let msg = Command::AddWebFont(family_name, sources, sender);
+                    let msg = Command::LoadWebFont(family_name, index);
                     self.channel_to_self.send(msg).unwrap();
                 }
             }
@@ -352,22 +666,92 @@ impl FontCache {
         }
     }
 
+    /// Finds the font in the loaded faces of a web font family that matches the style, only
+    /// considering the faces covering `codepoint` if one is given.
     fn find_font_in_web_family(
         &mut self,
         template_descriptor: &FontTemplateDescriptor,
         family_name: &FontFamilyName,
-    ) -> Option<Arc<FontTemplateData>> {
+        codepoint: Option<char>,
+    ) -> Option<(Arc<FontTemplateData>, FontFaceDescriptors)> {
         let family_name = LowercaseString::from(family_name);
+        let faces = match self.web_families.get_mut(&family_name) {
+            Some(faces) => faces,
+            None => return None,
+        };
+
+        let template_data = {
+            let templates = faces.iter_mut()
+                .filter(|face| face.enabled)
+                .filter(|face| codepoint.map_or(true, |codepoint| face.descriptors.covers(codepoint)))
+                .filter_map(|face| match face.state {
+                    WebFontState::Loaded(ref mut templates) => Some(templates.templates.iter_mut()),
+                    _ => None,
+                })
+                .flat_map(|templates| templates)
+                .collect();
+            find_font_for_style(templates, template_descriptor, &self.font_context)
+        };
+
+        template_data.and_then(|template_data| {
+            faces.iter().find(|face| match face.state {
+                WebFontState::Loaded(ref templates) => {
+                    templates.templates.iter().any(|template| *template.identifier() == template_data.identifier)
+                }
+                _ => false,
+            }).map(|face| (template_data.clone(), face.descriptors.clone()))
+        })
+    }
+
+    /// Looks up the faces of a web font family for `codepoint`, starting to load the deferred
+    /// faces covering it.
+    fn find_web_font_template(
+        &mut self,
+        template_descriptor: &FontTemplateDescriptor,
+        family_descriptor: &FontFamilyDescriptor,
+        codepoint: char,
+    ) -> WebFontTemplate {
+        let family_name = LowercaseString::from(&family_descriptor.name);
+        let deferred_faces: Vec<usize> = match (&family_descriptor.scope, self.web_families.get(&family_name)) {
+            (&FontSearchScope::Any, Some(faces)) => {
+                faces.iter().enumerate().filter(|&(_, face)| {
+                    let deferred = match face.state {
+                        WebFontState::Deferred => true,
+                        _ => false,
+                    };
+                    deferred && face.descriptors.covers(codepoint)
+                }).map(|(index, _)| index).collect()
+            }
+            _ => return WebFontTemplate::NoSuchFamily,
+        };
+        for index in deferred_faces {
+            self.start_loading_deferred_web_font(family_name.clone(), index);
+        }
+
+        let found = self.find_font_in_web_family(template_descriptor, &family_descriptor.name, Some(codepoint));
+        if let Some((template, descriptors)) = found {
+            return WebFontTemplate::Available(self.get_font_template_info(template, Some(descriptors)))
+        }
 
-        if self.web_families.contains_key(&family_name) {
-            let templates = self.web_families.get_mut(&family_name).unwrap();
-            templates.find_font_for_style(template_descriptor, &self.font_context)
+        let blocked = self.web_families[&family_name].iter().any(|face| {
+            let in_block_period = match face.state {
+                WebFontState::Loading(start) => start.elapsed() < font_display_periods(face.descriptors.display).0,
+                _ => false,
+            };
+            face.enabled && in_block_period && face.descriptors.covers(codepoint)
+        });
+        if blocked {
+            WebFontTemplate::Blocked
         } else {
-            None
+            WebFontTemplate::Unavailable
         }
     }
 
-    fn get_font_template_info(&mut self, template: Arc<FontTemplateData>) -> FontTemplateInfo {
+    fn get_font_template_info(
+        &mut self,
+        template: Arc<FontTemplateData>,
+        face_descriptors: Option<FontFaceDescriptors>,
+    ) -> FontTemplateInfo {
         let webrender_api = &self.webrender_api;
         let webrender_fonts = &mut self.webrender_fonts;
 
@@ -386,6 +770,7 @@ impl FontCache {
         FontTemplateInfo {
             font_template: template,
             font_key: font_key,
+            face_descriptors,
         }
     }
 
@@ -396,14 +781,19 @@ impl FontCache {
     ) -> Option<FontTemplateInfo> {
         match family_descriptor.scope {
             FontSearchScope::Any => {
-                self.find_font_in_web_family(&template_descriptor, &family_descriptor.name)
-                    .or_else(|| self.find_font_in_local_family(&template_descriptor, &family_descriptor.name))
+                self.find_font_in_web_family(&template_descriptor, &family_descriptor.name, None)
+                    .map(|(template, descriptors)| (template, Some(descriptors)))
+                    .or_else(|| {
+                        self.find_font_in_local_family(&template_descriptor, &family_descriptor.name)
+                            .map(|template| (template, None))
+                    })
             }
 
             FontSearchScope::Local => {
                 self.find_font_in_local_family(&template_descriptor, &family_descriptor.name)
+                    .map(|template| (template, None))
             }
-        }.map(|t| self.get_font_template_info(t))
+        }.map(|(template, descriptors)| self.get_font_template_info(template, descriptors))
     }
 }
 
@@ -446,8 +836,48 @@ impl FontCacheThread {
         }
     }
 
-    pub fn add_web_font(&self, family: FamilyName, sources: EffectiveSources, sender: IpcSender<()>) {
-        self.chan.send(Command::AddWebFont(LowercaseString::new(&family.name), sources, sender)).unwrap();
+    pub fn add_web_font(&self,
+                        family: FamilyName,
+                        sources: EffectiveSources,
+                        descriptors: FontFaceDescriptors,
+                        sender: IpcSender<WebFontEvent>)
+                        -> WebFontId {
+        let family_name = LowercaseString::new(&family.name);
+        let (response_chan, response_port) =
+            ipc::channel().expect("failed to create IPC channel");
+        self.chan.send(Command::AddWebFont(family_name, sources, descriptors, sender, response_chan))
+            .expect("failed to send message to font cache thread");
+        response_port.recv().expect("Couldn't receive FontCacheThread reply")
+    }
+
+    /// Adds a font face created by script and starts loading it. The face is not used to render
+    /// text until it is enabled with `set_web_font_enabled`.
+    pub fn add_script_web_font(&self,
+                               family: FamilyName,
+                               source: ScriptFontFaceSource,
+                               descriptors: FontFaceDescriptors,
+                               sender: IpcSender<WebFontEvent>)
+                               -> WebFontId {
+        let family_name = LowercaseString::new(&family.name);
+        let (response_chan, response_port) =
+            ipc::channel().expect("failed to create IPC channel");
+        self.chan.send(Command::AddScriptWebFont(family_name, source, descriptors, sender, response_chan))
+            .expect("failed to send message to font cache thread");
+        response_port.recv().expect("Couldn't receive FontCacheThread reply")
+    }
+
+    pub fn set_web_font_enabled(&self, id: WebFontId, enabled: bool) {
+        self.chan.send(Command::SetWebFontEnabled(id, enabled)).unwrap();
+    }
+
+    /// Starts loading a web font face which is only loaded once some text needs it.
+    pub fn load_deferred_web_font(&self, id: WebFontId) {
+        self.chan.send(Command::LoadDeferredWebFont(id)).unwrap();
+    }
+
+    /// Forgets a web font face created by script, which must not be used anymore.
+    pub fn remove_web_font(&self, id: WebFontId) {
+        self.chan.send(Command::RemoveWebFont(id)).unwrap();
     }
 
     pub fn exit(&self) {
@@ -458,10 +888,15 @@ impl FontCacheThread {
 }
 
 impl FontSource for FontCacheThread {
-    fn get_font_instance(&mut self, key: webrender_api::FontKey, size: Au) -> webrender_api::FontInstanceKey {
+    fn get_font_instance(
+        &mut self,
+        key: webrender_api::FontKey,
+        size: Au,
+        variations: Vec<webrender_api::FontVariation>,
+    ) -> webrender_api::FontInstanceKey {
         let (response_chan, response_port) =
             ipc::channel().expect("failed to create IPC channel");
-        self.chan.send(Command::GetFontInstance(key, size, response_chan))
+        self.chan.send(Command::GetFontInstance(key, size, variations, response_chan))
             .expect("failed to send message to font cache thread");
 
         let instance_key = response_port.recv();
@@ -473,6 +908,24 @@ impl FontSource for FontCacheThread {
         instance_key.unwrap()
     }
 
+    fn get_sideways_font_instance(
+        &mut self,
+        instance_key: webrender_api::FontInstanceKey,
+    ) -> webrender_api::FontInstanceKey {
+        let (response_chan, response_port) =
+            ipc::channel().expect("failed to create IPC channel");
+        self.chan.send(Command::GetSidewaysFontInstance(instance_key, response_chan))
+            .expect("failed to send message to font cache thread");
+
+        let sideways_key = response_port.recv();
+        if sideways_key.is_err() {
+            let font_thread_has_closed = self.chan.send(Command::Ping).is_err();
+            assert!(font_thread_has_closed, "Failed to receive a response from live font cache");
+            panic!("Font cache thread has already exited.");
+        }
+        sideways_key.unwrap()
+    }
+
     fn font_template(
         &mut self,
         template_descriptor: FontTemplateDescriptor,
@@ -495,6 +948,34 @@ impl FontSource for FontCacheThread {
             Reply::GetFontTemplateReply(data) => {
                 data
             }
+            Reply::GetWebFontTemplateReply(_) => unreachable!(),
+        }
+    }
+
+    fn web_font_template(
+        &mut self,
+        template_descriptor: FontTemplateDescriptor,
+        family_descriptor: FontFamilyDescriptor,
+        codepoint: char,
+    ) -> WebFontTemplate {
+        let (response_chan, response_port) =
+            ipc::channel().expect("failed to create IPC channel");
+        self.chan.send(Command::GetWebFontTemplate(template_descriptor, family_descriptor, codepoint, response_chan))
+            .expect("failed to send message to font cache thread");
+
+        let reply = response_port.recv();
+
+        if reply.is_err() {
+            let font_thread_has_closed = self.chan.send(Command::Ping).is_err();
+            assert!(font_thread_has_closed, "Failed to receive a response from live font cache");
+            panic!("Font cache thread has already exited.");
+        }
+
+        match reply.unwrap() {
+            Reply::GetWebFontTemplateReply(web_font_template) => {
+                web_font_template
+            }
+            Reply::GetFontTemplateReply(_) => unreachable!(),
         }
     }
 }
@@ -5,12 +5,13 @@
 use app_units::Au;
 use fnv::FnvHasher;
 use font::{Font, FontDescriptor, FontFamilyDescriptor, FontGroup, FontHandleMethods, FontRef};
-use font_cache_thread::FontTemplateInfo;
+use font_cache_thread::{FontTemplateInfo, WebFontTemplate};
 use font_template::FontTemplateDescriptor;
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
 use platform::font::FontHandle;
 pub use platform::font_context::FontContextHandle;
 use servo_arc::Arc;
+use servo_atoms::Atom;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::default::Default;
@@ -28,13 +29,45 @@ static SMALL_CAPS_SCALE_FACTOR: f32 = 0.8;      // Matches FireFox (see gfxFont.
 static FONT_CACHE_EPOCH: AtomicUsize = ATOMIC_USIZE_INIT;
 
 pub trait FontSource {
-    fn get_font_instance(&mut self, key: webrender_api::FontKey, size: Au) -> webrender_api::FontInstanceKey;
+    fn get_font_instance(
+        &mut self,
+        key: webrender_api::FontKey,
+        size: Au,
+        variations: Vec<webrender_api::FontVariation>,
+    ) -> webrender_api::FontInstanceKey;
+
+    /// Returns the instance of the same font as `instance` whose glyphs are rotated for sideways
+    /// text in vertical writing modes.
+    fn get_sideways_font_instance(
+        &mut self,
+        instance: webrender_api::FontInstanceKey,
+    ) -> webrender_api::FontInstanceKey;
 
     fn font_template(
         &mut self,
         template_descriptor: FontTemplateDescriptor,
         family_descriptor: FontFamilyDescriptor,
     ) -> Option<FontTemplateInfo>;
+
+    /// Looks up the faces defined by `@font-face` rules for a family which cover `codepoint`.
+    fn web_font_template(
+        &mut self,
+        template_descriptor: FontTemplateDescriptor,
+        family_descriptor: FontFamilyDescriptor,
+        codepoint: char,
+    ) -> WebFontTemplate;
+}
+
+/// A web font for a character, see `FontContext::web_font`.
+#[derive(Debug)]
+pub enum WebFont {
+    /// No `@font-face` rule defines the family.
+    NoSuchFamily,
+    /// No face covering the character can be used.
+    Unavailable,
+    /// A face covering the character is in the block period of its loading.
+    Blocked,
+    Available(FontRef),
 }
 
 /// The FontContext represents the per-thread/thread state necessary for
@@ -51,6 +84,8 @@ pub struct FontContext<S: FontSource> {
     // See bug https://github.com/servo/servo/issues/3300
     font_cache: HashMap<FontCacheKey, Option<FontRef>>,
     font_template_cache: HashMap<FontTemplateCacheKey, Option<FontTemplateInfo>>,
+    web_font_cache: HashMap<(Atom, FontDescriptor), FontRef>,
+    invisible_font_cache: HashMap<(Atom, FontDescriptor), FontRef>,
 
     font_group_cache:
         HashMap<FontGroupCacheKey, Rc<RefCell<FontGroup>>, BuildHasherDefault<FnvHasher>>,
@@ -66,6 +101,8 @@ impl<S: FontSource> FontContext<S> {
             font_source,
             font_cache: HashMap::new(),
             font_template_cache: HashMap::new(),
+            web_font_cache: HashMap::new(),
+            invisible_font_cache: HashMap::new(),
             font_group_cache: HashMap::with_hasher(Default::default()),
             epoch: 0,
         }
@@ -79,6 +116,8 @@ impl<S: FontSource> FontContext<S> {
 
         self.font_cache.clear();
         self.font_template_cache.clear();
+        self.web_font_cache.clear();
+        self.invisible_font_cache.clear();
         self.font_group_cache.clear();
         self.epoch = current_epoch
     }
@@ -132,6 +171,88 @@ impl<S: FontSource> FontContext<S> {
         })
     }
 
+    /// Returns the face of a web font family to use for `codepoint`, which gets loaded if it is
+    /// restricted to some characters and was not needed before.
+    pub fn web_font(
+        &mut self,
+        font_descriptor: &FontDescriptor,
+        family_descriptor: &FontFamilyDescriptor,
+        codepoint: char,
+    ) -> WebFont {
+        let template_info = match self.font_source.web_font_template(
+            font_descriptor.template_descriptor.clone(),
+            family_descriptor.clone(),
+            codepoint,
+        ) {
+            WebFontTemplate::NoSuchFamily => return WebFont::NoSuchFamily,
+            WebFontTemplate::Unavailable => return WebFont::Unavailable,
+            WebFontTemplate::Blocked => return WebFont::Blocked,
+            WebFontTemplate::Available(template_info) => template_info,
+        };
+
+        let cache_key = (template_info.font_template.identifier.clone(), font_descriptor.clone());
+        if let Some(font) = self.web_font_cache.get(&cache_key) {
+            return WebFont::Available(font.clone())
+        }
+
+        match self.create_font(template_info, font_descriptor.to_owned()) {
+            Ok(font) => {
+                let font = Rc::new(RefCell::new(font));
+                self.web_font_cache.insert(cache_key, font.clone());
+                WebFont::Available(font)
+            }
+            Err(()) => WebFont::Unavailable,
+        }
+    }
+
+    /// Makes sure `font` knows the instance rendering its glyphs rotated for sideways text, which
+    /// is only created once vertical text needs it.
+    pub fn ensure_sideways_font_key(&mut self, font: &FontRef) {
+        let instance = {
+            let font = font.borrow();
+            if font.sideways_font_key.is_some() {
+                return
+            }
+            font.font_key
+        };
+        font.borrow_mut().sideways_font_key = Some(self.font_source.get_sideways_font_instance(instance));
+    }
+
+    /// Returns a copy of `font` whose text is not painted, used while the web font the text
+    /// should be displayed with is in its block period.
+    pub fn invisible_font(&mut self, font: &FontRef) -> FontRef {
+        let cache_key = (font.borrow().identifier(), font.borrow().descriptor.clone());
+        if let Some(invisible_font) = self.invisible_font_cache.get(&cache_key) {
+            return invisible_font.clone()
+        }
+
+        let invisible_font = {
+            let visible_font = font.borrow();
+            let handle = FontHandle::new_from_template(
+                &self.platform_handle,
+                visible_font.handle.template(),
+                Some(visible_font.actual_pt_size)
+            );
+            match handle {
+                Ok(mut handle) => {
+                    handle.set_variations(&visible_font.descriptor.variations);
+                    let mut invisible_font = Font::new(
+                        handle,
+                        visible_font.descriptor.clone(),
+                        visible_font.actual_pt_size,
+                        visible_font.font_key,
+                    );
+                    invisible_font.face_descriptors = visible_font.face_descriptors.clone();
+                    invisible_font.invisible = true;
+                    Rc::new(RefCell::new(invisible_font))
+                }
+                Err(()) => return font.clone(),
+            }
+        };
+        self.invisible_font_cache.insert(cache_key, invisible_font.clone());
+        invisible_font
+    }
+
     fn font_template(
         &mut self,
         template_descriptor: &FontTemplateDescriptor,
@@ -174,14 +295,18 @@ impl<S: FontSource> FontContext<S> {
             FontVariantCaps::Normal => descriptor.pt_size,
         };
 
-        let handle = FontHandle::new_from_template(
+        let mut handle = FontHandle::new_from_template(
             &self.platform_handle,
             info.font_template,
             Some(actual_pt_size)
         )?;
+        handle.set_variations(&descriptor.variations);
 
-        let font_instance_key = self.font_source.get_font_instance(info.font_key, actual_pt_size);
-        Ok(Font::new(handle, descriptor.to_owned(), actual_pt_size, font_instance_key))
+        let font_instance_key =
+            self.font_source.get_font_instance(info.font_key, actual_pt_size, descriptor.variations.clone());
+        let mut font = Font::new(handle, descriptor.to_owned(), actual_pt_size, font_instance_key);
+        font.face_descriptors = info.face_descriptors;
+        Ok(font)
     }
 }
 
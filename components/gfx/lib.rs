@@ -18,6 +18,7 @@ extern crate bitflags;
 #[cfg(target_os = "windows")] extern crate dwrote;
 #[cfg(target_os = "windows")] extern crate truetype;
 
+extern crate embedder_traits;
 extern crate euclid;
 extern crate fnv;
 
@@ -525,6 +525,8 @@ pub fn fallback_font_families(codepoint: Option<char>) -> Vec<&'static str> {
     }
 
     families.push("Droid Sans Fallback");
+    // Color emoji, which text fonts may also have monochrome glyphs for.
+    families.push("Noto Color Emoji");
     families
 }
 
@@ -4,9 +4,9 @@
 
 use app_units::Au;
 use font::{FontHandleMethods, FontMetrics, FontTableMethods};
-use font::{FontTableTag, FractionalPixel, GPOS, GSUB, KERN};
+use font::{CBDT, COLR, FontTableTag, FractionalPixel, GPOS, GSUB, KERN, SBIX};
 use freetype::freetype::{FT_Done_Face, FT_New_Face, FT_New_Memory_Face};
-use freetype::freetype::{FT_F26Dot6, FT_Face, FT_FaceRec};
+use freetype::freetype::{FT_Error, FT_F26Dot6, FT_Face, FT_FaceRec, FT_Fixed, FT_String};
 use freetype::freetype::{FT_Get_Char_Index, FT_Get_Postscript_Name};
 use freetype::freetype::{FT_Get_Kerning, FT_Get_Sfnt_Table, FT_Load_Sfnt_Table};
 use freetype::freetype::{FT_GlyphSlot, FT_Library, FT_Long, FT_ULong};
@@ -19,32 +19,69 @@ use freetype::tt_os2::TT_OS2;
 use platform::font_context::FontContextHandle;
 use platform::font_template::FontTemplateData;
 use servo_atoms::Atom;
-use std::{mem, ptr};
+use std::{mem, ptr, slice};
 use std::ffi::CString;
-use std::os::raw::{c_char, c_long};
+use std::os::raw::{c_char, c_int, c_long, c_void};
 use std::sync::Arc;
 use style::computed_values::font_stretch::T as FontStretch;
 use style::computed_values::font_weight::T as FontWeight;
 use style::values::computed::font::FontStyle;
 use super::c_str_to_string;
 use text::glyph::GlyphId;
-use text::util::fixed_to_float;
+use text::util::{fixed_to_float, float_to_fixed};
+use webrender_api;
 
 // This constant is not present in the freetype
 // bindings due to bindgen not handling the way
 // the macro is defined.
 const FT_LOAD_TARGET_LIGHT: FT_Int32 = 1 << 16;
 
+// Neither are these ones.
+const FT_LOAD_COLOR: FT_Int32 = 1 << 20;
+const FT_FACE_FLAG_SCALABLE: c_long = 1 << 0;
+const FT_FACE_FLAG_FIXED_SIZES: c_long = 1 << 1;
+
 // Default to slight hinting, which is what most
 // Linux distros use by default, and is a better
-// default than no hinting.
+// default than no hinting. Color bitmaps are loaded
+// so that the metrics of the glyphs of color emoji
+// fonts are those of their strike.
 // TODO(gw): Make this configurable.
-const GLYPH_LOAD_FLAGS: FT_Int32 = FT_LOAD_TARGET_LIGHT;
+const GLYPH_LOAD_FLAGS: FT_Int32 = FT_LOAD_TARGET_LIGHT | FT_LOAD_COLOR;
 
 fn fixed_to_float_ft(f: i32) -> f64 {
     fixed_to_float(6, f)
 }
 
+// The multiple masters API of FreeType, used for variable fonts, is not present in the freetype
+// bindings.
+#[allow(non_camel_case_types)]
+#[repr(C)]
+struct FT_Var_Axis {
+    name: *mut FT_String,
+    minimum: FT_Fixed,
+    def: FT_Fixed,
+    maximum: FT_Fixed,
+    tag: FT_ULong,
+    strid: FT_UInt,
+}
+
+#[allow(non_camel_case_types)]
+#[repr(C)]
+struct FT_MM_Var {
+    num_axis: FT_UInt,
+    num_designs: FT_UInt,
+    num_namedstyles: FT_UInt,
+    axis: *mut FT_Var_Axis,
+    namedstyle: *mut c_void,
+}
+
+extern "C" {
+    fn FT_Get_MM_Var(face: FT_Face, amaster: *mut *mut FT_MM_Var) -> FT_Error;
+    fn FT_Set_Var_Design_Coordinates(face: FT_Face, num_coords: FT_UInt, coords: *mut FT_Fixed) -> FT_Error;
+    fn FT_Select_Size(face: FT_Face, strike_index: c_int) -> FT_Error;
+}
+
 #[derive(Debug)]
 pub struct FontTable {
     buffer: Vec<u8>,
@@ -75,6 +112,9 @@ pub struct FontHandle {
     face: FT_Face,
     handle: FontContextHandle,
     can_do_fast_shaping: bool,
+    /// The scale from the selected strike of a face which only has bitmap glyphs to the requested
+    /// size, 1 for scalable faces.
+    bitmap_scale: f64,
 }
 
 impl Drop for FontHandle {
@@ -92,7 +132,7 @@ fn create_face(
     lib: FT_Library,
     template: &FontTemplateData,
     pt_size: Option<Au>,
-) -> Result<FT_Face, ()> {
+) -> Result<(FT_Face, f64), ()> {
     unsafe {
         let mut face: FT_Face = ptr::null_mut();
         let face_index = 0 as FT_Long;
@@ -113,11 +153,12 @@ fn create_face(
             return Err(());
         }
 
+        let mut bitmap_scale = 1.;
         if let Some(s) = pt_size {
-            FontHandle::set_char_size(face, s).or(Err(()))?
+            bitmap_scale = FontHandle::set_char_size(face, s).or(Err(()))?
         }
 
-        Ok(face)
+        Ok((face, bitmap_scale))
     }
 }
 
@@ -129,13 +170,14 @@ impl FontHandleMethods for FontHandle {
         let ft_ctx: FT_Library = fctx.ctx.ctx;
         if ft_ctx.is_null() { return Err(()); }
 
-        let face = create_face(ft_ctx, &template, pt_size)?;
+        let (face, bitmap_scale) = create_face(ft_ctx, &template, pt_size)?;
 
         let mut handle = FontHandle {
               face: face,
               font_data: template.clone(),
               handle: fctx.clone(),
               can_do_fast_shaping: false,
+              bitmap_scale,
         };
         // TODO (#11310): Implement basic support for GPOS and GSUB.
         handle.can_do_fast_shaping = handle.has_table(KERN) &&
@@ -235,6 +277,37 @@ impl FontHandleMethods for FontHandle {
         self.can_do_fast_shaping
     }
 
+    fn set_variations(&mut self, variations: &[webrender_api::FontVariation]) {
+        if variations.is_empty() {
+            return
+        }
+
+        unsafe {
+            let mut mm_var: *mut FT_MM_Var = ptr::null_mut();
+            if !succeeded(FT_Get_MM_Var(self.face, &mut mm_var)) || mm_var.is_null() {
+                return
+            }
+
+            let axes = slice::from_raw_parts((*mm_var).axis, (*mm_var).num_axis as usize);
+            let mut coords: Vec<FT_Fixed> = axes.iter().map(|axis| {
+                // The last value given for an axis wins.
+                variations.iter().rev().find(|variation| variation.tag as FT_ULong == axis.tag)
+                    .map_or(axis.def, |variation| float_to_fixed(16, variation.value as f64) as FT_Fixed)
+                    .max(axis.minimum)
+                    .min(axis.maximum)
+            }).collect();
+            if !succeeded(FT_Set_Var_Design_Coordinates(self.face, coords.len() as FT_UInt, coords.as_mut_ptr())) {
+                debug!("Setting the variations of font {} failed", self.font_data.identifier);
+            }
+
+            // The structure was allocated by the memory manager of the face.
+            let memory = (*self.face).memory;
+            if let Some(free) = (*memory).free {
+                free(memory, mm_var as *mut c_void);
+            }
+        }
+    }
+
     fn glyph_h_advance(&self, glyph: GlyphId) -> Option<FractionalPixel> {
         assert!(!self.face.is_null());
         unsafe {
@@ -248,7 +321,25 @@ impl FontHandleMethods for FontHandle {
                 let advance = (*slot).metrics.horiAdvance;
                 debug!("h_advance for {} is {}", glyph, advance);
                 let advance = advance as i32;
-                Some(fixed_to_float_ft(advance) as FractionalPixel)
+                Some((fixed_to_float_ft(advance) * self.bitmap_scale) as FractionalPixel)
+            } else {
+                debug!("Unable to load glyph {}. reason: {:?}", glyph, res);
+                None
+            }
+        }
+    }
+
+    fn glyph_v_advance(&self, glyph: GlyphId) -> Option<FractionalPixel> {
+        assert!(!self.face.is_null());
+        unsafe {
+            let res = FT_Load_Glyph(self.face, glyph as FT_UInt, GLYPH_LOAD_FLAGS);
+            if succeeded(res) {
+                let slot = (*self.face).glyph;
+                assert!(!slot.is_null());
+                // FreeType synthesizes the vertical metrics of fonts without `vhea` and `vmtx`
+                // tables.
+                let advance = (*slot).metrics.vertAdvance as i32;
+                Some((fixed_to_float_ft(advance) * self.bitmap_scale) as FractionalPixel)
             } else {
                 debug!("Unable to load glyph {}. reason: {:?}", glyph, res);
                 None
@@ -327,18 +418,43 @@ impl FontHandleMethods for FontHandle {
         }
     }
 
+    fn has_color_glyphs(&self) -> bool {
+        self.has_table(CBDT) || self.has_table(COLR) || self.has_table(SBIX)
+    }
+
     fn identifier(&self) -> Atom {
         self.font_data.identifier.clone()
     }
 }
 
 impl<'a> FontHandle {
-    fn set_char_size(face: FT_Face, pt_size: Au) -> Result<(), ()>{
-        let char_size = pt_size.to_f64_px() * 64.0 + 0.5;
-
+    /// Sets the size of the face, and returns the scale of its glyphs to that size.
+    ///
+    /// Faces which only have bitmap glyphs, like those of color emoji fonts, come in a few fixed
+    /// sizes: the smallest strike at least as large as `pt_size` is selected, or else the largest
+    /// one, and its glyphs are scaled by WebRender.
+    fn set_char_size(face: FT_Face, pt_size: Au) -> Result<f64, ()> {
         unsafe {
+            let face_flags = (*face).face_flags;
+            if face_flags & FT_FACE_FLAG_SCALABLE == 0 && face_flags & FT_FACE_FLAG_FIXED_SIZES != 0 {
+                let sizes = slice::from_raw_parts((*face).available_sizes, (*face).num_fixed_sizes as usize);
+                let strike_size = |index: usize| fixed_to_float_ft(sizes[index].y_ppem as i32);
+                let requested_size = pt_size.to_f64_px();
+                let larger_strike = (0..sizes.len())
+                    .filter(|&index| strike_size(index) >= requested_size)
+                    .min_by(|&a, &b| strike_size(a).partial_cmp(&strike_size(b)).unwrap());
+                let strike = larger_strike.or_else(|| {
+                    (0..sizes.len()).max_by(|&a, &b| strike_size(a).partial_cmp(&strike_size(b)).unwrap())
+                }).ok_or(())?;
+                if !succeeded(FT_Select_Size(face, strike as c_int)) {
+                    return Err(())
+                }
+                return Ok(requested_size / strike_size(strike))
+            }
+
+            let char_size = pt_size.to_f64_px() * 64.0 + 0.5;
             let result = FT_Set_Char_Size(face, char_size as FT_F26Dot6, 0, 0, 0);
-            if succeeded(result) { Ok(()) } else { Err(()) }
+            if succeeded(result) { Ok(1.) } else { Err(()) }
         }
     }
 
@@ -363,7 +479,7 @@ impl<'a> FontHandle {
         let metrics: &FT_Size_Metrics = &(*size).metrics;
 
         let em_size = face.units_per_EM as f64;
-        let x_scale = (metrics.x_ppem as f64) / em_size as f64;
+        let x_scale = (metrics.x_ppem as f64) / em_size as f64 * self.bitmap_scale;
 
         // If this isn't true then we're scaling one of the axes wrong
         assert_eq!(metrics.x_ppem, metrics.y_ppem);
@@ -152,5 +152,7 @@ pub fn fallback_font_families(codepoint: Option<char>) -> Vec<&'static str> {
         }
     }
 
+    // Color emoji, which text fonts may also have monochrome glyphs for.
+    families.push("Noto Color Emoji");
     families
 }
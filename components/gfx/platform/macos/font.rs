@@ -13,7 +13,7 @@ use core_graphics::font::CGGlyph;
 use core_graphics::geometry::CGRect;
 use core_text::font::CTFont;
 use core_text::font_descriptor::{SymbolicTraitAccessors, TraitAccessors};
-use core_text::font_descriptor::kCTFontDefaultOrientation;
+use core_text::font_descriptor::{kCTFontColorGlyphsTrait, kCTFontDefaultOrientation, kCTFontVerticalOrientation};
 use font::{FontHandleMethods, FontMetrics, FontTableMethods, FontTableTag, FractionalPixel};
 use font::{GPOS, GSUB, KERN};
 use platform::font_template::FontTemplateData;
@@ -263,6 +263,10 @@ impl FontHandleMethods for FontHandle {
         self.can_do_fast_shaping
     }
 
+    fn set_variations(&mut self, _variations: &[webrender_api::FontVariation]) {
+        // TODO: Create the CTFont with the variations. WebRender still renders glyphs with them.
+    }
+
     fn glyph_h_advance(&self, glyph: GlyphId) -> Option<FractionalPixel> {
         let glyphs = [glyph as CGGlyph];
         let advance = self.ctfont.get_advances_for_glyphs(kCTFontDefaultOrientation,
@@ -272,6 +276,15 @@ impl FontHandleMethods for FontHandle {
         Some(advance as FractionalPixel)
     }
 
+    fn glyph_v_advance(&self, glyph: GlyphId) -> Option<FractionalPixel> {
+        let glyphs = [glyph as CGGlyph];
+        let advance = self.ctfont.get_advances_for_glyphs(kCTFontVerticalOrientation,
+                                                          &glyphs[0],
+                                                          ptr::null_mut(),
+                                                          1);
+        Some(advance.abs() as FractionalPixel)
+    }
+
     fn metrics(&self) -> FontMetrics {
         let bounding_rect: CGRect = self.ctfont.bounding_box();
         let ascent = self.ctfont.ascent() as f64;
@@ -318,6 +331,10 @@ impl FontHandleMethods for FontHandle {
         })
     }
 
+    fn has_color_glyphs(&self) -> bool {
+        self.ctfont.symbolic_traits() & kCTFontColorGlyphsTrait != 0
+    }
+
     fn identifier(&self) -> Atom {
         self.font_data.identifier.clone()
     }
@@ -184,6 +184,8 @@ pub fn fallback_font_families(codepoint: Option<char>) -> Vec<&'static str> {
 
     families.push("Geneva");
     families.push("Arial Unicode MS");
+    // Color emoji, which text fonts may also have monochrome glyphs for.
+    families.push("Apple Color Emoji");
     families
 }
 
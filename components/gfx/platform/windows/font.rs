@@ -11,7 +11,7 @@ use dwrote;
 use dwrote::{Font, FontFace, FontFile};
 use dwrote::{FontWeight, FontStretch, FontStyle};
 use font::{FontHandleMethods, FontMetrics, FontTableMethods};
-use font::{FontTableTag, FractionalPixel};
+use font::{COLR, FontTableTag, FractionalPixel};
 use platform::font_template::FontTemplateData;
 use platform::windows::font_context::FontContextHandle;
 use platform::windows::font_list::font_from_atom;
@@ -333,6 +333,15 @@ impl FontHandleMethods for FontHandle {
         Some(f)
     }
 
+    fn glyph_v_advance(&self, glyph: GlyphId) -> Option<FractionalPixel> {
+        if glyph == 0 {
+            return None;
+        }
+
+        let gm = self.face.get_design_glyph_metrics(&[glyph as u16], true)[0];
+        Some((gm.advanceHeight as f32 * self.scaled_du_to_px) as FractionalPixel)
+    }
+
     /// Can this font do basic horizontal LTR shaping without Harfbuzz?
     fn can_do_fast_shaping(&self) -> bool {
         // TODO copy CachedKernTable from the MacOS X implementation to
@@ -342,6 +351,11 @@ impl FontHandleMethods for FontHandle {
         false
     }
 
+    fn set_variations(&mut self, _variations: &[webrender_api::FontVariation]) {
+        // TODO: Use IDWriteFontFace5 to apply the variations. WebRender still renders glyphs with
+        // them.
+    }
+
     fn glyph_h_kerning(&self, _: GlyphId, _: GlyphId) -> FractionalPixel {
         0.0
     }
@@ -378,6 +392,11 @@ impl FontHandleMethods for FontHandle {
         self.face.get_font_table(tag).map(|bytes| FontTable { data: bytes })
     }
 
+    fn has_color_glyphs(&self) -> bool {
+        // Only layered color glyphs are painted on Windows.
+        self.face.get_font_table(COLR).is_some()
+    }
+
     fn identifier(&self) -> Atom {
         self.font_data.identifier.clone()
     }
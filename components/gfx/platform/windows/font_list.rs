@@ -332,5 +332,7 @@ pub fn fallback_font_families(codepoint: Option<char>) -> Vec<&'static str> {
     }
 
     families.push("Arial Unicode MS");
+    // Color emoji, which text fonts may also have monochrome glyphs for.
+    families.push("Segoe UI Emoji");
     families
 }
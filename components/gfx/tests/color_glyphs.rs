@@ -0,0 +1,62 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+extern crate gfx;
+extern crate webrender_api;
+
+use gfx::text::color_glyphs::ColorGlyphTable;
+use webrender_api::ColorF;
+
+fn colr() -> Vec<u8> {
+    vec![
+        // Version, base glyph count, base glyphs offset, layers offset, layer count.
+        0, 0, 0, 2, 0, 0, 0, 14, 0, 0, 0, 26, 0, 3,
+        // Glyph 5 has layers 0 and 1, glyph 9 has layer 2.
+        0, 5, 0, 0, 0, 2,
+        0, 9, 0, 2, 0, 1,
+        // Layers: glyph 20 in palette entry 1, glyph 21 in the text color, glyph 22 in entry 0.
+        0, 20, 0, 1,
+        0, 21, 0xFF, 0xFF,
+        0, 22, 0, 0,
+    ]
+}
+
+fn cpal() -> Vec<u8> {
+    vec![
+        // Version, entry count, palette count, color record count, color records offset.
+        0, 0, 0, 2, 0, 1, 0, 2, 0, 0, 0, 14,
+        // The first palette starts at the first color record.
+        0, 0,
+        // Opaque blue, half transparent red.
+        255, 0, 0, 255,
+        0, 0, 255, 128,
+    ]
+}
+
+#[test]
+fn test_color_glyph_layers() {
+    let table = ColorGlyphTable::new(&colr(), &cpal()).unwrap();
+
+    let layers = table.layers(5).unwrap();
+    assert_eq!(layers.len(), 2);
+    assert_eq!(layers[0].glyph, 20);
+    assert_eq!(layers[0].color, Some(ColorF::new(1., 0., 0., 128. / 255.)));
+    assert_eq!(layers[1].glyph, 21);
+    assert_eq!(layers[1].color, None);
+
+    let layers = table.layers(9).unwrap();
+    assert_eq!(layers.len(), 1);
+    assert_eq!(layers[0].glyph, 22);
+    assert_eq!(layers[0].color, Some(ColorF::new(0., 0., 1., 1.)));
+
+    assert!(table.layers(6).is_none());
+}
+
+#[test]
+fn test_color_glyph_table_rejects_out_of_bounds_layers() {
+    let mut colr = colr();
+    // Make glyph 9 use layers past the end of the table.
+    colr[23] = 3;
+    assert!(ColorGlyphTable::new(&colr, &cpal()).is_none());
+}
@@ -11,7 +11,7 @@ extern crate webrender_api;
 
 use app_units::Au;
 use gfx::font::{fallback_font_families, FontDescriptor, FontFamilyDescriptor, FontFamilyName, FontSearchScope};
-use gfx::font_cache_thread::{FontTemplates, FontTemplateInfo};
+use gfx::font_cache_thread::{FontTemplates, FontTemplateInfo, WebFontTemplate};
 use gfx::font_context::{FontContext, FontContextHandle, FontSource};
 use gfx::font_template::FontTemplateDescriptor;
 use servo_arc::Arc;
@@ -22,15 +22,19 @@ use std::fs::File;
 use std::io::prelude::*;
 use std::path::PathBuf;
 use std::rc::Rc;
+use style::font_face::{FontDisplay, FontFaceDescriptors};
+use style::properties::longhands::font_kerning::computed_value::T as FontKerning;
 use style::properties::longhands::font_variant_caps::computed_value::T as FontVariantCaps;
 use style::properties::style_structs::Font as FontStyleStruct;
 use style::values::computed::font::{FamilyName, FamilyNameSyntax, FontFamily, FontFamilyList, FontSize};
+use style::values::computed::font::{FontFeatureSettings, FontVariantLigatures, FontVariationSettings};
 use style::values::computed::font::{FontStretch, FontWeight, SingleFontFamily};
-use style::values::generics::font::FontStyle;
+use style::values::generics::font::{FeatureTagValue, FontSettings, FontStyle, FontTag};
 
 struct TestFontSource {
     handle: FontContextHandle,
     families: HashMap<String, FontTemplates>,
+    web_families: HashMap<String, (FontTemplates, FontFaceDescriptors)>,
     find_font_count: Rc<Cell<isize>>,
 }
 
@@ -50,9 +54,21 @@ impl TestFontSource {
         families.insert("CSSTest Basic".to_owned(), csstest_basic);
         families.insert(fallback_font_families(None)[0].to_owned(), fallback);
 
+        let mut csstest_web = FontTemplates::new();
+        Self::add_face(&mut csstest_web, "csstest-basic-regular", Some("csstest-web"));
+        let descriptors = FontFaceDescriptors {
+            unicode_ranges: vec![('á' as u32, 'á' as u32)],
+            display: FontDisplay::Auto,
+            feature_settings: vec![],
+        };
+
+        let mut web_families = HashMap::new();
+        web_families.insert("CSSTest Web".to_owned(), (csstest_web, descriptors));
+
         TestFontSource {
             handle: FontContextHandle::new(),
             families,
+            web_families,
             find_font_count: Rc::new(Cell::new(0)),
         }
     }
@@ -77,10 +93,22 @@ impl TestFontSource {
 }
 
 impl FontSource for TestFontSource {
-    fn get_font_instance(&mut self, _key: webrender_api::FontKey, _size: Au) -> webrender_api::FontInstanceKey {
+    fn get_font_instance(
+        &mut self,
+        _key: webrender_api::FontKey,
+        _size: Au,
+        _variations: Vec<webrender_api::FontVariation>,
+    ) -> webrender_api::FontInstanceKey {
         webrender_api::FontInstanceKey(webrender_api::IdNamespace(0), 0)
     }
 
+    fn get_sideways_font_instance(
+        &mut self,
+        instance: webrender_api::FontInstanceKey,
+    ) -> webrender_api::FontInstanceKey {
+        instance
+    }
+
     fn font_template(
         &mut self,
         template_descriptor: FontTemplateDescriptor,
@@ -89,6 +117,15 @@ impl FontSource for TestFontSource {
         let handle = &self.handle;
 
         self.find_font_count.set(self.find_font_count.get() + 1);
+        if let Some(&mut (ref mut family, ref descriptors)) = self.web_families.get_mut(family_descriptor.name()) {
+            return family.find_font_for_style(&template_descriptor, handle).map(|template| {
+                FontTemplateInfo {
+                    font_template: template,
+                    font_key: webrender_api::FontKey(webrender_api::IdNamespace(0), 0),
+                    face_descriptors: Some(descriptors.clone()),
+                }
+            })
+        }
         self.families
             .get_mut(family_descriptor.name())
             .and_then(|family| family.find_font_for_style(&template_descriptor, handle))
@@ -96,9 +133,29 @@ impl FontSource for TestFontSource {
                 FontTemplateInfo {
                     font_template: template,
                     font_key: webrender_api::FontKey(webrender_api::IdNamespace(0), 0),
+                    face_descriptors: None,
                 }
             })
     }
+
+    fn web_font_template(
+        &mut self,
+        template_descriptor: FontTemplateDescriptor,
+        family_descriptor: FontFamilyDescriptor,
+        codepoint: char,
+    ) -> WebFontTemplate {
+        let covered = match self.web_families.get(family_descriptor.name()) {
+            Some(&(_, ref descriptors)) => descriptors.covers(codepoint),
+            None => return WebFontTemplate::NoSuchFamily,
+        };
+        if !covered {
+            return WebFontTemplate::Unavailable
+        }
+        match self.font_template(template_descriptor, family_descriptor) {
+            Some(template_info) => WebFontTemplate::Available(template_info),
+            None => WebFontTemplate::Unavailable,
+        }
+    }
 }
 
 fn style() -> FontStyleStruct {
@@ -109,6 +166,10 @@ fn style() -> FontStyleStruct {
         font_weight: FontWeight::normal(),
         font_size: FontSize::medium(),
         font_stretch: FontStretch::hundred(),
+        font_kerning: FontKerning::Auto,
+        font_variant_ligatures: FontVariantLigatures::empty(),
+        font_feature_settings: FontFeatureSettings::normal(),
+        font_variation_settings: FontVariationSettings::normal(),
         hash: 0,
     };
     style.compute_font_hash();
@@ -173,6 +234,29 @@ fn test_font_group_find_by_codepoint() {
     assert_eq!(count.get(), 2, "both fonts should now have been loaded");
 }
 
+#[test]
+fn test_font_group_find_by_codepoint_honors_unicode_range() {
+    let source = TestFontSource::new();
+    let mut context = FontContext::new(source);
+
+    let mut style = style();
+    style.set_font_family(font_family(vec!("CSSTest Web", "CSSTest ASCII")));
+
+    let group = context.font_group(Arc::new(style));
+
+    let font = group.borrow_mut().find_by_codepoint(&mut context, 'a').unwrap();
+    assert_eq!(
+        &*font.borrow().identifier(), "csstest-ascii",
+        "a web font should not be used outside of its unicode-range"
+    );
+
+    let font = group.borrow_mut().find_by_codepoint(&mut context, 'á').unwrap();
+    assert_eq!(
+        &*font.borrow().identifier(), "csstest-web",
+        "a web font should be used inside of its unicode-range"
+    );
+}
+
 #[test]
 fn test_font_fallback() {
     let source = TestFontSource::new();
@@ -210,6 +294,8 @@ fn test_font_template_is_cached() {
         },
         variant: FontVariantCaps::Normal,
         pt_size: Au(10),
+        features: vec![],
+        variations: vec![],
     };
 
     let family_descriptor = FontFamilyDescriptor::new(
@@ -230,3 +316,29 @@ fn test_font_template_is_cached() {
 
     assert_eq!(count.get(), 1, "we should only have fetched the template data from the cache thread once");
 }
+
+fn tag(name: &str) -> u32 {
+    name.bytes().fold(0, |tag, byte| (tag << 8) | byte as u32)
+}
+
+#[test]
+fn test_font_descriptor_features() {
+    let mut style = style();
+    style.set_font_kerning(FontKerning::None);
+    style.set_font_variant_ligatures(FontVariantLigatures::NO_COMMON_LIGATURES);
+    style.set_font_feature_settings(FontSettings(vec![
+        FeatureTagValue { tag: FontTag(tag("liga")), value: 1 },
+    ].into_boxed_slice()));
+
+    let descriptor = FontDescriptor::from(&style);
+    assert_eq!(
+        descriptor.features,
+        vec![
+            (tag("kern"), 0),
+            (tag("liga"), 0),
+            (tag("clig"), 0),
+            (tag("liga"), 1),
+        ],
+        "font-feature-settings should come after the features of the other properties"
+    );
+}
@@ -0,0 +1,43 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+extern crate gfx;
+
+use gfx::text::hyphenation::Hyphenator;
+
+// The patterns used as an example in appendix H of The TeXbook.
+const PATTERNS: &'static str = "hy3ph he2n hena4 hen5at 1na n2at 1tio 2io o2n";
+
+#[test]
+fn test_hyphenation_points() {
+    let hyphenator = Hyphenator::new(PATTERNS, 2, 3);
+    assert_eq!(hyphenator.hyphenation_points("hyphenation"), vec![2, 6]);
+}
+
+#[test]
+fn test_hyphenation_points_ignore_case() {
+    let hyphenator = Hyphenator::new(PATTERNS, 2, 3);
+    assert_eq!(hyphenator.hyphenation_points("Hyphenation"), vec![2, 6]);
+}
+
+#[test]
+fn test_hyphenation_points_are_byte_offsets() {
+    let hyphenator = Hyphenator::new("1bé", 1, 1);
+    assert_eq!(hyphenator.hyphenation_points("ébé"), vec![2]);
+}
+
+#[test]
+fn test_hyphenation_respects_minimum_lengths() {
+    let hyphenator = Hyphenator::new(PATTERNS, 3, 6);
+    assert_eq!(hyphenator.hyphenation_points("hyphenation"), vec![]);
+    let hyphenator = Hyphenator::new(PATTERNS, 2, 3);
+    assert_eq!(hyphenator.hyphenation_points("hy"), vec![]);
+}
+
+#[test]
+fn test_hyphenation_patterns_skip_comments() {
+    let hyphenator = Hyphenator::new("% Copyright 1bé\n1na % 1ph\n", 1, 1);
+    assert_eq!(hyphenator.hyphenation_points("bébé"), vec![]);
+    assert_eq!(hyphenator.hyphenation_points("hyphenation"), vec![5]);
+}
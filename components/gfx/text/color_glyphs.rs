@@ -0,0 +1,128 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Layered color glyphs, as defined by the `COLR` and `CPAL` tables of OpenType fonts.
+//!
+//! A color glyph is painted as a stack of layers, each of which is another glyph of the font
+//! painted in a color of the palette of the font, or in the color of the text.
+
+use text::glyph::GlyphId;
+use webrender_api::ColorF;
+
+/// The palette index of the layers painted in the color of the text.
+const FOREGROUND_PALETTE_INDEX: u16 = 0xFFFF;
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset + 2).map(|bytes| ((bytes[0] as u16) << 8) | bytes[1] as u16)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(((read_u16(data, offset)? as u32) << 16) | read_u16(data, offset + 2)? as u32)
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct BaseGlyph {
+    glyph: GlyphId,
+    first_layer: usize,
+    layer_count: usize,
+}
+
+/// A layer of a color glyph.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct ColorGlyphLayer {
+    pub glyph: GlyphId,
+    /// The color to paint the layer with, or `None` for the color of the text.
+    pub color: Option<ColorF>,
+}
+
+/// The color glyphs of a font, read from its `COLR` (version 0) and `CPAL` tables. Only the first
+/// palette is used.
+///
+/// <https://docs.microsoft.com/en-us/typography/opentype/spec/colr>
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ColorGlyphTable {
+    /// The glyphs which have layers, sorted by glyph id.
+    base_glyphs: Vec<BaseGlyph>,
+    layers: Vec<ColorGlyphLayer>,
+}
+
+impl ColorGlyphTable {
+    /// Reads the color glyphs from the `COLR` and `CPAL` tables of a font. Returns `None` if the
+    /// tables are malformed or of an unsupported version.
+    pub fn new(colr: &[u8], cpal: &[u8]) -> Option<ColorGlyphTable> {
+        let palette = ColorGlyphTable::read_palette(cpal)?;
+
+        if read_u16(colr, 0)? != 0 {
+            return None
+        }
+        let base_glyph_count = read_u16(colr, 2)? as usize;
+        let base_glyphs_offset = read_u32(colr, 4)? as usize;
+        let layers_offset = read_u32(colr, 8)? as usize;
+        let layer_count = read_u16(colr, 12)? as usize;
+
+        let mut base_glyphs = Vec::with_capacity(base_glyph_count);
+        for index in 0..base_glyph_count {
+            let offset = base_glyphs_offset + index * 6;
+            let base_glyph = BaseGlyph {
+                glyph: read_u16(colr, offset)? as GlyphId,
+                first_layer: read_u16(colr, offset + 2)? as usize,
+                layer_count: read_u16(colr, offset + 4)? as usize,
+            };
+            if base_glyph.first_layer + base_glyph.layer_count > layer_count {
+                return None
+            }
+            base_glyphs.push(base_glyph);
+        }
+        // The records should already be sorted, but lookups rely on it.
+        base_glyphs.sort_by_key(|base_glyph| base_glyph.glyph);
+
+        let mut layers = Vec::with_capacity(layer_count);
+        for index in 0..layer_count {
+            let offset = layers_offset + index * 4;
+            let glyph = read_u16(colr, offset)? as GlyphId;
+            let palette_index = read_u16(colr, offset + 2)?;
+            let color = if palette_index == FOREGROUND_PALETTE_INDEX {
+                None
+            } else {
+                Some(*palette.get(palette_index as usize)?)
+            };
+            layers.push(ColorGlyphLayer { glyph, color });
+        }
+
+        Some(ColorGlyphTable { base_glyphs, layers })
+    }
+
+    /// Reads the first palette of a `CPAL` table.
+    ///
+    /// <https://docs.microsoft.com/en-us/typography/opentype/spec/cpal>
+    fn read_palette(cpal: &[u8]) -> Option<Vec<ColorF>> {
+        let entry_count = read_u16(cpal, 2)? as usize;
+        if read_u16(cpal, 4)? == 0 {
+            return None
+        }
+        let color_records_offset = read_u32(cpal, 8)? as usize;
+        let first_color_index = read_u16(cpal, 12)? as usize;
+
+        let mut palette = Vec::with_capacity(entry_count);
+        for index in 0..entry_count {
+            let offset = color_records_offset + (first_color_index + index) * 4;
+            // Color records are stored as BGRA.
+            let record = cpal.get(offset..offset + 4)?;
+            palette.push(ColorF::new(
+                record[2] as f32 / 255.,
+                record[1] as f32 / 255.,
+                record[0] as f32 / 255.,
+                record[3] as f32 / 255.,
+            ));
+        }
+        Some(palette)
+    }
+
+    /// Returns the layers of `glyph`, from bottom to top, or `None` if it is not a color glyph.
+    pub fn layers(&self, glyph: GlyphId) -> Option<&[ColorGlyphLayer]> {
+        let index = self.base_glyphs.binary_search_by_key(&glyph, |base_glyph| base_glyph.glyph).ok()?;
+        let base_glyph = &self.base_glyphs[index];
+        Some(&self.layers[base_glyph.first_layer..base_glyph.first_layer + base_glyph.layer_count])
+    }
+}
@@ -0,0 +1,116 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Dictionary-based hyphenation, using Frank Liang's TeX hyphenation patterns.
+//!
+//! A pattern is a fragment of a word with a level between each pair of letters, such as `hy3ph`.
+//! The levels of every pattern matching a word are merged, keeping the highest one at each
+//! position, and the word may be hyphenated wherever the resulting level is odd.
+
+use embedder_traits::resources::{self, Resource};
+use std::cmp::{max, min};
+use std::collections::HashMap;
+
+lazy_static! {
+    static ref ENGLISH: Hyphenator =
+        Hyphenator::new(&resources::read_string(Resource::EnglishHyphenationPatterns), 2, 3);
+    static ref GERMAN: Hyphenator =
+        Hyphenator::new(&resources::read_string(Resource::GermanHyphenationPatterns), 2, 2);
+}
+
+/// Returns the hyphenator for a BCP 47 language tag, or `None` if there are no patterns for the
+/// language. Only the primary language subtag is taken into account.
+pub fn hyphenator_for_language(lang: &str) -> Option<&'static Hyphenator> {
+    let primary_subtag = lang.split(|c| c == '-' || c == '_').next().unwrap_or("");
+    if primary_subtag.eq_ignore_ascii_case("en") {
+        Some(&*ENGLISH)
+    } else if primary_subtag.eq_ignore_ascii_case("de") {
+        Some(&*GERMAN)
+    } else {
+        None
+    }
+}
+
+pub struct Hyphenator {
+    /// The levels of each pattern, keyed by its letters. There is one more level than letters.
+    patterns: HashMap<String, Vec<u8>>,
+    /// The number of letters of the longest pattern.
+    max_pattern_length: usize,
+    /// The minimum number of characters to keep before the first hyphen of a word.
+    left_min: usize,
+    /// The minimum number of characters to keep after the last hyphen of a word.
+    right_min: usize,
+}
+
+impl Hyphenator {
+    /// Creates a hyphenator from whitespace-separated patterns, in the format of the `.pat.txt`
+    /// files of the hyph-utf8 project. `.` marks the start or the end of a word, and `%` starts a
+    /// comment which runs to the end of the line.
+    pub fn new(patterns: &str, left_min: usize, right_min: usize) -> Hyphenator {
+        let mut hyphenator = Hyphenator {
+            patterns: HashMap::new(),
+            max_pattern_length: 0,
+            left_min: max(left_min, 1),
+            right_min: max(right_min, 1),
+        };
+        let lines = patterns.lines().map(|line| line.split('%').next().unwrap());
+        for pattern in lines.flat_map(|line| line.split_whitespace()) {
+            let mut letters = String::new();
+            let mut levels = vec![0];
+            for c in pattern.chars() {
+                match c.to_digit(10) {
+                    Some(level) => *levels.last_mut().unwrap() = level as u8,
+                    None => {
+                        letters.push(c);
+                        levels.push(0);
+                    }
+                }
+            }
+            hyphenator.max_pattern_length = max(hyphenator.max_pattern_length, levels.len() - 1);
+            hyphenator.patterns.insert(letters, levels);
+        }
+        hyphenator
+    }
+
+    /// Returns the byte offsets within `word` before which a hyphen may be inserted, in order.
+    pub fn hyphenation_points(&self, word: &str) -> Vec<usize> {
+        let offsets: Vec<usize> = word.char_indices().map(|(offset, _)| offset).collect();
+        if offsets.len() < self.left_min + self.right_min {
+            return vec![]
+        }
+
+        // Patterns are lowercase. Characters whose lowercase form is more than one character are
+        // kept as is, so that positions in the word and in the dotted word stay in step.
+        let mut dotted = vec!['.'];
+        dotted.extend(word.chars().map(|c| {
+            let mut lowercase = c.to_lowercase();
+            match (lowercase.next(), lowercase.next()) {
+                (Some(lowercase), None) => lowercase,
+                _ => c,
+            }
+        }));
+        dotted.push('.');
+
+        // `levels[i]` is the level before `dotted[i]`.
+        let mut levels = vec![0; dotted.len() + 1];
+        let mut key = String::new();
+        for start in 0..dotted.len() {
+            key.clear();
+            for end in start..min(start + self.max_pattern_length, dotted.len()) {
+                key.push(dotted[end]);
+                if let Some(pattern_levels) = self.patterns.get(&key) {
+                    for (index, &level) in pattern_levels.iter().enumerate() {
+                        levels[start + index] = max(levels[start + index], level);
+                    }
+                }
+            }
+        }
+
+        // A break before the character at `index` is the level before `dotted[index + 1]`.
+        (self.left_min..offsets.len() - self.right_min + 1)
+            .filter(|&index| levels[index + 1] % 2 == 1)
+            .map(|index| offsets[index])
+            .collect()
+    }
+}
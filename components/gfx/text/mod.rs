@@ -5,7 +5,9 @@
 pub use text::shaping::Shaper;
 pub use text::text_run::TextRun;
 
+pub mod color_glyphs;
 pub mod glyph;
+pub mod hyphenation;
 pub mod shaping;
 pub mod text_run;
 pub mod util;
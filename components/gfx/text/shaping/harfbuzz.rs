@@ -7,7 +7,7 @@
 use app_units::Au;
 use euclid::Point2D;
 use font::{ShapingFlags, Font, FontTableMethods, FontTableTag, ShapingOptions, KERN};
-use harfbuzz::{HB_DIRECTION_LTR, HB_DIRECTION_RTL, HB_MEMORY_MODE_READONLY};
+use harfbuzz::{HB_DIRECTION_LTR, HB_DIRECTION_RTL, HB_DIRECTION_TTB, HB_MEMORY_MODE_READONLY};
 use harfbuzz::{hb_blob_create, hb_face_create_for_tables};
 use harfbuzz::{hb_buffer_create, hb_font_destroy};
 use harfbuzz::{hb_buffer_get_glyph_infos, hb_shape};
@@ -27,6 +27,8 @@ use harfbuzz::hb_font_create;
 use harfbuzz::hb_font_funcs_create;
 use harfbuzz::hb_font_funcs_set_glyph_h_advance_func;
 use harfbuzz::hb_font_funcs_set_glyph_h_kerning_func;
+use harfbuzz::hb_font_funcs_set_glyph_v_advance_func;
+use harfbuzz::hb_font_funcs_set_glyph_v_origin_func;
 use harfbuzz::hb_font_funcs_set_nominal_glyph_func;
 use harfbuzz::hb_font_set_funcs;
 use harfbuzz::hb_font_set_ppem;
@@ -41,6 +43,20 @@ use text::shaping::ShaperMethods;
 use text::util::{fixed_to_float, float_to_fixed, is_bidi_control};
 
 const NO_GLYPH: i32 = -1;
+
+// `hb_font_set_variations` is not part of the harfbuzz-sys bindings.
+#[allow(non_camel_case_types)]
+#[repr(C)]
+struct hb_variation_t {
+    tag: hb_tag_t,
+    value: f32,
+}
+
+extern "C" {
+    fn hb_font_set_variations(font: *mut hb_font_t,
+                              variations: *const hb_variation_t,
+                              variations_length: c_uint);
+}
 const LIGA: u32 = ot_tag!('l', 'i', 'g', 'a');
 
 pub struct ShapedGlyphData {
@@ -88,8 +104,9 @@ impl ShapedGlyphData {
         self.count
     }
 
-    /// Returns shaped glyph data for one glyph, and updates the y-position of the pen.
-    pub fn entry_for_glyph(&self, i: usize, y_pos: &mut Au) -> ShapedGlyphEntry {
+    /// Returns shaped glyph data for one glyph, and updates the y-position of the pen. In vertical
+    /// text, the advance is downwards and the offset is relative to the pen.
+    pub fn entry_for_glyph(&self, i: usize, y_pos: &mut Au, vertical: bool) -> ShapedGlyphEntry {
         assert!(i < self.count);
 
         unsafe {
@@ -105,6 +122,15 @@ impl ShapedGlyphData {
             let x_advance = Au::from_f64_px(x_advance);
             let y_advance = Au::from_f64_px(y_advance);
 
+            if vertical {
+                // Harfbuzz points the y axis upwards.
+                return ShapedGlyphEntry {
+                    codepoint: (*glyph_info_i).codepoint as GlyphId,
+                    advance: -y_advance,
+                    offset: Some(Point2D::new(x_offset, -y_offset)),
+                }
+            }
+
             let offset = if x_offset == Au(0) && y_offset == Au(0) && y_advance == Au(0) {
                 None
             } else {
@@ -165,6 +191,17 @@ impl Shaper {
             // configure static function callbacks.
             hb_font_set_funcs(hb_font, HB_FONT_FUNCS.0, font as *mut Font as *mut c_void, None);
 
+            // Let the variation axes apply to the substitutions and positionings of the font.
+            let variations: Vec<hb_variation_t> = (*font).descriptor.variations.iter().map(|variation| {
+                hb_variation_t {
+                    tag: variation.tag,
+                    value: variation.value,
+                }
+            }).collect();
+            if !variations.is_empty() {
+                hb_font_set_variations(hb_font, variations.as_ptr(), variations.len() as c_uint);
+            }
+
             Shaper {
                 hb_face: hb_face,
                 hb_font: hb_font,
@@ -188,7 +225,9 @@ impl ShaperMethods for Shaper {
     fn shape_text(&self, text: &str, options: &ShapingOptions, glyphs: &mut GlyphStore) {
         unsafe {
             let hb_buffer: *mut hb_buffer_t = hb_buffer_create();
-            hb_buffer_set_direction(hb_buffer, if options.flags.contains(ShapingFlags::RTL_FLAG) {
+            hb_buffer_set_direction(hb_buffer, if options.flags.contains(ShapingFlags::VERTICAL_FLAG) {
+                HB_DIRECTION_TTB
+            } else if options.flags.contains(ShapingFlags::RTL_FLAG) {
                 HB_DIRECTION_RTL
             } else {
                 HB_DIRECTION_LTR
@@ -202,7 +241,21 @@ impl ShaperMethods for Shaper {
                                0,
                                text.len() as c_int);
 
-            let mut features = Vec::new();
+            // The settings of the `@font-face` rule come first, then those of the style, so that
+            // those implied by the shaping options override them.
+            let face_features = match (*self.font).face_descriptors {
+                Some(ref descriptors) => &descriptors.feature_settings[..],
+                None => &[][..],
+            };
+            let mut features: Vec<hb_feature_t> =
+                face_features.iter().chain((*self.font).descriptor.features.iter()).map(|&(tag, value)| {
+                    hb_feature_t {
+                        tag: tag,
+                        value: value,
+                        start: 0,
+                        end: hb_buffer_get_length(hb_buffer),
+                    }
+                }).collect();
             if options.flags.contains(ShapingFlags::IGNORE_LIGATURES_SHAPING_FLAG) {
                 features.push(hb_feature_t {
                     tag: LIGA,
@@ -267,6 +320,7 @@ impl Shaper {
         let mut byte_range = 0..0;
 
         let mut y_pos = Au(0);
+        let vertical = options.flags.contains(ShapingFlags::VERTICAL_FLAG);
 
         // main loop over each glyph. each iteration usually processes 1 glyph and 1+ chars.
         // in cases with complex glyph-character associations, 2+ glyphs and 1+ chars can be
@@ -355,7 +409,7 @@ impl Shaper {
                                               true);
                     glyphs.add_glyph_for_byte_index(byte_idx, character, &data);
                 } else {
-                    let shape = glyph_data.entry_for_glyph(glyph_span.start, &mut y_pos);
+                    let shape = glyph_data.entry_for_glyph(glyph_span.start, &mut y_pos, vertical);
                     let advance = self.advance_for_shaped_glyph(shape.advance, character, options);
                     let data = GlyphData::new(shape.codepoint,
                                               advance,
@@ -369,7 +423,7 @@ impl Shaper {
                 let mut datas = vec!();
 
                 for glyph_i in glyph_span.clone() {
-                    let shape = glyph_data.entry_for_glyph(glyph_i, &mut y_pos);
+                    let shape = glyph_data.entry_for_glyph(glyph_i, &mut y_pos, vertical);
                     datas.push(GlyphData::new(shape.codepoint,
                                               shape.advance,
                                               shape.offset,
@@ -423,6 +477,10 @@ lazy_static! {
             hb_funcs, Some(glyph_h_advance_func), ptr::null_mut(), None);
         hb_font_funcs_set_glyph_h_kerning_func(
             hb_funcs, Some(glyph_h_kerning_func), ptr::null_mut(), None);
+        hb_font_funcs_set_glyph_v_advance_func(
+            hb_funcs, Some(glyph_v_advance_func), ptr::null_mut(), None);
+        hb_font_funcs_set_glyph_v_origin_func(
+            hb_funcs, Some(glyph_v_origin_func), ptr::null_mut(), None);
 
         FontFuncs(hb_funcs)
     };
@@ -462,6 +520,39 @@ extern fn glyph_h_advance_func(_: *mut hb_font_t,
     }
 }
 
+extern fn glyph_v_advance_func(_: *mut hb_font_t,
+                               font_data: *mut c_void,
+                               glyph: hb_codepoint_t,
+                               _: *mut c_void)
+                            -> hb_position_t {
+    let font: *mut Font = font_data as *mut Font;
+    assert!(!font.is_null());
+
+    unsafe {
+        // Harfbuzz points the y axis upwards, so downward advances are negative.
+        let advance = (*font).glyph_v_advance(glyph as GlyphId);
+        -Shaper::float_to_fixed(advance)
+    }
+}
+
+/// The vertical origin of a glyph is centered above it, at the ascent of the font.
+extern fn glyph_v_origin_func(_: *mut hb_font_t,
+                              font_data: *mut c_void,
+                              glyph: hb_codepoint_t,
+                              x: *mut hb_position_t,
+                              y: *mut hb_position_t,
+                              _: *mut c_void)
+                           -> hb_bool_t {
+    let font: *mut Font = font_data as *mut Font;
+    assert!(!font.is_null());
+
+    unsafe {
+        *x = Shaper::float_to_fixed((*font).glyph_h_advance(glyph as GlyphId) / 2.);
+        *y = Shaper::float_to_fixed((*font).metrics.ascent.to_f64_px());
+    }
+    true as hb_bool_t
+}
+
 fn glyph_space_advance(font: *const Font) -> (hb_codepoint_t, f64) {
     let space_unicode = ' ';
     let space_glyph: hb_codepoint_t;
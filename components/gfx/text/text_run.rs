@@ -3,6 +3,7 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
 use app_units::Au;
+use euclid::Point2D;
 use font::{Font, FontHandleMethods, FontMetrics, ShapingFlags};
 use font::{RunMetrics, ShapingOptions};
 use platform::font_template::FontTemplateData;
@@ -12,7 +13,9 @@ use std::cmp::{Ordering, max};
 use std::slice::Iter;
 use std::sync::Arc;
 use style::str::char_is_whitespace;
-use text::glyph::{ByteIndex, GlyphStore};
+use text::color_glyphs::ColorGlyphTable;
+use text::glyph::{ByteIndex, GlyphId, GlyphStore};
+use text::util;
 use unicode_bidi as bidi;
 use webrender_api;
 use xi_unicode::LineBreakLeafIter;
@@ -22,6 +25,17 @@ thread_local! {
         Cell::new(None)
 }
 
+/// How the glyphs of a text run are set.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum GlyphOrientation {
+    /// Horizontal text.
+    Horizontal,
+    /// Vertical text, with upright glyphs advancing downwards.
+    Upright,
+    /// Vertical text, with glyphs rotated a quarter turn clockwise.
+    Sideways,
+}
+
 /// A single "paragraph" of text in one font size and style.
 #[derive(Clone, Deserialize, Serialize)]
 pub struct TextRun {
@@ -35,6 +49,15 @@ pub struct TextRun {
     pub glyphs: Arc<Vec<GlyphRun>>,
     pub bidi_level: bidi::Level,
     pub extra_word_spacing: Au,
+    /// Whether the text waits for a web font in its block period, and must not be painted.
+    pub invisible: bool,
+    /// The layered color glyphs of the font.
+    pub color_glyphs: Option<Arc<ColorGlyphTable>>,
+    /// The glyph, advance and offset from the pen position of the hyphen rendered when a line is
+    /// broken at a soft hyphen, if the text contains any.
+    pub hyphen_glyph: Option<(GlyphId, Au, Point2D<Au>)>,
+    /// How the glyphs are laid out in a line.
+    pub orientation: GlyphOrientation,
 }
 
 impl Drop for TextRun {
@@ -181,15 +204,43 @@ impl<'a> TextRun {
     pub fn new(font: &mut Font, text: String, options: &ShapingOptions,
                bidi_level: bidi::Level, breaker: &mut Option<LineBreakLeafIter>) -> (TextRun, bool) {
         let (glyphs, break_at_zero) = TextRun::break_and_shape(font, &text, options, breaker);
+        let (orientation, font_key) = if options.flags.contains(ShapingFlags::VERTICAL_FLAG) {
+            (GlyphOrientation::Upright, font.font_key)
+        } else if options.flags.contains(ShapingFlags::SIDEWAYS_FLAG) {
+            (GlyphOrientation::Sideways, font.sideways_font_key.unwrap_or(font.font_key))
+        } else {
+            (GlyphOrientation::Horizontal, font.font_key)
+        };
+        let hyphen_glyph = if text.contains(util::SOFT_HYPHEN) {
+            font.glyph_index(util::HYPHEN).or_else(|| font.glyph_index('-')).map(|glyph| {
+                match orientation {
+                    GlyphOrientation::Upright => {
+                        // Upright glyphs are centered on the vertical line, below its pen position.
+                        let offset = Point2D::new(Au::from_f64_px(-font.glyph_h_advance(glyph) / 2.),
+                                                  font.metrics.ascent);
+                        (glyph, Au::from_f64_px(font.glyph_v_advance(glyph)), offset)
+                    }
+                    GlyphOrientation::Horizontal | GlyphOrientation::Sideways => {
+                        (glyph, Au::from_f64_px(font.glyph_h_advance(glyph)), Point2D::zero())
+                    }
+                }
+            })
+        } else {
+            None
+        };
         (TextRun {
             text: Arc::new(text),
             font_metrics: font.metrics.clone(),
             font_template: font.handle.template(),
-            font_key: font.font_key,
+            font_key: font_key,
             actual_pt_size: font.actual_pt_size,
             glyphs: Arc::new(glyphs),
             bidi_level: bidi_level,
             extra_word_spacing: Au(0),
+            invisible: font.invisible,
+            color_glyphs: font.color_glyphs.clone(),
+            hyphen_glyph: hyphen_glyph,
+            orientation: orientation,
         }, break_at_zero)
     }
 
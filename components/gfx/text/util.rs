@@ -117,6 +117,97 @@ pub fn is_bidi_control(c: char) -> bool {
     }
 }
 
+/// Selects the text presentation of the preceding character.
+pub const TEXT_PRESENTATION_SELECTOR: char = '\u{FE0E}';
+
+/// Selects the emoji presentation of the preceding character.
+pub const EMOJI_PRESENTATION_SELECTOR: char = '\u{FE0F}';
+
+/// Joins emoji into a sequence rendered as a single glyph.
+pub const ZERO_WIDTH_JOINER: char = '\u{200D}';
+
+/// Marks a position where a word may be hyphenated. It is only rendered, as a hyphen, if a line
+/// break is taken there.
+pub const SOFT_HYPHEN: char = '\u{AD}';
+
+/// The hyphen rendered at the end of a line broken at a soft hyphen.
+pub const HYPHEN: char = '\u{2010}';
+
+/// Returns whether `codepoint` is presented as an emoji rather than as text by default, that is
+/// whether it has the `Emoji_Presentation` property.
+///
+/// <https://unicode.org/reports/tr51/#Presentation_Style>
+pub fn is_emoji_presentation(codepoint: char) -> bool {
+    match codepoint {
+        '\u{231A}'...'\u{231B}' | '\u{23E9}'...'\u{23EC}' | '\u{23F0}' | '\u{23F3}' |
+        '\u{25FD}'...'\u{25FE}' | '\u{2614}'...'\u{2615}' | '\u{2648}'...'\u{2653}' | '\u{267F}' |
+        '\u{2693}' | '\u{26A1}' | '\u{26AA}'...'\u{26AB}' | '\u{26BD}'...'\u{26BE}' |
+        '\u{26C4}'...'\u{26C5}' | '\u{26CE}' | '\u{26D4}' | '\u{26EA}' | '\u{26F2}'...'\u{26F3}' |
+        '\u{26F5}' | '\u{26FA}' | '\u{26FD}' | '\u{2705}' | '\u{270A}'...'\u{270B}' | '\u{2728}' |
+        '\u{274C}' | '\u{274E}' | '\u{2753}'...'\u{2755}' | '\u{2757}' | '\u{2795}'...'\u{2797}' |
+        '\u{27B0}' | '\u{27BF}' | '\u{2B1B}'...'\u{2B1C}' | '\u{2B50}' | '\u{2B55}' |
+        '\u{1F004}' | '\u{1F0CF}' | '\u{1F18E}' | '\u{1F191}'...'\u{1F19A}' | '\u{1F1E6}'...'\u{1F1FF}' |
+        '\u{1F201}' | '\u{1F21A}' | '\u{1F22F}' | '\u{1F232}'...'\u{1F236}' | '\u{1F238}'...'\u{1F23A}' |
+        '\u{1F250}'...'\u{1F251}' | '\u{1F300}'...'\u{1F320}' | '\u{1F32D}'...'\u{1F335}' |
+        '\u{1F337}'...'\u{1F37C}' | '\u{1F37E}'...'\u{1F393}' | '\u{1F3A0}'...'\u{1F3CA}' |
+        '\u{1F3CF}'...'\u{1F3D3}' | '\u{1F3E0}'...'\u{1F3F0}' | '\u{1F3F4}' | '\u{1F3F8}'...'\u{1F43E}' |
+        '\u{1F440}' | '\u{1F442}'...'\u{1F4FC}' | '\u{1F4FF}'...'\u{1F53D}' | '\u{1F54B}'...'\u{1F54E}' |
+        '\u{1F550}'...'\u{1F567}' | '\u{1F57A}' | '\u{1F595}'...'\u{1F596}' | '\u{1F5A4}' |
+        '\u{1F5FB}'...'\u{1F64F}' | '\u{1F680}'...'\u{1F6C5}' | '\u{1F6CC}' | '\u{1F6D0}'...'\u{1F6D2}' |
+        '\u{1F6EB}'...'\u{1F6EC}' | '\u{1F6F4}'...'\u{1F6F9}' | '\u{1F910}'...'\u{1F93A}' |
+        '\u{1F93C}'...'\u{1F93E}' | '\u{1F940}'...'\u{1F945}' | '\u{1F947}'...'\u{1F970}' |
+        '\u{1F973}'...'\u{1F976}' | '\u{1F97A}' | '\u{1F97C}'...'\u{1F9A2}' | '\u{1F9B0}'...'\u{1F9B9}' |
+        '\u{1F9C0}'...'\u{1F9C2}' | '\u{1F9D0}'...'\u{1F9FF}' => true,
+        _ => false,
+    }
+}
+
+/// Returns whether `codepoint` continues an emoji sequence, and so must be rendered with the
+/// font of the character before it: presentation selectors, the zero width joiner, the combining
+/// keycap, skin tone modifiers and tags.
+///
+/// <https://unicode.org/reports/tr51/#Emoji_Sequences>
+pub fn continues_emoji_sequence(codepoint: char) -> bool {
+    match codepoint {
+        TEXT_PRESENTATION_SELECTOR | EMOJI_PRESENTATION_SELECTOR | ZERO_WIDTH_JOINER => true,
+        '\u{20E3}' | '\u{1F3FB}'...'\u{1F3FF}' | '\u{E0020}'...'\u{E007F}' => true,
+        _ => false,
+    }
+}
+
+/// Returns whether `codepoint` is set upright rather than sideways in vertical text with
+/// `text-orientation: mixed`, that is whether its `Vertical_Orientation` is `U` or `Tu`. Characters
+/// whose orientation is `Tr` are set sideways, since their vertical alternates are not used.
+///
+/// <https://www.unicode.org/reports/tr50/>
+pub fn is_upright_in_vertical_text(codepoint: char) -> bool {
+    match codepoint {
+        '\u{A7}' | '\u{A9}' | '\u{AE}' | '\u{B1}' | '\u{BC}'...'\u{BE}' | '\u{D7}' | '\u{F7}' |
+        '\u{2EA}'...'\u{2EB}' | '\u{1100}'...'\u{11FF}' | '\u{1401}'...'\u{167F}' |
+        '\u{18B0}'...'\u{18FF}' | '\u{2016}' | '\u{2020}'...'\u{2021}' | '\u{2030}'...'\u{2031}' |
+        '\u{203B}'...'\u{203C}' | '\u{2042}' | '\u{2047}'...'\u{2049}' | '\u{2051}' |
+        '\u{20DD}'...'\u{20E0}' | '\u{20E2}'...'\u{20E4}' | '\u{2100}'...'\u{2101}' |
+        '\u{2103}'...'\u{2109}' | '\u{210F}' | '\u{2113}'...'\u{2114}' | '\u{2116}'...'\u{2117}' |
+        '\u{211E}'...'\u{2123}' | '\u{2125}' | '\u{2127}' | '\u{2129}' | '\u{212E}' |
+        '\u{2135}'...'\u{213F}' | '\u{2145}'...'\u{214A}' | '\u{214C}'...'\u{214D}' |
+        '\u{214F}'...'\u{2189}' | '\u{218C}'...'\u{218F}' | '\u{221E}' | '\u{2234}'...'\u{2235}' |
+        '\u{2300}'...'\u{2307}' | '\u{230C}'...'\u{231F}' | '\u{2324}'...'\u{2328}' | '\u{232B}' |
+        '\u{237D}'...'\u{239A}' | '\u{23BE}'...'\u{23CD}' | '\u{23CF}' | '\u{23D1}'...'\u{23DB}' |
+        '\u{23E2}'...'\u{2422}' | '\u{2424}'...'\u{24FF}' | '\u{25A0}'...'\u{2619}' |
+        '\u{2620}'...'\u{2767}' | '\u{2776}'...'\u{2793}' | '\u{2B12}'...'\u{2B2F}' |
+        '\u{2B50}'...'\u{2B59}' | '\u{2BB8}'...'\u{2BFF}' | '\u{2E80}'...'\u{3007}' |
+        '\u{3012}'...'\u{3013}' | '\u{3020}'...'\u{302F}' | '\u{3031}'...'\u{309F}' |
+        '\u{30A1}'...'\u{30FB}' | '\u{30FD}'...'\u{A4CF}' | '\u{A960}'...'\u{A97F}' |
+        '\u{AC00}'...'\u{D7FF}' | '\u{E000}'...'\u{FAFF}' | '\u{FE10}'...'\u{FE1F}' |
+        '\u{FE30}'...'\u{FE48}' | '\u{FE50}'...'\u{FE57}' | '\u{FE5F}'...'\u{FE62}' | '\u{FE67}'...'\u{FE6F}' |
+        '\u{FF01}'...'\u{FF07}' | '\u{FF0A}'...'\u{FF0C}' | '\u{FF0E}'...'\u{FF19}' | '\u{FF1F}'...'\u{FF3A}' |
+        '\u{FF3C}' | '\u{FF3E}' | '\u{FF40}'...'\u{FF5A}' | '\u{FFE0}'...'\u{FFE2}' |
+        '\u{FFE4}'...'\u{FFE7}' | '\u{1F000}'...'\u{1FAFF}' | '\u{20000}'...'\u{3FFFD}' |
+        '\u{F0000}'...'\u{10FFFD}' => true,
+        _ => false,
+    }
+}
+
 pub fn unicode_plane(codepoint: char) -> u32 {
     (codepoint as u32) >> 16
 }
@@ -10,15 +10,72 @@ use flow::{Flow, GetBaseFlow};
 use fnv::FnvHashMap;
 use ipc_channel::ipc::IpcSender;
 use msg::constellation_msg::PipelineId;
+use net_traits::image::base::Image;
+use net_traits::image_cache::ImageCache;
 use opaque_node::OpaqueNodeMethods;
 use script_traits::{AnimationState, ConstellationControlMsg, LayoutMsg as ConstellationMsg};
 use script_traits::UntrustedNodeAddress;
+use std::sync::Arc;
 use std::sync::mpsc::Receiver;
 use style::animation::{Animation, update_style_for_animation};
 use style::dom::TElement;
 use style::font_metrics::ServoMetricsProvider;
 use style::selector_parser::RestyleDamage;
 use style::timer::Timer;
+use webrender_api::ImageKey;
+
+/// An animated image that has been laid out for a node.
+pub struct AnimatedImage {
+    pub image: Arc<Image>,
+    /// The webrender image that shows the animation of this node, which is separate from the
+    /// image's own so that other nodes showing the image animate independently.
+    pub key: ImageKey,
+    /// The time at which the animation started, in seconds.
+    pub started_at: f64,
+    /// The frame that the webrender image of the animation shows.
+    pub current_frame: usize,
+    /// Whether the image is in the most recently built display list. Only those are animated.
+    pub displayed: bool,
+}
+
+impl AnimatedImage {
+    pub fn new(image: Arc<Image>, key: ImageKey, started_at: f64) -> AnimatedImage {
+        AnimatedImage {
+            image: image,
+            key: key,
+            started_at: started_at,
+            current_frame: 0,
+            displayed: false,
+        }
+    }
+
+    /// Shows the frame of the animation at the given time, and returns whether that changed
+    /// the frame that is shown, and whether the animation is still playing.
+    pub fn tick(&mut self, image_cache: &ImageCache, now: f64) -> (bool, bool) {
+        let (frame, playing) = match self.image.animation {
+            Some(ref animation) => animation.frame_at(((now - self.started_at) * 1000.) as u64),
+            None => return (false, false),
+        };
+        if frame == self.current_frame {
+            return (false, playing);
+        }
+        image_cache.set_webrender_image_frame(&self.image, self.key, frame);
+        self.current_frame = frame;
+        (true, playing)
+    }
+
+    /// Shows the first frame of the animation, which starts over from the given time once it
+    /// is ticked again, and returns whether that changed the frame that is shown.
+    pub fn stop(&mut self, image_cache: &ImageCache, now: f64) -> bool {
+        self.started_at = now;
+        if self.current_frame == 0 {
+            return false;
+        }
+        image_cache.set_webrender_image_frame(&self.image, self.key, 0);
+        self.current_frame = 0;
+        true
+    }
+}
 
 /// Processes any new animations that were discovered after style recalculation.
 /// Also expire any old animations that have completed, inserting them into
@@ -32,6 +89,7 @@ pub fn update_animation_state<E>(
     new_animations_receiver: &Receiver<Animation>,
     pipeline_id: PipelineId,
     timer: &Timer,
+    animated_images_playing: bool,
 )
 where
     E: TElement,
@@ -137,7 +195,7 @@ where
                           .push(new_running_animation)
     }
 
-    let animation_state = if running_animations.is_empty() {
+    let animation_state = if running_animations.is_empty() && !animated_images_playing {
         AnimationState::NoAnimationsPresent
     } else {
         AnimationState::AnimationsPresent
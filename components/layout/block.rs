@@ -34,11 +34,12 @@ use display_list::{DisplayListBuildState, StackingContextCollectionFlags};
 use display_list::StackingContextCollectionState;
 use display_list::items::DisplayListSection;
 use euclid::{Point2D, Rect, SideOffsets2D, Size2D};
-use floats::{ClearType, FloatKind, Floats, PlacementInfo};
+use floats::{ClearType, FloatKind, FloatShape, Floats, PlacementInfo};
 use flow::{BaseFlow, EarlyAbsolutePositionInfo, Flow, FlowClass, ForceNonfloatedFlag, GetBaseFlow};
 use flow::{ImmutableFlowUtils, LateAbsolutePositionInfo, OpaqueFlow, FragmentationContext, FlowFlags};
 use flow_list::FlowList;
 use fragment::{CoordinateSystem, Fragment, FragmentBorderBoxIterator, Overflow, FragmentFlags};
+use fragmentation;
 use gfx_traits::print_tree::PrintTree;
 use incremental::RelayoutMode;
 use layout_debug;
@@ -75,6 +76,10 @@ pub struct FloatedBlockInfo {
 
     /// Left or right?
     pub float_kind: FloatKind,
+
+    /// The float area given by `shape-outside`, if any.
+    #[serde(skip_serializing)]
+    pub shape: Option<Arc<FloatShape>>,
 }
 
 impl FloatedBlockInfo {
@@ -83,6 +88,7 @@ impl FloatedBlockInfo {
             containing_inline_size: Au(0),
             float_ceiling: Au(0),
             float_kind: float_kind,
+            shape: None,
         }
     }
 }
@@ -732,7 +738,7 @@ impl BlockFlow {
     // FIXME: Record enough info to deal with fragmented decorations.
     // See https://drafts.csswg.org/css-break/#break-decoration
     // For borders, this might be `enum FragmentPosition { First, Middle, Last }`
-    fn clone_with_children(&self, new_children: FlowList) -> BlockFlow {
+    pub fn clone_with_children(&self, new_children: FlowList) -> BlockFlow {
         BlockFlow {
             base: self.base.clone_with_children(new_children),
             fragment: self.fragment.clone(),
@@ -815,6 +821,7 @@ impl BlockFlow {
             let mut floats = self.base.floats.clone();
             let thread_id = self.base.thread_id;
             let (mut had_floated_children, mut had_children_with_clearance) = (false, false);
+            let child_count = self.base.children.len();
             for (child_index, kid) in self.base.child_iter_mut().enumerate() {
                 if kid.base().flags.contains(FlowFlags::IS_ABSOLUTELY_POSITIONED) {
                     // Assume that the *hypothetical box* for an absolute flow starts immediately
@@ -841,9 +848,21 @@ impl BlockFlow {
 
                 let previous_b = cur_b;
                 if let Some(ctx) = fragmentation_context {
+                    if !ctx.this_fragment_is_empty && fragmentation::forces_break_before(kid, ctx.kind) {
+                        break_at = Some((child_index, None));
+                        break
+                    }
+
+                    // A child that avoids breaks inside is laid out whole. If it does not fit,
+                    // the check below moves it to the next fragment.
                     let child_ctx = FragmentationContext {
-                        available_block_size: ctx.available_block_size - cur_b,
+                        available_block_size: if fragmentation::avoids_break_inside(kid, ctx.kind) {
+                            MAX_AU
+                        } else {
+                            ctx.available_block_size - cur_b
+                        },
                         this_fragment_is_empty: ctx.this_fragment_is_empty,
+                        kind: ctx.kind,
                     };
                     if let Some(remaining) = kid.fragment(layout_context, Some(child_ctx)) {
                         break_at = Some((child_index + 1, Some(remaining)));
@@ -956,7 +975,12 @@ impl BlockFlow {
                         cur_b = previous_b;
                         break
                     }
-                    ctx.this_fragment_is_empty = false
+                    ctx.this_fragment_is_empty = false;
+                    if child_index + 1 < child_count && fragmentation::forces_break_after(kid, ctx.kind) {
+                        break_at = Some((child_index + 1, None));
+                        cur_b = cur_b - collapse_delta;
+                        break
+                    }
                 }
 
                 // For consecutive collapse-through flows, their top margin should be calculated
@@ -1128,6 +1152,12 @@ impl BlockFlow {
             ceiling: clearance + float_info.float_ceiling,
             max_inline_size: float_info.containing_inline_size,
             kind: float_info.float_kind,
+            // Shapes are only computed in the float's own writing mode.
+            shape: if self.fragment.style.writing_mode == self.base.floats.writing_mode {
+                float_info.shape
+            } else {
+                None
+            },
         };
 
         // Place the float and return the `Floats` back to the parent flow.
@@ -1922,6 +1952,11 @@ impl Flow for BlockFlow {
     fn assign_block_size(&mut self, ctx: &LayoutContext) {
         let remaining = Flow::fragment(self, ctx, None);
         debug_assert!(remaining.is_none());
+
+        if let Some(ref mut float) = self.float {
+            float.shape = FloatShape::new(&self.fragment, float.containing_inline_size, ctx)
+                .map(Arc::new);
+        }
     }
 
     fn fragment(&mut self, layout_context: &LayoutContext,
@@ -18,6 +18,7 @@ use block::BlockFlow;
 use context::{LayoutContext, with_thread_local_font_context};
 use data::{LayoutDataFlags, LayoutData};
 use display_list::items::OpaqueNode;
+use euclid::Size2D;
 use flex::FlexFlow;
 use floats::FloatKind;
 use flow::{AbsoluteDescendants, Flow, FlowClass, GetBaseFlow, ImmutableFlowUtils};
@@ -32,6 +33,7 @@ use inline::{InlineFlow, InlineFragmentNodeInfo, InlineFragmentNodeFlags};
 use linked_list::prepend_from;
 use list_item::{ListItemFlow, ListStyleTypeContent};
 use multicol::{MulticolColumnFlow, MulticolFlow};
+use net_traits::image::base::ImageMetadata;
 use parallel;
 use script_layout_interface::{LayoutElementType, LayoutNodeType, is_image_data};
 use script_layout_interface::wrapper_traits::{PseudoElementType, ThreadSafeLayoutElement, ThreadSafeLayoutNode};
@@ -404,7 +406,8 @@ impl<'a, ConcreteThreadSafeLayoutNode: ThreadSafeLayoutNode>
             }
             Some(LayoutNodeType::Element(LayoutElementType::SVGSVGElement)) => {
                 let data = node.svg_data().unwrap();
-                SpecificFragmentInfo::Svg(Box::new(SvgFragmentInfo::new(data)))
+                let info = SvgFragmentInfo::new(node, data, self.style_context());
+                SpecificFragmentInfo::Svg(Box::new(info))
             }
             _ => {
                 // This includes pseudo-elements.
@@ -1278,9 +1281,18 @@ impl<'a, ConcreteThreadSafeLayoutNode: ThreadSafeLayoutNode>
         let flotation = FloatKind::from_property(flotation);
         let marker_fragments = match node.style(self.style_context()).get_list().list_style_image {
             ImageUrlOrNone::Url(ref url_value) => {
-                let image_info = Box::new(ImageFragmentInfo::new(
-                    url_value.url().map(|u| u.clone()), node, &self.layout_context
-                ));
+                let url = url_value.url().map(|u| u.clone());
+                let mut image_info = Box::new(ImageFragmentInfo::new(url.clone(), node, &self.layout_context));
+                // Vector images are sized with a default object size of 1em square.
+                // <https://drafts.csswg.org/css-lists-3/#image-markers>
+                let font_size = node.style(self.style_context()).get_font().font_size.size().to_f32_px();
+                let default_size = Size2D::new(font_size, font_size);
+                if let Some(size) = url.and_then(|url| self.layout_context.get_vector_image_size(&url, default_size)) {
+                    image_info.metadata = Some(ImageMetadata {
+                        width: size.width.round() as u32,
+                        height: size.height.round() as u32,
+                    });
+                }
                 vec![Fragment::new(node, SpecificFragmentInfo::Image(image_info), self.layout_context)]
             }
             ImageUrlOrNone::None => {
@@ -1407,6 +1419,12 @@ impl<'a, ConcreteThreadSafeLayoutNode: ThreadSafeLayoutNode>
             return false;
         }
 
+        // The contents of an `svg` element are read when its fragment is built, so changes
+        // below it need a new fragment.
+        if node.type_id() == Some(LayoutNodeType::Element(LayoutElementType::SVGSVGElement)) {
+            return false;
+        }
+
         for kid in node.children() {
             if kid.flags().contains(LayoutDataFlags::HAS_NEWLY_CONSTRUCTED_FLOW) {
                 kid.remove_flags(LayoutDataFlags::HAS_NEWLY_CONSTRUCTED_FLOW);
@@ -4,12 +4,17 @@
 
 //! Data needed by the layout thread.
 
+use animation::AnimatedImage;
+use app_units::Au;
+use display_list::PaintedMasks;
 use display_list::items::{WebRenderImageInfo, OpaqueNode};
-use fnv::FnvHasher;
+use euclid::Size2D;
+use fnv::{FnvHashMap, FnvHasher};
 use gfx::font_cache_thread::FontCacheThread;
 use gfx::font_context::FontContext;
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
 use msg::constellation_msg::PipelineId;
+use net_traits::image::base::Image;
 use net_traits::image_cache::{CanRequestImages, ImageCache, ImageState};
 use net_traits::image_cache::{ImageOrMetadataAvailable, UsePlaceholder};
 use opaque_node::OpaqueNodeMethods;
@@ -26,6 +31,7 @@ use std::sync::{Arc, Mutex};
 use std::thread;
 use style::context::RegisteredSpeculativePainter;
 use style::context::SharedStyleContext;
+use webrender_api::ImageKey;
 
 pub type LayoutFontContext = FontContext<FontCacheThread>;
 
@@ -73,6 +79,22 @@ pub struct LayoutContext<'a> {
                                                   WebRenderImageInfo,
                                                   BuildHasherDefault<FnvHasher>>>>,
 
+    /// The animated images that have been laid out, by their node and the key of the image's own
+    /// webrender image.
+    pub animated_images: Arc<RwLock<FnvHashMap<(OpaqueNode, ImageKey), AnimatedImage>>>,
+
+    /// The webrender images that the display lists built from now on no longer show. The layout
+    /// thread deletes them once it has sent the next display list.
+    pub retired_image_keys: Arc<Mutex<Vec<ImageKey>>>,
+
+    /// The image masks of `clip-path` and `mask-*`, which are reused across display lists.
+    pub painted_masks: Arc<Mutex<PaintedMasks>>,
+
+    /// The rasterizations of vector images that the display list being built shows. The image
+    /// cache deletes evicted rasterizations once nothing holds them, so the layout thread keeps
+    /// these alive for as long as that display list is shown.
+    pub drawn_vector_images: Arc<Mutex<Vec<Arc<Image>>>>,
+
     /// Paint worklets
     pub registered_painters: &'a RegisteredPainters,
 
@@ -120,7 +142,12 @@ impl<'a> LayoutContext<'a> {
                                                              use_placeholder,
                                                              can_request);
         match result {
-            Ok(image_or_metadata) => Some(image_or_metadata),
+            Ok(image_or_metadata) => {
+                if let ImageOrMetadataAvailable::ImageAvailable(ref image, _) = image_or_metadata {
+                    self.start_image_animation(node, image);
+                }
+                Some(image_or_metadata)
+            }
             // Image failed to load, so just return nothing
             Err(ImageState::LoadError) => None,
             // Not yet requested - request image or metadata from the cache
@@ -152,6 +179,31 @@ impl<'a> LayoutContext<'a> {
         }
     }
 
+    /// Starts animating an animated image the first time it is laid out for a node.
+    fn start_image_animation(&self, node: OpaqueNode, image: &Arc<Image>) {
+        let image_key = match image.id {
+            Some(image_key) if image.is_animated() => image_key,
+            _ => return,
+        };
+        let mut animated_images = self.animated_images.write();
+        if animated_images.contains_key(&(node, image_key)) {
+            return;
+        }
+        if let Some(key) = self.image_cache.add_webrender_image_animation(image) {
+            let started_at = self.style_context.timer.seconds();
+            animated_images.insert((node, image_key), AnimatedImage::new(image.clone(), key, started_at));
+        }
+    }
+
+    /// Returns the key of the webrender image that shows the given image for the given node,
+    /// which is separate from the image's own if the node animates it.
+    pub fn get_animated_image_key(&self, node: OpaqueNode, image_key: ImageKey) -> ImageKey {
+        self.animated_images
+            .read()
+            .get(&(node, image_key))
+            .map_or(image_key, |animated_image| animated_image.key)
+    }
+
     pub fn get_webrender_image_for_url(&self,
                                        node: OpaqueNode,
                                        url: ServoUrl,
@@ -166,7 +218,9 @@ impl<'a> LayoutContext<'a> {
         match self.get_or_request_image_or_meta(node, url.clone(), use_placeholder) {
             Some(ImageOrMetadataAvailable::ImageAvailable(image, _)) => {
                 let image_info = WebRenderImageInfo::from_image(&*image);
-                if image_info.key.is_none() {
+                // Animated images aren't cached, so that each node that shows them starts its
+                // own animation.
+                if image_info.key.is_none() || image.is_animated() {
                     Some(image_info)
                 } else {
                     let mut webrender_image_cache = self.webrender_image_cache.write();
@@ -178,6 +232,45 @@ impl<'a> LayoutContext<'a> {
             None | Some(ImageOrMetadataAvailable::MetadataAvailable(_)) => None,
         }
     }
+
+    /// Returns the vector image at the given URL rasterized for drawing at the given size, or
+    /// `None` if the URL isn't a loaded vector image.
+    pub fn get_webrender_image_for_url_at_size(&self,
+                                               url: &ServoUrl,
+                                               size: Size2D<Au>)
+                                               -> Option<WebRenderImageInfo> {
+        let device_pixel_ratio = self.style_context.device_pixel_ratio().get();
+        let width = (size.width.to_f32_px() * device_pixel_ratio).round();
+        let height = (size.height.to_f32_px() * device_pixel_ratio).round();
+        if !(width >= 1.0 && height >= 1.0) {
+            return None;
+        }
+        let image = self.image_cache.get_image_at_size(url, width as u32, height as u32)?;
+        let image_info = WebRenderImageInfo::from_image(&*image);
+        self.drawn_vector_images.lock().unwrap().push(image);
+        Some(image_info)
+    }
+
+    /// Returns the size, in CSS pixels, that the vector image at the given URL is drawn at when
+    /// its missing intrinsic dimensions come from the given default object size, or `None` if
+    /// the URL isn't a loaded vector image.
+    /// <https://drafts.csswg.org/css-images-3/#default-sizing>
+    pub fn get_vector_image_size(&self, url: &ServoUrl, default_size: Size2D<f32>) -> Option<Size2D<f32>> {
+        let dimensions = self.image_cache.get_intrinsic_dimensions(url)?;
+        Some(match (dimensions.width, dimensions.height, dimensions.ratio) {
+            (Some(width), Some(height), _) => Size2D::new(width, height),
+            (Some(width), None, Some(ratio)) => Size2D::new(width, width / ratio),
+            (None, Some(height), Some(ratio)) => Size2D::new(height * ratio, height),
+            // Contain the image within the default object size.
+            (None, None, Some(ratio)) if ratio > default_size.width / default_size.height => {
+                Size2D::new(default_size.width, default_size.width / ratio)
+            },
+            (None, None, Some(ratio)) => Size2D::new(default_size.height * ratio, default_size.height),
+            (width, height, None) => {
+                Size2D::new(width.unwrap_or(default_size.width), height.unwrap_or(default_size.height))
+            },
+        })
+    }
 }
 
 /// A registered painter
@@ -20,10 +20,12 @@ use style::computed_values::background_attachment::single_value::T as Background
 use style::computed_values::background_clip::single_value::T as BackgroundClip;
 use style::computed_values::background_origin::single_value::T as BackgroundOrigin;
 use style::computed_values::border_image_outset::T as BorderImageOutset;
+use style::computed_values::mask_clip::single_value::T as MaskClip;
+use style::computed_values::mask_origin::single_value::T as MaskOrigin;
 use style::properties::ComputedValues;
 use style::properties::style_structs::{self, Background};
 use style::values::Either;
-use style::values::computed::{Angle, GradientItem, BackgroundSize as ComputedBackgroundSize};
+use style::values::computed::{Angle, BackgroundRepeat, GradientItem, BackgroundSize as ComputedBackgroundSize};
 use style::values::computed::{LengthOrNumber, LengthOrPercentage, LengthOrPercentageOrAuto};
 use style::values::computed::{NumberOrPercentage, Percentage, Position};
 use style::values::computed::image::{EndingShape, LineDirection};
@@ -218,14 +220,96 @@ pub fn compute_background_placement(
         },
     };
 
-    let mut tile_size = compute_background_image_size(bg_size, bounds.size, intrinsic_size);
+    let (tile_size, tile_spacing) = place_image_layer(
+        &mut bounds,
+        clip_rect,
+        intrinsic_size,
+        bg_size,
+        bg_position_x,
+        bg_position_y,
+        bg_repeat,
+    );
+
+    BackgroundPlacement {
+        bounds,
+        tile_size,
+        tile_spacing,
+        clip_rect,
+        clip_radii,
+        fixed,
+    }
+}
+
+/// Determines where to place a mask image or gradient.
+///
+/// Mask layers are placed like background layers that scroll with the element, but their
+/// painting area does not follow the border radii.
+pub fn compute_mask_placement(
+    svg: &style_structs::SVG,
+    absolute_bounds: Rect<Au>,
+    intrinsic_size: Option<Size2D<Au>>,
+    border: SideOffsets2D<Au>,
+    border_padding: SideOffsets2D<Au>,
+    index: usize,
+) -> BackgroundPlacement {
+    let mask_clip = match *get_cyclic(&svg.mask_clip.0, index) {
+        MaskClip::BorderBox => BackgroundClip::BorderBox,
+        MaskClip::PaddingBox => BackgroundClip::PaddingBox,
+        MaskClip::ContentBox => BackgroundClip::ContentBox,
+    };
+    let (clip_rect, clip_radii) = compute_background_clip(
+        mask_clip,
+        absolute_bounds,
+        border,
+        border_padding,
+        BorderRadius::zero(),
+    );
+
+    let mut bounds = match *get_cyclic(&svg.mask_origin.0, index) {
+        MaskOrigin::BorderBox => absolute_bounds,
+        MaskOrigin::PaddingBox => absolute_bounds.inner_rect(border),
+        MaskOrigin::ContentBox => absolute_bounds.inner_rect(border_padding),
+    };
+
+    let (tile_size, tile_spacing) = place_image_layer(
+        &mut bounds,
+        clip_rect,
+        intrinsic_size,
+        *get_cyclic(&svg.mask_size.0, index),
+        get_cyclic(&svg.mask_position_x.0, index),
+        get_cyclic(&svg.mask_position_y.0, index),
+        get_cyclic(&svg.mask_repeat.0, index),
+    );
+
+    BackgroundPlacement {
+        bounds,
+        tile_size,
+        tile_spacing,
+        clip_rect,
+        clip_radii,
+        fixed: false,
+    }
+}
+
+/// Sizes, positions and tiles an image layer within its positioning area `bounds`, which is
+/// changed to the area covered by the tiles. Returns the tile size and the spacing between tiles.
+fn place_image_layer(
+    bounds: &mut Rect<Au>,
+    clip_rect: Rect<Au>,
+    intrinsic_size: Option<Size2D<Au>>,
+    size: ComputedBackgroundSize,
+    position_x: &LengthOrPercentage,
+    position_y: &LengthOrPercentage,
+    repeat: &BackgroundRepeat,
+) -> (Size2D<Au>, Size2D<Au>) {
+    let mut tile_size = compute_background_image_size(size, bounds.size, intrinsic_size);
 
     let mut tile_spacing = Size2D::zero();
     let own_position = bounds.size - tile_size;
-    let pos_x = bg_position_x.to_used_value(own_position.width);
-    let pos_y = bg_position_y.to_used_value(own_position.height);
+    let pos_x = position_x.to_used_value(own_position.width);
+    let pos_y = position_y.to_used_value(own_position.height);
     tile_image_axis(
-        bg_repeat.0,
+        repeat.0,
         &mut bounds.origin.x,
         &mut bounds.size.width,
         &mut tile_size.width,
@@ -235,7 +319,7 @@ pub fn compute_background_placement(
         clip_rect.size.width,
     );
     tile_image_axis(
-        bg_repeat.1,
+        repeat.1,
         &mut bounds.origin.y,
         &mut bounds.size.height,
         &mut tile_size.height,
@@ -245,14 +329,7 @@ pub fn compute_background_placement(
         clip_rect.size.height,
     );
 
-    BackgroundPlacement {
-        bounds,
-        tile_size,
-        tile_spacing,
-        clip_rect,
-        clip_radii,
-        fixed,
-    }
+    (tile_size, tile_spacing)
 }
 
 fn tile_image_round(
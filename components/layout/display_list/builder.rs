@@ -29,7 +29,9 @@ use display_list::items::{IframeDisplayItem, ImageDisplayItem, LineDisplayItem,
 use display_list::items::{PopAllTextShadowsDisplayItem, PushTextShadowDisplayItem};
 use display_list::items::{RadialGradientBorder, RadialGradientDisplayItem, SolidColorDisplayItem};
 use display_list::items::{StackingContext, StackingContextType, StickyFrameData, TextDisplayItem};
-use display_list::items::{TextOrientation, WebRenderImageInfo};
+use display_list::items::WebRenderImageInfo;
+use display_list::masking::{masking_clips, MaskingClip, PendingMask};
+use display_list::scroll_snap::ScrollSnapFrame;
 use euclid::{rect, Point2D, Rect, SideOffsets2D, Size2D, TypedSize2D, Vector2D};
 use flex::FlexFlow;
 use flow::{BaseFlow, Flow, FlowFlags};
@@ -38,6 +40,7 @@ use fnv::FnvHashMap;
 use fragment::{CanvasFragmentSource, CoordinateSystem, Fragment, ScannedTextFragmentInfo};
 use fragment::SpecificFragmentInfo;
 use gfx::text::TextRun;
+use gfx::text::text_run::GlyphOrientation;
 use gfx::text::glyph::ByteIndex;
 use gfx_traits::{combine_id_with_fragment_type, FragmentType, StackingContextId};
 use inline::{InlineFlow, InlineFragmentNodeFlags};
@@ -45,10 +48,14 @@ use ipc_channel::ipc;
 use list_item::ListItemFlow;
 use model::MaybeAuto;
 use msg::constellation_msg::{BrowsingContextId, PipelineId};
+use multicol::MulticolFlow;
 use net_traits::image_cache::UsePlaceholder;
 use range::Range;
+use script_traits::ScrollSnapInfo;
 use servo_config::opts;
 use servo_geometry::MaxRect;
+use servo_url::ServoUrl;
+use std::collections::HashMap;
 use std::default::Default;
 use std::f32;
 use std::mem;
@@ -70,9 +77,10 @@ use style::values::generics::ui::Cursor;
 use style_traits::CSSPixel;
 use style_traits::ToCss;
 use style_traits::cursor::CursorKind;
+use svg::{SvgClipPath, SvgLayer};
 use table_cell::CollapsedBordersForCell;
 use webrender_api::{self, BorderRadius, BorderSide, BoxShadowClipMode, ColorF, ExternalScrollId};
-use webrender_api::{FilterOp, GlyphInstance, ImageRendering, LayoutRect, LayoutSize};
+use webrender_api::{FilterOp, GlyphInstance, ImageMask, ImageRendering, LayoutRect, LayoutSize};
 use webrender_api::{LayoutTransform, LayoutVector2D, LineStyle, NormalBorder, ScrollSensitivity};
 use webrender_api::StickyOffsetBounds;
 
@@ -226,10 +234,24 @@ pub struct StackingContextCollectionState {
 
     /// The flow parent's content box, used to calculate sticky constraints.
     parent_stacking_relative_content_box: Rect<Au>,
+
+    /// The `clipPath` elements of the document by id, which `clip-path` refers to.
+    svg_clip_paths: HashMap<String, Arc<SvgClipPath>>,
+
+    /// The image masks of ClipScrollNodes, which are painted once all `clipPath` elements are
+    /// known and images can be requested.
+    pending_masks: Vec<(ClipScrollNodeIndex, PendingMask)>,
+
+    /// The size of the viewport, which is the scrollport of the root scroll node.
+    viewport_size: Size2D<Au>,
+
+    /// The scroll containers with CSS scroll snapping, which collect the snap positions of
+    /// their descendants.
+    scroll_snap_frames: Vec<ScrollSnapFrame>,
 }
 
 impl StackingContextCollectionState {
-    pub fn new(pipeline_id: PipelineId) -> StackingContextCollectionState {
+    pub fn new(pipeline_id: PipelineId, viewport_size: Size2D<Au>) -> StackingContextCollectionState {
         let root_clip_indices =
             ClippingAndScrolling::simple(ClipScrollNodeIndex::root_scroll_node());
 
@@ -260,6 +282,65 @@ impl StackingContextCollectionState {
             clip_stack: Vec::new(),
             containing_block_clip_stack: Vec::new(),
             parent_stacking_relative_content_box: Rect::zero(),
+            svg_clip_paths: HashMap::new(),
+            pending_masks: Vec::new(),
+            viewport_size,
+            scroll_snap_frames: Vec::new(),
+        }
+    }
+
+    /// Makes the `clipPath` elements of an `svg` fragment available to `clip-path` references.
+    fn add_svg_clip_paths(&mut self, fragment: &Fragment) {
+        if let SpecificFragmentInfo::Svg(ref svg_fragment_info) = fragment.specific {
+            for (id, clip_path) in svg_fragment_info.scene.clip_paths() {
+                self.svg_clip_paths.entry(id.clone()).or_insert_with(|| clip_path.clone());
+            }
+        }
+    }
+
+    /// Makes the viewport snap to the `scroll-snap-coordinate` of its descendants if the root
+    /// element has a `scroll-snap-type`.
+    fn add_root_scroll_snap_frame(&mut self, style: &ComputedValues) {
+        let scrollport = Rect::new(Point2D::zero(), self.viewport_size);
+        let scroll_id = self.pipeline_id.root_scroll_id();
+        let root_scroll_node = ClipScrollNodeIndex::root_scroll_node();
+        if let Some(frame) = ScrollSnapFrame::new(style, root_scroll_node, scroll_id, scrollport) {
+            self.scroll_snap_frames.push(frame);
+        }
+    }
+
+    /// Adds the `scroll-snap-coordinate` of a fragment to the snap positions of its nearest
+    /// scroll container, if that scroll container snaps.
+    fn add_scroll_snap_coordinates(
+        &mut self,
+        style: &ComputedValues,
+        border_box: &Rect<Au>,
+        scrolling: ClipScrollNodeIndex,
+    ) {
+        if self.scroll_snap_frames.is_empty() ||
+            style.get_box().scroll_snap_coordinate.0.is_empty()
+        {
+            return;
+        }
+
+        let root_scroll_node = ClipScrollNodeIndex::root_scroll_node();
+        let mut index = scrolling;
+        while index.to_index() > root_scroll_node.to_index() {
+            let node = &self.clip_scroll_nodes[index.to_index()];
+            if let ClipScrollNodeType::ScrollFrame(..) = node.node_type {
+                break;
+            }
+            index = node.parent_index;
+        }
+        if index.to_index() < root_scroll_node.to_index() {
+            index = root_scroll_node;
+        }
+
+        let frame = self.scroll_snap_frames
+            .iter_mut()
+            .find(|frame| frame.clip_scroll_node == index);
+        if let Some(frame) = frame {
+            frame.add_snap_coordinates(style, border_box);
         }
     }
 
@@ -356,6 +437,12 @@ pub struct DisplayListBuildState<'a> {
 
     /// Stores text runs to answer text queries used to place a cursor inside text.
     pub indexable_text: IndexableText,
+
+    /// The `clipPath` elements of the document by id, which `clip-path` refers to.
+    svg_clip_paths: HashMap<String, Arc<SvgClipPath>>,
+
+    /// The scroll snap points of the scroll containers with CSS scroll snapping.
+    pub scroll_snap_info: Vec<ScrollSnapInfo>,
 }
 
 impl<'a> DisplayListBuildState<'a> {
@@ -363,18 +450,31 @@ impl<'a> DisplayListBuildState<'a> {
         layout_context: &'a LayoutContext,
         state: StackingContextCollectionState,
     ) -> DisplayListBuildState<'a> {
+        let mut clip_scroll_nodes = state.clip_scroll_nodes;
+        for (index, mask) in state.pending_masks {
+            let (clip, image_mask) = mask.paint(layout_context, &state.svg_clip_paths);
+            let node = &mut clip_scroll_nodes[index.to_index()];
+            node.clip = clip;
+            node.image_mask = image_mask;
+        }
+
         DisplayListBuildState {
             layout_context: layout_context,
             root_stacking_context: state.root_stacking_context,
             items: FnvHashMap::default(),
             stacking_context_info: state.stacking_context_info,
-            clip_scroll_nodes: state.clip_scroll_nodes,
+            clip_scroll_nodes: clip_scroll_nodes,
             processing_scrolling_overflow_element: false,
             current_stacking_context_id: StackingContextId::root(),
             current_clipping_and_scrolling:
                 ClippingAndScrolling::simple(ClipScrollNodeIndex::root_scroll_node()),
             iframe_sizes: Vec::new(),
             indexable_text: IndexableText::default(),
+            svg_clip_paths: state.svg_clip_paths,
+            scroll_snap_info: state.scroll_snap_frames
+                .into_iter()
+                .map(ScrollSnapFrame::into_info)
+                .collect(),
         }
     }
 
@@ -432,11 +532,19 @@ impl<'a> DisplayListBuildState<'a> {
     fn add_late_clip_node(&mut self, rect: LayoutRect, radii: BorderRadius) -> ClipScrollNodeIndex {
         let mut clip = ClippingRegion::from_rect(rect);
         clip.intersect_with_rounded_rect(rect, radii);
+        self.add_late_clip_node_with_mask(clip, None)
+    }
 
+    fn add_late_clip_node_with_mask(
+        &mut self,
+        clip: ClippingRegion,
+        image_mask: Option<ImageMask>,
+    ) -> ClipScrollNodeIndex {
         let node = ClipScrollNode {
             parent_index: self.current_clipping_and_scrolling.scrolling,
             clip,
             content_rect: LayoutRect::zero(), // content_rect isn't important for clips.
+            image_mask,
             node_type: ClipScrollNodeType::Clip,
         };
 
@@ -955,6 +1063,7 @@ impl FragmentDisplayListBuilding for Fragment {
                                 display_list_section,
                                 absolute_bounds,
                                 webrender_image,
+                                Some(url),
                                 i,
                             );
                         }
@@ -987,6 +1096,7 @@ impl FragmentDisplayListBuilding for Fragment {
                             display_list_section,
                             absolute_bounds,
                             webrender_image,
+                            None,
                             i,
                         );
                     }
@@ -1008,6 +1118,7 @@ impl FragmentDisplayListBuilding for Fragment {
         display_list_section: DisplayListSection,
         absolute_bounds: Rect<Au>,
         webrender_image: WebRenderImageInfo,
+        url: Option<&ServoUrl>,
         index: usize,
     ) {
         debug!("(building display list) building background image");
@@ -1030,6 +1141,20 @@ impl FragmentDisplayListBuilding for Fragment {
             index,
         );
 
+        // Vector images are rasterized at the size of a tile, rather than scaled.
+        let key = url
+            .and_then(|url| {
+                state
+                    .layout_context
+                    .get_webrender_image_for_url_at_size(url, placement.tile_size)
+            })
+            .and_then(|image| image.key)
+            .or_else(|| {
+                webrender_image
+                    .key
+                    .map(|key| state.layout_context.get_animated_image_key(self.node, key))
+            });
+
         state.clipping_and_scrolling_scope(|state| {
             if !placement.clip_radii.is_zero() {
                 let clip_id =
@@ -1049,7 +1174,7 @@ impl FragmentDisplayListBuilding for Fragment {
             debug!("(building display list) adding background image.");
             state.add_display_item(DisplayItem::Image(Box::new(ImageDisplayItem {
                 base: base,
-                id: webrender_image.key.unwrap(),
+                id: key.unwrap(),
                 stretch_size: placement.tile_size.to_layout(),
                 tile_spacing: placement.tile_spacing.to_layout(),
                 image_rendering: style.get_inherited_box().image_rendering.to_layout(),
@@ -1756,7 +1881,9 @@ impl FragmentDisplayListBuilding for Fragment {
         let stacking_relative_content_box =
             self.stacking_relative_content_box(stacking_relative_border_box);
 
-        let create_base_display_item = |state: &mut DisplayListBuildState| {
+        let create_base_display_item = |state: &mut DisplayListBuildState,
+                                        bounds: Rect<Au>,
+                                        clip_rect: Rect<Au>| {
             // Adjust the clipping region as necessary to account for `border-radius`.
             let radii =
                 build_border_radius_for_inner_rect(stacking_relative_border_box, &self.style);
@@ -1768,8 +1895,8 @@ impl FragmentDisplayListBuilding for Fragment {
             }
 
             state.create_base_display_item(
-                stacking_relative_content_box,
-                stacking_relative_border_box,
+                bounds,
+                clip_rect,
                 self.node,
                 self.style.get_cursor(CursorKind::Default),
                 DisplayListSection::Content,
@@ -1833,8 +1960,7 @@ impl FragmentDisplayListBuilding for Fragment {
             SpecificFragmentInfo::InlineBlock(_) |
             SpecificFragmentInfo::InlineAbsoluteHypothetical(_) |
             SpecificFragmentInfo::InlineAbsolute(_) |
-            SpecificFragmentInfo::TruncatedFragment(_) |
-            SpecificFragmentInfo::Svg(_) => {
+            SpecificFragmentInfo::TruncatedFragment(_) => {
                 if opts::get().show_debug_fragment_borders {
                     self.build_debug_borders_around_fragment(
                         state,
@@ -1854,7 +1980,11 @@ impl FragmentDisplayListBuilding for Fragment {
                         None => return warn!("No pipeline id for iframe {}.", browsing_context_id),
                     };
 
-                    let base = create_base_display_item(state);
+                    let base = create_base_display_item(
+                        state,
+                        stacking_relative_content_box,
+                        stacking_relative_border_box,
+                    );
                     let item = DisplayItem::Iframe(Box::new(IframeDisplayItem {
                         base,
                         iframe: pipeline_id,
@@ -1871,12 +2001,30 @@ impl FragmentDisplayListBuilding for Fragment {
             SpecificFragmentInfo::Image(ref image_fragment) => {
                 // Place the image into the display list.
                 if let Some(ref image) = image_fragment.image {
-                    if let Some(id) = image.id {
-                        let base = create_base_display_item(state);
+                    let object_rect = self.object_fit_rect(stacking_relative_content_box);
+                    let size = object_rect.size;
+                    let id = image_fragment
+                        .url
+                        .as_ref()
+                        .and_then(|url| {
+                            state.layout_context.get_webrender_image_for_url_at_size(url, size)
+                        })
+                        .and_then(|image| image.key)
+                        .or_else(|| {
+                            image.id.map(|key| {
+                                state.layout_context.get_animated_image_key(self.node, key)
+                            })
+                        });
+                    if let Some(id) = id {
+                        let base = create_base_display_item(
+                            state,
+                            object_rect,
+                            stacking_relative_content_box,
+                        );
                         state.add_display_item(DisplayItem::Image(Box::new(ImageDisplayItem {
                             base,
                             id,
-                            stretch_size: stacking_relative_content_box.size.to_layout(),
+                            stretch_size: size.to_layout(),
                             tile_spacing: LayoutSize::zero(),
                             image_rendering: self.style
                                 .get_inherited_box()
@@ -1886,6 +2034,50 @@ impl FragmentDisplayListBuilding for Fragment {
                     }
                 }
             },
+            SpecificFragmentInfo::Svg(ref svg_fragment_info) => {
+                if stacking_relative_content_box.is_empty() {
+                    return;
+                }
+                let size = Size2D::new(stacking_relative_content_box.size.width.to_f32_px(),
+                                       stacking_relative_content_box.size.height.to_f32_px());
+                let layers = svg_fragment_info.scene.layers(state.layout_context, size);
+                let base = create_base_display_item(
+                    state,
+                    stacking_relative_content_box,
+                    stacking_relative_border_box,
+                );
+                for layer in layers.iter() {
+                    let item = match *layer {
+                        SvgLayer::Image(id) => DisplayItem::Image(Box::new(ImageDisplayItem {
+                            base: base.clone(),
+                            id,
+                            stretch_size: stacking_relative_content_box.size.to_layout(),
+                            tile_spacing: LayoutSize::zero(),
+                            image_rendering: ImageRendering::Auto,
+                        })),
+                        SvgLayer::Text(ref text) => {
+                            let glyphs = convert_text_run_to_glyphs(
+                                text.run.clone(),
+                                text.range,
+                                stacking_relative_content_box.origin + text.baseline_origin.to_vector(),
+                            );
+                            for item in text_display_items(&base, &text.run, glyphs, text.color.to_layout()) {
+                                state.add_display_item(item);
+                            }
+                            continue;
+                        },
+                    };
+                    state.add_display_item(item);
+                }
+
+                if opts::get().show_debug_fragment_borders {
+                    self.build_debug_borders_around_fragment(
+                        state,
+                        stacking_relative_border_box,
+                        clip,
+                    );
+                }
+            },
             SpecificFragmentInfo::Canvas(ref canvas_fragment_info) => {
                 let image_key = match canvas_fragment_info.source {
                     CanvasFragmentSource::WebGL(image_key) => image_key,
@@ -1905,11 +2097,12 @@ impl FragmentDisplayListBuilding for Fragment {
                     },
                 };
 
-                let base = create_base_display_item(state);
+                let object_rect = self.object_fit_rect(stacking_relative_content_box);
+                let base = create_base_display_item(state, object_rect, stacking_relative_content_box);
                 let display_item = DisplayItem::Image(Box::new(ImageDisplayItem {
                     base,
                     id: image_key,
-                    stretch_size: stacking_relative_content_box.size.to_layout(),
+                    stretch_size: object_rect.size.to_layout(),
                     tile_spacing: LayoutSize::zero(),
                     image_rendering: ImageRendering::Auto,
                 }));
@@ -1985,6 +2178,11 @@ impl FragmentDisplayListBuilding for Fragment {
         text_shadows: &[SimpleShadow],
         clip: Rect<Au>,
     ) {
+        // Text waiting for a web font in its block period is laid out, but not painted.
+        if text_fragment.run.invisible {
+            return
+        }
+
         // NB: The order for painting text components (CSS Text Decoration Module Level 3) is:
         // shadows, underline, overline, text, text-emphasis, and then line-through.
 
@@ -1996,13 +2194,11 @@ impl FragmentDisplayListBuilding for Fragment {
             self.style().get_color().color
         };
 
-        // Determine the orientation and cursor to use.
-        let (_orientation, cursor) = if self.style.writing_mode.is_vertical() {
-            // TODO: Distinguish between 'sideways-lr' and 'sideways-rl' writing modes in CSS
-            // Writing Modes Level 4.
-            (TextOrientation::SidewaysRight, CursorKind::VerticalText)
+        // Determine the cursor to use.
+        let cursor = if self.style.writing_mode.is_vertical() {
+            CursorKind::VerticalText
         } else {
-            (TextOrientation::Upright, CursorKind::Text)
+            CursorKind::Text
         };
 
         // Compute location of the baseline.
@@ -2010,10 +2206,14 @@ impl FragmentDisplayListBuilding for Fragment {
         // FIXME(pcwalton): Get the real container size.
         let container_size = Size2D::zero();
         let metrics = &text_fragment.run.font_metrics;
-        let baseline_origin = stacking_relative_content_box.origin +
-            LogicalPoint::new(self.style.writing_mode, Au(0), metrics.ascent)
-                .to_physical(self.style.writing_mode, container_size)
-                .to_vector();
+        let baseline_origin = if self.style.writing_mode.is_vertical() {
+            vertical_baseline_origin(text_fragment, &stacking_relative_content_box)
+        } else {
+            stacking_relative_content_box.origin +
+                LogicalPoint::new(self.style.writing_mode, Au(0), metrics.ascent)
+                    .to_physical(self.style.writing_mode, container_size)
+                    .to_vector()
+        };
 
         // Base item for all text/shadows
         let base = state.create_base_display_item(
@@ -2078,11 +2278,27 @@ impl FragmentDisplayListBuilding for Fragment {
         }
 
         // Text
-        let glyphs = convert_text_run_to_glyphs(
+        let mut glyphs = convert_text_run_to_glyphs(
             text_fragment.run.clone(),
             text_fragment.range,
             baseline_origin,
         );
+
+        // The hyphen of a line broken at a soft hyphen.
+        if let Some((hyphen_glyph, _, offset)) = text_fragment.run.hyphen_glyph {
+            if text_fragment.hyphenated() {
+                let advance = text_fragment.run.advance_for_range(&text_fragment.range);
+                let pen_position = match text_fragment.run.orientation {
+                    GlyphOrientation::Horizontal => baseline_origin + Vector2D::new(advance, Au(0)),
+                    GlyphOrientation::Upright |
+                    GlyphOrientation::Sideways => baseline_origin + Vector2D::new(Au(0), advance),
+                };
+                glyphs.push(GlyphInstance {
+                    index: hyphen_glyph,
+                    point: (pen_position + offset.to_vector()).to_layout(),
+                });
+            }
+        }
         if !glyphs.is_empty() {
             let indexable_text = IndexableTextItem {
                 origin: stacking_relative_content_box.origin,
@@ -2092,12 +2308,9 @@ impl FragmentDisplayListBuilding for Fragment {
             };
             state.indexable_text.insert(self.node, indexable_text);
 
-            state.add_display_item(DisplayItem::Text(Box::new(TextDisplayItem {
-                base: base.clone(),
-                glyphs: glyphs,
-                font_key: text_fragment.run.font_key,
-                text_color: text_color.to_layout(),
-            })));
+            for item in text_display_items(&base, &text_fragment.run, glyphs, text_color.to_layout()) {
+                state.add_display_item(item);
+            }
         }
 
         // TODO(#17715): emit text-emphasis marks here.
@@ -2203,6 +2416,11 @@ pub trait BlockFlowDisplayListBuilding {
         state: &mut StackingContextCollectionState,
         border_box: Rect<Au>,
     );
+    fn setup_clip_scroll_nodes_for_masking(
+        &mut self,
+        state: &mut StackingContextCollectionState,
+        border_box: Rect<Au>,
+    );
     fn setup_clip_scroll_node_for_css_clip(
         &mut self,
         state: &mut StackingContextCollectionState,
@@ -2410,6 +2628,10 @@ impl BlockFlowDisplayListBuilding for BlockFlow {
         flags: StackingContextCollectionFlags,
     ) {
         let mut preserved_state = SavedStackingContextCollectionState::new(state);
+        state.add_svg_clip_paths(&self.fragment);
+        if self.is_root() {
+            state.add_root_scroll_snap_frame(&self.fragment.style);
+        }
 
         let stacking_context_type = self.stacking_context_type(flags);
         self.base.stacking_context_id = match stacking_context_type {
@@ -2443,6 +2665,13 @@ impl BlockFlowDisplayListBuilding for BlockFlow {
             flags,
         );
 
+        let border_box = self.stacking_relative_border_box(CoordinateSystem::Parent);
+        state.add_scroll_snap_coordinates(
+            &self.fragment.style,
+            &border_box,
+            containing_clipping_and_scrolling.scrolling,
+        );
+
         if establishes_containing_block_for_absolute(
             flags,
             self.positioning(),
@@ -2521,6 +2750,7 @@ impl BlockFlowDisplayListBuilding for BlockFlow {
 
         if !flags.contains(StackingContextCollectionFlags::NEVER_CREATES_CLIP_SCROLL_NODE) {
             self.setup_clip_scroll_node_for_position(state, stacking_relative_border_box);
+            self.setup_clip_scroll_nodes_for_masking(state, stacking_relative_border_box);
             self.setup_clip_scroll_node_for_overflow(state, stacking_relative_border_box);
             self.setup_clip_scroll_node_for_css_clip(
                 state,
@@ -2626,6 +2856,7 @@ impl BlockFlowDisplayListBuilding for BlockFlow {
             parent_index: self.clipping_and_scrolling().scrolling,
             clip: ClippingRegion::from_rect(border_box.to_layout()),
             content_rect: LayoutRect::zero(),
+            image_mask: None,
             node_type: ClipScrollNodeType::StickyFrame(sticky_frame_data),
         });
 
@@ -2684,14 +2915,57 @@ impl BlockFlowDisplayListBuilding for BlockFlow {
             parent_index: self.clipping_and_scrolling().scrolling,
             clip: clip,
             content_rect: Rect::new(content_box.origin, content_size).to_layout(),
+            image_mask: None,
             node_type: ClipScrollNodeType::ScrollFrame(sensitivity, external_id),
         });
 
+        let snap_frame = ScrollSnapFrame::new(
+            &self.fragment.style,
+            new_clip_scroll_index,
+            external_id,
+            clip_rect,
+        );
+        if let Some(snap_frame) = snap_frame {
+            state.scroll_snap_frames.push(snap_frame);
+        }
+
         let new_clipping_and_scrolling = ClippingAndScrolling::simple(new_clip_scroll_index);
         self.base.clipping_and_scrolling = Some(new_clipping_and_scrolling);
         state.current_clipping_and_scrolling = new_clipping_and_scrolling;
     }
 
+    /// Adds clip scroll nodes for a block to take the `clip-path` and `mask-*` properties into
+    /// account. They are added before the scroll root for overflow, so that they also clip the
+    /// background and borders of the block.
+    fn setup_clip_scroll_nodes_for_masking(
+        &mut self,
+        state: &mut StackingContextCollectionState,
+        border_box: Rect<Au>,
+    ) {
+        for masking_clip in masking_clips(&self.fragment, border_box) {
+            let (clip, pending_mask) = match masking_clip {
+                MaskingClip::Region(clip) => (clip, None),
+                // The clip of an image mask is only known once the mask is painted.
+                MaskingClip::Mask(mask) => (ClippingRegion::max(), Some(mask)),
+            };
+
+            let new_index = state.add_clip_scroll_node(ClipScrollNode {
+                parent_index: self.clipping_and_scrolling().scrolling,
+                clip: clip,
+                content_rect: LayoutRect::zero(), // content_rect isn't important for clips.
+                image_mask: None,
+                node_type: ClipScrollNodeType::Clip,
+            });
+            if let Some(mask) = pending_mask {
+                state.pending_masks.push((new_index, mask));
+            }
+
+            let new_clipping_and_scrolling = ClippingAndScrolling::simple(new_index);
+            self.base.clipping_and_scrolling = Some(new_clipping_and_scrolling);
+            state.current_clipping_and_scrolling = new_clipping_and_scrolling;
+        }
+    }
+
     /// Adds a scroll root for a block to take the `clip` property into account
     /// per CSS 2.1 § 11.1.2.
     fn setup_clip_scroll_node_for_css_clip(
@@ -2736,6 +3010,7 @@ impl BlockFlowDisplayListBuilding for BlockFlow {
             parent_index: self.clipping_and_scrolling().scrolling,
             clip: ClippingRegion::from_rect(clip_rect.to_layout()),
             content_rect: LayoutRect::zero(), // content_rect isn't important for clips.
+            image_mask: None,
             node_type: ClipScrollNodeType::Clip,
         });
 
@@ -2922,6 +3197,7 @@ impl InlineFlowDisplayListBuilding for InlineFlow {
             // We clear this here, but it might be set again if we create a stacking context for
             // this fragment.
             fragment.established_reference_frame = None;
+            state.add_svg_clip_paths(fragment);
 
             if !fragment.collect_stacking_contexts_for_blocklike_fragment(state) {
                 if !fragment.establishes_stacking_context() {
@@ -2945,6 +3221,29 @@ impl InlineFlowDisplayListBuilding for InlineFlow {
         let fragment = self.fragments.fragments.get_mut(index).unwrap();
         let stacking_relative_border_box = self.base
             .stacking_relative_border_box_for_display_list(fragment);
+
+        // Block-like fragments are clipped by the `clip-path` and `mask` of the block flow they
+        // contain, while other fragments only clip themselves, so their clips are added late.
+        //
+        // TODO: Support `clip-path` and `mask` on inline boxes that are split into several
+        // fragments.
+        let masking_clips = match fragment.specific {
+            SpecificFragmentInfo::InlineBlock(_) |
+            SpecificFragmentInfo::InlineAbsoluteHypothetical(_) |
+            SpecificFragmentInfo::InlineAbsolute(_) |
+            SpecificFragmentInfo::TruncatedFragment(_) => vec![],
+            _ => masking_clips(fragment, stacking_relative_border_box),
+        };
+        let previous_clipping_and_scrolling = state.current_clipping_and_scrolling;
+        for masking_clip in masking_clips {
+            let (clip, image_mask) = match masking_clip {
+                MaskingClip::Region(clip) => (clip, None),
+                MaskingClip::Mask(mask) => mask.paint(state.layout_context, &state.svg_clip_paths),
+            };
+            let clip_id = state.add_late_clip_node_with_mask(clip, image_mask);
+            state.current_clipping_and_scrolling = ClippingAndScrolling::simple(clip_id);
+        }
+
         fragment.build_display_list(
             state,
             stacking_relative_border_box,
@@ -2952,6 +3251,7 @@ impl InlineFlowDisplayListBuilding for InlineFlow {
             DisplayListSection::Content,
             self.base.clip,
         );
+        state.current_clipping_and_scrolling = previous_clipping_and_scrolling;
     }
 
     fn build_display_list_for_inline(&mut self, state: &mut DisplayListBuildState) {
@@ -3029,6 +3329,80 @@ impl FlexFlowDisplayListBuilding for FlexFlow {
     }
 }
 
+pub trait MulticolFlowDisplayListBuilding {
+    fn build_display_list_for_multicol(&mut self, state: &mut DisplayListBuildState);
+}
+
+impl MulticolFlowDisplayListBuilding for MulticolFlow {
+    fn build_display_list_for_multicol(&mut self, state: &mut DisplayListBuildState) {
+        use style::computed_values::column_rule_style::T as ColumnRuleStyle;
+
+        self.block_flow
+            .build_display_list_for_block(state, BorderPaintingMode::Separate);
+
+        // Draw the column rules on top of the background and borders, between each pair of
+        // adjacent columns in a row.
+        let style = self.block_flow.fragment.style.clone();
+        if style.get_inherited_box().visibility != Visibility::Visible {
+            return;
+        }
+        let column_style = style.get_column();
+        let rule_style = match column_style.column_rule_style {
+            ColumnRuleStyle::None | ColumnRuleStyle::Hidden => return,
+            ColumnRuleStyle::Dotted => BorderStyle::Dotted,
+            ColumnRuleStyle::Dashed => BorderStyle::Dashed,
+            ColumnRuleStyle::Solid => BorderStyle::Solid,
+            ColumnRuleStyle::Double => BorderStyle::Double,
+            ColumnRuleStyle::Groove => BorderStyle::Groove,
+            ColumnRuleStyle::Ridge => BorderStyle::Ridge,
+            ColumnRuleStyle::Inset => BorderStyle::Inset,
+            ColumnRuleStyle::Outset => BorderStyle::Outset,
+        };
+        let rule_width = Au::from(column_style.column_rule_width);
+        if rule_width == Au(0) {
+            return;
+        }
+        let color = style.resolve_color(column_style.column_rule_color).to_layout();
+
+        let writing_mode = self.block_flow.base.writing_mode;
+        let stacking_relative_border_box = self.block_flow
+            .base
+            .stacking_relative_border_box_for_display_list(&self.block_flow.fragment);
+        let border_widths = if writing_mode.is_vertical() {
+            SideOffsets2D::new(rule_width, Au(0), Au(0), Au(0))
+        } else {
+            SideOffsets2D::new(Au(0), Au(0), Au(0), rule_width)
+        };
+
+        // Rules are centered in the gaps, which end one pitch apart.
+        let first_rule_start = self.block_flow.fragment.border_padding.inline_start +
+            self.column_pitch - (self.column_gap + rule_width) / 2;
+        for row in &self.column_rows {
+            for i in 1..row.column_count {
+                let rule = LogicalRect::new(
+                    writing_mode,
+                    first_rule_start + self.column_pitch * (i as i32 - 1),
+                    row.block_start,
+                    rule_width,
+                    row.block_size,
+                ).to_physical(writing_mode, stacking_relative_border_box.size);
+                let base = state.create_base_display_item(
+                    rule.translate(&stacking_relative_border_box.origin.to_vector()),
+                    self.block_flow.base.clip,
+                    self.block_flow.fragment.node,
+                    style.get_cursor(CursorKind::Default),
+                    self.block_flow.background_border_section(),
+                );
+                state.add_display_item(DisplayItem::Border(Box::new(BorderDisplayItem {
+                    base: base,
+                    border_widths: border_widths.to_layout(),
+                    details: BorderDetails::Normal(simple_normal_border(color, rule_style.to_layout())),
+                })));
+            }
+        }
+    }
+}
+
 trait BaseFlowDisplayListBuilding {
     fn build_display_items_for_debugging_tint(
         &self,
@@ -3136,6 +3510,29 @@ pub enum BorderPaintingMode<'a> {
     Hidden,
 }
 
+/// Returns the position of the start of the baseline of a text fragment in a vertical line.
+/// Upright glyphs are centered on it, while sideways and combined text is centered in the line.
+fn vertical_baseline_origin(text_fragment: &ScannedTextFragmentInfo, content_box: &Rect<Au>) -> Point2D<Au> {
+    let metrics = &text_fragment.run.font_metrics;
+    let center_x = content_box.origin.x + content_box.size.width / 2;
+    match text_fragment.run.orientation {
+        GlyphOrientation::Upright => Point2D::new(center_x, content_box.origin.y),
+        // The ascent of rotated glyphs is on the right of the baseline.
+        GlyphOrientation::Sideways => {
+            Point2D::new(center_x - (metrics.ascent - metrics.descent) / 2, content_box.origin.y)
+        }
+        // Combined text is laid out horizontally in the middle of its em.
+        GlyphOrientation::Horizontal => {
+            let advance = text_fragment.run.advance_for_range(&text_fragment.range);
+            let em = text_fragment.run.actual_pt_size;
+            Point2D::new(
+                center_x - advance / 2,
+                content_box.origin.y + (em - metrics.ascent - metrics.descent) / 2 + metrics.ascent,
+            )
+        }
+    }
+}
+
 fn convert_text_run_to_glyphs(
     text_run: Arc<TextRun>,
     range: Range<ByteIndex>,
@@ -3152,19 +3549,77 @@ fn convert_text_run_to_glyphs(
             };
             if !slice.glyphs.is_whitespace() {
                 let glyph_offset = glyph.offset().unwrap_or(Point2D::zero());
-                let point = origin + glyph_offset.to_vector();
+                let point = match text_run.orientation {
+                    GlyphOrientation::Horizontal |
+                    GlyphOrientation::Upright => origin + glyph_offset.to_vector(),
+                    // Sideways glyphs are rotated a quarter turn clockwise, and so are their offsets.
+                    GlyphOrientation::Sideways => origin + Vector2D::new(-glyph_offset.y, glyph_offset.x),
+                };
                 let glyph = GlyphInstance {
                     index: glyph.id(),
                     point: point.to_layout(),
                 };
                 glyphs.push(glyph);
             }
-            origin.x += glyph_advance;
+            match text_run.orientation {
+                GlyphOrientation::Horizontal => origin.x += glyph_advance,
+                GlyphOrientation::Upright | GlyphOrientation::Sideways => origin.y += glyph_advance,
+            }
         }
     }
     return glyphs;
 }
 
+/// Returns the items painting `glyphs` of `text_run`. The layered color glyphs of its font are
+/// painted as their layers, each in its own color.
+fn text_display_items(
+    base: &BaseDisplayItem,
+    text_run: &TextRun,
+    glyphs: Vec<GlyphInstance>,
+    text_color: ColorF,
+) -> Vec<DisplayItem> {
+    let text_item = |glyphs: Vec<GlyphInstance>, text_color: ColorF| {
+        DisplayItem::Text(Box::new(TextDisplayItem {
+            base: base.clone(),
+            glyphs,
+            font_key: text_run.font_key,
+            text_color,
+        }))
+    };
+
+    if glyphs.is_empty() {
+        return vec![]
+    }
+    let color_glyphs = match text_run.color_glyphs {
+        Some(ref color_glyphs) => color_glyphs,
+        None => return vec![text_item(glyphs, text_color)],
+    };
+
+    // Consecutive glyphs painted in the same color share an item.
+    let mut colored_glyphs: Vec<(ColorF, Vec<GlyphInstance>)> = vec![];
+    for glyph in glyphs {
+        let layers = match color_glyphs.layers(glyph.index) {
+            Some(layers) => layers.iter().map(|layer| {
+                let layer_glyph = GlyphInstance {
+                    index: layer.glyph,
+                    point: glyph.point,
+                };
+                (layer.color.unwrap_or(text_color), layer_glyph)
+            }).collect(),
+            None => vec![(text_color, glyph)],
+        };
+        for (color, glyph) in layers {
+            let same_color = colored_glyphs.last().map_or(false, |&(last_color, _)| last_color == color);
+            if same_color {
+                colored_glyphs.last_mut().unwrap().1.push(glyph);
+            } else {
+                colored_glyphs.push((color, vec![glyph]));
+            }
+        }
+    }
+    colored_glyphs.into_iter().map(|(color, glyphs)| text_item(glyphs, color)).collect()
+}
+
 pub struct IndexableTextItem {
     /// The placement of the text item on the plane.
     pub origin: Point2D<Au>,
@@ -3197,10 +3652,14 @@ impl IndexableText {
         // TODO(#20020): access all elements
         let point = point_in_item + item[0].origin.to_vector();
         let offset = point - item[0].baseline_origin;
+        let advance = match item[0].text_run.orientation {
+            GlyphOrientation::Horizontal => offset.x,
+            GlyphOrientation::Upright | GlyphOrientation::Sideways => offset.y,
+        };
         Some(
             item[0]
                 .text_run
-                .range_index_of_advance(&item[0].range, offset.x),
+                .range_index_of_advance(&item[0].range, advance),
         )
     }
 }
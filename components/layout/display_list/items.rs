@@ -24,7 +24,7 @@ use std::f32;
 use std::fmt;
 use webrender_api::{BorderRadius, BorderWidths, BoxShadowClipMode, ClipMode, ColorF};
 use webrender_api::{ComplexClipRegion, ExtendMode, ExternalScrollId, FilterOp, FontInstanceKey};
-use webrender_api::{GlyphInstance, GradientStop, ImageKey, ImageRendering, LayoutPoint};
+use webrender_api::{GlyphInstance, GradientStop, ImageKey, ImageMask, ImageRendering, LayoutPoint};
 use webrender_api::{LayoutRect, LayoutSize, LayoutTransform, LayoutVector2D, LineStyle};
 use webrender_api::{MixBlendMode, NinePatchBorder, NormalBorder, ScrollSensitivity, Shadow};
 use webrender_api::{StickyOffsetBounds, TransformStyle};
@@ -351,6 +351,9 @@ pub struct ClipScrollNode {
     /// The rect of the contents that can be scrolled inside of the scroll root.
     pub content_rect: LayoutRect,
 
+    /// An image whose alpha channel further clips the contents, for `clip-path` and `mask`.
+    pub image_mask: Option<ImageMask>,
+
     /// The type of this ClipScrollNode.
     pub node_type: ClipScrollNodeType,
 }
@@ -361,6 +364,7 @@ impl ClipScrollNode {
             parent_index: ClipScrollNodeIndex(0),
             clip: ClippingRegion::from_rect(LayoutRect::zero()),
             content_rect: LayoutRect::zero(),
+            image_mask: None,
             node_type: ClipScrollNodeType::Placeholder,
         }
     }
@@ -679,13 +683,6 @@ pub struct TextDisplayItem {
     pub text_color: ColorF,
 }
 
-#[derive(Clone, Eq, PartialEq, Serialize)]
-pub enum TextOrientation {
-    Upright,
-    SidewaysLeft,
-    SidewaysRight,
-}
-
 /// Paints an image.
 #[derive(Clone, Serialize)]
 pub struct ImageDisplayItem {
@@ -0,0 +1,694 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Clipping and masking of elements with `clip-path` and the `mask-*` properties.
+//!
+//! Basic shapes that WebRender can clip to directly become clip regions. Polygons, references
+//! to SVG `clipPath` elements and mask images are painted into image masks instead.
+//!
+//! * [CSS Masking Module Level 1](https://drafts.fxtf.org/css-masking/)
+
+use ServoArc;
+use app_units::Au;
+use azure::azure::AzFloat;
+use azure::azure_hl::{AntialiasMode, BackendType, Color, ColorPattern, CompositionOp, DrawOptions};
+use azure::azure_hl::{DrawTarget, ExtendMode as AzureExtendMode, GradientStop as AzureGradientStop};
+use azure::azure_hl::{LinearGradientPattern, Pattern, RadialGradientPattern, SourceSurface};
+use azure::azure_hl::{SurfaceFormat, SurfacePattern};
+use context::LayoutContext;
+use display_list::ToLayout;
+use display_list::background::{build_border_radius, calculate_inner_border_radii};
+use display_list::background::{compute_mask_placement, convert_linear_gradient};
+use display_list::background::{convert_radial_gradient, get_cyclic, BackgroundPlacement};
+use display_list::items::{ClippingRegion, OpaqueNode};
+use euclid::{Point2D, Rect, SideOffsets2D, Size2D, Transform2D};
+use floats::{basic_shape_geometry, ShapeGeometry};
+use fnv::FnvHashMap;
+use fragment::Fragment;
+use ipc_channel::ipc::IpcSharedMemory;
+use net_traits::image::base::{Image, PixelFormat};
+use net_traits::image_cache::{ImageOrMetadataAvailable, UsePlaceholder};
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use style::computed_values::mask_composite::single_value::T as MaskComposite;
+use style::computed_values::mask_mode::single_value::T as MaskMode;
+use style::properties::ComputedValues;
+use style::values::Either;
+use style::values::computed::{Gradient, ImageLayer};
+use style::values::generics::basic_shape::{GeometryBox, ShapeBox, ShapeSource};
+use style::values::generics::image::{GradientKind, Image as GenericImage};
+use svg::SvgClipPath;
+use webrender_api::{BorderRadius, ExtendMode, GradientStop, ImageKey, ImageMask, LayoutRect};
+use webrender_api::LayoutSize;
+
+/// The number of device pixels above which masks are painted at a lower resolution.
+const MAX_MASK_AREA: f32 = 4096.0 * 4096.0;
+
+/// The image masks of the display lists being built and last sent, by their size in pixels and a
+/// hash of their pixels, so that unchanged masks keep their webrender image across display lists.
+#[derive(Default)]
+pub struct PaintedMasks {
+    masks: FnvHashMap<(i32, i32, u64), PaintedMask>,
+}
+
+struct PaintedMask {
+    image: ImageKey,
+    /// Whether the display list being built shows the mask.
+    displayed: bool,
+}
+
+impl PaintedMasks {
+    /// Returns the webrender image of a mask, uploading it unless an identical one already is.
+    fn image_key(
+        &mut self,
+        context: &LayoutContext,
+        size: Size2D<i32>,
+        coverage: &[u8],
+    ) -> Option<ImageKey> {
+        let mut hasher = DefaultHasher::new();
+        coverage.hash(&mut hasher);
+        let key = (size.width, size.height, hasher.finish());
+        if let Some(mask) = self.masks.get_mut(&key) {
+            mask.displayed = true;
+            return Some(mask.image);
+        }
+        let image = upload(context, size, coverage)?;
+        self.masks.insert(key, PaintedMask { image: image, displayed: true });
+        Some(image)
+    }
+
+    /// Retires the images of the masks that the display list just built doesn't show, and starts
+    /// tracking the masks of the next one.
+    pub fn retire_undisplayed(&mut self, retired_image_keys: &mut Vec<ImageKey>) {
+        self.masks.retain(|_, mask| {
+            if !mask.displayed {
+                retired_image_keys.push(mask.image);
+            }
+            mask.displayed
+        });
+        for mask in self.masks.values_mut() {
+            mask.displayed = false;
+        }
+    }
+}
+
+/// A clip that `clip-path` or `mask-*` apply to an element and its descendants.
+pub enum MaskingClip {
+    /// A shape that WebRender clips to directly.
+    Region(ClippingRegion),
+    /// A shape or mask that is painted into an image mask.
+    Mask(PendingMask),
+}
+
+/// An image mask, which is painted once the images and `clipPath` elements it refers to are
+/// known.
+pub struct PendingMask {
+    source: MaskSource,
+    node: OpaqueNode,
+    style: ServoArc<ComputedValues>,
+    boxes: MaskingBoxes,
+}
+
+enum MaskSource {
+    /// A `polygon()` basic shape with the given vertices, in CSS pixels.
+    Polygon(Vec<Point2D<f32>>),
+    /// A reference to a `clipPath` element by id, with the reference box it is laid out in.
+    ClipPath(String, Rect<Au>),
+    /// The layers of `mask-image`.
+    MaskLayers,
+}
+
+/// The border box of a fragment relative to its stacking context, and the offsets of its other
+/// boxes from it.
+#[derive(Clone, Copy)]
+struct MaskingBoxes {
+    border_box: Rect<Au>,
+    border: SideOffsets2D<Au>,
+    border_padding: SideOffsets2D<Au>,
+    margin: SideOffsets2D<Au>,
+}
+
+impl MaskingBoxes {
+    fn new(fragment: &Fragment, border_box: Rect<Au>) -> MaskingBoxes {
+        let writing_mode = fragment.style.writing_mode;
+        MaskingBoxes {
+            border_box: border_box,
+            border: fragment.border_width().to_physical(writing_mode),
+            border_padding: fragment.border_padding.to_physical(writing_mode),
+            margin: fragment.margin.to_physical(writing_mode),
+        }
+    }
+
+    fn reference_box(&self, shape_box: ShapeBox) -> Rect<Au> {
+        match shape_box {
+            ShapeBox::MarginBox => self.border_box.outer_rect(self.margin),
+            ShapeBox::BorderBox => self.border_box,
+            ShapeBox::PaddingBox => self.border_box.inner_rect(self.border),
+            ShapeBox::ContentBox => self.border_box.inner_rect(self.border_padding),
+        }
+    }
+
+    /// Returns the clip region of a reference box, which follows the curvature of the border
+    /// radii.
+    fn box_region(&self, style: &ComputedValues, shape_box: ShapeBox) -> ClippingRegion {
+        let radii = build_border_radius(self.border_box, style.get_border());
+        let radii = match shape_box {
+            ShapeBox::MarginBox => outer_border_radii(radii, self.margin),
+            ShapeBox::BorderBox => radii,
+            ShapeBox::PaddingBox => calculate_inner_border_radii(radii, self.border),
+            ShapeBox::ContentBox => calculate_inner_border_radii(radii, self.border_padding),
+        };
+        rounded_region(self.reference_box(shape_box).to_layout(), radii)
+    }
+}
+
+/// Returns the clips that the `clip-path` and `mask-*` properties of a fragment apply to it,
+/// from the outermost. `border_box` is the border box of the fragment relative to its stacking
+/// context.
+pub fn masking_clips(fragment: &Fragment, border_box: Rect<Au>) -> Vec<MaskingClip> {
+    let boxes = MaskingBoxes::new(fragment, border_box);
+    let pending_mask = |source| {
+        MaskingClip::Mask(PendingMask {
+            source: source,
+            node: fragment.node,
+            style: fragment.style.clone(),
+            boxes: boxes,
+        })
+    };
+
+    let svg = fragment.style.get_svg();
+    let mut clips = vec![];
+    match *svg.clip_path {
+        ShapeSource::None => {},
+        ShapeSource::ImageOrUrl(ref url) => {
+            if let Some(id) = url.url().and_then(|url| url.fragment()) {
+                let reference_box = boxes.reference_box(ShapeBox::BorderBox);
+                clips.push(pending_mask(MaskSource::ClipPath(id.to_owned(), reference_box)));
+            }
+        },
+        ShapeSource::Box(geometry_box) => {
+            clips.push(MaskingClip::Region(boxes.box_region(&fragment.style, css_box(geometry_box))));
+        },
+        ShapeSource::Shape(ref shape, geometry_box) => {
+            let shape_box = geometry_box.map_or(ShapeBox::BorderBox, css_box);
+            clips.push(match basic_shape_geometry(shape, &boxes.reference_box(shape_box)) {
+                ShapeGeometry::Ellipse(center, radii) => {
+                    let rect = Rect::new(center - radii.to_vector(), radii * 2.0);
+                    let radius = LayoutSize::from_untyped(&radii);
+                    MaskingClip::Region(rounded_region(LayoutRect::from_untyped(&rect), BorderRadius {
+                        top_left: radius,
+                        top_right: radius,
+                        bottom_right: radius,
+                        bottom_left: radius,
+                    }))
+                },
+                ShapeGeometry::RoundedRect(rect, radii) => {
+                    MaskingClip::Region(rounded_region(LayoutRect::from_untyped(&rect), BorderRadius {
+                        top_left: LayoutSize::from_untyped(&radii[0]),
+                        top_right: LayoutSize::from_untyped(&radii[1]),
+                        bottom_right: LayoutSize::from_untyped(&radii[2]),
+                        bottom_left: LayoutSize::from_untyped(&radii[3]),
+                    }))
+                },
+                ShapeGeometry::Polygon(vertices) => pending_mask(MaskSource::Polygon(vertices)),
+                ShapeGeometry::Mask(..) => unreachable!("Basic shapes are never images"),
+            });
+        },
+    }
+
+    // A mask is only applied if at least one layer is not `none`.
+    let has_mask_image = svg.mask_image.0.iter().any(|image| match *image {
+        Either::First(_none) => false,
+        Either::Second(_image) => true,
+    });
+    if has_mask_image {
+        clips.push(pending_mask(MaskSource::MaskLayers));
+    }
+
+    clips
+}
+
+impl PendingMask {
+    /// Paints the mask, and returns the clip region and image mask of its clip scroll node.
+    pub fn paint(
+        &self,
+        context: &LayoutContext,
+        clip_paths: &HashMap<String, Arc<SvgClipPath>>,
+    ) -> (ClippingRegion, Option<ImageMask>) {
+        let bounds = match self.source {
+            MaskSource::Polygon(ref vertices) => polygon_bounds(vertices),
+            MaskSource::ClipPath(ref id, reference_box) => match clip_paths.get(id) {
+                Some(clip_path) => clip_path.bounds(&to_px_rect(&reference_box)),
+                // References to missing elements are ignored.
+                None => return (ClippingRegion::max(), None),
+            },
+            MaskSource::MaskLayers => Some(to_px_rect(&self.boxes.border_box)),
+        };
+        let canvas = match bounds.and_then(|bounds| MaskCanvas::new(context, bounds)) {
+            Some(canvas) => canvas,
+            None => return (ClippingRegion::empty(), None),
+        };
+
+        let coverage = match self.source {
+            MaskSource::Polygon(ref vertices) => {
+                let draw_target = canvas.draw_target();
+                draw_target.set_transform(&canvas.transform());
+                let path_builder = draw_target.create_path_builder();
+                for (index, vertex) in vertices.iter().enumerate() {
+                    if index == 0 {
+                        path_builder.move_to(*vertex)
+                    } else {
+                        path_builder.line_to(*vertex)
+                    }
+                }
+                path_builder.close();
+                draw_target.fill(
+                    &path_builder.finish(),
+                    white_pattern().to_pattern_ref(),
+                    &DrawOptions::new(1.0, CompositionOp::Over, AntialiasMode::Default),
+                );
+                canvas.coverage(&draw_target, MaskMode::Alpha)
+            },
+            MaskSource::ClipPath(ref id, reference_box) => {
+                let draw_target = canvas.draw_target();
+                clip_paths[id].fill(&draw_target, &to_px_rect(&reference_box), &canvas.transform());
+                canvas.coverage(&draw_target, MaskMode::Alpha)
+            },
+            MaskSource::MaskLayers => self.paint_mask_layers(context, &canvas),
+        };
+
+        let rect = LayoutRect::from_untyped(&canvas.rect);
+        let image = context.painted_masks.lock().unwrap().image_key(context, canvas.size, &coverage);
+        let image_mask = image.map(|image| {
+            ImageMask {
+                image: image,
+                rect: rect,
+                repeat: false,
+            }
+        });
+        (ClippingRegion::from_rect(rect), image_mask)
+    }
+
+    /// Paints the layers of `mask-image`, and returns their composited coverage.
+    fn paint_mask_layers(&self, context: &LayoutContext, canvas: &MaskCanvas) -> Vec<u8> {
+        let svg = self.style.get_svg();
+        let mut coverage: Option<Vec<u8>> = None;
+
+        // The first layer is the topmost one, and each layer is composited over the result of
+        // compositing the layers below it.
+        for (index, image) in svg.mask_image.0.iter().enumerate().rev() {
+            let layer = self.paint_mask_layer(context, canvas, image, index);
+            coverage = Some(match coverage {
+                None => layer,
+                Some(below) => composite(&layer, &below, *get_cyclic(&svg.mask_composite.0, index)),
+            });
+        }
+        coverage.unwrap_or_else(|| vec![0; (canvas.size.width * canvas.size.height) as usize])
+    }
+
+    /// Paints one layer of `mask-image`, and returns its coverage. Layers that are `none`, and
+    /// images that have not loaded yet or cannot be displayed, cover nothing.
+    fn paint_mask_layer(
+        &self,
+        context: &LayoutContext,
+        canvas: &MaskCanvas,
+        image: &ImageLayer,
+        index: usize,
+    ) -> Vec<u8> {
+        let svg = self.style.get_svg();
+        let draw_target = canvas.draw_target();
+        let boxes = &self.boxes;
+        let placement = |intrinsic_size| {
+            compute_mask_placement(
+                svg,
+                boxes.border_box,
+                intrinsic_size,
+                boxes.border,
+                boxes.border_padding,
+                index,
+            )
+        };
+
+        match *image {
+            Either::Second(GenericImage::Url(ref image_url)) => {
+                // TODO: Support references to SVG `mask` elements.
+                let image = image_url.url().and_then(|url| {
+                    if url.fragment().is_some() {
+                        return None;
+                    }
+                    let image = context.get_or_request_image_or_meta(
+                        self.node,
+                        url.clone(),
+                        UsePlaceholder::No,
+                    );
+                    match image {
+                        Some(ImageOrMetadataAvailable::ImageAvailable(image, _)) => Some(image),
+                        _ => None,
+                    }
+                });
+                if let Some(image) = image {
+                    let intrinsic_size = Size2D::new(
+                        Au::from_px(image.width as i32),
+                        Au::from_px(image.height as i32),
+                    );
+                    if let Some(surface) = image_surface(&draw_target, &image) {
+                        let surface_size = Size2D::new(image.width as f32, image.height as f32);
+                        let placement = placement(Some(intrinsic_size));
+                        canvas.draw_tiles(&draw_target, surface, surface_size, &placement);
+                    }
+                }
+            },
+            Either::Second(GenericImage::Gradient(ref gradient)) => {
+                let placement = placement(None);
+                let tile_size = canvas.device_size(&to_px_size(&placement.tile_size));
+                if tile_size.width > 0 && tile_size.height > 0 {
+                    let tile = DrawTarget::new(BackendType::Skia, tile_size, SurfaceFormat::B8G8R8A8);
+                    tile.set_transform(&Transform2D::create_scale(
+                        tile_size.width as f32 / placement.tile_size.width.to_f32_px(),
+                        tile_size.height as f32 / placement.tile_size.height.to_f32_px(),
+                    ));
+                    paint_gradient(&tile, &self.style, gradient, placement.tile_size);
+                    let surface_size = Size2D::new(tile_size.width as f32, tile_size.height as f32);
+                    canvas.draw_tiles(&draw_target, tile.snapshot(), surface_size, &placement);
+                }
+            },
+            // TODO: Support `-moz-image-rect()`, `element()` and paint worklets.
+            _ => {},
+        }
+
+        canvas.coverage(&draw_target, *get_cyclic(&svg.mask_mode.0, index))
+    }
+}
+
+/// The pixels an image mask is painted into.
+struct MaskCanvas {
+    /// The area covered by the mask, in CSS pixels relative to the stacking context.
+    rect: Rect<f32>,
+    /// The number of mask pixels per CSS pixel.
+    scale: f32,
+    /// The size of the mask in pixels.
+    size: Size2D<i32>,
+}
+
+impl MaskCanvas {
+    /// Creates a canvas covering `bounds`, or returns `None` if they are empty.
+    fn new(context: &LayoutContext, bounds: Rect<f32>) -> Option<MaskCanvas> {
+        let bounds = bounds.round_out();
+        if bounds.size.width <= 0.0 || bounds.size.height <= 0.0 {
+            return None;
+        }
+
+        let mut scale = context.style_context.device_pixel_ratio().get();
+        let area = bounds.size.width * bounds.size.height * scale * scale;
+        if area > MAX_MASK_AREA {
+            scale *= (MAX_MASK_AREA / area).sqrt();
+        }
+        let size = Size2D::new(
+            (bounds.size.width * scale).ceil() as i32,
+            (bounds.size.height * scale).ceil() as i32,
+        );
+        Some(MaskCanvas {
+            // The mask covers whole pixels.
+            rect: Rect::new(
+                bounds.origin,
+                Size2D::new(size.width as f32 / scale, size.height as f32 / scale),
+            ),
+            scale: scale,
+            size: size,
+        })
+    }
+
+    fn draw_target(&self) -> DrawTarget {
+        DrawTarget::new(BackendType::Skia, self.size, SurfaceFormat::B8G8R8A8)
+    }
+
+    /// Returns the transform from CSS pixels relative to the stacking context to mask pixels.
+    fn transform(&self) -> Transform2D<f32> {
+        Transform2D::create_translation(-self.rect.origin.x, -self.rect.origin.y)
+            .post_scale(self.scale, self.scale)
+    }
+
+    fn device_size(&self, size: &Size2D<f32>) -> Size2D<i32> {
+        Size2D::new(
+            (size.width * self.scale).ceil() as i32,
+            (size.height * self.scale).ceil() as i32,
+        )
+    }
+
+    /// Fills the painting area of a mask layer with tiles of `surface`, which is `surface_size`
+    /// pixels large.
+    fn draw_tiles(
+        &self,
+        draw_target: &DrawTarget,
+        surface: SourceSurface,
+        surface_size: Size2D<f32>,
+        placement: &BackgroundPlacement,
+    ) {
+        let tile_size = to_px_size(&placement.tile_size);
+        if tile_size.width <= 0.0 || tile_size.height <= 0.0 {
+            return;
+        }
+        let area = match placement.bounds.intersection(&placement.clip_rect) {
+            Some(area) => to_px_rect(&area),
+            None => return,
+        };
+
+        // TODO: Support `mask-repeat: space`, which leaves gaps between the tiles.
+        let bounds = to_px_rect(&placement.bounds);
+        let pattern_transform = Transform2D::create_scale(
+            tile_size.width / surface_size.width,
+            tile_size.height / surface_size.height,
+        ).post_translate(bounds.origin.to_vector());
+        let pattern = Pattern::Surface(SurfacePattern::new(
+            surface.azure_source_surface,
+            true,
+            true,
+            &pattern_transform,
+        ));
+        draw_target.set_transform(&self.transform());
+        draw_target.fill_rect(
+            &area,
+            pattern.to_pattern_ref(),
+            Some(&DrawOptions::new(1.0, CompositionOp::Over, AntialiasMode::None)),
+        );
+    }
+
+    /// Reads how much each pixel of a draw target covers, from its alpha or its luminance.
+    fn coverage(&self, draw_target: &DrawTarget, mode: MaskMode) -> Vec<u8> {
+        let (width, height) = (self.size.width as usize, self.size.height as usize);
+        let mut coverage = Vec::with_capacity(width * height);
+        let surface = draw_target.snapshot().get_data_surface();
+        let stride = surface.stride() as usize;
+        surface.with_data(|data| {
+            for row in data.chunks(stride).take(height) {
+                for pixel in row[..width * 4].chunks(4) {
+                    coverage.push(match mode {
+                        // The pixels are premultiplied, so this is the luminance times the alpha.
+                        MaskMode::Luminance => {
+                            let (blue, green, red) = (pixel[0] as f32, pixel[1] as f32, pixel[2] as f32);
+                            (0.0721 * blue + 0.7154 * green + 0.2125 * red).round() as u8
+                        },
+                        MaskMode::Alpha | MaskMode::MatchSource => pixel[3],
+                    });
+                }
+            }
+        });
+        coverage
+    }
+}
+
+/// Composites the coverage of a mask layer over the coverage of the layers below it.
+fn composite(source: &[u8], destination: &[u8], operator: MaskComposite) -> Vec<u8> {
+    source.iter().zip(destination).map(|(&source, &destination)| {
+        let (source, destination) = (source as f32 / 255.0, destination as f32 / 255.0);
+        let coverage = match operator {
+            MaskComposite::Add => source + destination * (1.0 - source),
+            MaskComposite::Subtract => source * (1.0 - destination),
+            MaskComposite::Intersect => source * destination,
+            MaskComposite::Exclude => source * (1.0 - destination) + destination * (1.0 - source),
+        };
+        (coverage * 255.0).round() as u8
+    }).collect()
+}
+
+/// Paints a gradient that fills a rectangle of the given size at the origin.
+fn paint_gradient(
+    draw_target: &DrawTarget,
+    style: &ComputedValues,
+    gradient: &Gradient,
+    size: Size2D<Au>,
+) {
+    let pattern = match gradient.kind {
+        GradientKind::Linear(angle_or_corner) => {
+            let gradient = convert_linear_gradient(
+                style,
+                size,
+                &gradient.items[..],
+                angle_or_corner,
+                gradient.repeating,
+            );
+            let stops = draw_target.create_gradient_stops(
+                &azure_gradient_stops(&gradient.stops),
+                azure_extend_mode(gradient.extend_mode),
+            );
+            Pattern::LinearGradient(LinearGradientPattern::new(
+                &gradient.start_point.to_untyped(),
+                &gradient.end_point.to_untyped(),
+                stops,
+                &Transform2D::identity(),
+            ))
+        },
+        GradientKind::Radial(shape, center, _angle) => {
+            let gradient = convert_radial_gradient(
+                style,
+                size,
+                &gradient.items[..],
+                shape,
+                center,
+                gradient.repeating,
+            );
+            if gradient.radius.width <= 0.0 || gradient.radius.height <= 0.0 {
+                return;
+            }
+            let stops = draw_target.create_gradient_stops(
+                &azure_gradient_stops(&gradient.stops),
+                azure_extend_mode(gradient.extend_mode),
+            );
+            // Azure gradients are circular, so elliptical ones are stretched vertically.
+            let center = gradient.center.to_untyped();
+            let transform = Transform2D::create_translation(-center.x, -center.y)
+                .post_scale(1.0, gradient.radius.height / gradient.radius.width)
+                .post_translate(center.to_vector());
+            Pattern::RadialGradient(RadialGradientPattern::new(
+                &center,
+                &center,
+                0.0,
+                gradient.radius.width,
+                stops,
+                &transform,
+            ))
+        },
+    };
+    let rect = Rect::new(Point2D::zero(), to_px_size(&size));
+    draw_target.fill_rect(&rect, pattern.to_pattern_ref(), None);
+}
+
+fn azure_gradient_stops(stops: &[GradientStop]) -> Vec<AzureGradientStop> {
+    stops.iter().map(|stop| {
+        AzureGradientStop {
+            offset: stop.offset as AzFloat,
+            color: Color::rgba(stop.color.r, stop.color.g, stop.color.b, stop.color.a),
+        }
+    }).collect()
+}
+
+fn azure_extend_mode(extend_mode: ExtendMode) -> AzureExtendMode {
+    match extend_mode {
+        ExtendMode::Clamp => AzureExtendMode::Clamp,
+        ExtendMode::Repeat => AzureExtendMode::Repeat,
+    }
+}
+
+/// Makes an Azure surface out of the pixels of an image, which Azure expects premultiplied.
+fn image_surface(draw_target: &DrawTarget, image: &Image) -> Option<SourceSurface> {
+    if image.format != PixelFormat::BGRA8 {
+        return None;
+    }
+    let mut bytes = image.bytes.to_vec();
+    for pixel in bytes.chunks_mut(4) {
+        let alpha = pixel[3] as u32;
+        for component in &mut pixel[..3] {
+            *component = ((*component as u32 * alpha + 127) / 255) as u8;
+        }
+    }
+    draw_target.create_source_surface_from_data(
+        &bytes,
+        Size2D::new(image.width as i32, image.height as i32),
+        image.width as i32 * 4,
+        SurfaceFormat::B8G8R8A8,
+    )
+}
+
+/// Makes a single channel WebRender image out of the coverage of a mask.
+fn upload(context: &LayoutContext, size: Size2D<i32>, coverage: &[u8]) -> Option<ImageKey> {
+    let mut image = Image {
+        width: size.width as u32,
+        height: size.height as u32,
+        format: PixelFormat::K8,
+        bytes: IpcSharedMemory::from_bytes(coverage),
+        id: None,
+        animation: None,
+    };
+    context.image_cache.set_webrender_image_key(&mut image);
+    image.id
+}
+
+fn white_pattern() -> Pattern {
+    Pattern::Color(ColorPattern::new(Color::rgba(1.0, 1.0, 1.0, 1.0)))
+}
+
+/// CSS boxes have no fill, stroke or view box, so those map to the closest CSS box.
+fn css_box(geometry_box: GeometryBox) -> ShapeBox {
+    match geometry_box {
+        GeometryBox::FillBox => ShapeBox::ContentBox,
+        GeometryBox::StrokeBox | GeometryBox::ViewBox => ShapeBox::BorderBox,
+        GeometryBox::ShapeBox(shape_box) => shape_box,
+    }
+}
+
+fn rounded_region(rect: LayoutRect, radii: BorderRadius) -> ClippingRegion {
+    let mut region = ClippingRegion::from_rect(rect);
+    if !radii.is_zero() {
+        region.intersect_with_rounded_rect(rect, radii);
+    }
+    region
+}
+
+/// Calculates the radii of a box outset from the border box, whose corners stay sharp if those
+/// of the border box are.
+///
+/// https://drafts.csswg.org/css-shapes/#margin-box
+fn outer_border_radii(radii: BorderRadius, offsets: SideOffsets2D<Au>) -> BorderRadius {
+    let outer = |radius: LayoutSize, horizontal: Au, vertical: Au| {
+        if radius.width <= 0.0 || radius.height <= 0.0 {
+            radius
+        } else {
+            LayoutSize::new(
+                radius.width + horizontal.to_f32_px(),
+                radius.height + vertical.to_f32_px(),
+            )
+        }
+    };
+    BorderRadius {
+        top_left: outer(radii.top_left, offsets.left, offsets.top),
+        top_right: outer(radii.top_right, offsets.right, offsets.top),
+        bottom_right: outer(radii.bottom_right, offsets.right, offsets.bottom),
+        bottom_left: outer(radii.bottom_left, offsets.left, offsets.bottom),
+    }
+}
+
+fn polygon_bounds(vertices: &[Point2D<f32>]) -> Option<Rect<f32>> {
+    let first = match vertices.first() {
+        Some(first) => *first,
+        None => return None,
+    };
+    let (min, max) = vertices.iter().fold((first, first), |(min, max), vertex| {
+        (Point2D::new(min.x.min(vertex.x), min.y.min(vertex.y)),
+         Point2D::new(max.x.max(vertex.x), max.y.max(vertex.y)))
+    });
+    Some(Rect::new(min, Size2D::new(max.x - min.x, max.y - min.y)))
+}
+
+fn to_px_rect(rect: &Rect<Au>) -> Rect<f32> {
+    Rect::new(
+        Point2D::new(rect.origin.x.to_f32_px(), rect.origin.y.to_f32_px()),
+        to_px_size(&rect.size),
+    )
+}
+
+fn to_px_size(size: &Size2D<Au>) -> Size2D<f32> {
+    Size2D::new(size.width.to_f32_px(), size.height.to_f32_px())
+}
@@ -9,13 +9,17 @@ pub use self::builder::FlexFlowDisplayListBuilding;
 pub use self::builder::IndexableText;
 pub use self::builder::InlineFlowDisplayListBuilding;
 pub use self::builder::ListItemFlowDisplayListBuilding;
+pub use self::builder::MulticolFlowDisplayListBuilding;
 pub use self::builder::StackingContextCollectionFlags;
 pub use self::builder::StackingContextCollectionState;
 pub use self::conversions::ToLayout;
+pub use self::masking::PaintedMasks;
 pub use self::webrender_helpers::WebRenderDisplayListConverter;
 
 mod background;
 mod builder;
 mod conversions;
 pub mod items;
+mod masking;
+mod scroll_snap;
 mod webrender_helpers;
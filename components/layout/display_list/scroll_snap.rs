@@ -0,0 +1,103 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Snap positions of scroll containers with CSS scroll snapping.
+//!
+//! Scroll containers with a `scroll-snap-type` collect the `scroll-snap-coordinate` of their
+//! descendants while stacking contexts are collected. The compositor snaps to the resulting
+//! positions once a fling or wheel gesture ends.
+//!
+//! * [CSS Scroll Snap Points Module Level 1](https://www.w3.org/TR/2015/WD-css-snappoints-1-20150326/)
+
+use app_units::Au;
+use display_list::items::ClipScrollNodeIndex;
+use euclid::{Point2D, Rect, Vector2D};
+use script_traits::{ScrollSnapAxis, ScrollSnapInfo, ScrollSnapStrictness};
+use std::cmp::Ordering;
+use style::properties::ComputedValues;
+use style::values::computed::ScrollSnapType;
+use style::values::computed::position::Position;
+use webrender_api::ExternalScrollId;
+
+/// A scroll container with CSS scroll snapping, whose snap positions are being collected.
+pub struct ScrollSnapFrame {
+    /// The clip scroll node that scrolls the contents of the scroll container.
+    pub clip_scroll_node: ClipScrollNodeIndex,
+    /// The scrollport, in the coordinate space of the contents of the scroll container.
+    scrollport: Rect<Au>,
+    /// The `scroll-snap-destination` of the scroll container, relative to the scrollport.
+    destination: Vector2D<Au>,
+    info: ScrollSnapInfo,
+}
+
+impl ScrollSnapFrame {
+    /// Returns the snap frame of a scroll container, or `None` if it doesn't snap along either
+    /// axis.
+    pub fn new(
+        style: &ComputedValues,
+        clip_scroll_node: ClipScrollNodeIndex,
+        scroll_id: ExternalScrollId,
+        scrollport: Rect<Au>,
+    ) -> Option<ScrollSnapFrame> {
+        let box_style = style.get_box();
+        let x = snap_axis(box_style.scroll_snap_type_x);
+        let y = snap_axis(box_style.scroll_snap_type_y);
+        if x.is_none() && y.is_none() {
+            return None;
+        }
+
+        let destination = used_position(&box_style.scroll_snap_destination, &scrollport);
+        Some(ScrollSnapFrame {
+            clip_scroll_node,
+            scrollport,
+            destination: destination - scrollport.origin,
+            info: ScrollSnapInfo { scroll_id, x, y },
+        })
+    }
+
+    /// Adds the snap positions that bring each `scroll-snap-coordinate` of a descendant to the
+    /// `scroll-snap-destination` of the scroll container.
+    pub fn add_snap_coordinates(&mut self, style: &ComputedValues, border_box: &Rect<Au>) {
+        for coordinate in style.get_box().scroll_snap_coordinate.0.iter() {
+            let position =
+                used_position(coordinate, border_box) - self.scrollport.origin - self.destination;
+            if let Some(ref mut x) = self.info.x {
+                x.positions.push(position.x.to_f32_px());
+            }
+            if let Some(ref mut y) = self.info.y {
+                y.positions.push(position.y.to_f32_px());
+            }
+        }
+    }
+
+    /// Returns the snap points of the scroll container, with the positions along each axis
+    /// sorted and without duplicates.
+    pub fn into_info(mut self) -> ScrollSnapInfo {
+        for axis in self.info.x.iter_mut().chain(self.info.y.iter_mut()) {
+            axis.positions.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+            axis.positions.dedup();
+        }
+        self.info
+    }
+}
+
+fn snap_axis(snap_type: ScrollSnapType) -> Option<ScrollSnapAxis> {
+    let strictness = match snap_type {
+        ScrollSnapType::None => return None,
+        ScrollSnapType::Mandatory => ScrollSnapStrictness::Mandatory,
+        ScrollSnapType::Proximity => ScrollSnapStrictness::Proximity,
+    };
+    Some(ScrollSnapAxis {
+        strictness,
+        positions: vec![],
+    })
+}
+
+/// Resolves a position against a rectangle, returning the point it designates.
+fn used_position(position: &Position, rect: &Rect<Au>) -> Point2D<Au> {
+    rect.origin + Vector2D::new(
+        position.horizontal.to_used_value(rect.size.width),
+        position.vertical.to_used_value(rect.size.height),
+    )
+}
@@ -283,7 +283,7 @@ impl WebRenderDisplayItemConverter for DisplayItem {
                         parent_id,
                         item_rect,
                         node.clip.complex.clone(),
-                        None,
+                        node.image_mask,
                     ),
                     ClipScrollNodeType::ScrollFrame(scroll_sensitivity, external_id) => builder
                         .define_scroll_frame_with_parent(
@@ -4,13 +4,25 @@
 
 use app_units::{Au, MAX_AU};
 use block::FormattingContextType;
+use context::LayoutContext;
+use euclid::{Point2D, Rect, SideOffsets2D, Size2D};
 use flow::{Flow, FlowFlags, GetBaseFlow, ImmutableFlowUtils};
+use fragment::Fragment;
+use model;
+use net_traits::image::base::{Image, PixelFormat};
+use net_traits::image_cache::{ImageOrMetadataAvailable, UsePlaceholder};
 use persistent_list::PersistentList;
-use std::cmp::{max, min};
+use std::cmp::{Ordering, max, min};
+use std::f32;
 use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
 use style::computed_values::float::T as StyleFloat;
-use style::logical_geometry::{LogicalRect, LogicalSize, WritingMode};
-use style::values::computed::LengthOrPercentageOrAuto;
+use style::logical_geometry::{LogicalRect, LogicalSize, PhysicalSide, WritingMode};
+use style::values::computed::{LengthOrPercentage, LengthOrPercentageOrAuto};
+use style::values::computed::basic_shape::BasicShape;
+use style::values::generics::basic_shape::{BasicShape as GenericBasicShape, ShapeBox, ShapeRadius, ShapeSource};
+use style::values::generics::image::Image as GenericImage;
 
 /// The kind of float: left or right.
 #[derive(Clone, Copy, Debug, Serialize)]
@@ -38,12 +50,14 @@ pub enum ClearType {
 }
 
 /// Information about a single float.
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 struct Float {
     /// The boundaries of this float.
     bounds: LogicalRect<Au>,
     /// The kind of float: left or right.
     kind: FloatKind,
+    /// The float area given by `shape-outside`, if any, relative to `bounds`.
+    shape: Option<Arc<FloatShape>>,
 }
 
 impl fmt::Debug for Float {
@@ -95,7 +109,9 @@ pub struct PlacementInfo {
     /// The maximum inline-end position of the float, generally determined by the containing block.
     pub max_inline_size: Au,
     /// The kind of float.
-    pub kind: FloatKind
+    pub kind: FloatKind,
+    /// The float area given by `shape-outside`, if any. Only meaningful for floats.
+    pub shape: Option<Arc<FloatShape>>,
 }
 
 impl fmt::Debug for PlacementInfo {
@@ -113,6 +129,15 @@ fn range_intersect(block_start_1: Au, block_end_1: Au, block_start_2: Au, block_
     (max(block_start_1, block_start_2), min(block_end_1, block_end_2))
 }
 
+/// The area of a float that other content is kept out of.
+#[derive(Clone, Copy, PartialEq)]
+enum FloatArea {
+    /// The margin box, which other floats and block formatting contexts avoid.
+    MarginBox,
+    /// The float area given by `shape-outside`, which line boxes wrap around.
+    Shape,
+}
+
 /// Encapsulates information about floats. This is optimized to avoid allocation if there are
 /// no floats, and to avoid copying when translating the list of floats downward.
 #[derive(Clone)]
@@ -164,6 +189,11 @@ impl Floats {
     /// inline-size.)
     pub fn available_rect(&self, block_start: Au, block_size: Au, max_x: Au)
                           -> Option<LogicalRect<Au>> {
+        self.available_rect_in_area(block_start, block_size, max_x, FloatArea::MarginBox)
+    }
+
+    fn available_rect_in_area(&self, block_start: Au, block_size: Au, max_x: Au, area: FloatArea)
+                              -> Option<LogicalRect<Au>> {
         let list = &self.list;
         let block_start = block_start - self.offset.block;
 
@@ -184,27 +214,52 @@ impl Floats {
             let float_pos = float.bounds.start;
             let float_size = float.bounds.size;
 
+            // The inline and block extents of the part of the float to avoid. A float with a
+            // `shape-outside` only excludes the extent of its shape alongside the queried range.
+            let (float_inline_start, float_inline_end, float_block_start, float_block_end) =
+                match float.shape {
+                    Some(ref shape) if area == FloatArea::Shape => {
+                        let band_start = block_start - float_pos.b;
+                        match shape.inline_extent(band_start, band_start + block_size) {
+                            Some((start, end)) => {
+                                (float_pos.i + start,
+                                 float_pos.i + end,
+                                 max(float_pos.b, block_start),
+                                 min(float_pos.b + float_size.block,
+                                     block_start + max(block_size, Au::from_px(1))))
+                            }
+                            None => continue,
+                        }
+                    }
+                    _ => {
+                        (float_pos.i,
+                         float_pos.i + float_size.inline,
+                         float_pos.b,
+                         float_pos.b + float_size.block)
+                    }
+                };
+
             debug!("float_pos: {:?}, float_size: {:?}", float_pos, float_size);
             match float.kind {
-                FloatKind::Left if float_pos.i + float_size.inline > max_inline_start &&
-                        float_pos.b + float_size.block > block_start &&
-                        float_pos.b < block_start + block_size => {
-                    max_inline_start = float_pos.i + float_size.inline;
+                FloatKind::Left if float_inline_end > max_inline_start &&
+                        float_block_end > block_start &&
+                        float_block_start < block_start + block_size => {
+                    max_inline_start = float_inline_end;
 
-                    l_block_start = Some(float_pos.b);
-                    l_block_end = Some(float_pos.b + float_size.block);
+                    l_block_start = Some(float_block_start);
+                    l_block_end = Some(float_block_end);
 
                     debug!("available_rect: collision with inline_start float: new \
                             max_inline_start is {:?}",
                            max_inline_start);
                 }
-                FloatKind::Right if float_pos.i < min_inline_end &&
-                       float_pos.b + float_size.block > block_start &&
-                       float_pos.b < block_start + block_size => {
-                    min_inline_end = float_pos.i;
+                FloatKind::Right if float_inline_start < min_inline_end &&
+                       float_block_end > block_start &&
+                       float_block_start < block_start + block_size => {
+                    min_inline_end = float_inline_start;
 
-                    r_block_start = Some(float_pos.b);
-                    r_block_end = Some(float_pos.b + float_size.block);
+                    r_block_start = Some(float_block_start);
+                    r_block_end = Some(float_block_end);
                     debug!("available_rect: collision with inline_end float: new min_inline_end \
                             is {:?}",
                             min_inline_end);
@@ -260,7 +315,8 @@ impl Floats {
                 Some(max_block_start) => max(info.ceiling, max_block_start + self.offset.block),
             },
             max_inline_size: info.max_inline_size,
-            kind: info.kind
+            kind: info.kind,
+            shape: None,
         };
 
         debug!("add_float: added float with info {:?}", new_info);
@@ -271,20 +327,25 @@ impl Floats {
                 self.place_between_floats(&new_info).start - self.offset,
                 info.size,
             ),
-            kind: info.kind
+            kind: info.kind,
+            shape: info.shape.clone(),
         };
 
-        self.list.floats = self.list.floats.prepend_elem(new_float);
         self.list.max_block_start = match self.list.max_block_start {
             None => Some(new_float.bounds.start.b),
             Some(max_block_start) => Some(max(max_block_start, new_float.bounds.start.b)),
-        }
+        };
+        self.list.floats = self.list.floats.prepend_elem(new_float);
     }
 
     /// Given the three sides of the bounding rectangle in the block-start direction, finds the
     /// largest block-size that will result in the rectangle not colliding with any floats. Returns
     /// `None` if that block-size is infinite.
-    fn max_block_size_for_bounds(&self, inline_start: Au, block_start: Au, inline_size: Au)
+    fn max_block_size_for_bounds(&self,
+                                 inline_start: Au,
+                                 block_start: Au,
+                                 inline_size: Au,
+                                 area: FloatArea)
                                  -> Option<Au> {
         let list = &self.list;
 
@@ -296,8 +357,18 @@ impl Floats {
             if float.bounds.start.b + float.bounds.size.block > block_start &&
                    float.bounds.start.i + float.bounds.size.inline > inline_start &&
                    float.bounds.start.i < inline_start + inline_size {
-               let new_y = float.bounds.start.b;
-               max_block_size = Some(min(max_block_size.unwrap_or(new_y), new_y));
+                let new_y = match float.shape {
+                    Some(ref shape) if area == FloatArea::Shape => {
+                        match shape.first_collision(block_start - float.bounds.start.b,
+                                                    inline_start - float.bounds.start.i,
+                                                    inline_start + inline_size - float.bounds.start.i) {
+                            Some(offset) => float.bounds.start.b + offset,
+                            None => continue,
+                        }
+                    }
+                    _ => float.bounds.start.b,
+                };
+                max_block_size = Some(min(max_block_size.unwrap_or(new_y), new_y));
             }
         }
 
@@ -307,6 +378,17 @@ impl Floats {
     /// Given placement information, finds the closest place a fragment can be positioned without
     /// colliding with any floats.
     pub fn place_between_floats(&self, info: &PlacementInfo) -> LogicalRect<Au> {
+        self.place_between_floats_in_area(info, FloatArea::MarginBox)
+    }
+
+    /// Like `place_between_floats`, but for a line box, which wraps around the `shape-outside`
+    /// float areas of floats instead of their margin boxes.
+    pub fn place_line_between_floats(&self, info: &PlacementInfo) -> LogicalRect<Au> {
+        self.place_between_floats_in_area(info, FloatArea::Shape)
+    }
+
+    fn place_between_floats_in_area(&self, info: &PlacementInfo, area: FloatArea)
+                                    -> LogicalRect<Au> {
         debug!("place_between_floats: Placing object with {:?}", info.size);
 
         // If no floats, use this fast path.
@@ -334,9 +416,10 @@ impl Floats {
         // Can't go any higher than previous floats or previous elements in the document.
         let mut float_b = info.ceiling;
         loop {
-            let maybe_location = self.available_rect(float_b,
-                                                     info.size.block,
-                                                     info.max_inline_size);
+            let maybe_location = self.available_rect_in_area(float_b,
+                                                             info.size.block,
+                                                             info.max_inline_size,
+                                                             area);
             debug!("place_float: got available rect: {:?} for block-pos: {:?}",
                    maybe_location,
                    float_b);
@@ -371,7 +454,8 @@ impl Floats {
                     if rect.size.inline >= info.size.inline {
                         let block_size = self.max_block_size_for_bounds(rect.start.i,
                                                                         rect.start.b,
-                                                                        rect.size.inline);
+                                                                        rect.size.inline,
+                                                                        area);
                         let block_size = block_size.unwrap_or(MAX_AU);
                         return match info.kind {
                             FloatKind::Left => {
@@ -423,6 +507,567 @@ impl Floats {
     }
 }
 
+/// The float area of a float with `shape-outside`, stored as the inline extent of each one pixel
+/// high row of the float's margin box. Positions are relative to the inline-start and block-start
+/// edges of the margin box.
+///
+/// https://drafts.csswg.org/css-shapes/#shape-outside-property
+#[derive(Debug, Serialize)]
+pub struct FloatShape {
+    rows: Vec<Option<(Au, Au)>>,
+}
+
+impl FloatShape {
+    /// Computes the float area of the given float fragment, whose block size must already have
+    /// been assigned. Returns `None` if the float area is the margin box.
+    pub fn new(fragment: &Fragment, containing_inline_size: Au, layout_context: &LayoutContext)
+               -> Option<FloatShape> {
+        let style = fragment.style();
+        let box_style = style.get_box();
+        let writing_mode = style.writing_mode;
+
+        let margin = fragment.margin.to_physical(writing_mode);
+        let border = fragment.border_width().to_physical(writing_mode);
+        let padding = (fragment.border_padding - fragment.border_width()).to_physical(writing_mode);
+        let border_box_size = fragment.border_box.size.to_physical(writing_mode);
+        let margin_box = Rect::new(Point2D::zero(),
+                                   Size2D::new(border_box_size.width + margin.left + margin.right,
+                                               border_box_size.height + margin.top + margin.bottom));
+        let border_box = inset_rect(&margin_box, &margin);
+        let padding_box = inset_rect(&border_box, &border);
+        let content_box = inset_rect(&padding_box, &padding);
+
+        let reference_box = |shape_box: Option<ShapeBox>| {
+            match shape_box.unwrap_or(ShapeBox::MarginBox) {
+                ShapeBox::MarginBox => margin_box,
+                ShapeBox::BorderBox => border_box,
+                ShapeBox::PaddingBox => padding_box,
+                ShapeBox::ContentBox => content_box,
+            }
+        };
+
+        let geometry = match *box_style.shape_outside {
+            ShapeSource::None => return None,
+            ShapeSource::Box(shape_box) => {
+                // The shape follows the curvature of the border radii, adjusted to the
+                // reference box.
+                let (outset, sign) = match shape_box {
+                    ShapeBox::MarginBox => (margin, 1.0),
+                    ShapeBox::BorderBox => (SideOffsets2D::zero(), 1.0),
+                    ShapeBox::PaddingBox => (border, -1.0),
+                    ShapeBox::ContentBox => (border + padding, -1.0),
+                };
+                let border_style = style.get_border();
+                let corners = [
+                    (border_style.border_top_left_radius, outset.left, outset.top),
+                    (border_style.border_top_right_radius, outset.right, outset.top),
+                    (border_style.border_bottom_right_radius, outset.right, outset.bottom),
+                    (border_style.border_bottom_left_radius, outset.left, outset.bottom),
+                ];
+                let mut radii = [Size2D::zero(); 4];
+                for (radius, &(corner, horizontal, vertical)) in radii.iter_mut().zip(corners.iter()) {
+                    let corner = model::specified_border_radius(corner, border_box.size);
+                    *radius = Size2D::new(
+                        adjust_radius(corner.width.to_f32_px(), horizontal.to_f32_px() * sign),
+                        adjust_radius(corner.height.to_f32_px(), vertical.to_f32_px() * sign));
+                }
+                let rect = reference_box(Some(shape_box));
+                ShapeGeometry::RoundedRect(to_px_rect(&rect), normalize_radii(&rect, radii))
+            }
+            ShapeSource::Shape(ref shape, shape_box) => {
+                basic_shape_geometry(shape, &reference_box(shape_box))
+            }
+            ShapeSource::ImageOrUrl(ref image) => {
+                let url = match *image {
+                    GenericImage::Url(ref image_url) => image_url.url().cloned(),
+                    _ => None,
+                };
+                let image = url.as_ref().and_then(|url| {
+                    layout_context.get_or_request_image_or_meta(fragment.node,
+                                                                url.clone(),
+                                                                UsePlaceholder::No)
+                });
+                match image {
+                    Some(ImageOrMetadataAvailable::ImageAvailable(image, _)) => {
+                        // The pixels of images that aren't CORS-same-origin must not be exposed,
+                        // so those don't shape the float area.
+                        if !url.map_or(false, |url| layout_context.image_cache.is_cors_same_origin(&url)) {
+                            return None;
+                        }
+                        let threshold = box_style.shape_image_threshold;
+                        ShapeGeometry::from_image(&image, to_px_rect(&content_box), threshold)
+                    }
+                    // Until the image has loaded, the float area is empty.
+                    _ => ShapeGeometry::Polygon(vec![]),
+                }
+            }
+        };
+
+        let shape_margin =
+            box_style.shape_margin.0.to_used_value(containing_inline_size).to_f32_px();
+        Some(FloatShape::from_geometry(geometry,
+                                       margin_box.size,
+                                       writing_mode,
+                                       shape_margin))
+    }
+
+    /// Rasterizes the given physical geometry into rows along the block axis of `writing_mode`,
+    /// expanding it by `shape_margin` and clipping it to the margin box.
+    pub fn from_geometry(geometry: ShapeGeometry,
+                         margin_box_size: Size2D<Au>,
+                         writing_mode: WritingMode,
+                         shape_margin: f32)
+                         -> FloatShape {
+        let (geometry, inline_length, block_length) = if writing_mode.is_vertical() {
+            (geometry.transpose(), margin_box_size.height, margin_box_size.width)
+        } else {
+            (geometry, margin_box_size.width, margin_box_size.height)
+        };
+        let inline_reversed = match writing_mode.inline_start_physical_side() {
+            PhysicalSide::Right | PhysicalSide::Bottom => true,
+            PhysicalSide::Left | PhysicalSide::Top => false,
+        };
+        let block_reversed = match writing_mode.block_start_physical_side() {
+            PhysicalSide::Right | PhysicalSide::Bottom => true,
+            PhysicalSide::Left | PhysicalSide::Top => false,
+        };
+
+        let inline_length = inline_length.to_f32_px();
+        let block_length = block_length.to_f32_px();
+        let row_count = max(block_length.ceil() as i32, 0) as usize;
+        let critical_lines = geometry.critical_lines();
+        let rows: Vec<Option<(f32, f32)>> = (0..row_count).map(|row| {
+            let (start, end) = if block_reversed {
+                (block_length - row as f32 - 1.0, block_length - row as f32)
+            } else {
+                (row as f32, row as f32 + 1.0)
+            };
+            let mut extent = union_extent(geometry.extent_at(start), geometry.extent_at(end));
+            for &line in critical_lines.iter().filter(|&&line| line > start && line < end) {
+                extent = union_extent(extent, geometry.extent_at(line));
+            }
+            extent.map(|(start, end)| {
+                if inline_reversed {
+                    (inline_length - end, inline_length - start)
+                } else {
+                    (start, end)
+                }
+            })
+        }).collect();
+
+        // Expand the shape by `shape-margin`, rounding the corners of the expanded area.
+        let rows = if shape_margin > 0.0 { expand_rows(&rows, shape_margin) } else { rows };
+        let rows = rows.into_iter().map(|extent| {
+            extent.and_then(|(start, end)| {
+                let (start, end) = (start.max(0.0), end.min(inline_length));
+                if start < end || (start == end && end > 0.0 && end < inline_length) {
+                    Some((Au::from_f32_px(start), Au::from_f32_px(end)))
+                } else {
+                    None
+                }
+            })
+        }).collect();
+
+        FloatShape {
+            rows: rows,
+        }
+    }
+
+    /// Returns the range of rows covering the given block range, which is treated as a line if it
+    /// is empty.
+    fn rows_between(&self, block_start: Au, block_end: Au) -> (usize, usize) {
+        let row_size = Au::from_px(1);
+        let first = max(block_start.0 / row_size.0, 0) as usize;
+        let last = max((block_end.0 + row_size.0 - 1) / row_size.0, 0) as usize;
+        (min(first, self.rows.len()), min(max(last, first + 1), self.rows.len()))
+    }
+
+    /// Returns the inline extent of the float area between the given block positions, or `None`
+    /// if the float area is empty there.
+    fn inline_extent(&self, block_start: Au, block_end: Au) -> Option<(Au, Au)> {
+        let (first, last) = self.rows_between(block_start, block_end);
+        self.rows[first..last].iter().fold(None, |extent, row| {
+            match (extent, *row) {
+                (Some((start, end)), Some((row_start, row_end))) => {
+                    Some((min(start, row_start), max(end, row_end)))
+                }
+                (extent, None) => extent,
+                (None, row) => row,
+            }
+        })
+    }
+
+    /// Returns the block position of the first row at or after `block_start` in which the float
+    /// area overlaps the given inline range.
+    fn first_collision(&self, block_start: Au, inline_start: Au, inline_end: Au) -> Option<Au> {
+        let (first, _) = self.rows_between(block_start, block_start);
+        self.rows[first..].iter().position(|row| {
+            match *row {
+                Some((start, end)) => end > inline_start && start < inline_end,
+                None => false,
+            }
+        }).map(|row| max(block_start, Au::from_px((first + row) as i32)))
+    }
+}
+
+/// Expands the inline extents of the given rows by `shape_margin`, so that they cover every
+/// point within that distance of the shape.
+fn expand_rows(rows: &[Option<(f32, f32)>], shape_margin: f32) -> Vec<Option<(f32, f32)>> {
+    // The start edges are negated so that both edges move outwards as they grow.
+    let starts: Vec<_> = rows.iter().enumerate().filter_map(|(row, extent)| {
+        extent.map(|(start, _)| (row, -start))
+    }).collect();
+    let ends: Vec<_> = rows.iter().enumerate().filter_map(|(row, extent)| {
+        extent.map(|(_, end)| (row, end))
+    }).collect();
+    let starts = Dilation::new(&starts, shape_margin).dilate(rows.len());
+    let ends = Dilation::new(&ends, shape_margin).dilate(rows.len());
+    starts.into_iter().zip(ends).map(|(start, end)| {
+        start.and_then(|start| end.map(|end| (-start, end)))
+    }).collect()
+}
+
+/// The expansion of one edge of a shape by `shape-margin`: each row of the expanded edge is as
+/// far out as the furthest of the edge's rows within reach, pushed out by the margin left at
+/// that distance.
+///
+/// That outset is a concave function of the distance between the rows, so the row that pushes
+/// the expanded edge the furthest never moves back up as the expanded row moves down. Finding it
+/// for the middle row therefore bounds the search for the rows above and below, and the whole
+/// edge is expanded in O((rows + reach) log rows) rather than O(rows × reach).
+struct Dilation<'a> {
+    /// The rows that the edge is on, with its position in each of them, in row order.
+    edge: &'a [(usize, f32)],
+    shape_margin: f32,
+    /// The largest number of rows between two rows that are within reach of each other.
+    reach: usize,
+}
+
+impl<'a> Dilation<'a> {
+    fn new(edge: &'a [(usize, f32)], shape_margin: f32) -> Dilation<'a> {
+        Dilation {
+            edge: edge,
+            shape_margin: shape_margin,
+            reach: shape_margin.floor() as usize + 1,
+        }
+    }
+
+    /// How far an edge pushes out the expanded edge at the given number of rows from it. Rows
+    /// are one pixel high, so adjacent rows touch.
+    fn outset(&self, rows: usize) -> f32 {
+        let distance = rows.saturating_sub(1) as f32;
+        (self.shape_margin * self.shape_margin - distance * distance).max(0.0).sqrt()
+    }
+
+    /// Returns the indices in `edge` of the rows within reach of the given row.
+    fn within_reach(&self, row: usize) -> Range<usize> {
+        let (first, last) = (row.saturating_sub(self.reach), row + self.reach);
+        let start = self.edge.binary_search_by(|&(edge_row, _)| {
+            if edge_row < first { Ordering::Less } else { Ordering::Greater }
+        }).unwrap_err();
+        let end = self.edge.binary_search_by(|&(edge_row, _)| {
+            if edge_row <= last { Ordering::Less } else { Ordering::Greater }
+        }).unwrap_err();
+        start..end
+    }
+
+    /// Returns the position of the expanded edge in each of `row_count` rows.
+    fn dilate(&self, row_count: usize) -> Vec<Option<f32>> {
+        let mut dilated = vec![None; row_count];
+        self.dilate_rows(0..row_count, 0..self.edge.len(), &mut dilated);
+        dilated
+    }
+
+    /// Expands the edge in the given rows, knowing that the rows that push them the furthest are
+    /// within the given indices of `edge`.
+    fn dilate_rows(&self, rows: Range<usize>, candidates: Range<usize>, dilated: &mut [Option<f32>]) {
+        if rows.start >= rows.end {
+            return
+        }
+        let row = rows.start + (rows.end - rows.start) / 2;
+        let within_reach = self.within_reach(row);
+        let mut furthest: Option<(usize, f32)> = None;
+        for index in max(candidates.start, within_reach.start)..min(candidates.end, within_reach.end) {
+            let (edge_row, position) = self.edge[index];
+            let position = position + self.outset(max(row, edge_row) - min(row, edge_row));
+            if furthest.map_or(true, |(_, furthest_position)| position > furthest_position) {
+                furthest = Some((index, position));
+            }
+        }
+        dilated[row] = furthest.map(|(_, position)| position);
+        let split = furthest.map_or(within_reach.start, |(index, _)| index);
+        self.dilate_rows(rows.start..row, candidates.start..split + 1, dilated);
+        self.dilate_rows(row + 1..rows.end, split..candidates.end, dilated);
+    }
+}
+
+/// The geometry of a shape in CSS pixels. For float areas, it is relative to the float's margin
+/// box; for `clip-path` shapes, to the stacking context of the clipped element.
+pub enum ShapeGeometry {
+    /// An ellipse with the given center and radii.
+    Ellipse(Point2D<f32>, Size2D<f32>),
+    /// A rectangle with the given top-left, top-right, bottom-right and bottom-left corner radii.
+    RoundedRect(Rect<f32>, [Size2D<f32>; 4]),
+    /// A polygon with the given vertices.
+    Polygon(Vec<Point2D<f32>>),
+    /// An image drawn into the given rectangle, with the given number of columns and rows of
+    /// pixels, and whether each of those pixels lies within the shape.
+    Mask(Rect<f32>, usize, usize, Vec<bool>),
+}
+
+impl ShapeGeometry {
+    /// Builds the geometry of an image shape from the pixels whose alpha exceeds `threshold`.
+    fn from_image(image: &Image, rect: Rect<f32>, threshold: f32) -> ShapeGeometry {
+        let (bytes_per_pixel, alpha_offset) = match image.format {
+            PixelFormat::K8 => (1, None),
+            PixelFormat::KA8 => (2, Some(1)),
+            PixelFormat::RGB8 => (3, None),
+            PixelFormat::BGRA8 => (4, Some(3)),
+        };
+        let threshold = (threshold * 255.0) as u8;
+        let pixels = image.bytes.chunks(bytes_per_pixel).map(|pixel| {
+            alpha_offset.map_or(true, |offset| pixel[offset] > threshold)
+        }).collect();
+        ShapeGeometry::Mask(rect, image.width as usize, image.height as usize, pixels)
+    }
+
+    /// Swaps the horizontal and vertical axes of this geometry.
+    fn transpose(self) -> ShapeGeometry {
+        fn transpose_size(size: Size2D<f32>) -> Size2D<f32> {
+            Size2D::new(size.height, size.width)
+        }
+        fn transpose_rect(rect: Rect<f32>) -> Rect<f32> {
+            Rect::new(Point2D::new(rect.origin.y, rect.origin.x), transpose_size(rect.size))
+        }
+
+        match self {
+            ShapeGeometry::Ellipse(center, radii) => {
+                ShapeGeometry::Ellipse(Point2D::new(center.y, center.x), transpose_size(radii))
+            }
+            ShapeGeometry::RoundedRect(rect, radii) => {
+                ShapeGeometry::RoundedRect(transpose_rect(rect), [
+                    transpose_size(radii[0]),
+                    transpose_size(radii[3]),
+                    transpose_size(radii[2]),
+                    transpose_size(radii[1]),
+                ])
+            }
+            ShapeGeometry::Polygon(vertices) => {
+                ShapeGeometry::Polygon(vertices.iter().map(|vertex| {
+                    Point2D::new(vertex.y, vertex.x)
+                }).collect())
+            }
+            ShapeGeometry::Mask(rect, columns, rows, pixels) => {
+                let mut transposed = Vec::with_capacity(pixels.len());
+                for column in 0..columns {
+                    for row in 0..rows {
+                        transposed.push(pixels[row * columns + column]);
+                    }
+                }
+                ShapeGeometry::Mask(transpose_rect(rect), rows, columns, transposed)
+            }
+        }
+    }
+
+    /// Returns the vertical positions at which the horizontal extent of this geometry changes
+    /// direction, which must be sampled in addition to the edges of each row.
+    fn critical_lines(&self) -> Vec<f32> {
+        match *self {
+            ShapeGeometry::Ellipse(center, _) => vec![center.y],
+            ShapeGeometry::RoundedRect(ref rect, ref radii) => {
+                vec![rect.origin.y + radii[0].height,
+                     rect.origin.y + radii[1].height,
+                     rect.max_y() - radii[2].height,
+                     rect.max_y() - radii[3].height]
+            }
+            ShapeGeometry::Polygon(ref vertices) => vertices.iter().map(|vertex| vertex.y).collect(),
+            ShapeGeometry::Mask(ref rect, _, rows, _) => {
+                (0..rows).map(|row| rect.origin.y + (row as f32 + 0.5) * rect.size.height / rows as f32)
+                         .collect()
+            }
+        }
+    }
+
+    /// Returns the horizontal extent of this geometry along the horizontal line at `y`.
+    fn extent_at(&self, y: f32) -> Option<(f32, f32)> {
+        match *self {
+            ShapeGeometry::Ellipse(center, radii) => {
+                if radii.width <= 0.0 || radii.height <= 0.0 || (y - center.y).abs() > radii.height {
+                    return None
+                }
+                let dy = (y - center.y) / radii.height;
+                let dx = radii.width * (1.0 - dy * dy).max(0.0).sqrt();
+                Some((center.x - dx, center.x + dx))
+            }
+            ShapeGeometry::RoundedRect(ref rect, ref radii) => {
+                if y < rect.origin.y || y > rect.max_y() || rect.size.width <= 0.0 {
+                    return None
+                }
+                // The horizontal distance of the curve of a corner from the edge of `rect`.
+                let corner_inset = |radius: Size2D<f32>, distance_from_edge: f32| {
+                    if radius.width <= 0.0 || radius.height <= 0.0 || distance_from_edge >= radius.height {
+                        return 0.0
+                    }
+                    let dy = (radius.height - distance_from_edge) / radius.height;
+                    radius.width * (1.0 - (1.0 - dy * dy).max(0.0).sqrt())
+                };
+                let from_top = y - rect.origin.y;
+                let from_bottom = rect.max_y() - y;
+                let left = corner_inset(radii[0], from_top).max(corner_inset(radii[3], from_bottom));
+                let right = corner_inset(radii[1], from_top).max(corner_inset(radii[2], from_bottom));
+                Some((rect.origin.x + left, rect.max_x() - right))
+            }
+            ShapeGeometry::Polygon(ref vertices) => {
+                let mut extent = None;
+                for (index, start) in vertices.iter().enumerate() {
+                    let end = &vertices[(index + 1) % vertices.len()];
+                    if y < start.y.min(end.y) || y > start.y.max(end.y) {
+                        continue
+                    }
+                    let (left, right) = if start.y == end.y {
+                        (start.x.min(end.x), start.x.max(end.x))
+                    } else {
+                        let x = start.x + (y - start.y) * (end.x - start.x) / (end.y - start.y);
+                        (x, x)
+                    };
+                    extent = union_extent(extent, Some((left, right)));
+                }
+                extent
+            }
+            ShapeGeometry::Mask(ref rect, columns, rows, ref pixels) => {
+                if rect.size.height <= 0.0 || y < rect.origin.y || y >= rect.max_y() {
+                    return None
+                }
+                let row = ((y - rect.origin.y) * rows as f32 / rect.size.height) as usize;
+                let row = match pixels.get(row * columns..(row + 1) * columns) {
+                    Some(row) => row,
+                    None => return None,
+                };
+                let first = row.iter().position(|&inside| inside);
+                let last = row.iter().rposition(|&inside| inside);
+                let column_width = rect.size.width / columns as f32;
+                first.and_then(|first| last.map(|last| {
+                    (rect.origin.x + first as f32 * column_width,
+                     rect.origin.x + (last + 1) as f32 * column_width)
+                }))
+            }
+        }
+    }
+}
+
+/// Computes the geometry of a basic shape within the given reference box.
+pub fn basic_shape_geometry(shape: &BasicShape, reference_box: &Rect<Au>) -> ShapeGeometry {
+    let size = reference_box.size;
+    let origin = Point2D::new(reference_box.origin.x.to_f32_px(), reference_box.origin.y.to_f32_px());
+    let resolve = |length: &LengthOrPercentage, basis: Au| length.to_used_value(basis).to_f32_px();
+    let width = size.width.to_f32_px();
+    let height = size.height.to_f32_px();
+
+    match *shape {
+        GenericBasicShape::Inset(ref inset) => {
+            let top = resolve(&inset.rect.0, size.height);
+            let right = resolve(&inset.rect.1, size.width);
+            let bottom = resolve(&inset.rect.2, size.height);
+            let left = resolve(&inset.rect.3, size.width);
+            let rect = Rect::new(Point2D::new(reference_box.origin.x + Au::from_f32_px(left),
+                                              reference_box.origin.y + Au::from_f32_px(top)),
+                                 Size2D::new(Au::from_f32_px((width - left - right).max(0.0)),
+                                             Au::from_f32_px((height - top - bottom).max(0.0))));
+            let mut radii = [Size2D::zero(); 4];
+            if let Some(ref round) = inset.round {
+                let corners = [&round.top_left, &round.top_right, &round.bottom_right, &round.bottom_left];
+                for (radius, corner) in radii.iter_mut().zip(corners.iter()) {
+                    *radius = Size2D::new(resolve(corner.0.width(), size.width),
+                                          resolve(corner.0.height(), size.height));
+                }
+            }
+            ShapeGeometry::RoundedRect(to_px_rect(&rect), normalize_radii(&rect, radii))
+        }
+        GenericBasicShape::Circle(ref circle) => {
+            let center = Point2D::new(resolve(&circle.position.horizontal, size.width),
+                                      resolve(&circle.position.vertical, size.height));
+            let sides = [center.x.abs(), (width - center.x).abs(),
+                         center.y.abs(), (height - center.y).abs()];
+            let radius = match circle.radius {
+                ShapeRadius::Length(ref length) => {
+                    let basis = ((width * width + height * height) / 2.0).sqrt();
+                    resolve(length, Au::from_f32_px(basis))
+                }
+                ShapeRadius::ClosestSide => sides.iter().fold(f32::INFINITY, |a, &b| a.min(b)),
+                ShapeRadius::FarthestSide => sides.iter().fold(0.0, |a, &b| a.max(b)),
+            };
+            ShapeGeometry::Ellipse(origin + center.to_vector(), Size2D::new(radius, radius))
+        }
+        GenericBasicShape::Ellipse(ref ellipse) => {
+            let center = Point2D::new(resolve(&ellipse.position.horizontal, size.width),
+                                      resolve(&ellipse.position.vertical, size.height));
+            let semiaxis = |radius: &ShapeRadius<LengthOrPercentage>, position: f32, length: f32, basis: Au| {
+                match *radius {
+                    ShapeRadius::Length(ref radius) => resolve(radius, basis),
+                    ShapeRadius::ClosestSide => position.abs().min((length - position).abs()),
+                    ShapeRadius::FarthestSide => position.abs().max((length - position).abs()),
+                }
+            };
+            let radii = Size2D::new(semiaxis(&ellipse.semiaxis_x, center.x, width, size.width),
+                                    semiaxis(&ellipse.semiaxis_y, center.y, height, size.height));
+            ShapeGeometry::Ellipse(origin + center.to_vector(), radii)
+        }
+        GenericBasicShape::Polygon(ref polygon) => {
+            ShapeGeometry::Polygon(polygon.coordinates.iter().map(|&(ref x, ref y)| {
+                Point2D::new(origin.x + resolve(x, size.width), origin.y + resolve(y, size.height))
+            }).collect())
+        }
+    }
+}
+
+/// Adjusts a border radius for a box outset from the border box by `outset`.
+///
+/// https://drafts.csswg.org/css-backgrounds/#corner-shaping
+fn adjust_radius(radius: f32, outset: f32) -> f32 {
+    if radius <= 0.0 {
+        0.0
+    } else {
+        (radius + outset).max(0.0)
+    }
+}
+
+/// Scales down the given corner radii so that adjacent corners of `rect` do not overlap.
+///
+/// https://drafts.csswg.org/css-backgrounds/#corner-overlap
+fn normalize_radii(rect: &Rect<Au>, radii: [Size2D<f32>; 4]) -> [Size2D<f32>; 4] {
+    let width = rect.size.width.to_f32_px();
+    let height = rect.size.height.to_f32_px();
+    let ratio = |length: f32, first: f32, second: f32| {
+        if first + second > 0.0 { length / (first + second) } else { 1.0 }
+    };
+    let scale = ratio(width, radii[0].width, radii[1].width)
+        .min(ratio(width, radii[3].width, radii[2].width))
+        .min(ratio(height, radii[0].height, radii[3].height))
+        .min(ratio(height, radii[1].height, radii[2].height))
+        .min(1.0);
+    [radii[0] * scale, radii[1] * scale, radii[2] * scale, radii[3] * scale]
+}
+
+fn inset_rect(rect: &Rect<Au>, offsets: &SideOffsets2D<Au>) -> Rect<Au> {
+    Rect::new(Point2D::new(rect.origin.x + offsets.left, rect.origin.y + offsets.top),
+              Size2D::new(max(rect.size.width - offsets.left - offsets.right, Au(0)),
+                          max(rect.size.height - offsets.top - offsets.bottom, Au(0))))
+}
+
+fn to_px_rect(rect: &Rect<Au>) -> Rect<f32> {
+    Rect::new(Point2D::new(rect.origin.x.to_f32_px(), rect.origin.y.to_f32_px()),
+              Size2D::new(rect.size.width.to_f32_px(), rect.size.height.to_f32_px()))
+}
+
+fn union_extent(first: Option<(f32, f32)>, second: Option<(f32, f32)>) -> Option<(f32, f32)> {
+    match (first, second) {
+        (Some((start, end)), Some((other_start, other_end))) => {
+            Some((start.min(other_start), end.max(other_end)))
+        }
+        (extent, None) | (None, extent) => extent,
+    }
+}
+
 /// The speculated inline sizes of floats flowing through or around a flow (depending on whether
 /// the flow is a block formatting context). These speculations are always *upper bounds*; the
 /// actual inline sizes might be less. Note that this implies that a speculated value of zero is a
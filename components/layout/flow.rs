@@ -255,13 +255,7 @@ pub trait Flow: HasBaseFlow + fmt::Debug + Sync + Send + 'static {
                 layout_context: &LayoutContext,
                 _fragmentation_context: Option<FragmentationContext>)
                 -> Option<Arc<Flow>> {
-        fn recursive_assign_block_size<F: ?Sized + Flow + GetBaseFlow>(flow: &mut F, ctx: &LayoutContext) {
-            for child in flow.mut_base().child_iter_mut() {
-                recursive_assign_block_size(child, ctx)
-            }
-            flow.assign_block_size(ctx);
-        }
-        recursive_assign_block_size(self, layout_context);
+        assign_block_size_recursively(self, layout_context);
         None
     }
 
@@ -826,8 +820,32 @@ impl LateAbsolutePositionInfo {
     }
 }
 
+/// Assigns block-sizes to a flow and all of its descendants, children first.
+///
+/// This is what the assign-block-sizes traversal does, for flows that it skips because they can
+/// be fragmented.
+pub fn assign_block_size_recursively<F: ?Sized + Flow + GetBaseFlow>(flow: &mut F,
+                                                                     ctx: &LayoutContext) {
+    for child in flow.mut_base().child_iter_mut() {
+        assign_block_size_recursively(child, ctx)
+    }
+    flow.assign_block_size(ctx);
+}
+
+/// The kind of fragmentainers content is being fragmented into.
+///
+/// https://drafts.csswg.org/css-break/#fragmentation-context
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FragmentationKind {
+    /// The columns of a multi-column container.
+    Column,
+    /// The pages of paged media.
+    Page,
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct FragmentationContext {
+    pub kind: FragmentationKind,
     pub available_block_size: Au,
     pub this_fragment_is_empty: bool,
 }
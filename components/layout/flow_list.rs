@@ -93,6 +93,18 @@ impl FlowList {
         self.flows.front().map(|x| &**x)
     }
 
+    pub fn pop_front(&mut self) -> Option<FlowRef> {
+        self.flows.pop_front()
+    }
+
+    /// Returns another reference to the flow at the given index, for flows laid out in several
+    /// fragments at once, such as repeated table headers.
+    ///
+    /// O(n)
+    pub fn get_ref(&self, index: usize) -> Option<FlowRef> {
+        self.flows.iter().nth(index).cloned()
+    }
+
     /// Create an empty list
     #[inline]
     pub fn new() -> FlowList {
@@ -155,6 +167,14 @@ impl FlowList {
             flows: self.flows.split_off(i)
         }
     }
+
+    /// Moves all the flows of `other` to the end of this list.
+    ///
+    /// O(1)
+    #[inline]
+    pub fn append(&mut self, mut other: FlowList) {
+        self.flows.append(&mut other.flows)
+    }
 }
 
 impl<'a> DoubleEndedIterator for FlowListIterator<'a> {
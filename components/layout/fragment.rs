@@ -19,6 +19,7 @@ use flow_ref::FlowRef;
 use gfx;
 use gfx::text::glyph::ByteIndex;
 use gfx::text::text_run::{TextRun, TextRunSlice};
+use gfx::text::util::SOFT_HYPHEN;
 use gfx_traits::StackingContextId;
 use inline::{InlineFragmentNodeFlags, InlineFragmentContext, InlineFragmentNodeInfo};
 use inline::{InlineMetrics, LineMetrics};
@@ -47,6 +48,7 @@ use style::computed_values::clear::T as Clear;
 use style::computed_values::color::T as Color;
 use style::computed_values::display::T as Display;
 use style::computed_values::mix_blend_mode::T as MixBlendMode;
+use style::computed_values::object_fit::T as ObjectFit;
 use style::computed_values::overflow_wrap::T as OverflowWrap;
 use style::computed_values::overflow_x::T as StyleOverflow;
 use style::computed_values::position::T as Position;
@@ -54,6 +56,7 @@ use style::computed_values::text_decoration_line::T as TextDecorationLine;
 use style::computed_values::transform_style::T as TransformStyle;
 use style::computed_values::white_space::T as WhiteSpace;
 use style::computed_values::word_break::T as WordBreak;
+use style::context::SharedStyleContext;
 use style::logical_geometry::{Direction, LogicalMargin, LogicalRect, LogicalSize, WritingMode};
 use style::properties::ComputedValues;
 use style::selector_parser::RestyleDamage;
@@ -63,6 +66,7 @@ use style::values::computed::{Length, LengthOrPercentage, LengthOrPercentageOrAu
 use style::values::computed::counters::ContentItem;
 use style::values::generics::box_::{Perspective, VerticalAlign};
 use style::values::generics::transform;
+use svg::SvgScene;
 use text;
 use text::TextRunScanner;
 use webrender_api::{self, LayoutTransform};
@@ -377,13 +381,19 @@ impl CanvasFragmentInfo {
 pub struct SvgFragmentInfo {
     pub dom_width: Au,
     pub dom_height: Au,
+    /// The shapes and text of the subtree of the `svg` element.
+    pub scene: Arc<SvgScene>,
 }
 
 impl SvgFragmentInfo {
-    pub fn new(data: SVGSVGData) -> SvgFragmentInfo {
+    pub fn new<N: ThreadSafeLayoutNode>(node: &N,
+                                        data: SVGSVGData,
+                                        context: &SharedStyleContext)
+                                        -> SvgFragmentInfo {
         SvgFragmentInfo {
             dom_width: Au::from_px(data.width as i32),
             dom_height: Au::from_px(data.height as i32),
+            scene: Arc::new(SvgScene::new(node, &data, context)),
         }
     }
 }
@@ -394,6 +404,8 @@ impl SvgFragmentInfo {
 pub struct ImageFragmentInfo {
     pub image: Option<Arc<Image>>,
     pub metadata: Option<ImageMetadata>,
+    /// The URL of the image, used to rasterize vector images at the size they are drawn at.
+    pub url: Option<ServoUrl>,
 }
 
 impl ImageFragmentInfo {
@@ -405,7 +417,7 @@ impl ImageFragmentInfo {
                                         node: &N,
                                         layout_context: &LayoutContext)
                -> ImageFragmentInfo {
-        let image_or_metadata = url.and_then(|url| {
+        let image_or_metadata = url.clone().and_then(|url| {
             layout_context.get_or_request_image_or_meta(node.opaque(),
                                                         url,
                                                         UsePlaceholder::Yes)
@@ -426,6 +438,7 @@ impl ImageFragmentInfo {
         ImageFragmentInfo {
             image: image,
             metadata: metadata,
+            url: url,
         }
     }
 }
@@ -491,6 +504,14 @@ bitflags! {
         ///
         /// This handles cases like Foo<span>bar</span>
         const SUPPRESS_LINE_BREAK_BEFORE = 0x04;
+
+        /// Was the line broken at the soft hyphen that ends this fragment? If so, a hyphen is
+        /// rendered after it.
+        const HYPHENATED = 0x08;
+
+        /// Is the text of this fragment composed horizontally into a single em of a vertical line,
+        /// per `text-combine-upright: all`?
+        const COMBINED_UPRIGHT = 0x10;
     }
 }
 
@@ -523,6 +544,22 @@ impl ScannedTextFragmentInfo {
     pub fn selected(&self) -> bool {
         self.flags.contains(ScannedTextFlags::SELECTED)
     }
+
+    pub fn hyphenated(&self) -> bool {
+        self.flags.contains(ScannedTextFlags::HYPHENATED)
+    }
+
+    pub fn combined_upright(&self) -> bool {
+        self.flags.contains(ScannedTextFlags::COMBINED_UPRIGHT)
+    }
+
+    /// The advance of the hyphen rendered after this fragment, if any.
+    pub fn hyphen_advance(&self) -> Au {
+        match self.run.hyphen_glyph {
+            Some((_, advance, _)) if self.hyphenated() => advance,
+            _ => Au(0),
+        }
+    }
 }
 
 /// Describes how to split a fragment. This is used during line breaking as part of the return
@@ -533,6 +570,9 @@ pub struct SplitInfo {
     // currently needed for splitting in the `inline::try_append_*` functions.
     pub range: Range<ByteIndex>,
     pub inline_size: Au,
+    /// Whether the line is broken at a soft hyphen ending this part, whose hyphen is included in
+    /// `inline_size`.
+    pub hyphenated: bool,
 }
 
 impl SplitInfo {
@@ -541,6 +581,7 @@ impl SplitInfo {
         SplitInfo {
             range: range,
             inline_size: inline_size,
+            hyphenated: false,
         }
     }
 }
@@ -751,6 +792,7 @@ impl Fragment {
         if !first {
             flags.set(ScannedTextFlags::SUPPRESS_LINE_BREAK_BEFORE, false);
         }
+        flags.set(ScannedTextFlags::HYPHENATED, split.hyphenated);
 
         let info = Box::new(ScannedTextFragmentInfo::new(
             text_run,
@@ -1115,6 +1157,61 @@ impl Fragment {
         }
     }
 
+    /// Returns the rectangle, relative to the same origin as `content_box`, into which the
+    /// contents of this replaced element are drawn according to `object-fit` and
+    /// `object-position`. The result may extend outside of `content_box`.
+    ///
+    /// https://drafts.csswg.org/css-images-3/#the-object-fit
+    pub fn object_fit_rect(&self, content_box: Rect<Au>) -> Rect<Au> {
+        let (intrinsic_width, intrinsic_height) = match self.specific {
+            SpecificFragmentInfo::Image(_) | SpecificFragmentInfo::Canvas(_) => {
+                (self.intrinsic_width(), self.intrinsic_height())
+            }
+            _ => return content_box,
+        };
+        if intrinsic_width == Au(0) || intrinsic_height == Au(0) {
+            return content_box;
+        }
+
+        // The size that fits the intrinsic aspect ratio into the content box, either entirely
+        // inside of it (`contain`) or entirely covering it (`cover`).
+        let fit_size = |cover: bool| {
+            let width_fits = content_box.size.width.0 as i64 * intrinsic_height.0 as i64 <=
+                content_box.size.height.0 as i64 * intrinsic_width.0 as i64;
+            if width_fits != cover {
+                Size2D::new(content_box.size.width,
+                            Au::new((content_box.size.width.0 as i64 * intrinsic_height.0 as i64 /
+                                     intrinsic_width.0 as i64) as i32))
+            } else {
+                Size2D::new(Au::new((content_box.size.height.0 as i64 * intrinsic_width.0 as i64 /
+                                     intrinsic_height.0 as i64) as i32),
+                            content_box.size.height)
+            }
+        };
+
+        let position = self.style.get_position();
+        let size = match position.object_fit {
+            ObjectFit::Fill => return content_box,
+            ObjectFit::Contain => fit_size(false),
+            ObjectFit::Cover => fit_size(true),
+            ObjectFit::None => Size2D::new(intrinsic_width, intrinsic_height),
+            ObjectFit::ScaleDown => {
+                let contain_size = fit_size(false);
+                if contain_size.width < intrinsic_width {
+                    contain_size
+                } else {
+                    Size2D::new(intrinsic_width, intrinsic_height)
+                }
+            }
+        };
+
+        let object_position = &position.object_position;
+        let offset = Vector2D::new(
+            object_position.horizontal.to_used_value(content_box.size.width - size.width),
+            object_position.vertical.to_used_value(content_box.size.height - size.height));
+        Rect::new(content_box.origin + offset, size)
+    }
+
     /// Return a size constraint that can be used the clamp size in given direction.
     /// To take `box-sizing: border-box` into account, the `border_padding` field
     /// must be initialized first.
@@ -1430,9 +1527,14 @@ impl Fragment {
     }
 
     /// Returns true if this element can be split. This is true for text fragments, unless
-    /// `white-space: pre` or `white-space: nowrap` is set.
+    /// `white-space: pre` or `white-space: nowrap` is set or the text is combined upright.
     pub fn can_split(&self) -> bool {
-        self.is_scanned_text_fragment() && self.white_space().allow_wrap()
+        match self.specific {
+            SpecificFragmentInfo::ScannedText(ref info) => {
+                self.white_space().allow_wrap() && !info.combined_upright()
+            }
+            _ => false,
+        }
     }
 
     /// Returns true if and only if this fragment is a generated content fragment.
@@ -1545,6 +1647,15 @@ impl Fragment {
                        result: &mut IntrinsicISizesContribution) {
             let range = &text_fragment_info.range;
 
+            if text_fragment_info.combined_upright() {
+                let em = text_fragment_info.run.actual_pt_size;
+                result.union_block(&IntrinsicISizes {
+                    minimum_inline_size: em,
+                    preferred_inline_size: em,
+                });
+                return
+            }
+
             // See http://dev.w3.org/csswg/css-sizing/#max-content-inline-size.
             // TODO: Account for soft wrap opportunities.
             let max_line_inline_size = text_fragment_info.run
@@ -1601,6 +1712,9 @@ impl Fragment {
                 let text = t.text_info.as_ref().unwrap();
                 text.run.minimum_splittable_inline_size(&text.range)
             }
+            SpecificFragmentInfo::ScannedText(ref text) if text.combined_upright() => {
+                text.run.actual_pt_size
+            }
             SpecificFragmentInfo::ScannedText(ref text) => {
                 text.run.minimum_splittable_inline_size(&text.range)
             }
@@ -1627,7 +1741,7 @@ impl Fragment {
                                     -> Option<SplitResult> {
         let text_fragment_info = match self.specific {
             SpecificFragmentInfo::ScannedText(ref text_fragment_info)
-                => text_fragment_info,
+                if !text_fragment_info.combined_upright() => text_fragment_info,
             _   => return None,
         };
 
@@ -1683,12 +1797,12 @@ impl Fragment {
         }
         let info = self.calculate_truncate_to_inline_size(max_inline_size);
         let (size, text_info) = match info {
-            Some(TruncationResult { split: SplitInfo { inline_size, range }, text_run } ) => {
+            Some(TruncationResult { split: SplitInfo { inline_size, range, .. }, text_run } ) => {
                 let size = LogicalSize::new(self.style.writing_mode,
                                             inline_size,
                                             self.border_box.size.block);
                 // Preserve the insertion point if it is in this fragment's range or it is at line end.
-                let (flags, insertion_point) = match self.specific {
+                let (mut flags, insertion_point) = match self.specific {
                     SpecificFragmentInfo::ScannedText(ref info) => {
                         match info.insertion_point {
                             Some(index) if range.contains(index) => (info.flags, info.insertion_point),
@@ -1699,6 +1813,7 @@ impl Fragment {
                     },
                     _ => (ScannedTextFlags::empty(), None)
                 };
+                flags.remove(ScannedTextFlags::HYPHENATED);
                 let text_info = ScannedTextFragmentInfo::new(
                     text_run,
                     range,
@@ -1781,8 +1896,17 @@ impl Fragment {
             let metrics = text_fragment_info.run.metrics_for_slice(slice.glyphs, &slice.range);
             let advance = metrics.advance_width;
 
+            // If the line were broken after a soft hyphen, its hyphen would have to fit too.
+            let slice_range = slice.text_run_range();
+            let slice_text = &text_fragment_info.run.text[slice_range.begin().to_usize()..
+                                                          slice_range.end().to_usize()];
+            let hyphen_advance = match text_fragment_info.run.hyphen_glyph {
+                Some((_, hyphen_advance, _)) if slice_text.ends_with(SOFT_HYPHEN) => hyphen_advance,
+                _ => Au(0),
+            };
+
             // Have we found the split point?
-            if advance <= remaining_inline_size || slice.glyphs.is_whitespace() {
+            if advance + hyphen_advance <= remaining_inline_size || slice.glyphs.is_whitespace() {
                 // Keep going; we haven't found the split point yet.
                 debug!("calculate_split_position_using_breaking_strategy: enlarging span");
                 remaining_inline_size = remaining_inline_size - advance;
@@ -1846,7 +1970,16 @@ impl Fragment {
         let split_is_empty = inline_start_range.is_empty() &&
                 !self.requires_line_break_afterward_if_wrapping_on_newlines();
         let inline_start = if !split_is_empty {
-            Some(SplitInfo::new(inline_start_range, &**text_fragment_info))
+            let mut split = SplitInfo::new(inline_start_range, &**text_fragment_info);
+            let text = &text_fragment_info.run.text[inline_start_range.begin().to_usize()..
+                                                    inline_start_range.end().to_usize()];
+            if let Some((_, hyphen_advance, _)) = text_fragment_info.run.hyphen_glyph {
+                if inline_end_range.is_some() && text.ends_with(SOFT_HYPHEN) {
+                    split.inline_size = split.inline_size + hyphen_advance;
+                    split.hyphenated = true;
+                }
+            }
+            Some(split)
         } else {
             None
         };
@@ -1876,6 +2009,7 @@ impl Fragment {
                 if other_info.insertion_point.is_some() {
                     this_info.insertion_point = other_info.insertion_point;
                 }
+                this_info.flags.remove(ScannedTextFlags::HYPHENATED);
                 self.border_padding.inline_end = next_fragment.border_padding.inline_end;
                 self.margin.inline_end = next_fragment.margin.inline_end;
             }
@@ -2669,6 +2803,11 @@ impl Fragment {
         fn scanned_text(scanned_text_fragment_info: &mut ScannedTextFragmentInfo,
                         border_box: &mut LogicalRect<Au>)
                         -> WhitespaceStrippingResult {
+            // Combined text takes up an em whatever white space it contains.
+            if scanned_text_fragment_info.combined_upright() {
+                return WhitespaceStrippingResult::RetainFragment
+            }
+
             let leading_whitespace_byte_count = scanned_text_fragment_info.text()
                 .find(|c| !char_is_whitespace(c))
                 .unwrap_or(scanned_text_fragment_info.text().len());
@@ -2735,6 +2874,10 @@ impl Fragment {
         fn scanned_text(scanned_text_fragment_info: &mut ScannedTextFragmentInfo,
                         border_box: &mut LogicalRect<Au>)
                         -> WhitespaceStrippingResult {
+                if scanned_text_fragment_info.combined_upright() {
+                    return WhitespaceStrippingResult::RetainFragment
+                }
+
                 let mut trailing_whitespace_start_byte = 0;
                 for (i, c) in scanned_text_fragment_info.text().char_indices().rev() {
                     if !char_is_whitespace(c) {
@@ -0,0 +1,188 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Helpers for block fragmentation, shared by multi-column and paged layout.
+//!
+//! https://drafts.csswg.org/css-break/
+
+#![deny(unsafe_code)]
+
+use app_units::Au;
+use flow::{BaseFlow, Flow, FlowClass, FlowFlags, FragmentationKind, GetBaseFlow};
+use flow::ImmutableFlowUtils;
+use flow_list::FlowList;
+use std::cmp::{max, min};
+use std::mem;
+use std::ptr;
+use std::sync::Arc;
+use style::computed_values::break_after::T as BreakAfter;
+use style::computed_values::break_before::T as BreakBefore;
+use style::computed_values::break_inside::T as BreakInside;
+use style::servo::restyle_damage::ServoRestyleDamage;
+
+/// Returns true if the break properties of `flow` apply: it is an in-flow block-level box.
+fn has_break_properties(flow: &Flow) -> bool {
+    flow.is_block_like() &&
+        !flow.base().flags.is_float() &&
+        !flow.base().flags.contains(FlowFlags::IS_ABSOLUTELY_POSITIONED)
+}
+
+/// Returns true if `flow` forces a break before itself in the given kind of fragmentation.
+pub fn forces_break_before(flow: &Flow, kind: FragmentationKind) -> bool {
+    if !has_break_properties(flow) {
+        return false
+    }
+    match flow.as_block().fragment.style.get_box().break_before {
+        BreakBefore::Page | BreakBefore::Left | BreakBefore::Right |
+        BreakBefore::Recto | BreakBefore::Verso => kind == FragmentationKind::Page,
+        BreakBefore::Column => kind == FragmentationKind::Column,
+        BreakBefore::Auto | BreakBefore::Avoid | BreakBefore::AvoidPage |
+        BreakBefore::AvoidColumn => false,
+    }
+}
+
+/// Returns true if `flow` forces a break after itself in the given kind of fragmentation.
+pub fn forces_break_after(flow: &Flow, kind: FragmentationKind) -> bool {
+    if !has_break_properties(flow) {
+        return false
+    }
+    match flow.as_block().fragment.style.get_box().break_after {
+        BreakAfter::Page | BreakAfter::Left | BreakAfter::Right |
+        BreakAfter::Recto | BreakAfter::Verso => kind == FragmentationKind::Page,
+        BreakAfter::Column => kind == FragmentationKind::Column,
+        BreakAfter::Auto | BreakAfter::Avoid | BreakAfter::AvoidPage |
+        BreakAfter::AvoidColumn => false,
+    }
+}
+
+/// Returns true if `flow` should not be broken in the given kind of fragmentation.
+pub fn avoids_break_inside(flow: &Flow, kind: FragmentationKind) -> bool {
+    if !has_break_properties(flow) {
+        return false
+    }
+    match flow.as_block().fragment.style.get_box().break_inside {
+        BreakInside::Avoid => true,
+        BreakInside::AvoidPage => kind == FragmentationKind::Page,
+        BreakInside::AvoidColumn => kind == FragmentationKind::Column,
+        BreakInside::Auto => false,
+    }
+}
+
+/// Returns how many lines of an inline formatting context stay in the current fragment, given the
+/// block-end of each line, honoring `orphans` and `widows`. All the lines stay if they fit, and
+/// none if the fragment has content before them and `orphans` or `widows` can't be honored.
+///
+/// https://drafts.csswg.org/css-break/#widows-orphans
+pub fn lines_in_fragment(line_block_ends: &[Au],
+                         available_block_size: Au,
+                         this_fragment_is_empty: bool,
+                         orphans: usize,
+                         widows: usize)
+                         -> usize {
+    let line_count = line_block_ends.len();
+    let fitting_line_count = line_block_ends.iter().take_while(|&&block_end| {
+        block_end <= available_block_size
+    }).count();
+    if fitting_line_count == line_count {
+        return line_count
+    }
+
+    let split_at = min(fitting_line_count, line_count.saturating_sub(widows));
+    if split_at >= orphans && split_at > 0 {
+        split_at
+    } else if this_fragment_is_empty {
+        // Nothing fits in an empty fragment: make progress anyway.
+        max(fitting_line_count, 1)
+    } else {
+        0
+    }
+}
+
+/// Returns the index of the first row of a table row group that goes in the next fragment, if
+/// any, given the block-end of each row and whether the row group can break before it. Rows that
+/// can't be separated from the first row of an empty fragment stay in it.
+pub fn first_row_in_next_fragment<I>(rows: I,
+                                     available_block_size: Au,
+                                     this_fragment_is_empty: bool)
+                                     -> Option<usize>
+                                     where I: IntoIterator<Item = (Au, bool)> {
+    let (mut break_before, mut overflows) = (None, false);
+    for (row_index, (block_end, can_break_before)) in rows.into_iter().enumerate() {
+        // Break before the last row that can be broken before, up to the first one that does not
+        // fit.
+        if can_break_before && (row_index > 0 || !this_fragment_is_empty) {
+            break_before = Some(row_index)
+        }
+        overflows = overflows || block_end > available_block_size;
+        if overflows && break_before.is_some() {
+            return break_before
+        }
+    }
+    None
+}
+
+pub fn take_children(base: &mut BaseFlow) -> FlowList {
+    mem::replace(&mut base.children, FlowList::new())
+}
+
+/// Moves `children`, the children of the continuation of `flow` created by `Flow::fragment`,
+/// back into `flow`. If the last child of `flow` was fragmented too, its own continuation is
+/// joined back into it recursively.
+///
+/// The layout of `flow` is stale afterwards, see `mark_for_reflow`.
+pub fn rejoin_continuation(flow: &mut Flow, mut children: FlowList) {
+    // A table header repeated in the continuation is already in `flow`.
+    let repeats_child = match children.front() {
+        Some(first_child) => flow.base().child_iter().any(|kid| ptr::eq(kid.base(), first_child.base())),
+        None => false,
+    };
+    if repeats_child {
+        children.pop_front();
+    }
+
+    let last_child_was_fragmented = match (flow.base().children.back(), children.front()) {
+        (Some(last_child), Some(first_child)) => is_continuation_of(first_child, last_child),
+        _ => false,
+    };
+    if last_child_was_fragmented {
+        let mut continuation = children.pop_front_arc().unwrap();
+        let continuation = Arc::get_mut(&mut continuation).unwrap();
+        let last_child = flow.mut_base().child_iter_mut().next_back().unwrap();
+        if last_child.class() == FlowClass::Inline {
+            let fragments = mem::replace(&mut continuation.as_mut_inline().fragments.fragments,
+                                         vec![]);
+            last_child.as_mut_inline().fragments.fragments.extend(fragments);
+        } else {
+            rejoin_continuation(last_child, take_children(continuation.mut_base()));
+        }
+    }
+    flow.mut_base().children.append(children);
+}
+
+/// Returns true if `flow` is the continuation of `previous` created by `Flow::fragment`.
+fn is_continuation_of(flow: &Flow, previous: &Flow) -> bool {
+    match (previous.class(), flow.class()) {
+        // Inline content is gathered into a single inline flow between blocks, so two of them
+        // are only adjacent when one was split.
+        (FlowClass::Inline, FlowClass::Inline) => true,
+        (FlowClass::Block, FlowClass::Block) |
+        (FlowClass::TableWrapper, FlowClass::TableWrapper) |
+        (FlowClass::Table, FlowClass::Table) |
+        (FlowClass::TableRowGroup, FlowClass::TableRowGroup) => {
+            !flow.base().flags.contains(FlowFlags::IS_ABSOLUTELY_POSITIONED) &&
+                flow.as_block().fragment.node == previous.as_block().fragment.node &&
+                flow.as_block().fragment.pseudo == previous.as_block().fragment.pseudo
+        }
+        _ => false,
+    }
+}
+
+/// Makes the next layout of `flow` start from scratch, for subtrees laid out more than once.
+pub fn mark_for_reflow(flow: &mut Flow) {
+    flow.mut_base().restyle_damage.insert(ServoRestyleDamage::REFLOW_OUT_OF_FLOW |
+                                          ServoRestyleDamage::REFLOW);
+    for kid in flow.mut_base().child_iter_mut() {
+        mark_for_reflow(kid)
+    }
+}
@@ -13,12 +13,14 @@ use display_list::StackingContextCollectionState;
 use display_list::items::OpaqueNode;
 use euclid::{Point2D, Size2D};
 use floats::{FloatKind, Floats, PlacementInfo};
-use flow::{BaseFlow, Flow, FlowClass, ForceNonfloatedFlag};
+use flow::{self, BaseFlow, Flow, FlowClass, ForceNonfloatedFlag, FragmentationContext};
 use flow::{FlowFlags, EarlyAbsolutePositionInfo, GetBaseFlow, OpaqueFlow};
+use flow_list::FlowList;
 use flow_ref::FlowRef;
 use fragment::{CoordinateSystem, Fragment, FragmentBorderBoxIterator, Overflow};
 use fragment::FragmentFlags;
 use fragment::SpecificFragmentInfo;
+use fragmentation;
 use gfx::font::FontMetrics;
 use gfx_traits::print_tree::PrintTree;
 use layout_debug;
@@ -472,13 +474,14 @@ impl LineBreaker {
         };
 
         // Try to place the fragment between floats.
-        let line_bounds = self.floats.place_between_floats(&PlacementInfo {
+        let line_bounds = self.floats.place_line_between_floats(&PlacementInfo {
             size: LogicalSize::new(self.floats.writing_mode,
                                    placement_inline_size,
                                    first_fragment.border_box.size.block),
             ceiling: ceiling,
             max_inline_size: flow.base.position.size.inline,
             kind: FloatKind::Left,
+            shape: None,
         });
 
         let fragment_margin_box_inline_size = first_fragment.margin_box_inline_size();
@@ -1049,12 +1052,14 @@ impl InlineFlow {
             return
         }
 
-        // First, calculate the number of expansion opportunities (spaces, normally).
+        // First, calculate the number of expansion opportunities (spaces, normally). Combined
+        // upright text always takes up an em, so it has none.
         let mut expansion_opportunities = 0;
         for fragment_index in line.range.each_index() {
             let fragment = fragments.get(fragment_index.to_usize());
             let scanned_text_fragment_info = match fragment.specific {
-                SpecificFragmentInfo::ScannedText(ref info) if !info.range.is_empty() => info,
+                SpecificFragmentInfo::ScannedText(ref info)
+                    if !info.range.is_empty() && !info.combined_upright() => info,
                 _ => continue
             };
             let fragment_range = scanned_text_fragment_info.range;
@@ -1073,15 +1078,17 @@ impl InlineFlow {
         for fragment_index in line.range.each_index() {
             let fragment = fragments.get_mut(fragment_index.to_usize());
             let scanned_text_fragment_info = match fragment.specific {
-                SpecificFragmentInfo::ScannedText(ref mut info) if !info.range.is_empty() => info,
+                SpecificFragmentInfo::ScannedText(ref mut info)
+                    if !info.range.is_empty() && !info.combined_upright() => info,
                 _ => continue
             };
             let fragment_range = scanned_text_fragment_info.range;
+            let hyphen_advance = scanned_text_fragment_info.hyphen_advance();
             let run = Arc::make_mut(&mut scanned_text_fragment_info.run);
             run.extra_word_spacing = space_per_expansion_opportunity;
 
             // Recompute the fragment's border box size.
-            let new_inline_size = run.advance_for_range(&fragment_range);
+            let new_inline_size = run.advance_for_range(&fragment_range) + hyphen_advance;
             let new_size = LogicalSize::new(fragment.style.writing_mode,
                                             new_inline_size,
                                             fragment.border_box.size.block);
@@ -1562,6 +1569,57 @@ impl Flow for InlineFlow {
         }
     }
 
+    fn fragment(&mut self,
+                layout_context: &LayoutContext,
+                fragmentation_context: Option<FragmentationContext>)
+                -> Option<Arc<Flow>> {
+        flow::assign_block_size_recursively(self, layout_context);
+        let ctx = match fragmentation_context {
+            Some(ctx) => ctx,
+            None => return None,
+        };
+        if self.lines.len() < 2 {
+            return None
+        }
+
+        // `orphans` and `widows` are inherited, so the style of the first fragment is that of the
+        // containing block unless overridden.
+        let (orphans, widows) = {
+            let inherited_text = self.fragments.fragments[0].style.get_inherited_text();
+            (inherited_text.orphans.0 as usize, inherited_text.widows.0 as usize)
+        };
+        let line_block_ends = self.lines.iter().map(|line| {
+            line.bounds.start.b + line.bounds.size.block
+        }).collect::<Vec<_>>();
+        let split_at = fragmentation::lines_in_fragment(&line_block_ends,
+                                                        ctx.available_block_size,
+                                                        ctx.this_fragment_is_empty,
+                                                        orphans,
+                                                        widows);
+        if split_at == 0 || split_at == self.lines.len() {
+            // Either everything fits, or the whole flow goes to the next fragment.
+            return None
+        }
+
+        let first_remaining_fragment = self.lines[split_at].range.begin().get() as usize;
+        let remaining_fragments = self.fragments.fragments.split_off(first_remaining_fragment);
+        self.lines.truncate(split_at);
+        self.base.position.size.block = {
+            let last_line = &self.lines[split_at - 1];
+            last_line.bounds.start.b + last_line.bounds.size.block
+        };
+
+        Some(Arc::new(InlineFlow {
+            base: self.base.clone_with_children(FlowList::new()),
+            fragments: InlineFragments {
+                fragments: remaining_fragments,
+            },
+            lines: Vec::new(),
+            minimum_line_metrics: self.minimum_line_metrics,
+            first_line_indentation: Au(0),
+        }))
+    }
+
     fn compute_stacking_relative_position(&mut self, _: &LayoutContext) {
         // First, gather up the positions of all the containing blocks (if any).
         //
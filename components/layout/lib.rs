@@ -6,6 +6,7 @@
 
 extern crate app_units;
 extern crate atomic_refcell;
+extern crate azure;
 #[macro_use]
 extern crate bitflags;
 extern crate canvas_traits;
@@ -29,6 +30,7 @@ extern crate range;
 extern crate rayon;
 extern crate script_layout_interface;
 extern crate script_traits;
+extern crate selectors;
 #[macro_use] extern crate serde;
 extern crate serde_json;
 extern crate servo_arc;
@@ -59,6 +61,7 @@ pub mod flow;
 mod flow_list;
 pub mod flow_ref;
 mod fragment;
+mod fragmentation;
 mod generated_content;
 pub mod incremental;
 mod inline;
@@ -67,10 +70,12 @@ mod list_item;
 mod model;
 mod multicol;
 pub mod opaque_node;
+pub mod paged;
 pub mod parallel;
 mod persistent_list;
 pub mod query;
 pub mod sequential;
+mod svg;
 mod table;
 mod table_caption;
 mod table_cell;
@@ -83,8 +88,11 @@ pub mod traversal;
 pub mod wrapper;
 
 // For unit tests:
+pub use floats::{FloatKind, FloatShape, Floats, PlacementInfo, ShapeGeometry};
+pub use fragmentation::{first_row_in_next_fragment, lines_in_fragment};
 pub use fragment::Fragment;
 pub use fragment::SpecificFragmentInfo;
+pub use text::hyphenation_points;
 pub use self::data::LayoutData;
 
 // We can't use servo_arc for everything in layout, because the Flow stuff uses
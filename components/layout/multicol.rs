@@ -7,24 +7,36 @@
 #![deny(unsafe_code)]
 
 use ServoArc;
-use app_units::Au;
+use app_units::{Au, MAX_AU};
 use block::BlockFlow;
 use context::LayoutContext;
-use display_list::{DisplayListBuildState, StackingContextCollectionState};
-use euclid::{Point2D, Vector2D};
-use floats::FloatKind;
-use flow::{Flow, FlowClass, OpaqueFlow, FragmentationContext, GetBaseFlow};
+use display_list::{DisplayListBuildState, MulticolFlowDisplayListBuilding};
+use display_list::StackingContextCollectionState;
+use euclid::Point2D;
+use floats::{FloatKind, Floats};
+use flow::{Flow, FlowClass, FlowFlags, FragmentationContext, FragmentationKind, GetBaseFlow};
+use flow::{ImmutableFlowUtils, OpaqueFlow};
+use flow_list::FlowList;
 use fragment::{Fragment, FragmentBorderBoxIterator, Overflow};
+use fragmentation::{mark_for_reflow, rejoin_continuation, take_children};
 use gfx_traits::print_tree::PrintTree;
+use model::{AdjoiningMargins, CollapsibleMargins};
 use std::cmp::{min, max};
 use std::fmt;
+use std::mem;
 use std::sync::Arc;
+use style::computed_values::column_fill::T as ColumnFill;
+use style::computed_values::column_span::T as ColumnSpan;
 use style::logical_geometry::LogicalSize;
 use style::properties::ComputedValues;
 use style::values::Either;
 use style::values::computed::{LengthOrPercentageOrAuto, LengthOrPercentageOrNone};
 use style::values::generics::column::ColumnCount;
 
+/// The maximum number of block sizes tried while looking for the balanced block size of a column
+/// set. Balancing lays out a column set at most this many times, plus two.
+const MAX_BALANCING_PASSES: u32 = 6;
+
 #[allow(unsafe_code)]
 unsafe impl ::flow::HasBaseFlow for MulticolFlow {}
 
@@ -35,6 +47,28 @@ pub struct MulticolFlow {
     /// Length between the inline-start edge of a column and that of the next.
     /// That is, the used column-width + used column-gap.
     pub column_pitch: Au,
+
+    /// The used column-gap.
+    pub column_gap: Au,
+
+    /// The used column-count, that is the number of columns in a row unless they overflow.
+    pub column_count: i32,
+
+    /// The rows of columns laid out during the last block-size assignment, for painting rules.
+    pub column_rows: Vec<ColumnRow>,
+}
+
+/// A row of columns resulting from the layout of one column set.
+///
+/// Spanning elements split the content of a multicol into several column sets.
+#[derive(Clone, Copy, Debug)]
+pub struct ColumnRow {
+    /// The block-start edge of the row, relative to the border box of the multicol.
+    pub block_start: Au,
+    /// The block size of the tallest column in the row.
+    pub block_size: Au,
+    /// The number of columns in the row.
+    pub column_count: usize,
 }
 
 #[allow(unsafe_code)]
@@ -50,7 +84,111 @@ impl MulticolFlow {
         MulticolFlow {
             block_flow: BlockFlow::from_fragment_and_float_kind(fragment, float_kind),
             column_pitch: Au(0),
+            column_gap: Au(0),
+            column_count: 1,
+            column_rows: vec![],
+        }
+    }
+
+    /// Turns the children of this multicol into a sequence of column sets and spanning elements.
+    ///
+    /// Before the first layout, the only child is the anonymous column flow holding all the
+    /// content. After a layout, the children are the columns and spanners from that layout, so
+    /// they are joined back into the first column before being split again.
+    fn split_into_column_sets(&mut self) {
+        let mut children = take_children(&mut self.block_flow.base);
+        let mut first_column = children.pop_front_arc().unwrap();
+        let mut content = {
+            let first_column = Arc::get_mut(&mut first_column).unwrap();
+            while let Some(mut child) = children.pop_front_arc() {
+                if is_column_spanner(&*child) {
+                    first_column.mut_base().children.push_back_arc(child)
+                } else {
+                    let column_children = take_children(Arc::get_mut(&mut child).unwrap().mut_base());
+                    rejoin_continuation(first_column, column_children)
+                }
+            }
+            take_children(first_column.mut_base())
+        };
+
+        // The first column set is the anonymous column flow itself, even when empty. The ones
+        // after spanners are copies of it.
+        let mut column_set = FlowList::new();
+        while content.front().map_or(false, |kid| !is_column_spanner(kid)) {
+            column_set.push_back_arc(content.pop_front_arc().unwrap())
+        }
+        Arc::get_mut(&mut first_column).unwrap().mut_base().children = column_set;
+        let mut children = FlowList::new();
+        children.push_back_arc(first_column);
+
+        let mut column_set = FlowList::new();
+        while let Some(kid) = content.pop_front_arc() {
+            if !is_column_spanner(&*kid) {
+                column_set.push_back_arc(kid);
+                continue
+            }
+            if !column_set.is_empty() {
+                push_column_set(&mut children, mem::replace(&mut column_set, FlowList::new()))
+            }
+            children.push_back_arc(kid)
+        }
+        if !column_set.is_empty() {
+            push_column_set(&mut children, column_set)
+        }
+
+        for kid in children.iter_mut() {
+            mark_for_reflow(kid)
+        }
+        self.block_flow.base.children = children;
+    }
+
+    /// Lays out a column set into columns, returning them in order.
+    ///
+    /// When `balance` is true, the columns are made as short as possible while fitting the
+    /// content in `column_count` columns. Otherwise they are filled up to
+    /// `available_block_size`, which is unbounded if `None`.
+    fn lay_out_column_set(&self,
+                          layout_context: &LayoutContext,
+                          column_set: Arc<Flow>,
+                          available_block_size: Option<Au>,
+                          balance: bool)
+                          -> Vec<Arc<Flow>> {
+        let available_block_size = available_block_size.unwrap_or(MAX_AU);
+        if !balance {
+            return fragment_into_columns(layout_context, column_set, available_block_size)
+        }
+
+        // Lay out everything in a single column first to find the total block size.
+        let mut columns = fragment_into_columns(layout_context, column_set, MAX_AU);
+        let total_block_size = columns[0].base().position.size.block;
+
+        // Search for the shortest columns that fit, starting with an even split of the content,
+        // which is enough when it breaks evenly. `high` always fits, unless the content overflows
+        // the available block size anyway. Each try lays out the whole column set again, so the
+        // search is cut short after a few tries, leaving the columns at most slightly too tall.
+        let mut low = total_block_size / self.column_count;
+        let mut high = min(total_block_size, available_block_size);
+        let mut columns_are_high = high == total_block_size;
+        let mut candidate = low;
+        for _ in 0..MAX_BALANCING_PASSES {
+            if high - low <= Au::from_px(1) || candidate >= high {
+                break
+            }
+            columns = fragment_into_columns(layout_context, unfragment(columns), candidate);
+            if columns.len() <= self.column_count as usize {
+                high = candidate;
+                columns_are_high = true;
+            } else {
+                low = candidate;
+                columns_are_high = false;
+            }
+            candidate = (low + high) / 2;
         }
+
+        if columns_are_high {
+            return columns
+        }
+        fragment_into_columns(layout_context, unfragment(columns), high)
     }
 }
 
@@ -62,6 +200,65 @@ impl MulticolColumnFlow {
     }
 }
 
+/// Returns true if `flow` is a spanning element, laid out across all the columns.
+fn is_column_spanner(flow: &Flow) -> bool {
+    flow.is_block_like() &&
+        !flow.base().flags.is_float() &&
+        !flow.base().flags.contains(FlowFlags::IS_ABSOLUTELY_POSITIONED) &&
+        flow.as_block().fragment.style.get_column().column_span == ColumnSpan::All
+}
+
+/// Appends a column set with the given children, modeled after the first one of `children`.
+fn push_column_set(children: &mut FlowList, column_set_children: FlowList) {
+    let column_set = children.front().unwrap().as_block().clone_with_children(column_set_children);
+    children.push_back_arc(Arc::new(column_set));
+}
+
+/// Lays out a column set, fragmenting it into as many columns of the given block size as needed.
+fn fragment_into_columns(layout_context: &LayoutContext,
+                         mut column_set: Arc<Flow>,
+                         column_block_size: Au)
+                         -> Vec<Arc<Flow>> {
+    let fragmentation_context = Some(FragmentationContext {
+        this_fragment_is_empty: true,
+        available_block_size: column_block_size,
+        kind: FragmentationKind::Column,
+    });
+
+    // A multicol establishes a block formatting context.
+    {
+        let base = Arc::get_mut(&mut column_set).unwrap().mut_base();
+        base.floats = Floats::new(base.writing_mode);
+    }
+
+    let mut columns = vec![];
+    loop {
+        let remaining = Arc::get_mut(&mut column_set).unwrap()
+            .fragment(layout_context, fragmentation_context);
+        columns.push(column_set);
+        column_set = match remaining {
+            Some(remaining) => remaining,
+            None => return columns,
+        };
+    }
+}
+
+/// Joins the columns returned by `fragment_into_columns` back into the first one, and marks it to
+/// be laid out again.
+fn unfragment(columns: Vec<Arc<Flow>>) -> Arc<Flow> {
+    let mut columns = columns.into_iter();
+    let mut column_set = columns.next().unwrap();
+    {
+        let first_column = Arc::get_mut(&mut column_set).unwrap();
+        for mut column in columns {
+            let children = take_children(Arc::get_mut(&mut column).unwrap().mut_base());
+            rejoin_continuation(first_column, children);
+        }
+        mark_for_reflow(first_column);
+    }
+    column_set
+}
+
 impl Flow for MulticolFlow {
     fn class(&self) -> FlowClass {
         FlowClass::Multicol
@@ -128,62 +325,128 @@ impl Flow for MulticolFlow {
             column_width =
                 max(Au(0), (content_inline_size + column_gap) / column_count - column_gap);
             self.column_pitch = column_width + column_gap;
+            self.column_gap = column_gap;
+            self.column_count = column_count;
         }
 
         self.block_flow.fragment.border_box.size.inline = content_inline_size + padding_and_borders;
 
+        self.split_into_column_sets();
+
+        // Column sets get the column width, spanners the whole content box.
         self.block_flow.propagate_assigned_inline_size_to_children(
             shared_context, inline_start_content_edge, inline_end_content_edge, column_width,
-            |_, _, _, _, _, _| {});
+            |kid, _, _, _, _, _| {
+                if is_column_spanner(kid) {
+                    kid.mut_base().block_container_inline_size = content_inline_size
+                }
+            });
     }
 
     fn assign_block_size(&mut self, ctx: &LayoutContext) {
         debug!("assign_block_size: assigning block_size for multicol");
 
-        let fragmentation_context = Some(FragmentationContext {
-            this_fragment_is_empty: true,
-            available_block_size: {
-                let style = &self.block_flow.fragment.style;
+        let (available_block_size, column_fill) = {
+            let style = &self.block_flow.fragment.style;
+            let available_block_size =
                 if let LengthOrPercentageOrAuto::Length(length) = style.content_block_size() {
-                    Au::from(length)
+                    Some(Au::from(length))
                 } else if let LengthOrPercentageOrNone::Length(length) = style.max_block_size() {
-                    Au::from(length)
+                    Some(Au::from(length))
                 } else {
-                    // FIXME: do column balancing instead
-                    // FIXME: (until column balancing) substract margins/borders/padding
-                    LogicalSize::from_physical(
-                        self.block_flow.base.writing_mode,
-                        ctx.shared_context().viewport_size(),
-                    ).block
-                }
-            }
-        });
+                    None
+                };
+            (available_block_size, style.get_column().column_fill)
+        };
 
-        // Before layout, everything is in a single "column"
-        assert_eq!(self.block_flow.base.children.len(), 1);
-        let mut column = self.block_flow.base.children.pop_front_arc().unwrap();
+        let mut children = take_children(&mut self.block_flow.base);
 
         // Pretend there is no children for this:
         self.block_flow.assign_block_size(ctx);
 
-        loop {
-            let remaining = Arc::get_mut(&mut column).unwrap().fragment(ctx, fragmentation_context);
-            self.block_flow.base.children.push_back_arc(column);
-            column = match remaining {
-                Some(remaining) => remaining,
-                None => break
+        // Columns are balanced, except for the last column set of a multicol with a constrained
+        // block size and `column-fill: auto`, which fills columns one after the other.
+        let column_set_count = children.iter().filter(|kid| !is_column_spanner(*kid)).count();
+        let fill_last_column_set =
+            available_block_size.is_some() && column_fill == ColumnFill::Auto;
+
+        let block_start_offset = self.block_flow.fragment.border_padding.block_start;
+        let mut cur_b = block_start_offset;
+        let mut column_sets_seen = 0;
+        self.column_rows.clear();
+        while let Some(mut kid) = children.pop_front_arc() {
+            if is_column_spanner(&*kid) {
+                {
+                    let kid = Arc::get_mut(&mut kid).unwrap();
+                    kid.mut_base().floats = Floats::new(kid.base().writing_mode);
+                    kid.fragment(ctx, Some(FragmentationContext {
+                        this_fragment_is_empty: true,
+                        available_block_size: MAX_AU,
+                        kind: FragmentationKind::Column,
+                    }));
+                    let margins = kid.base().collapsible_margins;
+                    cur_b = cur_b + margins.block_start_margin_for_noncollapsible_context();
+                    kid.mut_base().position.start.b = cur_b;
+                    cur_b = cur_b + kid.base().position.size.block +
+                        margins.block_end_margin_for_noncollapsible_context();
+                }
+                self.block_flow.base.children.push_back_arc(kid);
+                continue
+            }
+
+            column_sets_seen += 1;
+            let balance = !(fill_last_column_set && column_sets_seen == column_set_count);
+            let columns = self.lay_out_column_set(ctx, kid, available_block_size, balance);
+            let mut row = ColumnRow {
+                block_start: cur_b,
+                block_size: columns.iter()
+                                   .map(|column| column.base().position.size.block)
+                                   .max()
+                                   .unwrap_or(Au(0)),
+                column_count: columns.len(),
             };
+            if !balance {
+                let remaining_block_size =
+                    available_block_size.unwrap() - (cur_b - block_start_offset);
+                row.block_size = max(row.block_size, remaining_block_size);
+            }
+            for (i, mut column) in columns.into_iter().enumerate() {
+                {
+                    let base = Arc::get_mut(&mut column).unwrap().mut_base();
+                    base.position.start.b = cur_b;
+                    base.position.start.i = base.position.start.i + self.column_pitch * i as i32;
+                }
+                self.block_flow.base.children.push_back_arc(column);
+            }
+            cur_b = cur_b + row.block_size;
+            self.column_rows.push(row);
+        }
+
+        // An auto block size is that of the rows and spanners, within min-block-size and
+        // max-block-size.
+        let content_block_size = cur_b - block_start_offset;
+        if let LengthOrPercentageOrAuto::Auto = self.block_flow.fragment.style.content_block_size() {
+            let border_padding = self.block_flow.fragment.border_padding.block_start_end();
+            let mut block_size = max(content_block_size,
+                                     self.block_flow.fragment.border_box.size.block - border_padding);
+            if let Some(max_block_size) = available_block_size {
+                block_size = min(block_size, max_block_size)
+            }
+            self.block_flow.fragment.border_box.size.block = block_size + border_padding;
+            self.block_flow.base.position.size.block = block_size + border_padding;
+        }
+        if let CollapsibleMargins::CollapseThrough(_) = self.block_flow.base.collapsible_margins {
+            if content_block_size > Au(0) {
+                let margin = self.block_flow.fragment.margin;
+                self.block_flow.base.collapsible_margins = CollapsibleMargins::Collapse(
+                    AdjoiningMargins::from_margin(margin.block_start),
+                    AdjoiningMargins::from_margin(margin.block_end));
+            }
         }
     }
 
     fn compute_stacking_relative_position(&mut self, layout_context: &LayoutContext) {
-        self.block_flow.compute_stacking_relative_position(layout_context);
-        let pitch = LogicalSize::new(self.block_flow.base.writing_mode, self.column_pitch, Au(0));
-        let pitch = pitch.to_physical(self.block_flow.base.writing_mode);
-        for (i, child) in self.block_flow.base.children.iter_mut().enumerate() {
-            let point = &mut child.mut_base().stacking_relative_position;
-            *point = *point + Vector2D::new(pitch.width * i as i32, pitch.height * i as i32);
-        }
+        self.block_flow.compute_stacking_relative_position(layout_context)
     }
 
     fn update_late_computed_inline_position_if_necessary(&mut self, inline_position: Au) {
@@ -196,7 +459,7 @@ impl Flow for MulticolFlow {
 
     fn build_display_list(&mut self, state: &mut DisplayListBuildState) {
         debug!("build_display_list_multicol");
-        self.block_flow.build_display_list(state);
+        self.build_display_list_for_multicol(state);
     }
 
     fn collect_stacking_contexts(&mut self, state: &mut StackingContextCollectionState) {
@@ -0,0 +1,127 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Paged media: fragments the flow tree into pages and builds a display list for each of them.
+//!
+//! https://drafts.csswg.org/css-page-3/
+
+#![deny(unsafe_code)]
+
+use app_units::Au;
+use context::LayoutContext;
+use display_list::ToLayout;
+use display_list::items::DisplayList;
+use euclid::{Point2D, Rect, SideOffsets2D, Size2D};
+use floats::Floats;
+use flow::{Flow, FragmentationContext, FragmentationKind, GetBaseFlow};
+use fragmentation::{mark_for_reflow, rejoin_continuation, take_children};
+use sequential;
+use servo_geometry::MaxRect;
+use std::cmp::max;
+use std::sync::Arc;
+use style::logical_geometry::LogicalSize;
+use style::servo::restyle_damage::ServoRestyleDamage;
+use style::values::computed::LengthOrPercentageOrAuto;
+use traversal::{ComputeStackingRelativePositions, PreorderFlowTraversal};
+
+/// The display list of one page.
+pub struct Page {
+    /// The size of the page box, margins included.
+    pub size: Size2D<Au>,
+    /// The content of the page, in the coordinate system of the page box.
+    pub display_list: DisplayList,
+}
+
+/// Returns the page area of pages of the given size with the given margins, from `@page` rules.
+///
+/// https://drafts.csswg.org/css-page-3/#page-model
+pub fn page_area(page_size: Size2D<Au>, margins: SideOffsets2D<LengthOrPercentageOrAuto>) -> Rect<Au> {
+    // Percentages of page margins, block-axis ones included, are relative to the page width.
+    let used_margin = |margin: LengthOrPercentageOrAuto| match margin {
+        LengthOrPercentageOrAuto::Length(length) => Au::from(length),
+        LengthOrPercentageOrAuto::Percentage(percentage) => page_size.width.scale_by(percentage.0),
+        LengthOrPercentageOrAuto::Calc(calc) => calc.to_used_value(Some(page_size.width)).unwrap(),
+        LengthOrPercentageOrAuto::Auto => Au(0),
+    };
+    let (top, right, bottom, left) = (used_margin(margins.top),
+                                      used_margin(margins.right),
+                                      used_margin(margins.bottom),
+                                      used_margin(margins.left));
+    Rect::new(Point2D::new(left, top),
+              Size2D::new(max(page_size.width - left - right, Au(0)),
+                          max(page_size.height - top - bottom, Au(0))))
+}
+
+/// Splits the content of `layout_root` into pages of the given size, the content going in the
+/// page area. `layout_root` must have had its inline sizes assigned for that page area.
+///
+/// The flow tree is left whole, but needs to be laid out again afterwards.
+pub fn paginate(layout_root: &mut Flow,
+                layout_context: &LayoutContext,
+                page_size: Size2D<Au>,
+                page_area: Rect<Au>)
+                -> Vec<Page> {
+    let writing_mode = layout_root.base().writing_mode;
+    let fragmentation_context = Some(FragmentationContext {
+        kind: FragmentationKind::Page,
+        available_block_size: LogicalSize::from_physical(writing_mode, page_area.size).block,
+        this_fragment_is_empty: true,
+    });
+
+    mark_for_reflow(layout_root);
+    layout_root.mut_base().floats = Floats::new(writing_mode);
+    let mut remaining = layout_root.fragment(layout_context, fragmentation_context);
+    let mut pages = vec![build_page(layout_root, layout_context, page_size, page_area)];
+
+    let mut continuations = vec![];
+    while let Some(mut continuation) = remaining {
+        {
+            let page_root = Arc::get_mut(&mut continuation).unwrap();
+            page_root.mut_base().floats = Floats::new(writing_mode);
+            remaining = page_root.fragment(layout_context, fragmentation_context);
+            pages.push(build_page(page_root, layout_context, page_size, page_area));
+        }
+        continuations.push(continuation);
+    }
+
+    for mut continuation in continuations {
+        let children = take_children(Arc::get_mut(&mut continuation).unwrap().mut_base());
+        rejoin_continuation(layout_root, children);
+    }
+    mark_for_reflow(layout_root);
+    pages
+}
+
+fn build_page(page_root: &mut Flow,
+              layout_context: &LayoutContext,
+              page_size: Size2D<Au>,
+              page_area: Rect<Au>)
+              -> Page {
+    mark_for_repositioning(page_root);
+    page_root.mut_base().stacking_relative_position = page_area.origin.to_vector();
+    page_root.mut_base().clip = Rect::max_rect();
+
+    let traversal = ComputeStackingRelativePositions { layout_context: layout_context };
+    traversal.traverse(page_root);
+
+    let mut build_state = sequential::build_display_list_for_subtree(page_root, layout_context);
+    let bounds = Rect::new(Point2D::zero(), page_size).to_layout();
+    build_state.root_stacking_context.bounds = bounds;
+    build_state.root_stacking_context.overflow = bounds;
+
+    Page {
+        size: page_size,
+        display_list: build_state.to_display_list(),
+    }
+}
+
+/// Makes the stacking-relative positions and display list of `flow` be computed again, as every
+/// page is positioned in its own coordinate system.
+fn mark_for_repositioning(flow: &mut Flow) {
+    flow.mut_base().restyle_damage.insert(ServoRestyleDamage::REPOSITION |
+                                          ServoRestyleDamage::REPAINT);
+    for kid in flow.mut_base().child_iter_mut() {
+        mark_for_repositioning(kid)
+    }
+}
@@ -70,7 +70,10 @@ pub fn reflow(root: &mut Flow, layout_context: &LayoutContext, relayout_mode: Re
 pub fn build_display_list_for_subtree<'a>(flow_root: &mut Flow,
                                           layout_context: &'a LayoutContext)
                                           -> DisplayListBuildState<'a> {
-    let mut state = StackingContextCollectionState::new(layout_context.id);
+    let mut state = StackingContextCollectionState::new(
+        layout_context.id,
+        layout_context.shared_context().viewport_size(),
+    );
     flow_root.collect_stacking_contexts(&mut state);
 
     let state = DisplayListBuildState::new(layout_context, state);
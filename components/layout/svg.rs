@@ -0,0 +1,1133 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Painting of inline SVG content.
+//!
+//! When the fragment of an `svg` element is constructed, its subtree is read into an
+//! `SvgScene`: a list of shapes with resolved geometry and paint, and of text chunks. When
+//! the display list is built, runs of consecutive shapes are rasterized with Azure into
+//! images, and text chunks are shaped with the regular font machinery so they can be
+//! emitted as WebRender text items.
+
+use ServoArc;
+use app_units::Au;
+use azure::azure::AzFloat;
+use azure::azure_hl::{AntialiasMode, BackendType, CapStyle, Color, ColorPattern, CompositionOp};
+use azure::azure_hl::{DrawOptions, DrawTarget, ExtendMode, GradientStop, JoinStyle};
+use azure::azure_hl::{LinearGradientPattern, Path, Pattern, RadialGradientPattern, StrokeOptions};
+use azure::azure_hl::SurfaceFormat;
+use context::{LayoutContext, with_thread_local_font_context};
+use euclid::{Point2D, Rect, Size2D, Transform2D};
+use gfx::font::{ShapingFlags, ShapingOptions};
+use gfx::text::glyph::ByteIndex;
+use gfx::text::text_run::TextRun;
+use html5ever::LocalName;
+use ipc_channel::ipc::IpcSharedMemory;
+use net_traits::image::base::{Image, PixelFormat};
+use net_traits::image::svg::{PathCommand, PreserveAspectRatio, parse_fraction, parse_length};
+use net_traits::image::svg::{parse_path_data, parse_points, parse_preserve_aspect_ratio};
+use net_traits::image::svg::{parse_transform, parse_view_box, path_bounds, push_ellipse};
+use net_traits::image::svg::{push_rounded_rect, view_box_transform};
+use ordered_float::NotNan;
+use range::Range;
+use script_layout_interface::{LayoutNodeType, SVGSVGData};
+use script_layout_interface::wrapper_traits::{ThreadSafeLayoutElement, ThreadSafeLayoutNode};
+use selectors::Element as SelectorsElement;
+use std::collections::HashMap;
+use std::str;
+use std::sync::{Arc, Mutex};
+use style::computed_values::display::T as Display;
+use style::computed_values::stroke_linecap::T as StrokeLinecap;
+use style::computed_values::stroke_linejoin::T as StrokeLinejoin;
+use style::computed_values::text_anchor::T as TextAnchor;
+use style::computed_values::visibility::T as Visibility;
+use style::context::SharedStyleContext;
+use style::properties::ComputedValues;
+use style::properties::style_structs::Font as FontStyleStruct;
+use style::values::{Either, RGBA};
+use style::values::computed::{FontSize, SVGPaint, SVGWidth};
+use style::values::computed::svg::NonNegativeSvgLengthOrPercentageOrNumber;
+use style::values::generics::svg::{SVGLength, SVGOpacity, SVGPaintKind, SVGStrokeDashArray};
+use style::values::generics::svg::SvgLengthOrPercentageOrNumber;
+use unicode_bidi as bidi;
+use unicode_script::Script;
+use webrender_api::ImageKey;
+
+/// The largest width or height of the images that shapes are rasterized into, in device
+/// pixels. Larger viewports are painted at a lower resolution, and stretched.
+const MAX_RASTER_SIZE: i32 = 4096;
+
+/// The painted content of an `svg` element.
+pub struct SvgScene {
+    /// The `viewBox` of the `svg` element, in user units.
+    view_box: Option<Rect<f32>>,
+    /// How the `viewBox` is fitted into the viewport.
+    preserve_aspect_ratio: PreserveAspectRatio,
+    /// The shapes and text of the subtree, in painting order.
+    items: Vec<SvgItem>,
+    /// The `clipPath` elements of the subtree, by id.
+    clip_paths: HashMap<String, Arc<SvgClipPath>>,
+    /// The layers painted for the most recently requested viewport size.
+    painted: Mutex<Option<PaintedLayers>>,
+}
+
+struct PaintedLayers {
+    /// The size of the viewport, in device pixels.
+    size: Size2D<i32>,
+    layers: Arc<Vec<SvgLayer>>,
+    /// Where the images of the layers go once they are no longer displayed.
+    retired_image_keys: Arc<Mutex<Vec<ImageKey>>>,
+}
+
+impl PaintedLayers {
+    fn retire(&self) {
+        let mut retired_image_keys = self.retired_image_keys.lock().unwrap();
+        for layer in self.layers.iter() {
+            if let SvgLayer::Image(key) = *layer {
+                retired_image_keys.push(key);
+            }
+        }
+    }
+}
+
+impl Drop for SvgScene {
+    fn drop(&mut self) {
+        if let Some(ref painted) = *self.painted.lock().unwrap() {
+            painted.retire();
+        }
+    }
+}
+
+/// Something that is emitted into the display list for an `svg` element.
+pub enum SvgLayer {
+    /// Rasterized shapes covering the whole viewport.
+    Image(ImageKey),
+    /// A run of text.
+    Text(SvgTextLayer),
+}
+
+pub struct SvgTextLayer {
+    pub run: Arc<TextRun>,
+    pub range: Range<ByteIndex>,
+    /// The start of the baseline, relative to the origin of the viewport.
+    pub baseline_origin: Point2D<Au>,
+    pub color: RGBA,
+}
+
+/// The contents of a `clipPath` element, which elements refer to with `clip-path: url(#id)`.
+pub struct SvgClipPath {
+    /// Whether the contents are laid out in fractions of the reference box of the clipped element
+    /// (`clipPathUnits="objectBoundingBox"`), rather than in CSS pixels from its origin.
+    object_bounding_box: bool,
+    /// The outlines of the shapes, with the transform from their user space to that of the
+    /// `clipPath` element.
+    shapes: Vec<(Vec<PathCommand>, Transform2D<f32>)>,
+}
+
+enum SvgItem {
+    Shape(SvgShape),
+    Text(SvgText),
+}
+
+struct SvgShape {
+    path: Vec<PathCommand>,
+    /// The transform from the user space of the shape to the user space of the `svg` element.
+    transform: Transform2D<f32>,
+    /// The group opacity the shape is painted with.
+    opacity: f32,
+    fill: Option<SvgPaint>,
+    stroke: Option<(SvgPaint, SvgStroke)>,
+}
+
+struct SvgStroke {
+    width: f32,
+    line_cap: StrokeLinecap,
+    line_join: StrokeLinejoin,
+    miter_limit: f32,
+    dashes: Vec<AzFloat>,
+}
+
+enum SvgPaint {
+    Color(RGBA),
+    LinearGradient {
+        start: Point2D<f32>,
+        end: Point2D<f32>,
+        gradient: ResolvedGradient,
+    },
+    RadialGradient {
+        focus: Point2D<f32>,
+        center: Point2D<f32>,
+        radius: f32,
+        gradient: ResolvedGradient,
+    },
+}
+
+struct ResolvedGradient {
+    stops: Vec<(f32, RGBA)>,
+    spread: SpreadMethod,
+    /// The transform from the gradient coordinate system to the user space of the shape.
+    transform: Transform2D<f32>,
+}
+
+#[derive(Clone, Copy)]
+enum SpreadMethod {
+    Pad,
+    Reflect,
+    Repeat,
+}
+
+struct SvgText {
+    /// The transform from the user space of the text to the user space of the `svg` element.
+    transform: Transform2D<f32>,
+    chunks: Vec<SvgTextChunk>,
+}
+
+/// A piece of text that is positioned and anchored as a whole.
+struct SvgTextChunk {
+    position: Point2D<f32>,
+    anchor: TextAnchor,
+    spans: Vec<SvgTextSpan>,
+}
+
+struct SvgTextSpan {
+    text: String,
+    font: ServoArc<FontStyleStruct>,
+    color: RGBA,
+}
+
+impl SvgScene {
+    pub fn new<N: ThreadSafeLayoutNode>(node: &N,
+                                        data: &SVGSVGData,
+                                        context: &SharedStyleContext)
+                                        -> SvgScene {
+        let element = node.as_element().unwrap();
+        let view_box = attr(&element, "viewBox").and_then(parse_view_box);
+        let preserve_aspect_ratio = parse_preserve_aspect_ratio(attr(&element, "preserveAspectRatio"));
+        let viewport = match view_box {
+            Some(view_box) => view_box.size,
+            None => Size2D::new(data.width as f32, data.height as f32),
+        };
+
+        let mut builder = SceneBuilder {
+            context: context,
+            viewport: viewport,
+            gradients: HashMap::new(),
+            clip_paths: HashMap::new(),
+            items: vec![],
+        };
+        builder.collect_definitions(node);
+        if node.style(context).get_box().display != Display::None {
+            builder.build_children(node, &Transform2D::identity(), 1.0);
+        }
+
+        SvgScene {
+            view_box: view_box,
+            preserve_aspect_ratio: preserve_aspect_ratio,
+            items: builder.items,
+            clip_paths: builder.clip_paths,
+            painted: Mutex::new(None),
+        }
+    }
+
+    /// Returns the `clipPath` elements of the subtree, by id.
+    pub fn clip_paths(&self) -> &HashMap<String, Arc<SvgClipPath>> {
+        &self.clip_paths
+    }
+
+    /// Returns the layers that paint the scene into a viewport of the given size, in CSS
+    /// pixels. The layers of the last size are kept, so this only repaints when the size or
+    /// the device pixel ratio changes.
+    pub fn layers(&self, context: &LayoutContext, size: Size2D<f32>) -> Arc<Vec<SvgLayer>> {
+        let device_pixel_ratio = context.style_context.device_pixel_ratio().get();
+        let device_size = Size2D::new((size.width * device_pixel_ratio).ceil() as i32,
+                                      (size.height * device_pixel_ratio).ceil() as i32);
+
+        let mut painted = self.painted.lock().unwrap();
+        if let Some(ref painted) = *painted {
+            if painted.size == device_size {
+                return painted.layers.clone()
+            }
+            // The display list being built replaces the one that shows these layers.
+            painted.retire();
+        }
+
+        let layers = Arc::new(self.paint(context, size, device_size, device_pixel_ratio));
+        *painted = Some(PaintedLayers {
+            size: device_size,
+            layers: layers.clone(),
+            retired_image_keys: context.retired_image_keys.clone(),
+        });
+        layers
+    }
+
+    /// Returns the transform from the user space of the `svg` element to the viewport.
+    fn viewport_transform(&self, size: Size2D<f32>) -> Transform2D<f32> {
+        match self.view_box {
+            Some(view_box) => view_box_transform(view_box, self.preserve_aspect_ratio, size),
+            None => Transform2D::identity(),
+        }
+    }
+
+    fn paint(&self,
+             context: &LayoutContext,
+             size: Size2D<f32>,
+             device_size: Size2D<i32>,
+             device_pixel_ratio: f32)
+             -> Vec<SvgLayer> {
+        let mut layers = vec![];
+        if device_size.width <= 0 || device_size.height <= 0 {
+            return layers
+        }
+
+        // Rasterize huge viewports at a lower resolution, rather than allocating the pixels.
+        let scale = (MAX_RASTER_SIZE as f32 / device_size.width.max(device_size.height) as f32).min(1.);
+        let device_size = Size2D::new(((device_size.width as f32 * scale) as i32).max(1),
+                                      ((device_size.height as f32 * scale) as i32).max(1));
+        let device_pixel_ratio = device_pixel_ratio * scale;
+
+        let viewport_transform = self.viewport_transform(size);
+        let mut draw_target = None;
+        for item in &self.items {
+            match *item {
+                SvgItem::Shape(ref shape) => {
+                    let draw_target = draw_target.get_or_insert_with(|| {
+                        DrawTarget::new(BackendType::Skia, device_size, SurfaceFormat::B8G8R8A8)
+                    });
+                    let transform = shape.transform
+                                         .post_mul(&viewport_transform)
+                                         .post_scale(device_pixel_ratio, device_pixel_ratio);
+                    draw_target.set_transform(&transform);
+                    shape.draw(draw_target);
+                }
+                SvgItem::Text(ref text) => {
+                    // Text is painted over the shapes before it, and under those after it.
+                    if let Some(draw_target) = draw_target.take() {
+                        layers.extend(upload(context, &draw_target, device_size).map(SvgLayer::Image));
+                    }
+                    let transform = text.transform.post_mul(&viewport_transform);
+                    text.layout(context, &transform, &mut layers);
+                }
+            }
+        }
+        if let Some(draw_target) = draw_target {
+            layers.extend(upload(context, &draw_target, device_size).map(SvgLayer::Image));
+        }
+        layers
+    }
+}
+
+/// Makes a WebRender image out of the contents of a draw target.
+fn upload(context: &LayoutContext, draw_target: &DrawTarget, size: Size2D<i32>) -> Option<ImageKey> {
+    let (width, height) = (size.width as usize, size.height as usize);
+    let mut bytes = Vec::with_capacity(width * height * 4);
+    let surface = draw_target.snapshot().get_data_surface();
+    let stride = surface.stride() as usize;
+    surface.with_data(|data| {
+        for row in data.chunks(stride).take(height) {
+            bytes.extend_from_slice(&row[..width * 4]);
+        }
+    });
+
+    // Azure hands out premultiplied pixels, but the image cache expects them unpremultiplied.
+    for pixel in bytes.chunks_mut(4) {
+        let alpha = pixel[3] as u32;
+        if alpha != 0 && alpha != 255 {
+            for component in &mut pixel[..3] {
+                *component = ((*component as u32 * 255 + alpha / 2) / alpha).min(255) as u8;
+            }
+        }
+    }
+
+    let mut image = Image {
+        width: size.width as u32,
+        height: size.height as u32,
+        format: PixelFormat::BGRA8,
+        bytes: IpcSharedMemory::from_bytes(&bytes),
+        id: None,
+        animation: None,
+    };
+    context.image_cache.set_webrender_image_key(&mut image);
+    image.id
+}
+
+/// Builds an Azure path out of a list of path commands.
+fn azure_path(draw_target: &DrawTarget, commands: &[PathCommand]) -> Path {
+    let path_builder = draw_target.create_path_builder();
+    for command in commands {
+        match *command {
+            PathCommand::MoveTo(point) => path_builder.move_to(point),
+            PathCommand::LineTo(point) => path_builder.line_to(point),
+            PathCommand::QuadraticCurveTo(ref control, ref point) => {
+                path_builder.quadratic_curve_to(control, point)
+            }
+            PathCommand::BezierCurveTo(ref control1, ref control2, ref point) => {
+                path_builder.bezier_curve_to(control1, control2, point)
+            }
+            PathCommand::Close => path_builder.close(),
+        }
+    }
+    path_builder.finish()
+}
+
+impl SvgClipPath {
+    /// Returns the bounds of the clipping region in CSS pixels, or `None` if it is empty.
+    /// `reference_box` is the reference box of the clipped element.
+    pub fn bounds(&self, reference_box: &Rect<f32>) -> Option<Rect<f32>> {
+        let transform = self.user_space_transform(reference_box);
+        self.shapes.iter().fold(None, |bounds, &(ref commands, ref shape_transform)| {
+            let shape_bounds = shape_transform.post_mul(&transform).transform_rect(&path_bounds(commands));
+            Some(bounds.map_or(shape_bounds, |bounds: Rect<f32>| bounds.union(&shape_bounds)))
+        })
+    }
+
+    /// Fills the clipping region in opaque white. `reference_box` is the reference box of the
+    /// clipped element, and `transform` maps CSS pixels to the pixels of the draw target.
+    pub fn fill(&self, draw_target: &DrawTarget, reference_box: &Rect<f32>, transform: &Transform2D<f32>) {
+        let transform = self.user_space_transform(reference_box).post_mul(transform);
+        let white = Pattern::Color(ColorPattern::new(Color::rgba(1.0, 1.0, 1.0, 1.0)));
+        let draw_options = DrawOptions::new(1.0, CompositionOp::Over, AntialiasMode::Default);
+        for &(ref commands, ref shape_transform) in &self.shapes {
+            draw_target.set_transform(&shape_transform.post_mul(&transform));
+            let path = azure_path(draw_target, commands);
+            draw_target.fill(&path, white.to_pattern_ref(), &draw_options);
+        }
+    }
+
+    /// Returns the transform from the user space of the `clipPath` element to CSS pixels.
+    fn user_space_transform(&self, reference_box: &Rect<f32>) -> Transform2D<f32> {
+        let transform = Transform2D::create_translation(reference_box.origin.x, reference_box.origin.y);
+        if self.object_bounding_box {
+            transform.pre_scale(reference_box.size.width, reference_box.size.height)
+        } else {
+            transform
+        }
+    }
+}
+
+impl SvgShape {
+    fn draw(&self, draw_target: &DrawTarget) {
+        let path = azure_path(draw_target, &self.path);
+        let draw_options = DrawOptions::new(self.opacity, CompositionOp::Over, AntialiasMode::Default);
+
+        if let Some(ref fill) = self.fill {
+            draw_target.fill(&path, fill.to_azure_pattern(draw_target).to_pattern_ref(), &draw_options);
+        }
+
+        if let Some((ref paint, ref stroke)) = self.stroke {
+            let line_cap = match stroke.line_cap {
+                StrokeLinecap::Butt => CapStyle::Butt,
+                StrokeLinecap::Round => CapStyle::Round,
+                StrokeLinecap::Square => CapStyle::Square,
+            };
+            let line_join = match stroke.line_join {
+                StrokeLinejoin::Miter => JoinStyle::MiterOrBevel,
+                StrokeLinejoin::Round => JoinStyle::Round,
+                StrokeLinejoin::Bevel => JoinStyle::Bevel,
+            };
+            let stroke_options =
+                StrokeOptions::new(stroke.width, line_join, line_cap, stroke.miter_limit, &stroke.dashes);
+            draw_target.stroke(&path,
+                               paint.to_azure_pattern(draw_target).to_pattern_ref(),
+                               &stroke_options,
+                               &draw_options);
+        }
+    }
+}
+
+impl SvgPaint {
+    fn to_azure_pattern(&self, draw_target: &DrawTarget) -> Pattern {
+        match *self {
+            SvgPaint::Color(color) => Pattern::Color(ColorPattern::new(to_azure_color(color))),
+            SvgPaint::LinearGradient { ref start, ref end, ref gradient } => {
+                Pattern::LinearGradient(LinearGradientPattern::new(
+                    start,
+                    end,
+                    draw_target.create_gradient_stops(&gradient.azure_stops(), gradient.extend_mode()),
+                    &gradient.transform))
+            }
+            SvgPaint::RadialGradient { ref focus, ref center, radius, ref gradient } => {
+                Pattern::RadialGradient(RadialGradientPattern::new(
+                    focus,
+                    center,
+                    0.0,
+                    radius,
+                    draw_target.create_gradient_stops(&gradient.azure_stops(), gradient.extend_mode()),
+                    &gradient.transform))
+            }
+        }
+    }
+}
+
+impl ResolvedGradient {
+    fn azure_stops(&self) -> Vec<GradientStop> {
+        self.stops.iter().map(|&(offset, color)| {
+            GradientStop {
+                offset: offset as AzFloat,
+                color: to_azure_color(color),
+            }
+        }).collect()
+    }
+
+    fn extend_mode(&self) -> ExtendMode {
+        match self.spread {
+            SpreadMethod::Pad => ExtendMode::Clamp,
+            SpreadMethod::Reflect => ExtendMode::Reflect,
+            SpreadMethod::Repeat => ExtendMode::Repeat,
+        }
+    }
+}
+
+fn to_azure_color(color: RGBA) -> Color {
+    Color::rgba(color.red_f32() as AzFloat,
+                color.green_f32() as AzFloat,
+                color.blue_f32() as AzFloat,
+                color.alpha_f32() as AzFloat)
+}
+
+impl SvgText {
+    /// Shapes the text and appends it to `layers`. Glyphs can't be transformed, so only the
+    /// position and the scale of the text follow `transform`, not rotations and skews.
+    fn layout(&self, context: &LayoutContext, transform: &Transform2D<f32>, layers: &mut Vec<SvgLayer>) {
+        let scale = (transform.m11 * transform.m22 - transform.m12 * transform.m21).abs().sqrt();
+        if scale == 0.0 {
+            return
+        }
+
+        let options = ShapingOptions {
+            letter_spacing: None,
+            word_spacing: (Au(0), NotNan::new(0.0).unwrap()),
+            script: Script::Common,
+            flags: ShapingFlags::empty(),
+        };
+
+        with_thread_local_font_context(context, |font_context| {
+            for chunk in &self.chunks {
+                let origin = transform.transform_point(&chunk.position);
+                let mut runs = vec![];
+                let mut advance = Au(0);
+                for span in &chunk.spans {
+                    let mut font_style = (*span.font).clone();
+                    let font_size = font_style.font_size.size().to_f32_px() * scale;
+                    font_style.font_size = FontSize {
+                        size: Au::from_f32_px(font_size).into(),
+                        keyword_info: None,
+                    };
+                    font_style.compute_font_hash();
+
+                    let font_group = font_context.font_group(ServoArc::new(font_style));
+                    let font = match font_group.borrow_mut().first(font_context) {
+                        Some(font) => font,
+                        None => continue,
+                    };
+                    let (run, _) = TextRun::new(&mut *font.borrow_mut(),
+                                                span.text.clone(),
+                                                &options,
+                                                bidi::Level::ltr(),
+                                                &mut None);
+                    let range = Range::new(ByteIndex(0), ByteIndex(span.text.len() as isize));
+                    let width = run.advance_for_range(&range);
+                    runs.push((Arc::new(run), range, advance, span.color));
+                    advance = advance + width;
+                }
+
+                let anchor_offset = match chunk.anchor {
+                    TextAnchor::Start => Au(0),
+                    TextAnchor::Middle => advance.scale_by(0.5),
+                    TextAnchor::End => advance,
+                };
+                let origin = Point2D::new(Au::from_f32_px(origin.x) - anchor_offset,
+                                          Au::from_f32_px(origin.y));
+                for (run, range, offset, color) in runs {
+                    layers.push(SvgLayer::Text(SvgTextLayer {
+                        run: run,
+                        range: range,
+                        baseline_origin: Point2D::new(origin.x + offset, origin.y),
+                        color: color,
+                    }));
+                }
+            }
+        })
+    }
+}
+
+/// Reads the subtree of an `svg` element into a list of items.
+struct SceneBuilder<'a, N: ThreadSafeLayoutNode> {
+    context: &'a SharedStyleContext<'a>,
+    /// The size of the nearest viewport in user units, which percentages resolve against.
+    viewport: Size2D<f32>,
+    /// The gradient elements of the subtree, by id.
+    gradients: HashMap<String, N::ConcreteThreadSafeLayoutElement>,
+    clip_paths: HashMap<String, Arc<SvgClipPath>>,
+    items: Vec<SvgItem>,
+}
+
+impl<'a, N: ThreadSafeLayoutNode> SceneBuilder<'a, N> {
+    /// Collects the gradient and `clipPath` elements of the subtree, which are not rendered
+    /// themselves but referred to by id.
+    fn collect_definitions(&mut self, node: &N) {
+        for kid in node.children() {
+            let element = match kid.as_element() {
+                Some(element) => element,
+                None => continue,
+            };
+            if *element.namespace() != ns!(svg) {
+                continue
+            }
+            match &**element.local_name() {
+                "linearGradient" | "radialGradient" => {
+                    if let Some(id) = element.get_attr(&ns!(), &local_name!("id")) {
+                        self.gradients.entry(id.to_owned()).or_insert(element);
+                    }
+                }
+                "clipPath" => {
+                    if let Some(id) = element.get_attr(&ns!(), &local_name!("id")) {
+                        if !self.clip_paths.contains_key(id) {
+                            let clip_path = Arc::new(self.build_clip_path(&element));
+                            self.clip_paths.insert(id.to_owned(), clip_path);
+                        }
+                    }
+                }
+                _ => self.collect_definitions(&kid),
+            }
+        }
+    }
+
+    fn build_children(&mut self, node: &N, transform: &Transform2D<f32>, opacity: f32) {
+        for kid in node.children() {
+            if let Some(element) = kid.as_element() {
+                self.build_element(element, transform, opacity);
+            }
+        }
+    }
+
+    fn build_element(&mut self,
+                     element: N::ConcreteThreadSafeLayoutElement,
+                     parent_transform: &Transform2D<f32>,
+                     parent_opacity: f32) {
+        if *element.namespace() != ns!(svg) {
+            return
+        }
+
+        let node = element.as_node();
+        let style = node.style(self.context);
+        if style.get_box().display == Display::None {
+            return
+        }
+
+        let mut transform = *parent_transform;
+        if let Some(value) = attr(&element, "transform") {
+            transform = transform.pre_mul(&parse_transform(value));
+        }
+        let opacity = parent_opacity * style.get_effects().opacity;
+
+        match &**element.local_name() {
+            "g" | "a" | "switch" => self.build_children(&node, &transform, opacity),
+            "svg" => self.build_nested_svg(&element, &style, &transform, opacity),
+            "text" => self.build_text(&element, &style, transform),
+            "rect" | "circle" | "ellipse" | "line" | "polyline" | "polygon" | "path" => {
+                if style.get_inherited_box().visibility != Visibility::Visible {
+                    return
+                }
+                if let Some(path) = self.shape_path(&element, &style) {
+                    self.build_shape(path, &style, transform, opacity);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Reads the shapes of a `clipPath` element. The union of their interiors is the clipping
+    /// region, regardless of how they are painted.
+    ///
+    /// TODO: Text, `use` elements and `clip-rule` are not supported yet.
+    fn build_clip_path(&self, element: &N::ConcreteThreadSafeLayoutElement) -> SvgClipPath {
+        let object_bounding_box = attr(element, "clipPathUnits")
+            .map_or(false, |units| units.trim() == "objectBoundingBox");
+        let transform = attr(element, "transform").map_or(Transform2D::identity(), parse_transform);
+
+        let mut shapes = vec![];
+        for kid in element.as_node().children() {
+            let kid = match kid.as_element() {
+                Some(kid) if *kid.namespace() == ns!(svg) => kid,
+                _ => continue,
+            };
+            let style = kid.as_node().style(self.context);
+            if style.get_box().display == Display::None ||
+               style.get_inherited_box().visibility != Visibility::Visible {
+                continue
+            }
+            let mut shape_transform = transform;
+            if let Some(value) = attr(&kid, "transform") {
+                shape_transform = shape_transform.pre_mul(&parse_transform(value));
+            }
+            if let Some(path) = self.shape_path(&kid, &style) {
+                shapes.push((path, shape_transform));
+            }
+        }
+
+        SvgClipPath {
+            object_bounding_box: object_bounding_box,
+            shapes: shapes,
+        }
+    }
+
+    /// Nested `svg` elements establish a new viewport for their children.
+    fn build_nested_svg(&mut self,
+                        element: &N::ConcreteThreadSafeLayoutElement,
+                        style: &ComputedValues,
+                        transform: &Transform2D<f32>,
+                        opacity: f32) {
+        let font_size = font_size(style);
+        let x = self.length(element, "x", self.viewport.width, font_size).unwrap_or(0.0);
+        let y = self.length(element, "y", self.viewport.height, font_size).unwrap_or(0.0);
+        let width = self.length(element, "width", self.viewport.width, font_size)
+                        .unwrap_or(self.viewport.width);
+        let height = self.length(element, "height", self.viewport.height, font_size)
+                         .unwrap_or(self.viewport.height);
+        if width <= 0.0 || height <= 0.0 {
+            return
+        }
+
+        let mut transform = transform.pre_translate(Point2D::new(x, y).to_vector());
+        let viewport = match attr(element, "viewBox").and_then(parse_view_box) {
+            Some(view_box) => {
+                let aspect_ratio = parse_preserve_aspect_ratio(attr(element, "preserveAspectRatio"));
+                transform =
+                    transform.pre_mul(&view_box_transform(view_box, aspect_ratio, Size2D::new(width, height)));
+                view_box.size
+            }
+            None => Size2D::new(width, height),
+        };
+
+        let parent_viewport = self.viewport;
+        self.viewport = viewport;
+        self.build_children(&element.as_node(), &transform, opacity);
+        self.viewport = parent_viewport;
+    }
+
+    fn build_shape(&mut self,
+                   path: Vec<PathCommand>,
+                   style: &ComputedValues,
+                   transform: Transform2D<f32>,
+                   opacity: f32) {
+        let svg = style.get_inherited_svg();
+        let bounds = path_bounds(&path);
+        let fill = self.paint(&svg.fill, style, opacity_value(&svg.fill_opacity), &bounds);
+
+        let stroke_width = self.svg_width(&svg.stroke_width);
+        let stroke = if stroke_width > 0.0 {
+            self.paint(&svg.stroke, style, opacity_value(&svg.stroke_opacity), &bounds).map(|paint| {
+                let mut dashes = match svg.stroke_dasharray {
+                    SVGStrokeDashArray::Values(ref values) => {
+                        values.iter().map(|value| self.svg_length(value)).collect()
+                    }
+                    SVGStrokeDashArray::ContextValue => vec![],
+                };
+                // A list with negative values disables dashing, and an odd one is repeated.
+                if dashes.iter().any(|&dash| dash < 0.0) || dashes.iter().all(|&dash| dash == 0.0) {
+                    dashes.clear();
+                } else if dashes.len() % 2 == 1 {
+                    let copy = dashes.clone();
+                    dashes.extend(copy);
+                }
+                (paint, SvgStroke {
+                    width: stroke_width,
+                    line_cap: svg.stroke_linecap,
+                    line_join: svg.stroke_linejoin,
+                    miter_limit: svg.stroke_miterlimit.0,
+                    dashes: dashes,
+                })
+            })
+        } else {
+            None
+        };
+
+        if fill.is_none() && stroke.is_none() {
+            return
+        }
+        self.items.push(SvgItem::Shape(SvgShape {
+            path: path,
+            transform: transform,
+            opacity: opacity,
+            fill: fill,
+            stroke: stroke,
+        }));
+    }
+
+    fn build_text(&mut self,
+                  element: &N::ConcreteThreadSafeLayoutElement,
+                  style: &ComputedValues,
+                  transform: Transform2D<f32>) {
+        let font_size = font_size(style);
+        let position = Point2D::new(
+            self.length(element, "x", self.viewport.width, font_size).unwrap_or(0.0),
+            self.length(element, "y", self.viewport.height, font_size).unwrap_or(0.0));
+        let mut chunks = vec![SvgTextChunk {
+            position: position,
+            anchor: style.get_inherited_svg().text_anchor,
+            spans: vec![],
+        }];
+        self.collect_text(&element.as_node(), style, &mut chunks);
+
+        // Collapse white space the way `xml:space="default"` does: drop newlines, turn tabs
+        // into spaces, and collapse runs of spaces, trimming the text as a whole.
+        let mut previous_was_space = true;
+        for chunk in &mut chunks {
+            for span in &mut chunk.spans {
+                let mut text = String::with_capacity(span.text.len());
+                for character in span.text.chars() {
+                    match character {
+                        '\n' | '\r' => {}
+                        ' ' | '\t' => {
+                            if !previous_was_space {
+                                text.push(' ');
+                            }
+                            previous_was_space = true;
+                        }
+                        _ => {
+                            text.push(character);
+                            previous_was_space = false;
+                        }
+                    }
+                }
+                span.text = text;
+            }
+        }
+        if let Some(span) = chunks.iter_mut().rev().flat_map(|chunk| chunk.spans.iter_mut().rev())
+                                  .find(|span| !span.text.is_empty()) {
+            if span.text.ends_with(' ') {
+                span.text.pop();
+            }
+        }
+        for chunk in &mut chunks {
+            chunk.spans.retain(|span| !span.text.is_empty());
+        }
+        chunks.retain(|chunk| !chunk.spans.is_empty());
+
+        if !chunks.is_empty() {
+            self.items.push(SvgItem::Text(SvgText {
+                transform: transform,
+                chunks: chunks,
+            }));
+        }
+    }
+
+    /// Appends the text content of `node` to the chunks. `tspan` elements with a position
+    /// start a new chunk.
+    fn collect_text(&self, node: &N, style: &ComputedValues, chunks: &mut Vec<SvgTextChunk>) {
+        for kid in node.children() {
+            if kid.type_id() == Some(LayoutNodeType::Text) {
+                if style.get_inherited_box().visibility != Visibility::Visible {
+                    continue
+                }
+                // Only plain colors can be used for WebRender text.
+                let color = match style.get_inherited_svg().fill.kind {
+                    SVGPaintKind::Color(color) => {
+                        let mut color = style.resolve_color(color);
+                        let opacity = opacity_value(&style.get_inherited_svg().fill_opacity) *
+                                      style.get_effects().opacity;
+                        color.alpha = (color.alpha as f32 * opacity).round() as u8;
+                        color
+                    }
+                    _ => continue,
+                };
+                chunks.last_mut().unwrap().spans.push(SvgTextSpan {
+                    text: kid.node_text_content(),
+                    font: style.clone_font(),
+                    color: color,
+                });
+                continue
+            }
+
+            let element = match kid.as_element() {
+                Some(element) => element,
+                None => continue,
+            };
+            if *element.namespace() != ns!(svg) || &**element.local_name() != "tspan" {
+                continue
+            }
+            let kid_style = kid.style(self.context);
+            if kid_style.get_box().display == Display::None {
+                continue
+            }
+
+            let font_size = font_size(&kid_style);
+            let x = self.length(&element, "x", self.viewport.width, font_size);
+            let y = self.length(&element, "y", self.viewport.height, font_size);
+            if x.is_some() || y.is_some() {
+                // FIXME: The position of a `tspan` without both coordinates should continue
+                // from the end of the previous text, which is only known once it is shaped.
+                let previous = chunks.last().unwrap().position;
+                chunks.push(SvgTextChunk {
+                    position: Point2D::new(x.unwrap_or(previous.x), y.unwrap_or(previous.y)),
+                    anchor: kid_style.get_inherited_svg().text_anchor,
+                    spans: vec![],
+                });
+            }
+            self.collect_text(&kid, &kid_style, chunks);
+        }
+    }
+
+    /// Returns the outline of a basic shape, or `None` if the shape is not rendered.
+    fn shape_path(&self,
+                  element: &N::ConcreteThreadSafeLayoutElement,
+                  style: &ComputedValues)
+                  -> Option<Vec<PathCommand>> {
+        let font_size = font_size(style);
+        let (width, height) = (self.viewport.width, self.viewport.height);
+        let diagonal = self.diagonal();
+        let length = |name, reference| self.length(element, name, reference, font_size);
+
+        let mut path = vec![];
+        match &**element.local_name() {
+            "rect" => {
+                let rect = Rect::new(Point2D::new(length("x", width).unwrap_or(0.0),
+                                                  length("y", height).unwrap_or(0.0)),
+                                     Size2D::new(length("width", width)?, length("height", height)?));
+                if rect.size.width <= 0.0 || rect.size.height <= 0.0 {
+                    return None
+                }
+                let (rx, ry) = match (length("rx", width), length("ry", height)) {
+                    (Some(rx), Some(ry)) => (rx, ry),
+                    (Some(rx), None) => (rx, rx),
+                    (None, Some(ry)) => (ry, ry),
+                    (None, None) => (0.0, 0.0),
+                };
+                let rx = rx.max(0.0).min(rect.size.width / 2.0);
+                let ry = ry.max(0.0).min(rect.size.height / 2.0);
+                push_rounded_rect(&mut path, &rect, rx, ry);
+            }
+            "circle" => {
+                let r = length("r", diagonal)?;
+                if r <= 0.0 {
+                    return None
+                }
+                let center = Point2D::new(length("cx", width).unwrap_or(0.0),
+                                          length("cy", height).unwrap_or(0.0));
+                push_ellipse(&mut path, center, r, r);
+            }
+            "ellipse" => {
+                let (rx, ry) = (length("rx", width)?, length("ry", height)?);
+                if rx <= 0.0 || ry <= 0.0 {
+                    return None
+                }
+                let center = Point2D::new(length("cx", width).unwrap_or(0.0),
+                                          length("cy", height).unwrap_or(0.0));
+                push_ellipse(&mut path, center, rx, ry);
+            }
+            "line" => {
+                path.push(PathCommand::MoveTo(Point2D::new(length("x1", width).unwrap_or(0.0),
+                                                           length("y1", height).unwrap_or(0.0))));
+                path.push(PathCommand::LineTo(Point2D::new(length("x2", width).unwrap_or(0.0),
+                                                           length("y2", height).unwrap_or(0.0))));
+            }
+            name @ "polyline" | name @ "polygon" => {
+                let points = parse_points(attr(element, "points")?);
+                for (index, point) in points.into_iter().enumerate() {
+                    path.push(if index == 0 { PathCommand::MoveTo(point) } else { PathCommand::LineTo(point) });
+                }
+                if name == "polygon" && !path.is_empty() {
+                    path.push(PathCommand::Close);
+                }
+            }
+            "path" => path = parse_path_data(attr(element, "d")?),
+            _ => return None,
+        }
+
+        if path.is_empty() {
+            None
+        } else {
+            Some(path)
+        }
+    }
+
+    fn paint(&self,
+             paint: &SVGPaint,
+             style: &ComputedValues,
+             opacity: f32,
+             bounds: &Rect<f32>)
+             -> Option<SvgPaint> {
+        let with_opacity = |color| {
+            let mut color = style.resolve_color(color);
+            color.alpha = (color.alpha as f32 * opacity).round() as u8;
+            color
+        };
+        let fallback = || match paint.fallback {
+            Some(Either::First(color)) => Some(SvgPaint::Color(with_opacity(color))),
+            _ => None,
+        };
+
+        match paint.kind {
+            SVGPaintKind::Color(color) => Some(SvgPaint::Color(with_opacity(color))),
+            SVGPaintKind::PaintServer(ref url) => {
+                let gradient = url.url().and_then(|url| url.fragment())
+                                  .and_then(|id| self.gradients.get(id));
+                match gradient {
+                    Some(gradient) => self.gradient_paint(gradient, opacity, bounds),
+                    None => fallback(),
+                }
+            }
+            SVGPaintKind::None |
+            SVGPaintKind::ContextFill |
+            SVGPaintKind::ContextStroke => None,
+        }
+    }
+
+    /// Resolves a gradient element for a shape with the given bounding box.
+    /// <https://svgwg.org/svg2-draft/pservers.html#Gradients>
+    fn gradient_paint(&self,
+                      element: &N::ConcreteThreadSafeLayoutElement,
+                      opacity: f32,
+                      bounds: &Rect<f32>)
+                      -> Option<SvgPaint> {
+        // Attributes and stops that are not specified are taken from the referenced gradient.
+        let mut chain = vec![*element];
+        while chain.len() < 8 {
+            let referenced = {
+                let last = chain[chain.len() - 1];
+                let href = last.get_attr(&ns!(xlink), &local_name!("href")).or_else(|| attr(&last, "href"));
+                href.and_then(|href| self.gradients.get(href.trim().trim_left_matches('#'))).cloned()
+            };
+            match referenced {
+                Some(referenced) => chain.push(referenced),
+                None => break,
+            }
+        }
+        let inherited_attr = |name| chain.iter().filter_map(|element| attr(element, name)).next();
+
+        let stops = chain.iter().map(|element| self.gradient_stops(element, opacity))
+                         .find(|stops| !stops.is_empty())?;
+        if stops.len() == 1 {
+            return Some(SvgPaint::Color(stops[0].1))
+        }
+
+        let spread = match inherited_attr("spreadMethod").map(str::trim) {
+            Some("reflect") => SpreadMethod::Reflect,
+            Some("repeat") => SpreadMethod::Repeat,
+            _ => SpreadMethod::Pad,
+        };
+        let object_bounding_box = inherited_attr("gradientUnits").map(str::trim) != Some("userSpaceOnUse");
+        let mut transform = inherited_attr("gradientTransform").map(parse_transform)
+                                                               .unwrap_or(Transform2D::identity());
+
+        // Coordinates are fractions of the bounding box, or user space lengths.
+        let coordinate = |name, default: &str, reference| {
+            let value = inherited_attr(name).unwrap_or(default);
+            if object_bounding_box {
+                parse_fraction(value)
+            } else {
+                parse_length(value, reference, 0.0)
+            }
+        };
+        if object_bounding_box {
+            if bounds.size.width <= 0.0 || bounds.size.height <= 0.0 {
+                return None
+            }
+            let bounds_transform = Transform2D::row_major(bounds.size.width, 0.0,
+                                                          0.0, bounds.size.height,
+                                                          bounds.origin.x, bounds.origin.y);
+            transform = bounds_transform.pre_mul(&transform);
+        }
+        let gradient = ResolvedGradient {
+            stops: stops,
+            spread: spread,
+            transform: transform,
+        };
+
+        let (width, height, diagonal) = (self.viewport.width, self.viewport.height, self.diagonal());
+        if &**element.local_name() == "linearGradient" {
+            Some(SvgPaint::LinearGradient {
+                start: Point2D::new(coordinate("x1", "0%", width)?, coordinate("y1", "0%", height)?),
+                end: Point2D::new(coordinate("x2", "100%", width)?, coordinate("y2", "0%", height)?),
+                gradient: gradient,
+            })
+        } else {
+            let center = Point2D::new(coordinate("cx", "50%", width)?, coordinate("cy", "50%", height)?);
+            let radius = coordinate("r", "50%", diagonal)?;
+            if radius <= 0.0 {
+                return None
+            }
+            // The focus defaults to the center.
+            let focus = Point2D::new(coordinate("fx", "", width).unwrap_or(center.x),
+                                     coordinate("fy", "", height).unwrap_or(center.y));
+            Some(SvgPaint::RadialGradient {
+                focus: focus,
+                center: center,
+                radius: radius,
+                gradient: gradient,
+            })
+        }
+    }
+
+    fn gradient_stops(&self,
+                      element: &N::ConcreteThreadSafeLayoutElement,
+                      opacity: f32)
+                      -> Vec<(f32, RGBA)> {
+        let mut stops: Vec<(f32, RGBA)> = vec![];
+        for kid in element.as_node().children() {
+            let stop = match kid.as_element() {
+                Some(stop) => stop,
+                None => continue,
+            };
+            if *stop.namespace() != ns!(svg) || &**stop.local_name() != "stop" {
+                continue
+            }
+            let style = kid.style(self.context);
+            let offset = attr(&stop, "offset").and_then(parse_fraction).unwrap_or(0.0).max(0.0).min(1.0);
+            // Offsets never decrease.
+            let offset = stops.last().map_or(offset, |&(previous, _)| offset.max(previous));
+            let mut color = style.resolve_color(style.get_svg().stop_color);
+            color.alpha = (color.alpha as f32 * style.get_svg().stop_opacity * opacity).round() as u8;
+            stops.push((offset, color));
+        }
+        stops
+    }
+
+    fn length(&self,
+              element: &N::ConcreteThreadSafeLayoutElement,
+              name: &str,
+              reference: f32,
+              font_size: f32)
+              -> Option<f32> {
+        attr(element, name).and_then(|value| parse_length(value, reference, font_size))
+    }
+
+    /// The length percentages that are relative to neither the width nor the height
+    /// resolve against.
+    fn diagonal(&self) -> f32 {
+        ((self.viewport.width * self.viewport.width +
+          self.viewport.height * self.viewport.height) / 2.0).sqrt()
+    }
+
+    fn svg_length(&self, value: &NonNegativeSvgLengthOrPercentageOrNumber) -> f32 {
+        match *value {
+            SvgLengthOrPercentageOrNumber::LengthOrPercentage(ref length) => {
+                length.0.to_pixel_length(Au::from_f32_px(self.diagonal())).px()
+            }
+            SvgLengthOrPercentageOrNumber::Number(number) => number.0,
+        }
+    }
+
+    fn svg_width(&self, value: &SVGWidth) -> f32 {
+        match *value {
+            SVGLength::Length(ref length) => self.svg_length(length),
+            SVGLength::ContextValue => 1.0,
+        }
+    }
+}
+
+fn attr<'e, E: ThreadSafeLayoutElement>(element: &'e E, name: &str) -> Option<&'e str> {
+    element.get_attr(&ns!(), &LocalName::from(name))
+}
+
+fn font_size(style: &ComputedValues) -> f32 {
+    style.get_font().font_size.size().to_f32_px()
+}
+
+fn opacity_value(opacity: &SVGOpacity<f32>) -> f32 {
+    match *opacity {
+        SVGOpacity::Opacity(opacity) => opacity.max(0.0).min(1.0),
+        SVGOpacity::ContextFillOpacity | SVGOpacity::ContextStrokeOpacity => 1.0,
+    }
+}
@@ -14,13 +14,17 @@ use display_list::{BlockFlowDisplayListBuilding, BorderPaintingMode};
 use display_list::{DisplayListBuildState, StackingContextCollectionFlags, StackingContextCollectionState};
 use euclid::Point2D;
 use flow::{BaseFlow, EarlyAbsolutePositionInfo, Flow, FlowClass, ImmutableFlowUtils, GetBaseFlow, OpaqueFlow};
-use flow_list::{FlowListIterator, MutFlowListIterator};
+use flow::{FragmentationContext, FragmentationKind, assign_block_size_recursively};
+use flow_list::{FlowList, FlowListIterator, MutFlowListIterator};
 use fragment::{Fragment, FragmentBorderBoxIterator, Overflow};
+use fragmentation::{first_row_in_next_fragment, mark_for_reflow};
 use gfx_traits::print_tree::PrintTree;
 use layout_debug;
 use model::{IntrinsicISizes, IntrinsicISizesContribution, MaybeAuto};
-use std::{cmp, fmt};
+use std::{cmp, fmt, iter};
+use std::sync::Arc;
 use style::computed_values::{border_collapse, border_spacing, table_layout};
+use style::computed_values::display::T as Display;
 use style::context::SharedStyleContext;
 use style::logical_geometry::LogicalSize;
 use style::properties::ComputedValues;
@@ -83,6 +87,85 @@ impl TableFlow {
         }
     }
 
+    /// Returns a new table like this one but with the given children, for the continuation of a
+    /// table split across fragments.
+    fn clone_with_children(&self, children: FlowList) -> TableFlow {
+        TableFlow {
+            block_flow: self.block_flow.clone_with_children(children),
+            column_intrinsic_inline_sizes: self.column_intrinsic_inline_sizes.clone(),
+            column_computed_inline_sizes: self.column_computed_inline_sizes.clone(),
+            collapsed_inline_direction_border_widths_for_table:
+                self.collapsed_inline_direction_border_widths_for_table.clone(),
+            collapsed_block_direction_border_widths_for_table:
+                self.collapsed_block_direction_border_widths_for_table.clone(),
+            table_layout: self.table_layout,
+        }
+    }
+
+    /// Returns the index of the header group of this table, if its rows start with one. It is
+    /// repeated at the top of every page the table is split across.
+    ///
+    /// https://drafts.csswg.org/css-tables-3/#repeated-headers
+    fn repeated_header_index(&self) -> Option<usize> {
+        let (index, kid) = self.block_flow.base.child_iter().enumerate().find(|&(_, kid)| {
+            kid.is_table_row() || kid.is_table_rowgroup()
+        })?;
+        if kid.is_table_rowgroup() &&
+                kid.as_block().fragment.style.get_box().display == Display::TableHeaderGroup {
+            Some(index)
+        } else {
+            None
+        }
+    }
+
+    /// Finds where to split this table, already laid out whole, so that the rows before the break
+    /// fit in `fragmentation_context`. Returns the index of the first child after the break and,
+    /// if a row group is split, the rest of its rows.
+    ///
+    /// Tables only break between rows, and never before a row that a cell of a previous row
+    /// spans into.
+    fn find_break(&mut self,
+                  fragmentation_context: FragmentationContext,
+                  repeated_header_index: Option<usize>)
+                  -> Option<(usize, Option<Arc<Flow>>)> {
+        fn can_break_before_row(row: &Flow) -> bool {
+            row.as_table_row().incoming_rowspan.iter().all(|&span| span == 1)
+        }
+
+        let available_block_size = fragmentation_context.available_block_size;
+        let mut this_fragment_is_empty = fragmentation_context.this_fragment_is_empty;
+        for (child_index, kid) in self.block_flow.base.child_iter_mut().enumerate() {
+            if Some(child_index) == repeated_header_index ||
+                    !(kid.is_table_row() || kid.is_table_rowgroup()) {
+                continue
+            }
+
+            let kid_block_start = kid.base().position.start.b;
+            let break_before = if kid.is_table_row() {
+                let row = (kid_block_start + kid.base().position.size.block, can_break_before_row(kid));
+                first_row_in_next_fragment(iter::once(row), available_block_size, this_fragment_is_empty)
+            } else {
+                let rows = kid.base().child_iter().map(|row| {
+                    let row_block_end = kid_block_start + row.base().position.start.b +
+                        row.base().position.size.block;
+                    (row_block_end, can_break_before_row(row))
+                });
+                first_row_in_next_fragment(rows, available_block_size, this_fragment_is_empty)
+            };
+
+            match break_before {
+                Some(0) => return Some((child_index, None)),
+                Some(row_index) => {
+                    let rows = kid.mut_base().children.split_off(row_index);
+                    let remaining_rows = kid.as_mut_table_rowgroup().clone_with_children(rows);
+                    return Some((child_index + 1, Some(Arc::new(remaining_rows))))
+                }
+                None => this_fragment_is_empty = false,
+            }
+        }
+        None
+    }
+
     /// Update the corresponding value of `self_inline_sizes` if a value of `kid_inline_sizes` has
     /// a larger value than one of `self_inline_sizes`. Returns the minimum and preferred inline
     /// sizes.
@@ -502,6 +585,34 @@ impl Flow for TableFlow {
         self.block_flow.assign_block_size_for_table_like_flow(vertical_spacing, lc)
     }
 
+    fn fragment(&mut self, layout_context: &LayoutContext,
+                fragmentation_context: Option<FragmentationContext>)
+                -> Option<Arc<Flow>> {
+        assign_block_size_recursively(self, layout_context);
+        let fragmentation_context = fragmentation_context?;
+        let repeated_header_index = match fragmentation_context.kind {
+            FragmentationKind::Page => self.repeated_header_index(),
+            FragmentationKind::Column => None,
+        };
+        let (break_index, remaining_rows) =
+            self.find_break(fragmentation_context, repeated_header_index)?;
+
+        let mut children = self.block_flow.base.children.split_off(break_index);
+        if let Some(remaining_rows) = remaining_rows {
+            children.push_front_arc(remaining_rows);
+        }
+        // The header is shared by every fragment of the table; `rejoin_continuation` drops it
+        // from the continuations. This is fine as fragments are laid out one after the other.
+        if let Some(index) = repeated_header_index {
+            children.push_front(self.block_flow.base.children.get_ref(index).unwrap());
+        }
+
+        // Lay out again the rows left in this fragment.
+        mark_for_reflow(self);
+        assign_block_size_recursively(self, layout_context);
+        Some(Arc::new(self.clone_with_children(children)))
+    }
+
     fn compute_stacking_relative_position(&mut self, layout_context: &LayoutContext) {
         self.block_flow.compute_stacking_relative_position(layout_context)
     }
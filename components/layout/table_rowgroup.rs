@@ -13,6 +13,7 @@ use display_list::{BlockFlowDisplayListBuilding, DisplayListBuildState};
 use display_list::{StackingContextCollectionFlags, StackingContextCollectionState};
 use euclid::Point2D;
 use flow::{Flow, FlowClass, OpaqueFlow};
+use flow_list::FlowList;
 use fragment::{Fragment, FragmentBorderBoxIterator, Overflow};
 use gfx_traits::print_tree::PrintTree;
 use layout_debug;
@@ -65,6 +66,20 @@ impl TableRowGroupFlow {
         }
     }
 
+    /// Returns a new row group like this one but with the given rows, for the continuation of a
+    /// row group split across fragments.
+    pub fn clone_with_children(&self, children: FlowList) -> TableRowGroupFlow {
+        TableRowGroupFlow {
+            block_flow: self.block_flow.clone_with_children(children),
+            column_intrinsic_inline_sizes: self.column_intrinsic_inline_sizes.clone(),
+            spacing: self.spacing,
+            collapsed_inline_direction_border_widths_for_table:
+                self.collapsed_inline_direction_border_widths_for_table.clone(),
+            collapsed_block_direction_border_widths_for_table:
+                self.collapsed_block_direction_border_widths_for_table.clone(),
+        }
+    }
+
     pub fn populate_collapsed_border_spacing<'a, I>(
             &mut self,
             collapsed_inline_direction_border_widths_for_table: &[Au],
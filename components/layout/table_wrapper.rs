@@ -21,13 +21,16 @@ use display_list::{BlockFlowDisplayListBuilding, DisplayListBuildState, Stacking
 use display_list::StackingContextCollectionState;
 use euclid::Point2D;
 use floats::FloatKind;
-use flow::{Flow, FlowClass, ImmutableFlowUtils, FlowFlags, OpaqueFlow};
+use flow::{Flow, FlowClass, ImmutableFlowUtils, FlowFlags, FragmentationContext, GetBaseFlow, OpaqueFlow};
+use flow_list::FlowList;
 use fragment::{Fragment, FragmentBorderBoxIterator, Overflow};
+use fragmentation::take_children;
 use gfx_traits::print_tree::PrintTree;
 use model::MaybeAuto;
 use std::cmp::{max, min};
 use std::fmt;
 use std::ops::Add;
+use std::sync::Arc;
 use style::computed_values::{position, table_layout};
 use style::context::SharedStyleContext;
 use style::logical_geometry::{LogicalRect, LogicalSize};
@@ -79,6 +82,16 @@ impl TableWrapperFlow {
         }
     }
 
+    /// Returns a new table wrapper like this one but with the given children, for the
+    /// continuation of a table split across fragments.
+    fn clone_with_children(&self, children: FlowList) -> TableWrapperFlow {
+        TableWrapperFlow {
+            block_flow: self.block_flow.clone_with_children(children),
+            column_intrinsic_inline_sizes: self.column_intrinsic_inline_sizes.clone(),
+            table_layout: self.table_layout,
+        }
+    }
+
     fn border_padding_and_spacing(&mut self) -> (Au, Au) {
         let (mut table_border_padding, mut spacing) = (Au(0), Au(0));
         for kid in self.block_flow.base.child_iter_mut() {
@@ -427,6 +440,17 @@ impl Flow for TableWrapperFlow {
         debug_assert!(remaining.is_none());
     }
 
+    fn fragment(&mut self, layout_context: &LayoutContext,
+                fragmentation_context: Option<FragmentationContext>)
+                -> Option<Arc<Flow>> {
+        let mut remaining = self.block_flow.assign_block_size_block_base(
+            layout_context,
+            fragmentation_context,
+            MarginsMayCollapseFlag::MarginsMayNotCollapse)?;
+        let children = take_children(Arc::get_mut(&mut remaining).unwrap().mut_base());
+        Some(Arc::new(self.clone_with_children(children)))
+    }
+
     fn compute_stacking_relative_position(&mut self, layout_context: &LayoutContext) {
         self.block_flow.compute_stacking_relative_position(layout_context)
     }
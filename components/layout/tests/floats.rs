@@ -0,0 +1,115 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+extern crate app_units;
+extern crate euclid;
+extern crate layout;
+extern crate style;
+
+use app_units::Au;
+use euclid::{Point2D, Rect, Size2D};
+use layout::{FloatKind, FloatShape, Floats, PlacementInfo, ShapeGeometry};
+use std::sync::Arc;
+use style::logical_geometry::{LogicalRect, LogicalSize, WritingMode};
+
+/// Returns the floats of a 300px wide containing block holding a single 100px square float with
+/// the given float area.
+fn floats_with_shape(kind: FloatKind, geometry: ShapeGeometry, shape_margin: f32) -> Floats {
+    let writing_mode = WritingMode::empty();
+    let size = Size2D::new(Au::from_px(100), Au::from_px(100));
+    let mut floats = Floats::new(writing_mode);
+    floats.add_float(&PlacementInfo {
+        size: LogicalSize::from_physical(writing_mode, size),
+        ceiling: Au(0),
+        max_inline_size: Au::from_px(300),
+        kind: kind,
+        shape: Some(Arc::new(FloatShape::from_geometry(geometry, size, writing_mode, shape_margin))),
+    });
+    floats
+}
+
+/// Places a 10px high line box at the given block position.
+fn place_line(floats: &Floats, block_start: i32) -> LogicalRect<Au> {
+    floats.place_line_between_floats(&PlacementInfo {
+        size: LogicalSize::new(floats.writing_mode, Au::from_px(10), Au::from_px(10)),
+        ceiling: Au::from_px(block_start),
+        max_inline_size: Au::from_px(300),
+        kind: FloatKind::Left,
+        shape: None,
+    })
+}
+
+fn assert_px(actual: Au, expected: f32) {
+    assert!((actual.to_f32_px() - expected).abs() < 0.05, "{:?} is not {}px", actual, expected);
+}
+
+#[test]
+fn test_line_wraps_around_circle() {
+    let circle = ShapeGeometry::Ellipse(Point2D::new(50.0, 50.0), Size2D::new(50.0, 50.0));
+    let floats = floats_with_shape(FloatKind::Left, circle, 0.0);
+
+    // 10px below its top, the circle extends 30px right of its center.
+    let line = place_line(&floats, 0);
+    assert_px(line.start.i, 80.0);
+    assert_px(line.start.b, 0.0);
+    assert_px(line.size.inline, 220.0);
+
+    // Lines next to the center avoid the whole radius.
+    assert_px(place_line(&floats, 45).start.i, 100.0);
+
+    // Lines below the float aren't affected by it.
+    let line = place_line(&floats, 100);
+    assert_px(line.start.i, 0.0);
+    assert_px(line.size.inline, 300.0);
+}
+
+#[test]
+fn test_line_wraps_around_polygon_in_right_float() {
+    // A triangle whose left edge runs from the top-right to the bottom-left corner.
+    let triangle = ShapeGeometry::Polygon(vec![
+        Point2D::new(100.0, 0.0),
+        Point2D::new(100.0, 100.0),
+        Point2D::new(0.0, 100.0),
+    ]);
+    let floats = floats_with_shape(FloatKind::Right, triangle, 0.0);
+
+    let line = place_line(&floats, 0);
+    assert_px(line.start.i, 0.0);
+    assert_px(line.size.inline, 290.0);
+
+    let line = place_line(&floats, 50);
+    assert_px(line.size.inline, 240.0);
+}
+
+#[test]
+fn test_shape_margin_expands_float_area() {
+    let circle = ShapeGeometry::Ellipse(Point2D::new(50.0, 50.0), Size2D::new(25.0, 25.0));
+    let floats = floats_with_shape(FloatKind::Left, circle, 10.0);
+
+    // The margin pushes lines out beside the circle.
+    assert_px(place_line(&floats, 45).start.i, 85.0);
+
+    // Lines more than the margin away from the circle aren't affected by it.
+    assert_px(place_line(&floats, 0).start.i, 0.0);
+}
+
+#[test]
+fn test_shape_margin_rounds_corners() {
+    let square = Rect::new(Point2D::new(40.0, 40.0), Size2D::new(20.0, 20.0));
+    let square = ShapeGeometry::RoundedRect(square, [Size2D::zero(); 4]);
+    let floats = floats_with_shape(FloatKind::Left, square, 30.0);
+
+    // Beside the square, the margin extends straight out.
+    assert_px(place_line(&floats, 45).start.i, 90.0);
+
+    // Above it, the margin curves around its corner: the line's last pixel row ends 19px above
+    // the row that the top edge of the square lies on.
+    assert_px(place_line(&floats, 10).start.i, 60.0 + (30.0f32 * 30.0 - 19.0 * 19.0).sqrt());
+
+    // A huge margin covers the float's whole margin box.
+    let square = Rect::new(Point2D::new(40.0, 40.0), Size2D::new(20.0, 20.0));
+    let square = ShapeGeometry::RoundedRect(square, [Size2D::zero(); 4]);
+    let floats = floats_with_shape(FloatKind::Left, square, 1000.0);
+    assert_px(place_line(&floats, 0).start.i, 100.0);
+}
@@ -0,0 +1,66 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+extern crate app_units;
+extern crate layout;
+
+use app_units::Au;
+use layout::{first_row_in_next_fragment, lines_in_fragment};
+
+/// The block-ends of `count` 10px high lines or rows.
+fn block_ends(count: i32) -> Vec<Au> {
+    (1..count + 1).map(|line| Au::from_px(line * 10)).collect()
+}
+
+#[test]
+fn test_lines_that_fit_stay_in_fragment() {
+    assert_eq!(lines_in_fragment(&block_ends(5), Au::from_px(50), false, 2, 2), 5);
+    assert_eq!(lines_in_fragment(&block_ends(5), Au::from_px(35), false, 2, 2), 3);
+}
+
+#[test]
+fn test_lines_in_fragment_honor_widows() {
+    // Four of the six lines fit, but three have to go to the next fragment.
+    assert_eq!(lines_in_fragment(&block_ends(6), Au::from_px(45), false, 1, 3), 3);
+
+    // Leaving fewer lines than `orphans` moves all of them instead.
+    assert_eq!(lines_in_fragment(&block_ends(6), Au::from_px(45), false, 4, 3), 0);
+}
+
+#[test]
+fn test_lines_in_fragment_honor_orphans() {
+    assert_eq!(lines_in_fragment(&block_ends(6), Au::from_px(25), false, 2, 1), 2);
+    assert_eq!(lines_in_fragment(&block_ends(6), Au::from_px(25), false, 3, 1), 0);
+}
+
+#[test]
+fn test_lines_in_empty_fragment_make_progress() {
+    // An empty fragment keeps the lines that fit when `orphans` can't be honored, and at least
+    // one line.
+    assert_eq!(lines_in_fragment(&block_ends(6), Au::from_px(25), true, 3, 1), 2);
+    assert_eq!(lines_in_fragment(&block_ends(6), Au::from_px(5), true, 2, 2), 1);
+}
+
+#[test]
+fn test_rows_break_before_first_overflowing_row() {
+    let rows = block_ends(4).into_iter().map(|block_end| (block_end, true));
+    assert_eq!(first_row_in_next_fragment(rows.clone(), Au::from_px(25), false), Some(2));
+    assert_eq!(first_row_in_next_fragment(rows.clone(), Au::from_px(40), false), None);
+
+    // A fragment with content before the rows can move all of them, an empty one can't.
+    assert_eq!(first_row_in_next_fragment(rows.clone(), Au::from_px(5), false), Some(0));
+    assert_eq!(first_row_in_next_fragment(rows, Au::from_px(5), true), Some(1));
+}
+
+#[test]
+fn test_rows_do_not_break_inside_row_spans() {
+    // The third row is spanned by a cell of the second one.
+    let rows = block_ends(4).into_iter().zip(vec![true, true, false, true]);
+    assert_eq!(first_row_in_next_fragment(rows.clone(), Au::from_px(25), false), Some(1));
+    assert_eq!(first_row_in_next_fragment(rows, Au::from_px(25), true), Some(1));
+
+    // Rows that can't be separated from the first row of an empty fragment stay in it.
+    let rows = block_ends(4).into_iter().zip(vec![true, false, false, true]);
+    assert_eq!(first_row_in_next_fragment(rows, Au::from_px(15), true), Some(3));
+}
@@ -0,0 +1,33 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+extern crate gfx;
+extern crate layout;
+
+use gfx::text::hyphenation::Hyphenator;
+use layout::hyphenation_points;
+
+// The patterns used as an example in appendix H of The TeXbook.
+const PATTERNS: &'static str = "hy3ph he2n hena4 hen5at 1na n2at 1tio 2io o2n";
+
+#[test]
+fn test_hyphenation_points_of_each_word() {
+    let hyphenator = Hyphenator::new(PATTERNS, 2, 3);
+    assert_eq!(hyphenation_points(&["hyphenation, hyphenation"], &hyphenator), vec![vec![2, 6, 15, 19]]);
+}
+
+#[test]
+fn test_words_spanning_several_texts_are_hyphenated_as_a_whole() {
+    let hyphenator = Hyphenator::new(PATTERNS, 2, 3);
+    // Hyphenated piecewise, neither part would have a hyphenation point.
+    assert_eq!(hyphenation_points(&["a hyphe", "nation"], &hyphenator), vec![vec![4], vec![1]]);
+    assert_eq!(hyphenation_points(&["hyp", "", "henation"], &hyphenator), vec![vec![2], vec![], vec![3]]);
+}
+
+#[test]
+fn test_words_with_soft_hyphens_are_left_alone() {
+    let hyphenator = Hyphenator::new(PATTERNS, 2, 3);
+    assert_eq!(hyphenation_points(&["hyphen\u{ad}", "ation hyphenation"], &hyphenator),
+               vec![vec![], vec![8, 12]]);
+}
@@ -0,0 +1,41 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+extern crate app_units;
+extern crate euclid;
+extern crate layout;
+extern crate style;
+
+use app_units::Au;
+use euclid::{Point2D, Rect, SideOffsets2D, Size2D};
+use layout::paged::page_area;
+use style::values::computed::{Length, LengthOrPercentageOrAuto, Percentage};
+
+#[test]
+fn test_page_area_is_inside_page_margins() {
+    let page_size = Size2D::new(Au::from_px(600), Au::from_px(800));
+    let margins = SideOffsets2D::new(LengthOrPercentageOrAuto::Length(Length::new(10.)),
+                                     LengthOrPercentageOrAuto::Length(Length::new(20.)),
+                                     LengthOrPercentageOrAuto::Auto,
+                                     LengthOrPercentageOrAuto::Length(Length::new(40.)));
+    assert_eq!(page_area(page_size, margins),
+               Rect::new(Point2D::new(Au::from_px(40), Au::from_px(10)),
+                         Size2D::new(Au::from_px(540), Au::from_px(790))));
+}
+
+#[test]
+fn test_page_margin_percentages_are_relative_to_page_width() {
+    let page_size = Size2D::new(Au::from_px(600), Au::from_px(800));
+    let margin = LengthOrPercentageOrAuto::Percentage(Percentage(0.1));
+    assert_eq!(page_area(page_size, SideOffsets2D::new_all_same(margin)),
+               Rect::new(Point2D::new(Au::from_px(60), Au::from_px(60)),
+                         Size2D::new(Au::from_px(480), Au::from_px(680))));
+}
+
+#[test]
+fn test_page_area_is_empty_when_margins_overlap() {
+    let page_size = Size2D::new(Au::from_px(100), Au::from_px(100));
+    let margin = LengthOrPercentageOrAuto::Length(Length::new(60.));
+    assert_eq!(page_area(page_size, SideOffsets2D::new_all_same(margin)).size, Size2D::new(Au(0), Au(0)));
+}
@@ -12,7 +12,8 @@ use fragment::{Fragment, ScannedTextFlags};
 use fragment::{ScannedTextFragmentInfo, SpecificFragmentInfo, UnscannedTextFragmentInfo};
 use gfx::font::{FontRef, FontMetrics, RunMetrics, ShapingFlags, ShapingOptions};
 use gfx::text::glyph::ByteIndex;
-use gfx::text::text_run::TextRun;
+use gfx::text::hyphenation::{self, Hyphenator};
+use gfx::text::text_run::{GlyphOrientation, TextRun};
 use gfx::text::util::{self, CompressionMode};
 use inline::{InlineFragmentNodeFlags, InlineFragments};
 use linked_list::split_off_head;
@@ -23,6 +24,8 @@ use std::borrow::ToOwned;
 use std::collections::LinkedList;
 use std::mem;
 use std::sync::Arc;
+use style::computed_values::hyphens::T as Hyphens;
+use style::computed_values::text_combine_upright::T as TextCombineUpright;
 use style::computed_values::text_rendering::T as TextRendering;
 use style::computed_values::text_transform::T as TextTransform;
 use style::computed_values::white_space::T as WhiteSpace;
@@ -163,10 +166,14 @@ impl TextRunScanner {
             let font_group;
             let compression;
             let text_transform;
+            let hyphens;
+            let hyphenator;
             let letter_spacing;
             let word_spacing;
             let text_rendering;
             let word_break;
+            let writing_mode;
+            let combine_upright;
             {
                 let in_fragment = self.clump.front().unwrap();
                 let font_style = in_fragment.style().clone_font();
@@ -180,14 +187,35 @@ impl TextRunScanner {
                     WhiteSpace::PreLine => CompressionMode::CompressWhitespace,
                 };
                 text_transform = inherited_text_style.text_transform;
+                hyphens = inherited_text_style.hyphens;
+                hyphenator = match hyphens {
+                    Hyphens::Auto => hyphenation::hyphenator_for_language(&in_fragment.style().get_font()._x_lang.0),
+                    Hyphens::Manual | Hyphens::None => None,
+                };
                 letter_spacing = inherited_text_style.letter_spacing;
                 word_spacing = inherited_text_style.word_spacing.value()
                                .map(|lop| lop.to_hash_key())
                                .unwrap_or((Au(0), NotNan::new(0.0).unwrap()));
                 text_rendering = inherited_text_style.text_rendering;
                 word_break = inherited_text_style.word_break;
+                writing_mode = in_fragment.style().writing_mode;
+                combine_upright = writing_mode.is_vertical() &&
+                    inherited_text_style.text_combine_upright == TextCombineUpright::All;
             }
 
+            // Find the hyphenation points of the whole clump first, so that words which span
+            // several fragments or mappings are hyphenated as a whole.
+            let fragment_hyphenation_points = match hyphenator {
+                Some(hyphenator) => {
+                    let texts: Vec<&str> = self.clump.iter().map(|fragment| match fragment.specific {
+                        SpecificFragmentInfo::UnscannedText(ref text_fragment_info) => &*text_fragment_info.text,
+                        _ => panic!("Expected an unscanned text fragment!"),
+                    }).collect();
+                    hyphenation_points(&texts, hyphenator)
+                }
+                None => vec![vec![]; self.clump.len()],
+            };
+
             // First, transform/compress text of all the nodes.
             let (mut run_info_list, mut run_info) = (Vec::new(), RunInfo::new());
             let mut insertion_point = None;
@@ -214,9 +242,29 @@ impl TextRunScanner {
                 };
 
                 let (mut start_position, mut end_position) = (0, 0);
-                for (byte_index, character) in text.char_indices() {
+                let mut previous_character = None;
+                let mut characters = text.char_indices().peekable();
+                while let Some((byte_index, character)) = characters.next() {
                     if !character.is_control() {
-                        let font = font_group.borrow_mut().find_by_codepoint(&mut font_context, character);
+                        // The characters of an emoji sequence are rendered with the same font.
+                        let continues_emoji_sequence = util::continues_emoji_sequence(character) ||
+                            previous_character == Some(util::ZERO_WIDTH_JOINER);
+                        let emoji_presentation = match characters.peek() {
+                            Some(&(_, util::EMOJI_PRESENTATION_SELECTOR)) => true,
+                            Some(&(_, util::TEXT_PRESENTATION_SELECTOR)) => false,
+                            _ => util::is_emoji_presentation(character),
+                        };
+                        // Soft hyphens are invisible unless a line is broken at one, so they don't
+                        // need a font of their own.
+                        let font = match run_info.font {
+                            Some(ref font) if continues_emoji_sequence || character == util::SOFT_HYPHEN => {
+                                Some(font.clone())
+                            }
+                            _ if emoji_presentation => {
+                                font_group.borrow_mut().find_emoji_by_codepoint(&mut font_context, character)
+                            }
+                            _ => font_group.borrow_mut().find_by_codepoint(&mut font_context, character),
+                        };
 
                         let bidi_level = match bidi_levels {
                             Some(levels) => levels[*paragraph_bytes_processed],
@@ -239,9 +287,12 @@ impl TextRunScanner {
                             None => false
                         };
 
+                        let orientation = glyph_orientation(character, writing_mode, combine_upright);
+
                         // Now, if necessary, flush the mapping we were building up.
                         let flush_run = !run_info.has_font(&font) ||
                                         run_info.bidi_level != bidi_level ||
+                                        run_info.orientation != orientation ||
                                         !compatible_script;
                         let new_mapping_needed = flush_run || mapping.selected != selected;
 
@@ -255,6 +306,8 @@ impl TextRunScanner {
                                               &**text,
                                               compression,
                                               text_transform,
+                                              hyphens,
+                                              &fragment_hyphenation_points[fragment_index],
                                               &mut last_whitespace,
                                               &mut start_position,
                                               end_position);
@@ -270,6 +323,7 @@ impl TextRunScanner {
                             run_info.font = font;
                             run_info.bidi_level = bidi_level;
                             run_info.script = script;
+                            run_info.orientation = orientation;
                             mapping.selected = selected;
                         }
                     }
@@ -277,6 +331,7 @@ impl TextRunScanner {
                     // Consume this character.
                     end_position += character.len_utf8();
                     *paragraph_bytes_processed += character.len_utf8();
+                    previous_character = Some(character);
                 }
 
                 // Flush the last mapping we created for this fragment to the list.
@@ -285,6 +340,8 @@ impl TextRunScanner {
                               &**text,
                               compression,
                               text_transform,
+                              hyphens,
+                              &fragment_hyphenation_points[fragment_index],
                               &mut last_whitespace,
                               &mut start_position,
                               end_position);
@@ -324,12 +381,20 @@ impl TextRunScanner {
                 if run_info.bidi_level.is_rtl() {
                     options.flags.insert(ShapingFlags::RTL_FLAG);
                 }
+                match run_info.orientation {
+                    GlyphOrientation::Horizontal => {}
+                    GlyphOrientation::Upright => options.flags.insert(ShapingFlags::VERTICAL_FLAG),
+                    GlyphOrientation::Sideways => options.flags.insert(ShapingFlags::SIDEWAYS_FLAG),
+                }
 
                 // If no font is found (including fallbacks), there's no way we can render.
                 let font =
                     run_info.font
                         .or_else(|| font_group.borrow_mut().first(&mut font_context))
                         .expect("No font found for text run!");
+                if run_info.orientation == GlyphOrientation::Sideways {
+                    font_context.ensure_sideways_font_key(&font);
+                }
 
                 let (run, break_at_zero) = TextRun::new(&mut *font.borrow_mut(),
                                                         run_info.text,
@@ -394,6 +459,10 @@ impl TextRunScanner {
                     flags.insert(ScannedTextFlags::SELECTED);
                 }
 
+                if combine_upright {
+                    flags.insert(ScannedTextFlags::COMBINED_UPRIGHT);
+                }
+
                 let insertion_point = if mapping.contains_insertion_point(scanned_run.insertion_point) {
                     scanned_run.insertion_point
                 } else {
@@ -410,7 +479,11 @@ impl TextRunScanner {
 
                 let new_metrics = new_text_fragment_info.run.metrics_for_range(&byte_range);
                 let writing_mode = old_fragment.style.writing_mode;
-                let bounding_box_size = bounding_box_for_run_metrics(&new_metrics, writing_mode);
+                let mut bounding_box_size = bounding_box_for_run_metrics(&new_metrics, writing_mode);
+                if combine_upright {
+                    // The text is composed horizontally into a single em of the vertical line.
+                    bounding_box_size.inline = new_text_fragment_info.run.actual_pt_size;
+                }
                 new_text_fragment_info.content_size = bounding_box_size;
 
                 let mut new_fragment = old_fragment.transform(
@@ -449,14 +522,29 @@ impl TextRunScanner {
 #[inline]
 fn bounding_box_for_run_metrics(metrics: &RunMetrics, writing_mode: WritingMode)
                                 -> LogicalSize<Au> {
-    // TODO: When the text-orientation property is supported, the block and inline directions may
-    // be swapped for horizontal glyphs in vertical lines.
+    // Glyph advances are along the inline axis whatever the orientation of the glyphs, since
+    // upright glyphs are shaped vertically and sideways glyphs are rotated into vertical lines.
     LogicalSize::new(
         writing_mode,
         metrics.bounding_box.size.width,
         metrics.bounding_box.size.height)
 }
 
+/// Returns how a character is laid out in a line of the given writing mode.
+///
+/// https://drafts.csswg.org/css-writing-modes-3/#text-orientation
+fn glyph_orientation(character: char, writing_mode: WritingMode, combine_upright: bool) -> GlyphOrientation {
+    if !writing_mode.is_vertical() || combine_upright {
+        GlyphOrientation::Horizontal
+    } else if writing_mode.is_upright() {
+        GlyphOrientation::Upright
+    } else if writing_mode.is_sideways() || !util::is_upright_in_vertical_text(character) {
+        GlyphOrientation::Sideways
+    } else {
+        GlyphOrientation::Upright
+    }
+}
+
 /// Returns the metrics of the font represented by the given `FontStyleStruct`.
 ///
 /// `#[inline]` because often the caller only needs a few fields from the font metrics.
@@ -558,6 +646,8 @@ struct RunInfo {
     bidi_level: bidi::Level,
     /// The Unicode script property of this text run.
     script: Script,
+    /// How the glyphs of this text run are laid out in a vertical line.
+    orientation: GlyphOrientation,
 }
 
 impl RunInfo {
@@ -568,6 +658,7 @@ impl RunInfo {
             font: None,
             bidi_level: bidi::Level::ltr(),
             script: Script::Common,
+            orientation: GlyphOrientation::Horizontal,
         }
     }
 
@@ -592,8 +683,9 @@ impl RunInfo {
     }
 
     fn has_font(&self, font: &Option<FontRef>) -> bool {
-        fn identifier(font: &Option<FontRef>) -> Option<Atom> {
-            font.as_ref().map(|f| f.borrow().identifier())
+        // An invisible copy of a font must not be in the same run as the font itself.
+        fn identifier(font: &Option<FontRef>) -> Option<(Atom, bool)> {
+            font.as_ref().map(|f| (f.borrow().identifier(), f.borrow().invisible))
         }
 
         identifier(&self.font) == identifier(font)
@@ -628,13 +720,16 @@ impl RunMapping {
     }
 
     /// Flushes this run mapping to the list. `run_info` describes the text run that we're
-    /// currently working on. `text` refers to the text of this fragment.
+    /// currently working on. `text` refers to the text of this fragment, and
+    /// `hyphenation_points` to the byte offsets within it where soft hyphens are inserted.
     fn flush(mut self,
              mappings: &mut Vec<RunMapping>,
              run_info: &mut RunInfo,
              text: &str,
              compression: CompressionMode,
              text_transform: TextTransform,
+             hyphens: Hyphens,
+             hyphenation_points: &[usize],
              last_whitespace: &mut bool,
              start_position: &mut usize,
              end_position: usize) {
@@ -645,6 +740,14 @@ impl RunMapping {
                                                 *last_whitespace,
                                                 &mut run_info.text);
 
+        // Account for `hyphens` before `text-transform`, which may change the number of letters.
+        apply_hyphens_if_necessary(&mut run_info.text,
+                                   old_byte_length,
+                                   hyphens,
+                                   &text[(*start_position)..end_position],
+                                   *start_position,
+                                   hyphenation_points);
+
         // Account for `text-transform`. (Confusingly, this is not handled in "text
         // transformation" above, but we follow Gecko in the naming.)
         let is_first_run = *start_position == 0;
@@ -733,6 +836,85 @@ fn apply_style_transform_if_necessary(string: &mut String,
     }
 }
 
+/// Accounts for `hyphens`: removes the soft hyphens of the text if it is `none`, and inserts soft
+/// hyphens at the hyphenation points found by `hyphenation_points` if it is `auto`.
+///
+/// `source` is the text the characters after `first_character_position` were transformed from,
+/// which starts at `source_position` in the text of its fragment. Transforming text only
+/// discards or collapses non-letters, so the letters of both are matched in order.
+fn apply_hyphens_if_necessary(string: &mut String,
+                              first_character_position: usize,
+                              hyphens: Hyphens,
+                              source: &str,
+                              source_position: usize,
+                              hyphenation_points: &[usize]) {
+    match hyphens {
+        Hyphens::Manual => {}
+        Hyphens::None => {
+            if string[first_character_position..].contains(util::SOFT_HYPHEN) {
+                let original = string[first_character_position..].to_owned();
+                string.truncate(first_character_position);
+                string.extend(original.chars().filter(|&ch| ch != util::SOFT_HYPHEN));
+            }
+        }
+        Hyphens::Auto => {
+            let source_end = source_position + source.len();
+            if !hyphenation_points.iter().any(|&point| source_position <= point && point < source_end) {
+                return
+            }
+            let mut hyphenated_letters = source.char_indices()
+                .filter(|&(_, character)| character.is_alphabetic())
+                .map(|(index, _)| hyphenation_points.binary_search(&(source_position + index)).is_ok());
+
+            let original = string[first_character_position..].to_owned();
+            string.truncate(first_character_position);
+            for character in original.chars() {
+                if character.is_alphabetic() && hyphenated_letters.next() == Some(true) {
+                    string.push(util::SOFT_HYPHEN);
+                }
+                string.push(character);
+            }
+        }
+    }
+}
+
+/// Returns, for each of `texts`, the byte offsets within it before which a soft hyphen may be
+/// inserted, in order. A word may span several texts. Words which already contain soft hyphens
+/// are left to the author.
+pub fn hyphenation_points(texts: &[&str], hyphenator: &Hyphenator) -> Vec<Vec<usize>> {
+    let mut points = vec![vec![]; texts.len()];
+    let mut word = String::new();
+    // The text index and the byte offset within that text of each letter of `word`, keyed by its
+    // byte offset within `word`.
+    let mut letters = vec![];
+    {
+        let mut end_word = |word: &mut String, letters: &mut Vec<(usize, usize, usize)>| {
+            if !word.contains(util::SOFT_HYPHEN) {
+                for point in hyphenator.hyphenation_points(word) {
+                    if let Ok(letter) = letters.binary_search_by_key(&point, |&(offset, _, _)| offset) {
+                        let (_, text_index, index) = letters[letter];
+                        points[text_index].push(index);
+                    }
+                }
+            }
+            word.clear();
+            letters.clear();
+        };
+        for (text_index, text) in texts.iter().enumerate() {
+            for (index, character) in text.char_indices() {
+                if character.is_alphabetic() || character == util::SOFT_HYPHEN {
+                    letters.push((word.len(), text_index, index));
+                    word.push(character);
+                } else {
+                    end_word(&mut word, &mut letters);
+                }
+            }
+        }
+        end_word(&mut word, &mut letters);
+    }
+    points
+}
+
 #[derive(Clone)]
 struct ScannedTextRun {
     run: Arc<TextRun>,
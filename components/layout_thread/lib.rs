@@ -57,27 +57,28 @@ use app_units::Au;
 use dom_wrapper::{ServoLayoutElement, ServoLayoutDocument, ServoLayoutNode};
 use dom_wrapper::drop_style_and_layout_data;
 use embedder_traits::resources::{self, Resource};
-use euclid::{Point2D, Rect, Size2D, TypedScale, TypedSize2D};
-use fnv::FnvHashMap;
+use euclid::{Point2D, Rect, SideOffsets2D, Size2D, TypedScale, TypedSize2D};
+use fnv::{FnvHashMap, FnvHashSet};
 use gfx::font;
-use gfx::font_cache_thread::FontCacheThread;
+use gfx::font_cache_thread::{FontCacheThread, WebFontEvent, WebFontId};
 use gfx::font_context;
 use gfx_traits::{Epoch, node_id_from_scroll_id};
 use histogram::Histogram;
 use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
 use ipc_channel::router::ROUTER;
-use layout::animation;
+use layout::animation::{self, AnimatedImage};
 use layout::construct::ConstructionResult;
 use layout::context::LayoutContext;
 use layout::context::RegisteredPainter;
 use layout::context::RegisteredPainters;
 use layout::context::malloc_size_of_persistent_local_context;
-use layout::display_list::{IndexableText, ToLayout, WebRenderDisplayListConverter};
-use layout::display_list::items::{OpaqueNode, WebRenderImageInfo};
+use layout::display_list::{IndexableText, PaintedMasks, ToLayout, WebRenderDisplayListConverter};
+use layout::display_list::items::{DisplayItem, DisplayList, OpaqueNode, WebRenderImageInfo};
 use layout::flow::{Flow, GetBaseFlow, ImmutableFlowUtils, MutableOwnedFlowUtils};
 use layout::flow_ref::FlowRef;
 use layout::incremental::{LayoutDamageComputation, RelayoutMode, SpecialRestyleDamage};
 use layout::layout_debug;
+use layout::paged;
 use layout::parallel;
 use layout::query::{LayoutRPCImpl, LayoutThreadData, process_content_box_request, process_content_boxes_request};
 use layout::query::{process_element_inner_text_query, process_node_geometry_request};
@@ -92,6 +93,7 @@ use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
 use metrics::{PaintTimeMetrics, ProfilerMetadataFactory, ProgressiveWebMetric};
 use msg::constellation_msg::PipelineId;
 use msg::constellation_msg::TopLevelBrowsingContextId;
+use net_traits::image::base::Image;
 use net_traits::image_cache::{ImageCache, UsePlaceholder};
 use parking_lot::RwLock;
 use profile_traits::mem::{self, Report, ReportKind, ReportsChan};
@@ -99,12 +101,13 @@ use profile_traits::time::{self, TimerMetadata, profile};
 use profile_traits::time::{TimerMetadataFrameType, TimerMetadataReflowType};
 use script_layout_interface::message::{Msg, NewLayoutThreadInfo, NodesFromPointQueryType, Reflow};
 use script_layout_interface::message::{ReflowComplete, QueryMsg, ReflowGoal, ScriptReflow};
-use script_layout_interface::rpc::{LayoutRPC, StyleResponse, OffsetParentResponse};
+use script_layout_interface::rpc::{LayoutPage, LayoutRPC, StyleResponse, OffsetParentResponse};
 use script_layout_interface::rpc::TextIndexResponse;
 use script_layout_interface::wrapper_traits::LayoutNode;
-use script_traits::{ConstellationControlMsg, LayoutControlMsg, LayoutMsg as ConstellationMsg};
+use script_traits::{AnimationState, ConstellationControlMsg, LayoutControlMsg};
+use script_traits::LayoutMsg as ConstellationMsg;
 use script_traits::{DrawAPaintImageResult, PaintWorkletError};
-use script_traits::{ScrollState, UntrustedNodeAddress};
+use script_traits::{ScrollSnapInfo, ScrollState, UntrustedNodeAddress, WebFontLoadEvent};
 use script_traits::Painter;
 use selectors::Element;
 use servo_arc::Arc as ServoArc;
@@ -115,7 +118,7 @@ use servo_geometry::MaxRect;
 use servo_url::ServoUrl;
 use std::borrow::ToOwned;
 use std::cell::{Cell, RefCell};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::mem as std_mem;
 use std::ops::{Deref, DerefMut};
 use std::process;
@@ -129,10 +132,11 @@ use style::context::{SharedStyleContext, StyleSystemOptions, ThreadLocalStyleCon
 use style::dom::{ShowSubtree, ShowSubtreeDataAndPrimaryValues, TElement, TNode};
 use style::driver;
 use style::error_reporting::RustLogReporter;
+use style::font_face::{FontFaceDescriptors, ScriptFontFaceSource};
 use style::invalidation::element::restyle_hints::RestyleHint;
 use style::logical_geometry::LogicalPoint;
 use style::media_queries::{Device, MediaList, MediaType};
-use style::properties::PropertyId;
+use style::properties::{ComputedValues, PropertyId};
 use style::selector_parser::SnapshotMap;
 use style::servo::restyle_damage::ServoRestyleDamage;
 use style::shared_lock::{SharedRwLock, SharedRwLockReadGuard, StylesheetGuards};
@@ -142,6 +146,7 @@ use style::thread_state::{self, ThreadState};
 use style::timer::Timer;
 use style::traversal::DomTraversal;
 use style::traversal_flags::TraversalFlags;
+use style::values::computed::font::FamilyName;
 use style_traits::CSSPixel;
 use style_traits::DevicePixel;
 use style_traits::SpeculativePainter;
@@ -170,10 +175,10 @@ pub struct LayoutThread {
     pipeline_port: Receiver<LayoutControlMsg>,
 
     /// The port on which we receive messages from the font cache thread.
-    font_cache_receiver: Receiver<()>,
+    font_cache_receiver: Receiver<WebFontEvent>,
 
     /// The channel on which the font cache can send messages to us.
-    font_cache_sender: IpcSender<()>,
+    font_cache_sender: IpcSender<WebFontEvent>,
 
     /// The channel on which messages can be sent to the constellation.
     constellation_chan: IpcSender<ConstellationMsg>,
@@ -216,6 +221,17 @@ pub struct LayoutThread {
     /// The number of Web fonts that have been requested but not yet loaded.
     outstanding_web_fonts: Arc<AtomicUsize>,
 
+    /// The font faces created by script that were given to the font cache, by identifier.
+    script_web_fonts: HashMap<usize, WebFontId>,
+
+    /// The font faces of the `@font-face` rules that were given to the font cache, by rule
+    /// identifier.
+    css_web_fonts: HashMap<usize, WebFontId>,
+
+    /// The identifiers of the font faces created by script that belong to the fonts of the
+    /// document.
+    used_font_faces: HashSet<usize>,
+
     /// The root of the flow tree.
     root_flow: RefCell<Option<FlowRef>>,
 
@@ -228,13 +244,42 @@ pub struct LayoutThread {
     /// The list of animations that have expired since the last style recalculation.
     expired_animations: ServoArc<RwLock<FnvHashMap<OpaqueNode, Vec<Animation>>>>,
 
+    /// The animated images that have been laid out, by their node and the key of the image's own
+    /// webrender image.
+    animated_images: Arc<RwLock<FnvHashMap<(OpaqueNode, webrender_api::ImageKey), AnimatedImage>>>,
+
+    /// Whether any animated image in the display list is still playing.
+    animated_images_playing: Cell<bool>,
+
+    /// Whether the embedder asked for animated images to stay on their first frame.
+    prefers_reduced_motion: Cell<bool>,
+
+    /// The webrender images to delete once the next display list is sent.
+    retired_image_keys: Arc<Mutex<Vec<webrender_api::ImageKey>>>,
+
+    /// The image masks of `clip-path` and `mask-*`, which are reused across display lists.
+    painted_masks: Arc<Mutex<PaintedMasks>>,
+
+    /// The rasterizations of vector images shown by the display list last built.
+    drawn_vector_images: Arc<Mutex<Vec<Arc<Image>>>>,
+
+    /// The rasterizations of vector images shown by the display list last sent, kept alive so
+    /// that the image cache doesn't delete them while they are on screen.
+    sent_vector_images: RefCell<Vec<Arc<Image>>>,
+
     /// A counter for epoch messages
     epoch: Cell<Epoch>,
 
+    /// The scroll snap points that were last sent to the compositor.
+    scroll_snap_info: RefCell<Vec<ScrollSnapInfo>>,
+
     /// The size of the viewport. This may be different from the size of the screen due to viewport
     /// constraints.
     viewport_size: Size2D<Au>,
 
+    /// The page area, inside the `@page` margins, when laying out for a pages query.
+    page_area: Option<Rect<Au>>,
+
     /// A mutex to allow for fast, read-only RPC of layout's internal data
     /// structures, while still letting the LayoutThread modify them.
     ///
@@ -413,36 +458,6 @@ impl<'a, 'b: 'a> RwData<'a, 'b> {
     }
 }
 
-fn add_font_face_rules(stylesheet: &Stylesheet,
-                       guard: &SharedRwLockReadGuard,
-                       device: &Device,
-                       font_cache_thread: &FontCacheThread,
-                       font_cache_sender: &IpcSender<()>,
-                       outstanding_web_fonts_counter: &Arc<AtomicUsize>) {
-    if opts::get().load_webfonts_synchronously {
-        let (sender, receiver) = ipc::channel().unwrap();
-        stylesheet.effective_font_face_rules(&device, guard, |rule| {
-            if let Some(font_face) = rule.font_face() {
-                let effective_sources = font_face.effective_sources();
-                font_cache_thread.add_web_font(font_face.family().clone(),
-                                               effective_sources,
-                                               sender.clone());
-                receiver.recv().unwrap();
-            }
-        })
-    } else {
-        stylesheet.effective_font_face_rules(&device, guard, |rule| {
-            if let Some(font_face) = rule.font_face() {
-                let effective_sources = font_face.effective_sources();
-                outstanding_web_fonts_counter.fetch_add(1, Ordering::SeqCst);
-                font_cache_thread.add_web_font(font_face.family().clone(),
-                                              effective_sources,
-                                              (*font_cache_sender).clone());
-            }
-        })
-    }
-}
-
 impl LayoutThread {
     /// Creates a new `LayoutThread` structure.
     fn new(id: PipelineId,
@@ -515,12 +530,24 @@ impl LayoutThread {
             new_animations_sender: new_animations_sender,
             new_animations_receiver: new_animations_receiver,
             outstanding_web_fonts: Arc::new(AtomicUsize::new(0)),
+            script_web_fonts: HashMap::new(),
+            css_web_fonts: HashMap::new(),
+            used_font_faces: HashSet::new(),
             root_flow: RefCell::new(None),
             document_shared_lock: None,
             running_animations: ServoArc::new(RwLock::new(FnvHashMap::default())),
             expired_animations: ServoArc::new(RwLock::new(FnvHashMap::default())),
+            animated_images: Arc::new(RwLock::new(FnvHashMap::default())),
+            animated_images_playing: Cell::new(false),
+            prefers_reduced_motion: Cell::new(false),
+            retired_image_keys: Arc::new(Mutex::new(vec![])),
+            painted_masks: Arc::new(Mutex::new(PaintedMasks::default())),
+            drawn_vector_images: Arc::new(Mutex::new(vec![])),
+            sent_vector_images: RefCell::new(vec![]),
             epoch: Cell::new(Epoch(0)),
+            scroll_snap_info: RefCell::new(vec![]),
             viewport_size: Size2D::new(Au(0), Au(0)),
+            page_area: None,
             webrender_api: webrender_api_sender.create_api(),
             webrender_document,
             stylist: Stylist::new(device, QuirksMode::NoQuirks),
@@ -541,6 +568,7 @@ impl LayoutThread {
                     text_index_response: TextIndexResponse(None),
                     nodes_from_point_response: vec![],
                     element_inner_text_response: String::new(),
+                    pages_response: vec![],
                 })),
             webrender_image_cache:
                 Arc::new(RwLock::new(FnvHashMap::default())),
@@ -597,6 +625,10 @@ impl LayoutThread {
             image_cache: self.image_cache.clone(),
             font_cache_thread: Mutex::new(self.font_cache_thread.clone()),
             webrender_image_cache: self.webrender_image_cache.clone(),
+            animated_images: self.animated_images.clone(),
+            retired_image_keys: self.retired_image_keys.clone(),
+            painted_masks: self.painted_masks.clone(),
+            drawn_vector_images: self.drawn_vector_images.clone(),
             pending_images: if script_initiated_layout { Some(Mutex::new(vec![])) } else { None },
             newly_transitioning_nodes: if script_initiated_layout { Some(Mutex::new(vec![])) } else { None },
             registered_painters: &self.registered_painters,
@@ -608,7 +640,7 @@ impl LayoutThread {
         enum Request {
             FromPipeline(LayoutControlMsg),
             FromScript(Msg),
-            FromFontCache,
+            FromFontCache(WebFontEvent),
         }
 
         let request = {
@@ -623,8 +655,7 @@ impl LayoutThread {
                     Request::FromScript(msg.unwrap())
                 },
                 msg = port_from_font_cache.recv() => {
-                    msg.unwrap();
-                    Request::FromFontCache
+                    Request::FromFontCache(msg.unwrap())
                 }
             }
         };
@@ -651,12 +682,30 @@ impl LayoutThread {
                 self.paint_time_metrics.maybe_set_metric(epoch, paint_time);
                 true
             },
+            Request::FromPipeline(LayoutControlMsg::SetPrefersReducedMotion(prefers_reduced_motion)) => {
+                self.prefers_reduced_motion.set(prefers_reduced_motion);
+                self.tick_animated_images();
+                true
+            },
             Request::FromScript(msg) => {
                 self.handle_request_helper(msg, possibly_locked_rw_data)
             },
-            Request::FromFontCache => {
+            Request::FromFontCache(event) => {
                 let _rw_data = possibly_locked_rw_data.lock();
-                self.outstanding_web_fonts.fetch_sub(1, Ordering::SeqCst);
+                match event {
+                    WebFontEvent::Deferred => {
+                        self.outstanding_web_fonts.fetch_sub(1, Ordering::SeqCst);
+                        return true
+                    }
+                    WebFontEvent::LoadStarted => {
+                        self.outstanding_web_fonts.fetch_add(1, Ordering::SeqCst);
+                        return true
+                    }
+                    WebFontEvent::BlockPeriodEnded => {}
+                    WebFontEvent::LoadFinished(_) => {
+                        self.outstanding_web_fonts.fetch_sub(1, Ordering::SeqCst);
+                    }
+                }
                 font_context::invalidate_font_caches();
                 self.script_chan.send(ConstellationControlMsg::WebFontLoaded(self.id)).unwrap();
                 true
@@ -782,11 +831,81 @@ impl LayoutThread {
             Msg::SetNavigationStart(time) => {
                 self.paint_time_metrics.set_navigation_start(time);
             },
+            Msg::LoadFontFace(id, family, source, descriptors, sender) => {
+                self.handle_load_font_face(id, family, source, descriptors, sender);
+            },
+            Msg::SetFontFaceUsed(id, used) => {
+                self.handle_set_font_face_used(id, used);
+            },
+            Msg::LoadFontFaceRule(rule_id) => {
+                self.handle_load_font_face_rule(rule_id);
+            },
+            Msg::RemoveFontFace(id) => {
+                self.used_font_faces.remove(&id);
+                if let Some(web_font_id) = self.script_web_fonts.remove(&id) {
+                    self.font_cache_thread.remove_web_font(web_font_id);
+                }
+            },
         }
 
         true
     }
 
+    /// Gives a font face created by script to the font cache. Its events are handled like the
+    /// ones of the `@font-face` rules, and its outcome is sent back to script.
+    fn handle_load_font_face(&mut self,
+                             id: usize,
+                             family: FamilyName,
+                             source: ScriptFontFaceSource,
+                             descriptors: FontFaceDescriptors,
+                             sender: IpcSender<bool>) {
+        self.outstanding_web_fonts.fetch_add(1, Ordering::SeqCst);
+
+        let (event_sender, event_receiver) = ipc::channel().unwrap();
+        let font_cache_sender = self.font_cache_sender.clone();
+        ROUTER.add_route(event_receiver.to_opaque(), Box::new(move |message| {
+            let event = message.to().unwrap();
+            if let WebFontEvent::LoadFinished(loaded) = event {
+                let _ = sender.send(loaded);
+            }
+            let _ = font_cache_sender.send(event);
+        }));
+
+        let web_font_id =
+            self.font_cache_thread.add_script_web_font(family, source, descriptors, event_sender);
+        if self.used_font_faces.contains(&id) {
+            self.font_cache_thread.set_web_font_enabled(web_font_id.clone(), true);
+        }
+        self.script_web_fonts.insert(id, web_font_id);
+    }
+
+    fn handle_set_font_face_used(&mut self, id: usize, used: bool) {
+        if used {
+            self.used_font_faces.insert(id);
+        } else {
+            self.used_font_faces.remove(&id);
+        }
+
+        if let Some(web_font_id) = self.script_web_fonts.get(&id) {
+            self.font_cache_thread.set_web_font_enabled(web_font_id.clone(), used);
+            font_context::invalidate_font_caches();
+            self.script_chan.send(ConstellationControlMsg::WebFontLoaded(self.id)).unwrap();
+        }
+    }
+
+    fn handle_load_font_face_rule(&self, rule_id: usize) {
+        match self.css_web_fonts.get(&rule_id) {
+            Some(web_font_id) => self.font_cache_thread.load_deferred_web_font(web_font_id.clone()),
+            None => {
+                // The faces of the rules inserted through the CSSOM are not given to the font
+                // cache, so they can't load.
+                let event = WebFontLoadEvent::Finished(false);
+                let msg = ConstellationControlMsg::FontFaceRuleLoad(self.id, rule_id, event);
+                self.script_chan.send(msg).unwrap();
+            },
+        }
+    }
+
     fn collect_reports<'a, 'b>(&self,
                                reports_chan: ReportsChan,
                                possibly_locked_rw_data: &mut RwData<'a, 'b>) {
@@ -889,22 +1008,85 @@ impl LayoutThread {
     }
 
     fn handle_add_stylesheet(
-        &self,
+        &mut self,
         stylesheet: &Stylesheet,
         guard: &SharedRwLockReadGuard,
     ) {
         // Find all font-face rules and notify the font cache of them.
         // GWTODO: Need to handle unloading web fonts.
         if stylesheet.is_effective_for_device(self.stylist.device(), &guard) {
-            add_font_face_rules(&*stylesheet,
-                                &guard,
-                                self.stylist.device(),
-                                &self.font_cache_thread,
-                                &self.font_cache_sender,
-                                &self.outstanding_web_fonts);
+            self.add_font_face_rules(&*stylesheet, &guard);
         }
     }
 
+    /// Gives the font faces of the `@font-face` rules of `stylesheet` to the font cache, and tells
+    /// script when they start and finish loading.
+    fn add_font_face_rules(&mut self, stylesheet: &Stylesheet, guard: &SharedRwLockReadGuard) {
+        let (sender, receiver) = ipc::channel().unwrap();
+        let pipeline_id = self.id;
+        let font_cache_thread = &self.font_cache_thread;
+        let font_cache_sender = &self.font_cache_sender;
+        let script_chan = &self.script_chan;
+        let outstanding_web_fonts = &self.outstanding_web_fonts;
+        let css_web_fonts = &mut self.css_web_fonts;
+        stylesheet.effective_font_face_rules(self.stylist.device(), guard, |rule| {
+            let font_face = match rule.font_face() {
+                Some(font_face) => font_face,
+                None => return,
+            };
+            let rule_id = rule.id();
+            let effective_sources = font_face.effective_sources();
+            let descriptors = font_face.descriptors();
+            // The font cache only tells when faces restricted to some characters start loading.
+            let eager = descriptors.covers_all();
+            if eager {
+                let msg = ConstellationControlMsg::FontFaceRuleLoad(pipeline_id, rule_id, WebFontLoadEvent::Started);
+                let _ = script_chan.send(msg);
+            }
+
+            // Faces restricted to some characters are only loaded once some text needs them, so
+            // their loading cannot be waited for here.
+            if opts::get().load_webfonts_synchronously && eager {
+                let web_font_id = font_cache_thread.add_web_font(font_face.family().clone(),
+                                                                 effective_sources,
+                                                                 descriptors,
+                                                                 sender.clone());
+                css_web_fonts.insert(rule_id, web_font_id);
+                let loaded = loop {
+                    if let WebFontEvent::LoadFinished(loaded) = receiver.recv().unwrap() {
+                        break loaded
+                    }
+                };
+                let event = WebFontLoadEvent::Finished(loaded);
+                let _ = script_chan.send(ConstellationControlMsg::FontFaceRuleLoad(pipeline_id, rule_id, event));
+                return
+            }
+
+            outstanding_web_fonts.fetch_add(1, Ordering::SeqCst);
+            let (event_sender, event_receiver) = ipc::channel().unwrap();
+            let font_cache_sender = font_cache_sender.clone();
+            let script_chan = script_chan.clone();
+            ROUTER.add_route(event_receiver.to_opaque(), Box::new(move |message| {
+                let event = message.to().unwrap();
+                let load_event = match event {
+                    WebFontEvent::LoadStarted => Some(WebFontLoadEvent::Started),
+                    WebFontEvent::LoadFinished(loaded) => Some(WebFontLoadEvent::Finished(loaded)),
+                    WebFontEvent::Deferred | WebFontEvent::BlockPeriodEnded => None,
+                };
+                if let Some(load_event) = load_event {
+                    let msg = ConstellationControlMsg::FontFaceRuleLoad(pipeline_id, rule_id, load_event);
+                    let _ = script_chan.send(msg);
+                }
+                let _ = font_cache_sender.send(event);
+            }));
+            let web_font_id = font_cache_thread.add_web_font(font_face.family().clone(),
+                                                             effective_sources,
+                                                             descriptors,
+                                                             event_sender);
+            css_web_fonts.insert(rule_id, web_font_id);
+        })
+    }
+
     /// Advances the animation clock of the document.
     fn handle_advance_clock_ms<'a, 'b>(&mut self,
                                        how_many_ms: i32,
@@ -1001,6 +1183,7 @@ impl LayoutThread {
             if layout_root.base().restyle_damage.contains(ServoRestyleDamage::REPAINT) ||
                     rw_data.display_list.is_none() {
                 if reflow_goal.needs_display_list() {
+                    self.drawn_vector_images.lock().unwrap().clear();
                     let mut build_state =
                         sequential::build_display_list_for_subtree(layout_root, layout_context);
 
@@ -1029,10 +1212,25 @@ impl LayoutThread {
                         }
                     }
 
+                    if build_state.scroll_snap_info != *self.scroll_snap_info.borrow() {
+                        let scroll_snap_info = std::mem::replace(
+                            &mut build_state.scroll_snap_info,
+                            vec![],
+                        );
+                        *self.scroll_snap_info.borrow_mut() = scroll_snap_info.clone();
+                        let msg = ConstellationMsg::ScrollSnapInfo(self.id, scroll_snap_info);
+                        if let Err(e) = self.constellation_chan.send(msg) {
+                            warn!("Sending scroll snap points to constellation failed ({}).", e);
+                        }
+                    }
+
                     rw_data.indexable_text = std::mem::replace(
                         &mut build_state.indexable_text,
                         IndexableText::default());
                     rw_data.display_list = Some(Arc::new(build_state.to_display_list()));
+                    self.update_displayed_animated_images(rw_data.display_list.as_ref().unwrap());
+                    self.painted_masks.lock().unwrap()
+                        .retire_undisplayed(&mut self.retired_image_keys.lock().unwrap());
                 }
             }
 
@@ -1084,9 +1282,32 @@ impl LayoutThread {
                 true);
             txn.generate_frame();
             self.webrender_api.send_transaction(self.webrender_document, txn);
+            *self.sent_vector_images.borrow_mut() = self.drawn_vector_images.lock().unwrap().clone();
+            self.delete_retired_images();
         });
     }
 
+    /// Deletes the webrender images that the display list just sent no longer shows, and forgets
+    /// the animated images that it doesn't show, so that their images can be freed.
+    fn delete_retired_images(&self) {
+        let mut retired_image_keys = std_mem::replace(&mut *self.retired_image_keys.lock().unwrap(), vec![]);
+        self.animated_images.write().retain(|_, animated_image| {
+            // The animation starts over if the image is displayed again.
+            if !animated_image.displayed {
+                retired_image_keys.push(animated_image.key);
+            }
+            animated_image.displayed
+        });
+        if retired_image_keys.is_empty() {
+            return;
+        }
+        let mut txn = webrender_api::Transaction::new();
+        for key in retired_image_keys {
+            txn.delete_image(key);
+        }
+        self.webrender_api.update_resources(txn.resource_updates);
+    }
+
     /// The high-level routine that performs layout threads.
     fn handle_reflow<'a, 'b>(&mut self,
                              data: &mut ScriptReflowResult,
@@ -1148,6 +1369,9 @@ impl LayoutThread {
                         &QueryMsg::ElementInnerTextQuery(_) => {
                             rw_data.element_inner_text_response = String::new();
                         },
+                        &QueryMsg::PagesQuery(_) => {
+                            rw_data.pages_response = vec![];
+                        },
                     },
                     ReflowGoal::Full | ReflowGoal:: TickAnimations => {}
                 }
@@ -1163,8 +1387,6 @@ impl LayoutThread {
         let initial_viewport = data.window_size.initial_viewport;
         let device_pixel_ratio = data.window_size.device_pixel_ratio;
         let old_viewport_size = self.viewport_size;
-        let current_screen_size = Size2D::new(Au::from_f32_px(initial_viewport.width),
-                                              Au::from_f32_px(initial_viewport.height));
 
         // Calculate the actual viewport as per DEVICE-ADAPT § 6
         // If the entire flow tree is invalid, then it will be reflowed anyhow.
@@ -1179,8 +1401,26 @@ impl LayoutThread {
             ua_or_user: &ua_or_user_guard,
         };
 
+        // A pages query lays the document out for print media, the page area being the viewport.
+        self.page_area = match data.reflow_goal {
+            ReflowGoal::LayoutQuery(QueryMsg::PagesQuery(page_size), _) => {
+                Some(self.compute_page_area(page_size, &guards))
+            }
+            _ => None,
+        };
+        let (media_type, initial_viewport) = match self.page_area {
+            Some(page_area) => {
+                (MediaType::print(), TypedSize2D::new(page_area.size.width.to_f32_px(),
+                                                      page_area.size.height.to_f32_px()))
+            }
+            None => (MediaType::screen(), initial_viewport),
+        };
+        let current_screen_size = Size2D::new(Au::from_f32_px(initial_viewport.width),
+                                              Au::from_f32_px(initial_viewport.height));
+        let media_type_changed = self.stylist.device().media_type() != media_type;
+
         let had_used_viewport_units = self.stylist.device().used_viewport_units();
-        let device = Device::new(MediaType::screen(), initial_viewport, device_pixel_ratio);
+        let device = Device::new(media_type, initial_viewport, device_pixel_ratio);
         let sheet_origins_affected_by_device_change =
             self.stylist.set_device(device, &guards);
 
@@ -1235,7 +1475,13 @@ impl LayoutThread {
             }
         }
 
-        if viewport_size_changed {
+        if media_type_changed {
+            if let Some(mut data) = element.mutate_data() {
+                data.hint.insert(RestyleHint::restyle_subtree());
+            }
+        }
+
+        if viewport_size_changed || media_type_changed {
             if let Some(mut flow) = self.try_get_layout_root(element.as_node()) {
                 LayoutThread::reflow_all_nodes(FlowRef::deref_mut(&mut flow));
             }
@@ -1464,6 +1710,18 @@ impl LayoutThread {
                     rw_data.element_inner_text_response =
                         process_element_inner_text_query(node, &rw_data.indexable_text);
                 },
+                &QueryMsg::PagesQuery(page_size) => {
+                    let page_area = self.page_area.expect("No page area for a pages query?");
+                    let pages = paged::paginate(root_flow, context, page_size, page_area);
+                    rw_data.pages_response = pages.into_iter().map(|page| {
+                        let (_, _, display_list) =
+                            page.display_list.convert_to_webrender(self.id).finalize();
+                        LayoutPage {
+                            size: page.size,
+                            display_list: display_list,
+                        }
+                    }).collect();
+                },
             },
             ReflowGoal::Full | ReflowGoal::TickAnimations => {}
         }
@@ -1492,9 +1750,72 @@ impl LayoutThread {
 
     fn tick_all_animations<'a, 'b>(&mut self, possibly_locked_rw_data: &mut RwData<'a, 'b>) {
         let mut rw_data = possibly_locked_rw_data.lock();
+        self.tick_animated_images();
         self.tick_animations(&mut rw_data);
     }
 
+    /// Marks the animated images in a new display list as displayed, so that only they are
+    /// animated, and shows their current frame.
+    fn update_displayed_animated_images(&self, display_list: &DisplayList) {
+        {
+            let mut animated_images = self.animated_images.write();
+            if animated_images.is_empty() {
+                return;
+            }
+            let displayed_keys: FnvHashSet<_> = display_list.list.iter().filter_map(|item| {
+                match *item {
+                    DisplayItem::Image(ref item) => Some(item.id),
+                    _ => None,
+                }
+            }).collect();
+            for animated_image in animated_images.values_mut() {
+                animated_image.displayed = displayed_keys.contains(&animated_image.key);
+            }
+        }
+        self.tick_animated_images();
+    }
+
+    /// Shows the current frame of the displayed animated images, or their first frame while the
+    /// embedder asks for reduced motion.
+    fn tick_animated_images(&self) {
+        let now = self.timer.seconds();
+        let prefers_reduced_motion = self.prefers_reduced_motion.get();
+        let mut frame_changed = false;
+        let mut playing = false;
+        for animated_image in self.animated_images.write().values_mut() {
+            if !animated_image.displayed {
+                continue;
+            }
+            if prefers_reduced_motion {
+                frame_changed |= animated_image.stop(&*self.image_cache, now);
+            } else {
+                let (changed, still_playing) = animated_image.tick(&*self.image_cache, now);
+                frame_changed |= changed;
+                playing |= still_playing;
+            }
+        }
+
+        if frame_changed {
+            let mut txn = webrender_api::Transaction::new();
+            txn.generate_frame();
+            self.webrender_api.send_transaction(self.webrender_document, txn);
+        }
+
+        // Keep the compositor ticking animations while an animated image is playing.
+        if playing != self.animated_images_playing.get() {
+            self.animated_images_playing.set(playing);
+            let animation_state = if playing || !self.running_animations.read().is_empty() {
+                AnimationState::AnimationsPresent
+            } else {
+                AnimationState::NoAnimationsPresent
+            };
+            let msg = ConstellationMsg::ChangeRunningAnimationsState(self.id, animation_state);
+            if let Err(e) = self.constellation_chan.send(msg) {
+                warn!("Sending animation state to constellation failed ({}).", e);
+            }
+        }
+    }
+
     fn tick_animations(&mut self, rw_data: &mut LayoutThreadData) {
         if opts::get().relayout_event {
             println!("**** pipeline={}\tForDisplay\tSpecial\tAnimationTick", self.id);
@@ -1569,6 +1890,7 @@ impl LayoutThread {
                 &self.new_animations_receiver,
                 self.id,
                 &self.timer,
+                self.animated_images_playing.get(),
             );
         }
 
@@ -1667,6 +1989,22 @@ impl LayoutThread {
         self.generation.set(self.generation.get() + 1);
     }
 
+    /// Returns the page area of pages of the given size, inside the margins set by `@page` rules.
+    fn compute_page_area(&self, page_size: Size2D<Au>, guards: &StylesheetGuards) -> Rect<Au> {
+        let document_shared_lock = self.document_shared_lock.as_ref().unwrap();
+        let declarations = self.stylist.page_declarations(guards);
+        let style = self.stylist.compute_for_declarations::<ServoLayoutElement>(
+            guards,
+            ComputedValues::initial_values(),
+            ServoArc::new(document_shared_lock.wrap(declarations)));
+
+        let margin = style.get_margin();
+        paged::page_area(page_size, SideOffsets2D::new(margin.margin_top,
+                                                       margin.margin_right,
+                                                       margin.margin_bottom,
+                                                       margin.margin_left))
+    }
+
     fn reflow_all_nodes(flow: &mut Flow) {
         debug!("reflowing all nodes!");
         flow.mut_base()
@@ -172,6 +172,17 @@ impl CookieStorage {
         }
     }
 
+    /// Removes the cookies that would be sent to `url`, or only those named `name`.
+    pub fn remove_cookies_for_url(&mut self, url: &ServoUrl, name: Option<&str>) {
+        let domain = reg_host(url.host_str().unwrap_or(""));
+        if let Some(cookies) = self.cookies_map.get_mut(&domain) {
+            cookies.retain(|c| {
+                !c.appropriate_for_url(url, CookieSource::HTTP) ||
+                name.map_or(false, |name| c.cookie.name() != name)
+            });
+        }
+    }
+
     pub fn cookies_data_for_url<'a>(&'a mut self,
                                     url: &'a ServoUrl,
                                     source: CookieSource)
@@ -4,7 +4,8 @@
 
 use blob_loader::load_blob_sync;
 use data_loader::decode;
-use devtools_traits::DevtoolsControlMsg;
+use devtools_traits::{ConsoleMessage, DevtoolsControlMsg, LogLevel, ScriptToDevtoolsControlMsg};
+use embedder_traits::resources::{self, Resource};
 use fetch::cors_cache::CorsCache;
 use filemanager_thread::FileManager;
 use http_loader::{HttpState, determine_request_referrer, http_fetch};
@@ -18,10 +19,12 @@ use hyper::status::StatusCode;
 use ipc_channel::ipc::IpcReceiver;
 use mime_guess::guess_mime_type;
 use net_traits::{FetchTaskTarget, NetworkError, ReferrerPolicy};
-use net_traits::request::{CredentialsMode, Destination, Referrer, Request, RequestMode};
-use net_traits::request::{ResponseTainting, Origin, Window};
+use net_traits::request::{CredentialsMode, Destination, InsecureRequestsPolicy, Referrer, Request, RequestMode};
+use net_traits::request::{ResponseTainting, Origin, Window, is_mixed_content};
+use net_traits::request::upgrade_to_potentially_trustworthy_url;
+use net_traits::pub_domains::{reg_host, reg_suffix};
 use net_traits::response::{Response, ResponseBody, ResponseType};
-use servo_url::ServoUrl;
+use servo_url::{Host, ImmutableOrigin, ServoUrl};
 use std::borrow::Cow;
 use std::fmt;
 use std::fs::File;
@@ -147,13 +150,15 @@ pub fn main_fetch(request: &mut Request,
     // TODO: handle content security policy violations.
 
     // Step 4.
-    // TODO: handle upgrade to a potentially secure URL.
+    upgrade_request_to_potentially_trustworthy(request);
 
     // Step 5.
     if should_be_blocked_due_to_bad_port(&request.current_url()) {
         response = Some(Response::network_error(NetworkError::Internal("Request attempted on bad port".into())));
     }
-    // TODO: handle blocking as mixed content.
+    if should_be_blocked_as_mixed_content(request, context) {
+        response = Some(Response::network_error(NetworkError::Internal("Blocked as mixed content".into())));
+    }
     // TODO: handle blocking by content security policy.
 
     // Step 6
@@ -234,7 +239,8 @@ pub fn main_fetch(request: &mut Request,
         } else if request.use_cors_preflight ||
             (request.unsafe_request &&
                 (!is_cors_safelisted_method(&request.method) ||
-                request.headers.iter().any(|h| !is_cors_safelisted_request_header(&h)))) {
+                request.headers.iter().any(|h| !is_cors_safelisted_request_header(&h) &&
+                                               !is_fetch_metadata_header(&h)))) {
             // Substep 1.
             request.response_tainting = ResponseTainting::CorsTainting;
             // Substep 2.
@@ -315,7 +321,8 @@ pub fn main_fetch(request: &mut Request,
         }
 
         // Step 17.
-        // TODO: handle blocking as mixed content.
+        // Mixed content responses are blocked when main fetch runs again for
+        // every redirect, see `should_be_blocked_as_mixed_content`.
         // TODO: handle blocking by content security policy.
         let blocked_error_response;
         let internal_response =
@@ -464,6 +471,15 @@ fn scheme_fetch(request: &mut Request,
             response
         },
 
+        // The placeholder page shown in place of a crashed content process.
+        "about" if url.path() == "crash" => {
+            let mut response = Response::new(url);
+            response.headers.set(ContentType(mime!(Text / Html; Charset = Utf8)));
+            let page = resources::read_bytes(Resource::CrashHTML);
+            *response.body.lock().unwrap() = ResponseBody::Done(page);
+            response
+        },
+
         "http" | "https" => {
             http_fetch(request, cache, false, false, false, target, done_chan, context)
         },
@@ -558,6 +574,172 @@ pub fn is_cors_safelisted_method(m: &Method) -> bool {
     }
 }
 
+/// <https://w3c.github.io/webappsec-upgrade-insecure-requests/#upgrade-request>
+fn upgrade_request_to_potentially_trustworthy(request: &mut Request) {
+    // Step 1.
+    // TODO: upgrade top-level navigation requests to hosts in the upgrade
+    // navigation set of the client.
+    if request.is_navigation_request() && request.parent_origin.is_none() {
+        return;
+    }
+
+    // Step 2.
+    if request.insecure_requests_policy != InsecureRequestsPolicy::Upgrade {
+        return;
+    }
+
+    // Steps 3-4.
+    upgrade_to_potentially_trustworthy_url(request.current_url_mut());
+}
+
+/// <https://w3c.github.io/webappsec-mixed-content/#should-block-fetch>
+fn should_be_blocked_as_mixed_content(request: &Request, context: &FetchContext) -> bool {
+    // Steps 1-2. Top-level navigations are never mixed content, and nested
+    // ones are mixed content in their parent document.
+    let origin = if request.is_navigation_request() {
+        match request.parent_origin {
+            Some(ref origin) => origin,
+            None => return false,
+        }
+    } else {
+        match request.origin {
+            Origin::Origin(ref origin) => origin,
+            Origin::Client => return false,
+        }
+    };
+    let url = request.current_url();
+    if !is_mixed_content(origin, &url) {
+        return false;
+    }
+
+    // Step 3.
+    let blocked = !request.destination.is_optionally_blockable();
+    let (message, level) = if blocked {
+        (format!("Blocked loading mixed active content \"{}\"", url), LogLevel::Error)
+    } else {
+        (format!("Loading mixed (insecure) display content \"{}\"", url), LogLevel::Warn)
+    };
+    warn!("{}", message);
+    if let (Some(devtools_chan), Some(pipeline_id)) = (context.devtools_chan.as_ref(), request.pipeline_id) {
+        let console_message = ConsoleMessage {
+            message: message,
+            logLevel: level,
+            filename: url.into_string(),
+            lineNumber: 0,
+            columnNumber: 0,
+        };
+        let msg = ScriptToDevtoolsControlMsg::ConsoleAPI(pipeline_id, console_message, None);
+        let _ = devtools_chan.send(DevtoolsControlMsg::FromScript(msg));
+    }
+    blocked
+}
+
+const FETCH_METADATA_HEADERS: [&'static str; 4] = [
+    "Sec-Fetch-Dest",
+    "Sec-Fetch-Mode",
+    "Sec-Fetch-Site",
+    "Sec-Fetch-User",
+];
+
+/// Whether `h` is a Fetch Metadata request header. They are only set on the outgoing request,
+/// once CORS has been decided, and never make a request need a CORS preflight.
+pub fn is_fetch_metadata_header(h: &HeaderView) -> bool {
+    FETCH_METADATA_HEADERS.iter().any(|name| h.name().eq_ignore_ascii_case(name))
+}
+
+/// <https://w3c.github.io/webappsec-fetch-metadata/#abstract-opdef-append-the-fetch-metadata-headers-for-a-request>
+pub fn append_fetch_metadata_headers(request: &mut Request) {
+    // Headers appended before a redirect must not leak to the new URL.
+    for name in &FETCH_METADATA_HEADERS {
+        request.headers.remove_raw(name);
+    }
+
+    // Step 1.
+    if !request.current_url().is_potentially_trustworthy() {
+        return;
+    }
+
+    // Step 2.
+    let dest = match request.destination {
+        Destination::None => "empty",
+        Destination::Audio => "audio",
+        Destination::Document => "document",
+        Destination::Embed => "embed",
+        Destination::Font => "font",
+        Destination::Image => "image",
+        Destination::Manifest => "manifest",
+        Destination::Object => "object",
+        Destination::Report => "report",
+        Destination::Script => "script",
+        Destination::ServiceWorker => "serviceworker",
+        Destination::SharedWorker => "sharedworker",
+        Destination::Style => "style",
+        Destination::Track => "track",
+        Destination::Video => "video",
+        Destination::Worker => "worker",
+        Destination::Xslt => "xslt",
+    };
+    request.headers.set_raw("Sec-Fetch-Dest", vec![dest.as_bytes().to_vec()]);
+
+    // Step 3.
+    let mode = match request.mode {
+        RequestMode::Navigate => "navigate",
+        RequestMode::SameOrigin => "same-origin",
+        RequestMode::NoCors => "no-cors",
+        RequestMode::CorsMode => "cors",
+        RequestMode::WebSocket { .. } => "websocket",
+    };
+    request.headers.set_raw("Sec-Fetch-Mode", vec![mode.as_bytes().to_vec()]);
+
+    // Step 4.
+    let site = fetch_metadata_site(request);
+    request.headers.set_raw("Sec-Fetch-Site", vec![site.as_bytes().to_vec()]);
+
+    // Step 5.
+    if request.is_navigation_request() && request.user_initiated {
+        request.headers.set_raw("Sec-Fetch-User", vec![b"?1".to_vec()]);
+    }
+}
+
+/// <https://w3c.github.io/webappsec-fetch-metadata/#abstract-opdef-set-site>
+fn fetch_metadata_site(request: &Request) -> &'static str {
+    // Step 2.
+    if request.is_navigation_request() && request.user_initiated {
+        return "none";
+    }
+
+    let origin = match request.origin {
+        Origin::Origin(ref origin) => origin,
+        Origin::Client => return "cross-site",
+    };
+
+    // Steps 1, 3.
+    let mut site = "same-origin";
+    for url in &request.url_list {
+        if url.origin() == *origin {
+            continue;
+        }
+        site = "same-site";
+        let url_site = reg_host(url);
+        let same_site = origin.scheme() == Some(url.scheme()) &&
+            url_site.is_some() && url_site == reg_origin_host(origin);
+        if !same_site {
+            return "cross-site";
+        }
+    }
+    site
+}
+
+/// The registrable domain of an origin, as computed by `reg_host` for URLs.
+fn reg_origin_host(origin: &ImmutableOrigin) -> Option<Host> {
+    match *origin {
+        ImmutableOrigin::Tuple(_, Host::Domain(ref domain), _) =>
+            Some(Host::Domain(String::from(reg_suffix(domain)))),
+        ImmutableOrigin::Tuple(_, ref ip, _) => Some(ip.clone()),
+        ImmutableOrigin::Opaque(_) => None,
+    }
+}
+
 fn is_null_body_status(status: &Option<StatusCode>) -> bool {
     match *status {
         Some(status) => match status {
@@ -113,6 +113,9 @@ impl FileManager {
                     store.select_files(filter, sender, origin, opt_test_paths, embedder);
                 }).expect("Thread spawning failed");
             }
+            FileManagerThreadMsg::AddDroppedFiles(paths, sender, origin) => {
+                let _ = sender.send(self.store.add_dropped_files(&paths, &origin));
+            }
             FileManagerThreadMsg::ReadFile(sender, id, check_url_validity, origin) => {
                 self.read_file(sender, id, check_url_validity, origin);
             }
@@ -304,6 +307,14 @@ impl FileManagerStore {
         }
     }
 
+    fn add_dropped_files(&self, paths: &[PathBuf], origin: &str) -> Vec<SelectedFile> {
+        paths.iter().filter_map(|path| {
+            self.create_entry(path, origin).map_err(|e| {
+                warn!("Failed to add dropped file {} ({:?}).", path.display(), e);
+            }).ok()
+        }).collect()
+    }
+
     fn create_entry(&self, file_path: &Path, origin: &str) -> Result<SelectedFile, FileManagerThreadError> {
         use net_traits::filemanager_thread::FileManagerThreadError::FileSystemError;
 
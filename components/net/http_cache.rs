@@ -690,6 +690,28 @@ impl HttpCache {
         self.invalidate_for_url(&request.url());
     }
 
+    /// Returns the URLs of the responses cached for the origin of `url`, with their statuses.
+    pub fn entries_for_origin(&self, url: &ServoUrl) -> Vec<(ServoUrl, Option<u16>)> {
+        let origin = url.origin();
+        self.entries.iter()
+            .filter(|&(key, _)| key.url.origin() == origin)
+            .flat_map(|(key, resources)| resources.iter().map(move |resource| {
+                (key.url(), resource.data.raw_status.as_ref().map(|&(code, _)| code))
+            }))
+            .collect()
+    }
+
+    /// Removes the responses cached for the origin of `url`.
+    pub fn remove_entries_for_origin(&mut self, url: &ServoUrl) {
+        let origin = url.origin();
+        self.entries.retain(|key, _| key.url.origin() != origin);
+    }
+
+    /// Removes the responses cached for `url`.
+    pub fn remove_entries_for_url(&mut self, url: &ServoUrl) {
+        self.entries.remove(&CacheKey::from_servo_url(url));
+    }
+
     /// Storing Responses in Caches.
     /// <https://tools.ietf.org/html/rfc7234#section-3>
     pub fn store(&mut self, request: &Request, response: &Response) {
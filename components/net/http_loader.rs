@@ -10,7 +10,8 @@ use devtools_traits::{ChromeToDevtoolsControlMsg, DevtoolsControlMsg, HttpReques
 use devtools_traits::{HttpResponse as DevtoolsHttpResponse, NetworkEvent};
 use fetch::cors_cache::CorsCache;
 use fetch::methods::{Data, DoneChannel, FetchContext, Target};
-use fetch::methods::{is_cors_safelisted_request_header, is_cors_safelisted_method, main_fetch};
+use fetch::methods::{append_fetch_metadata_headers, is_cors_safelisted_request_header};
+use fetch::methods::{is_cors_safelisted_method, is_fetch_metadata_header, main_fetch};
 use flate2::read::{DeflateDecoder, GzDecoder};
 use hsts::HstsList;
 use http_cache::HttpCache;
@@ -25,7 +26,7 @@ use hyper::header::{Authorization, Basic, CacheControl, CacheDirective};
 use hyper::header::{ContentEncoding, ContentLength, Encoding, Header, Headers};
 use hyper::header::{Host, HttpDate, Origin as HyperOrigin, IfMatch, IfRange};
 use hyper::header::{IfUnmodifiedSince, IfModifiedSince, IfNoneMatch, Location};
-use hyper::header::{Pragma, Quality, QualityItem, Referer, SetCookie};
+use hyper::header::{Pragma, Quality, QualityItem, Referer, ReferrerPolicy as ReferrerPolicyHeader, SetCookie};
 use hyper::header::{UserAgent, q, qitem};
 use hyper::method::Method;
 use hyper::status::StatusCode;
@@ -544,7 +545,8 @@ pub fn http_fetch(request: &mut Request,
             let method_mismatch = !method_cache_match && (!is_cors_safelisted_method(&request.method) ||
                                                           request.use_cors_preflight);
             let header_mismatch = request.headers.iter().any(|view|
-                !cache.match_header(&*request, view.name()) && !is_cors_safelisted_request_header(&view)
+                !cache.match_header(&*request, view.name()) && !is_cors_safelisted_request_header(&view) &&
+                    !is_fetch_metadata_header(&view)
             );
 
             // Sub-substep 1
@@ -691,7 +693,7 @@ pub fn http_redirect_fetch(request: &mut Request,
     request.url_list.push(location_url);
 
     // Step 14
-    // TODO implement referrer policy
+    set_request_referrer_policy_on_redirect(request, response.actual_response());
 
     // Step 15
     let recursive_flag = request.redirect_mode != RedirectMode::Manual;
@@ -699,6 +701,14 @@ pub fn http_redirect_fetch(request: &mut Request,
     main_fetch(request, cache, cors_flag, recursive_flag, target, done_chan, context)
 }
 
+/// <https://w3c.github.io/webappsec-referrer-policy/#set-requests-referrer-policy-on-redirect>
+fn set_request_referrer_policy_on_redirect(request: &mut Request, response: &Response) {
+    // Steps 1-2.
+    if let Some(policy) = response.headers.get::<ReferrerPolicyHeader>() {
+        request.referrer_policy = Some(ReferrerPolicy::from(policy));
+    }
+}
+
 fn try_immutable_origin_to_hyper_origin(url_origin: &ImmutableOrigin) -> Option<HyperOrigin> {
     match *url_origin {
         // TODO (servo/servo#15569) Set "Origin: null" when hyper supports it
@@ -787,6 +797,10 @@ fn http_network_or_cache_fetch(request: &mut Request,
         http_request.headers.set(UserAgent(user_agent));
     }
 
+    // Fetch Metadata request headers, on the outgoing request only, so that they don't take
+    // part in deciding whether it needs a CORS preflight.
+    append_fetch_metadata_headers(http_request);
+
     match http_request.cache_mode {
         // Step 13
         CacheMode::Default if is_no_store_cache(&http_request.headers) => {
@@ -1170,9 +1184,12 @@ fn http_network_fetch(request: &Request,
 
         // Substep 2
 
-    // TODO Determine if response was retrieved over HTTPS
     // TODO Servo needs to decide what ciphers are to be treated as "deprecated"
-    response.https_state = HttpsState::None;
+    response.https_state = if url.is_secure_scheme() {
+        HttpsState::Modern
+    } else {
+        HttpsState::None
+    };
 
     // TODO Read request
 
@@ -1242,7 +1259,7 @@ fn cors_preflight_fetch(request: &Request,
     // Step 3
     let mut headers = request.headers
         .iter()
-        .filter(|view| !is_cors_safelisted_request_header(view))
+        .filter(|view| !is_cors_safelisted_request_header(view) && !is_fetch_metadata_header(view))
         .map(|view| UniCase(view.name().to_ascii_lowercase().to_owned()))
         .collect::<Vec<UniCase<String>>>();
     headers.sort();
@@ -1313,7 +1330,8 @@ fn cors_preflight_fetch(request: &Request,
         debug!("CORS check: Allowed headers: {:?}, current headers: {:?}", header_names, request.headers);
         let set: HashSet<&UniCase<String>> = HashSet::from_iter(header_names.iter());
         if request.headers.iter().any(
-            |ref hv| !set.contains(&UniCase(hv.name().to_owned())) && !is_cors_safelisted_request_header(hv)) {
+            |ref hv| !set.contains(&UniCase(hv.name().to_owned())) && !is_cors_safelisted_request_header(hv) &&
+                     !is_fetch_metadata_header(hv)) {
             return Response::network_error(NetworkError::Internal("CORS headers check failed".into()));
         }
 
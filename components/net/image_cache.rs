@@ -4,9 +4,10 @@
 
 use embedder_traits::resources::{self, Resource};
 use immeta::load_from_buf;
-use net_traits::{FetchMetadata, FetchResponseMsg, NetworkError};
-use net_traits::image::base::{Image, ImageMetadata, PixelFormat, load_from_memory};
-use net_traits::image_cache::{CanRequestImages, ImageCache, ImageResponder};
+use net_traits::{FetchMetadata, FetchResponseMsg, FilteredMetadata, NetworkError};
+use net_traits::image::base::{Image, ImageMetadata, PixelFormat, load_from_memory, rasterize_svg};
+use net_traits::image::svg::{MAX_VECTOR_IMAGE_SIZE, SvgImage, is_svg};
+use net_traits::image_cache::{CanRequestImages, ImageCache, ImageResponder, IntrinsicDimensions};
 use net_traits::image_cache::{ImageOrMetadataAvailable, ImageResponse, ImageState};
 use net_traits::image_cache::{PendingImageId, UsePlaceholder};
 use servo_url::ServoUrl;
@@ -33,10 +34,20 @@ use webrender_api;
 // ======================================================================
 
 fn decode_bytes_sync(key: LoadKey, bytes: &[u8]) -> DecoderMsg {
-    let image = load_from_memory(bytes);
+    // Vector images are kept around after decoding, so that they can be
+    // rasterized again at the size they are drawn at.
+    let vector_image = if is_svg(bytes) { SvgImage::parse(bytes) } else { None };
+    let image = match vector_image {
+        Some(ref vector_image) => {
+            let (width, height) = vector_image.default_size();
+            rasterize_svg(vector_image, width, height)
+        }
+        None => load_from_memory(bytes),
+    };
     DecoderMsg {
         key: key,
-        image: image
+        image: image,
+        vector_image: vector_image,
     }
 }
 
@@ -48,14 +59,50 @@ fn get_placeholder_image(webrender_api: &webrender_api::RenderApi, data: &[u8])
 
 fn set_webrender_image_key(webrender_api: &webrender_api::RenderApi, image: &mut Image) {
     if image.id.is_some() { return; }
+    let (descriptor, data) = get_webrender_image_data(image, &*image.bytes);
+    let image_key = webrender_api.generate_image_key();
+    let mut txn = webrender_api::Transaction::new();
+    txn.add_image(image_key, descriptor, data, None);
+    webrender_api.update_resources(txn.resource_updates);
+    image.id = Some(image_key);
+}
+
+fn add_webrender_image_animation(webrender_api: &webrender_api::RenderApi, image: &Image)
+                                 -> Option<webrender_api::ImageKey> {
+    let animation = image.animation.as_ref()?;
+    let (descriptor, data) = get_webrender_image_data(image, animation.frame(0));
+    let image_key = webrender_api.generate_image_key();
+    let mut txn = webrender_api::Transaction::new();
+    txn.add_image(image_key, descriptor, data, None);
+    webrender_api.update_resources(txn.resource_updates);
+    Some(image_key)
+}
+
+fn set_webrender_image_frame(webrender_api: &webrender_api::RenderApi,
+                             image: &Image,
+                             image_key: webrender_api::ImageKey,
+                             frame: usize) {
+    let animation = match image.animation {
+        Some(ref animation) => animation,
+        None => return,
+    };
+    let (descriptor, data) = get_webrender_image_data(image, animation.frame(frame));
+    let mut txn = webrender_api::Transaction::new();
+    txn.update_image(image_key, descriptor, data, None);
+    webrender_api.update_resources(txn.resource_updates);
+}
+
+/// Returns the webrender descriptor and data of some pixels of an image.
+fn get_webrender_image_data(image: &Image, pixels: &[u8])
+                            -> (webrender_api::ImageDescriptor, webrender_api::ImageData) {
     let mut bytes = Vec::new();
-    let is_opaque = match image.format {
+    let (format, is_opaque) = match image.format {
         PixelFormat::BGRA8 => {
-            bytes.extend_from_slice(&*image.bytes);
-            premultiply(bytes.as_mut_slice())
+            bytes.extend_from_slice(pixels);
+            (webrender_api::ImageFormat::BGRA8, premultiply(bytes.as_mut_slice()))
         }
         PixelFormat::RGB8 => {
-            for bgr in image.bytes.chunks(3) {
+            for bgr in pixels.chunks(3) {
                 bytes.extend_from_slice(&[
                     bgr[2],
                     bgr[1],
@@ -64,26 +111,26 @@ fn set_webrender_image_key(webrender_api: &webrender_api::RenderApi, image: &mut
                 ]);
             }
 
-            true
+            (webrender_api::ImageFormat::BGRA8, true)
+        }
+        // Single channel images are used as clip masks.
+        PixelFormat::K8 => {
+            bytes.extend_from_slice(pixels);
+            (webrender_api::ImageFormat::R8, false)
         }
-        PixelFormat::K8 | PixelFormat::KA8 => {
+        PixelFormat::KA8 => {
             panic!("Not support by webrender yet");
         }
     };
     let descriptor = webrender_api::ImageDescriptor {
         size: webrender_api::DeviceUintSize::new(image.width, image.height),
         stride: None,
-        format: webrender_api::ImageFormat::BGRA8,
+        format: format,
         offset: 0,
         is_opaque,
         allow_mipmaps: true,
     };
-    let data = webrender_api::ImageData::new(bytes);
-    let image_key = webrender_api.generate_image_key();
-    let mut txn = webrender_api::Transaction::new();
-    txn.add_image(image_key, descriptor, data, None);
-    webrender_api.update_resources(txn.resource_updates);
-    image.id = Some(image_key);
+    (descriptor, webrender_api::ImageData::new(bytes))
 }
 
 // Returns true if the image was found to be
@@ -194,13 +241,39 @@ enum CacheResult<'a> {
 struct CompletedLoad {
     image_response: ImageResponse,
     id: PendingImageId,
+    vector_image: Option<VectorImage>,
+    cors_same_origin: bool,
 }
 
 impl CompletedLoad {
-    fn new(image_response: ImageResponse, id: PendingImageId) -> CompletedLoad {
+    fn new(image_response: ImageResponse,
+           id: PendingImageId,
+           vector_image: Option<VectorImage>,
+           cors_same_origin: bool)
+           -> CompletedLoad {
         CompletedLoad {
             image_response: image_response,
             id: id,
+            vector_image: vector_image,
+            cors_same_origin: cors_same_origin,
+        }
+    }
+}
+
+/// The number of rasterizations of a vector image that are kept at once.
+const MAX_VECTOR_IMAGE_RASTERIZATIONS: usize = 8;
+
+/// A decoded vector image, along with the sizes it has been rasterized at so far.
+struct VectorImage {
+    image: SvgImage,
+    rasterizations: HashMap<(u32, u32), Arc<Image>>,
+}
+
+impl VectorImage {
+    fn new(image: SvgImage) -> VectorImage {
+        VectorImage {
+            image: image,
+            rasterizations: HashMap::new(),
         }
     }
 }
@@ -209,6 +282,7 @@ impl CompletedLoad {
 struct DecoderMsg {
     key: LoadKey,
     image: Option<Image>,
+    vector_image: Option<SvgImage>,
 }
 
 enum ImageBytes {
@@ -265,7 +339,7 @@ impl LoadKeyGenerator {
 }
 
 enum LoadResult {
-    Loaded(Image),
+    Loaded(Image, Option<SvgImage>),
     PlaceholderLoaded(Arc<Image>),
     None
 }
@@ -289,6 +363,9 @@ struct PendingLoad {
     url: ServoUrl,
 
     final_url: Option<ServoUrl>,
+
+    // Whether the response is CORS-same-origin, rather than opaque.
+    cors_same_origin: bool,
 }
 
 impl PendingLoad {
@@ -300,6 +377,7 @@ impl PendingLoad {
             listeners: vec!(),
             url: url,
             final_url: None,
+            cors_same_origin: false,
         }
     }
 
@@ -324,6 +402,9 @@ struct ImageCacheStore {
     // The URL used for the placeholder image
     placeholder_url: ServoUrl,
 
+    // Rasterizations of vector images that were evicted, but may still be drawn.
+    evicted_rasterizations: Vec<Arc<Image>>,
+
     // Webrender API instance.
     webrender_api: webrender_api::RenderApi,
 }
@@ -337,19 +418,27 @@ impl ImageCacheStore {
         };
 
         match load_result {
-            LoadResult::Loaded(ref mut image) => set_webrender_image_key(&self.webrender_api, image),
+            LoadResult::Loaded(ref mut image, _) => set_webrender_image_key(&self.webrender_api, image),
             LoadResult::PlaceholderLoaded(..) | LoadResult::None => {}
         }
 
         let url = pending_load.final_url.clone();
+        let mut vector_image = None;
         let image_response = match load_result {
-            LoadResult::Loaded(image) => ImageResponse::Loaded(Arc::new(image), url.unwrap()),
+            LoadResult::Loaded(image, svg_image) => {
+                vector_image = svg_image.map(VectorImage::new);
+                ImageResponse::Loaded(Arc::new(image), url.unwrap())
+            }
             LoadResult::PlaceholderLoaded(image) =>
                 ImageResponse::PlaceholderLoaded(image, self.placeholder_url.clone()),
             LoadResult::None => ImageResponse::None,
         };
 
-        let completed_load = CompletedLoad::new(image_response.clone(), key);
+        let cors_same_origin = match image_response {
+            ImageResponse::Loaded(..) => pending_load.cors_same_origin,
+            _ => false,
+        };
+        let completed_load = CompletedLoad::new(image_response.clone(), key, vector_image, cors_same_origin);
         self.completed_loads.insert(pending_load.url.into(), completed_load);
 
         for listener in pending_load.listeners {
@@ -383,10 +472,54 @@ impl ImageCacheStore {
     fn handle_decoder(&mut self, msg: DecoderMsg) {
         let image = match msg.image {
             None => LoadResult::None,
-            Some(image) => LoadResult::Loaded(image),
+            Some(image) => LoadResult::Loaded(image, msg.vector_image),
         };
         self.complete_load(msg.key, image);
     }
+
+    /// Return a rasterization of the completed vector image at the given url,
+    /// rasterizing it at the given size if that has not been done yet.
+    fn get_vector_image_at_size(&mut self, url: &ServoUrl, width: u32, height: u32) -> Option<Arc<Image>> {
+        let width = width.min(MAX_VECTOR_IMAGE_SIZE);
+        let height = height.min(MAX_VECTOR_IMAGE_SIZE);
+        self.delete_unused_rasterizations();
+        let webrender_api = &self.webrender_api;
+        let vector_image = self.completed_loads.get_mut(url)?.vector_image.as_mut()?;
+        if let Some(image) = vector_image.rasterizations.get(&(width, height)) {
+            return Some(image.clone());
+        }
+
+        let mut image = rasterize_svg(&vector_image.image, width, height)?;
+        set_webrender_image_key(webrender_api, &mut image);
+        let image = Arc::new(image);
+        if vector_image.rasterizations.len() >= MAX_VECTOR_IMAGE_RASTERIZATIONS {
+            self.evicted_rasterizations.extend(vector_image.rasterizations.drain().map(|(_, image)| image));
+        }
+        vector_image.rasterizations.insert((width, height), image.clone());
+        Some(image)
+    }
+
+    /// Delete the webrender images of the evicted rasterizations that nothing refers to
+    /// anymore. Layout holds the rasterizations shown by the display lists it has built and
+    /// sent, so these are no longer on screen.
+    fn delete_unused_rasterizations(&mut self) {
+        let mut unused_image_keys = vec![];
+        self.evicted_rasterizations.retain(|image| {
+            if Arc::strong_count(image) > 1 {
+                return true;
+            }
+            unused_image_keys.extend(image.id);
+            false
+        });
+        if unused_image_keys.is_empty() {
+            return;
+        }
+        let mut txn = webrender_api::Transaction::new();
+        for image_key in unused_image_keys {
+            txn.delete_image(image_key);
+        }
+        self.webrender_api.update_resources(txn.resource_updates);
+    }
 }
 
 pub struct ImageCacheImpl {
@@ -405,6 +538,7 @@ impl ImageCache for ImageCacheImpl {
                 completed_loads: HashMap::new(),
                 placeholder_image: get_placeholder_image(&webrender_api, &rippy_data).ok(),
                 placeholder_url: ServoUrl::parse("chrome://resources/rippy.png").unwrap(),
+                evicted_rasterizations: vec![],
                 webrender_api: webrender_api,
             }))
         }
@@ -490,6 +624,12 @@ impl ImageCache for ImageCacheImpl {
             (FetchResponseMsg::ProcessResponse(response), _) => {
                 let mut store = self.store.lock().unwrap();
                 let pending_load = store.pending_loads.get_by_key_mut(&id).unwrap();
+                pending_load.cors_same_origin = match response {
+                    Ok(FetchMetadata::Filtered { filtered: FilteredMetadata::Opaque, .. }) |
+                    Ok(FetchMetadata::Filtered { filtered: FilteredMetadata::OpaqueRedirect, .. }) |
+                    Err(_) => false,
+                    Ok(_) => true,
+                };
                 let metadata = match response {
                     Ok(meta) => {
                         Some(match meta {
@@ -560,4 +700,39 @@ impl ImageCache for ImageCacheImpl {
     fn set_webrender_image_key(&self, image: &mut Image) {
         set_webrender_image_key(&self.store.lock().unwrap().webrender_api, image);
     }
+
+    /// Add a new webrender image showing the first frame of an animated image.
+    fn add_webrender_image_animation(&self, image: &Image) -> Option<webrender_api::ImageKey> {
+        add_webrender_image_animation(&self.store.lock().unwrap().webrender_api, image)
+    }
+
+    /// Show the frame at the given index of an animated image in one of its webrender images.
+    fn set_webrender_image_frame(&self, image: &Image, key: webrender_api::ImageKey, frame: usize) {
+        set_webrender_image_frame(&self.store.lock().unwrap().webrender_api, image, key, frame);
+    }
+
+    /// Return the image for the given URL rasterized at the given size in pixels, if it is a
+    /// loaded vector image.
+    fn get_image_at_size(&self, url: &ServoUrl, width: u32, height: u32) -> Option<Arc<Image>> {
+        self.store.lock().unwrap().get_vector_image_at_size(url, width, height)
+    }
+
+    /// Return the intrinsic dimensions of the image for the given URL, if it is a loaded vector
+    /// image.
+    fn get_intrinsic_dimensions(&self, url: &ServoUrl) -> Option<IntrinsicDimensions> {
+        let store = self.store.lock().unwrap();
+        let vector_image = store.completed_loads.get(url)?.vector_image.as_ref()?;
+        let (width, height) = vector_image.image.intrinsic_size();
+        Some(IntrinsicDimensions {
+            width: width,
+            height: height,
+            ratio: vector_image.image.intrinsic_ratio(),
+        })
+    }
+
+    /// Return whether the loaded image for the given URL came from a CORS-same-origin response.
+    fn is_cors_same_origin(&self, url: &ServoUrl) -> bool {
+        let store = self.store.lock().unwrap();
+        store.completed_loads.get(url).map_or(false, |load| load.cors_same_origin)
+    }
 }
@@ -248,6 +248,23 @@ impl ResourceChannelManager {
                 let cookies = cookie_jar.cookies_data_for_url(&url, source).map(Serde).collect();
                 consumer.send(cookies).unwrap();
             }
+            CoreResourceMsg::DeleteCookies(url, name) => {
+                let mut cookie_jar = http_state.cookie_jar.write().unwrap();
+                cookie_jar.remove_cookies_for_url(&url, name.as_ref().map(|name| &**name));
+            }
+            CoreResourceMsg::GetCacheEntries(url, consumer) => {
+                let http_cache = http_state.http_cache.read().unwrap();
+                consumer.send(http_cache.entries_for_origin(&url)).unwrap();
+            }
+            CoreResourceMsg::DeleteCacheEntries(url, entry) => {
+                let mut http_cache = http_state.http_cache.write().unwrap();
+                match entry {
+                    Some(ref entry) if entry.origin() == url.origin() =>
+                        http_cache.remove_entries_for_url(entry),
+                    Some(_) => {},
+                    None => http_cache.remove_entries_for_origin(&url),
+                }
+            }
             CoreResourceMsg::GetHistoryState(history_state_id, consumer) => {
                 let history_states = http_state.history_states.read().unwrap();
                 consumer.send(history_states.get(&history_state_id).cloned()).unwrap();
@@ -411,3 +411,20 @@ fn test_cookie_eviction_all_nonsecure_new_nonsecure() {
                                  &vec, "https://home.example.org:8888/cookie-parser-result?0001");
     assert_eq!(&r, "extra2=bar; extra3=bar; extra4=bar; extra5=bar; foo=bar");
 }
+
+#[test]
+fn test_remove_cookies_for_url() {
+    let mut storage = CookieStorage::new(5);
+    let url = ServoUrl::parse("http://home.example.org:8888/cookie-parser?0001").unwrap();
+    add_cookie_to_storage(&mut storage, &url, "foo=bar");
+    add_cookie_to_storage(&mut storage, &url, "foo2=bar");
+    add_cookie_to_storage(&mut storage, &url, "foo3=bar; Path=/other");
+
+    storage.remove_cookies_for_url(&url, Some("foo"));
+    assert_eq!(storage.cookies_for_url(&url, CookieSource::HTTP).unwrap(), "foo2=bar");
+
+    storage.remove_cookies_for_url(&url, None);
+    assert!(storage.cookies_for_url(&url, CookieSource::HTTP).is_none());
+    let other_url = ServoUrl::parse("http://home.example.org:8888/other").unwrap();
+    assert_eq!(storage.cookies_for_url(&other_url, CookieSource::HTTP).unwrap(), "foo3=bar");
+}
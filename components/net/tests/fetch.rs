@@ -6,6 +6,7 @@ use {DEFAULT_USER_AGENT, new_fetch_context, create_embedder_proxy, fetch, make_s
 use devtools_traits::DevtoolsControlMsg;
 use devtools_traits::HttpRequest as DevtoolsHttpRequest;
 use devtools_traits::HttpResponse as DevtoolsHttpResponse;
+use embedder_traits::resources::{self, Resource};
 use fetch_with_context;
 use fetch_with_cors_cache;
 use http_loader::{expect_devtools_http_request, expect_devtools_http_response};
@@ -32,7 +33,8 @@ use net::test::HttpState;
 use net_traits::IncludeSubdomains;
 use net_traits::NetworkError;
 use net_traits::ReferrerPolicy;
-use net_traits::request::{Destination, Origin, RedirectMode, Referrer, Request, RequestMode};
+use net_traits::request::{Destination, InsecureRequestsPolicy, Origin, RedirectMode, Referrer, Request};
+use net_traits::request::RequestMode;
 use net_traits::response::{CacheState, Response, ResponseBody, ResponseType};
 use servo_url::{ImmutableOrigin, ServoUrl};
 use std::fs::File;
@@ -113,6 +115,18 @@ fn test_fetch_aboutblank() {
     assert_eq!(*fetch_response.body.lock().unwrap(), ResponseBody::Done(vec![]));
 }
 
+#[test]
+fn test_fetch_about_crash() {
+    let url = ServoUrl::parse("about:crash").unwrap();
+    let origin = Origin::Origin(url.origin());
+    let mut request = Request::new(url, Some(origin), None);
+    request.referrer = Referrer::NoReferrer;
+    let fetch_response = fetch(&mut request, None);
+    assert!(!fetch_response.is_network_error());
+    assert_eq!(*fetch_response.body.lock().unwrap(),
+               ResponseBody::Done(resources::read_bytes(Resource::CrashHTML)));
+}
+
 #[test]
 fn test_fetch_blob() {
     use ipc_channel::ipc;
@@ -561,6 +575,156 @@ fn test_fetch_with_hsts() {
                "https");
 }
 
+#[test]
+fn test_fetch_upgrades_insecure_requests() {
+    static MESSAGE: &'static [u8] = b"";
+    let handler = move |_: HyperRequest, response: HyperResponse| {
+        response.send(MESSAGE).unwrap();
+    };
+
+    let cert_path = Path::new("../../resources/self_signed_certificate_for_testing.crt").canonicalize().unwrap();
+    let key_path = Path::new("../../resources/privatekey_for_testing.key").canonicalize().unwrap();
+
+    let ssl = hyper_openssl::OpensslServer::from_files(key_path, cert_path.clone())
+        .unwrap();
+
+    let mut server = Server::https("0.0.0.0:0", ssl).unwrap().handle_threads(handler, 1).unwrap();
+
+    let mut ca_content = String::new();
+    File::open(cert_path).unwrap().read_to_string(&mut ca_content).unwrap();
+    let ssl_client = create_ssl_client(&ca_content);
+
+    let context = FetchContext {
+        state: Arc::new(HttpState::new(ssl_client)),
+        user_agent: DEFAULT_USER_AGENT.into(),
+        devtools_chan: None,
+        filemanager: FileManager::new(create_embedder_proxy()),
+        cancellation_listener: Arc::new(Mutex::new(CancellationListener::new(None))),
+    };
+
+    let url = ServoUrl::parse(&format!("http://localhost:{}/image.png", server.socket.port())).unwrap();
+    let client_url = ServoUrl::parse(&format!("https://localhost:{}", server.socket.port())).unwrap();
+    let origin = Origin::Origin(client_url.origin());
+    let mut request = Request::new(url, Some(origin), None);
+    request.referrer = Referrer::NoReferrer;
+    request.destination = Destination::Image;
+    request.insecure_requests_policy = InsecureRequestsPolicy::Upgrade;
+    request.local_urls_only = false;
+    let response = fetch_with_context(&mut request, &context);
+    let _ = server.close();
+
+    assert!(!response.is_network_error());
+    assert_eq!(request.current_url().scheme(), "https");
+    assert_eq!(response.internal_response.unwrap().url().unwrap().scheme(), "https");
+}
+
+#[test]
+fn test_fetch_sends_fetch_metadata_headers() {
+    static MESSAGE: &'static [u8] = b"";
+    let handler = move |request: HyperRequest, response: HyperResponse| {
+        assert_eq!(request.headers.get_raw("Sec-Fetch-Dest").unwrap(), &[b"image".to_vec()]);
+        assert_eq!(request.headers.get_raw("Sec-Fetch-Mode").unwrap(), &[b"no-cors".to_vec()]);
+        assert_eq!(request.headers.get_raw("Sec-Fetch-Site").unwrap(), &[b"same-origin".to_vec()]);
+        assert!(request.headers.get_raw("Sec-Fetch-User").is_none());
+        response.send(MESSAGE).unwrap();
+    };
+    let (mut server, url) = make_server(handler);
+
+    let origin = Origin::Origin(url.origin());
+    let mut request = Request::new(url, Some(origin), None);
+    request.referrer = Referrer::NoReferrer;
+    request.destination = Destination::Image;
+    let fetch_response = fetch(&mut request, None);
+    let _ = server.close();
+
+    assert!(!fetch_response.is_network_error());
+}
+
+#[test]
+fn test_fetch_sends_fetch_metadata_headers_for_user_navigation() {
+    static MESSAGE: &'static [u8] = b"";
+    let handler = move |request: HyperRequest, response: HyperResponse| {
+        assert_eq!(request.headers.get_raw("Sec-Fetch-Dest").unwrap(), &[b"document".to_vec()]);
+        assert_eq!(request.headers.get_raw("Sec-Fetch-Mode").unwrap(), &[b"navigate".to_vec()]);
+        assert_eq!(request.headers.get_raw("Sec-Fetch-Site").unwrap(), &[b"none".to_vec()]);
+        assert_eq!(request.headers.get_raw("Sec-Fetch-User").unwrap(), &[b"?1".to_vec()]);
+        response.send(MESSAGE).unwrap();
+    };
+    let (mut server, url) = make_server(handler);
+
+    let origin = Origin::Origin(ImmutableOrigin::new_opaque());
+    let mut request = Request::new(url, Some(origin), None);
+    request.referrer = Referrer::NoReferrer;
+    request.destination = Destination::Document;
+    request.mode = RequestMode::Navigate;
+    request.user_initiated = true;
+    let fetch_response = fetch(&mut request, None);
+    let _ = server.close();
+
+    assert!(!fetch_response.is_network_error());
+}
+
+#[test]
+fn test_fetch_blocks_mixed_active_content() {
+    let url = ServoUrl::parse("http://example.com/script.js").unwrap();
+    let origin = Origin::Origin(ServoUrl::parse("https://example.com").unwrap().origin());
+    let mut request = Request::new(url, Some(origin), None);
+    request.referrer = Referrer::NoReferrer;
+    request.destination = Destination::Script;
+    let fetch_response = fetch(&mut request, None);
+
+    assert!(fetch_response.is_network_error());
+    assert_eq!(fetch_response.get_network_error(),
+               Some(&NetworkError::Internal("Blocked as mixed content".into())));
+}
+
+#[test]
+fn test_fetch_metadata_headers_do_not_need_cors_preflight() {
+    static ACK: &'static [u8] = b"ACK";
+    let preflights = Arc::new(AtomicUsize::new(0));
+    let counter = preflights.clone();
+    let handler = move |request: HyperRequest, mut response: HyperResponse| {
+        if request.method == Method::Options {
+            preflights.fetch_add(1, Ordering::SeqCst);
+        }
+        assert_eq!(request.headers.get_raw("Sec-Fetch-Mode").unwrap(), &[b"cors".to_vec()]);
+        assert_eq!(request.headers.get_raw("Sec-Fetch-Site").unwrap(), &[b"cross-site".to_vec()]);
+        response.headers_mut().set(AccessControlAllowOrigin::Any);
+        response.send(ACK).unwrap();
+    };
+    let (mut server, url) = make_server(handler);
+
+    // A simple cross-origin request, as made by fetch() or XMLHttpRequest.
+    let origin = Origin::Origin(ImmutableOrigin::new_opaque());
+    let mut request = Request::new(url, Some(origin), None);
+    request.referrer = Referrer::NoReferrer;
+    request.mode = RequestMode::CorsMode;
+    request.unsafe_request = true;
+    let fetch_response = fetch(&mut request, None);
+    let _ = server.close();
+
+    assert!(!fetch_response.is_network_error());
+    assert_eq!(fetch_response.response_type, ResponseType::Cors);
+    assert_eq!(0, counter.load(Ordering::SeqCst));
+    assert!(request.headers.get_raw("Sec-Fetch-Mode").is_none());
+}
+
+#[test]
+fn test_fetch_blocks_mixed_content_nested_navigations() {
+    let url = ServoUrl::parse("http://example.com/frame.html").unwrap();
+    let origin = Origin::Origin(url.origin());
+    let mut request = Request::new(url, Some(origin), None);
+    request.referrer = Referrer::NoReferrer;
+    request.destination = Destination::Document;
+    request.mode = RequestMode::Navigate;
+    request.parent_origin = Some(ServoUrl::parse("https://example.com").unwrap().origin());
+    let fetch_response = fetch(&mut request, None);
+
+    assert!(fetch_response.is_network_error());
+    assert_eq!(fetch_response.get_network_error(),
+               Some(&NetworkError::Internal("Blocked as mixed content".into())));
+}
+
 #[test]
 fn test_fetch_with_sri_network_error() {
     static MESSAGE: &'static [u8] = b"alert('Hello, Network Error');";
@@ -947,6 +1111,10 @@ fn test_fetch_with_devtools() {
 
     headers.set(UserAgent(DEFAULT_USER_AGENT.to_owned()));
 
+    headers.set_raw("Sec-Fetch-Dest", vec![b"empty".to_vec()]);
+    headers.set_raw("Sec-Fetch-Mode", vec![b"no-cors".to_vec()]);
+    headers.set_raw("Sec-Fetch-Site", vec![b"same-origin".to_vec()]);
+
     let httprequest = DevtoolsHttpRequest {
         url: url,
         method: Method::Get,
@@ -135,6 +135,10 @@ fn test_check_default_headers_loaded_in_every_request() {
 
     headers.set(UserAgent(::DEFAULT_USER_AGENT.to_owned()));
 
+    headers.set_raw("Sec-Fetch-Dest", vec![b"document".to_vec()]);
+    headers.set_raw("Sec-Fetch-Mode", vec![b"no-cors".to_vec()]);
+    headers.set_raw("Sec-Fetch-Site", vec![b"same-origin".to_vec()]);
+
     *expected_headers.lock().unwrap() = Some(headers.clone());
 
     // Testing for method.GET
@@ -254,6 +258,10 @@ fn test_request_and_response_data_with_network_messages() {
 
     headers.set(UserAgent(::DEFAULT_USER_AGENT.to_owned()));
 
+    headers.set_raw("Sec-Fetch-Dest", vec![b"document".to_vec()]);
+    headers.set_raw("Sec-Fetch-Mode", vec![b"no-cors".to_vec()]);
+    headers.set_raw("Sec-Fetch-Site", vec![b"cross-site".to_vec()]);
+
     let httprequest = DevtoolsHttpRequest {
         url: url,
         method: Method::Get,
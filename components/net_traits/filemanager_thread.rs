@@ -119,6 +119,10 @@ pub enum FileManagerThreadMsg {
     /// Select multiple files. Last field is pre-selected file paths for testing
     SelectFiles(Vec<FilterPattern>, IpcSender<FileManagerResult<Vec<SelectedFile>>>, FileOrigin, Option<Vec<String>>),
 
+    /// Add entries for files that the user dropped onto a document, skipping those that can't be
+    /// read. Sent by the constellation with the paths reported by the embedder.
+    AddDroppedFiles(Vec<PathBuf>, IpcSender<Vec<SelectedFile>>, FileOrigin),
+
     /// Read FileID-indexed file in chunks, optionally check URL validity based on boolean flag
     ReadFile(IpcSender<FileManagerResult<ReadFileProgress>>, Uuid, bool, FileOrigin),
 
@@ -0,0 +1,388 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Decoding of every frame of animated GIF and PNG images.
+//!
+//! Frames are composited onto the canvas of the image as they are decoded, so that each
+//! decoded frame is the whole image as it is shown at that point of the animation.
+//!
+//! <https://www.w3.org/Graphics/GIF/spec-gif89a.txt>
+//! <https://wiki.mozilla.org/APNG_Specification>
+
+use gif::{self, SetParameter};
+use piston_image::{self, ImageFormat};
+
+/// The most memory that the decoded frames of an animation may take, in bytes. Frames past
+/// this are dropped.
+const MAX_ANIMATION_SIZE: usize = 256 * 1024 * 1024;
+
+/// Frames that are shown for at most this long, in milliseconds, are shown for
+/// `DEFAULT_FRAME_DELAY` instead, as in other browsers: many images rely on it.
+const MIN_FRAME_DELAY: u32 = 10;
+const DEFAULT_FRAME_DELAY: u32 = 100;
+
+/// The decoded frames of an image, as non-premultiplied RGBA pixels.
+pub struct DecodedAnimation {
+    pub width: u32,
+    pub height: u32,
+    /// The frames, with how long each of them is shown for, in milliseconds.
+    pub frames: Vec<(u32, Vec<u8>)>,
+    /// How many times the animation is played, or `None` if it loops forever.
+    pub loop_count: Option<u32>,
+}
+
+/// Returns the size in bytes of the RGBA canvas of an image of the given size, or `None` if a
+/// single frame of it would be past `MAX_ANIMATION_SIZE`.
+fn canvas_size(width: usize, height: usize) -> Option<usize> {
+    width.checked_mul(height)?.checked_mul(4).filter(|&size| size <= MAX_ANIMATION_SIZE)
+}
+
+fn frame_delay(milliseconds: u32) -> u32 {
+    if milliseconds <= MIN_FRAME_DELAY { DEFAULT_FRAME_DELAY } else { milliseconds }
+}
+
+/// The area of the canvas that a frame covers, in pixels.
+#[derive(Clone, Copy)]
+struct FrameRect {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+impl FrameRect {
+    /// Clears the area of the frame to transparent black.
+    fn clear(&self, canvas: &mut [u8], canvas_width: usize) {
+        for y in self.y..self.y + self.height {
+            let row = (y * canvas_width + self.x) * 4;
+            for byte in &mut canvas[row..row + self.width * 4] {
+                *byte = 0;
+            }
+        }
+    }
+}
+
+// ======================================================================
+// GIF.
+// ======================================================================
+
+/// Decodes every frame of a GIF image.
+pub fn decode_gif(buffer: &[u8]) -> Option<DecodedAnimation> {
+    let mut decoder = gif::Decoder::new(buffer);
+    decoder.set(gif::ColorOutput::RGBA);
+    // The decoder refuses frames of more pixels than this.
+    decoder.set(gif::MemoryLimit((MAX_ANIMATION_SIZE / 4) as u32));
+    let mut reader = decoder.read_info().ok()?;
+    let (width, height) = (reader.width() as usize, reader.height() as usize);
+    let mut canvas = vec![0; canvas_size(width, height)?];
+    let mut frames = vec![];
+    loop {
+        let frame = match reader.read_next_frame() {
+            Ok(Some(frame)) => frame,
+            // The frames before an error are still shown.
+            Ok(None) | Err(_) => break,
+        };
+        if (frames.len() + 1) * canvas.len() > MAX_ANIMATION_SIZE {
+            break;
+        }
+
+        // Frames may extend past the canvas, in which case they are clipped.
+        let (left, top) = (frame.left as usize, frame.top as usize);
+        let rect = FrameRect {
+            x: left.min(width),
+            y: top.min(height),
+            width: (left + frame.width as usize).min(width).saturating_sub(left),
+            height: (top + frame.height as usize).min(height).saturating_sub(top),
+        };
+        let previous = if frame.dispose == gif::DisposalMethod::Previous {
+            Some(canvas.clone())
+        } else {
+            None
+        };
+
+        // Transparent pixels leave the canvas as it is.
+        for y in 0..rect.height {
+            let source = &frame.buffer[y * frame.width as usize * 4..][..rect.width * 4];
+            let destination = &mut canvas[((rect.y + y) * width + rect.x) * 4..][..rect.width * 4];
+            for (source, destination) in source.chunks(4).zip(destination.chunks_mut(4)) {
+                if source[3] != 0 {
+                    destination.copy_from_slice(source);
+                }
+            }
+        }
+        frames.push((frame_delay(frame.delay as u32 * 10), canvas.clone()));
+
+        match frame.dispose {
+            gif::DisposalMethod::Background => rect.clear(&mut canvas, width),
+            gif::DisposalMethod::Previous => canvas = previous.unwrap(),
+            gif::DisposalMethod::Any | gif::DisposalMethod::Keep => {}
+        }
+    }
+
+    if frames.is_empty() {
+        return None;
+    }
+    Some(DecodedAnimation {
+        width: width as u32,
+        height: height as u32,
+        frames: frames,
+        loop_count: gif_loop_count(buffer),
+    })
+}
+
+/// Returns how many times a GIF image is played, from its `NETSCAPE2.0` application
+/// extension. Images without one are played once.
+fn gif_loop_count(buffer: &[u8]) -> Option<u32> {
+    const EXTENSION: &'static [u8] = b"\x21\xff\x0bNETSCAPE2.0\x03\x01";
+    let start = match buffer.windows(EXTENSION.len()).position(|window| window == EXTENSION) {
+        Some(position) => position + EXTENSION.len(),
+        None => return Some(1),
+    };
+    match buffer.get(start..start + 2) {
+        Some(count) => {
+            // The count is of the repetitions after the image is first played, and zero
+            // repeats it forever.
+            match count[0] as u32 | (count[1] as u32) << 8 {
+                0 => None,
+                count => Some(count + 1),
+            }
+        }
+        None => Some(1),
+    }
+}
+
+// ======================================================================
+// Animated PNG.
+// ======================================================================
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+
+/// The `fcTL` chunk that precedes the data of each frame.
+struct FrameControl {
+    rect: FrameRect,
+    /// How long the frame is shown for, in milliseconds.
+    delay: u32,
+    dispose_op: u8,
+    blend_op: u8,
+}
+
+const APNG_DISPOSE_OP_BACKGROUND: u8 = 1;
+const APNG_DISPOSE_OP_PREVIOUS: u8 = 2;
+const APNG_BLEND_OP_SOURCE: u8 = 0;
+
+impl FrameControl {
+    fn parse(data: &[u8]) -> Option<FrameControl> {
+        if data.len() < 26 {
+            return None;
+        }
+        let delay_numerator = read_u16(&data[20..]) as u32;
+        let delay_denominator = match read_u16(&data[22..]) as u32 {
+            0 => 100,
+            denominator => denominator,
+        };
+        Some(FrameControl {
+            rect: FrameRect {
+                width: read_u32(&data[4..]) as usize,
+                height: read_u32(&data[8..]) as usize,
+                x: read_u32(&data[12..]) as usize,
+                y: read_u32(&data[16..]) as usize,
+            },
+            delay: frame_delay(delay_numerator * 1000 / delay_denominator),
+            dispose_op: data[24],
+            blend_op: data[25],
+        })
+    }
+}
+
+/// Decodes every frame of an animated PNG image, or returns `None` if the image isn't
+/// animated.
+pub fn decode_apng(buffer: &[u8]) -> Option<DecodedAnimation> {
+    if !buffer.starts_with(&PNG_SIGNATURE) {
+        return None;
+    }
+    let mut chunks = vec![];
+    let mut rest = &buffer[PNG_SIGNATURE.len()..];
+    while rest.len() >= 12 {
+        let length = read_u32(rest) as usize;
+        if rest.len() < length + 12 {
+            break;
+        }
+        chunks.push((&rest[4..8], &rest[8..8 + length]));
+        rest = &rest[length + 12..];
+    }
+
+    let header = match chunks.first() {
+        Some(&(kind, data)) if kind == b"IHDR" && data.len() == 13 => data,
+        _ => return None,
+    };
+    let (width, height) = (read_u32(header) as usize, read_u32(&header[4..]) as usize);
+    let canvas_size = canvas_size(width, height)?;
+
+    // An image is animated if it has an `acTL` chunk before its image data. The chunks
+    // before the image data, like the palette, apply to every frame.
+    let mut animation_control = None;
+    let mut shared_chunks = vec![];
+    for &(kind, data) in &chunks[1..] {
+        match kind {
+            b"IDAT" | b"fcTL" => break,
+            b"acTL" => animation_control = Some(data),
+            _ => shared_chunks.push((kind, data)),
+        }
+    }
+    let animation_control = animation_control?;
+    if animation_control.len() < 8 {
+        return None;
+    }
+
+    // The image data of each frame is in `IDAT` chunks for the first frame, if the
+    // image data is part of the animation, and in `fdAT` chunks for the others.
+    let mut frame_data: Vec<(FrameControl, Vec<&[u8]>)> = vec![];
+    for &(kind, data) in &chunks[1..] {
+        match kind {
+            b"fcTL" => frame_data.push((FrameControl::parse(data)?, vec![])),
+            b"IDAT" if frame_data.len() == 1 => frame_data[0].1.push(data),
+            b"fdAT" if frame_data.len() > 1 && data.len() > 4 => {
+                let index = frame_data.len() - 1;
+                frame_data[index].1.push(&data[4..]);
+            }
+            _ => {}
+        }
+    }
+
+    let crc_table = crc_table();
+    let mut canvas = vec![0; canvas_size];
+    let mut frames = vec![];
+    for (index, (control, data)) in frame_data.into_iter().enumerate() {
+        let rect = control.rect;
+        if rect.x.checked_add(rect.width).map_or(true, |right| right > width) ||
+                rect.y.checked_add(rect.height).map_or(true, |bottom| bottom > height) ||
+                data.is_empty() {
+            return None;
+        }
+        if (frames.len() + 1) * canvas.len() > MAX_ANIMATION_SIZE {
+            break;
+        }
+
+        // Each frame is decoded as a PNG image of its own, with the header of the image
+        // changed to the size of the frame.
+        let mut frame_header = header.to_vec();
+        write_u32(&mut frame_header[0..4], rect.width as u32);
+        write_u32(&mut frame_header[4..8], rect.height as u32);
+        let mut png = PNG_SIGNATURE.to_vec();
+        push_png_chunk(&mut png, b"IHDR", &frame_header, &crc_table);
+        for &(kind, data) in &shared_chunks {
+            push_png_chunk(&mut png, kind, data, &crc_table);
+        }
+        for data in data {
+            push_png_chunk(&mut png, b"IDAT", data, &crc_table);
+        }
+        push_png_chunk(&mut png, b"IEND", &[], &crc_table);
+        let pixels = match piston_image::load_from_memory_with_format(&png, ImageFormat::PNG) {
+            Ok(image) => image.to_rgba().into_raw(),
+            Err(_) => break,
+        };
+
+        let previous = if control.dispose_op == APNG_DISPOSE_OP_PREVIOUS && index > 0 {
+            Some(canvas.clone())
+        } else {
+            None
+        };
+        for y in 0..rect.height {
+            let source = &pixels[y * rect.width * 4..][..rect.width * 4];
+            let destination = &mut canvas[((rect.y + y) * width + rect.x) * 4..][..rect.width * 4];
+            if control.blend_op == APNG_BLEND_OP_SOURCE {
+                destination.copy_from_slice(source);
+                continue;
+            }
+            for (source, destination) in source.chunks(4).zip(destination.chunks_mut(4)) {
+                blend_over(source, destination);
+            }
+        }
+        frames.push((control.delay, canvas.clone()));
+
+        match (control.dispose_op, previous) {
+            (APNG_DISPOSE_OP_PREVIOUS, Some(previous)) => canvas = previous,
+            // Disposing of the first frame to the previous frame clears it.
+            (APNG_DISPOSE_OP_PREVIOUS, None) | (APNG_DISPOSE_OP_BACKGROUND, _) => {
+                rect.clear(&mut canvas, width)
+            }
+            _ => {}
+        }
+    }
+
+    if frames.len() < 2 {
+        return None;
+    }
+    Some(DecodedAnimation {
+        width: width as u32,
+        height: height as u32,
+        frames: frames,
+        loop_count: match read_u32(&animation_control[4..]) {
+            0 => None,
+            count => Some(count),
+        },
+    })
+}
+
+/// Composites a non-premultiplied RGBA pixel over another.
+fn blend_over(source: &[u8], destination: &mut [u8]) {
+    let source_alpha = source[3] as u32;
+    if source_alpha == 255 {
+        destination.copy_from_slice(source);
+        return;
+    }
+    if source_alpha == 0 {
+        return;
+    }
+    let destination_alpha = destination[3] as u32 * (255 - source_alpha) / 255;
+    let alpha = source_alpha + destination_alpha;
+    for channel in 0..3 {
+        destination[channel] = ((source[channel] as u32 * source_alpha +
+                                 destination[channel] as u32 * destination_alpha) / alpha) as u8;
+    }
+    destination[3] = alpha as u8;
+}
+
+fn push_png_chunk(png: &mut Vec<u8>, kind: &[u8], data: &[u8], crc_table: &[u32; 256]) {
+    let mut length = [0; 4];
+    write_u32(&mut length, data.len() as u32);
+    png.extend_from_slice(&length);
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let mut crc = [0; 4];
+    write_u32(&mut crc, crc32(&png[start..], crc_table));
+    png.extend_from_slice(&crc);
+}
+
+fn crc_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    for (index, entry) in table.iter_mut().enumerate() {
+        let mut value = index as u32;
+        for _ in 0..8 {
+            value = if value & 1 != 0 { 0xedb88320 ^ (value >> 1) } else { value >> 1 };
+        }
+        *entry = value;
+    }
+    table
+}
+
+fn crc32(bytes: &[u8], table: &[u32; 256]) -> u32 {
+    !bytes.iter().fold(!0, |crc, &byte| table[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8))
+}
+
+fn read_u16(bytes: &[u8]) -> u16 {
+    (bytes[0] as u16) << 8 | bytes[1] as u16
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    (bytes[0] as u32) << 24 | (bytes[1] as u32) << 16 | (bytes[2] as u32) << 8 | bytes[3] as u32
+}
+
+fn write_u32(bytes: &mut [u8], value: u32) {
+    bytes[0] = (value >> 24) as u8;
+    bytes[1] = (value >> 16) as u8;
+    bytes[2] = (value >> 8) as u8;
+    bytes[3] = value as u8;
+}
@@ -2,8 +2,11 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+use image::animation::{self, DecodedAnimation};
+use image::svg::{self, SvgImage};
+use image::webp;
 use ipc_channel::ipc::IpcSharedMemory;
-use piston_image::{self, DynamicImage, ImageFormat};
+use piston_image::{self, DynamicImage};
 use std::fmt;
 use webrender_api;
 
@@ -28,6 +31,58 @@ pub struct Image {
     pub bytes: IpcSharedMemory,
     #[ignore_malloc_size_of = "Defined in webrender_api"]
     pub id: Option<webrender_api::ImageKey>,
+    /// The frames of an animated image, the first of which is `bytes`.
+    pub animation: Option<ImageAnimation>,
+}
+
+#[derive(Clone, Deserialize, MallocSizeOf, Serialize)]
+pub struct ImageAnimation {
+    /// How long each frame is shown for, in milliseconds.
+    pub frame_delays: Vec<u32>,
+    /// The pixels of every frame, one after the other, in the format of the image.
+    #[ignore_malloc_size_of = "Defined in ipc-channel"]
+    pub frames: IpcSharedMemory,
+    /// How many times the animation is played, or `None` if it loops forever.
+    pub loop_count: Option<u32>,
+}
+
+impl ImageAnimation {
+    /// Returns the pixels of the frame at the given index.
+    pub fn frame(&self, index: usize) -> &[u8] {
+        let size = self.frames.len() / self.frame_delays.len();
+        &self.frames[index * size..(index + 1) * size]
+    }
+
+    /// Returns the index of the frame that is shown at the given time since the animation
+    /// started, in milliseconds, and whether the animation is still playing at that time.
+    pub fn frame_at(&self, time: u64) -> (usize, bool) {
+        let duration = self.frame_delays.iter().fold(0, |duration, &delay| duration + delay as u64);
+        let last_frame = self.frame_delays.len() - 1;
+        if duration == 0 {
+            return (last_frame, false);
+        }
+        if let Some(loop_count) = self.loop_count {
+            if time >= duration * loop_count as u64 {
+                return (last_frame, false);
+            }
+        }
+
+        let mut time = time % duration;
+        for (index, &delay) in self.frame_delays.iter().enumerate() {
+            if time < delay as u64 {
+                return (index, true);
+            }
+            time -= delay as u64;
+        }
+        (last_frame, true)
+    }
+}
+
+impl Image {
+    /// Returns whether the image has more than one frame.
+    pub fn is_animated(&self) -> bool {
+        self.animation.is_some()
+    }
 }
 
 impl fmt::Debug for Image {
@@ -43,6 +98,17 @@ pub struct ImageMetadata {
     pub height: u32,
 }
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ImageFormat {
+    GIF,
+    JPEG,
+    PNG,
+    BMP,
+    ICO,
+    WEBP,
+    SVG,
+}
+
 // FIXME: Images must not be copied every frame. Instead we should atomically
 // reference count them.
 
@@ -75,7 +141,40 @@ pub fn load_from_memory(buffer: &[u8]) -> Option<Image> {
             debug!("{}", msg);
             None
         },
-        Ok(_) => {
+        Ok(ImageFormat::SVG) => {
+            let image = SvgImage::parse(buffer)?;
+            let (width, height) = image.default_size();
+            rasterize_svg(&image, width, height)
+        },
+        Ok(ImageFormat::WEBP) => {
+            match webp::decode(buffer) {
+                Some(mut image) => {
+                    byte_swap_and_premultiply(&mut image.rgba);
+                    Some(Image {
+                        width: image.width,
+                        height: image.height,
+                        format: PixelFormat::BGRA8,
+                        bytes: IpcSharedMemory::from_bytes(&image.rgba),
+                        id: None,
+                        animation: None,
+                    })
+                },
+                None => {
+                    debug!("WebP decoding error");
+                    None
+                },
+            }
+        },
+        Ok(format) => {
+            let frames = match format {
+                ImageFormat::GIF => animation::decode_gif(buffer),
+                ImageFormat::PNG => animation::decode_apng(buffer),
+                _ => None,
+            };
+            if let Some(frames) = frames {
+                return Some(image_from_frames(frames));
+            }
+
             match piston_image::load_from_memory(buffer) {
                 Ok(image) => {
                     let mut rgba = match image {
@@ -89,6 +188,7 @@ pub fn load_from_memory(buffer: &[u8]) -> Option<Image> {
                         format: PixelFormat::BGRA8,
                         bytes: IpcSharedMemory::from_bytes(&*rgba),
                         id: None,
+                        animation: None,
                     })
                 },
                 Err(e) => {
@@ -100,6 +200,52 @@ pub fn load_from_memory(buffer: &[u8]) -> Option<Image> {
     }
 }
 
+/// Converts the decoded frames of an image to an image, which is animated if there is more
+/// than one frame.
+fn image_from_frames(decoded: DecodedAnimation) -> Image {
+    let frame_delays: Vec<u32> = decoded.frames.iter().map(|&(delay, _)| delay).collect();
+    let mut frames = Vec::with_capacity(decoded.frames.iter().map(|&(_, ref rgba)| rgba.len()).sum());
+    for (_, rgba) in decoded.frames {
+        frames.extend_from_slice(&rgba);
+    }
+    byte_swap_and_premultiply(&mut frames);
+
+    let frame_size = frames.len() / frame_delays.len();
+    let animation = if frame_delays.len() > 1 {
+        Some(ImageAnimation {
+            frame_delays: frame_delays,
+            frames: IpcSharedMemory::from_bytes(&frames),
+            loop_count: decoded.loop_count,
+        })
+    } else {
+        None
+    };
+    Image {
+        width: decoded.width,
+        height: decoded.height,
+        format: PixelFormat::BGRA8,
+        bytes: IpcSharedMemory::from_bytes(&frames[..frame_size]),
+        id: None,
+        animation: animation,
+    }
+}
+
+/// Rasterizes an SVG image at the given size in pixels.
+pub fn rasterize_svg(image: &SvgImage, width: u32, height: u32) -> Option<Image> {
+    if width == 0 || height == 0 {
+        return None;
+    }
+    let mut rgba = image.rasterize(width, height);
+    byte_swap_and_premultiply(&mut rgba);
+    Some(Image {
+        width: width,
+        height: height,
+        format: PixelFormat::BGRA8,
+        bytes: IpcSharedMemory::from_bytes(&rgba),
+        id: None,
+        animation: None,
+    })
+}
 
 // https://developer.mozilla.org/en-US/docs/Web/HTML/Element/img
 pub fn detect_image_format(buffer: &[u8]) -> Result<ImageFormat, &str> {
@@ -113,6 +259,10 @@ pub fn detect_image_format(buffer: &[u8]) -> Result<ImageFormat, &str> {
         Ok(ImageFormat::BMP)
     } else if is_ico(buffer) {
         Ok(ImageFormat::ICO)
+    } else if webp::is_webp(buffer) {
+        Ok(ImageFormat::WEBP)
+    } else if svg::is_svg(buffer) {
+        Ok(ImageFormat::SVG)
     } else {
         Err("Image Format Not Supported")
     }
@@ -0,0 +1,1852 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! SVG documents used as images, and the parsing of the SVG attribute microsyntaxes that is
+//! shared with the rendering of inline `svg` elements in layout.
+//!
+//! Images support a static subset of SVG: basic shapes and paths, painted with colors and
+//! gradients, with presentation attributes and `style` attributes. Text, `use` elements,
+//! style sheets, clipping, masking and filters are ignored.
+
+use cssparser::{Color, Parser, ParserInput, RGBA};
+use euclid::{Point2D, Rect, Size2D, Transform2D, Vector2D};
+use std::collections::HashMap;
+use std::f32::consts::{FRAC_PI_2, PI};
+use std::str;
+use xml5ever::driver::parse_document;
+use xml5ever::rcdom::{Handle, NodeData, RcDom};
+use xml5ever::tendril::TendrilSink;
+
+/// The control point distance, relative to the radius, of a cubic Bézier curve
+/// approximating a quarter of a circle.
+const KAPPA: f32 = 0.5522848;
+
+/// The size of images without an intrinsic size, as for other replaced elements.
+/// <https://drafts.csswg.org/css-images-3/#default-object-size>
+const DEFAULT_WIDTH: f32 = 300.0;
+const DEFAULT_HEIGHT: f32 = 150.0;
+
+/// The largest width or height, in pixels, that a vector image is rasterized at.
+pub const MAX_VECTOR_IMAGE_SIZE: u32 = 4096;
+
+/// The font size that `em` and `ex` lengths resolve against, as documents used as images
+/// don't inherit one.
+const DEFAULT_FONT_SIZE: f32 = 16.0;
+
+#[derive(Clone, Copy, Debug)]
+pub enum PathCommand {
+    MoveTo(Point2D<f32>),
+    LineTo(Point2D<f32>),
+    QuadraticCurveTo(Point2D<f32>, Point2D<f32>),
+    BezierCurveTo(Point2D<f32>, Point2D<f32>, Point2D<f32>),
+    Close,
+}
+
+#[derive(Clone, Copy)]
+pub struct PreserveAspectRatio {
+    /// The alignment of the `viewBox` in the viewport along each axis, from 0 (min) to 1 (max),
+    /// or `None` if the `viewBox` is stretched to the viewport.
+    pub align: Option<(f32, f32)>,
+    pub slice: bool,
+}
+
+/// Returns whether a resource looks like an SVG document: markup whose first element is
+/// an `svg` element.
+pub fn is_svg(buffer: &[u8]) -> bool {
+    let prefix = String::from_utf8_lossy(&buffer[..buffer.len().min(1024)]);
+    let mut rest = prefix.trim_left_matches('\u{feff}').trim_left();
+    // Skip the XML declaration, processing instructions, comments and the doctype.
+    while rest.starts_with("<?") || rest.starts_with("<!") {
+        let end = if rest.starts_with("<!--") {
+            rest.find("-->").map(|end| end + 3)
+        } else {
+            rest.find('>').map(|end| end + 1)
+        };
+        match end {
+            Some(end) => rest = rest[end..].trim_left(),
+            None => return false,
+        }
+    }
+    rest.starts_with("<svg") &&
+        rest[4..].chars().next().map_or(false, |next| next.is_whitespace() || next == '>' || next == '/')
+}
+
+/// A parsed SVG document, ready to be rasterized at any size.
+pub struct SvgImage {
+    /// The `width` and `height` of the root element, if they are absolute lengths.
+    width: Option<f32>,
+    height: Option<f32>,
+    view_box: Option<Rect<f32>>,
+    preserve_aspect_ratio: PreserveAspectRatio,
+    /// The shapes of the document, in painting order.
+    shapes: Vec<Shape>,
+}
+
+impl SvgImage {
+    pub fn parse(buffer: &[u8]) -> Option<SvgImage> {
+        if !is_svg(buffer) {
+            return None
+        }
+        let dom: RcDom = parse_document(RcDom::default(), Default::default())
+                         .one(String::from_utf8_lossy(buffer).into_owned());
+        let root = dom.document.children.borrow().iter().find(|node| element_name(node) == Some("svg"))?.clone();
+
+        // Percentages of the root element can't be resolved, as images have no containing block.
+        let absolute_length = |name| attr(&root, name).filter(|value| !value.trim().ends_with('%'))
+                                                      .and_then(|value| parse_length(&value, 0.0, DEFAULT_FONT_SIZE))
+                                                      .filter(|length| length.is_finite())
+                                                      .map(|length| length.max(0.0));
+        let width = absolute_length("width");
+        let height = absolute_length("height");
+        let view_box = attr(&root, "viewBox").and_then(|value| parse_view_box(&value));
+        let preserve_aspect_ratio =
+            parse_preserve_aspect_ratio(attr(&root, "preserveAspectRatio").as_ref().map(|value| &**value));
+
+        let mut image = SvgImage {
+            width: width,
+            height: height,
+            view_box: view_box,
+            preserve_aspect_ratio: preserve_aspect_ratio,
+            shapes: vec![],
+        };
+        let mut builder = ImageBuilder {
+            viewport: view_box.map_or(image.unclamped_default_size(), |view_box| view_box.size),
+            gradients: HashMap::new(),
+            shapes: vec![],
+        };
+        builder.collect_gradients(&root);
+        let style = Style::initial().inherit(&root);
+        if style.display {
+            builder.build_children(&root, &style, &Transform2D::identity(), style.opacity);
+        }
+        image.shapes = builder.shapes;
+        Some(image)
+    }
+
+    /// The intrinsic width and height of the image, in CSS pixels.
+    pub fn intrinsic_size(&self) -> (Option<f32>, Option<f32>) {
+        (self.width, self.height)
+    }
+
+    /// The intrinsic aspect ratio of the image, as its width divided by its height.
+    /// <https://svgwg.org/svg2-draft/coords.html#SizingSVGInCSS>
+    pub fn intrinsic_ratio(&self) -> Option<f32> {
+        match (self.width, self.height) {
+            (Some(width), Some(height)) if width > 0.0 && height > 0.0 => Some(width / height),
+            _ => self.view_box.map(|view_box| view_box.size.width / view_box.size.height)
+                              .filter(|ratio| ratio.is_finite() && *ratio > 0.0),
+        }
+    }
+
+    /// The size the image is used at when nothing else constrains it, in CSS pixels.
+    /// Extreme aspect ratios can make one of the lengths overflow, in which case the
+    /// default object size is used for it instead.
+    fn unclamped_default_size(&self) -> Size2D<f32> {
+        let (width, height) = match (self.width, self.height, self.intrinsic_ratio()) {
+            (Some(width), Some(height), _) => (width, height),
+            (Some(width), None, Some(ratio)) => (width, width / ratio),
+            (None, Some(height), Some(ratio)) => (height * ratio, height),
+            (None, None, Some(ratio)) => (DEFAULT_WIDTH, DEFAULT_WIDTH / ratio),
+            (width, height, None) => (width.unwrap_or(DEFAULT_WIDTH), height.unwrap_or(DEFAULT_HEIGHT)),
+        };
+        Size2D::new(if width.is_finite() { width } else { DEFAULT_WIDTH },
+                    if height.is_finite() { height } else { DEFAULT_HEIGHT })
+    }
+
+    /// The size, in pixels, that the image is rasterized at when nothing else constrains
+    /// it: its default size, at most `MAX_VECTOR_IMAGE_SIZE` along each axis.
+    pub fn default_size(&self) -> (u32, u32) {
+        let size = self.unclamped_default_size();
+        let clamp = |length: f32| length.max(0.0).min(MAX_VECTOR_IMAGE_SIZE as f32).round() as u32;
+        (clamp(size.width), clamp(size.height))
+    }
+
+    /// Paints the image into a viewport of the given size, in pixels. The result is in
+    /// unpremultiplied RGBA.
+    pub fn rasterize(&self, width: u32, height: u32) -> Vec<u8> {
+        let mut canvas = Canvas::new(width as usize, height as usize);
+        let viewport_transform = match self.view_box {
+            Some(view_box) => {
+                view_box_transform(view_box, self.preserve_aspect_ratio, Size2D::new(width as f32, height as f32))
+            }
+            // Without a `viewBox`, the image is drawn at its default size and not scaled.
+            None => Transform2D::identity(),
+        };
+        for shape in &self.shapes {
+            shape.paint(&mut canvas, &shape.transform.post_mul(&viewport_transform));
+        }
+        canvas.into_rgba()
+    }
+}
+
+// ======================================================================
+// Building the shapes of a document.
+// ======================================================================
+
+/// The properties that shapes are painted with, after cascading presentation attributes and
+/// `style` declarations.
+#[derive(Clone)]
+struct Style {
+    color: RGBA,
+    fill: PaintValue,
+    fill_opacity: f32,
+    fill_rule: FillRule,
+    stroke: PaintValue,
+    stroke_opacity: f32,
+    stroke_width: f32,
+    stroke_line_cap: LineCap,
+    stroke_line_join: LineJoin,
+    stroke_miter_limit: f32,
+    stroke_dash_array: Vec<f32>,
+    stroke_dash_offset: f32,
+    visible: bool,
+    // The properties below are not inherited.
+    display: bool,
+    opacity: f32,
+    stop_color: Color,
+    stop_opacity: f32,
+}
+
+#[derive(Clone)]
+enum PaintValue {
+    None,
+    Color(Color),
+    /// A reference to a paint server, and the color to use if it doesn't exist.
+    Server(String, Option<Color>),
+}
+
+#[derive(Clone, Copy)]
+enum FillRule {
+    NonZero,
+    EvenOdd,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum LineCap {
+    Butt,
+    Round,
+    Square,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum LineJoin {
+    Miter,
+    Round,
+    Bevel,
+}
+
+impl Style {
+    fn initial() -> Style {
+        Style {
+            color: RGBA::new(0, 0, 0, 255),
+            fill: PaintValue::Color(Color::RGBA(RGBA::new(0, 0, 0, 255))),
+            fill_opacity: 1.0,
+            fill_rule: FillRule::NonZero,
+            stroke: PaintValue::None,
+            stroke_opacity: 1.0,
+            stroke_width: 1.0,
+            stroke_line_cap: LineCap::Butt,
+            stroke_line_join: LineJoin::Miter,
+            stroke_miter_limit: 4.0,
+            stroke_dash_array: vec![],
+            stroke_dash_offset: 0.0,
+            visible: true,
+            display: true,
+            opacity: 1.0,
+            stop_color: Color::RGBA(RGBA::new(0, 0, 0, 255)),
+            stop_opacity: 1.0,
+        }
+    }
+
+    /// Returns the style of an element with this parent style.
+    fn inherit(&self, element: &Handle) -> Style {
+        let mut style = Style {
+            display: true,
+            opacity: 1.0,
+            stop_color: Color::RGBA(RGBA::new(0, 0, 0, 255)),
+            stop_opacity: 1.0,
+            ..self.clone()
+        };
+        // Presentation attributes come first, and declarations override them.
+        if let NodeData::Element { ref attrs, .. } = element.data {
+            for attr in attrs.borrow().iter() {
+                style.set(&attr.name.local, &attr.value);
+            }
+        }
+        if let Some(declarations) = attr(element, "style") {
+            for declaration in declarations.split(';') {
+                let mut parts = declaration.splitn(2, ':');
+                if let (Some(name), Some(value)) = (parts.next(), parts.next()) {
+                    style.set(name.trim(), value.trim_right_matches("!important"));
+                }
+            }
+        }
+        style
+    }
+
+    /// Sets a property, ignoring invalid values and names of other attributes.
+    fn set(&mut self, name: &str, value: &str) {
+        let value = value.trim();
+        if value == "inherit" {
+            return
+        }
+        let length = |value: &str| parse_length(value, 0.0, DEFAULT_FONT_SIZE);
+        let opacity = |value: &str| parse_fraction(value).map(|opacity| opacity.max(0.0).min(1.0));
+        match name {
+            "color" => if let Some(Color::RGBA(color)) = parse_color(value) {
+                self.color = color
+            },
+            "fill" => if let Some(paint) = parse_paint(value) {
+                self.fill = paint
+            },
+            "stroke" => if let Some(paint) = parse_paint(value) {
+                self.stroke = paint
+            },
+            "fill-opacity" => if let Some(opacity) = opacity(value) {
+                self.fill_opacity = opacity
+            },
+            "stroke-opacity" => if let Some(opacity) = opacity(value) {
+                self.stroke_opacity = opacity
+            },
+            "opacity" => if let Some(opacity) = opacity(value) {
+                self.opacity = opacity
+            },
+            "stop-opacity" => if let Some(opacity) = opacity(value) {
+                self.stop_opacity = opacity
+            },
+            "stop-color" => if let Some(color) = parse_color(value) {
+                self.stop_color = color
+            },
+            "fill-rule" => match value {
+                "nonzero" => self.fill_rule = FillRule::NonZero,
+                "evenodd" => self.fill_rule = FillRule::EvenOdd,
+                _ => {}
+            },
+            "stroke-width" => if let Some(width) = length(value).filter(|&width| width >= 0.0) {
+                self.stroke_width = width
+            },
+            "stroke-linecap" => match value {
+                "butt" => self.stroke_line_cap = LineCap::Butt,
+                "round" => self.stroke_line_cap = LineCap::Round,
+                "square" => self.stroke_line_cap = LineCap::Square,
+                _ => {}
+            },
+            "stroke-linejoin" => match value {
+                "miter" => self.stroke_line_join = LineJoin::Miter,
+                "round" => self.stroke_line_join = LineJoin::Round,
+                "bevel" => self.stroke_line_join = LineJoin::Bevel,
+                _ => {}
+            },
+            "stroke-miterlimit" => if let Some(limit) = value.parse::<f32>().ok().filter(|&limit| limit >= 1.0) {
+                self.stroke_miter_limit = limit
+            },
+            "stroke-dasharray" => {
+                let mut dashes = vec![];
+                if value != "none" {
+                    let mut tokenizer = Tokenizer::new(value);
+                    while !tokenizer.at_end() {
+                        let start = tokenizer.position;
+                        while tokenizer.peek().map_or(false, |byte| byte != b',' && !byte.is_ascii_whitespace()) {
+                            tokenizer.position += 1;
+                        }
+                        match str::from_utf8(&tokenizer.input[start..tokenizer.position]).ok().and_then(length) {
+                            Some(dash) => dashes.push(dash),
+                            None => return,
+                        }
+                        tokenizer.skip_separator();
+                    }
+                }
+                self.stroke_dash_array = dashes;
+            }
+            "stroke-dashoffset" => if let Some(offset) = length(value) {
+                self.stroke_dash_offset = offset
+            },
+            "display" => self.display = value != "none",
+            "visibility" => self.visible = value == "visible",
+            _ => {}
+        }
+    }
+}
+
+fn parse_color(value: &str) -> Option<Color> {
+    let mut input = ParserInput::new(value);
+    let mut parser = Parser::new(&mut input);
+    let color = Color::parse(&mut parser).ok()?;
+    if parser.is_exhausted() { Some(color) } else { None }
+}
+
+fn parse_paint(value: &str) -> Option<PaintValue> {
+    if value == "none" {
+        return Some(PaintValue::None)
+    }
+    if value.starts_with("url(") {
+        let end = value.find(')')?;
+        let id = value[4..end].trim().trim_matches(|character| character == '"' || character == '\'');
+        let fallback = value[end + 1..].trim();
+        let fallback = if fallback.is_empty() || fallback == "none" { None } else { Some(parse_color(fallback)?) };
+        return Some(PaintValue::Server(id.trim_left_matches('#').to_owned(), fallback))
+    }
+    parse_color(value).map(PaintValue::Color)
+}
+
+fn element_name(node: &Handle) -> Option<&str> {
+    match node.data {
+        NodeData::Element { ref name, .. } => Some(&*name.local),
+        _ => None,
+    }
+}
+
+fn attr(node: &Handle, name: &str) -> Option<String> {
+    match node.data {
+        NodeData::Element { ref attrs, .. } => {
+            attrs.borrow().iter().find(|attr| &*attr.name.local == name).map(|attr| String::from(&attr.value))
+        }
+        _ => None,
+    }
+}
+
+/// Reads the elements of a document into a list of shapes.
+struct ImageBuilder {
+    /// The size of the nearest viewport in user units, which percentages resolve against.
+    viewport: Size2D<f32>,
+    /// The gradient elements of the document, by id.
+    gradients: HashMap<String, Handle>,
+    shapes: Vec<Shape>,
+}
+
+impl ImageBuilder {
+    fn collect_gradients(&mut self, node: &Handle) {
+        for kid in node.children.borrow().iter() {
+            match element_name(kid) {
+                Some("linearGradient") | Some("radialGradient") => {
+                    if let Some(id) = attr(kid, "id") {
+                        self.gradients.entry(id).or_insert(kid.clone());
+                    }
+                }
+                Some(_) => self.collect_gradients(kid),
+                None => {}
+            }
+        }
+    }
+
+    fn build_children(&mut self, node: &Handle, style: &Style, transform: &Transform2D<f32>, opacity: f32) {
+        for kid in node.children.borrow().iter() {
+            if element_name(kid).is_some() {
+                self.build_element(kid, style, transform, opacity);
+            }
+        }
+    }
+
+    fn build_element(&mut self,
+                     element: &Handle,
+                     parent_style: &Style,
+                     parent_transform: &Transform2D<f32>,
+                     parent_opacity: f32) {
+        let style = parent_style.inherit(element);
+        if !style.display {
+            return
+        }
+
+        let mut transform = *parent_transform;
+        if let Some(value) = attr(element, "transform") {
+            transform = transform.pre_mul(&parse_transform(&value));
+        }
+        // FIXME: Group opacity is applied to each shape of the group.
+        let opacity = parent_opacity * style.opacity;
+
+        match element_name(element).unwrap_or("") {
+            "g" | "a" | "switch" => self.build_children(element, &style, &transform, opacity),
+            "svg" => self.build_nested_svg(element, &style, &transform, opacity),
+            "rect" | "circle" | "ellipse" | "line" | "polyline" | "polygon" | "path" => {
+                if !style.visible {
+                    return
+                }
+                if let Some(path) = self.shape_path(element) {
+                    self.build_shape(path, &style, transform, opacity);
+                }
+            }
+            // FIXME: Text and `use` elements are not rendered in images.
+            _ => {}
+        }
+    }
+
+    /// Nested `svg` elements establish a new viewport for their children.
+    fn build_nested_svg(&mut self, element: &Handle, style: &Style, transform: &Transform2D<f32>, opacity: f32) {
+        let x = self.length(element, "x", self.viewport.width).unwrap_or(0.0);
+        let y = self.length(element, "y", self.viewport.height).unwrap_or(0.0);
+        let width = self.length(element, "width", self.viewport.width).unwrap_or(self.viewport.width);
+        let height = self.length(element, "height", self.viewport.height).unwrap_or(self.viewport.height);
+        if width <= 0.0 || height <= 0.0 {
+            return
+        }
+
+        let mut transform = transform.pre_translate(Vector2D::new(x, y));
+        let viewport = match attr(element, "viewBox").and_then(|value| parse_view_box(&value)) {
+            Some(view_box) => {
+                let value = attr(element, "preserveAspectRatio");
+                let aspect_ratio = parse_preserve_aspect_ratio(value.as_ref().map(|value| &**value));
+                transform =
+                    transform.pre_mul(&view_box_transform(view_box, aspect_ratio, Size2D::new(width, height)));
+                view_box.size
+            }
+            None => Size2D::new(width, height),
+        };
+
+        let parent_viewport = self.viewport;
+        self.viewport = viewport;
+        self.build_children(element, style, &transform, opacity);
+        self.viewport = parent_viewport;
+    }
+
+    fn build_shape(&mut self, path: Vec<PathCommand>, style: &Style, transform: Transform2D<f32>, opacity: f32) {
+        let bounds = path_bounds(&path);
+        let fill = self.paint(&style.fill, style, style.fill_opacity, &bounds).map(|paint| (paint, style.fill_rule));
+
+        let stroke = if style.stroke_width > 0.0 {
+            self.paint(&style.stroke, style, style.stroke_opacity, &bounds).map(|paint| {
+                let mut dashes = style.stroke_dash_array.clone();
+                // A list with negative values disables dashing, and an odd one is repeated.
+                if dashes.iter().any(|&dash| dash < 0.0) || dashes.iter().all(|&dash| dash == 0.0) {
+                    dashes.clear();
+                } else if dashes.len() % 2 == 1 {
+                    let copy = dashes.clone();
+                    dashes.extend(copy);
+                }
+                (paint, Stroke {
+                    width: style.stroke_width,
+                    line_cap: style.stroke_line_cap,
+                    line_join: style.stroke_line_join,
+                    miter_limit: style.stroke_miter_limit,
+                    dashes: dashes,
+                    dash_offset: style.stroke_dash_offset,
+                })
+            })
+        } else {
+            None
+        };
+
+        if fill.is_none() && stroke.is_none() {
+            return
+        }
+        self.shapes.push(Shape {
+            path: path,
+            transform: transform,
+            opacity: opacity,
+            fill: fill,
+            stroke: stroke,
+        });
+    }
+
+    /// Returns the outline of a basic shape, or `None` if the shape is not rendered.
+    fn shape_path(&self, element: &Handle) -> Option<Vec<PathCommand>> {
+        let (width, height) = (self.viewport.width, self.viewport.height);
+        let diagonal = self.diagonal();
+        let length = |name, reference| self.length(element, name, reference);
+
+        let mut path = vec![];
+        match element_name(element).unwrap_or("") {
+            "rect" => {
+                let rect = Rect::new(Point2D::new(length("x", width).unwrap_or(0.0),
+                                                  length("y", height).unwrap_or(0.0)),
+                                     Size2D::new(length("width", width)?, length("height", height)?));
+                if rect.size.width <= 0.0 || rect.size.height <= 0.0 {
+                    return None
+                }
+                let (rx, ry) = match (length("rx", width), length("ry", height)) {
+                    (Some(rx), Some(ry)) => (rx, ry),
+                    (Some(rx), None) => (rx, rx),
+                    (None, Some(ry)) => (ry, ry),
+                    (None, None) => (0.0, 0.0),
+                };
+                let rx = rx.max(0.0).min(rect.size.width / 2.0);
+                let ry = ry.max(0.0).min(rect.size.height / 2.0);
+                push_rounded_rect(&mut path, &rect, rx, ry);
+            }
+            "circle" => {
+                let r = length("r", diagonal)?;
+                if r <= 0.0 {
+                    return None
+                }
+                let center = Point2D::new(length("cx", width).unwrap_or(0.0), length("cy", height).unwrap_or(0.0));
+                push_ellipse(&mut path, center, r, r);
+            }
+            "ellipse" => {
+                let (rx, ry) = (length("rx", width)?, length("ry", height)?);
+                if rx <= 0.0 || ry <= 0.0 {
+                    return None
+                }
+                let center = Point2D::new(length("cx", width).unwrap_or(0.0), length("cy", height).unwrap_or(0.0));
+                push_ellipse(&mut path, center, rx, ry);
+            }
+            "line" => {
+                path.push(PathCommand::MoveTo(Point2D::new(length("x1", width).unwrap_or(0.0),
+                                                           length("y1", height).unwrap_or(0.0))));
+                path.push(PathCommand::LineTo(Point2D::new(length("x2", width).unwrap_or(0.0),
+                                                           length("y2", height).unwrap_or(0.0))));
+            }
+            name @ "polyline" | name @ "polygon" => {
+                let points = parse_points(&attr(element, "points")?);
+                for (index, point) in points.into_iter().enumerate() {
+                    path.push(if index == 0 { PathCommand::MoveTo(point) } else { PathCommand::LineTo(point) });
+                }
+                if name == "polygon" && !path.is_empty() {
+                    path.push(PathCommand::Close);
+                }
+            }
+            "path" => path = parse_path_data(&attr(element, "d")?),
+            _ => return None,
+        }
+
+        if path.is_empty() {
+            None
+        } else {
+            Some(path)
+        }
+    }
+
+    fn paint(&self, paint: &PaintValue, style: &Style, opacity: f32, bounds: &Rect<f32>) -> Option<Paint> {
+        let with_opacity = |color: &Color| {
+            let mut color = match *color {
+                Color::RGBA(color) => color,
+                Color::CurrentColor => style.color,
+            };
+            color.alpha = (color.alpha as f32 * opacity).round() as u8;
+            color
+        };
+        match *paint {
+            PaintValue::None => None,
+            PaintValue::Color(ref color) => Some(Paint::Color(with_opacity(color))),
+            PaintValue::Server(ref id, ref fallback) => match self.gradients.get(id) {
+                Some(gradient) => self.gradient_paint(gradient, style, opacity, bounds),
+                None => fallback.as_ref().map(|color| Paint::Color(with_opacity(color))),
+            },
+        }
+    }
+
+    /// Resolves a gradient element for a shape with the given bounding box.
+    /// <https://svgwg.org/svg2-draft/pservers.html#Gradients>
+    fn gradient_paint(&self, element: &Handle, style: &Style, opacity: f32, bounds: &Rect<f32>) -> Option<Paint> {
+        // Attributes and stops that are not specified are taken from the referenced gradient.
+        let mut chain = vec![element.clone()];
+        while chain.len() < 8 {
+            let referenced = attr(&chain[chain.len() - 1], "href")
+                .and_then(|href| self.gradients.get(href.trim().trim_left_matches('#')).cloned());
+            match referenced {
+                Some(referenced) => chain.push(referenced),
+                None => break,
+            }
+        }
+        let inherited_attr = |name| chain.iter().filter_map(|element| attr(element, name)).next();
+
+        let stops = chain.iter().map(|element| gradient_stops(element, style, opacity))
+                         .find(|stops| !stops.is_empty())?;
+        if stops.len() == 1 {
+            return Some(Paint::Color(stops[0].1))
+        }
+
+        let spread = match inherited_attr("spreadMethod").as_ref().map(|value| value.trim()) {
+            Some("reflect") => SpreadMethod::Reflect,
+            Some("repeat") => SpreadMethod::Repeat,
+            _ => SpreadMethod::Pad,
+        };
+        let object_bounding_box =
+            inherited_attr("gradientUnits").as_ref().map(|value| value.trim()) != Some("userSpaceOnUse");
+        let mut transform = inherited_attr("gradientTransform").map(|value| parse_transform(&value))
+                                                               .unwrap_or(Transform2D::identity());
+
+        // Coordinates are fractions of the bounding box, or user space lengths.
+        let coordinate = |name, default: &str, reference| {
+            let value = inherited_attr(name).unwrap_or(default.to_owned());
+            if object_bounding_box {
+                parse_fraction(&value)
+            } else {
+                parse_length(&value, reference, DEFAULT_FONT_SIZE)
+            }
+        };
+        if object_bounding_box {
+            if bounds.size.width <= 0.0 || bounds.size.height <= 0.0 {
+                return None
+            }
+            let bounds_transform = Transform2D::row_major(bounds.size.width, 0.0,
+                                                          0.0, bounds.size.height,
+                                                          bounds.origin.x, bounds.origin.y);
+            transform = bounds_transform.pre_mul(&transform);
+        }
+        let gradient = Gradient {
+            stops: stops,
+            spread: spread,
+            transform: transform,
+        };
+
+        let (width, height, diagonal) = (self.viewport.width, self.viewport.height, self.diagonal());
+        if element_name(element) == Some("linearGradient") {
+            Some(Paint::LinearGradient {
+                start: Point2D::new(coordinate("x1", "0%", width)?, coordinate("y1", "0%", height)?),
+                end: Point2D::new(coordinate("x2", "100%", width)?, coordinate("y2", "0%", height)?),
+                gradient: gradient,
+            })
+        } else {
+            let center = Point2D::new(coordinate("cx", "50%", width)?, coordinate("cy", "50%", height)?);
+            let radius = coordinate("r", "50%", diagonal)?;
+            if radius <= 0.0 {
+                return None
+            }
+            // The focus defaults to the center.
+            let focus = Point2D::new(coordinate("fx", "", width).unwrap_or(center.x),
+                                     coordinate("fy", "", height).unwrap_or(center.y));
+            Some(Paint::RadialGradient {
+                focus: focus,
+                center: center,
+                radius: radius,
+                gradient: gradient,
+            })
+        }
+    }
+
+    fn length(&self, element: &Handle, name: &str, reference: f32) -> Option<f32> {
+        attr(element, name).and_then(|value| parse_length(&value, reference, DEFAULT_FONT_SIZE))
+    }
+
+    /// The length percentages that are relative to neither the width nor the height
+    /// resolve against.
+    fn diagonal(&self) -> f32 {
+        ((self.viewport.width * self.viewport.width +
+          self.viewport.height * self.viewport.height) / 2.0).sqrt()
+    }
+}
+
+fn gradient_stops(element: &Handle, style: &Style, opacity: f32) -> Vec<(f32, RGBA)> {
+    let mut stops: Vec<(f32, RGBA)> = vec![];
+    for kid in element.children.borrow().iter() {
+        if element_name(kid) != Some("stop") {
+            continue
+        }
+        let stop_style = style.inherit(kid);
+        let offset = attr(kid, "offset").and_then(|value| parse_fraction(&value)).unwrap_or(0.0).max(0.0).min(1.0);
+        // Offsets never decrease.
+        let offset = stops.last().map_or(offset, |&(previous, _)| offset.max(previous));
+        let mut color = match stop_style.stop_color {
+            Color::RGBA(color) => color,
+            Color::CurrentColor => stop_style.color,
+        };
+        color.alpha = (color.alpha as f32 * stop_style.stop_opacity * opacity).round() as u8;
+        stops.push((offset, color));
+    }
+    stops
+}
+
+// ======================================================================
+// Painting.
+// ======================================================================
+
+struct Shape {
+    path: Vec<PathCommand>,
+    /// The transform from the user space of the shape to the user space of the root element.
+    transform: Transform2D<f32>,
+    /// The group opacity the shape is painted with.
+    opacity: f32,
+    fill: Option<(Paint, FillRule)>,
+    stroke: Option<(Paint, Stroke)>,
+}
+
+struct Stroke {
+    width: f32,
+    line_cap: LineCap,
+    line_join: LineJoin,
+    miter_limit: f32,
+    dashes: Vec<f32>,
+    dash_offset: f32,
+}
+
+enum Paint {
+    Color(RGBA),
+    LinearGradient {
+        start: Point2D<f32>,
+        end: Point2D<f32>,
+        gradient: Gradient,
+    },
+    RadialGradient {
+        focus: Point2D<f32>,
+        center: Point2D<f32>,
+        radius: f32,
+        gradient: Gradient,
+    },
+}
+
+struct Gradient {
+    stops: Vec<(f32, RGBA)>,
+    spread: SpreadMethod,
+    /// The transform from the gradient coordinate system to the user space of the shape.
+    transform: Transform2D<f32>,
+}
+
+#[derive(Clone, Copy)]
+enum SpreadMethod {
+    Pad,
+    Reflect,
+    Repeat,
+}
+
+/// The largest distance, in pixels, between a curve and the line segments it is drawn with.
+const FLATTENING_TOLERANCE: f32 = 0.25;
+
+/// A subpath made of line segments.
+struct Polyline {
+    points: Vec<Point2D<f32>>,
+    closed: bool,
+}
+
+impl Shape {
+    /// Paints the shape, with `transform` going from its user space to pixels.
+    fn paint(&self, canvas: &mut Canvas, transform: &Transform2D<f32>) {
+        // Curves are flattened in user space, finely enough for the scale of the transform.
+        let scale = transform.determinant().abs().sqrt().max(1e-6);
+        let polylines = flatten(&self.path, FLATTENING_TOLERANCE / scale);
+
+        if let Some((ref paint, fill_rule)) = self.fill {
+            let polygons: Vec<Vec<Point2D<f32>>> =
+                polylines.iter().map(|polyline| {
+                    polyline.points.iter().map(|point| transform.transform_point(point)).collect()
+                }).collect();
+            canvas.fill(&polygons, fill_rule, &PaintSource::new(paint, transform), self.opacity);
+        }
+
+        if let Some((ref paint, ref stroke)) = self.stroke {
+            let polygons: Vec<Vec<Point2D<f32>>> =
+                stroke.outline(&polylines, scale).into_iter().map(|polygon| {
+                    polygon.iter().map(|point| transform.transform_point(point)).collect()
+                }).collect();
+            canvas.fill(&polygons, FillRule::NonZero, &PaintSource::new(paint, transform), self.opacity);
+        }
+    }
+}
+
+/// Turns a path into line segments that are at most `tolerance` away from its curves.
+fn flatten(path: &[PathCommand], tolerance: f32) -> Vec<Polyline> {
+    let mut polylines: Vec<Polyline> = vec![];
+    let mut current = Point2D::zero();
+    let mut subpath_start = Point2D::zero();
+    for command in path {
+        // Drawing commands without a current subpath start one at the current point.
+        if let PathCommand::MoveTo(_) = *command {} else if polylines.last().map_or(true, |polyline| polyline.closed) {
+            polylines.push(Polyline { points: vec![current], closed: false });
+            subpath_start = current;
+        }
+        match *command {
+            PathCommand::MoveTo(point) => {
+                polylines.push(Polyline { points: vec![point], closed: false });
+                current = point;
+                subpath_start = point;
+            }
+            PathCommand::LineTo(point) => {
+                polylines.last_mut().unwrap().points.push(point);
+                current = point;
+            }
+            PathCommand::QuadraticCurveTo(control, point) => {
+                // Elevate to a cubic curve.
+                let control1 = current + (control - current) * (2.0 / 3.0);
+                let control2 = point + (control - point) * (2.0 / 3.0);
+                flatten_cubic(&mut polylines.last_mut().unwrap().points, current, control1, control2, point, tolerance);
+                current = point;
+            }
+            PathCommand::BezierCurveTo(control1, control2, point) => {
+                flatten_cubic(&mut polylines.last_mut().unwrap().points, current, control1, control2, point, tolerance);
+                current = point;
+            }
+            PathCommand::Close => {
+                polylines.last_mut().unwrap().closed = true;
+                current = subpath_start;
+            }
+        }
+    }
+    polylines
+}
+
+fn flatten_cubic(points: &mut Vec<Point2D<f32>>,
+                 from: Point2D<f32>,
+                 control1: Point2D<f32>,
+                 control2: Point2D<f32>,
+                 to: Point2D<f32>,
+                 tolerance: f32) {
+    // The number of segments that keeps the error of uniform subdivision under the tolerance,
+    // from the second differences of the control points.
+    let second_difference = |a: Point2D<f32>, b: Point2D<f32>, c: Point2D<f32>| {
+        (a.to_vector() - b.to_vector() * 2.0 + c.to_vector()).length()
+    };
+    let deviation = second_difference(from, control1, control2).max(second_difference(control1, control2, to));
+    let segments = ((0.75 * deviation / tolerance).sqrt().ceil() as usize).max(1).min(256);
+    for segment in 1..segments + 1 {
+        let t = segment as f32 / segments as f32;
+        let u = 1.0 - t;
+        let point = from.to_vector() * (u * u * u) + control1.to_vector() * (3.0 * u * u * t) +
+                    control2.to_vector() * (3.0 * u * t * t) + to.to_vector() * (t * t * t);
+        points.push(point.to_point());
+    }
+}
+
+impl Stroke {
+    /// Returns polygons whose union is the stroke of the polylines, in user space.
+    fn outline(&self, polylines: &[Polyline], scale: f32) -> Vec<Vec<Point2D<f32>>> {
+        let half_width = self.width / 2.0;
+        // Round joins and caps use enough segments to look round at the scale they are drawn at.
+        let round_segments = ((half_width * scale).sqrt() * 4.0).ceil().max(8.0).min(64.0) as usize;
+        let mut polygons = vec![];
+        for polyline in polylines {
+            let mut points = polyline.points.clone();
+            points.dedup();
+            let closed = polyline.closed && points.len() > 2;
+            if closed && points.first() == points.last() {
+                points.pop();
+            }
+
+            let pieces = if self.dashes.is_empty() {
+                vec![Polyline { points: points, closed: closed }]
+            } else {
+                self.dash(&points, closed)
+            };
+            for piece in pieces {
+                self.outline_polyline(&piece, half_width, round_segments, &mut polygons);
+            }
+        }
+
+        // Give every polygon the same orientation, so that the non-zero rule fills their union.
+        for polygon in &mut polygons {
+            if signed_area(polygon) < 0.0 {
+                polygon.reverse();
+            }
+        }
+        polygons
+    }
+
+    /// Splits a polyline into the dashes of the dash array.
+    fn dash(&self, points: &[Point2D<f32>], closed: bool) -> Vec<Polyline> {
+        let mut points = points.to_vec();
+        if closed {
+            let first = points[0];
+            points.push(first);
+        }
+        let period: f32 = self.dashes.iter().sum();
+        let mut index = 0;
+        let mut remaining = self.dashes[0];
+        let mut offset = self.dash_offset % period;
+        if offset < 0.0 {
+            offset += period;
+        }
+        while offset > 0.0 {
+            if offset < remaining {
+                remaining -= offset;
+                break
+            }
+            offset -= remaining;
+            index = (index + 1) % self.dashes.len();
+            remaining = self.dashes[index];
+        }
+
+        let mut pieces = vec![];
+        let mut current = if index % 2 == 0 { Some(vec![points[0]]) } else { None };
+        for segment in points.windows(2) {
+            let (mut from, to) = (segment[0], segment[1]);
+            let mut length = (to - from).length();
+            while length > 0.0 {
+                let step = remaining.min(length);
+                let point = from + (to - from) * (step / length);
+                if let Some(ref mut dash) = current {
+                    dash.push(point);
+                }
+                length -= step;
+                remaining -= step;
+                from = point;
+                if remaining <= 0.0 {
+                    index = (index + 1) % self.dashes.len();
+                    remaining = self.dashes[index];
+                    if index % 2 == 0 {
+                        current = Some(vec![point]);
+                    } else if let Some(dash) = current.take() {
+                        pieces.push(Polyline { points: dash, closed: false });
+                    }
+                }
+            }
+        }
+        if let Some(dash) = current {
+            pieces.push(Polyline { points: dash, closed: false });
+        }
+        pieces
+    }
+
+    fn outline_polyline(&self,
+                        polyline: &Polyline,
+                        half_width: f32,
+                        round_segments: usize,
+                        polygons: &mut Vec<Vec<Point2D<f32>>>) {
+        let points = &polyline.points;
+        if points.len() == 1 {
+            // Zero length subpaths only have caps, which are oriented along the x axis.
+            let point = points[0];
+            match self.line_cap {
+                LineCap::Butt => {}
+                LineCap::Round => polygons.push(circle(point, half_width, round_segments)),
+                LineCap::Square => {
+                    let (dx, dy) = (Vector2D::new(half_width, 0.0), Vector2D::new(0.0, half_width));
+                    polygons.push(vec![point - dx - dy, point + dx - dy, point + dx + dy, point - dx + dy]);
+                }
+            }
+            return
+        }
+
+        let count = points.len();
+        let segments = if polyline.closed { count } else { count - 1 };
+        let direction = |index: usize| {
+            let vector = points[(index + 1) % count] - points[index];
+            vector / vector.length()
+        };
+        let normal = |direction: Vector2D<f32>| Vector2D::new(-direction.y, direction.x) * half_width;
+
+        for index in 0..segments {
+            let (from, to) = (points[index], points[(index + 1) % count]);
+            let offset = normal(direction(index));
+            polygons.push(vec![from + offset, to + offset, to - offset, from - offset]);
+        }
+
+        // Joins between consecutive segments.
+        let joins = if polyline.closed { 0..count } else { 1..count - 1 };
+        for index in joins {
+            let incoming = direction((index + count - 1) % count);
+            let outgoing = direction(index);
+            self.join(points[index], incoming, outgoing, half_width, round_segments, polygons);
+        }
+
+        if !polyline.closed {
+            let start_direction = direction(0);
+            let end_direction = direction(count - 2);
+            self.cap(points[0], -start_direction, half_width, round_segments, polygons);
+            self.cap(points[count - 1], end_direction, half_width, round_segments, polygons);
+        }
+    }
+
+    fn join(&self,
+            point: Point2D<f32>,
+            incoming: Vector2D<f32>,
+            outgoing: Vector2D<f32>,
+            half_width: f32,
+            round_segments: usize,
+            polygons: &mut Vec<Vec<Point2D<f32>>>) {
+        let cross = incoming.cross(outgoing);
+        if cross.abs() < 1e-6 && incoming.dot(outgoing) > 0.0 {
+            return
+        }
+        if self.line_join == LineJoin::Round {
+            polygons.push(circle(point, half_width, round_segments));
+            return
+        }
+
+        // The outer side of the turn is opposite to the direction it turns to.
+        let side = if cross > 0.0 { -half_width } else { half_width };
+        let incoming_offset = Vector2D::new(-incoming.y, incoming.x) * side;
+        let outgoing_offset = Vector2D::new(-outgoing.y, outgoing.x) * side;
+        let (outer1, outer2) = (point + incoming_offset, point + outgoing_offset);
+
+        if self.line_join == LineJoin::Miter {
+            // The ratio of the miter length to the stroke width is 1 / sin(θ / 2), for the
+            // angle θ between the segments.
+            let cos_half_angle = ((1.0 + incoming.dot(outgoing)) / 2.0).max(0.0).sqrt();
+            let miter_ratio = 1.0 / cos_half_angle.max(1e-6);
+            if miter_ratio <= self.miter_limit {
+                let bisector = incoming_offset + outgoing_offset;
+                let tip = point + bisector / bisector.length() * (half_width * miter_ratio);
+                polygons.push(vec![point, outer1, tip, outer2]);
+                return
+            }
+        }
+        polygons.push(vec![point, outer1, outer2]);
+    }
+
+    /// Adds the cap at the end of a subpath going in `direction`.
+    fn cap(&self,
+           point: Point2D<f32>,
+           direction: Vector2D<f32>,
+           half_width: f32,
+           round_segments: usize,
+           polygons: &mut Vec<Vec<Point2D<f32>>>) {
+        let offset = Vector2D::new(-direction.y, direction.x) * half_width;
+        match self.line_cap {
+            LineCap::Butt => {}
+            LineCap::Round => polygons.push(circle(point, half_width, round_segments)),
+            LineCap::Square => {
+                let extension = direction * half_width;
+                polygons.push(vec![point + offset, point + offset + extension,
+                                   point - offset + extension, point - offset]);
+            }
+        }
+    }
+}
+
+fn circle(center: Point2D<f32>, radius: f32, segments: usize) -> Vec<Point2D<f32>> {
+    (0..segments).map(|index| {
+        let (sin, cos) = (index as f32 * 2.0 * PI / segments as f32).sin_cos();
+        center + Vector2D::new(cos, sin) * radius
+    }).collect()
+}
+
+fn signed_area(polygon: &[Point2D<f32>]) -> f32 {
+    let mut area = 0.0;
+    for index in 0..polygon.len() {
+        let (a, b) = (polygon[index], polygon[(index + 1) % polygon.len()]);
+        area += a.x * b.y - b.x * a.y;
+    }
+    area / 2.0
+}
+
+/// A paint, with the transform from pixels to its coordinate system.
+struct PaintSource<'a> {
+    paint: &'a Paint,
+    inverse: Transform2D<f32>,
+}
+
+impl<'a> PaintSource<'a> {
+    fn new(paint: &'a Paint, transform: &Transform2D<f32>) -> PaintSource<'a> {
+        let gradient_transform = match *paint {
+            Paint::Color(_) => Transform2D::identity(),
+            Paint::LinearGradient { ref gradient, .. } |
+            Paint::RadialGradient { ref gradient, .. } => gradient.transform.post_mul(transform),
+        };
+        PaintSource {
+            paint: paint,
+            inverse: gradient_transform.inverse().unwrap_or(Transform2D::identity()),
+        }
+    }
+
+    /// Returns the color of the pixel at the given position, unpremultiplied.
+    fn color_at(&self, x: f32, y: f32) -> [f32; 4] {
+        let point = self.inverse.transform_point(&Point2D::new(x, y));
+        let (offset, gradient) = match *self.paint {
+            Paint::Color(color) => return to_float(color),
+            Paint::LinearGradient { start, end, ref gradient } => {
+                let axis = end - start;
+                let length_squared = axis.dot(axis);
+                let offset = if length_squared > 0.0 { (point - start).dot(axis) / length_squared } else { 1.0 };
+                (offset, gradient)
+            }
+            Paint::RadialGradient { focus, center, radius, ref gradient } => {
+                // The offset is the t for which the point lies on the circle of radius t × r
+                // centered at focus + t × (center - focus). The focus is kept inside the circle.
+                let mut to_center = center - focus;
+                if to_center.length() > radius * 0.999 {
+                    to_center = to_center * (radius * 0.999 / to_center.length());
+                }
+                let focus = center - to_center;
+                let relative = point - focus;
+                let a = to_center.dot(to_center) - radius * radius;
+                let b = relative.dot(to_center);
+                let c = relative.dot(relative);
+                let offset = (b - (b * b - a * c).max(0.0).sqrt()) / a;
+                (offset, gradient)
+            }
+        };
+
+        let offset = match gradient.spread {
+            SpreadMethod::Pad => offset.max(0.0).min(1.0),
+            SpreadMethod::Repeat => offset - offset.floor(),
+            SpreadMethod::Reflect => {
+                let offset = offset.abs() % 2.0;
+                if offset > 1.0 { 2.0 - offset } else { offset }
+            }
+        };
+        let stops = &gradient.stops;
+        let next = match stops.iter().position(|&(stop, _)| stop > offset) {
+            Some(0) => return to_float(stops[0].1),
+            Some(next) => next,
+            None => return to_float(stops[stops.len() - 1].1),
+        };
+        let ((start, start_color), (end, end_color)) = (stops[next - 1], stops[next]);
+        let fraction = (offset - start) / (end - start);
+        let (start_color, end_color) = (to_float(start_color), to_float(end_color));
+        let mut color = [0.0; 4];
+        for index in 0..4 {
+            color[index] = start_color[index] + (end_color[index] - start_color[index]) * fraction;
+        }
+        color
+    }
+}
+
+fn to_float(color: RGBA) -> [f32; 4] {
+    [color.red as f32 / 255.0, color.green as f32 / 255.0, color.blue as f32 / 255.0, color.alpha as f32 / 255.0]
+}
+
+/// The number of rows each row of pixels is sampled at, for anti-aliasing.
+const SUBSAMPLES: usize = 4;
+
+/// A straight edge of a polygon, going down.
+struct Edge {
+    top: f32,
+    bottom: f32,
+    /// The x coordinate of the top.
+    x: f32,
+    /// The change in x per unit of y.
+    slope: f32,
+    /// Whether the edge went down (1) or up (-1) in the polygon.
+    winding: i32,
+}
+
+/// A premultiplied RGBA image that polygons are painted into.
+struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<[f32; 4]>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Canvas {
+        Canvas {
+            width: width,
+            height: height,
+            pixels: vec![[0.0; 4]; width * height],
+        }
+    }
+
+    /// Fills polygons with a paint, anti-aliased.
+    fn fill(&mut self, polygons: &[Vec<Point2D<f32>>], fill_rule: FillRule, paint: &PaintSource, opacity: f32) {
+        let mut edges = vec![];
+        for polygon in polygons {
+            for index in 0..polygon.len() {
+                let (a, b) = (polygon[index], polygon[(index + 1) % polygon.len()]);
+                if a.y == b.y || !a.x.is_finite() || !a.y.is_finite() || !b.x.is_finite() || !b.y.is_finite() {
+                    continue
+                }
+                let (top, bottom, winding) = if a.y < b.y { (a, b, 1) } else { (b, a, -1) };
+                edges.push(Edge {
+                    top: top.y,
+                    bottom: bottom.y,
+                    x: top.x,
+                    slope: (bottom.x - top.x) / (bottom.y - top.y),
+                    winding: winding,
+                });
+            }
+        }
+        if edges.is_empty() {
+            return
+        }
+        edges.sort_by(|a, b| a.top.partial_cmp(&b.top).unwrap());
+        let first_row = edges[0].top.floor().max(0.0) as usize;
+        let last_row = edges.iter().fold(0.0f32, |bottom, edge| bottom.max(edge.bottom)).ceil()
+                            .min(self.height as f32) as usize;
+
+        let mut coverage = vec![0.0f32; self.width + 1];
+        let mut active: Vec<usize> = vec![];
+        let mut next_edge = 0;
+        let mut crossings: Vec<(f32, i32)> = vec![];
+        for row in first_row..last_row {
+            for value in &mut coverage {
+                *value = 0.0;
+            }
+            for subsample in 0..SUBSAMPLES {
+                let y = row as f32 + (subsample as f32 + 0.5) / SUBSAMPLES as f32;
+                while next_edge < edges.len() && edges[next_edge].top <= y {
+                    active.push(next_edge);
+                    next_edge += 1;
+                }
+                active.retain(|&index| edges[index].bottom > y);
+
+                crossings.clear();
+                crossings.extend(active.iter().map(|&index| {
+                    let edge = &edges[index];
+                    (edge.x + (y - edge.top) * edge.slope, edge.winding)
+                }));
+                crossings.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+                let mut winding = 0;
+                for pair in crossings.windows(2) {
+                    winding += pair[0].1;
+                    let inside = match fill_rule {
+                        FillRule::NonZero => winding != 0,
+                        FillRule::EvenOdd => winding % 2 != 0,
+                    };
+                    if inside {
+                        add_span(&mut coverage[..self.width], pair[0].0, pair[1].0, 1.0 / SUBSAMPLES as f32);
+                    }
+                }
+            }
+
+            for x in 0..self.width {
+                let alpha = coverage[x].min(1.0) * opacity;
+                if alpha <= 0.0 {
+                    continue
+                }
+                let color = paint.color_at(x as f32 + 0.5, row as f32 + 0.5);
+                let alpha = alpha * color[3];
+                let pixel = &mut self.pixels[row * self.width + x];
+                for index in 0..3 {
+                    pixel[index] = color[index] * alpha + pixel[index] * (1.0 - alpha);
+                }
+                pixel[3] = alpha + pixel[3] * (1.0 - alpha);
+            }
+        }
+    }
+
+    fn into_rgba(self) -> Vec<u8> {
+        let mut rgba = Vec::with_capacity(self.pixels.len() * 4);
+        for pixel in self.pixels {
+            let alpha = pixel[3];
+            let to_byte = |value: f32| (value * 255.0).round().max(0.0).min(255.0) as u8;
+            for index in 0..3 {
+                rgba.push(if alpha > 0.0 { to_byte(pixel[index] / alpha) } else { 0 });
+            }
+            rgba.push(to_byte(alpha));
+        }
+        rgba
+    }
+}
+
+/// Adds the horizontal coverage of a span from `start` to `end` to a row.
+fn add_span(coverage: &mut [f32], start: f32, end: f32, weight: f32) {
+    let width = coverage.len() as f32;
+    let (start, end) = (start.max(0.0).min(width), end.max(0.0).min(width));
+    if end <= start {
+        return
+    }
+    let (first, last) = (start as usize, end as usize);
+    if first == last {
+        coverage[first] += (end - start) * weight;
+        return
+    }
+    coverage[first] += (first as f32 + 1.0 - start) * weight;
+    for value in &mut coverage[first + 1..last] {
+        *value += weight;
+    }
+    if last < coverage.len() {
+        coverage[last] += (end - last as f32) * weight;
+    }
+}
+
+// ======================================================================
+// Geometry and attribute parsing.
+// ======================================================================
+
+/// Returns the transform that fits a `viewBox` into a viewport of the given size.
+/// <https://svgwg.org/svg2-draft/coords.html#ComputingAViewportsTransform>
+pub fn view_box_transform(view_box: Rect<f32>,
+                          preserve_aspect_ratio: PreserveAspectRatio,
+                          size: Size2D<f32>)
+                          -> Transform2D<f32> {
+    let mut scale_x = size.width / view_box.size.width;
+    let mut scale_y = size.height / view_box.size.height;
+    let (mut translate_x, mut translate_y) = (0.0, 0.0);
+    if let Some((align_x, align_y)) = preserve_aspect_ratio.align {
+        let scale = if preserve_aspect_ratio.slice {
+            scale_x.max(scale_y)
+        } else {
+            scale_x.min(scale_y)
+        };
+        scale_x = scale;
+        scale_y = scale;
+        translate_x = (size.width - view_box.size.width * scale) * align_x;
+        translate_y = (size.height - view_box.size.height * scale) * align_y;
+    }
+    Transform2D::row_major(scale_x, 0.0,
+                           0.0, scale_y,
+                           translate_x - view_box.origin.x * scale_x,
+                           translate_y - view_box.origin.y * scale_y)
+}
+
+pub fn push_ellipse(path: &mut Vec<PathCommand>, center: Point2D<f32>, rx: f32, ry: f32) {
+    let (kx, ky) = (rx * KAPPA, ry * KAPPA);
+    let (x, y) = (center.x, center.y);
+    path.push(PathCommand::MoveTo(Point2D::new(x + rx, y)));
+    path.push(PathCommand::BezierCurveTo(Point2D::new(x + rx, y + ky),
+                                         Point2D::new(x + kx, y + ry),
+                                         Point2D::new(x, y + ry)));
+    path.push(PathCommand::BezierCurveTo(Point2D::new(x - kx, y + ry),
+                                         Point2D::new(x - rx, y + ky),
+                                         Point2D::new(x - rx, y)));
+    path.push(PathCommand::BezierCurveTo(Point2D::new(x - rx, y - ky),
+                                         Point2D::new(x - kx, y - ry),
+                                         Point2D::new(x, y - ry)));
+    path.push(PathCommand::BezierCurveTo(Point2D::new(x + kx, y - ry),
+                                         Point2D::new(x + rx, y - ky),
+                                         Point2D::new(x + rx, y)));
+    path.push(PathCommand::Close);
+}
+
+pub fn push_rounded_rect(path: &mut Vec<PathCommand>, rect: &Rect<f32>, rx: f32, ry: f32) {
+    let (left, top) = (rect.origin.x, rect.origin.y);
+    let (right, bottom) = (rect.max_x(), rect.max_y());
+    let (kx, ky) = (rx * (1.0 - KAPPA), ry * (1.0 - KAPPA));
+    path.push(PathCommand::MoveTo(Point2D::new(left + rx, top)));
+    path.push(PathCommand::LineTo(Point2D::new(right - rx, top)));
+    if rx > 0.0 && ry > 0.0 {
+        path.push(PathCommand::BezierCurveTo(Point2D::new(right - kx, top),
+                                             Point2D::new(right, top + ky),
+                                             Point2D::new(right, top + ry)));
+    }
+    path.push(PathCommand::LineTo(Point2D::new(right, bottom - ry)));
+    if rx > 0.0 && ry > 0.0 {
+        path.push(PathCommand::BezierCurveTo(Point2D::new(right, bottom - ky),
+                                             Point2D::new(right - kx, bottom),
+                                             Point2D::new(right - rx, bottom)));
+    }
+    path.push(PathCommand::LineTo(Point2D::new(left + rx, bottom)));
+    if rx > 0.0 && ry > 0.0 {
+        path.push(PathCommand::BezierCurveTo(Point2D::new(left + kx, bottom),
+                                             Point2D::new(left, bottom - ky),
+                                             Point2D::new(left, bottom - ry)));
+    }
+    path.push(PathCommand::LineTo(Point2D::new(left, top + ry)));
+    if rx > 0.0 && ry > 0.0 {
+        path.push(PathCommand::BezierCurveTo(Point2D::new(left, top + ky),
+                                             Point2D::new(left + kx, top),
+                                             Point2D::new(left + rx, top)));
+    }
+    path.push(PathCommand::Close);
+}
+
+/// Returns the bounding box of the points and control points of a path.
+pub fn path_bounds(path: &[PathCommand]) -> Rect<f32> {
+    let mut points = vec![];
+    for command in path {
+        match *command {
+            PathCommand::MoveTo(point) | PathCommand::LineTo(point) => points.push(point),
+            PathCommand::QuadraticCurveTo(control, point) => points.extend(&[control, point]),
+            PathCommand::BezierCurveTo(control1, control2, point) => {
+                points.extend(&[control1, control2, point])
+            }
+            PathCommand::Close => {}
+        }
+    }
+    Rect::from_points(&points)
+}
+
+/// A cursor over the numbers, flags and commands of SVG attribute microsyntaxes.
+struct Tokenizer<'a> {
+    input: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Tokenizer<'a> {
+    fn new(input: &'a str) -> Tokenizer<'a> {
+        Tokenizer {
+            input: input.as_bytes(),
+            position: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.position).cloned()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') | Some(b'\x0C') = self.peek() {
+            self.position += 1;
+        }
+    }
+
+    /// Skips white space and at most one comma.
+    fn skip_separator(&mut self) {
+        self.skip_whitespace();
+        if self.peek() == Some(b',') {
+            self.position += 1;
+            self.skip_whitespace();
+        }
+    }
+
+    fn at_end(&mut self) -> bool {
+        self.skip_whitespace();
+        self.position == self.input.len()
+    }
+
+    fn is_digit_at(&self, position: usize) -> bool {
+        self.input.get(position).map_or(false, |byte| byte.is_ascii_digit())
+    }
+
+    /// Consumes a number followed by an optional separator.
+    fn number(&mut self) -> Option<f32> {
+        self.skip_whitespace();
+        let start = self.position;
+        if let Some(b'+') | Some(b'-') = self.peek() {
+            self.position += 1;
+        }
+        let mut has_digits = false;
+        while self.is_digit_at(self.position) {
+            self.position += 1;
+            has_digits = true;
+        }
+        if self.peek() == Some(b'.') && self.is_digit_at(self.position + 1) {
+            self.position += 1;
+            while self.is_digit_at(self.position) {
+                self.position += 1;
+                has_digits = true;
+            }
+        }
+        if !has_digits {
+            self.position = start;
+            return None
+        }
+        if let Some(b'e') | Some(b'E') = self.peek() {
+            let sign = match self.input.get(self.position + 1) {
+                Some(&b'+') | Some(&b'-') => 1,
+                _ => 0,
+            };
+            if self.is_digit_at(self.position + 1 + sign) {
+                self.position += 1 + sign;
+                while self.is_digit_at(self.position) {
+                    self.position += 1;
+                }
+            }
+        }
+        let number = str::from_utf8(&self.input[start..self.position]).ok()?.parse().ok()?;
+        self.skip_separator();
+        Some(number)
+    }
+
+    /// Consumes an arc flag, which needs no separator after it.
+    fn flag(&mut self) -> Option<bool> {
+        self.skip_whitespace();
+        let flag = match self.peek()? {
+            b'0' => false,
+            b'1' => true,
+            _ => return None,
+        };
+        self.position += 1;
+        self.skip_separator();
+        Some(flag)
+    }
+
+    fn point(&mut self) -> Option<Point2D<f32>> {
+        Some(Point2D::new(self.number()?, self.number()?))
+    }
+
+    /// Consumes the rest of the input if it is an identifier.
+    fn rest(&mut self) -> &'a str {
+        let rest = &self.input[self.position..];
+        self.position = self.input.len();
+        str::from_utf8(rest).unwrap_or("")
+    }
+}
+
+/// Parses a length attribute into user units.
+pub fn parse_length(value: &str, reference: f32, font_size: f32) -> Option<f32> {
+    let mut tokenizer = Tokenizer::new(value.trim());
+    let number = tokenizer.number()?;
+    Some(match &*tokenizer.rest().to_ascii_lowercase() {
+        "" | "px" => number,
+        "%" => number * reference / 100.0,
+        "em" => number * font_size,
+        "ex" => number * font_size / 2.0,
+        "in" => number * 96.0,
+        "cm" => number * 96.0 / 2.54,
+        "mm" => number * 96.0 / 25.4,
+        "pt" => number * 4.0 / 3.0,
+        "pc" => number * 16.0,
+        _ => return None,
+    })
+}
+
+/// Parses a number or a percentage into a fraction.
+pub fn parse_fraction(value: &str) -> Option<f32> {
+    let mut tokenizer = Tokenizer::new(value.trim());
+    let number = tokenizer.number()?;
+    match tokenizer.rest() {
+        "" => Some(number),
+        "%" => Some(number / 100.0),
+        _ => None,
+    }
+}
+
+pub fn parse_view_box(value: &str) -> Option<Rect<f32>> {
+    let mut tokenizer = Tokenizer::new(value);
+    let view_box = Rect::new(tokenizer.point()?, Size2D::new(tokenizer.number()?, tokenizer.number()?));
+    if !tokenizer.at_end() || view_box.size.width <= 0.0 || view_box.size.height <= 0.0 ||
+       !view_box.origin.x.is_finite() || !view_box.origin.y.is_finite() ||
+       !view_box.size.width.is_finite() || !view_box.size.height.is_finite() {
+        return None
+    }
+    Some(view_box)
+}
+
+pub fn parse_preserve_aspect_ratio(value: Option<&str>) -> PreserveAspectRatio {
+    let mut preserve_aspect_ratio = PreserveAspectRatio {
+        align: Some((0.5, 0.5)),
+        slice: false,
+    };
+    let mut words = value.unwrap_or("").split_whitespace().skip_while(|&word| word == "defer");
+    match words.next() {
+        Some("none") => preserve_aspect_ratio.align = None,
+        Some(align) if align.len() == 8 => {
+            let fraction = |position: &str| match position {
+                "Min" => Some(0.0),
+                "Mid" => Some(0.5),
+                "Max" => Some(1.0),
+                _ => None,
+            };
+            if let (Some(x), Some(y)) = (fraction(&align[1..4]), fraction(&align[5..8])) {
+                preserve_aspect_ratio.align = Some((x, y));
+            }
+        }
+        _ => return preserve_aspect_ratio,
+    }
+    preserve_aspect_ratio.slice = words.next() == Some("slice");
+    preserve_aspect_ratio
+}
+
+pub fn parse_points(value: &str) -> Vec<Point2D<f32>> {
+    let mut tokenizer = Tokenizer::new(value);
+    let mut points = vec![];
+    while let Some(point) = tokenizer.point() {
+        points.push(point);
+    }
+    points
+}
+
+/// Parses a transform list. Parsing stops at the first error, keeping the transforms before it.
+/// <https://svgwg.org/svg2-draft/coords.html#TransformProperty>
+pub fn parse_transform(value: &str) -> Transform2D<f32> {
+    let mut transform = Transform2D::identity();
+    let mut tokenizer = Tokenizer::new(value);
+    loop {
+        tokenizer.skip_separator();
+        let start = tokenizer.position;
+        while tokenizer.peek().map_or(false, |byte| byte.is_ascii_alphabetic()) {
+            tokenizer.position += 1;
+        }
+        let name = str::from_utf8(&tokenizer.input[start..tokenizer.position]).unwrap_or("");
+        tokenizer.skip_whitespace();
+        if name.is_empty() || tokenizer.peek() != Some(b'(') {
+            break
+        }
+        tokenizer.position += 1;
+        let mut arguments = vec![];
+        while let Some(argument) = tokenizer.number() {
+            arguments.push(argument);
+        }
+        tokenizer.skip_whitespace();
+        if tokenizer.peek() != Some(b')') {
+            break
+        }
+        tokenizer.position += 1;
+
+        let next = match (name, &*arguments) {
+            ("matrix", &[a, b, c, d, e, f]) => Transform2D::row_major(a, b, c, d, e, f),
+            ("translate", &[x]) => Transform2D::row_major(1.0, 0.0, 0.0, 1.0, x, 0.0),
+            ("translate", &[x, y]) => Transform2D::row_major(1.0, 0.0, 0.0, 1.0, x, y),
+            ("scale", &[s]) => Transform2D::row_major(s, 0.0, 0.0, s, 0.0, 0.0),
+            ("scale", &[x, y]) => Transform2D::row_major(x, 0.0, 0.0, y, 0.0, 0.0),
+            ("rotate", &[angle]) => rotation(angle),
+            ("rotate", &[angle, x, y]) => {
+                Transform2D::row_major(1.0, 0.0, 0.0, 1.0, x, y)
+                    .pre_mul(&rotation(angle))
+                    .pre_mul(&Transform2D::row_major(1.0, 0.0, 0.0, 1.0, -x, -y))
+            }
+            ("skewX", &[angle]) => Transform2D::row_major(1.0, 0.0, angle.to_radians().tan(), 1.0, 0.0, 0.0),
+            ("skewY", &[angle]) => Transform2D::row_major(1.0, angle.to_radians().tan(), 0.0, 1.0, 0.0, 0.0),
+            _ => break,
+        };
+        transform = transform.pre_mul(&next);
+    }
+    transform
+}
+
+fn rotation(degrees: f32) -> Transform2D<f32> {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    Transform2D::row_major(cos, sin, -sin, cos, 0.0, 0.0)
+}
+
+/// Parses path data into absolute commands. Parsing stops at the first error, keeping the
+/// segments before it.
+/// <https://svgwg.org/svg2-draft/paths.html#PathDataBNF>
+pub fn parse_path_data(data: &str) -> Vec<PathCommand> {
+    let mut parser = PathParser {
+        tokenizer: Tokenizer::new(data),
+        path: vec![],
+        command: None,
+        current: Point2D::zero(),
+        subpath_start: Point2D::zero(),
+        last_cubic_control: None,
+        last_quadratic_control: None,
+    };
+    while !parser.tokenizer.at_end() && parser.segment().is_some() {}
+    parser.path
+}
+
+struct PathParser<'a> {
+    tokenizer: Tokenizer<'a>,
+    path: Vec<PathCommand>,
+    /// The command of the previous segment, which is repeated when a segment has none.
+    command: Option<u8>,
+    current: Point2D<f32>,
+    subpath_start: Point2D<f32>,
+    /// The second control point of the previous segment, if it was a cubic curve.
+    last_cubic_control: Option<Point2D<f32>>,
+    /// The control point of the previous segment, if it was a quadratic curve.
+    last_quadratic_control: Option<Point2D<f32>>,
+}
+
+impl<'a> PathParser<'a> {
+    fn segment(&mut self) -> Option<()> {
+        let byte = self.tokenizer.peek()?;
+        if byte.is_ascii_alphabetic() {
+            self.tokenizer.position += 1;
+            self.command = Some(byte);
+        }
+        let command = self.command?;
+        let relative = command.is_ascii_lowercase();
+        let origin = if relative { self.current.to_vector() } else { Point2D::zero().to_vector() };
+
+        let (mut cubic_control, mut quadratic_control) = (None, None);
+        match command.to_ascii_uppercase() {
+            b'M' => {
+                let point = self.tokenizer.point()? + origin;
+                self.path.push(PathCommand::MoveTo(point));
+                self.current = point;
+                self.subpath_start = point;
+                // Further coordinate pairs are implicit line segments.
+                self.command = Some(if relative { b'l' } else { b'L' });
+            }
+            b'L' => {
+                let point = self.tokenizer.point()? + origin;
+                self.line_to(point)
+            }
+            b'H' => {
+                let x = self.tokenizer.number()? + origin.x;
+                let y = self.current.y;
+                self.line_to(Point2D::new(x, y))
+            }
+            b'V' => {
+                let y = self.tokenizer.number()? + origin.y;
+                let x = self.current.x;
+                self.line_to(Point2D::new(x, y))
+            }
+            b'C' => {
+                let control1 = self.tokenizer.point()? + origin;
+                let control2 = self.tokenizer.point()? + origin;
+                let point = self.tokenizer.point()? + origin;
+                self.path.push(PathCommand::BezierCurveTo(control1, control2, point));
+                self.current = point;
+                cubic_control = Some(control2);
+            }
+            b'S' => {
+                let control1 = self.reflect(self.last_cubic_control);
+                let control2 = self.tokenizer.point()? + origin;
+                let point = self.tokenizer.point()? + origin;
+                self.path.push(PathCommand::BezierCurveTo(control1, control2, point));
+                self.current = point;
+                cubic_control = Some(control2);
+            }
+            b'Q' => {
+                let control = self.tokenizer.point()? + origin;
+                let point = self.tokenizer.point()? + origin;
+                self.path.push(PathCommand::QuadraticCurveTo(control, point));
+                self.current = point;
+                quadratic_control = Some(control);
+            }
+            b'T' => {
+                let control = self.reflect(self.last_quadratic_control);
+                let point = self.tokenizer.point()? + origin;
+                self.path.push(PathCommand::QuadraticCurveTo(control, point));
+                self.current = point;
+                quadratic_control = Some(control);
+            }
+            b'A' => {
+                let (rx, ry) = (self.tokenizer.number()?, self.tokenizer.number()?);
+                let rotation = self.tokenizer.number()?;
+                let (large_arc, sweep) = (self.tokenizer.flag()?, self.tokenizer.flag()?);
+                let point = self.tokenizer.point()? + origin;
+                push_arc(&mut self.path, self.current, rx, ry, rotation, large_arc, sweep, point);
+                self.current = point;
+            }
+            b'Z' => {
+                self.path.push(PathCommand::Close);
+                self.current = self.subpath_start;
+                self.tokenizer.skip_separator();
+                // Coordinates can't follow a closepath.
+                self.command = None;
+            }
+            _ => return None,
+        }
+        self.last_cubic_control = cubic_control;
+        self.last_quadratic_control = quadratic_control;
+        Some(())
+    }
+
+    fn line_to(&mut self, point: Point2D<f32>) {
+        self.path.push(PathCommand::LineTo(point));
+        self.current = point;
+    }
+
+    /// Reflects the control point of the previous segment about the current point.
+    fn reflect(&self, control: Option<Point2D<f32>>) -> Point2D<f32> {
+        match control {
+            Some(control) => self.current + (self.current - control),
+            None => self.current,
+        }
+    }
+}
+
+/// Appends an elliptical arc as cubic Bézier curves.
+/// <https://svgwg.org/svg2-draft/implnote.html#ArcImplementationNotes>
+fn push_arc(path: &mut Vec<PathCommand>,
+            from: Point2D<f32>,
+            rx: f32,
+            ry: f32,
+            x_axis_rotation: f32,
+            large_arc: bool,
+            sweep: bool,
+            to: Point2D<f32>) {
+    if from == to {
+        return
+    }
+    let (mut rx, mut ry) = (rx.abs(), ry.abs());
+    if rx == 0.0 || ry == 0.0 {
+        path.push(PathCommand::LineTo(to));
+        return
+    }
+
+    let (sin_phi, cos_phi) = x_axis_rotation.to_radians().sin_cos();
+    let (dx, dy) = ((from.x - to.x) / 2.0, (from.y - to.y) / 2.0);
+    let x1 = cos_phi * dx + sin_phi * dy;
+    let y1 = -sin_phi * dx + cos_phi * dy;
+
+    // Scale radii that are too small up to fit the end points.
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+
+    let numerator = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+    let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let mut coefficient = (numerator / denominator).max(0.0).sqrt();
+    if large_arc == sweep {
+        coefficient = -coefficient;
+    }
+    let center_x1 = coefficient * rx * y1 / ry;
+    let center_y1 = -coefficient * ry * x1 / rx;
+    let center_x = cos_phi * center_x1 - sin_phi * center_y1 + (from.x + to.x) / 2.0;
+    let center_y = sin_phi * center_x1 + cos_phi * center_y1 + (from.y + to.y) / 2.0;
+
+    let start_angle = ((y1 - center_y1) / ry).atan2((x1 - center_x1) / rx);
+    let end_angle = ((-y1 - center_y1) / ry).atan2((-x1 - center_x1) / rx);
+    let mut sweep_angle = end_angle - start_angle;
+    if sweep && sweep_angle < 0.0 {
+        sweep_angle += 2.0 * PI;
+    } else if !sweep && sweep_angle > 0.0 {
+        sweep_angle -= 2.0 * PI;
+    }
+
+    // Split the arc into segments of at most a quarter turn, which cubic curves approximate well.
+    let segments = (sweep_angle.abs() / FRAC_PI_2).ceil().max(1.0) as usize;
+    let delta = sweep_angle / segments as f32;
+    let handle = 4.0 / 3.0 * (delta / 4.0).tan();
+    let map = |x: f32, y: f32| {
+        Point2D::new(center_x + rx * x * cos_phi - ry * y * sin_phi,
+                     center_y + rx * x * sin_phi + ry * y * cos_phi)
+    };
+    for segment in 0..segments {
+        let angle1 = start_angle + segment as f32 * delta;
+        let angle2 = angle1 + delta;
+        let (sin1, cos1) = angle1.sin_cos();
+        let (sin2, cos2) = angle2.sin_cos();
+        let end = if segment + 1 == segments { to } else { map(cos2, sin2) };
+        path.push(PathCommand::BezierCurveTo(map(cos1 - handle * sin1, sin1 + handle * cos1),
+                                             map(cos2 + handle * sin2, sin2 - handle * cos2),
+                                             end));
+    }
+}
@@ -0,0 +1,2437 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! A decoder for WebP images.
+//!
+//! Lossy (VP8) and lossless (VP8L) images are supported, with or without an alpha channel, in
+//! both the simple and the extended file format. Only the first frame of animated images is
+//! decoded.
+//!
+//! <https://developers.google.com/speed/webp/docs/riff_container>
+
+use std::cmp;
+
+/// The largest canvas, in bytes of RGBA pixels, that will be allocated for an image.
+const MAX_IMAGE_SIZE: usize = 256 * 1024 * 1024;
+
+/// A decoded image, as non-premultiplied RGBA pixels.
+pub struct DecodedImage {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+pub fn is_webp(buffer: &[u8]) -> bool {
+    buffer.len() >= 12 && &buffer[0..4] == b"RIFF" && &buffer[8..12] == b"WEBP"
+}
+
+/// Returns the size of the image, without decoding it.
+pub fn dimensions(buffer: &[u8]) -> Option<(u32, u32)> {
+    if !is_webp(buffer) {
+        return None
+    }
+    let (fourcc, payload) = Chunks::new(&buffer[12..]).next()?;
+    match fourcc {
+        b"VP8X" if payload.len() >= 10 => Some((read_u24(&payload[4..]) + 1, read_u24(&payload[7..]) + 1)),
+        b"VP8 " if payload.len() >= 10 => {
+            Some(((read_u16(&payload[6..]) & 0x3fff) as u32, (read_u16(&payload[8..]) & 0x3fff) as u32))
+        }
+        b"VP8L" if payload.len() >= 5 && payload[0] == LOSSLESS_SIGNATURE => {
+            let bits = read_u32(&payload[1..]);
+            Some(((bits & 0x3fff) + 1, ((bits >> 14) & 0x3fff) + 1))
+        }
+        _ => None,
+    }
+}
+
+pub fn decode(buffer: &[u8]) -> Option<DecodedImage> {
+    if !is_webp(buffer) {
+        return None
+    }
+    let riff_size = read_u32(&buffer[4..]) as usize;
+    let data = &buffer[12..cmp::min(buffer.len(), riff_size.saturating_add(8))];
+
+    let mut chunks = Chunks::new(data);
+    let (fourcc, payload) = chunks.next()?;
+    if fourcc != b"VP8X" {
+        return decode_frame(fourcc, payload, None)
+    }
+
+    // The extended format: optional chunks, and the image data possibly preceded by an
+    // alpha channel.
+    if payload.len() < 10 {
+        return None
+    }
+    let canvas_width = read_u24(&payload[4..]) + 1;
+    let canvas_height = read_u24(&payload[7..]) + 1;
+    rgba_size(canvas_width, canvas_height)?;
+    let mut alpha = None;
+    for (fourcc, payload) in chunks {
+        match fourcc {
+            b"ALPH" => alpha = Some(payload),
+            b"VP8 " | b"VP8L" => return decode_frame(fourcc, payload, alpha),
+            b"ANMF" => return decode_first_animation_frame(payload, canvas_width, canvas_height),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Decodes the first frame of an animation, placed on a transparent canvas.
+fn decode_first_animation_frame(payload: &[u8], canvas_width: u32, canvas_height: u32) -> Option<DecodedImage> {
+    if payload.len() < 16 {
+        return None
+    }
+    let x = read_u24(payload) as usize * 2;
+    let y = read_u24(&payload[3..]) as usize * 2;
+
+    let mut alpha = None;
+    let mut frame = None;
+    for (fourcc, payload) in Chunks::new(&payload[16..]) {
+        match fourcc {
+            b"ALPH" => alpha = Some(payload),
+            b"VP8 " | b"VP8L" => {
+                frame = decode_frame(fourcc, payload, alpha);
+                break
+            }
+            _ => {}
+        }
+    }
+    let frame = frame?;
+
+    // The frame must fit within the canvas.
+    let (canvas_width, canvas_height) = (canvas_width as usize, canvas_height as usize);
+    let (frame_width, frame_height) = (frame.width as usize, frame.height as usize);
+    if x + frame_width > canvas_width || y + frame_height > canvas_height {
+        return None
+    }
+    let mut rgba = vec![0; canvas_width * canvas_height * 4];
+    for row in 0..frame_height {
+        let source = &frame.rgba[row * frame_width * 4..][..frame_width * 4];
+        rgba[((y + row) * canvas_width + x) * 4..][..frame_width * 4].copy_from_slice(source);
+    }
+    Some(DecodedImage {
+        width: canvas_width as u32,
+        height: canvas_height as u32,
+        rgba: rgba,
+    })
+}
+
+fn decode_frame(fourcc: &[u8], payload: &[u8], alpha: Option<&[u8]>) -> Option<DecodedImage> {
+    match fourcc {
+        b"VP8L" => {
+            let (width, height, argb) = decode_lossless(payload)?;
+            let mut rgba = Vec::with_capacity(argb.len() * 4);
+            for pixel in argb {
+                rgba.extend_from_slice(&[(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8, (pixel >> 24) as u8]);
+            }
+            Some(DecodedImage {
+                width: width,
+                height: height,
+                rgba: rgba,
+            })
+        }
+        b"VP8 " => {
+            let frame = decode_lossy(payload)?;
+            let alpha = match alpha {
+                Some(alpha) => Some(decode_alpha(alpha, frame.width, frame.height)?),
+                None => None,
+            };
+            Some(frame.to_rgba(alpha.as_ref().map(|alpha| &**alpha)))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the size in bytes of an RGBA image of the given size, or `None` if it is too
+/// large to be decoded.
+fn rgba_size(width: u32, height: u32) -> Option<usize> {
+    (width as usize).checked_mul(height as usize)?.checked_mul(4).filter(|&size| size <= MAX_IMAGE_SIZE)
+}
+
+fn read_u16(bytes: &[u8]) -> u16 {
+    bytes[0] as u16 | (bytes[1] as u16) << 8
+}
+
+fn read_u24(bytes: &[u8]) -> u32 {
+    bytes[0] as u32 | (bytes[1] as u32) << 8 | (bytes[2] as u32) << 16
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    read_u24(bytes) | (bytes[3] as u32) << 24
+}
+
+/// An iterator over the chunks of a RIFF file, as pairs of FourCC and payload.
+struct Chunks<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Chunks<'a> {
+    fn new(data: &'a [u8]) -> Chunks<'a> {
+        Chunks {
+            data: data,
+        }
+    }
+}
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = (&'a [u8], &'a [u8]);
+
+    fn next(&mut self) -> Option<(&'a [u8], &'a [u8])> {
+        if self.data.len() < 8 {
+            return None
+        }
+        let size = read_u32(&self.data[4..]) as usize;
+        if size > self.data.len() - 8 {
+            return None
+        }
+        let chunk = (&self.data[..4], &self.data[8..8 + size]);
+        // Chunks are padded to an even size.
+        self.data = &self.data[cmp::min(self.data.len(), 8 + size + (size & 1))..];
+        Some(chunk)
+    }
+}
+
+// ======================================================================
+// Alpha channel.
+// ======================================================================
+
+/// Decodes the alpha channel of a lossy image.
+/// <https://developers.google.com/speed/webp/docs/riff_container#alpha>
+fn decode_alpha(data: &[u8], width: u32, height: u32) -> Option<Vec<u8>> {
+    let header = *data.first()?;
+    let (width, height) = (width as usize, height as usize);
+    let mut alpha = match header & 0b11 {
+        0 => {
+            let alpha = data.get(1..1 + width * height)?;
+            alpha.to_vec()
+        }
+        1 => {
+            // The alpha values are the green channel of a lossless image stream without a header.
+            let mut reader = BitReader::new(&data[1..]);
+            let argb = decode_image_stream(&mut reader, width as u32, height as u32)?;
+            argb.iter().map(|&pixel| (pixel >> 8) as u8).collect()
+        }
+        _ => return None,
+    };
+
+    // Undo the spatial filtering.
+    let filter = (header >> 2) & 0b11;
+    if filter != 0 {
+        for y in 0..height {
+            for x in 0..width {
+                let index = y * width + x;
+                let left = if x > 0 { Some(alpha[index - 1]) } else { None };
+                let above = if y > 0 { Some(alpha[index - width]) } else { None };
+                let prediction = match (filter, left, above) {
+                    (_, None, None) => 0,
+                    (_, Some(left), None) => left,
+                    (_, None, Some(above)) => above,
+                    (1, Some(left), Some(_)) => left,
+                    (2, Some(_), Some(above)) => above,
+                    (_, Some(left), Some(above)) => {
+                        let above_left = alpha[index - width - 1] as i32;
+                        cmp::max(0, cmp::min(255, left as i32 + above as i32 - above_left)) as u8
+                    }
+                };
+                alpha[index] = alpha[index].wrapping_add(prediction);
+            }
+        }
+    }
+    Some(alpha)
+}
+
+// ======================================================================
+// Lossless images.
+// https://developers.google.com/speed/webp/docs/webp_lossless_bitstream_specification
+// ======================================================================
+
+const LOSSLESS_SIGNATURE: u8 = 0x2f;
+
+/// The order in which the code lengths of the code length code are stored.
+const CODE_LENGTH_CODE_ORDER: [usize; 19] = [17, 18, 0, 1, 2, 3, 4, 5, 16, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+
+/// The offsets, as (x, y) pairs, of the first 120 distance codes of backward references.
+const DISTANCE_MAP: [(i8, i8); 120] = [
+    (0, 1), (1, 0), (1, 1), (-1, 1), (0, 2), (2, 0), (1, 2), (-1, 2),
+    (2, 1), (-2, 1), (2, 2), (-2, 2), (0, 3), (3, 0), (1, 3), (-1, 3),
+    (3, 1), (-3, 1), (2, 3), (-2, 3), (3, 2), (-3, 2), (0, 4), (4, 0),
+    (1, 4), (-1, 4), (4, 1), (-4, 1), (3, 3), (-3, 3), (2, 4), (-2, 4),
+    (4, 2), (-4, 2), (0, 5), (3, 4), (-3, 4), (4, 3), (-4, 3), (5, 0),
+    (1, 5), (-1, 5), (5, 1), (-5, 1), (2, 5), (-2, 5), (5, 2), (-5, 2),
+    (4, 4), (-4, 4), (3, 5), (-3, 5), (5, 3), (-5, 3), (0, 6), (6, 0),
+    (1, 6), (-1, 6), (6, 1), (-6, 1), (2, 6), (-2, 6), (6, 2), (-6, 2),
+    (4, 5), (-4, 5), (5, 4), (-5, 4), (3, 6), (-3, 6), (6, 3), (-6, 3),
+    (0, 7), (7, 0), (1, 7), (-1, 7), (5, 5), (-5, 5), (7, 1), (-7, 1),
+    (4, 6), (-4, 6), (6, 4), (-6, 4), (2, 7), (-2, 7), (7, 2), (-7, 2),
+    (3, 7), (-3, 7), (7, 3), (-7, 3), (5, 6), (-5, 6), (6, 5), (-6, 5),
+    (8, 0), (4, 7), (-4, 7), (7, 4), (-7, 4), (8, 1), (8, 2), (6, 6),
+    (-6, 6), (8, 3), (5, 7), (-5, 7), (7, 5), (-7, 5), (8, 4), (6, 7),
+    (-6, 7), (7, 6), (-7, 6), (8, 5), (7, 7), (-7, 7), (8, 6), (8, 7),
+];
+
+/// Reads a lossless bitstream, least significant bit first.
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    buffer: u64,
+    bits: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> BitReader<'a> {
+        BitReader {
+            data: data,
+            position: 0,
+            buffer: 0,
+            bits: 0,
+        }
+    }
+
+    /// Makes at least `count` bits available in the buffer, if the data has them.
+    fn fill(&mut self, count: u32) {
+        while self.bits < count && self.position < self.data.len() {
+            self.buffer |= (self.data[self.position] as u64) << self.bits;
+            self.position += 1;
+            self.bits += 8;
+        }
+    }
+
+    fn read(&mut self, count: u32) -> Option<u32> {
+        if count == 0 {
+            return Some(0)
+        }
+        self.fill(count);
+        if self.bits < count {
+            return None
+        }
+        let value = (self.buffer & ((1 << count) - 1)) as u32;
+        self.buffer >>= count;
+        self.bits -= count;
+        Some(value)
+    }
+
+    /// Returns up to `count` bits without consuming them, and how many bits are available.
+    fn peek(&mut self, count: u32) -> (u32, u32) {
+        self.fill(count);
+        ((self.buffer & ((1 << count) - 1)) as u32, cmp::min(self.bits, count))
+    }
+
+    fn consume(&mut self, count: u32) {
+        self.buffer >>= count;
+        self.bits -= count;
+    }
+}
+
+/// The number of bits of the lookup table that decodes short codes at once.
+const HUFFMAN_TABLE_BITS: u32 = 8;
+
+/// A canonical prefix code.
+struct HuffmanCode {
+    /// The number of codes of each length.
+    counts: [u16; 16],
+    /// The symbols, ordered by code.
+    symbols: Vec<u16>,
+    /// The symbol and the length of codes up to `HUFFMAN_TABLE_BITS` long, by their bits in
+    /// the order they are read.
+    table: Vec<(u16, u8)>,
+    /// The only symbol of a code that has a single symbol, which takes no bits.
+    single: Option<u16>,
+}
+
+impl HuffmanCode {
+    fn new(lengths: &[u8]) -> Option<HuffmanCode> {
+        let mut counts = [0u16; 16];
+        for &length in lengths {
+            counts[length as usize] += 1;
+        }
+        counts[0] = 0;
+
+        let used = lengths.iter().filter(|&&length| length != 0).count();
+        if used == 0 {
+            return None
+        }
+        if used == 1 {
+            let symbol = lengths.iter().position(|&length| length != 0).unwrap();
+            return Some(HuffmanCode {
+                counts: counts,
+                symbols: vec![],
+                table: vec![],
+                single: Some(symbol as u16),
+            })
+        }
+
+        // Reject over-subscribed codes.
+        let mut left = 1i32;
+        for &count in &counts[1..] {
+            left = (left << 1) - count as i32;
+            if left < 0 {
+                return None
+            }
+        }
+
+        let mut offsets = [0u16; 16];
+        for length in 1..15 {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+        let mut symbols = vec![0; used];
+        for (symbol, &length) in lengths.iter().enumerate() {
+            if length != 0 {
+                symbols[offsets[length as usize] as usize] = symbol as u16;
+                offsets[length as usize] += 1;
+            }
+        }
+
+        let mut table = vec![(0, 0); 1 << HUFFMAN_TABLE_BITS];
+        let mut code = 0u32;
+        let mut index = 0;
+        for length in 1..16u32 {
+            for _ in 0..counts[length as usize] {
+                if length <= HUFFMAN_TABLE_BITS {
+                    // Codes are read most significant bit first.
+                    let mut entry = (0..length).fold(0, |reversed, bit| reversed << 1 | (code >> bit) & 1);
+                    while entry < 1 << HUFFMAN_TABLE_BITS {
+                        table[entry as usize] = (symbols[index], length as u8);
+                        entry += 1 << length;
+                    }
+                }
+                code += 1;
+                index += 1;
+            }
+            code <<= 1;
+        }
+
+        Some(HuffmanCode {
+            counts: counts,
+            symbols: symbols,
+            table: table,
+            single: None,
+        })
+    }
+
+    fn read_symbol(&self, reader: &mut BitReader) -> Option<u16> {
+        if let Some(symbol) = self.single {
+            return Some(symbol)
+        }
+
+        let (bits, available) = reader.peek(HUFFMAN_TABLE_BITS);
+        let (symbol, length) = self.table[bits as usize];
+        if length != 0 && length as u32 <= available {
+            reader.consume(length as u32);
+            return Some(symbol)
+        }
+
+        // Longer codes are decoded one bit at a time.
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for length in 1..16 {
+            code |= reader.read(1)? as i32;
+            let count = self.counts[length] as i32;
+            if code - count < first {
+                return self.symbols.get((index + code - first) as usize).cloned()
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        None
+    }
+
+    fn read(reader: &mut BitReader, alphabet_size: usize) -> Option<HuffmanCode> {
+        let mut lengths = vec![0u8; alphabet_size];
+        if reader.read(1)? == 1 {
+            // A simple code of one or two symbols.
+            let symbol_count = reader.read(1)? + 1;
+            let first_bits = if reader.read(1)? == 1 { 8 } else { 1 };
+            *lengths.get_mut(reader.read(first_bits)? as usize)? = 1;
+            if symbol_count == 2 {
+                *lengths.get_mut(reader.read(8)? as usize)? = 1;
+            }
+            return HuffmanCode::new(&lengths)
+        }
+
+        let mut code_length_lengths = [0u8; 19];
+        let code_length_count = reader.read(4)? as usize + 4;
+        for &symbol in &CODE_LENGTH_CODE_ORDER[..code_length_count] {
+            code_length_lengths[symbol] = reader.read(3)? as u8;
+        }
+        let code_length_code = HuffmanCode::new(&code_length_lengths)?;
+
+        let mut max_symbol = if reader.read(1)? == 1 {
+            let bits = 2 + 2 * reader.read(3)?;
+            let max_symbol = 2 + reader.read(bits)? as usize;
+            if max_symbol > alphabet_size {
+                return None
+            }
+            max_symbol
+        } else {
+            alphabet_size
+        };
+
+        let mut symbol = 0;
+        let mut previous_length = 8;
+        while symbol < alphabet_size && max_symbol > 0 {
+            max_symbol -= 1;
+            let code = code_length_code.read_symbol(reader)?;
+            let (length, repeat) = match code {
+                0...15 => (code as u8, 1),
+                16 => (previous_length, 3 + reader.read(2)? as usize),
+                17 => (0, 3 + reader.read(3)? as usize),
+                _ => (0, 11 + reader.read(7)? as usize),
+            };
+            if symbol + repeat > alphabet_size {
+                return None
+            }
+            for length_slot in &mut lengths[symbol..symbol + repeat] {
+                *length_slot = length;
+            }
+            symbol += repeat;
+            if code < 16 && length != 0 {
+                previous_length = length;
+            }
+        }
+        HuffmanCode::new(&lengths)
+    }
+}
+
+/// The five prefix codes used to decode a part of an image.
+struct HuffmanGroup {
+    green: HuffmanCode,
+    red: HuffmanCode,
+    blue: HuffmanCode,
+    alpha: HuffmanCode,
+    distance: HuffmanCode,
+}
+
+enum Transform {
+    Predictor {
+        bits: u32,
+        modes: Vec<u32>,
+        width: u32,
+    },
+    Color {
+        bits: u32,
+        elements: Vec<u32>,
+        width: u32,
+    },
+    SubtractGreen,
+    ColorIndexing {
+        bits: u32,
+        palette: Vec<u32>,
+        width: u32,
+    },
+}
+
+fn subsampled_size(size: u32, bits: u32) -> u32 {
+    (size + (1 << bits) - 1) >> bits
+}
+
+fn decode_lossless(data: &[u8]) -> Option<(u32, u32, Vec<u32>)> {
+    let mut reader = BitReader::new(data);
+    if reader.read(8)? != LOSSLESS_SIGNATURE as u32 {
+        return None
+    }
+    let width = reader.read(14)? + 1;
+    let height = reader.read(14)? + 1;
+    rgba_size(width, height)?;
+    let _alpha_is_used = reader.read(1)?;
+    if reader.read(3)? != 0 {
+        return None
+    }
+    let pixels = decode_image_stream(&mut reader, width, height)?;
+    Some((width, height, pixels))
+}
+
+/// Decodes the transforms and the pixels of an image, as ARGB values.
+fn decode_image_stream(reader: &mut BitReader, width: u32, height: u32) -> Option<Vec<u32>> {
+    let mut transforms = vec![];
+    let mut coded_width = width;
+    let mut seen = [false; 4];
+    while reader.read(1)? == 1 {
+        let kind = reader.read(2)? as usize;
+        if seen[kind] {
+            return None
+        }
+        seen[kind] = true;
+        transforms.push(match kind {
+            0 | 1 => {
+                let bits = reader.read(3)? + 2;
+                let data = decode_entropy_coded_image(reader,
+                                                      subsampled_size(coded_width, bits),
+                                                      subsampled_size(height, bits),
+                                                      false)?;
+                if kind == 0 {
+                    Transform::Predictor { bits: bits, modes: data, width: coded_width }
+                } else {
+                    Transform::Color { bits: bits, elements: data, width: coded_width }
+                }
+            }
+            2 => Transform::SubtractGreen,
+            _ => {
+                let size = reader.read(8)? + 1;
+                let mut palette = decode_entropy_coded_image(reader, size, 1, false)?;
+                // The palette is coded as differences between consecutive entries.
+                for index in 1..palette.len() {
+                    palette[index] = add_pixels(palette[index], palette[index - 1]);
+                }
+                // Small palettes pack several pixels in one.
+                let bits = match size {
+                    0...2 => 3,
+                    3...4 => 2,
+                    5...16 => 1,
+                    _ => 0,
+                };
+                let transform = Transform::ColorIndexing { bits: bits, palette: palette, width: coded_width };
+                coded_width = subsampled_size(coded_width, bits);
+                transform
+            }
+        });
+    }
+
+    let mut pixels = decode_entropy_coded_image(reader, coded_width, height, true)?;
+    for transform in transforms.iter().rev() {
+        match *transform {
+            Transform::Predictor { bits, ref modes, width } => {
+                inverse_predictor_transform(&mut pixels, width, height, bits, modes)
+            }
+            Transform::Color { bits, ref elements, width } => {
+                inverse_color_transform(&mut pixels, width, bits, elements)
+            }
+            Transform::SubtractGreen => {
+                for pixel in &mut pixels {
+                    let green = (*pixel >> 8) & 0xff;
+                    *pixel = add_pixels(*pixel, green << 16 | green);
+                }
+            }
+            Transform::ColorIndexing { bits, ref palette, width } => {
+                pixels = inverse_color_indexing_transform(&pixels, width, height, bits, palette)
+            }
+        }
+    }
+    Some(pixels)
+}
+
+/// Decodes an image made of literal pixels, backward references and color cache entries.
+/// Only the main image of a stream can use different prefix codes in different places.
+fn decode_entropy_coded_image(reader: &mut BitReader, width: u32, height: u32, main: bool) -> Option<Vec<u32>> {
+    let cache_bits = if reader.read(1)? == 1 {
+        let bits = reader.read(4)?;
+        if bits < 1 || bits > 11 {
+            return None
+        }
+        bits
+    } else {
+        0
+    };
+
+    let (group_bits, group_image) = if main && reader.read(1)? == 1 {
+        let bits = reader.read(3)? + 2;
+        let image = decode_entropy_coded_image(reader,
+                                               subsampled_size(width, bits),
+                                               subsampled_size(height, bits),
+                                               false)?;
+        let groups: Vec<u32> = image.iter().map(|&pixel| (pixel >> 8) & 0xffff).collect();
+        (bits, Some(groups))
+    } else {
+        (0, None)
+    };
+    let group_count = group_image.as_ref().map_or(1, |groups| *groups.iter().max().unwrap() as usize + 1);
+
+    let cache_size = if cache_bits > 0 { 1 << cache_bits } else { 0 };
+    let mut groups = Vec::with_capacity(group_count);
+    for _ in 0..group_count {
+        groups.push(HuffmanGroup {
+            green: HuffmanCode::read(reader, 256 + 24 + cache_size)?,
+            red: HuffmanCode::read(reader, 256)?,
+            blue: HuffmanCode::read(reader, 256)?,
+            alpha: HuffmanCode::read(reader, 256)?,
+            distance: HuffmanCode::read(reader, 40)?,
+        });
+    }
+
+    let (width, height) = (width as usize, height as usize);
+    let total = width * height;
+    let group_width = subsampled_size(width as u32, group_bits) as usize;
+    let mut cache = vec![0u32; cache_size];
+    let mut pixels = vec![0u32; total];
+    let mut position = 0;
+    let mut cached = 0;
+    while position < total {
+        let group = match group_image {
+            Some(ref group_image) => {
+                let (x, y) = (position % width, position / width);
+                &groups[group_image[(y >> group_bits) * group_width + (x >> group_bits)] as usize]
+            }
+            None => &groups[0],
+        };
+
+        let symbol = group.green.read_symbol(reader)? as usize;
+        if symbol < 256 {
+            let red = group.red.read_symbol(reader)? as u32;
+            let blue = group.blue.read_symbol(reader)? as u32;
+            let alpha = group.alpha.read_symbol(reader)? as u32;
+            pixels[position] = alpha << 24 | red << 16 | (symbol as u32) << 8 | blue;
+            position += 1;
+        } else if symbol < 256 + 24 {
+            let length = read_lz77_value(reader, symbol as u32 - 256)? as usize;
+            let distance_symbol = group.distance.read_symbol(reader)?;
+            let distance_code = read_lz77_value(reader, distance_symbol as u32)? as usize;
+            let distance = if distance_code > 120 {
+                distance_code - 120
+            } else {
+                let (x, y) = DISTANCE_MAP[distance_code - 1];
+                cmp::max(1, x as isize + y as isize * width as isize) as usize
+            };
+            if distance > position || length > total - position {
+                return None
+            }
+            for index in position..position + length {
+                pixels[index] = pixels[index - distance];
+            }
+            position += length;
+        } else {
+            pixels[position] = *cache.get(symbol - 256 - 24)?;
+            position += 1;
+        }
+
+        if cache_bits > 0 {
+            for &pixel in &pixels[cached..position] {
+                cache[(0x1e35a7bd_u32.wrapping_mul(pixel) >> (32 - cache_bits)) as usize] = pixel;
+            }
+            cached = position;
+        }
+    }
+    Some(pixels)
+}
+
+/// Reads a backward reference length or distance from its prefix code and extra bits.
+fn read_lz77_value(reader: &mut BitReader, prefix: u32) -> Option<u32> {
+    if prefix < 4 {
+        return Some(prefix + 1)
+    }
+    let extra_bits = (prefix - 2) >> 1;
+    let offset = (2 + (prefix & 1)) << extra_bits;
+    Some(offset + reader.read(extra_bits)? + 1)
+}
+
+/// Adds two pixels component-wise, modulo 256.
+fn add_pixels(a: u32, b: u32) -> u32 {
+    let alpha_and_green = (a & 0xff00ff00).wrapping_add(b & 0xff00ff00);
+    let red_and_blue = (a & 0x00ff00ff).wrapping_add(b & 0x00ff00ff);
+    (alpha_and_green & 0xff00ff00) | (red_and_blue & 0x00ff00ff)
+}
+
+fn average2(a: u32, b: u32) -> u32 {
+    (((a ^ b) & 0xfefefefe) >> 1) + (a & b)
+}
+
+fn components(pixel: u32) -> [i32; 4] {
+    [(pixel >> 24) as i32, (pixel >> 16 & 0xff) as i32, (pixel >> 8 & 0xff) as i32, (pixel & 0xff) as i32]
+}
+
+fn from_components(components: [i32; 4]) -> u32 {
+    let clamp = |value: i32| cmp::max(0, cmp::min(255, value)) as u32;
+    clamp(components[0]) << 24 | clamp(components[1]) << 16 | clamp(components[2]) << 8 | clamp(components[3])
+}
+
+fn predict(mode: u32, left: u32, top: u32, top_right: u32, top_left: u32) -> u32 {
+    match mode {
+        1 => left,
+        2 => top,
+        3 => top_right,
+        4 => top_left,
+        5 => average2(average2(left, top_right), top),
+        6 => average2(left, top_left),
+        7 => average2(left, top),
+        8 => average2(top_left, top),
+        9 => average2(top, top_right),
+        10 => average2(average2(left, top_left), average2(top, top_right)),
+        11 => {
+            // Whichever of the left and top pixels is closer to their gradient.
+            let (l, t, tl) = (components(left), components(top), components(top_left));
+            let left_distance: i32 = (0..4).map(|i| (t[i] - tl[i]).abs()).sum();
+            let top_distance: i32 = (0..4).map(|i| (l[i] - tl[i]).abs()).sum();
+            if left_distance < top_distance { left } else { top }
+        }
+        12 => {
+            let (l, t, tl) = (components(left), components(top), components(top_left));
+            from_components([l[0] + t[0] - tl[0], l[1] + t[1] - tl[1], l[2] + t[2] - tl[2], l[3] + t[3] - tl[3]])
+        }
+        13 => {
+            let (a, b) = (components(average2(left, top)), components(top_left));
+            from_components([a[0] + (a[0] - b[0]) / 2,
+                             a[1] + (a[1] - b[1]) / 2,
+                             a[2] + (a[2] - b[2]) / 2,
+                             a[3] + (a[3] - b[3]) / 2])
+        }
+        _ => 0xff000000,
+    }
+}
+
+fn inverse_predictor_transform(pixels: &mut [u32], width: u32, height: u32, bits: u32, modes: &[u32]) {
+    let block_width = subsampled_size(width, bits) as usize;
+    let (width, height) = (width as usize, height as usize);
+    for y in 0..height {
+        for x in 0..width {
+            let index = y * width + x;
+            let prediction = if y == 0 {
+                if x == 0 { 0xff000000 } else { pixels[index - 1] }
+            } else if x == 0 {
+                pixels[index - width]
+            } else {
+                let mode = (modes[(y >> bits) * block_width + (x >> bits)] >> 8) & 0xf;
+                // The top right pixel of the last column is the first pixel of the current row.
+                predict(mode, pixels[index - 1], pixels[index - width], pixels[index - width + 1],
+                        pixels[index - width - 1])
+            };
+            pixels[index] = add_pixels(pixels[index], prediction);
+        }
+    }
+}
+
+fn color_transform_delta(multiplier: u8, color: u8) -> i32 {
+    (multiplier as i8 as i32 * color as i8 as i32) >> 5
+}
+
+fn inverse_color_transform(pixels: &mut [u32], width: u32, bits: u32, elements: &[u32]) {
+    let block_width = subsampled_size(width, bits) as usize;
+    let width = width as usize;
+    for (index, pixel) in pixels.iter_mut().enumerate() {
+        let (x, y) = (index % width, index / width);
+        let element = elements[(y >> bits) * block_width + (x >> bits)];
+        let (green_to_red, green_to_blue, red_to_blue) = (element as u8, (element >> 8) as u8, (element >> 16) as u8);
+        let green = (*pixel >> 8) as u8;
+        let red = ((*pixel >> 16) as i32 + color_transform_delta(green_to_red, green)) as u8;
+        let blue = (*pixel as i32 +
+                    color_transform_delta(green_to_blue, green) +
+                    color_transform_delta(red_to_blue, red)) as u8;
+        *pixel = (*pixel & 0xff00ff00) | (red as u32) << 16 | blue as u32;
+    }
+}
+
+fn inverse_color_indexing_transform(pixels: &[u32],
+                                    width: u32,
+                                    height: u32,
+                                    bits: u32,
+                                    palette: &[u32])
+                                    -> Vec<u32> {
+    let packed_width = subsampled_size(width, bits) as usize;
+    let bits_per_pixel = 8 >> bits;
+    let mask = (1 << bits_per_pixel) - 1;
+    let (width, height) = (width as usize, height as usize);
+    let mut output = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            let packed = pixels[y * packed_width + (x >> bits)] >> 8;
+            let index = (packed >> ((x & ((1 << bits) - 1)) as u32 * bits_per_pixel)) & mask;
+            // Indices outside of the palette are transparent black.
+            output.push(palette.get(index as usize).cloned().unwrap_or(0));
+        }
+    }
+    output
+}
+
+// ======================================================================
+// Lossy images.
+// https://tools.ietf.org/html/rfc6386
+// ======================================================================
+
+/// A decoded lossy frame, as Y'CbCr planes covering whole macroblocks.
+struct LossyFrame {
+    width: u32,
+    height: u32,
+    y: Vec<u8>,
+    u: Vec<u8>,
+    v: Vec<u8>,
+    /// The width of the luma plane. The chroma planes are half as wide and high.
+    stride: usize,
+}
+
+impl LossyFrame {
+    /// Converts the frame to RGBA, upsampling the chroma planes bilinearly.
+    fn to_rgba(&self, alpha: Option<&[u8]>) -> DecodedImage {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let chroma_stride = self.stride / 2;
+        let chroma_width = (width + 1) / 2;
+        let chroma_height = (height + 1) / 2;
+        let chroma = |plane: &[u8], x: usize, y: usize| {
+            // The chroma sample nearest to a pixel has a weight of 9/16, its neighbours
+            // towards the pixel 3/16 each, and the diagonal one 1/16.
+            let (cx, cy) = (x / 2, y / 2);
+            let nx = if x & 1 == 1 { cmp::min(cx + 1, chroma_width - 1) } else { cx.saturating_sub(1) };
+            let ny = if y & 1 == 1 { cmp::min(cy + 1, chroma_height - 1) } else { cy.saturating_sub(1) };
+            let sample = |x: usize, y: usize| plane[y * chroma_stride + x] as u32;
+            ((9 * sample(cx, cy) + 3 * sample(nx, cy) + 3 * sample(cx, ny) + sample(nx, ny) + 8) >> 4) as i32
+        };
+
+        let mut rgba = Vec::with_capacity(width * height * 4);
+        for y in 0..height {
+            for x in 0..width {
+                let luma = self.y[y * self.stride + x] as i32;
+                let (u, v) = (chroma(&self.u, x, y), chroma(&self.v, x, y));
+                let multiply = |value: i32, coefficient: i32| (value * coefficient) >> 8;
+                let clip = |value: i32| {
+                    if value & !16383 == 0 { (value >> 6) as u8 } else if value < 0 { 0 } else { 255 }
+                };
+                rgba.push(clip(multiply(luma, 19077) + multiply(v, 26149) - 14234));
+                rgba.push(clip(multiply(luma, 19077) - multiply(u, 6419) - multiply(v, 13320) + 8708));
+                rgba.push(clip(multiply(luma, 19077) + multiply(u, 33050) - 17685));
+                rgba.push(alpha.map_or(255, |alpha| alpha[y * width + x]));
+            }
+        }
+        DecodedImage {
+            width: self.width,
+            height: self.height,
+            rgba: rgba,
+        }
+    }
+}
+
+/// The boolean entropy decoder of VP8.
+struct BoolDecoder<'a> {
+    data: &'a [u8],
+    position: usize,
+    value: u32,
+    range: u32,
+    bit_count: u32,
+}
+
+impl<'a> BoolDecoder<'a> {
+    fn new(data: &'a [u8]) -> BoolDecoder<'a> {
+        let mut decoder = BoolDecoder {
+            data: data,
+            position: 0,
+            value: 0,
+            range: 255,
+            bit_count: 0,
+        };
+        decoder.value = (decoder.next_byte() as u32) << 8 | decoder.next_byte() as u32;
+        decoder
+    }
+
+    /// Returns the next byte of the data, or zero past its end.
+    fn next_byte(&mut self) -> u8 {
+        let byte = self.data.get(self.position).cloned().unwrap_or(0);
+        self.position += 1;
+        byte
+    }
+
+    fn read_bool(&mut self, probability: u8) -> bool {
+        let split = 1 + (((self.range - 1) * probability as u32) >> 8);
+        let big_split = split << 8;
+        let bit = if self.value >= big_split {
+            self.range -= split;
+            self.value -= big_split;
+            true
+        } else {
+            self.range = split;
+            false
+        };
+        while self.range < 128 {
+            self.value <<= 1;
+            self.range <<= 1;
+            self.bit_count += 1;
+            if self.bit_count == 8 {
+                self.bit_count = 0;
+                self.value |= self.next_byte() as u32;
+            }
+        }
+        bit
+    }
+
+    fn read_flag(&mut self) -> bool {
+        self.read_bool(128)
+    }
+
+    fn read_literal(&mut self, bits: u32) -> u32 {
+        (0..bits).fold(0, |value, _| value << 1 | self.read_flag() as u32)
+    }
+
+    fn read_signed(&mut self, bits: u32) -> i32 {
+        let magnitude = self.read_literal(bits) as i32;
+        if self.read_flag() { -magnitude } else { magnitude }
+    }
+
+    /// Reads an optional signed value, which is zero if absent.
+    fn read_optional_signed(&mut self, bits: u32) -> i32 {
+        if self.read_flag() { self.read_signed(bits) } else { 0 }
+    }
+
+    /// Reads a value coded with a tree, whose leaves are stored negated.
+    fn read_tree(&mut self, tree: &[i8], probabilities: &[u8]) -> i8 {
+        let mut index = 0;
+        loop {
+            index = tree[index as usize + self.read_bool(probabilities[index as usize >> 1]) as usize];
+            if index <= 0 {
+                return -index
+            }
+        }
+    }
+}
+
+const DC_PRED: i8 = 0;
+const V_PRED: i8 = 1;
+const H_PRED: i8 = 2;
+const TM_PRED: i8 = 3;
+const B_PRED: i8 = 4;
+
+const B_DC_PRED: i8 = 0;
+const B_TM_PRED: i8 = 1;
+const B_VE_PRED: i8 = 2;
+const B_HE_PRED: i8 = 3;
+const B_LD_PRED: i8 = 4;
+const B_RD_PRED: i8 = 5;
+const B_VR_PRED: i8 = 6;
+const B_VL_PRED: i8 = 7;
+const B_HD_PRED: i8 = 8;
+const B_HU_PRED: i8 = 9;
+
+const SEGMENT_TREE: [i8; 6] = [2, 4, -0, -1, -2, -3];
+const KEYFRAME_YMODE_TREE: [i8; 8] = [-B_PRED, 2, 4, 6, -DC_PRED, -V_PRED, -H_PRED, -TM_PRED];
+const KEYFRAME_YMODE_PROBS: [u8; 4] = [145, 156, 163, 128];
+const UV_MODE_TREE: [i8; 6] = [-DC_PRED, 2, -V_PRED, 4, -H_PRED, -TM_PRED];
+const KEYFRAME_UV_MODE_PROBS: [u8; 3] = [142, 114, 183];
+const BMODE_TREE: [i8; 18] = [
+    -B_DC_PRED, 2, -B_TM_PRED, 4, -B_VE_PRED, 6, 8, 12, -B_HE_PRED, 10, -B_RD_PRED, -B_VR_PRED,
+    -B_LD_PRED, 14, -B_VL_PRED, 16, -B_HD_PRED, -B_HU_PRED,
+];
+
+/// The probabilities of the extra bits of the coefficient token categories 1 to 6.
+const DCT_CATEGORY_PROBS: [&[u8]; 6] = [
+    &[159],
+    &[165, 145],
+    &[173, 148, 140],
+    &[176, 155, 140, 135],
+    &[180, 157, 141, 134, 130],
+    &[254, 254, 243, 230, 196, 177, 153, 140, 133, 130, 129],
+];
+const DCT_CATEGORY_BASE: [i32; 6] = [5, 7, 11, 19, 35, 67];
+const COEFF_BANDS: [usize; 17] = [0, 1, 2, 3, 6, 4, 5, 6, 6, 6, 6, 6, 6, 6, 6, 7, 0];
+const ZIGZAG: [usize; 16] = [0, 1, 4, 8, 5, 2, 3, 6, 9, 12, 13, 10, 7, 11, 14, 15];
+
+/// The coefficient block types, which select coefficient probabilities.
+const BLOCK_Y_AFTER_Y2: usize = 0;
+const BLOCK_Y2: usize = 1;
+const BLOCK_CHROMA: usize = 2;
+const BLOCK_Y_WITH_DC: usize = 3;
+
+type CoeffProbs = [[[[u8; 11]; 3]; 8]; 4];
+
+/// Dequantization factors, as (DC, AC) pairs.
+#[derive(Clone, Copy, Default)]
+struct Dequantization {
+    y: (i32, i32),
+    y2: (i32, i32),
+    uv: (i32, i32),
+}
+
+/// Whether the blocks along an edge of a macroblock have non-zero coefficients.
+#[derive(Clone, Copy, Default)]
+struct NonZeroContext {
+    y: [u8; 4],
+    u: [u8; 2],
+    v: [u8; 2],
+    y2: u8,
+}
+
+#[derive(Clone, Copy, Default)]
+struct MacroblockFilter {
+    level: i32,
+    /// Whether the edges between the subblocks are filtered.
+    inner: bool,
+}
+
+struct LossyDecoder<'a> {
+    header: BoolDecoder<'a>,
+    partitions: Vec<BoolDecoder<'a>>,
+    mb_width: usize,
+    mb_height: usize,
+    segment_map_update: bool,
+    segment_probs: [u8; 3],
+    dequantization: [Dequantization; 4],
+    /// The base loop filter level of each segment.
+    filter_levels: [i32; 4],
+    simple_filter: bool,
+    sharpness: i32,
+    /// The loop filter level adjustments of intra frames, and of subblock predicted macroblocks.
+    filter_deltas: Option<(i32, i32)>,
+    coeff_probs: CoeffProbs,
+    skip_prob: Option<u8>,
+    /// The subblock modes along the bottom of the macroblocks above.
+    intra_above: Vec<[i8; 4]>,
+    intra_left: [i8; 4],
+    nonzero_above: Vec<NonZeroContext>,
+    nonzero_left: NonZeroContext,
+    y: Vec<u8>,
+    u: Vec<u8>,
+    v: Vec<u8>,
+    filters: Vec<MacroblockFilter>,
+}
+
+fn decode_lossy(data: &[u8]) -> Option<LossyFrame> {
+    if data.len() < 10 {
+        return None
+    }
+    let tag = read_u24(data);
+    let key_frame = tag & 1 == 0;
+    let first_partition_size = (tag >> 5) as usize;
+    if !key_frame || data[3..6] != [0x9d, 0x01, 0x2a] {
+        return None
+    }
+    let width = (read_u16(&data[6..]) & 0x3fff) as u32;
+    let height = (read_u16(&data[8..]) & 0x3fff) as u32;
+    if width == 0 || height == 0 {
+        return None
+    }
+    // The planes cover whole macroblocks.
+    rgba_size((width + 15) & !15, (height + 15) & !15)?;
+    let first_partition = data.get(10..10 + first_partition_size)?;
+    let mut decoder = LossyDecoder::new(first_partition, width, height);
+    decoder.read_frame_header(&data[10 + first_partition_size..])?;
+    decoder.decode_macroblocks();
+    decoder.apply_loop_filter();
+
+    Some(LossyFrame {
+        width: width,
+        height: height,
+        stride: decoder.mb_width * 16,
+        y: decoder.y,
+        u: decoder.u,
+        v: decoder.v,
+    })
+}
+
+impl<'a> LossyDecoder<'a> {
+    fn new(first_partition: &'a [u8], width: u32, height: u32) -> LossyDecoder<'a> {
+        let mb_width = (width as usize + 15) / 16;
+        let mb_height = (height as usize + 15) / 16;
+        LossyDecoder {
+            header: BoolDecoder::new(first_partition),
+            partitions: vec![],
+            mb_width: mb_width,
+            mb_height: mb_height,
+            segment_map_update: false,
+            segment_probs: [255; 3],
+            dequantization: [Dequantization::default(); 4],
+            filter_levels: [0; 4],
+            simple_filter: false,
+            sharpness: 0,
+            filter_deltas: None,
+            coeff_probs: DEFAULT_COEFF_PROBS,
+            skip_prob: None,
+            intra_above: vec![[B_DC_PRED; 4]; mb_width],
+            intra_left: [B_DC_PRED; 4],
+            nonzero_above: vec![NonZeroContext::default(); mb_width],
+            nonzero_left: NonZeroContext::default(),
+            y: vec![0; mb_width * mb_height * 256],
+            u: vec![0; mb_width * mb_height * 64],
+            v: vec![0; mb_width * mb_height * 64],
+            filters: vec![MacroblockFilter::default(); mb_width * mb_height],
+        }
+    }
+
+    /// Reads the frame header from the first partition, and sets up the other partitions
+    /// from `data`, which follows the first partition.
+    fn read_frame_header(&mut self, data: &'a [u8]) -> Option<()> {
+        let header = &mut self.header;
+        let _color_space = header.read_flag();
+        let _clamping_type = header.read_flag();
+
+        let mut segment_quantizers = None;
+        let mut segment_filter_levels = None;
+        let mut absolute_segment_values = false;
+        if header.read_flag() {
+            self.segment_map_update = header.read_flag();
+            if header.read_flag() {
+                absolute_segment_values = header.read_flag();
+                let mut quantizers = [0; 4];
+                for quantizer in &mut quantizers {
+                    *quantizer = header.read_optional_signed(7);
+                }
+                let mut filter_levels = [0; 4];
+                for filter_level in &mut filter_levels {
+                    *filter_level = header.read_optional_signed(6);
+                }
+                segment_quantizers = Some(quantizers);
+                segment_filter_levels = Some(filter_levels);
+            }
+            if self.segment_map_update {
+                for probability in &mut self.segment_probs {
+                    *probability = if header.read_flag() { header.read_literal(8) as u8 } else { 255 };
+                }
+            }
+        }
+
+        self.simple_filter = header.read_flag();
+        let filter_level = header.read_literal(6) as i32;
+        self.sharpness = header.read_literal(3) as i32;
+        if header.read_flag() {
+            let (mut reference_deltas, mut mode_deltas) = ([0; 4], [0; 4]);
+            if header.read_flag() {
+                for delta in reference_deltas.iter_mut().chain(mode_deltas.iter_mut()) {
+                    if header.read_flag() {
+                        *delta = header.read_signed(6);
+                    }
+                }
+            }
+            self.filter_deltas = Some((reference_deltas[0], mode_deltas[0]));
+        }
+        for (segment, level) in self.filter_levels.iter_mut().enumerate() {
+            *level = match segment_filter_levels {
+                Some(levels) if absolute_segment_values => levels[segment],
+                Some(levels) => filter_level + levels[segment],
+                None => filter_level,
+            };
+        }
+
+        let partition_count = 1 << header.read_literal(2);
+        let sizes_length = 3 * (partition_count - 1);
+        let sizes = data.get(..sizes_length)?;
+        let mut partitions = &data[sizes_length..];
+        for index in 0..partition_count {
+            let size = if index + 1 < partition_count {
+                read_u24(&sizes[index * 3..]) as usize
+            } else {
+                partitions.len()
+            };
+            let partition = partitions.get(..size)?;
+            self.partitions.push(BoolDecoder::new(partition));
+            partitions = &partitions[size..];
+        }
+
+        let base_quantizer = header.read_literal(7) as i32;
+        let y_dc_delta = header.read_optional_signed(4);
+        let y2_dc_delta = header.read_optional_signed(4);
+        let y2_ac_delta = header.read_optional_signed(4);
+        let uv_dc_delta = header.read_optional_signed(4);
+        let uv_ac_delta = header.read_optional_signed(4);
+        for (segment, dequantization) in self.dequantization.iter_mut().enumerate() {
+            let quantizer = match segment_quantizers {
+                Some(quantizers) if absolute_segment_values => quantizers[segment],
+                Some(quantizers) => base_quantizer + quantizers[segment],
+                None => base_quantizer,
+            };
+            let index = |delta: i32| cmp::max(0, cmp::min(127, quantizer + delta)) as usize;
+            *dequantization = Dequantization {
+                y: (DC_QUANT[index(y_dc_delta)], AC_QUANT[index(0)]),
+                y2: (DC_QUANT[index(y2_dc_delta)] * 2, cmp::max(8, AC_QUANT[index(y2_ac_delta)] * 155 / 100)),
+                uv: (cmp::min(132, DC_QUANT[index(uv_dc_delta)]), AC_QUANT[index(uv_ac_delta)]),
+            };
+        }
+
+        let _refresh_entropy_probs = header.read_flag();
+        for (block_type, bands) in COEFF_UPDATE_PROBS.iter().enumerate() {
+            for (band, contexts) in bands.iter().enumerate() {
+                for (context, probabilities) in contexts.iter().enumerate() {
+                    for (token, &probability) in probabilities.iter().enumerate() {
+                        if header.read_bool(probability) {
+                            self.coeff_probs[block_type][band][context][token] = header.read_literal(8) as u8;
+                        }
+                    }
+                }
+            }
+        }
+
+        if header.read_flag() {
+            self.skip_prob = Some(header.read_literal(8) as u8);
+        }
+        Some(())
+    }
+
+    fn decode_macroblocks(&mut self) {
+        for mb_y in 0..self.mb_height {
+            self.intra_left = [B_DC_PRED; 4];
+            self.nonzero_left = NonZeroContext::default();
+            for mb_x in 0..self.mb_width {
+                self.decode_macroblock(mb_x, mb_y);
+            }
+        }
+    }
+
+    fn decode_macroblock(&mut self, mb_x: usize, mb_y: usize) {
+        let segment = if self.segment_map_update {
+            self.header.read_tree(&SEGMENT_TREE, &self.segment_probs) as usize
+        } else {
+            0
+        };
+        let skip = match self.skip_prob {
+            Some(probability) => self.header.read_bool(probability),
+            None => false,
+        };
+
+        let y_mode = self.header.read_tree(&KEYFRAME_YMODE_TREE, &KEYFRAME_YMODE_PROBS);
+        let mut subblock_modes = [B_DC_PRED; 16];
+        if y_mode == B_PRED {
+            for index in 0..16 {
+                let (x, y) = (index % 4, index / 4);
+                let above = if y == 0 { self.intra_above[mb_x][x] } else { subblock_modes[index - 4] };
+                let left = if x == 0 { self.intra_left[y] } else { subblock_modes[index - 1] };
+                subblock_modes[index] =
+                    self.header.read_tree(&BMODE_TREE, &KEYFRAME_BMODE_PROBS[above as usize][left as usize]);
+            }
+        } else {
+            let implied_mode = match y_mode {
+                V_PRED => B_VE_PRED,
+                H_PRED => B_HE_PRED,
+                TM_PRED => B_TM_PRED,
+                _ => B_DC_PRED,
+            };
+            subblock_modes = [implied_mode; 16];
+        }
+        for index in 0..4 {
+            self.intra_above[mb_x][index] = subblock_modes[12 + index];
+            self.intra_left[index] = subblock_modes[index * 4 + 3];
+        }
+        let uv_mode = self.header.read_tree(&UV_MODE_TREE, &KEYFRAME_UV_MODE_PROBS);
+
+        // Blocks 0 to 15 are luma, 16 to 19 blue chroma and 20 to 23 red chroma.
+        let mut coefficients = [0i32; 24 * 16];
+        let has_coefficients = if skip {
+            // The context of the second order block only changes in macroblocks that have one.
+            let (above, left) = (&mut self.nonzero_above[mb_x], &mut self.nonzero_left);
+            let (above_y2, left_y2) = if y_mode == B_PRED { (above.y2, left.y2) } else { (0, 0) };
+            *above = NonZeroContext { y2: above_y2, ..NonZeroContext::default() };
+            *left = NonZeroContext { y2: left_y2, ..NonZeroContext::default() };
+            false
+        } else {
+            let partition = mb_y % self.partitions.len();
+            self.read_residuals(partition, mb_x, segment, y_mode != B_PRED, &mut coefficients);
+            coefficients.iter().any(|&coefficient| coefficient != 0)
+        };
+
+        self.reconstruct_luma(mb_x, mb_y, y_mode, &subblock_modes, &coefficients[..256]);
+        self.reconstruct_chroma(mb_x, mb_y, uv_mode, &coefficients[256..]);
+
+        let mut level = self.filter_levels[segment];
+        if let Some((reference_delta, mode_delta)) = self.filter_deltas {
+            level += reference_delta;
+            if y_mode == B_PRED {
+                level += mode_delta;
+            }
+        }
+        self.filters[mb_y * self.mb_width + mb_x] = MacroblockFilter {
+            level: cmp::max(0, cmp::min(63, level)),
+            inner: y_mode == B_PRED || has_coefficients,
+        };
+    }
+
+    fn read_residuals(&mut self,
+                      partition: usize,
+                      mb_x: usize,
+                      segment: usize,
+                      has_y2: bool,
+                      coefficients: &mut [i32; 24 * 16]) {
+        let dequantization = self.dequantization[segment];
+        let probs = &self.coeff_probs;
+        let decoder = &mut self.partitions[partition];
+        let above = &mut self.nonzero_above[mb_x];
+        let left = &mut self.nonzero_left;
+
+        let (y_type, first) = if has_y2 {
+            let mut y2 = [0i32; 16];
+            let context = (above.y2 + left.y2) as usize;
+            let end = read_coefficients(decoder, &probs[BLOCK_Y2], context, 0, dequantization.y2, &mut y2);
+            above.y2 = (end > 0) as u8;
+            left.y2 = above.y2;
+            let dc = inverse_walsh_hadamard_transform(&y2);
+            for (block, &value) in dc.iter().enumerate() {
+                coefficients[block * 16] = value;
+            }
+            (BLOCK_Y_AFTER_Y2, 1)
+        } else {
+            (BLOCK_Y_WITH_DC, 0)
+        };
+
+        for block in 0..16 {
+            let (x, y) = (block % 4, block / 4);
+            let context = (above.y[x] + left.y[y]) as usize;
+            let end = read_coefficients(decoder, &probs[y_type], context, first, dequantization.y,
+                                        &mut coefficients[block * 16..(block + 1) * 16]);
+            above.y[x] = (end > first) as u8;
+            left.y[y] = above.y[x];
+        }
+
+        for plane in 0..2 {
+            for block in 0..4 {
+                let (x, y) = (block % 2, block / 2);
+                let (above_context, left_context) = if plane == 0 {
+                    (&mut above.u, &mut left.u)
+                } else {
+                    (&mut above.v, &mut left.v)
+                };
+                let context = (above_context[x] + left_context[y]) as usize;
+                let offset = (16 + plane * 4 + block) * 16;
+                let end = read_coefficients(decoder, &probs[BLOCK_CHROMA], context, 0, dequantization.uv,
+                                            &mut coefficients[offset..offset + 16]);
+                above_context[x] = (end > 0) as u8;
+                left_context[y] = above_context[x];
+            }
+        }
+    }
+
+    fn reconstruct_luma(&mut self, mb_x: usize, mb_y: usize, mode: i8, subblock_modes: &[i8; 16],
+                        coefficients: &[i32]) {
+        let stride = self.mb_width * 16;
+        let (x0, y0) = (mb_x * 16, mb_y * 16);
+
+        if mode != B_PRED {
+            let edges = Edges::new(&self.y, stride, x0, y0, 16, mb_x == self.mb_width - 1);
+            let mut prediction = [0u8; 256];
+            predict_block(&mut prediction, 16, mode, &edges, mb_x > 0, mb_y > 0);
+            for row in 0..16 {
+                self.y[(y0 + row) * stride + x0..][..16].copy_from_slice(&prediction[row * 16..][..16]);
+            }
+            for block in 0..16 {
+                let (x, y) = (x0 + block % 4 * 4, y0 + block / 4 * 4);
+                add_residual(&mut self.y, stride, x, y, &coefficients[block * 16..(block + 1) * 16]);
+            }
+            return
+        }
+
+        // The pixels above and to the right of the macroblock, which the subblocks on its right
+        // use in place of the pixels of the next macroblock, not decoded yet.
+        let above_right = Edges::new(&self.y, stride, x0, y0, 16, mb_x == self.mb_width - 1).above_right;
+        for block in 0..16 {
+            let (bx, by) = (block % 4, block / 4);
+            let (x, y) = (x0 + bx * 4, y0 + by * 4);
+            let mut edges = Edges::new(&self.y, stride, x, y, 4, false);
+            if bx == 3 {
+                edges.above_right = above_right;
+            }
+            let mut prediction = [0u8; 16];
+            predict_subblock(&mut prediction, subblock_modes[block], &edges);
+            for row in 0..4 {
+                self.y[(y + row) * stride + x..][..4].copy_from_slice(&prediction[row * 4..][..4]);
+            }
+            add_residual(&mut self.y, stride, x, y, &coefficients[block * 16..(block + 1) * 16]);
+        }
+    }
+
+    fn reconstruct_chroma(&mut self, mb_x: usize, mb_y: usize, mode: i8, coefficients: &[i32]) {
+        let stride = self.mb_width * 8;
+        let (x0, y0) = (mb_x * 8, mb_y * 8);
+        for (index, plane) in [&mut self.u, &mut self.v].iter_mut().enumerate() {
+            let edges = Edges::new(plane, stride, x0, y0, 8, false);
+            let mut prediction = [0u8; 64];
+            predict_block(&mut prediction, 8, mode, &edges, mb_x > 0, mb_y > 0);
+            for row in 0..8 {
+                plane[(y0 + row) * stride + x0..][..8].copy_from_slice(&prediction[row * 8..][..8]);
+            }
+            for block in 0..4 {
+                let (x, y) = (x0 + block % 2 * 4, y0 + block / 2 * 4);
+                let offset = (index * 4 + block) * 16;
+                add_residual(plane, stride, x, y, &coefficients[offset..offset + 16]);
+            }
+        }
+    }
+
+    fn apply_loop_filter(&mut self) {
+        for mb_y in 0..self.mb_height {
+            for mb_x in 0..self.mb_width {
+                let filter = self.filters[mb_y * self.mb_width + mb_x];
+                if filter.level == 0 {
+                    continue
+                }
+
+                let (level, inner) = (filter.level, filter.inner);
+                let mut interior_limit = level;
+                if self.sharpness > 0 {
+                    interior_limit >>= if self.sharpness > 4 { 2 } else { 1 };
+                    interior_limit = cmp::min(interior_limit, 9 - self.sharpness);
+                }
+                let interior_limit = cmp::max(1, interior_limit);
+                let edge_limit = (level + 2) * 2 + interior_limit;
+                let subblock_edge_limit = level * 2 + interior_limit;
+                let hev_threshold = if level >= 40 { 2 } else if level >= 15 { 1 } else { 0 };
+                let filter = EdgeFilter {
+                    simple: self.simple_filter,
+                    interior_limit: interior_limit,
+                    hev_threshold: hev_threshold,
+                };
+
+                let stride = self.mb_width * 16;
+                let chroma_stride = self.mb_width * 8;
+                let (x, y) = (mb_x * 16, mb_y * 16);
+                let (chroma_x, chroma_y) = (mb_x * 8, mb_y * 8);
+                let luma = y * stride + x;
+                let chroma = chroma_y * chroma_stride + chroma_x;
+
+                // The left edge, the vertical edges inside, the top edge, then the horizontal
+                // edges inside. The simple filter only applies to luma.
+                if mb_x > 0 {
+                    filter.macroblock_edge(&mut self.y, luma, 1, stride, 16, edge_limit);
+                    if !self.simple_filter {
+                        filter.macroblock_edge(&mut self.u, chroma, 1, chroma_stride, 8, edge_limit);
+                        filter.macroblock_edge(&mut self.v, chroma, 1, chroma_stride, 8, edge_limit);
+                    }
+                }
+                if inner {
+                    for offset in &[4, 8, 12] {
+                        filter.subblock_edge(&mut self.y, luma + offset, 1, stride, 16, subblock_edge_limit);
+                    }
+                    if !self.simple_filter {
+                        filter.subblock_edge(&mut self.u, chroma + 4, 1, chroma_stride, 8, subblock_edge_limit);
+                        filter.subblock_edge(&mut self.v, chroma + 4, 1, chroma_stride, 8, subblock_edge_limit);
+                    }
+                }
+                if mb_y > 0 {
+                    filter.macroblock_edge(&mut self.y, luma, stride, 1, 16, edge_limit);
+                    if !self.simple_filter {
+                        filter.macroblock_edge(&mut self.u, chroma, chroma_stride, 1, 8, edge_limit);
+                        filter.macroblock_edge(&mut self.v, chroma, chroma_stride, 1, 8, edge_limit);
+                    }
+                }
+                if inner {
+                    for offset in &[4, 8, 12] {
+                        filter.subblock_edge(&mut self.y, luma + offset * stride, stride, 1, 16, subblock_edge_limit);
+                    }
+                    if !self.simple_filter {
+                        let offset = 4 * chroma_stride;
+                        filter.subblock_edge(&mut self.u, chroma + offset, chroma_stride, 1, 8, subblock_edge_limit);
+                        filter.subblock_edge(&mut self.v, chroma + offset, chroma_stride, 1, 8, subblock_edge_limit);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Decodes the tokens of a block of coefficients, and returns the index that follows the last
+/// token, which is `first` if the block is empty.
+fn read_coefficients(decoder: &mut BoolDecoder,
+                     probs: &[[[u8; 11]; 3]; 8],
+                     context: usize,
+                     first: usize,
+                     (dc_factor, ac_factor): (i32, i32),
+                     output: &mut [i32]) -> usize {
+    let mut index = first;
+    let mut p = &probs[COEFF_BANDS[index]][context];
+    while index < 16 {
+        if !decoder.read_bool(p[0]) {
+            // The end of the block.
+            return index
+        }
+        while !decoder.read_bool(p[1]) {
+            // A zero, which can't be followed by the end of the block.
+            index += 1;
+            if index == 16 {
+                return 16
+            }
+            p = &probs[COEFF_BANDS[index]][0];
+        }
+
+        let (value, next_context) = if !decoder.read_bool(p[2]) {
+            (1, 1)
+        } else {
+            let value = if !decoder.read_bool(p[3]) {
+                if !decoder.read_bool(p[4]) { 2 } else { 3 + decoder.read_bool(p[5]) as i32 }
+            } else if !decoder.read_bool(p[6]) {
+                if !decoder.read_bool(p[7]) {
+                    5 + decoder.read_bool(159) as i32
+                } else {
+                    7 + 2 * decoder.read_bool(165) as i32 + decoder.read_bool(145) as i32
+                }
+            } else {
+                let category = if !decoder.read_bool(p[8]) {
+                    2 + decoder.read_bool(p[9]) as usize
+                } else {
+                    4 + decoder.read_bool(p[10]) as usize
+                };
+                let extra = DCT_CATEGORY_PROBS[category].iter()
+                                                        .fold(0, |extra, &p| extra << 1 | decoder.read_bool(p) as i32);
+                DCT_CATEGORY_BASE[category] + extra
+            };
+            (value, 2)
+        };
+        let value = if decoder.read_flag() { -value } else { value };
+        let factor = if index > 0 { ac_factor } else { dc_factor };
+        output[ZIGZAG[index]] = value * factor;
+
+        index += 1;
+        p = &probs[COEFF_BANDS[index]][next_context];
+    }
+    16
+}
+
+fn inverse_walsh_hadamard_transform(input: &[i32; 16]) -> [i32; 16] {
+    let mut temporary = [0i32; 16];
+    for i in 0..4 {
+        let a1 = input[i] + input[12 + i];
+        let b1 = input[4 + i] + input[8 + i];
+        let c1 = input[4 + i] - input[8 + i];
+        let d1 = input[i] - input[12 + i];
+        temporary[i] = a1 + b1;
+        temporary[4 + i] = c1 + d1;
+        temporary[8 + i] = a1 - b1;
+        temporary[12 + i] = d1 - c1;
+    }
+    let mut output = [0i32; 16];
+    for i in 0..4 {
+        let row = &temporary[i * 4..i * 4 + 4];
+        let a1 = row[0] + row[3];
+        let b1 = row[1] + row[2];
+        let c1 = row[1] - row[2];
+        let d1 = row[0] - row[3];
+        output[i * 4] = (a1 + b1 + 3) >> 3;
+        output[i * 4 + 1] = (c1 + d1 + 3) >> 3;
+        output[i * 4 + 2] = (a1 - b1 + 3) >> 3;
+        output[i * 4 + 3] = (d1 - c1 + 3) >> 3;
+    }
+    output
+}
+
+/// Applies the inverse DCT to a block of coefficients and adds the result to the prediction.
+fn add_residual(plane: &mut [u8], stride: usize, x: usize, y: usize, coefficients: &[i32]) {
+    if coefficients.iter().all(|&coefficient| coefficient == 0) {
+        return
+    }
+    const COS_PI_8_SQRT_2_MINUS_1: i32 = 20091;
+    const SIN_PI_8_SQRT_2: i32 = 35468;
+
+    let mut temporary = [0i32; 16];
+    for i in 0..4 {
+        let (i0, i1, i2, i3) = (coefficients[i], coefficients[4 + i], coefficients[8 + i], coefficients[12 + i]);
+        let a1 = i0 + i2;
+        let b1 = i0 - i2;
+        let c1 = ((i1 * SIN_PI_8_SQRT_2) >> 16) - (i3 + ((i3 * COS_PI_8_SQRT_2_MINUS_1) >> 16));
+        let d1 = (i1 + ((i1 * COS_PI_8_SQRT_2_MINUS_1) >> 16)) + ((i3 * SIN_PI_8_SQRT_2) >> 16);
+        temporary[i] = a1 + d1;
+        temporary[12 + i] = a1 - d1;
+        temporary[4 + i] = b1 + c1;
+        temporary[8 + i] = b1 - c1;
+    }
+    for row in 0..4 {
+        let (i0, i1, i2, i3) = {
+            let values = &temporary[row * 4..row * 4 + 4];
+            (values[0], values[1], values[2], values[3])
+        };
+        let a1 = i0 + i2;
+        let b1 = i0 - i2;
+        let c1 = ((i1 * SIN_PI_8_SQRT_2) >> 16) - (i3 + ((i3 * COS_PI_8_SQRT_2_MINUS_1) >> 16));
+        let d1 = (i1 + ((i1 * COS_PI_8_SQRT_2_MINUS_1) >> 16)) + ((i3 * SIN_PI_8_SQRT_2) >> 16);
+        let residual = [(a1 + d1 + 4) >> 3, (b1 + c1 + 4) >> 3, (b1 - c1 + 4) >> 3, (a1 - d1 + 4) >> 3];
+        let pixels = &mut plane[(y + row) * stride + x..][..4];
+        for (pixel, value) in pixels.iter_mut().zip(residual.iter()) {
+            *pixel = clamp_pixel(*pixel as i32 + value);
+        }
+    }
+}
+
+fn clamp_pixel(value: i32) -> u8 {
+    cmp::max(0, cmp::min(255, value)) as u8
+}
+
+/// The reconstructed pixels around a block that its prediction is made from. Pixels outside
+/// of the frame are 127 above it and 129 to its left.
+struct Edges {
+    above: [u8; 16],
+    above_right: [u8; 4],
+    left: [u8; 16],
+    above_left: u8,
+}
+
+impl Edges {
+    /// `last_column` is whether a macroblock is the last of its row, in which case the pixels
+    /// above and to the right of it repeat the last pixel above it.
+    fn new(plane: &[u8], stride: usize, x: usize, y: usize, size: usize, last_column: bool) -> Edges {
+        let mut edges = Edges {
+            above: [127; 16],
+            above_right: [127; 4],
+            left: [129; 16],
+            above_left: if y == 0 { 127 } else { 129 },
+        };
+        if y > 0 {
+            let above = &plane[(y - 1) * stride..y * stride];
+            edges.above[..size].copy_from_slice(&above[x..x + size]);
+            if last_column {
+                edges.above_right = [above[x + size - 1]; 4];
+            } else if x + size + 4 <= stride {
+                edges.above_right.copy_from_slice(&above[x + size..x + size + 4]);
+            }
+            if x > 0 {
+                edges.above_left = above[x - 1];
+            }
+        }
+        if x > 0 {
+            for row in 0..size {
+                edges.left[row] = plane[(y + row) * stride + x - 1];
+            }
+        }
+        edges
+    }
+}
+
+/// Predicts a whole 16×16 luma or 8×8 chroma block.
+fn predict_block(output: &mut [u8], size: usize, mode: i8, edges: &Edges, has_left: bool, has_above: bool) {
+    let (above, left) = (&edges.above[..size], &edges.left[..size]);
+    match mode {
+        V_PRED => {
+            for row in output.chunks_mut(size) {
+                row.copy_from_slice(above);
+            }
+        }
+        H_PRED => {
+            for (row, &value) in output.chunks_mut(size).zip(left.iter()) {
+                for pixel in row {
+                    *pixel = value;
+                }
+            }
+        }
+        TM_PRED => {
+            for (row, &left) in output.chunks_mut(size).zip(left.iter()) {
+                for (pixel, &above) in row.iter_mut().zip(above.iter()) {
+                    *pixel = clamp_pixel(left as i32 + above as i32 - edges.above_left as i32);
+                }
+            }
+        }
+        _ => {
+            // Only the edges inside the frame count towards the average.
+            let shift = if size == 16 { 3 } else { 2 };
+            let sum = |values: &[u8]| values.iter().map(|&value| value as u32).sum::<u32>();
+            let value = match (has_above, has_left) {
+                (true, true) => (sum(above) + sum(left) + size as u32) >> (shift + 2),
+                (true, false) => (sum(above) + (size as u32 >> 1)) >> (shift + 1),
+                (false, true) => (sum(left) + (size as u32 >> 1)) >> (shift + 1),
+                (false, false) => 128,
+            };
+            for pixel in output {
+                *pixel = value as u8;
+            }
+        }
+    }
+}
+
+/// Predicts a 4×4 luma subblock.
+fn predict_subblock(output: &mut [u8; 16], mode: i8, edges: &Edges) {
+    let average2 = |a: u8, b: u8| ((a as u32 + b as u32 + 1) >> 1) as u8;
+    let average3 = |a: u8, b: u8, c: u8| ((a as u32 + 2 * b as u32 + c as u32 + 2) >> 2) as u8;
+
+    let p = edges.above_left;
+    let a = [edges.above[0], edges.above[1], edges.above[2], edges.above[3],
+             edges.above_right[0], edges.above_right[1], edges.above_right[2], edges.above_right[3]];
+    let l = [edges.left[0], edges.left[1], edges.left[2], edges.left[3]];
+    let mut set = |x: usize, y: usize, value: u8| output[y * 4 + x] = value;
+
+    match mode {
+        B_TM_PRED => {
+            for y in 0..4 {
+                for x in 0..4 {
+                    set(x, y, clamp_pixel(l[y] as i32 + a[x] as i32 - p as i32));
+                }
+            }
+        }
+        B_VE_PRED => {
+            let values = [average3(p, a[0], a[1]), average3(a[0], a[1], a[2]),
+                          average3(a[1], a[2], a[3]), average3(a[2], a[3], a[4])];
+            for y in 0..4 {
+                for x in 0..4 {
+                    set(x, y, values[x]);
+                }
+            }
+        }
+        B_HE_PRED => {
+            let values = [average3(p, l[0], l[1]), average3(l[0], l[1], l[2]),
+                          average3(l[1], l[2], l[3]), average3(l[2], l[3], l[3])];
+            for y in 0..4 {
+                for x in 0..4 {
+                    set(x, y, values[y]);
+                }
+            }
+        }
+        B_LD_PRED => {
+            for y in 0..4 {
+                for x in 0..4 {
+                    let i = x + y;
+                    let value = if i < 6 { average3(a[i], a[i + 1], a[i + 2]) } else { average3(a[6], a[7], a[7]) };
+                    set(x, y, value);
+                }
+            }
+        }
+        B_RD_PRED => {
+            // The edge from the bottom left to the top right.
+            let e = [l[3], l[2], l[1], l[0], p, a[0], a[1], a[2], a[3]];
+            for y in 0..4 {
+                for x in 0..4 {
+                    let i = 3 - y + x;
+                    set(x, y, average3(e[i], e[i + 1], e[i + 2]));
+                }
+            }
+        }
+        B_VR_PRED => {
+            set(0, 3, average3(l[2], l[1], l[0]));
+            set(0, 2, average3(l[1], l[0], p));
+            set(0, 1, average3(l[0], p, a[0]));
+            set(1, 3, average3(l[0], p, a[0]));
+            set(1, 1, average3(p, a[0], a[1]));
+            set(2, 3, average3(p, a[0], a[1]));
+            set(2, 1, average3(a[0], a[1], a[2]));
+            set(3, 3, average3(a[0], a[1], a[2]));
+            set(3, 1, average3(a[1], a[2], a[3]));
+            set(0, 0, average2(p, a[0]));
+            set(1, 2, average2(p, a[0]));
+            set(1, 0, average2(a[0], a[1]));
+            set(2, 2, average2(a[0], a[1]));
+            set(2, 0, average2(a[1], a[2]));
+            set(3, 2, average2(a[1], a[2]));
+            set(3, 0, average2(a[2], a[3]));
+        }
+        B_VL_PRED => {
+            set(0, 0, average2(a[0], a[1]));
+            set(1, 0, average2(a[1], a[2]));
+            set(0, 2, average2(a[1], a[2]));
+            set(2, 0, average2(a[2], a[3]));
+            set(1, 2, average2(a[2], a[3]));
+            set(3, 0, average2(a[3], a[4]));
+            set(2, 2, average2(a[3], a[4]));
+            set(0, 1, average3(a[0], a[1], a[2]));
+            set(1, 1, average3(a[1], a[2], a[3]));
+            set(0, 3, average3(a[1], a[2], a[3]));
+            set(2, 1, average3(a[2], a[3], a[4]));
+            set(1, 3, average3(a[2], a[3], a[4]));
+            set(3, 1, average3(a[3], a[4], a[5]));
+            set(2, 3, average3(a[3], a[4], a[5]));
+            set(3, 2, average3(a[4], a[5], a[6]));
+            set(3, 3, average3(a[5], a[6], a[7]));
+        }
+        B_HD_PRED => {
+            set(0, 0, average2(l[0], p));
+            set(2, 1, average2(l[0], p));
+            set(0, 1, average2(l[1], l[0]));
+            set(2, 2, average2(l[1], l[0]));
+            set(0, 2, average2(l[2], l[1]));
+            set(2, 3, average2(l[2], l[1]));
+            set(0, 3, average2(l[3], l[2]));
+            set(3, 0, average3(a[0], a[1], a[2]));
+            set(2, 0, average3(p, a[0], a[1]));
+            set(1, 0, average3(l[0], p, a[0]));
+            set(3, 1, average3(l[0], p, a[0]));
+            set(1, 1, average3(l[1], l[0], p));
+            set(3, 2, average3(l[1], l[0], p));
+            set(1, 2, average3(l[2], l[1], l[0]));
+            set(3, 3, average3(l[2], l[1], l[0]));
+            set(1, 3, average3(l[3], l[2], l[1]));
+        }
+        B_HU_PRED => {
+            set(0, 0, average2(l[0], l[1]));
+            set(2, 0, average2(l[1], l[2]));
+            set(0, 1, average2(l[1], l[2]));
+            set(2, 1, average2(l[2], l[3]));
+            set(0, 2, average2(l[2], l[3]));
+            set(1, 0, average3(l[0], l[1], l[2]));
+            set(3, 0, average3(l[1], l[2], l[3]));
+            set(1, 1, average3(l[1], l[2], l[3]));
+            set(3, 1, average3(l[2], l[3], l[3]));
+            set(1, 2, average3(l[2], l[3], l[3]));
+            for &(x, y) in &[(3, 2), (2, 2), (0, 3), (1, 3), (2, 3), (3, 3)] {
+                set(x, y, l[3]);
+            }
+        }
+        _ => {
+            let sum = a[..4].iter().chain(l.iter()).map(|&value| value as u32).sum::<u32>();
+            let value = ((sum + 4) >> 3) as u8;
+            for index in 0..16 {
+                set(index % 4, index / 4, value);
+            }
+        }
+    }
+}
+
+/// The loop filter of one macroblock.
+struct EdgeFilter {
+    simple: bool,
+    interior_limit: i32,
+    hev_threshold: i32,
+}
+
+/// Clamps a value to the range of a signed byte.
+fn clamp_signed(value: i32) -> i32 {
+    cmp::max(-128, cmp::min(127, value))
+}
+
+impl EdgeFilter {
+    /// Filters `length` segments across an edge, starting at the first pixel after the edge at
+    /// `position`. `step` goes across the edge, and `along` to the next segment.
+    fn macroblock_edge(&self, plane: &mut [u8], position: usize, step: usize, along: usize, length: usize,
+                       edge_limit: i32) {
+        for segment in 0..length {
+            let q0 = position + segment * along;
+            if self.simple {
+                if self.simple_threshold(plane, q0, step, edge_limit) {
+                    common_adjust(plane, q0, step, true);
+                }
+            } else if self.normal_threshold(plane, q0, step, edge_limit) {
+                if high_edge_variance(plane, q0, step, self.hev_threshold) {
+                    common_adjust(plane, q0, step, true);
+                } else {
+                    let (p2, p1, p0, q0_value, q1, q2) = {
+                        let signed = |index: usize| plane[index] as i32 - 128;
+                        (signed(q0 - 3 * step), signed(q0 - 2 * step), signed(q0 - step),
+                         signed(q0), signed(q0 + step), signed(q0 + 2 * step))
+                    };
+                    let w = clamp_signed(clamp_signed(p1 - q1) + 3 * (q0_value - p0));
+                    let unsigned = |value: i32| (clamp_signed(value) + 128) as u8;
+
+                    let a = clamp_signed((27 * w + 63) >> 7);
+                    plane[q0] = unsigned(q0_value - a);
+                    plane[q0 - step] = unsigned(p0 + a);
+                    let a = clamp_signed((18 * w + 63) >> 7);
+                    plane[q0 + step] = unsigned(q1 - a);
+                    plane[q0 - 2 * step] = unsigned(p1 + a);
+                    let a = clamp_signed((9 * w + 63) >> 7);
+                    plane[q0 + 2 * step] = unsigned(q2 - a);
+                    plane[q0 - 3 * step] = unsigned(p2 + a);
+                }
+            }
+        }
+    }
+
+    fn subblock_edge(&self, plane: &mut [u8], position: usize, step: usize, along: usize, length: usize,
+                     edge_limit: i32) {
+        for segment in 0..length {
+            let q0 = position + segment * along;
+            if self.simple {
+                if self.simple_threshold(plane, q0, step, edge_limit) {
+                    common_adjust(plane, q0, step, true);
+                }
+            } else if self.normal_threshold(plane, q0, step, edge_limit) {
+                let (p1, q1) = (plane[q0 - 2 * step] as i32 - 128, plane[q0 + step] as i32 - 128);
+                let high_variance = high_edge_variance(plane, q0, step, self.hev_threshold);
+                let a = (common_adjust(plane, q0, step, high_variance) + 1) >> 1;
+                if !high_variance {
+                    plane[q0 + step] = (clamp_signed(q1 - a) + 128) as u8;
+                    plane[q0 - 2 * step] = (clamp_signed(p1 + a) + 128) as u8;
+                }
+            }
+        }
+    }
+
+    fn simple_threshold(&self, plane: &[u8], q0: usize, step: usize, edge_limit: i32) -> bool {
+        let pixel = |index: usize| plane[index] as i32;
+        (pixel(q0 - step) - pixel(q0)).abs() * 2 + ((pixel(q0 - 2 * step) - pixel(q0 + step)).abs() >> 1) <= edge_limit
+    }
+
+    fn normal_threshold(&self, plane: &[u8], q0: usize, step: usize, edge_limit: i32) -> bool {
+        let pixel = |index: usize| plane[index] as i32;
+        let (p3, p2, p1, p0) = (pixel(q0 - 4 * step), pixel(q0 - 3 * step), pixel(q0 - 2 * step), pixel(q0 - step));
+        let (q0, q1, q2, q3) = (pixel(q0), pixel(q0 + step), pixel(q0 + 2 * step), pixel(q0 + 3 * step));
+        let limit = self.interior_limit;
+        (p0 - q0).abs() * 2 + ((p1 - q1).abs() >> 1) <= edge_limit &&
+            (p3 - p2).abs() <= limit && (p2 - p1).abs() <= limit && (p1 - p0).abs() <= limit &&
+            (q3 - q2).abs() <= limit && (q2 - q1).abs() <= limit && (q1 - q0).abs() <= limit
+    }
+}
+
+fn high_edge_variance(plane: &[u8], q0: usize, step: usize, threshold: i32) -> bool {
+    let pixel = |index: usize| plane[index] as i32;
+    (pixel(q0 - 2 * step) - pixel(q0 - step)).abs() > threshold || (pixel(q0 + step) - pixel(q0)).abs() > threshold
+}
+
+/// Moves the two pixels nearest to an edge towards each other, and returns the adjustment.
+fn common_adjust(plane: &mut [u8], q0: usize, step: usize, use_outer_taps: bool) -> i32 {
+    let signed = |value: u8| value as i32 - 128;
+    let (p1, p0) = (signed(plane[q0 - 2 * step]), signed(plane[q0 - step]));
+    let (q0_value, q1) = (signed(plane[q0]), signed(plane[q0 + step]));
+    let outer = if use_outer_taps { clamp_signed(p1 - q1) } else { 0 };
+    let a = clamp_signed(outer + 3 * (q0_value - p0));
+    let b = clamp_signed(a + 3) >> 3;
+    let a = clamp_signed(a + 4) >> 3;
+    plane[q0] = (clamp_signed(q0_value - a) + 128) as u8;
+    plane[q0 - step] = (clamp_signed(p0 + b) + 128) as u8;
+    a
+}
+
+
+// ======================================================================
+// Tables.
+// ======================================================================
+
+/// The probabilities of the subblock intra modes of key frames, by the modes of the subblocks
+/// above and to the left.
+static KEYFRAME_BMODE_PROBS: [[[u8; 9]; 10]; 10] = [
+    [
+        [231, 120, 48, 89, 115, 113, 120, 152, 112],
+        [152, 179, 64, 126, 170, 118, 46, 70, 95],
+        [175, 69, 143, 80, 85, 82, 72, 155, 103],
+        [56, 58, 10, 171, 218, 189, 17, 13, 152],
+        [144, 71, 10, 38, 171, 213, 144, 34, 26],
+        [114, 26, 17, 163, 44, 195, 21, 10, 173],
+        [121, 24, 80, 195, 26, 62, 44, 64, 85],
+        [170, 46, 55, 19, 136, 160, 33, 206, 71],
+        [63, 20, 8, 114, 114, 208, 12, 9, 226],
+        [81, 40, 11, 96, 182, 84, 29, 16, 36],
+    ],
+    [
+        [134, 183, 89, 137, 98, 101, 106, 165, 148],
+        [72, 187, 100, 130, 157, 111, 32, 75, 80],
+        [66, 102, 167, 99, 74, 62, 40, 234, 128],
+        [41, 53, 9, 178, 241, 141, 26, 8, 107],
+        [104, 79, 12, 27, 217, 255, 87, 17, 7],
+        [74, 43, 26, 146, 73, 166, 49, 23, 157],
+        [65, 38, 105, 160, 51, 52, 31, 115, 128],
+        [87, 68, 71, 44, 114, 51, 15, 186, 23],
+        [47, 41, 14, 110, 182, 183, 21, 17, 194],
+        [66, 45, 25, 102, 197, 189, 23, 18, 22],
+    ],
+    [
+        [88, 88, 147, 150, 42, 46, 45, 196, 205],
+        [43, 97, 183, 117, 85, 38, 35, 179, 61],
+        [39, 53, 200, 87, 26, 21, 43, 232, 171],
+        [56, 34, 51, 104, 114, 102, 29, 93, 77],
+        [107, 54, 32, 26, 51, 1, 81, 43, 31],
+        [39, 28, 85, 171, 58, 165, 90, 98, 64],
+        [34, 22, 116, 206, 23, 34, 43, 166, 73],
+        [68, 25, 106, 22, 64, 171, 36, 225, 114],
+        [34, 19, 21, 102, 132, 188, 16, 76, 124],
+        [62, 18, 78, 95, 85, 57, 50, 48, 51],
+    ],
+    [
+        [193, 101, 35, 159, 215, 111, 89, 46, 111],
+        [60, 148, 31, 172, 219, 228, 21, 18, 111],
+        [112, 113, 77, 85, 179, 255, 38, 120, 114],
+        [40, 42, 1, 196, 245, 209, 10, 25, 109],
+        [100, 80, 8, 43, 154, 1, 51, 26, 71],
+        [88, 43, 29, 140, 166, 213, 37, 43, 154],
+        [61, 63, 30, 155, 67, 45, 68, 1, 209],
+        [142, 78, 78, 16, 255, 128, 34, 197, 171],
+        [41, 40, 5, 102, 211, 183, 4, 1, 221],
+        [51, 50, 17, 168, 209, 192, 23, 25, 82],
+    ],
+    [
+        [125, 98, 42, 88, 104, 85, 117, 175, 82],
+        [95, 84, 53, 89, 128, 100, 113, 101, 45],
+        [75, 79, 123, 47, 51, 128, 81, 171, 1],
+        [57, 17, 5, 71, 102, 57, 53, 41, 49],
+        [115, 21, 2, 10, 102, 255, 166, 23, 6],
+        [38, 33, 13, 121, 57, 73, 26, 1, 85],
+        [41, 10, 67, 138, 77, 110, 90, 47, 114],
+        [101, 29, 16, 10, 85, 128, 101, 196, 26],
+        [57, 18, 10, 102, 102, 213, 34, 20, 43],
+        [117, 20, 15, 36, 163, 128, 68, 1, 26],
+    ],
+    [
+        [138, 31, 36, 171, 27, 166, 38, 44, 229],
+        [67, 87, 58, 169, 82, 115, 26, 59, 179],
+        [63, 59, 90, 180, 59, 166, 93, 73, 154],
+        [40, 40, 21, 116, 143, 209, 34, 39, 175],
+        [57, 46, 22, 24, 128, 1, 54, 17, 37],
+        [47, 15, 16, 183, 34, 223, 49, 45, 183],
+        [46, 17, 33, 183, 6, 98, 15, 32, 183],
+        [65, 32, 73, 115, 28, 128, 23, 128, 205],
+        [40, 3, 9, 115, 51, 192, 18, 6, 223],
+        [87, 37, 9, 115, 59, 77, 64, 21, 47],
+    ],
+    [
+        [104, 55, 44, 218, 9, 54, 53, 130, 226],
+        [64, 90, 70, 205, 40, 41, 23, 26, 57],
+        [54, 57, 112, 184, 5, 41, 38, 166, 213],
+        [30, 34, 26, 133, 152, 116, 10, 32, 134],
+        [75, 32, 12, 51, 192, 255, 160, 43, 51],
+        [39, 19, 53, 221, 26, 114, 32, 73, 255],
+        [31, 9, 65, 234, 2, 15, 1, 118, 73],
+        [88, 31, 35, 67, 102, 85, 55, 186, 85],
+        [56, 21, 23, 111, 59, 205, 45, 37, 192],
+        [55, 38, 70, 124, 73, 102, 1, 34, 98],
+    ],
+    [
+        [102, 61, 71, 37, 34, 53, 31, 243, 192],
+        [69, 60, 71, 38, 73, 119, 28, 222, 37],
+        [68, 45, 128, 34, 1, 47, 11, 245, 171],
+        [62, 17, 19, 70, 146, 85, 55, 62, 70],
+        [75, 15, 9, 9, 64, 255, 184, 119, 16],
+        [37, 43, 37, 154, 100, 163, 85, 160, 1],
+        [63, 9, 92, 136, 28, 64, 32, 201, 85],
+        [86, 6, 28, 5, 64, 255, 25, 248, 1],
+        [56, 8, 17, 132, 137, 255, 55, 116, 128],
+        [58, 15, 20, 82, 135, 57, 26, 121, 40],
+    ],
+    [
+        [164, 50, 31, 137, 154, 133, 25, 35, 218],
+        [51, 103, 44, 131, 131, 123, 31, 6, 158],
+        [86, 40, 64, 135, 148, 224, 45, 183, 128],
+        [22, 26, 17, 131, 240, 154, 14, 1, 209],
+        [83, 12, 13, 54, 192, 255, 68, 47, 28],
+        [45, 16, 21, 91, 64, 222, 7, 1, 197],
+        [56, 21, 39, 155, 60, 138, 23, 102, 213],
+        [85, 26, 85, 85, 128, 128, 32, 146, 171],
+        [18, 11, 7, 63, 144, 171, 4, 4, 246],
+        [35, 27, 10, 146, 174, 171, 12, 26, 128],
+    ],
+    [
+        [190, 80, 35, 99, 180, 80, 126, 54, 45],
+        [85, 126, 47, 87, 176, 51, 41, 20, 32],
+        [101, 75, 128, 139, 118, 146, 116, 128, 85],
+        [56, 41, 15, 176, 236, 85, 37, 9, 62],
+        [146, 36, 19, 30, 171, 255, 97, 27, 20],
+        [71, 30, 17, 119, 118, 255, 17, 18, 138],
+        [101, 38, 60, 138, 55, 70, 43, 26, 142],
+        [138, 45, 61, 62, 219, 1, 81, 188, 64],
+        [32, 41, 20, 117, 151, 142, 20, 21, 163],
+        [112, 19, 12, 61, 195, 128, 48, 4, 24],
+    ],
+];
+
+/// The probabilities that the coefficient token probabilities are updated in the frame header.
+static COEFF_UPDATE_PROBS: [[[[u8; 11]; 3]; 8]; 4] = [
+    [
+        [
+            [255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+            [255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+            [255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+        ],
+        [
+            [176, 246, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+            [223, 241, 252, 255, 255, 255, 255, 255, 255, 255, 255],
+            [249, 253, 253, 255, 255, 255, 255, 255, 255, 255, 255],
+        ],
+        [
+            [255, 244, 252, 255, 255, 255, 255, 255, 255, 255, 255],
+            [234, 254, 254, 255, 255, 255, 255, 255, 255, 255, 255],
+            [253, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+        ],
+        [
+            [255, 246, 254, 255, 255, 255, 255, 255, 255, 255, 255],
+            [239, 253, 254, 255, 255, 255, 255, 255, 255, 255, 255],
+            [254, 255, 254, 255, 255, 255, 255, 255, 255, 255, 255],
+        ],
+        [
+            [255, 248, 254, 255, 255, 255, 255, 255, 255, 255, 255],
+            [251, 255, 254, 255, 255, 255, 255, 255, 255, 255, 255],
+            [255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+        ],
+        [
+            [255, 253, 254, 255, 255, 255, 255, 255, 255, 255, 255],
+            [251, 254, 254, 255, 255, 255, 255, 255, 255, 255, 255],
+            [254, 255, 254, 255, 255, 255, 255, 255, 255, 255, 255],
+        ],
+        [
+            [255, 254, 253, 255, 254, 255, 255, 255, 255, 255, 255],
+            [250, 255, 254, 255, 254, 255, 255, 255, 255, 255, 255],
+            [254, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+        ],
+        [
+            [255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+            [255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+            [255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+        ],
+    ],
+    [
+        [
+            [217, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+            [225, 252, 241, 253, 255, 255, 254, 255, 255, 255, 255],
+            [234, 250, 241, 250, 253, 255, 253, 254, 255, 255, 255],
+        ],
+        [
+            [255, 254, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+            [223, 254, 254, 255, 255, 255, 255, 255, 255, 255, 255],
+            [238, 253, 254, 254, 255, 255, 255, 255, 255, 255, 255],
+        ],
+        [
+            [255, 248, 254, 255, 255, 255, 255, 255, 255, 255, 255],
+            [249, 254, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+            [255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+        ],
+        [
+            [255, 253, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+            [247, 254, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+            [255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+        ],
+        [
+            [255, 253, 254, 255, 255, 255, 255, 255, 255, 255, 255],
+            [252, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+            [255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+        ],
+        [
+            [255, 254, 254, 255, 255, 255, 255, 255, 255, 255, 255],
+            [253, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+            [255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+        ],
+        [
+            [255, 254, 253, 255, 255, 255, 255, 255, 255, 255, 255],
+            [250, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+            [254, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+        ],
+        [
+            [255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+            [255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+            [255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+        ],
+    ],
+    [
+        [
+            [186, 251, 250, 255, 255, 255, 255, 255, 255, 255, 255],
+            [234, 251, 244, 254, 255, 255, 255, 255, 255, 255, 255],
+            [251, 251, 243, 253, 254, 255, 254, 255, 255, 255, 255],
+        ],
+        [
+            [255, 253, 254, 255, 255, 255, 255, 255, 255, 255, 255],
+            [236, 253, 254, 255, 255, 255, 255, 255, 255, 255, 255],
+            [251, 253, 253, 254, 254, 255, 255, 255, 255, 255, 255],
+        ],
+        [
+            [255, 254, 254, 255, 255, 255, 255, 255, 255, 255, 255],
+            [254, 254, 254, 255, 255, 255, 255, 255, 255, 255, 255],
+            [255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+        ],
+        [
+            [255, 254, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+            [254, 254, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+            [254, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+        ],
+        [
+            [255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+            [254, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+            [255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+        ],
+        [
+            [255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+            [255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+            [255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+        ],
+        [
+            [255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+            [255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+            [255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+        ],
+        [
+            [255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+            [255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+            [255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+        ],
+    ],
+    [
+        [
+            [248, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+            [250, 254, 252, 254, 255, 255, 255, 255, 255, 255, 255],
+            [248, 254, 249, 253, 255, 255, 255, 255, 255, 255, 255],
+        ],
+        [
+            [255, 253, 253, 255, 255, 255, 255, 255, 255, 255, 255],
+            [246, 253, 253, 255, 255, 255, 255, 255, 255, 255, 255],
+            [252, 254, 251, 254, 254, 255, 255, 255, 255, 255, 255],
+        ],
+        [
+            [255, 254, 252, 255, 255, 255, 255, 255, 255, 255, 255],
+            [248, 254, 253, 255, 255, 255, 255, 255, 255, 255, 255],
+            [253, 255, 254, 254, 255, 255, 255, 255, 255, 255, 255],
+        ],
+        [
+            [255, 251, 254, 255, 255, 255, 255, 255, 255, 255, 255],
+            [245, 251, 254, 255, 255, 255, 255, 255, 255, 255, 255],
+            [253, 253, 254, 255, 255, 255, 255, 255, 255, 255, 255],
+        ],
+        [
+            [255, 251, 253, 255, 255, 255, 255, 255, 255, 255, 255],
+            [252, 253, 254, 255, 255, 255, 255, 255, 255, 255, 255],
+            [255, 254, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+        ],
+        [
+            [255, 252, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+            [249, 255, 254, 255, 255, 255, 255, 255, 255, 255, 255],
+            [255, 255, 254, 255, 255, 255, 255, 255, 255, 255, 255],
+        ],
+        [
+            [255, 255, 253, 255, 255, 255, 255, 255, 255, 255, 255],
+            [250, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+            [255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+        ],
+        [
+            [255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+            [254, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+            [255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+        ],
+    ],
+];
+
+/// The default coefficient token probabilities.
+static DEFAULT_COEFF_PROBS: [[[[u8; 11]; 3]; 8]; 4] = [
+    [
+        [
+            [128, 128, 128, 128, 128, 128, 128, 128, 128, 128, 128],
+            [128, 128, 128, 128, 128, 128, 128, 128, 128, 128, 128],
+            [128, 128, 128, 128, 128, 128, 128, 128, 128, 128, 128],
+        ],
+        [
+            [253, 136, 254, 255, 228, 219, 128, 128, 128, 128, 128],
+            [189, 129, 242, 255, 227, 213, 255, 219, 128, 128, 128],
+            [106, 126, 227, 252, 214, 209, 255, 255, 128, 128, 128],
+        ],
+        [
+            [1, 98, 248, 255, 236, 226, 255, 255, 128, 128, 128],
+            [181, 133, 238, 254, 221, 234, 255, 154, 128, 128, 128],
+            [78, 134, 202, 247, 198, 180, 255, 219, 128, 128, 128],
+        ],
+        [
+            [1, 185, 249, 255, 243, 255, 128, 128, 128, 128, 128],
+            [184, 150, 247, 255, 236, 224, 128, 128, 128, 128, 128],
+            [77, 110, 216, 255, 236, 230, 128, 128, 128, 128, 128],
+        ],
+        [
+            [1, 101, 251, 255, 241, 255, 128, 128, 128, 128, 128],
+            [170, 139, 241, 252, 236, 209, 255, 255, 128, 128, 128],
+            [37, 116, 196, 243, 228, 255, 255, 255, 128, 128, 128],
+        ],
+        [
+            [1, 204, 254, 255, 245, 255, 128, 128, 128, 128, 128],
+            [207, 160, 250, 255, 238, 128, 128, 128, 128, 128, 128],
+            [102, 103, 231, 255, 211, 171, 128, 128, 128, 128, 128],
+        ],
+        [
+            [1, 152, 252, 255, 240, 255, 128, 128, 128, 128, 128],
+            [177, 135, 243, 255, 234, 225, 128, 128, 128, 128, 128],
+            [80, 129, 211, 255, 194, 224, 128, 128, 128, 128, 128],
+        ],
+        [
+            [1, 1, 255, 128, 128, 128, 128, 128, 128, 128, 128],
+            [246, 1, 255, 128, 128, 128, 128, 128, 128, 128, 128],
+            [255, 128, 128, 128, 128, 128, 128, 128, 128, 128, 128],
+        ],
+    ],
+    [
+        [
+            [198, 35, 237, 223, 193, 187, 162, 160, 145, 155, 62],
+            [131, 45, 198, 221, 172, 176, 220, 157, 252, 221, 1],
+            [68, 47, 146, 208, 149, 167, 221, 162, 255, 223, 128],
+        ],
+        [
+            [1, 149, 241, 255, 221, 224, 255, 255, 128, 128, 128],
+            [184, 141, 234, 253, 222, 220, 255, 199, 128, 128, 128],
+            [81, 99, 181, 242, 176, 190, 249, 202, 255, 255, 128],
+        ],
+        [
+            [1, 129, 232, 253, 214, 197, 242, 196, 255, 255, 128],
+            [99, 121, 210, 250, 201, 198, 255, 202, 128, 128, 128],
+            [23, 91, 163, 242, 170, 187, 247, 210, 255, 255, 128],
+        ],
+        [
+            [1, 200, 246, 255, 234, 255, 128, 128, 128, 128, 128],
+            [109, 178, 241, 255, 231, 245, 255, 255, 128, 128, 128],
+            [44, 130, 201, 253, 205, 192, 255, 255, 128, 128, 128],
+        ],
+        [
+            [1, 132, 239, 251, 219, 209, 255, 165, 128, 128, 128],
+            [94, 136, 225, 251, 218, 190, 255, 255, 128, 128, 128],
+            [22, 100, 174, 245, 186, 161, 255, 199, 128, 128, 128],
+        ],
+        [
+            [1, 182, 249, 255, 232, 235, 128, 128, 128, 128, 128],
+            [124, 143, 241, 255, 227, 234, 128, 128, 128, 128, 128],
+            [35, 77, 181, 251, 193, 211, 255, 205, 128, 128, 128],
+        ],
+        [
+            [1, 157, 247, 255, 236, 231, 255, 255, 128, 128, 128],
+            [121, 141, 235, 255, 225, 227, 255, 255, 128, 128, 128],
+            [45, 99, 188, 251, 195, 217, 255, 224, 128, 128, 128],
+        ],
+        [
+            [1, 1, 251, 255, 213, 255, 128, 128, 128, 128, 128],
+            [203, 1, 248, 255, 255, 128, 128, 128, 128, 128, 128],
+            [137, 1, 177, 255, 224, 255, 128, 128, 128, 128, 128],
+        ],
+    ],
+    [
+        [
+            [253, 9, 248, 251, 207, 208, 255, 192, 128, 128, 128],
+            [175, 13, 224, 243, 193, 185, 249, 198, 255, 255, 128],
+            [73, 17, 171, 221, 161, 179, 236, 167, 255, 234, 128],
+        ],
+        [
+            [1, 95, 247, 253, 212, 183, 255, 255, 128, 128, 128],
+            [239, 90, 244, 250, 211, 209, 255, 255, 128, 128, 128],
+            [155, 77, 195, 248, 188, 195, 255, 255, 128, 128, 128],
+        ],
+        [
+            [1, 24, 239, 251, 218, 219, 255, 205, 128, 128, 128],
+            [201, 51, 219, 255, 196, 186, 128, 128, 128, 128, 128],
+            [69, 46, 190, 239, 201, 218, 255, 228, 128, 128, 128],
+        ],
+        [
+            [1, 191, 251, 255, 255, 128, 128, 128, 128, 128, 128],
+            [223, 165, 249, 255, 213, 255, 128, 128, 128, 128, 128],
+            [141, 124, 248, 255, 255, 128, 128, 128, 128, 128, 128],
+        ],
+        [
+            [1, 16, 248, 255, 255, 128, 128, 128, 128, 128, 128],
+            [190, 36, 230, 255, 236, 255, 128, 128, 128, 128, 128],
+            [149, 1, 255, 128, 128, 128, 128, 128, 128, 128, 128],
+        ],
+        [
+            [1, 226, 255, 128, 128, 128, 128, 128, 128, 128, 128],
+            [247, 192, 255, 128, 128, 128, 128, 128, 128, 128, 128],
+            [240, 128, 255, 128, 128, 128, 128, 128, 128, 128, 128],
+        ],
+        [
+            [1, 134, 252, 255, 255, 128, 128, 128, 128, 128, 128],
+            [213, 62, 250, 255, 255, 128, 128, 128, 128, 128, 128],
+            [55, 93, 255, 128, 128, 128, 128, 128, 128, 128, 128],
+        ],
+        [
+            [128, 128, 128, 128, 128, 128, 128, 128, 128, 128, 128],
+            [128, 128, 128, 128, 128, 128, 128, 128, 128, 128, 128],
+            [128, 128, 128, 128, 128, 128, 128, 128, 128, 128, 128],
+        ],
+    ],
+    [
+        [
+            [202, 24, 213, 235, 186, 191, 220, 160, 240, 175, 255],
+            [126, 38, 182, 232, 169, 184, 228, 174, 255, 187, 128],
+            [61, 46, 138, 219, 151, 178, 240, 170, 255, 216, 128],
+        ],
+        [
+            [1, 112, 230, 250, 199, 191, 247, 159, 255, 255, 128],
+            [166, 109, 228, 252, 211, 215, 255, 174, 128, 128, 128],
+            [39, 77, 162, 232, 172, 180, 245, 178, 255, 255, 128],
+        ],
+        [
+            [1, 52, 220, 246, 198, 199, 249, 220, 255, 255, 128],
+            [124, 74, 191, 243, 183, 193, 250, 221, 255, 255, 128],
+            [24, 71, 130, 219, 154, 170, 243, 182, 255, 255, 128],
+        ],
+        [
+            [1, 182, 225, 249, 219, 240, 255, 224, 128, 128, 128],
+            [149, 150, 226, 252, 216, 205, 255, 171, 128, 128, 128],
+            [28, 108, 170, 242, 183, 194, 254, 223, 255, 255, 128],
+        ],
+        [
+            [1, 81, 230, 252, 204, 203, 255, 192, 128, 128, 128],
+            [123, 102, 209, 247, 188, 196, 255, 233, 128, 128, 128],
+            [20, 95, 153, 243, 164, 173, 255, 203, 128, 128, 128],
+        ],
+        [
+            [1, 222, 248, 255, 216, 213, 128, 128, 128, 128, 128],
+            [168, 175, 246, 252, 235, 205, 255, 255, 128, 128, 128],
+            [47, 116, 215, 255, 211, 212, 255, 255, 128, 128, 128],
+        ],
+        [
+            [1, 121, 236, 253, 212, 214, 255, 255, 128, 128, 128],
+            [141, 84, 213, 252, 201, 202, 255, 219, 128, 128, 128],
+            [42, 80, 160, 240, 162, 185, 255, 205, 128, 128, 128],
+        ],
+        [
+            [1, 1, 255, 128, 128, 128, 128, 128, 128, 128, 128],
+            [244, 1, 255, 128, 128, 128, 128, 128, 128, 128, 128],
+            [238, 1, 255, 128, 128, 128, 128, 128, 128, 128, 128],
+        ],
+    ],
+];
+
+static DC_QUANT: [i32; 128] = [
+    4, 5, 6, 7, 8, 9, 10, 10, 11, 12, 13, 14, 15, 16, 17, 17,
+    18, 19, 20, 20, 21, 21, 22, 22, 23, 23, 24, 25, 25, 26, 27, 28,
+    29, 30, 31, 32, 33, 34, 35, 36, 37, 37, 38, 39, 40, 41, 42, 43,
+    44, 45, 46, 46, 47, 48, 49, 50, 51, 52, 53, 54, 55, 56, 57, 58,
+    59, 60, 61, 62, 63, 64, 65, 66, 67, 68, 69, 70, 71, 72, 73, 74,
+    75, 76, 76, 77, 78, 79, 80, 81, 82, 83, 84, 85, 86, 87, 88, 89,
+    91, 93, 95, 96, 98, 100, 101, 102, 104, 106, 108, 110, 112, 114, 116, 118,
+    122, 124, 126, 128, 130, 132, 134, 136, 138, 140, 143, 145, 148, 151, 154, 157,
+];
+
+static AC_QUANT: [i32; 128] = [
+    4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
+    20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35,
+    36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50, 51,
+    52, 53, 54, 55, 56, 57, 58, 60, 62, 64, 66, 68, 70, 72, 74, 76,
+    78, 80, 82, 84, 86, 88, 90, 92, 94, 96, 98, 100, 102, 104, 106, 108,
+    110, 112, 114, 116, 119, 122, 125, 128, 131, 134, 137, 140, 143, 146, 149, 152,
+    155, 158, 161, 164, 167, 170, 173, 177, 181, 185, 189, 193, 197, 201, 205, 209,
+    213, 217, 221, 225, 229, 234, 239, 245, 249, 254, 259, 264, 269, 274, 279, 284,
+];
@@ -121,4 +121,36 @@ pub trait ImageCache: Sync + Send {
 
     /// Ensure an image has a webrender key.
     fn set_webrender_image_key(&self, image: &mut Image);
+
+    /// Add a new webrender image showing the first frame of an animated image, so that each
+    /// use of the image can be animated on its own. Returns `None` if the image isn't animated.
+    fn add_webrender_image_animation(&self, image: &Image) -> Option<webrender_api::ImageKey>;
+
+    /// Show the frame at the given index of an animated image in one of its webrender images
+    /// added by `add_webrender_image_animation`.
+    fn set_webrender_image_frame(&self, image: &Image, key: webrender_api::ImageKey, frame: usize);
+
+    /// Return the image for the given URL rasterized at the given size in pixels, if it is a
+    /// loaded vector image. Raster images only have their decoded size, so this returns `None`
+    /// for them.
+    fn get_image_at_size(&self, url: &ServoUrl, width: u32, height: u32) -> Option<Arc<Image>>;
+
+    /// Return the intrinsic dimensions of the image for the given URL, if it is a loaded vector
+    /// image.
+    fn get_intrinsic_dimensions(&self, url: &ServoUrl) -> Option<IntrinsicDimensions>;
+
+    /// Return whether the loaded image for the given URL came from a CORS-same-origin response.
+    /// Only those images may expose their pixels, like when they shape a float area.
+    /// <https://html.spec.whatwg.org/multipage/#cors-same-origin>
+    fn is_cors_same_origin(&self, url: &ServoUrl) -> bool;
+}
+
+/// The intrinsic dimensions of a vector image, in CSS pixels, any of which may be missing.
+/// <https://drafts.csswg.org/css-images-3/#intrinsic-dimensions>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IntrinsicDimensions {
+    pub width: Option<f32>,
+    pub height: Option<f32>,
+    /// The width divided by the height.
+    pub ratio: Option<f32>,
 }
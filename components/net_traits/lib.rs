@@ -6,7 +6,10 @@
 #![deny(unsafe_code)]
 
 extern crate cookie as cookie_rs;
+extern crate cssparser;
 extern crate embedder_traits;
+extern crate euclid;
+extern crate gif;
 extern crate hyper;
 extern crate hyper_serde;
 extern crate image as piston_image;
@@ -23,6 +26,7 @@ extern crate servo_url;
 extern crate url;
 extern crate uuid;
 extern crate webrender_api;
+extern crate xml5ever;
 
 use cookie_rs::Cookie;
 use filemanager_thread::FileManagerThreadMsg;
@@ -56,7 +60,10 @@ pub mod storage_thread;
 /// However, image handling is generally very integrated with the network stack (especially where
 /// caching is involved) and as a result it must live in here.
 pub mod image {
+    pub mod animation;
     pub mod base;
+    pub mod svg;
+    pub mod webp;
 }
 
 /// A loading context, for context-specific sniffing, as defined in
@@ -364,6 +371,12 @@ pub enum CoreResourceMsg {
     GetCookiesForUrl(ServoUrl, IpcSender<Option<String>>, CookieSource),
     /// Get a cookie by name for a given originating URL
     GetCookiesDataForUrl(ServoUrl, IpcSender<Vec<Serde<Cookie<'static>>>>, CookieSource),
+    /// Remove the stored cookies for a given URL, or only those with the given name
+    DeleteCookies(ServoUrl, Option<String>),
+    /// Retrieve the URLs and statuses of the responses cached for the origin of a given URL
+    GetCacheEntries(ServoUrl, IpcSender<Vec<(ServoUrl, Option<u16>)>>),
+    /// Remove the responses cached for the origin of a given URL, or only the one for the given URL
+    DeleteCacheEntries(ServoUrl, Option<ServoUrl>),
     /// Get a history state by a given history state id
     GetHistoryState(HistoryStateId, IpcSender<Option<Vec<u8>>>),
     /// Set a history state for a given history state id
@@ -50,6 +50,14 @@ impl Destination {
         *self == Destination::SharedWorker ||
         *self == Destination::Worker
     }
+
+    /// <https://w3c.github.io/webappsec-mixed-content/#category-optionally-blockable>
+    #[inline]
+    pub fn is_optionally_blockable(&self) -> bool {
+        *self == Destination::Audio ||
+        *self == Destination::Image ||
+        *self == Destination::Video
+    }
 }
 
 /// A request [origin](https://fetch.spec.whatwg.org/#concept-request-origin)
@@ -128,6 +136,13 @@ pub enum Window {
     Client, // TODO: Environmental settings object
 }
 
+/// An [insecure requests policy](https://w3c.github.io/webappsec-upgrade-insecure-requests/#insecure-requests-policy)
+#[derive(Clone, Copy, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub enum InsecureRequestsPolicy {
+    DoNotUpgrade,
+    Upgrade,
+}
+
 /// [CORS settings attribute](https://html.spec.whatwg.org/multipage/#attr-crossorigin-anonymous)
 #[derive(Clone, Copy, Deserialize, PartialEq, Serialize)]
 pub enum CorsSettings {
@@ -166,6 +181,9 @@ pub struct RequestInit {
     pub integrity_metadata: String,
     // to keep track of redirects
     pub url_list: Vec<ServoUrl>,
+    pub user_initiated: bool,
+    pub insecure_requests_policy: InsecureRequestsPolicy,
+    pub parent_origin: Option<ImmutableOrigin>,
 }
 
 impl Default for RequestInit {
@@ -191,6 +209,9 @@ impl Default for RequestInit {
             redirect_mode: RedirectMode::Follow,
             integrity_metadata: "".to_owned(),
             url_list: vec![],
+            user_initiated: false,
+            insecure_requests_policy: InsecureRequestsPolicy::DoNotUpgrade,
+            parent_origin: None,
         }
     }
 }
@@ -256,6 +277,16 @@ pub struct Request {
     pub redirect_count: u32,
     /// <https://fetch.spec.whatwg.org/#concept-request-response-tainting>
     pub response_tainting: ResponseTainting,
+    /// Whether this is a navigation request explicitly caused by the user
+    /// interacting with the embedder, e.g. by typing an address.
+    /// <https://w3c.github.io/webappsec-fetch-metadata/#sec-fetch-site-header>
+    pub user_initiated: bool,
+    /// The insecure requests policy of the request's client.
+    pub insecure_requests_policy: InsecureRequestsPolicy,
+    /// The origin of the parent document of the browsing context that a navigation request
+    /// navigates, or `None` if the browsing context is top-level or this is no navigation
+    /// request.
+    pub parent_origin: Option<ImmutableOrigin>,
 }
 
 impl Request {
@@ -290,6 +321,9 @@ impl Request {
             url_list: vec![url],
             redirect_count: 0,
             response_tainting: ResponseTainting::Basic,
+            user_initiated: false,
+            insecure_requests_policy: InsecureRequestsPolicy::DoNotUpgrade,
+            parent_origin: None,
         }
     }
 
@@ -324,6 +358,9 @@ impl Request {
         req.redirect_count = url_list.len() as u32 - 1;
         req.url_list = url_list;
         req.integrity_metadata = init.integrity_metadata;
+        req.user_initiated = init.user_initiated;
+        req.insecure_requests_policy = init.insecure_requests_policy;
+        req.parent_origin = init.parent_origin;
         req
     }
 
@@ -366,3 +403,38 @@ impl Referrer {
         }
     }
 }
+
+/// <https://w3c.github.io/webappsec-mixed-content/#categorize-settings-object>
+///
+/// Whether a client with the given origin prohibits mixed security contexts.
+pub fn prohibits_mixed_security_contexts(origin: &ImmutableOrigin) -> bool {
+    match origin.scheme() {
+        Some("https") | Some("wss") => true,
+        _ => false,
+    }
+}
+
+/// <https://w3c.github.io/webappsec-mixed-content/#a-priori-authenticated-url>
+///
+/// Whether fetching `url` from a client with the given origin would be mixed content.
+pub fn is_mixed_content(origin: &ImmutableOrigin, url: &ServoUrl) -> bool {
+    prohibits_mixed_security_contexts(origin) && !url.is_potentially_trustworthy()
+}
+
+/// <https://w3c.github.io/webappsec-upgrade-insecure-requests/#delivery>
+///
+/// Whether a serialized content security policy has an `upgrade-insecure-requests` directive.
+pub fn policy_upgrades_insecure_requests(policy: &str) -> bool {
+    policy.split(';').any(|directive| directive.trim().eq_ignore_ascii_case("upgrade-insecure-requests"))
+}
+
+/// <https://w3c.github.io/webappsec-upgrade-insecure-requests/#upgrade-request>
+/// Steps 3-4.
+pub fn upgrade_to_potentially_trustworthy_url(url: &mut ServoUrl) {
+    let scheme = match url.scheme() {
+        "http" => "https",
+        "ws" => "wss",
+        _ => return,
+    };
+    let _ = url.as_mut_url().set_scheme(scheme);
+}
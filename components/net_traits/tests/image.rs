@@ -2,9 +2,76 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+extern crate ipc_channel;
 extern crate net_traits;
 
-use net_traits::image::base::detect_image_format;
+use ipc_channel::ipc::IpcSharedMemory;
+use net_traits::image::animation::{decode_apng, decode_gif};
+use net_traits::image::base::{ImageAnimation, detect_image_format, load_from_memory};
+use net_traits::image::svg::{MAX_VECTOR_IMAGE_SIZE, SvgImage};
+use net_traits::image::webp::{self, DecodedImage};
+
+// 4x4 images, red on the left half and blue on the right half.
+const LOSSY_WEBP: &[u8] =
+    b"\x52\x49\x46\x46\x6c\x00\x00\x00\x57\x45\x42\x50\x56\x50\x38\x20\x60\x00\x00\x00\xf0\x03\x00\x9d\
+    \x01\x2a\x04\x00\x04\x00\x00\x00\x00\x25\xa8\x02\x74\xba\x01\xf8\x01\xf8\x01\x4a\x03\xf0\x02\xb4\
+    \x03\xf8\x06\x50\x07\xe8\x00\x2d\xda\xcb\xc2\xc0\x00\xfe\xff\xff\x50\xc7\xff\x8b\xd0\x3f\xf9\xa3\
+    \x0a\x0f\xff\xbc\xec\xf5\x46\x90\x84\xcf\xfe\x30\xdb\xfd\xe7\x67\xaa\x34\x84\x26\x7f\xff\x18\x6d\
+    \xff\xde\x4e\xd2\x66\x01\x77\x7f\x79\x88\x31\x01\x80\x5d\xdf\xfe\xbc\x00\x00\x00";
+const LOSSLESS_WEBP: &[u8] =
+    b"\x52\x49\x46\x46\x1e\x00\x00\x00\x57\x45\x42\x50\x56\x50\x38\x4c\x12\x00\x00\x00\x2f\x03\xc0\x00\
+    \x00\x0f\x10\xf3\xbf\xff\xf3\x1f\x0e\x3a\x10\xd1\xff\x00";
+// A lossy image whose bottom half is transparent.
+const LOSSY_ALPHA_WEBP: &[u8] =
+    b"\x52\x49\x46\x46\x9c\x00\x00\x00\x57\x45\x42\x50\x56\x50\x38\x58\x0a\x00\x00\x00\x10\x00\x00\x00\
+    \x03\x00\x00\x03\x00\x00\x41\x4c\x50\x48\x0c\x00\x00\x00\x01\x0f\x30\xff\x11\x11\x62\x1e\x22\xfa\
+    \x1f\x03\x56\x50\x38\x20\x6a\x00\x00\x00\x30\x04\x00\x9d\x01\x2a\x04\x00\x04\x00\x00\x00\x00\x25\
+    \xa8\x02\x74\xba\x01\xf8\x01\xf8\x01\x4a\x03\xf0\x02\xb4\x03\xf8\x06\x50\x07\xe8\x01\x06\x00\x59\
+    \xb9\x92\x36\x00\x00\xfe\xff\xff\x50\xc7\xff\x03\xb3\xfc\xf3\x5f\xfd\x88\x8f\xc8\x4f\xc7\x81\x47\
+    \xe0\x4a\x2e\x7f\xfe\xf3\xb3\xd5\x1a\x42\x13\x3f\xf8\xc3\x6f\xf7\x9d\x9e\xa8\xd2\x10\x99\xff\xfc\
+    \x61\xb7\xff\x79\x3b\x49\x98\x05\xdd\xfd\xe6\x20\xc4\x06\x01\x77\x7f\xfa\xf0\x00";
+// An 8x8 animation, whose first frame is a 4x4 green square at (2, 2).
+const ANIMATED_WEBP: &[u8] =
+    b"\x52\x49\x46\x46\x84\x00\x00\x00\x57\x45\x42\x50\x56\x50\x38\x58\x0a\x00\x00\x00\x02\x00\x00\x00\
+    \x07\x00\x00\x07\x00\x00\x41\x4e\x49\x4d\x06\x00\x00\x00\xff\xff\xff\xff\x00\x00\x41\x4e\x4d\x46\
+    \x28\x00\x00\x00\x01\x00\x00\x01\x00\x00\x03\x00\x00\x03\x00\x00\x64\x00\x00\x02\x56\x50\x38\x4c\
+    \x0f\x00\x00\x00\x2f\x03\xc0\x00\x00\x07\xd0\xff\x88\xfe\x07\x22\xa2\xff\x01\x00\x41\x4e\x4d\x46\
+    \x28\x00\x00\x00\x00\x00\x00\x00\x00\x00\x07\x00\x00\x07\x00\x00\x64\x00\x00\x00\x56\x50\x38\x4c\
+    \x0f\x00\x00\x00\x2f\x07\xc0\x01\x00\x07\x10\xfd\x8f\xfe\x07\x22\xa2\xff\x01\x00";
+
+// A 2x2 GIF image with four frames, that dispose of themselves to the background, to the
+// previous frame, and not at all, and the last of which is partly transparent.
+const ANIMATED_GIF: &[u8] =
+    b"\x47\x49\x46\x38\x39\x61\x02\x00\x02\x00\xf1\x00\x00\x00\x00\x00\xff\x00\x00\x00\xff\x00\x00\x00\
+    \xff\x21\xff\x0b\x4e\x45\x54\x53\x43\x41\x50\x45\x32\x2e\x30\x03\x01\x00\x00\x00\x21\xf9\x04\x09\
+    \x0a\x00\x00\x00\x2c\x00\x00\x00\x00\x02\x00\x02\x00\x00\x02\x03\x4c\x98\x14\x00\x21\xf9\x04\x0d\
+    \x0a\x00\x00\x00\x2c\x01\x00\x00\x00\x01\x00\x01\x00\x00\x02\x02\x54\x01\x00\x21\xf9\x04\x05\x0a\
+    \x00\x00\x00\x2c\x00\x00\x01\x00\x01\x00\x01\x00\x00\x02\x02\x5c\x01\x00\x21\xf9\x04\x05\x0a\x00\
+    \x00\x00\x2c\x00\x00\x00\x00\x02\x00\x02\x00\x00\x02\x03\x94\x08\x15\x00\x3b";
+// A 2x2 animated PNG image with three frames, blended over the previous frame or replacing
+// it, that dispose of themselves to the previous frame and to the background.
+const ANIMATED_PNG: &[u8] =
+    b"\x89\x50\x4e\x47\x0d\x0a\x1a\x0a\x00\x00\x00\x0d\x49\x48\x44\x52\x00\x00\x00\x02\x00\x00\x00\x02\
+    \x08\x06\x00\x00\x00\x72\xb6\x0d\x24\x00\x00\x00\x08\x61\x63\x54\x4c\x00\x00\x00\x03\x00\x00\x00\
+    \x00\xce\xed\xba\xc0\x00\x00\x00\x1a\x66\x63\x54\x4c\x00\x00\x00\x00\x00\x00\x00\x02\x00\x00\x00\
+    \x02\x00\x00\x00\x00\x00\x00\x00\x00\x00\x01\x00\x0a\x00\x00\xe8\x54\xdc\x00\x00\x00\x00\x11\x49\
+    \x44\x41\x54\x78\x9c\x63\xf8\xcf\xc0\xf0\x1f\x84\x19\x60\x0c\x00\x47\xca\x07\xf9\x67\x59\x6e\xb7\
+    \x00\x00\x00\x1a\x66\x63\x54\x4c\x00\x00\x00\x01\x00\x00\x00\x01\x00\x00\x00\x01\x00\x00\x00\x01\
+    \x00\x00\x00\x01\x00\x01\x00\x0a\x02\x01\xe3\xc4\x5c\x64\x00\x00\x00\x11\x66\x64\x41\x54\x00\x00\
+    \x00\x02\x78\x9c\x63\x60\x60\xf8\xdf\x00\x00\x02\x83\x01\x80\x50\xb7\x56\x7a\x00\x00\x00\x1a\x66\
+    \x63\x54\x4c\x00\x00\x00\x03\x00\x00\x00\x01\x00\x00\x00\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\
+    \x01\x00\x0a\x01\x00\x35\x81\x38\xac\x00\x00\x00\x11\x66\x64\x41\x54\x00\x00\x00\x04\x78\x9c\x63\
+    \x60\xf8\xcf\xc0\x00\x00\x03\x02\x01\x00\x72\x55\xde\x5b\x00\x00\x00\x00\x49\x45\x4e\x44\xae\x42\
+    \x60\x82";
+
+fn pixel(image: &DecodedImage, x: usize, y: usize) -> &[u8] {
+    &image.rgba[(y * image.width as usize + x) * 4..][..4]
+}
+
+fn assert_close(actual: &[u8], expected: [u8; 4]) {
+    let close = actual.iter().zip(&expected).all(|(&a, &e)| (a as i32 - e as i32).abs() <= 64);
+    assert!(close, "{:?} is not close to {:?}", actual, expected);
+}
 
 #[test]
 fn test_supported_images() {
@@ -14,6 +81,8 @@ fn test_supported_images() {
     let png = [0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
     let bmp = [0x42, 0x4D];
     let ico = [0x00, 0x00, 0x01, 0x00];
+    let webp = *b"RIFF\x24\x00\x00\x00WEBPVP8 ";
+    let svg = b"<?xml version=\"1.0\"?>\n<svg xmlns=\"http://www.w3.org/2000/svg\"/>";
     let junk_format = [0x01, 0x02, 0x03, 0x04, 0x05];
 
     assert!(detect_image_format(&gif1).is_ok());
@@ -22,5 +91,167 @@ fn test_supported_images() {
     assert!(detect_image_format(&png).is_ok());
     assert!(detect_image_format(&bmp).is_ok());
     assert!(detect_image_format(&ico).is_ok());
+    assert!(detect_image_format(&webp).is_ok());
+    assert!(detect_image_format(svg).is_ok());
     assert!(detect_image_format(&junk_format).is_err());
 }
+
+#[test]
+fn test_animation_frame_at() {
+    let animation = ImageAnimation {
+        frame_delays: vec![100, 50, 200],
+        frames: IpcSharedMemory::from_bytes(&[0; 12]),
+        loop_count: Some(2),
+    };
+
+    assert_eq!(animation.frame_at(0), (0, true));
+    assert_eq!(animation.frame_at(99), (0, true));
+    assert_eq!(animation.frame_at(100), (1, true));
+    assert_eq!(animation.frame_at(150), (2, true));
+    assert_eq!(animation.frame_at(350), (0, true));
+    assert_eq!(animation.frame_at(699), (2, true));
+    assert_eq!(animation.frame_at(700), (2, false));
+}
+
+#[test]
+fn test_decode_lossy_webp() {
+    let image = webp::decode(LOSSY_WEBP).unwrap();
+    assert_eq!((image.width, image.height), (4, 4));
+    assert_eq!(image.rgba.len(), 4 * 4 * 4);
+    assert_close(pixel(&image, 0, 0), [255, 0, 0, 255]);
+    assert_close(pixel(&image, 3, 3), [0, 0, 255, 255]);
+}
+
+#[test]
+fn test_decode_lossless_webp() {
+    let image = webp::decode(LOSSLESS_WEBP).unwrap();
+    assert_eq!((image.width, image.height), (4, 4));
+    for y in 0..4 {
+        assert_eq!(pixel(&image, 1, y), [255, 0, 0, 255]);
+        assert_eq!(pixel(&image, 2, y), [0, 0, 255, 255]);
+    }
+}
+
+#[test]
+fn test_decode_webp_with_alpha() {
+    let image = webp::decode(LOSSY_ALPHA_WEBP).unwrap();
+    assert_eq!((image.width, image.height), (4, 4));
+    assert_close(pixel(&image, 0, 1), [255, 0, 0, 255]);
+    assert_eq!(pixel(&image, 0, 2)[3], 0);
+    assert_eq!(pixel(&image, 3, 3)[3], 0);
+}
+
+#[test]
+fn test_decode_animated_webp() {
+    let image = webp::decode(ANIMATED_WEBP).unwrap();
+    assert_eq!((image.width, image.height), (8, 8));
+    assert_eq!(pixel(&image, 1, 1), [0, 0, 0, 0]);
+    assert_eq!(pixel(&image, 2, 2), [0, 255, 0, 255]);
+    assert_eq!(pixel(&image, 5, 5), [0, 255, 0, 255]);
+    assert_eq!(pixel(&image, 6, 6), [0, 0, 0, 0]);
+}
+
+#[test]
+fn test_decode_malformed_webp() {
+    assert!(webp::decode(&LOSSY_WEBP[..60]).is_none());
+    assert!(webp::decode(&LOSSLESS_WEBP[..24]).is_none());
+    assert!(webp::decode(b"RIFF\x04\x00\x00\x00WEBP").is_none());
+
+    // A frame that lies partly outside the canvas.
+    let mut outside_canvas = ANIMATED_WEBP.to_vec();
+    outside_canvas[52] = 3;
+    assert!(webp::decode(&outside_canvas).is_none());
+
+    // A canvas too large to be allocated.
+    let mut huge_canvas = ANIMATED_WEBP.to_vec();
+    for byte in &mut huge_canvas[24..30] {
+        *byte = 0xff;
+    }
+    assert!(webp::decode(&huge_canvas).is_none());
+}
+
+const TRANSPARENT: [u8; 4] = [0, 0, 0, 0];
+const RED: [u8; 4] = [255, 0, 0, 255];
+const GREEN: [u8; 4] = [0, 255, 0, 255];
+const BLUE: [u8; 4] = [0, 0, 255, 255];
+
+fn pixels(frame: &[u8]) -> Vec<[u8; 4]> {
+    frame.chunks(4).map(|pixel| [pixel[0], pixel[1], pixel[2], pixel[3]]).collect()
+}
+
+#[test]
+fn test_decode_gif_disposes_of_frames() {
+    let animation = decode_gif(ANIMATED_GIF).unwrap();
+    assert_eq!((animation.width, animation.height), (2, 2));
+    assert_eq!(animation.loop_count, None);
+    let frames: Vec<_> = animation.frames.iter().map(|&(_, ref frame)| pixels(frame)).collect();
+    assert_eq!(frames, vec![
+        vec![RED, RED, RED, RED],
+        // The first frame was disposed of to the background.
+        vec![TRANSPARENT, GREEN, TRANSPARENT, TRANSPARENT],
+        // The second frame was disposed of to the frame before it.
+        vec![TRANSPARENT, TRANSPARENT, BLUE, TRANSPARENT],
+        // The transparent pixel leaves the blue one.
+        vec![GREEN, GREEN, BLUE, GREEN],
+    ]);
+    assert!(animation.frames.iter().all(|&(delay, _)| delay == 100));
+}
+
+#[test]
+fn test_decode_apng_blends_frames() {
+    let animation = decode_apng(ANIMATED_PNG).unwrap();
+    assert_eq!((animation.width, animation.height), (2, 2));
+    assert_eq!(animation.loop_count, None);
+    let frames: Vec<_> = animation.frames.iter().map(|&(_, ref frame)| pixels(frame)).collect();
+    assert_eq!(frames, vec![
+        vec![RED, RED, RED, RED],
+        // Half transparent blue over red.
+        vec![RED, RED, RED, [127, 0, 128, 255]],
+        // The second frame was disposed of to the frame before it, and the transparent
+        // pixel replaces the red one.
+        vec![[0, 255, 0, 0], RED, RED, RED],
+    ]);
+    assert!(animation.frames.iter().all(|&(delay, _)| delay == 100));
+}
+
+#[test]
+fn test_decode_oversized_animations() {
+    let mut gif = ANIMATED_GIF.to_vec();
+    for byte in &mut gif[6..10] {
+        *byte = 0xff;
+    }
+    assert!(decode_gif(&gif).is_none());
+
+    let mut png = ANIMATED_PNG.to_vec();
+    png[16..24].copy_from_slice(&[0, 1, 0, 0, 0, 1, 0, 0]);
+    assert!(decode_apng(&png).is_none());
+
+    // A frame that lies partly outside the canvas.
+    let mut png = ANIMATED_PNG.to_vec();
+    let frame_control = png.windows(4).rposition(|window| window == b"fcTL").unwrap();
+    png[frame_control + 4 + 15] = 2;
+    assert!(decode_apng(&png).is_none());
+}
+
+fn svg_default_size(attributes: &str) -> (u32, u32) {
+    let svg = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" {}/>", attributes);
+    SvgImage::parse(svg.as_bytes()).unwrap().default_size()
+}
+
+#[test]
+fn test_svg_default_size_with_malformed_sizes() {
+    // Lengths and viewBoxes that overflow are ignored.
+    assert_eq!(svg_default_size("width=\"1e39\" height=\"20\""), (300, 20));
+    assert_eq!(svg_default_size("viewBox=\"0 0 1e39 1\""), (300, 150));
+    // So are ratios that underflow.
+    assert_eq!(svg_default_size("viewBox=\"0 0 1e-30 1e30\""), (300, 150));
+    // A width over a tiny ratio overflows the height, which falls back to the default.
+    assert_eq!(svg_default_size("width=\"1e30\" viewBox=\"0 0 1e-10 1e10\""), (MAX_VECTOR_IMAGE_SIZE, 150));
+    assert_eq!(svg_default_size("viewBox=\"0 0 1 1e30\""), (300, MAX_VECTOR_IMAGE_SIZE));
+    // Large sizes are clamped.
+    assert_eq!(svg_default_size("width=\"100000\" height=\"10\""), (MAX_VECTOR_IMAGE_SIZE, 10));
+
+    let svg = b"<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"1e20\" height=\"2\"/>";
+    let image = load_from_memory(svg).unwrap();
+    assert_eq!((image.width, image.height), (MAX_VECTOR_IMAGE_SIZE, 2));
+}
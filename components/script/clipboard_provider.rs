@@ -2,6 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+use embedder_traits::EmbedderMsg;
 use ipc_channel::ipc::channel;
 use script_traits::{ScriptToConstellationChan, ScriptMsg};
 use std::borrow::ToOwned;
@@ -16,11 +17,11 @@ pub trait ClipboardProvider {
 impl ClipboardProvider for ScriptToConstellationChan {
     fn clipboard_contents(&mut self) -> String {
         let (tx, rx) = channel().unwrap();
-        self.send(ScriptMsg::GetClipboardContents(tx)).unwrap();
+        self.send(ScriptMsg::ForwardToEmbedder(EmbedderMsg::GetClipboardContents(tx))).unwrap();
         rx.recv().unwrap()
     }
     fn set_clipboard_contents(&mut self, s: String) {
-        self.send(ScriptMsg::SetClipboardContents(s)).unwrap();
+        self.send(ScriptMsg::ForwardToEmbedder(EmbedderMsg::SetClipboardContents(s))).unwrap();
     }
 }
 
@@ -0,0 +1,216 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! The JavaScript debugger used by devtools' thread actors.
+//!
+//! SpiderMonkey's `Debugger` API can only be used from a global that is not itself being
+//! debugged, so each script thread creates a separate debugger global lazily, the first
+//! time a devtools client attaches to one of its pipelines. The logic of the debugger
+//! lives in `resources/debugger.js`; Rust talks to it by passing JSON requests to its
+//! `handleRequest` function, and it reports pauses through the native `pause` function.
+
+use devtools_traits::{BreakpointInfo, FrameInfo, ObjectPropertiesInfo, PauseInfo, ScopeInfo};
+use devtools_traits::{SourceInfo, SourceLocation};
+use dom::bindings::conversions::jsstring_to_str;
+use dom::bindings::reflector::DomObject;
+use dom::globalscope::GlobalScope;
+use embedder_traits::resources::{self, Resource};
+use js::jsapi::{CallArgs, CompartmentOptions, Heap, JSAutoCompartment, JSContext, JSObject};
+use js::jsapi::{JS_ClearPendingException, JS_IsExceptionPending, JS_NewGlobalObject};
+use js::jsapi::{JS_NewStringCopyN, OnNewGlobalHookOption};
+use js::jsval::{JSVal, ObjectValue, StringValue, UndefinedValue};
+use js::rust::{CompileOptionsWrapper, SIMPLE_GLOBAL_CLASS};
+use js::rust::wrappers::{Evaluate2, JS_DefineDebuggerObject, JS_DefineFunction};
+use js::rust::wrappers::{JS_SetProperty, JS_WrapObject};
+use libc;
+use msg::constellation_msg::PipelineId;
+use script_thread::ScriptThread;
+use serde::de::DeserializeOwned;
+use serde_json;
+use std::ffi::CString;
+use std::ptr;
+
+#[derive(JSTraceable)]
+pub struct ScriptDebugger {
+    /// The global in which `debugger.js` runs.
+    global: Box<Heap<*mut JSObject>>,
+}
+
+impl ScriptDebugger {
+    #[allow(unsafe_code)]
+    pub fn new(cx: *mut JSContext) -> ScriptDebugger {
+        let global = Box::new(Heap::default());
+        unsafe {
+            let options = CompartmentOptions::default();
+            rooted!(in(cx) let object = JS_NewGlobalObject(
+                cx,
+                &SIMPLE_GLOBAL_CLASS,
+                ptr::null_mut(),
+                OnNewGlobalHookOption::FireOnNewGlobalHook,
+                &options,
+            ));
+            assert!(!object.is_null());
+            global.set(object.get());
+
+            let _ac = JSAutoCompartment::new(cx, object.get());
+            assert!(JS_DefineDebuggerObject(cx, object.handle()));
+            let name = b"pause\0".as_ptr() as *const libc::c_char;
+            assert!(!JS_DefineFunction(cx, object.handle(), name, Some(pause), 2, 0).is_null());
+        }
+        let debugger = ScriptDebugger { global: global };
+        debugger.evaluate(cx, &resources::read_string(Resource::DebuggerJS), "debugger.js");
+        debugger
+    }
+
+    /// Start debugging the scripts of the given global.
+    #[allow(unsafe_code)]
+    pub fn attach(&self, global: &GlobalScope, pipeline: PipelineId) {
+        let cx = global.get_cx();
+        unsafe {
+            let _ac = JSAutoCompartment::new(cx, self.global.get());
+            rooted!(in(cx) let mut debuggee = global.reflector().get_jsobject().get());
+            assert!(JS_WrapObject(cx, debuggee.handle_mut()));
+            rooted!(in(cx) let value = ObjectValue(debuggee.get()));
+            rooted!(in(cx) let debugger_global = self.global.get());
+            assert!(JS_SetProperty(cx, debugger_global.handle(),
+                                   b"debuggee\0".as_ptr() as *const libc::c_char,
+                                   value.handle()));
+        }
+        self.request::<()>(cx, json!({ "type": "attach", "pipeline": pipeline }));
+    }
+
+    pub fn detach(&self, cx: *mut JSContext, pipeline: PipelineId) {
+        self.request::<()>(cx, json!({ "type": "detach", "pipeline": pipeline }));
+    }
+
+    pub fn sources(&self, cx: *mut JSContext, pipeline: PipelineId) -> Vec<SourceInfo> {
+        self.request(cx, json!({ "type": "sources", "pipeline": pipeline })).unwrap_or_default()
+    }
+
+    pub fn source_text(&self,
+                       cx: *mut JSContext,
+                       pipeline: PipelineId,
+                       source: u32)
+                       -> Option<String> {
+        self.request(cx, json!({ "type": "sourceText", "pipeline": pipeline, "source": source }))
+            .and_then(|text| text)
+    }
+
+    pub fn set_breakpoint(&self,
+                          cx: *mut JSContext,
+                          pipeline: PipelineId,
+                          location: SourceLocation)
+                          -> Option<BreakpointInfo> {
+        self.request(cx, json!({
+            "type": "setBreakpoint",
+            "pipeline": pipeline,
+            "location": location,
+        })).and_then(|breakpoint| breakpoint)
+    }
+
+    pub fn remove_breakpoint(&self, cx: *mut JSContext, pipeline: PipelineId, breakpoint: u32) {
+        self.request::<()>(cx, json!({
+            "type": "removeBreakpoint",
+            "pipeline": pipeline,
+            "breakpoint": breakpoint,
+        }));
+    }
+
+    pub fn interrupt(&self, cx: *mut JSContext, pipeline: PipelineId) {
+        self.request::<()>(cx, json!({ "type": "interrupt", "pipeline": pipeline }));
+    }
+
+    pub fn frames(&self, cx: *mut JSContext, pipeline: PipelineId) -> Vec<FrameInfo> {
+        self.request(cx, json!({ "type": "frames", "pipeline": pipeline })).unwrap_or_default()
+    }
+
+    pub fn scopes(&self,
+                  cx: *mut JSContext,
+                  pipeline: PipelineId,
+                  depth: u32)
+                  -> Option<Vec<ScopeInfo>> {
+        self.request(cx, json!({ "type": "scopes", "pipeline": pipeline, "depth": depth }))
+            .and_then(|scopes| scopes)
+    }
+
+    pub fn object_properties(&self,
+                             cx: *mut JSContext,
+                             pipeline: PipelineId,
+                             object: u32)
+                             -> Option<ObjectPropertiesInfo> {
+        self.request(cx, json!({ "type": "objectProperties", "pipeline": pipeline, "object": object }))
+            .and_then(|properties| properties)
+    }
+
+    /// Pass a request to `handleRequest` and deserialize its reply.
+    fn request<T>(&self, cx: *mut JSContext, request: serde_json::Value) -> Option<T>
+        where T: DeserializeOwned
+    {
+        // JSON is not quite a subset of JavaScript, so pass the request as a string literal.
+        let request = serde_json::to_string(&request.to_string()).unwrap();
+        let reply = self.evaluate(cx, &format!("handleRequest({})", request), "")?;
+        match serde_json::from_str(&reply) {
+            Ok(reply) => Some(reply),
+            Err(error) => {
+                warn!("Unexpected debugger reply {:?}: {}", reply, error);
+                None
+            },
+        }
+    }
+
+    /// Evaluate the given code in the debugger global, returning its result if it is a string.
+    #[allow(unsafe_code)]
+    fn evaluate(&self, cx: *mut JSContext, code: &str, filename: &str) -> Option<String> {
+        let code: Vec<u16> = code.encode_utf16().collect();
+        let filename = CString::new(filename).unwrap();
+        unsafe {
+            let _ac = JSAutoCompartment::new(cx, self.global.get());
+            let options = CompileOptionsWrapper::new(cx, filename.as_ptr(), 1);
+            rooted!(in(cx) let mut rval = UndefinedValue());
+            if !Evaluate2(cx, options.ptr, code.as_ptr(), code.len() as libc::size_t,
+                          rval.handle_mut()) {
+                if JS_IsExceptionPending(cx) {
+                    JS_ClearPendingException(cx);
+                }
+                warn!("Error evaluating debugger script.");
+                return None;
+            }
+            if !rval.is_string() {
+                return None;
+            }
+            Some(String::from(jsstring_to_str(cx, rval.to_string())))
+        }
+    }
+}
+
+/// The native `pause(pipeline, info)` function of the debugger global, which blocks until
+/// the devtools client resumes the pipeline. Returns the requested step kind, if any.
+#[allow(unsafe_code)]
+unsafe extern "C" fn pause(cx: *mut JSContext, argc: u32, vp: *mut JSVal) -> bool {
+    let args = CallArgs::from_vp(vp, argc);
+    *args.rval() = UndefinedValue();
+    if argc < 2 || !args.get(0).is_string() || !args.get(1).is_string() {
+        return true;
+    }
+    let pipeline = String::from(jsstring_to_str(cx, args.get(0).to_string()));
+    let info = String::from(jsstring_to_str(cx, args.get(1).to_string()));
+    let (pipeline, info) = match (serde_json::from_str::<PipelineId>(&pipeline),
+                                  serde_json::from_str::<PauseInfo>(&info)) {
+        (Ok(pipeline), Ok(info)) => (pipeline, info),
+        _ => {
+            warn!("Malformed debugger pause {} {}", pipeline, info);
+            return true;
+        },
+    };
+
+    let step = match ScriptThread::debugger_paused(pipeline, info) {
+        Some(step) => serde_json::to_value(step).unwrap(),
+        None => return true,
+    };
+    let step = step.as_str().unwrap();
+    let string = JS_NewStringCopyN(cx, step.as_ptr() as *const libc::c_char, step.len());
+    *args.rval() = StringValue(&*string);
+    true
+}
+
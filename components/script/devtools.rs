@@ -2,34 +2,60 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+use cookie_rs;
+use cssparser::ToCss;
 use devtools_traits::{AutoMargins, CachedConsoleMessage, CachedConsoleMessageTypes};
-use devtools_traits::{ComputedNodeLayout, ConsoleAPI, PageError};
-use devtools_traits::{EvaluateJSReply, Modification, NodeInfo, TimelineMarker};
-use devtools_traits::TimelineMarkerType;
+use devtools_traits::{ComputedNodeLayout, ConsoleAPI, CookieAttributes, PageError};
+use devtools_traits::{EvaluateJSReply, Modification, NodeInfo, PropertyModification};
+use devtools_traits::{StoreChange, StoreEntry, StoreType, StyleRuleId, StyleRuleInfo};
+use devtools_traits::{StyleSheetInfo, TimelineMarker, TimelineMarkerType};
+use dom::bindings::codegen::Bindings::CSSGroupingRuleBinding::CSSGroupingRuleMethods;
 use dom::bindings::codegen::Bindings::CSSStyleDeclarationBinding::CSSStyleDeclarationMethods;
+use dom::bindings::codegen::Bindings::CSSStyleRuleBinding::CSSStyleRuleMethods;
 use dom::bindings::codegen::Bindings::DOMRectBinding::DOMRectMethods;
 use dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
 use dom::bindings::codegen::Bindings::ElementBinding::ElementMethods;
+use dom::bindings::codegen::Bindings::HTMLElementBinding::HTMLElementMethods;
+use dom::bindings::codegen::Bindings::StorageBinding::StorageMethods;
+use dom::bindings::codegen::Bindings::StyleSheetBinding::StyleSheetMethods;
 use dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use dom::bindings::conversions::{ConversionResult, FromJSValConvertible, jsstring_to_str};
 use dom::bindings::inheritance::Castable;
 use dom::bindings::reflector::DomObject;
 use dom::bindings::root::DomRoot;
 use dom::bindings::str::DOMString;
+use dom::cssgroupingrule::CSSGroupingRule;
+use dom::cssstyledeclaration::CSSStyleDeclaration;
+use dom::cssstylerule::CSSStyleRule;
+use dom::cssstylesheet::CSSStyleSheet;
 use dom::document::AnimationFrameCallback;
 use dom::element::Element;
 use dom::globalscope::GlobalScope;
-use dom::node::{Node, window_from_node};
+use dom::htmlelement::HTMLElement;
+use dom::node::{Node, document_from_node, window_from_node};
+use dom::storage::Storage;
+use dom::stylesheet::StyleSheet;
 use dom::window::Window;
-use ipc_channel::ipc::IpcSender;
+use hyper_serde::Serde;
+use ipc_channel::ipc::{self, IpcSender};
 use js::jsapi::JSAutoCompartment;
 use js::jsval::UndefinedValue;
 use js::rust::wrappers::ObjectClassName;
 use msg::constellation_msg::PipelineId;
+use net_traits::{CookieSource, IpcSend};
+use net_traits::CoreResourceMsg::{DeleteCacheEntries, DeleteCookies, GetCacheEntries};
+use net_traits::CoreResourceMsg::{GetCookiesDataForUrl, SetCookieForUrl};
 use script_thread::Documents;
+use selectors::matching::{self, MatchingContext, MatchingMode};
+use servo_url::ServoUrl;
 use std::ffi::CStr;
 use std::str;
+use style::context::QuirksMode;
+use style::media_queries::Device;
 use style::properties::longhands::{margin_bottom, margin_left, margin_right, margin_top};
+use style::shared_lock::{SharedRwLockReadGuard, ToCssWithGuard};
+use style::stylesheets::{CssRule, CssRules};
+use time::{self, Timespec};
 use uuid::Uuid;
 
 
@@ -229,6 +255,373 @@ pub fn handle_modify_attribute(documents: &Documents,
     }
 }
 
+pub fn handle_get_style_sheets(documents: &Documents,
+                               pipeline: PipelineId,
+                               reply: IpcSender<Vec<StyleSheetInfo>>) {
+    let document = match documents.find_document(pipeline) {
+        None => return reply.send(vec![]).unwrap(),
+        Some(document) => document,
+    };
+    let sheets = (0..document.stylesheet_count()).filter_map(|index| {
+        let sheet = document.stylesheet_at(index)?;
+        let guard = sheet.shared_lock().read();
+        let rule_count = sheet.style_stylesheet().contents.rules.read_with(&guard).0.len();
+        Some(StyleSheetInfo {
+            index: index as u32,
+            href: sheet.upcast::<StyleSheet>().GetHref().map(String::from),
+            title: sheet.upcast::<StyleSheet>().GetTitle().map(String::from),
+            disabled: sheet.disabled(),
+            rule_count: rule_count as u32,
+        })
+    }).collect();
+    reply.send(sheets).unwrap();
+}
+
+fn find_style_sheet(documents: &Documents,
+                    pipeline: PipelineId,
+                    index: u32)
+                    -> Option<DomRoot<CSSStyleSheet>> {
+    documents.find_document(pipeline).and_then(|document| document.stylesheet_at(index as usize))
+}
+
+pub fn handle_get_style_sheet_text(documents: &Documents,
+                                   pipeline: PipelineId,
+                                   index: u32,
+                                   reply: IpcSender<Option<String>>) {
+    let text = find_style_sheet(documents, pipeline, index).map(|sheet| {
+        let guard = sheet.shared_lock().read();
+        let rules = sheet.style_stylesheet().contents.rules.read_with(&guard);
+        rules.0.iter().map(|rule| rule.to_css_string(&guard)).collect::<Vec<_>>().join("\n")
+    });
+    reply.send(text).unwrap();
+}
+
+pub fn handle_update_style_sheet(documents: &Documents,
+                                 pipeline: PipelineId,
+                                 index: u32,
+                                 text: String,
+                                 reply: IpcSender<bool>) {
+    let applied = match find_style_sheet(documents, pipeline, index) {
+        Some(sheet) => sheet.replace_rules(&text).is_ok(),
+        None => {
+            warn!("stylesheet {} for pipeline id {} is not found", index, pipeline);
+            false
+        },
+    };
+    reply.send(applied).unwrap();
+}
+
+/// Push the style rules of `rules` that match `element` to `applied`, along with the
+/// specificity of their most specific matching selector.
+fn collect_applied_rules(element: &DomRoot<Element>,
+                         device: &Device,
+                         quirks_mode: QuirksMode,
+                         guard: &SharedRwLockReadGuard,
+                         rules: &CssRules,
+                         sheet: u32,
+                         path: &mut Vec<u32>,
+                         applied: &mut Vec<(u32, StyleRuleInfo)>) {
+    for (index, rule) in rules.0.iter().enumerate() {
+        path.push(index as u32);
+        match *rule {
+            CssRule::Style(ref rule) => {
+                let rule = rule.read_with(guard);
+                let mut context = MatchingContext::new(MatchingMode::Normal, None, None, quirks_mode);
+                let matched: Vec<_> = rule.selectors.0.iter().filter(|&selector| {
+                    matching::matches_selector(selector, 0, None, element, &mut context, &mut |_, _| {})
+                }).collect();
+                if let Some(specificity) = matched.iter().map(|selector| selector.specificity()).max() {
+                    let mut declarations = String::new();
+                    rule.block.read_with(guard).to_css(&mut declarations).unwrap();
+                    applied.push((specificity, StyleRuleInfo {
+                        id: StyleRuleId::Sheet(sheet, path.clone()),
+                        selectors: rule.selectors.0.iter().map(|selector| selector.to_css_string()).collect(),
+                        matched_selectors: matched.iter().map(|selector| selector.to_css_string()).collect(),
+                        declarations: declarations,
+                        line: rule.source_location.line as u32 + 1,
+                        column: rule.source_location.column as u32,
+                    }));
+                }
+            },
+            CssRule::Media(ref rule) => {
+                let rule = rule.read_with(guard);
+                if rule.media_queries.read_with(guard).evaluate(device, quirks_mode) {
+                    collect_applied_rules(element, device, quirks_mode, guard,
+                                          rule.rules.read_with(guard), sheet, path, applied);
+                }
+            },
+            CssRule::Supports(ref rule) => {
+                let rule = rule.read_with(guard);
+                if rule.enabled {
+                    collect_applied_rules(element, device, quirks_mode, guard,
+                                          rule.rules.read_with(guard), sheet, path, applied);
+                }
+            },
+            _ => {},
+        }
+        path.pop();
+    }
+}
+
+pub fn handle_get_applied_rules(documents: &Documents,
+                                pipeline: PipelineId,
+                                node_id: String,
+                                reply: IpcSender<Option<Vec<StyleRuleInfo>>>) {
+    let node = match find_node_by_unique_id(documents, pipeline, &*node_id) {
+        None => return reply.send(None).unwrap(),
+        Some(found_node) => found_node
+    };
+    let element = match node.downcast::<Element>() {
+        None => return reply.send(None).unwrap(),
+        Some(element) => DomRoot::from_ref(element),
+    };
+    let document = document_from_node(&*element);
+    let quirks_mode = document.quirks_mode();
+
+    let mut applied = vec![];
+    if let Some(device) = document.device() {
+        for index in 0..document.stylesheet_count() {
+            let sheet = match document.stylesheet_at(index) {
+                Some(sheet) => sheet,
+                None => continue,
+            };
+            let guard = sheet.shared_lock().read();
+            let stylesheet = sheet.style_stylesheet();
+            if sheet.disabled() || !stylesheet.media.read_with(&guard).evaluate(&device, quirks_mode) {
+                continue;
+            }
+            collect_applied_rules(&element, &device, quirks_mode, &guard,
+                                  stylesheet.contents.rules.read_with(&guard),
+                                  index as u32, &mut vec![], &mut applied);
+        }
+    }
+    // Later rules win over earlier ones of the same specificity.
+    applied.reverse();
+    applied.sort_by(|&(a, _), &(b, _)| b.cmp(&a));
+    let mut rules: Vec<_> = applied.into_iter().map(|(_, rule)| rule).collect();
+
+    if element.is::<HTMLElement>() {
+        let mut declarations = String::new();
+        if let Some(ref block) = *element.style_attribute().borrow() {
+            let guard = document.style_shared_lock().read();
+            block.read_with(&guard).to_css(&mut declarations).unwrap();
+        }
+        rules.insert(0, StyleRuleInfo {
+            id: StyleRuleId::Element(node_id),
+            selectors: vec![],
+            matched_selectors: vec![],
+            declarations: declarations,
+            line: 0,
+            column: 0,
+        });
+    }
+    reply.send(Some(rules)).unwrap();
+}
+
+/// Returns the declarations edited through the given style rule id.
+fn find_rule_style(documents: &Documents,
+                   pipeline: PipelineId,
+                   rule: &StyleRuleId)
+                   -> Option<DomRoot<CSSStyleDeclaration>> {
+    match *rule {
+        StyleRuleId::Element(ref node_id) => {
+            let node = find_node_by_unique_id(documents, pipeline, node_id)?;
+            Some(node.downcast::<HTMLElement>()?.Style())
+        },
+        StyleRuleId::Sheet(index, ref path) => {
+            let sheet = find_style_sheet(documents, pipeline, index)?;
+            let (rule, parents) = path.split_last()?;
+            let mut rules = sheet.rulelist();
+            for parent in parents {
+                let parent = rules.item(*parent)?;
+                rules = parent.downcast::<CSSGroupingRule>()?.CssRules();
+            }
+            let rule = rules.item(*rule)?;
+            Some(rule.downcast::<CSSStyleRule>()?.Style())
+        },
+    }
+}
+
+pub fn handle_modify_rule(documents: &Documents,
+                          pipeline: PipelineId,
+                          rule: StyleRuleId,
+                          modifications: Vec<PropertyModification>,
+                          reply: IpcSender<Option<String>>) {
+    let style = match find_rule_style(documents, pipeline, &rule) {
+        None => return reply.send(None).unwrap(),
+        Some(style) => style,
+    };
+
+    for modification in modifications {
+        let name = DOMString::from(modification.name);
+        let _ = match modification.value {
+            Some(value) => style.SetProperty(name,
+                                             DOMString::from(value),
+                                             DOMString::from(modification.priority)),
+            None => style.RemoveProperty(name).map(|_| ()),
+        };
+    }
+    reply.send(Some(String::from(style.CssText()))).unwrap();
+}
+
+pub fn handle_set_rule_text(documents: &Documents,
+                            pipeline: PipelineId,
+                            rule: StyleRuleId,
+                            text: String,
+                            reply: IpcSender<Option<String>>) {
+    let style = match find_rule_style(documents, pipeline, &rule) {
+        None => return reply.send(None).unwrap(),
+        Some(style) => style,
+    };
+
+    let _ = style.SetCssText(DOMString::from(text));
+    reply.send(Some(String::from(style.CssText()))).unwrap();
+}
+
+pub fn handle_get_storage_host(documents: &Documents, pipeline: PipelineId, reply: IpcSender<Option<String>>) {
+    let host = documents.find_document(pipeline).map(|document| document.url().origin().ascii_serialization());
+    reply.send(host).unwrap();
+}
+
+fn cookie_entry(cookie: cookie_rs::Cookie<'static>) -> StoreEntry {
+    StoreEntry {
+        name: cookie.name().to_owned(),
+        value: cookie.value().to_owned(),
+        cookie: Some(CookieAttributes {
+            domain: cookie.domain().unwrap_or("").to_owned(),
+            path: cookie.path().unwrap_or("/").to_owned(),
+            expires: cookie.expires().map(|expires| {
+                let time = expires.to_timespec();
+                time.sec as u64 * 1000 + time.nsec as u64 / 1_000_000
+            }),
+            http_only: cookie.http_only(),
+            secure: cookie.secure(),
+        }),
+    }
+}
+
+fn storage_entries(storage: &Storage) -> Vec<StoreEntry> {
+    storage.SupportedPropertyNames().into_iter().filter_map(|name| {
+        let value = storage.GetItem(name.clone())?;
+        Some(StoreEntry {
+            name: String::from(name),
+            value: String::from(value),
+            cookie: None,
+        })
+    }).collect()
+}
+
+pub fn handle_get_store_entries(documents: &Documents,
+                                pipeline: PipelineId,
+                                store: StoreType,
+                                reply: IpcSender<Vec<StoreEntry>>) {
+    let window = match documents.find_window(pipeline) {
+        None => return reply.send(vec![]).unwrap(),
+        Some(window) => window,
+    };
+    let global = window.upcast::<GlobalScope>();
+    let entries = match store {
+        StoreType::Cookies => {
+            let (tx, rx) = ipc::channel().unwrap();
+            global.resource_threads().send(GetCookiesDataForUrl(global.get_url(), tx, CookieSource::HTTP))
+                                     .unwrap();
+            rx.recv().unwrap().into_iter().map(|cookie| cookie_entry(cookie.into_inner())).collect()
+        },
+        StoreType::LocalStorage => storage_entries(&window.LocalStorage()),
+        StoreType::SessionStorage => storage_entries(&window.SessionStorage()),
+        StoreType::Cache => {
+            let (tx, rx) = ipc::channel().unwrap();
+            global.resource_threads().send(GetCacheEntries(global.get_url(), tx)).unwrap();
+            rx.recv().unwrap().into_iter().map(|(url, status)| StoreEntry {
+                name: url.into_string(),
+                value: status.map(|status| status.to_string()).unwrap_or_default(),
+                cookie: None,
+            }).collect()
+        },
+    };
+    reply.send(entries).unwrap();
+}
+
+pub fn handle_set_store_entry(documents: &Documents, pipeline: PipelineId, store: StoreType, entry: StoreEntry) {
+    let window = match documents.find_window(pipeline) {
+        None => return warn!("Message sent to closed pipeline {}.", pipeline),
+        Some(window) => window,
+    };
+    let global = window.upcast::<GlobalScope>();
+    let name = DOMString::from(entry.name.clone());
+    let value = DOMString::from(entry.value.clone());
+    match store {
+        StoreType::Cookies => {
+            let mut cookie = cookie_rs::Cookie::new(entry.name.clone(), entry.value);
+            if let Some(attributes) = entry.cookie {
+                if !attributes.domain.is_empty() {
+                    cookie.set_domain(attributes.domain);
+                }
+                cookie.set_path(attributes.path);
+                if let Some(expires) = attributes.expires {
+                    cookie.set_expires(time::at_utc(Timespec::new((expires / 1000) as i64, 0)));
+                }
+                cookie.set_http_only(attributes.http_only);
+                cookie.set_secure(attributes.secure);
+            }
+            global.resource_threads().send(SetCookieForUrl(global.get_url(), Serde(cookie), CookieSource::HTTP))
+                                     .unwrap();
+            global.notify_store_changed(store, StoreChange::Changed(entry.name));
+        },
+        StoreType::LocalStorage => {
+            let _ = window.LocalStorage().SetItem(name, value);
+        },
+        StoreType::SessionStorage => {
+            let _ = window.SessionStorage().SetItem(name, value);
+        },
+        StoreType::Cache => warn!("Cache entries can not be edited."),
+    }
+}
+
+pub fn handle_remove_store_entry(documents: &Documents, pipeline: PipelineId, store: StoreType, name: String) {
+    let window = match documents.find_window(pipeline) {
+        None => return warn!("Message sent to closed pipeline {}.", pipeline),
+        Some(window) => window,
+    };
+    let global = window.upcast::<GlobalScope>();
+    match store {
+        StoreType::Cookies => {
+            global.resource_threads().send(DeleteCookies(global.get_url(), Some(name.clone()))).unwrap();
+            global.notify_store_changed(store, StoreChange::Deleted(name));
+        },
+        StoreType::LocalStorage => window.LocalStorage().RemoveItem(DOMString::from(name)),
+        StoreType::SessionStorage => window.SessionStorage().RemoveItem(DOMString::from(name)),
+        StoreType::Cache => {
+            let entry = match ServoUrl::parse(&name) {
+                Ok(entry) => entry,
+                Err(_) => return warn!("Invalid cache entry {}.", name),
+            };
+            global.resource_threads().send(DeleteCacheEntries(global.get_url(), Some(entry))).unwrap();
+            global.notify_store_changed(store, StoreChange::Deleted(name));
+        },
+    }
+}
+
+pub fn handle_clear_store(documents: &Documents, pipeline: PipelineId, store: StoreType) {
+    let window = match documents.find_window(pipeline) {
+        None => return warn!("Message sent to closed pipeline {}.", pipeline),
+        Some(window) => window,
+    };
+    let global = window.upcast::<GlobalScope>();
+    match store {
+        StoreType::Cookies => {
+            global.resource_threads().send(DeleteCookies(global.get_url(), None)).unwrap();
+            global.notify_store_changed(store, StoreChange::Cleared);
+        },
+        StoreType::LocalStorage => window.LocalStorage().Clear(),
+        StoreType::SessionStorage => window.SessionStorage().Clear(),
+        StoreType::Cache => {
+            global.resource_threads().send(DeleteCacheEntries(global.get_url(), None)).unwrap();
+            global.notify_store_changed(store, StoreChange::Cleared);
+        },
+    }
+}
+
 pub fn handle_wants_live_notifications(global: &GlobalScope, send_notifications: bool) {
     global.set_devtools_wants_updates(send_notifications);
 }
@@ -0,0 +1,125 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::ClipboardBinding;
+use dom::bindings::codegen::Bindings::ClipboardBinding::ClipboardMethods;
+use dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use dom::bindings::codegen::Bindings::PermissionStatusBinding::PermissionName;
+use dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use dom::bindings::error::Error;
+use dom::bindings::refcounted::TrustedPromise;
+use dom::bindings::reflector::{DomObject, reflect_dom_object};
+use dom::bindings::root::DomRoot;
+use dom::bindings::str::DOMString;
+use dom::eventtarget::EventTarget;
+use dom::globalscope::GlobalScope;
+use dom::permissions::request_permission_to_use;
+use dom::promise::Promise;
+use dom_struct::dom_struct;
+use embedder_traits::EmbedderMsg;
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use std::rc::Rc;
+use task_source::{TaskSource, TaskSourceName};
+
+// https://w3c.github.io/clipboard-apis/#clipboard-interface
+#[dom_struct]
+pub struct Clipboard {
+    eventtarget: EventTarget,
+}
+
+impl Clipboard {
+    fn new_inherited() -> Clipboard {
+        Clipboard {
+            eventtarget: EventTarget::new_inherited(),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<Clipboard> {
+        reflect_dom_object(Box::new(Clipboard::new_inherited()),
+                           global,
+                           ClipboardBinding::Wrap)
+    }
+
+    /// Whether the document may access the clipboard. It has to be the focused document of a
+    /// top-level browsing context, so that no frame can access the clipboard behind the back of
+    /// the page that the user sees, and the user has to have just interacted with it. Reading
+    /// the clipboard also requires the permission of the user.
+    ///
+    /// The bindings do not enforce `[SecureContext]` yet, so the document is also checked to be
+    /// a secure context here.
+    fn may_access_clipboard(&self, permission_name: Option<PermissionName>) -> bool {
+        let global = self.global();
+        // https://html.spec.whatwg.org/multipage/#secure-context
+        if !global.get_url().is_potentially_trustworthy() {
+            return false;
+        }
+        let window = global.as_window();
+        if !window.is_top_level() || !window.Document().HasFocus() || !window.has_transient_activation() {
+            return false;
+        }
+        permission_name.map_or(true, |name| request_permission_to_use(name, &global))
+    }
+}
+
+impl ClipboardMethods for Clipboard {
+    #[allow(unrooted_must_root)]
+    // https://w3c.github.io/clipboard-apis/#dom-clipboard-readtext
+    fn ReadText(&self) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global);
+        if !self.may_access_clipboard(Some(PermissionName::Clipboard_read)) {
+            promise.reject_error(Error::NotAllowed);
+            return promise;
+        }
+
+        // The embedder may take a while to read the clipboard, so the promise is settled from a
+        // task once it replies.
+        let window = global.as_window();
+        let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+        let mut trusted_promise = Some(TrustedPromise::new(promise.clone()));
+        let task_source = window.user_interaction_task_source();
+        let canceller = window.task_canceller(TaskSourceName::UserInteraction);
+        ROUTER.add_route(receiver.to_opaque(), Box::new(move |message| {
+            let promise = match trusted_promise.take() {
+                Some(promise) => promise,
+                None => return,
+            };
+            let text = message.to::<String>().map_err(|_| ());
+            let _ = task_source.queue_with_canceller(
+                task!(resolve_clipboard_text: move || {
+                    let promise = promise.root();
+                    match text {
+                        Ok(text) => promise.resolve_native(&DOMString::from(text)),
+                        Err(()) => promise.reject_error(Error::NotReadable),
+                    }
+                }),
+                &canceller,
+            );
+        }));
+        window.send_to_embedder(EmbedderMsg::GetClipboardContents(sender));
+        promise
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://w3c.github.io/clipboard-apis/#dom-clipboard-writetext
+    fn WriteText(&self, data: DOMString) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global);
+        if !self.may_access_clipboard(None) {
+            promise.reject_error(Error::NotAllowed);
+            return promise;
+        }
+        let window = global.as_window();
+        window.send_to_embedder(EmbedderMsg::SetClipboardContents(String::from(data)));
+        let promise_to_resolve = TrustedPromise::new(promise.clone());
+        let _ = window.user_interaction_task_source().queue_with_canceller(
+            task!(resolve_clipboard_write: move || {
+                promise_to_resolve.root().resolve_native(&());
+            }),
+            &window.task_canceller(TaskSourceName::UserInteraction),
+        );
+        promise
+    }
+}
@@ -0,0 +1,92 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::ClipboardEventBinding;
+use dom::bindings::codegen::Bindings::ClipboardEventBinding::ClipboardEventMethods;
+use dom::bindings::codegen::Bindings::EventBinding::EventMethods;
+use dom::bindings::error::Fallible;
+use dom::bindings::inheritance::Castable;
+use dom::bindings::reflector::reflect_dom_object;
+use dom::bindings::root::{DomRoot, MutNullableDom, RootedReference};
+use dom::bindings::str::DOMString;
+use dom::datatransfer::DataTransfer;
+use dom::event::{Event, EventBubbles, EventCancelable};
+use dom::window::Window;
+use dom_struct::dom_struct;
+use servo_atoms::Atom;
+use textinput::ClipboardAction;
+
+#[dom_struct]
+pub struct ClipboardEvent {
+    event: Event,
+    clipboard_data: MutNullableDom<DataTransfer>,
+}
+
+impl ClipboardEvent {
+    fn new_inherited(clipboard_data: Option<&DataTransfer>) -> ClipboardEvent {
+        ClipboardEvent {
+            event: Event::new_inherited(),
+            clipboard_data: MutNullableDom::new(clipboard_data),
+        }
+    }
+
+    pub fn new(window: &Window,
+               type_: Atom,
+               bubbles: EventBubbles,
+               cancelable: EventCancelable,
+               clipboard_data: Option<&DataTransfer>)
+               -> DomRoot<ClipboardEvent> {
+        let ev = reflect_dom_object(Box::new(ClipboardEvent::new_inherited(clipboard_data)),
+                                    window,
+                                    ClipboardEventBinding::Wrap);
+        {
+            let event = ev.upcast::<Event>();
+            event.init_event(type_, bool::from(bubbles), bool::from(cancelable));
+        }
+        ev
+    }
+
+    pub fn Constructor(window: &Window,
+                       type_: DOMString,
+                       init: &ClipboardEventBinding::ClipboardEventInit)
+                       -> Fallible<DomRoot<ClipboardEvent>> {
+        let bubbles = EventBubbles::from(init.parent.bubbles);
+        let cancelable = EventCancelable::from(init.parent.cancelable);
+        Ok(ClipboardEvent::new(window,
+                               Atom::from(type_),
+                               bubbles,
+                               cancelable,
+                               init.clipboardData.r()))
+    }
+
+    /// The default action of this event on a focused text input, if any.
+    pub fn text_input_action(&self) -> Option<ClipboardAction> {
+        let event = self.upcast::<Event>();
+        if !event.IsTrusted() || event.DefaultPrevented() {
+            return None;
+        }
+        match event.type_() {
+            atom!("copy") => Some(ClipboardAction::Copy),
+            atom!("cut") => Some(ClipboardAction::Cut),
+            atom!("paste") => {
+                let data_transfer = self.clipboard_data.get();
+                let text = data_transfer.and_then(|data_transfer| data_transfer.data("text/plain"));
+                Some(ClipboardAction::Paste(text.unwrap_or_default()))
+            },
+            _ => None,
+        }
+    }
+}
+
+impl ClipboardEventMethods for ClipboardEvent {
+    // https://w3c.github.io/clipboard-apis/#dom-clipboardevent-clipboarddata
+    fn GetClipboardData(&self) -> Option<DomRoot<DataTransfer>> {
+        self.clipboard_data.get()
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.event.IsTrusted()
+    }
+}
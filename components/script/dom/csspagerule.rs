@@ -0,0 +1,74 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::CSSPageRuleBinding::{self, CSSPageRuleMethods};
+use dom::bindings::inheritance::Castable;
+use dom::bindings::reflector::{DomObject, reflect_dom_object};
+use dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use dom::bindings::str::DOMString;
+use dom::cssrule::{CSSRule, SpecificCSSRule};
+use dom::cssstyledeclaration::{CSSModificationAccess, CSSStyleDeclaration, CSSStyleOwner};
+use dom::cssstylesheet::CSSStyleSheet;
+use dom::window::Window;
+use dom_struct::dom_struct;
+use servo_arc::Arc;
+use style::shared_lock::{Locked, ToCssWithGuard};
+use style::stylesheets::PageRule;
+
+#[dom_struct]
+pub struct CSSPageRule {
+    cssrule: CSSRule,
+    #[ignore_malloc_size_of = "Arc"]
+    pagerule: Arc<Locked<PageRule>>,
+    style_decl: MutNullableDom<CSSStyleDeclaration>,
+}
+
+impl CSSPageRule {
+    fn new_inherited(parent_stylesheet: &CSSStyleSheet, pagerule: Arc<Locked<PageRule>>)
+                     -> CSSPageRule {
+        CSSPageRule {
+            cssrule: CSSRule::new_inherited(parent_stylesheet),
+            pagerule: pagerule,
+            style_decl: Default::default(),
+        }
+    }
+
+    #[allow(unrooted_must_root)]
+    pub fn new(window: &Window, parent_stylesheet: &CSSStyleSheet,
+               pagerule: Arc<Locked<PageRule>>) -> DomRoot<CSSPageRule> {
+        reflect_dom_object(Box::new(CSSPageRule::new_inherited(parent_stylesheet, pagerule)),
+                           window,
+                           CSSPageRuleBinding::Wrap)
+    }
+}
+
+impl SpecificCSSRule for CSSPageRule {
+    fn ty(&self) -> u16 {
+        use dom::bindings::codegen::Bindings::CSSRuleBinding::CSSRuleConstants;
+        CSSRuleConstants::PAGE_RULE
+    }
+
+    fn get_css(&self) -> DOMString {
+        let guard = self.cssrule.shared_lock().read();
+        self.pagerule.read_with(&guard).to_css_string(&guard).into()
+    }
+}
+
+impl CSSPageRuleMethods for CSSPageRule {
+    // https://drafts.csswg.org/cssom/#dom-csspagerule-style
+    fn Style(&self) -> DomRoot<CSSStyleDeclaration> {
+        self.style_decl.or_init(|| {
+            let guard = self.cssrule.shared_lock().read();
+            CSSStyleDeclaration::new(
+                self.global().as_window(),
+                CSSStyleOwner::CSSRule(
+                    Dom::from_ref(self.upcast()),
+                    self.pagerule.read_with(&guard).block.clone()
+                ),
+                None,
+                CSSModificationAccess::ReadWrite
+            )
+        })
+    }
+}
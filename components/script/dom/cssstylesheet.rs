@@ -2,6 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+use cssparser::SourceLocation;
 use dom::bindings::codegen::Bindings::CSSStyleSheetBinding;
 use dom::bindings::codegen::Bindings::CSSStyleSheetBinding::CSSStyleSheetMethods;
 use dom::bindings::codegen::Bindings::WindowBinding::WindowBinding::WindowMethods;
@@ -15,8 +16,12 @@ use dom::stylesheet::StyleSheet;
 use dom::window::Window;
 use dom_struct::dom_struct;
 use servo_arc::Arc;
+use servo_url::ServoUrl;
 use std::cell::Cell;
+use style::context::QuirksMode;
+use style::error_reporting::{ContextualParseError, ParseErrorReporter};
 use style::shared_lock::SharedRwLock;
+use style::stylesheets::{Origin, StylesheetContents};
 use style::stylesheets::Stylesheet as StyleStyleSheet;
 
 #[dom_struct]
@@ -56,7 +61,7 @@ impl CSSStyleSheet {
                            CSSStyleSheetBinding::Wrap)
     }
 
-    fn rulelist(&self) -> DomRoot<CSSRuleList> {
+    pub fn rulelist(&self) -> DomRoot<CSSRuleList> {
         self.rulelist.or_init(|| {
             let rules = self.style_stylesheet.contents.rules.clone();
             CSSRuleList::new(
@@ -88,6 +93,55 @@ impl CSSStyleSheet {
     pub fn set_origin_clean(&self, origin_clean: bool) {
         self.origin_clean.set(origin_clean);
     }
+
+    /// Replace the rules of this stylesheet with the ones parsed from `css`, as done by the
+    /// devtools style editor. Text with parse errors is rejected, leaving the rules as they
+    /// were.
+    pub fn replace_rules(&self, css: &str) -> Result<(), ()> {
+        let global = self.global();
+        let window = global.as_window();
+        let url_data = self.style_stylesheet.contents.url_data.read().clone();
+        let quirks_mode = self.style_stylesheet.contents.quirks_mode;
+        if !parses_without_errors(css, url_data.clone(), quirks_mode, window.css_error_reporter()) {
+            return Err(());
+        }
+
+        if let Some(rulelist) = self.rulelist.get() {
+            rulelist.deparent_all();
+        }
+        self.rulelist.set(None);
+        StyleStyleSheet::update_from_str(&self.style_stylesheet, css, url_data, None, None, 0);
+        window.Document().invalidate_stylesheets();
+        Ok(())
+    }
+}
+
+/// Counts the errors of a parse, passing them on to another reporter.
+struct CountingErrorReporter<'a> {
+    reporter: Option<&'a ParseErrorReporter>,
+    errors: Cell<usize>,
+}
+
+impl<'a> ParseErrorReporter for CountingErrorReporter<'a> {
+    fn report_error(&self, url: &ServoUrl, location: SourceLocation, error: ContextualParseError) {
+        self.errors.set(self.errors.get() + 1);
+        if let Some(reporter) = self.reporter {
+            reporter.report_error(url, location, error);
+        }
+    }
+}
+
+/// Whether `css` parses as an author stylesheet without errors, which are reported to
+/// `reporter`.
+pub fn parses_without_errors(css: &str,
+                             url_data: ServoUrl,
+                             quirks_mode: QuirksMode,
+                             reporter: Option<&ParseErrorReporter>)
+                             -> bool {
+    let counter = CountingErrorReporter { reporter: reporter, errors: Cell::new(0) };
+    StylesheetContents::from_str(css, url_data, Origin::Author, &SharedRwLock::new(), None,
+                                 Some(&counter), quirks_mode, 0);
+    counter.errors.get() == 0
 }
 
 impl CSSStyleSheetMethods for CSSStyleSheet {
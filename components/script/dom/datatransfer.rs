@@ -0,0 +1,217 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::cell::DomRefCell;
+use dom::bindings::codegen::Bindings::DataTransferBinding;
+use dom::bindings::codegen::Bindings::DataTransferBinding::DataTransferMethods;
+use dom::bindings::error::Fallible;
+use dom::bindings::reflector::{DomObject, Reflector, reflect_dom_object};
+use dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use dom::bindings::str::DOMString;
+use dom::element::Element;
+use dom::file::File;
+use dom::filelist::FileList;
+use dom::window::Window;
+use dom_struct::dom_struct;
+use js::conversions::ToJSValConvertible;
+use js::jsapi::JSContext;
+use js::jsval::{JSVal, UndefinedValue};
+use std::cell::Cell;
+
+/// <https://html.spec.whatwg.org/multipage/#drag-data-store-mode>
+#[derive(Clone, Copy, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum DataTransferMode {
+    /// Script can add and remove data, as during `dragstart`, `copy` and `cut`.
+    ReadWrite,
+    /// Script can read data, as during `drop` and `paste`.
+    ReadOnly,
+    /// Script can only see the types of the data.
+    Protected,
+}
+
+#[dom_struct]
+pub struct DataTransfer {
+    reflector_: Reflector,
+    mode: Cell<DataTransferMode>,
+    drop_effect: DomRefCell<DOMString>,
+    effect_allowed: DomRefCell<DOMString>,
+    /// The plain Unicode string items of the drag data store, with their type, in order.
+    items: DomRefCell<Vec<(DOMString, DOMString)>>,
+    /// The File items of the drag data store.
+    files: Vec<Dom<File>>,
+    file_list: MutNullableDom<FileList>,
+}
+
+impl DataTransfer {
+    #[allow(unrooted_must_root)]
+    fn new_inherited(mode: DataTransferMode, files: Vec<Dom<File>>) -> DataTransfer {
+        DataTransfer {
+            reflector_: Reflector::new(),
+            mode: Cell::new(mode),
+            drop_effect: DomRefCell::new(DOMString::from("none")),
+            effect_allowed: DomRefCell::new(DOMString::from("uninitialized")),
+            items: DomRefCell::new(vec![]),
+            files: files,
+            file_list: Default::default(),
+        }
+    }
+
+    #[allow(unrooted_must_root)]
+    pub fn new(window: &Window, mode: DataTransferMode, files: Vec<DomRoot<File>>) -> DomRoot<DataTransfer> {
+        let files = files.iter().map(|file| Dom::from_ref(&**file)).collect();
+        reflect_dom_object(Box::new(DataTransfer::new_inherited(mode, files)),
+                           window,
+                           DataTransferBinding::Wrap)
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-datatransfer
+    pub fn Constructor(window: &Window) -> Fallible<DomRoot<DataTransfer>> {
+        let data_transfer = DataTransfer::new(window, DataTransferMode::ReadWrite, vec![]);
+        *data_transfer.effect_allowed.borrow_mut() = DOMString::from("none");
+        Ok(data_transfer)
+    }
+
+    pub fn set_mode(&self, mode: DataTransferMode) {
+        self.mode.set(mode);
+    }
+
+    pub fn set_drop_effect(&self, drop_effect: &str) {
+        *self.drop_effect.borrow_mut() = DOMString::from(drop_effect);
+    }
+
+    /// Sets the drop effect to the one that the effects allowed by the source suggest, as before
+    /// `dragenter` and `dragover` events.
+    pub fn reset_drop_effect(&self) {
+        let drop_effect = match &**self.effect_allowed.borrow() {
+            "none" => "none",
+            "link" | "linkMove" => "link",
+            "move" => "move",
+            _ => "copy",
+        };
+        self.set_drop_effect(drop_effect);
+    }
+
+    /// Adds a plain Unicode string item to the drag data store, whatever its mode.
+    pub fn add_data(&self, format: &str, data: DOMString) {
+        let format = normalize_format(format).0;
+        let mut items = self.items.borrow_mut();
+        items.retain(|&(ref type_, _)| *type_ != format);
+        items.push((format, data));
+    }
+
+    /// Returns the plain Unicode string item of the given type, whatever the mode of the drag
+    /// data store.
+    pub fn data(&self, format: &str) -> Option<DOMString> {
+        let format = normalize_format(format).0;
+        self.items.borrow().iter().find(|&&(ref type_, _)| *type_ == format).map(|&(_, ref data)| data.clone())
+    }
+}
+
+/// Returns the type of the items that a format designates, and whether the first URL of the item
+/// is requested.
+fn normalize_format(format: &str) -> (DOMString, bool) {
+    let format = format.to_ascii_lowercase();
+    match &*format {
+        "text" => (DOMString::from("text/plain"), false),
+        "url" => (DOMString::from("text/uri-list"), true),
+        _ => (DOMString::from(format), false),
+    }
+}
+
+impl DataTransferMethods for DataTransfer {
+    // https://html.spec.whatwg.org/multipage/#dom-datatransfer-dropeffect
+    fn DropEffect(&self) -> DOMString {
+        self.drop_effect.borrow().clone()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-datatransfer-dropeffect
+    fn SetDropEffect(&self, value: DOMString) {
+        match &*value {
+            "none" | "copy" | "link" | "move" => *self.drop_effect.borrow_mut() = value,
+            _ => {},
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-datatransfer-effectallowed
+    fn EffectAllowed(&self) -> DOMString {
+        self.effect_allowed.borrow().clone()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-datatransfer-effectallowed
+    fn SetEffectAllowed(&self, value: DOMString) {
+        if self.mode.get() != DataTransferMode::ReadWrite {
+            return;
+        }
+        match &*value {
+            "none" | "copy" | "copyLink" | "copyMove" | "link" | "linkMove" | "move" | "all" |
+            "uninitialized" => *self.effect_allowed.borrow_mut() = value,
+            _ => {},
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-datatransfer-setdragimage
+    fn SetDragImage(&self, _image: &Element, _x: i32, _y: i32) {
+        // Servo doesn't draw the data being dragged, so there is no drag image to change.
+    }
+
+    #[allow(unsafe_code)]
+    // https://html.spec.whatwg.org/multipage/#dom-datatransfer-types
+    unsafe fn Types(&self, cx: *mut JSContext) -> JSVal {
+        let mut types: Vec<DOMString> = self.items.borrow().iter().map(|&(ref type_, _)| type_.clone()).collect();
+        if !self.files.is_empty() {
+            types.push(DOMString::from("Files"));
+        }
+        rooted!(in(cx) let mut rval = UndefinedValue());
+        types.to_jsval(cx, rval.handle_mut());
+        rval.get()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-datatransfer-getdata
+    fn GetData(&self, format: DOMString) -> DOMString {
+        if self.mode.get() == DataTransferMode::Protected {
+            return DOMString::new();
+        }
+        let (format, convert_to_url) = normalize_format(&format);
+        let items = self.items.borrow();
+        let data = match items.iter().find(|&&(ref type_, _)| *type_ == format) {
+            Some(&(_, ref data)) => data,
+            None => return DOMString::new(),
+        };
+        if !convert_to_url {
+            return data.clone();
+        }
+        let url = data.lines().map(str::trim).find(|line| !line.is_empty() && !line.starts_with('#'));
+        DOMString::from(url.unwrap_or(""))
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-datatransfer-setdata
+    fn SetData(&self, format: DOMString, data: DOMString) {
+        if self.mode.get() == DataTransferMode::ReadWrite {
+            self.add_data(&format, data);
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-datatransfer-cleardata
+    fn ClearData(&self, format: Option<DOMString>) {
+        if self.mode.get() != DataTransferMode::ReadWrite {
+            return;
+        }
+        let mut items = self.items.borrow_mut();
+        match format {
+            Some(format) => {
+                let format = normalize_format(&format).0;
+                items.retain(|&(ref type_, _)| *type_ != format);
+            },
+            None => items.clear(),
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-datatransfer-files
+    fn Files(&self) -> DomRoot<FileList> {
+        self.file_list.or_init(|| {
+            let files = self.files.iter().map(|file| DomRoot::from_ref(&**file)).collect();
+            FileList::new(self.global().as_window(), files)
+        })
+    }
+}
@@ -186,6 +186,7 @@ impl DedicatedWorkerGlobalScope {
                 referrer_url: referrer_url,
                 referrer_policy: referrer_policy,
                 origin,
+                insecure_requests_policy: init.insecure_requests_policy,
                 .. RequestInit::default()
             };
 
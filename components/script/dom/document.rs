@@ -3,7 +3,7 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
 use cookie_rs;
-use devtools_traits::ScriptToDevtoolsControlMsg;
+use devtools_traits::{ScriptToDevtoolsControlMsg, StoreChange, StoreType};
 use document_loader::{DocumentLoader, LoadType};
 use dom::activation::{ActivationSource, synthetic_click_activation};
 use dom::attr::Attr;
@@ -11,6 +11,7 @@ use dom::beforeunloadevent::BeforeUnloadEvent;
 use dom::bindings::callback::ExceptionHandling;
 use dom::bindings::cell::DomRefCell;
 use dom::bindings::codegen::Bindings::BeforeUnloadEventBinding::BeforeUnloadEventBinding::BeforeUnloadEventMethods;
+use dom::bindings::codegen::Bindings::DataTransferBinding::DataTransferMethods;
 use dom::bindings::codegen::Bindings::DocumentBinding;
 use dom::bindings::codegen::Bindings::DocumentBinding::{DocumentMethods, DocumentReadyState, ElementCreationOptions};
 use dom::bindings::codegen::Bindings::EventBinding::EventBinding::EventMethods;
@@ -19,6 +20,7 @@ use dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
 use dom::bindings::codegen::Bindings::NodeFilterBinding::NodeFilter;
 use dom::bindings::codegen::Bindings::PerformanceBinding::PerformanceMethods;
 use dom::bindings::codegen::Bindings::TouchBinding::TouchMethods;
+use dom::bindings::codegen::Bindings::WheelEventBinding::WheelEventConstants;
 use dom::bindings::codegen::Bindings::WindowBinding::{FrameRequestCallback, ScrollBehavior, WindowMethods};
 use dom::bindings::codegen::UnionTypes::NodeOrString;
 use dom::bindings::error::{Error, ErrorResult, Fallible};
@@ -30,20 +32,25 @@ use dom::bindings::root::{Dom, DomRoot, LayoutDom, MutNullableDom, RootedReferen
 use dom::bindings::str::{DOMString, USVString};
 use dom::bindings::xmlname::{namespace_from_domstring, validate_and_extract, xml_name_type};
 use dom::bindings::xmlname::XMLName::InvalidXMLName;
+use dom::clipboardevent::ClipboardEvent;
 use dom::closeevent::CloseEvent;
 use dom::comment::Comment;
 use dom::cssstylesheet::CSSStyleSheet;
 use dom::customelementregistry::CustomElementDefinition;
 use dom::customevent::CustomEvent;
+use dom::datatransfer::{DataTransfer, DataTransferMode};
 use dom::documentfragment::DocumentFragment;
 use dom::documenttype::DocumentType;
 use dom::domimplementation::DOMImplementation;
+use dom::dragevent::DragEvent;
 use dom::element::{Element, ElementCreator, ElementPerformFullscreenEnter, ElementPerformFullscreenExit};
 use dom::element::CustomElementCreationMode;
 use dom::errorevent::ErrorEvent;
 use dom::event::{Event, EventBubbles, EventCancelable, EventDefault, EventStatus};
 use dom::eventtarget::EventTarget;
+use dom::file::File;
 use dom::focusevent::FocusEvent;
+use dom::fontfaceset::FontFaceSet;
 use dom::globalscope::GlobalScope;
 use dom::hashchangeevent::HashChangeEvent;
 use dom::htmlanchorelement::HTMLAnchorElement;
@@ -71,6 +78,7 @@ use dom::node::VecPreOrderInsertionHelper;
 use dom::nodeiterator::NodeIterator;
 use dom::nodelist::NodeList;
 use dom::pagetransitionevent::PageTransitionEvent;
+use dom::pointerevent::PointerEvent;
 use dom::popstateevent::PopStateEvent;
 use dom::processinginstruction::ProcessingInstruction;
 use dom::progressevent::ProgressEvent;
@@ -87,10 +95,11 @@ use dom::treewalker::TreeWalker;
 use dom::uievent::UIEvent;
 use dom::virtualmethods::vtable_for;
 use dom::webglcontextevent::WebGLContextEvent;
+use dom::wheelevent::WheelEvent;
 use dom::window::{ReflowReason, Window};
 use dom::windowproxy::WindowProxy;
 use dom_struct::dom_struct;
-use embedder_traits::EmbedderMsg;
+use embedder_traits::{EmbedderMsg, SecurityState};
 use encoding_rs::{Encoding, UTF_8};
 use euclid::Point2D;
 use fetch::FetchCanceller;
@@ -106,18 +115,20 @@ use msg::constellation_msg::{BrowsingContextId, Key, KeyModifiers, KeyState};
 use net_traits::{FetchResponseMsg, IpcSend, ReferrerPolicy};
 use net_traits::CookieSource::NonHTTP;
 use net_traits::CoreResourceMsg::{GetCookiesForUrl, SetCookiesForUrl};
+use net_traits::filemanager_thread::SelectedFile;
 use net_traits::pub_domains::is_pub_domain;
-use net_traits::request::RequestInit;
+use net_traits::request::{InsecureRequestsPolicy, RequestInit, is_mixed_content};
+use net_traits::request::upgrade_to_potentially_trustworthy_url;
 use net_traits::response::HttpsState;
 use num_traits::ToPrimitive;
 use profile_traits::ipc as profile_ipc;
 use profile_traits::time::{TimerMetadata, TimerMetadataFrameType, TimerMetadataReflowType};
 use ref_slice::ref_slice;
 use script_layout_interface::message::{Msg, NodesFromPointQueryType, QueryMsg, ReflowGoal};
-use script_runtime::{CommonScriptMsg, ScriptThreadEventCategory};
+use script_runtime::{CommonScriptMsg, ScriptThreadEventCategory, get_dom_object_size};
 use script_thread::{MainThreadScriptMsg, ScriptThread};
-use script_traits::{AnimationState, DocumentActivity, MouseButton, MouseEventType};
-use script_traits::{MsDuration, ScriptMsg, TouchEventType, TouchId, UntrustedNodeAddress};
+use script_traits::{AllowedTouchActions, AnimationState, DocumentActivity, EventResult, MouseButton, MouseEventType};
+use script_traits::{MsDuration, ScriptMsg, TouchEventType, TouchId, UntrustedNodeAddress, WheelDelta, WheelMode};
 use servo_arc::Arc;
 use servo_atoms::Atom;
 use servo_config::prefs::PREFS;
@@ -140,8 +151,11 @@ use style::selector_parser::{RestyleDamage, Snapshot};
 use style::shared_lock::{SharedRwLock as StyleSharedRwLock, SharedRwLockReadGuard};
 use style::str::{split_html_space_chars, str_join};
 use style::stylesheet_set::DocumentStylesheetSet;
-use style::stylesheets::{CssRule, Stylesheet, Origin, OriginSet};
+use style::stylesheets::{CssRule, FontFaceRule, Stylesheet, Origin, OriginSet};
+use style::stylesheets::StylesheetInDocument as StylesheetInDocumentTrait;
+use style::values::computed::TouchAction;
 use task_source::{TaskSource, TaskSourceName};
+use textinput::is_control_key;
 use time;
 use timers::OneshotTimerCallback;
 use url::Host;
@@ -156,8 +170,11 @@ const SPURIOUS_ANIMATION_FRAME_THRESHOLD: u8 = 5;
 /// The amount of time between fake `requestAnimationFrame()`s.
 const FAKE_REQUEST_ANIMATION_FRAME_DELAY: u64 = 16;
 
+/// The pointer id of the mouse. Touch points are pointers with the ids after it.
+const MOUSE_POINTER_ID: i32 = 1;
+
 pub enum TouchEventResult {
-    Processed(bool),
+    Processed(EventResult),
     Forwarded,
 }
 
@@ -165,6 +182,8 @@ pub enum FireMouseEventType {
     Move,
     Over,
     Out,
+    Enter,
+    Leave,
 }
 
 impl FireMouseEventType {
@@ -173,10 +192,132 @@ impl FireMouseEventType {
             &FireMouseEventType::Move => "mousemove",
             &FireMouseEventType::Over => "mouseover",
             &FireMouseEventType::Out => "mouseout",
+            &FireMouseEventType::Enter => "mouseenter",
+            &FireMouseEventType::Leave => "mouseleave",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum PointerEventType {
+    Over,
+    Enter,
+    Down,
+    Move,
+    Up,
+    Cancel,
+    Out,
+    Leave,
+    GotCapture,
+    LostCapture,
+}
+
+impl PointerEventType {
+    fn as_str(&self) -> &str {
+        match *self {
+            PointerEventType::Over => "pointerover",
+            PointerEventType::Enter => "pointerenter",
+            PointerEventType::Down => "pointerdown",
+            PointerEventType::Move => "pointermove",
+            PointerEventType::Up => "pointerup",
+            PointerEventType::Cancel => "pointercancel",
+            PointerEventType::Out => "pointerout",
+            PointerEventType::Leave => "pointerleave",
+            PointerEventType::GotCapture => "gotpointercapture",
+            PointerEventType::LostCapture => "lostpointercapture",
+        }
+    }
+
+    fn bubbles(&self) -> EventBubbles {
+        match *self {
+            PointerEventType::Enter | PointerEventType::Leave => EventBubbles::DoesNotBubble,
+            _ => EventBubbles::Bubbles,
+        }
+    }
+
+    fn cancelable(&self) -> EventCancelable {
+        match *self {
+            PointerEventType::Over |
+            PointerEventType::Down |
+            PointerEventType::Move |
+            PointerEventType::Up |
+            PointerEventType::Out => EventCancelable::Cancelable,
+            _ => EventCancelable::NotCancelable,
         }
     }
 }
 
+#[derive(Clone, Copy, JSTraceable, MallocSizeOf, PartialEq)]
+enum PointerType {
+    Mouse,
+    Touch,
+}
+
+impl PointerType {
+    fn as_str(&self) -> &str {
+        match *self {
+            PointerType::Mouse => "mouse",
+            PointerType::Touch => "touch",
+        }
+    }
+}
+
+/// <https://w3c.github.io/pointerevents/#dfn-active-pointer>
+#[derive(JSTraceable, MallocSizeOf)]
+#[must_root]
+struct ActivePointer {
+    pointer_type: PointerType,
+    /// <https://w3c.github.io/pointerevents/#dom-pointerevent-isprimary>
+    is_primary: bool,
+    /// The pressed buttons, as a bitmask in the format of `MouseEvent.buttons`. A touch point
+    /// presses the primary button while it is in contact with the screen.
+    buttons: u16,
+    /// Whether `pointerdown` was canceled, which prevents the compatibility mouse events until
+    /// the pointer is released.
+    mouse_events_prevented: bool,
+    /// The element that the pointer is over, as of its last `pointerover` event.
+    over_target: Option<Dom<Element>>,
+    /// <https://w3c.github.io/pointerevents/#dfn-pending-pointer-capture-target-override>
+    pending_capture_target: Option<Dom<Element>>,
+    /// <https://w3c.github.io/pointerevents/#dfn-pointer-capture-target-override>
+    capture_target: Option<Dom<Element>>,
+}
+
+impl ActivePointer {
+    fn new(pointer_type: PointerType, is_primary: bool, buttons: u16) -> ActivePointer {
+        ActivePointer {
+            pointer_type,
+            is_primary,
+            buttons,
+            mouse_events_prevented: false,
+            over_target: None,
+            pending_capture_target: None,
+            capture_target: None,
+        }
+    }
+}
+
+/// The distance in CSS pixels that the mouse has to move while pressed on a draggable element
+/// to start dragging it.
+const DRAG_THRESHOLD: f32 = 3.0;
+
+/// A drag-and-drop operation of the mouse, from the time it is pressed on a draggable element.
+/// <https://html.spec.whatwg.org/multipage/#drag-and-drop-processing-model>
+#[derive(JSTraceable, MallocSizeOf)]
+#[must_root]
+struct DragOperation {
+    /// <https://html.spec.whatwg.org/multipage/#source-node>
+    source: Dom<Element>,
+    /// The point where the mouse was pressed.
+    start_point: Point2D<f32>,
+    /// The drag data store, once `dragstart` has been fired and not canceled.
+    data_transfer: Option<Dom<DataTransfer>>,
+    /// <https://html.spec.whatwg.org/multipage/#current-target-element>
+    current_target: Option<Dom<Element>>,
+    /// Whether the last `dragover` event was canceled with a drop effect, which allows a drop.
+    drop_allowed: bool,
+}
+
 #[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
 pub enum IsHTMLDocument {
     HTMLDocument,
@@ -282,6 +423,8 @@ pub struct Document {
     /// List of stylesheets associated with nodes in this document. |None| if the list needs to be refreshed.
     stylesheets: DomRefCell<DocumentStylesheetSet<StyleSheetInDocument>>,
     stylesheet_list: MutNullableDom<StyleSheetList>,
+    /// <https://drafts.csswg.org/css-font-loading/#dom-fontfacesource-fonts>
+    fonts: MutNullableDom<FontFaceSet>,
     ready_state: Cell<DocumentReadyState>,
     /// Whether the DOMContentLoaded event has already been dispatched.
     domcontentloaded_dispatched: Cell<bool>,
@@ -334,6 +477,16 @@ pub struct Document {
     needs_paint: Cell<bool>,
     /// <http://w3c.github.io/touch-events/#dfn-active-touch-point>
     active_touch_points: DomRefCell<Vec<Dom<Touch>>>,
+    /// <https://w3c.github.io/pointerevents/#dfn-active-pointer>, by pointer id.
+    active_pointers: DomRefCell<HashMap<i32, ActivePointer>>,
+    /// The point where the primary touch point was last lifted, and whether the compatibility
+    /// mouse events of the tap are prevented, until the compositor has simulated them.
+    touch_tap: Cell<Option<(Point2D<f32>, bool)>>,
+    /// The drag-and-drop operation of the mouse, if it was pressed on a draggable element.
+    drag_operation: DomRefCell<Option<DragOperation>>,
+    /// The point where the mouse was released to drop, until the compositor has simulated the
+    /// click that it suppresses.
+    drag_end_point: Cell<Option<Point2D<f32>>>,
     /// Navigation Timing properties:
     /// <https://w3c.github.io/navigation-timing/#sec-PerformanceNavigationTiming>
     dom_loading: Cell<u64>,
@@ -346,6 +499,11 @@ pub struct Document {
     load_event_end: Cell<u64>,
     /// <https://html.spec.whatwg.org/multipage/#concept-document-https-state>
     https_state: Cell<HttpsState>,
+    /// <https://w3c.github.io/webappsec-upgrade-insecure-requests/#insecure-requests-policy>
+    insecure_requests_policy: Cell<InsecureRequestsPolicy>,
+    /// The security state of this document, as reported to the embedder.
+    #[ignore_malloc_size_of = "Defined in embedder_traits"]
+    security_state: Cell<SecurityState>,
     /// The document's origin.
     origin: MutableOrigin,
     ///  https://w3c.github.io/webappsec-referrer-policy/#referrer-policy-states
@@ -392,6 +550,9 @@ pub struct Document {
     salvageable: Cell<bool>,
     /// Whether the unload event has already been fired.
     fired_unload: Cell<bool>,
+    /// Whether the document has been unloaded into the back-forward cache, in which case
+    /// its tasks are held back until it becomes fully active again.
+    frozen: Cell<bool>,
 }
 
 #[derive(JSTraceable, MallocSizeOf)]
@@ -479,6 +640,49 @@ impl Document {
 
     pub fn set_https_state(&self, https_state: HttpsState) {
         self.https_state.set(https_state);
+        let security_state = match https_state {
+            HttpsState::None => SecurityState::Insecure,
+            HttpsState::Deprecated | HttpsState::Modern => SecurityState::Secure,
+        };
+        self.security_state.set(security_state);
+        if self.window.is_top_level() {
+            self.send_to_embedder(EmbedderMsg::SecurityStateChanged(security_state));
+        }
+    }
+
+    pub fn insecure_requests_policy(&self) -> InsecureRequestsPolicy {
+        self.insecure_requests_policy.get()
+    }
+
+    pub fn set_insecure_requests_policy(&self, policy: InsecureRequestsPolicy) {
+        self.insecure_requests_policy.set(policy);
+    }
+
+    /// Applies this document's fetch settings to a subresource request, and
+    /// reports mixed content in it to the embedder.
+    /// <https://w3c.github.io/webappsec-mixed-content/#should-block-fetch>
+    pub fn prepare_subresource_request(&self, request: &mut RequestInit) {
+        request.insecure_requests_policy = self.insecure_requests_policy.get();
+
+        let mut url = request.url.clone();
+        if request.insecure_requests_policy == InsecureRequestsPolicy::Upgrade {
+            upgrade_to_potentially_trustworthy_url(&mut url);
+        }
+        if !is_mixed_content(&request.origin, &url) {
+            return;
+        }
+
+        let security_state = match (self.security_state.get(), request.destination.is_optionally_blockable()) {
+            (_, false) => SecurityState::MixedContentBlocked,
+            (SecurityState::MixedContentBlocked, true) => SecurityState::MixedContentBlocked,
+            (_, true) => SecurityState::MixedContentDisplayed,
+        };
+        if security_state != self.security_state.get() {
+            self.security_state.set(security_state);
+            if self.window.is_top_level() {
+                self.send_to_embedder(EmbedderMsg::SecurityStateChanged(security_state));
+            }
+        }
     }
 
     pub fn is_fully_active(&self) -> bool {
@@ -496,6 +700,7 @@ impl Document {
         if activity != self.activity.get() {
             self.activity.set(activity);
             if activity == DocumentActivity::FullyActive {
+                self.frozen.set(false);
                 self.title_changed();
                 self.dirty_all_nodes();
                 self.window().reflow(ReflowGoal::Full, ReflowReason::CachedPageNeededReflow);
@@ -921,7 +1126,7 @@ impl Document {
     pub fn handle_mouse_event(
         &self,
         js_runtime: *mut JSRuntime,
-        _button: MouseButton,
+        button: MouseButton,
         client_point: Point2D<f32>,
         mouse_event_type: MouseEventType,
         node_address: Option<UntrustedNodeAddress>,
@@ -934,6 +1139,18 @@ impl Document {
         };
         debug!("{}: at {:?}", mouse_event_type_string, client_point);
 
+        // https://html.spec.whatwg.org/multipage/#drag-and-drop-processing-model
+        match mouse_event_type {
+            MouseEventType::MouseUp if self.end_drag_operation(client_point, true) => {
+                self.drag_end_point.set(Some(client_point));
+                self.window.reflow(ReflowGoal::Full, ReflowReason::MouseEvent);
+                return;
+            },
+            MouseEventType::Click if self.drag_end_point.take() == Some(client_point) => return,
+            MouseEventType::MouseDown => self.drag_end_point.set(None),
+            _ => {},
+        }
+
         let el = node_address.and_then(|address| {
             let node = unsafe { node::from_untrusted_node_address(js_runtime, address) };
             node.inclusive_ancestors()
@@ -947,8 +1164,19 @@ impl Document {
 
         let node = el.upcast::<Node>();
         debug!("{} on {:?}", mouse_event_type_string, node.debug_str());
+
+        // https://html.spec.whatwg.org/multipage/#activation-triggering-input-event
+        if let MouseEventType::MouseDown = mouse_event_type {
+            self.window.notify_activation();
+        }
+
+        // The compositor simulates mouse events for a tap once the touch point is lifted. They
+        // follow the pointer events of the touch point, so they fire none of their own.
+        let tap = self.touch_tap.get().filter(|&(tap_point, _)| tap_point == client_point);
+
         // Prevent click event if form control element is disabled.
         if let MouseEventType::Click = mouse_event_type {
+            self.touch_tap.set(None);
             if el.click_event_filter_by_disabled_state() {
                 return;
             }
@@ -956,6 +1184,18 @@ impl Document {
             self.begin_focus_transaction();
         }
 
+        // https://w3c.github.io/pointerevents/#compatibility-mapping-with-mouse-events
+        let mouse_events_prevented = match (tap, &mouse_event_type) {
+            (Some((_, prevented)), _) => prevented,
+            (None, &MouseEventType::MouseDown) => {
+                self.dispatch_mouse_button_pointer_event(&el, button, true, client_point)
+            },
+            (None, &MouseEventType::MouseUp) => {
+                self.dispatch_mouse_button_pointer_event(&el, button, false, client_point)
+            },
+            (None, &MouseEventType::Click) => false,
+        };
+
         // https://w3c.github.io/uievents/#event-type-click
         let client_x = client_point.x as i32;
         let client_y = client_point.y as i32;
@@ -992,16 +1232,26 @@ impl Document {
                     a.enter_formal_activation_state();
                 }
 
-                let target = node.upcast();
-                event.fire(target);
+                if !mouse_events_prevented {
+                    let target = node.upcast();
+                    event.fire(target);
+                }
+
+                if let MouseButton::Left = button {
+                    if !event.DefaultPrevented() {
+                        self.begin_drag_operation(&el, client_point);
+                    }
+                }
             },
             MouseEventType::MouseUp => {
                 if let Some(a) = activatable {
                     a.exit_formal_activation_state();
                 }
 
-                let target = node.upcast();
-                event.fire(target);
+                if !mouse_events_prevented {
+                    let target = node.upcast();
+                    event.fire(target);
+                }
             },
         }
 
@@ -1069,12 +1319,18 @@ impl Document {
     pub fn fire_mouse_event(&self, client_point: Point2D<f32>, target: &EventTarget, event_name: FireMouseEventType) {
         let client_x = client_point.x.to_i32().unwrap_or(0);
         let client_y = client_point.y.to_i32().unwrap_or(0);
+        let (can_bubble, cancelable) = match event_name {
+            FireMouseEventType::Enter | FireMouseEventType::Leave => {
+                (EventBubbles::DoesNotBubble, EventCancelable::NotCancelable)
+            },
+            _ => (EventBubbles::Bubbles, EventCancelable::Cancelable),
+        };
 
         let mouse_event = MouseEvent::new(
             &self.window,
             DOMString::from(event_name.as_str()),
-            EventBubbles::Bubbles,
-            EventCancelable::Cancelable,
+            can_bubble,
+            cancelable,
             Some(&self.window),
             0i32,
             client_x,
@@ -1125,7 +1381,24 @@ impl Document {
             None => return,
         };
 
-        self.fire_mouse_event(client_point, new_target.upcast(), FireMouseEventType::Move);
+        // The mouse fires drag-and-drop events instead of mouse events while it is dragging.
+        if self.update_drag_operation(client_point, new_target) {
+            self.window.reflow(ReflowGoal::Full, ReflowReason::MouseEvent);
+            return;
+        }
+
+        // https://w3c.github.io/pointerevents/#compatibility-mapping-with-mouse-events
+        let tap = self.touch_tap.get().filter(|&(tap_point, _)| tap_point == client_point);
+        let mouse_events_prevented = match tap {
+            Some((_, prevented)) => prevented,
+            None => {
+                self.dispatch_pointer_event(PointerEventType::Move, MOUSE_POINTER_ID, new_target, client_point, -1);
+                self.active_pointers.borrow()[&MOUSE_POINTER_ID].mouse_events_prevented
+            },
+        };
+        if !mouse_events_prevented {
+            self.fire_mouse_event(client_point, new_target.upcast(), FireMouseEventType::Move);
+        }
 
         // Nothing more to do here, mousemove is sent,
         // and the element under the mouse hasn't changed.
@@ -1156,8 +1429,10 @@ impl Document {
             // Remove hover state to old target and its parents
             self.fire_mouse_event(client_point, old_target.upcast(), FireMouseEventType::Out);
 
-            // TODO: Fire mouseleave here only if the old target is
-            // not an ancestor of the new target.
+            // https://w3c.github.io/uievents/#event-type-mouseleave
+            for element in boundary_elements(&old_target, maybe_new_target.r()) {
+                self.fire_mouse_event(client_point, element.upcast(), FireMouseEventType::Leave);
+            }
         }
 
         if let Some(ref new_target) = maybe_new_target {
@@ -1173,7 +1448,11 @@ impl Document {
 
             self.fire_mouse_event(client_point, &new_target.upcast(), FireMouseEventType::Over);
 
-            // TODO: Fire mouseenter here.
+            // https://w3c.github.io/uievents/#event-type-mouseenter
+            let old_target = prev_mouse_over_target.get();
+            for element in boundary_elements(new_target, old_target.r()).iter().rev() {
+                self.fire_mouse_event(client_point, element.upcast(), FireMouseEventType::Enter);
+            }
         }
 
         // Store the current mouse over target for next frame.
@@ -1211,7 +1490,14 @@ impl Document {
             None => return TouchEventResult::Forwarded,
         };
 
-        let target = DomRoot::upcast::<EventTarget>(el);
+        // https://html.spec.whatwg.org/multipage/#activation-triggering-input-event
+        if let TouchEventType::Up = event_type {
+            self.window.notify_activation();
+        }
+
+        self.dispatch_touch_pointer_event(event_type, identifier, &el, point);
+
+        let target = DomRoot::from_ref(el.upcast::<EventTarget>());
         let window = &*self.window;
 
         let client_x = Finite::wrap(point.x as f64);
@@ -1284,8 +1570,671 @@ impl Document {
         window.reflow(ReflowGoal::Full, ReflowReason::MouseEvent);
 
         match result {
-            EventStatus::Canceled => TouchEventResult::Processed(false),
-            EventStatus::NotCanceled => TouchEventResult::Processed(true),
+            EventStatus::Canceled => TouchEventResult::Processed(EventResult::DefaultPrevented),
+            EventStatus::NotCanceled => {
+                // The `touch-action` of the target of touchstart decides on the default actions
+                // of the whole touch sequence.
+                let allowed_actions = match event_type {
+                    TouchEventType::Down => allowed_touch_actions(&el),
+                    _ => AllowedTouchActions::all(),
+                };
+                TouchEventResult::Processed(EventResult::DefaultAllowed(allowed_actions))
+            },
+        }
+    }
+
+    /// <https://w3c.github.io/pointerevents/#dom-element-setpointercapture>
+    pub fn set_pointer_capture(&self, element: &Element, pointer_id: i32) -> ErrorResult {
+        let mut active_pointers = self.active_pointers.borrow_mut();
+
+        // Step 1.
+        let pointer = match active_pointers.get_mut(&pointer_id) {
+            Some(pointer) => pointer,
+            None => return Err(Error::NotFound),
+        };
+
+        // Step 2.
+        if !element.upcast::<Node>().is_in_doc() {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 3 is about pointer lock, which isn't supported.
+
+        // Step 4.
+        if pointer.buttons != 0 {
+            pointer.pending_capture_target = Some(Dom::from_ref(element));
+        }
+        Ok(())
+    }
+
+    /// <https://w3c.github.io/pointerevents/#dom-element-releasepointercapture>
+    pub fn release_pointer_capture(&self, element: &Element, pointer_id: i32) -> ErrorResult {
+        let mut active_pointers = self.active_pointers.borrow_mut();
+
+        // Step 1.
+        let pointer = match active_pointers.get_mut(&pointer_id) {
+            Some(pointer) => pointer,
+            None => return Err(Error::NotFound),
+        };
+
+        // Steps 2-3.
+        if pointer.pending_capture_target.as_ref().map_or(false, |target| *target == element) {
+            pointer.pending_capture_target = None;
+        }
+        Ok(())
+    }
+
+    /// <https://w3c.github.io/pointerevents/#dom-element-haspointercapture>
+    pub fn has_pointer_capture(&self, element: &Element, pointer_id: i32) -> bool {
+        self.active_pointers
+            .borrow()
+            .get(&pointer_id)
+            .and_then(|pointer| pointer.pending_capture_target.as_ref())
+            .map_or(false, |target| *target == element)
+    }
+
+    /// Fires the pointer event of a mouse button being pressed or released: `pointerdown` for
+    /// the first button pressed, `pointerup` for the last one released, and `pointermove` for
+    /// the other buttons of a chord. Returns whether the compatibility mouse events are
+    /// prevented.
+    /// <https://w3c.github.io/pointerevents/#chorded-button-interactions>
+    fn dispatch_mouse_button_pointer_event(
+        &self,
+        target: &Element,
+        button: MouseButton,
+        pressed: bool,
+        client_point: Point2D<f32>,
+    ) -> bool {
+        let (button_index, button_flag) = match button {
+            MouseButton::Left => (0, 1),
+            MouseButton::Middle => (1, 4),
+            MouseButton::Right => (2, 2),
+        };
+        let (event_type, mouse_events_prevented) = {
+            let mut active_pointers = self.active_pointers.borrow_mut();
+            let mouse = active_pointers.get_mut(&MOUSE_POINTER_ID).unwrap();
+            let was_pressed = mouse.buttons != 0;
+            if pressed {
+                mouse.buttons |= button_flag;
+            } else {
+                mouse.buttons &= !button_flag;
+            }
+            let event_type = match (was_pressed, mouse.buttons != 0) {
+                (false, true) => PointerEventType::Down,
+                (true, true) => PointerEventType::Move,
+                (_, false) => PointerEventType::Up,
+            };
+            (event_type, mouse.mouse_events_prevented)
+        };
+
+        let status = self.dispatch_pointer_event(event_type, MOUSE_POINTER_ID, target, client_point, button_index);
+        match event_type {
+            PointerEventType::Down => {
+                let prevented = status == EventStatus::Canceled;
+                if let Some(mouse) = self.active_pointers.borrow_mut().get_mut(&MOUSE_POINTER_ID) {
+                    mouse.mouse_events_prevented = prevented;
+                }
+                prevented
+            },
+            PointerEventType::Up => {
+                self.release_pointer_capture_implicitly(MOUSE_POINTER_ID, client_point);
+                if let Some(mouse) = self.active_pointers.borrow_mut().get_mut(&MOUSE_POINTER_ID) {
+                    mouse.mouse_events_prevented = false;
+                }
+                mouse_events_prevented
+            },
+            _ => mouse_events_prevented,
+        }
+    }
+
+    /// Fires the pointer events of a touch point, which is the pointer with the id that follows
+    /// the one of the mouse by its identifier.
+    /// <https://w3c.github.io/pointerevents/#mapping-for-devices-that-do-not-support-hover>
+    #[allow(unrooted_must_root)]
+    fn dispatch_touch_pointer_event(
+        &self,
+        event_type: TouchEventType,
+        identifier: i32,
+        target: &Element,
+        client_point: Point2D<f32>,
+    ) {
+        let pointer_id = MOUSE_POINTER_ID + 1 + identifier;
+        match event_type {
+            TouchEventType::Down => {
+                self.touch_tap.set(None);
+                {
+                    let mut active_pointers = self.active_pointers.borrow_mut();
+                    // The first touch point of a multi-touch interaction is the primary pointer.
+                    let is_primary = !active_pointers
+                        .values()
+                        .any(|pointer| pointer.pointer_type == PointerType::Touch);
+                    active_pointers.insert(pointer_id, ActivePointer::new(PointerType::Touch, is_primary, 1));
+                }
+                self.update_pointer_over_target(pointer_id, Some(target), client_point);
+
+                // Direct manipulation pointers are implicitly captured by the target of
+                // `pointerdown`, as if its listeners were preceded by `setPointerCapture()`.
+                // https://w3c.github.io/pointerevents/#implicit-pointer-capture
+                if let Some(pointer) = self.active_pointers.borrow_mut().get_mut(&pointer_id) {
+                    pointer.pending_capture_target = Some(Dom::from_ref(target));
+                }
+                let status = self.fire_pointer_event(
+                    PointerEventType::Down,
+                    pointer_id,
+                    target.upcast(),
+                    client_point,
+                    0,
+                );
+                if let Some(pointer) = self.active_pointers.borrow_mut().get_mut(&pointer_id) {
+                    pointer.mouse_events_prevented = status == EventStatus::Canceled;
+                }
+            },
+            TouchEventType::Move => {
+                self.dispatch_pointer_event(PointerEventType::Move, pointer_id, target, client_point, -1);
+            },
+            TouchEventType::Up | TouchEventType::Cancel => {
+                let (pointer_event_type, button) = match event_type {
+                    TouchEventType::Up => (PointerEventType::Up, 0),
+                    _ => (PointerEventType::Cancel, -1),
+                };
+                if let Some(pointer) = self.active_pointers.borrow_mut().get_mut(&pointer_id) {
+                    pointer.buttons = 0;
+                }
+                self.dispatch_pointer_event(pointer_event_type, pointer_id, target, client_point, button);
+                self.release_pointer_capture_implicitly(pointer_id, client_point);
+                self.update_pointer_over_target(pointer_id, None, client_point);
+
+                let pointer = self.active_pointers.borrow_mut().remove(&pointer_id);
+                if let (TouchEventType::Up, Some(pointer)) = (event_type, pointer) {
+                    if pointer.is_primary {
+                        self.touch_tap.set(Some((client_point, pointer.mouse_events_prevented)));
+                    }
+                }
+            },
+        }
+    }
+
+    /// Fires a pointer event for a pointer over `hit_target`, after processing its pending
+    /// pointer capture. The event is fired at the element that captures the pointer, if any,
+    /// following the boundary events of the pointer moving over that element.
+    fn dispatch_pointer_event(
+        &self,
+        event_type: PointerEventType,
+        pointer_id: i32,
+        hit_target: &Element,
+        client_point: Point2D<f32>,
+        button: i16,
+    ) -> EventStatus {
+        self.process_pending_pointer_capture(pointer_id, client_point);
+        let target = self.active_pointers
+            .borrow()
+            .get(&pointer_id)
+            .and_then(|pointer| root_element(&pointer.capture_target))
+            .unwrap_or_else(|| DomRoot::from_ref(hit_target));
+        self.update_pointer_over_target(pointer_id, Some(&target), client_point);
+        self.fire_pointer_event(event_type, pointer_id, target.upcast(), client_point, button)
+    }
+
+    /// Fires the `pointerout` and `pointerleave` events of a pointer leaving the element it is
+    /// over, and the `pointerover` and `pointerenter` events of it moving over `new_target`.
+    fn update_pointer_over_target(
+        &self,
+        pointer_id: i32,
+        new_target: Option<&Element>,
+        client_point: Point2D<f32>,
+    ) {
+        let old_target = match self.active_pointers.borrow().get(&pointer_id) {
+            Some(pointer) => root_element(&pointer.over_target),
+            None => return,
+        };
+        if old_target == new_target.map(DomRoot::from_ref) {
+            return;
+        }
+
+        if let Some(ref old_target) = old_target {
+            self.fire_pointer_event(PointerEventType::Out, pointer_id, old_target.upcast(), client_point, -1);
+            for element in boundary_elements(old_target, new_target) {
+                self.fire_pointer_event(PointerEventType::Leave, pointer_id, element.upcast(), client_point, -1);
+            }
+        }
+
+        if let Some(pointer) = self.active_pointers.borrow_mut().get_mut(&pointer_id) {
+            pointer.over_target = new_target.map(Dom::from_ref);
+        }
+
+        if let Some(new_target) = new_target {
+            self.fire_pointer_event(PointerEventType::Over, pointer_id, new_target.upcast(), client_point, -1);
+            for element in boundary_elements(new_target, old_target.r()).iter().rev() {
+                self.fire_pointer_event(PointerEventType::Enter, pointer_id, element.upcast(), client_point, -1);
+            }
+        }
+    }
+
+    /// <https://w3c.github.io/pointerevents/#process-pending-pointer-capture>
+    fn process_pending_pointer_capture(&self, pointer_id: i32, client_point: Point2D<f32>) {
+        let (capture_target, pending_capture_target) = match self.active_pointers.borrow_mut().get_mut(&pointer_id) {
+            Some(pointer) => {
+                // Removing the pending capture target from the document releases the capture.
+                // https://w3c.github.io/pointerevents/#implicit-release-after-node-removal
+                let pending_target_removed = pointer.pending_capture_target
+                    .as_ref()
+                    .map_or(false, |target| !target.upcast::<Node>().is_in_doc());
+                if pending_target_removed {
+                    pointer.pending_capture_target = None;
+                }
+                (root_element(&pointer.capture_target), root_element(&pointer.pending_capture_target))
+            },
+            None => return,
+        };
+        if capture_target == pending_capture_target {
+            return;
+        }
+
+        // Step 1.
+        if let Some(capture_target) = capture_target {
+            // The event is fired at the document if the capture target was removed from it.
+            let target = if capture_target.upcast::<Node>().is_in_doc() {
+                DomRoot::upcast::<EventTarget>(capture_target)
+            } else {
+                DomRoot::from_ref(self.upcast::<EventTarget>())
+            };
+            self.fire_pointer_event(PointerEventType::LostCapture, pointer_id, &target, client_point, -1);
+        }
+
+        // Step 2.
+        if let Some(ref pending_capture_target) = pending_capture_target {
+            self.fire_pointer_event(
+                PointerEventType::GotCapture,
+                pointer_id,
+                pending_capture_target.upcast(),
+                client_point,
+                -1,
+            );
+        }
+
+        // Step 3.
+        if let Some(pointer) = self.active_pointers.borrow_mut().get_mut(&pointer_id) {
+            pointer.capture_target = pending_capture_target.r().map(Dom::from_ref);
+        }
+    }
+
+    /// <https://w3c.github.io/pointerevents/#implicit-release-of-pointer-capture>
+    fn release_pointer_capture_implicitly(&self, pointer_id: i32, client_point: Point2D<f32>) {
+        if let Some(pointer) = self.active_pointers.borrow_mut().get_mut(&pointer_id) {
+            pointer.pending_capture_target = None;
+        }
+        self.process_pending_pointer_capture(pointer_id, client_point);
+    }
+
+    fn fire_pointer_event(
+        &self,
+        event_type: PointerEventType,
+        pointer_id: i32,
+        target: &EventTarget,
+        client_point: Point2D<f32>,
+        button: i16,
+    ) -> EventStatus {
+        let (pointer_type, is_primary, buttons) = match self.active_pointers.borrow().get(&pointer_id) {
+            Some(pointer) => (pointer.pointer_type, pointer.is_primary, pointer.buttons),
+            None => return EventStatus::NotCanceled,
+        };
+        let client_x = client_point.x.to_i32().unwrap_or(0);
+        let client_y = client_point.y.to_i32().unwrap_or(0);
+        // Pointers without pressure sensitivity have half the pressure while a button is pressed.
+        let pressure = if buttons != 0 { 0.5 } else { 0.0 };
+
+        let event = PointerEvent::new(
+            &self.window,
+            DOMString::from(event_type.as_str()),
+            event_type.bubbles(),
+            event_type.cancelable(),
+            Some(&self.window),
+            0i32,
+            client_x,
+            client_y,
+            client_x,
+            client_y, // TODO: Get real screen coordinates?
+            false,
+            false,
+            false,
+            false,
+            button,
+            None,
+            pointer_id,
+            Finite::wrap(1.0),
+            Finite::wrap(1.0),
+            Finite::wrap(pressure),
+            Finite::wrap(0.0),
+            0,
+            0,
+            0,
+            DOMString::from(pointer_type.as_str()),
+            is_primary,
+        );
+        let event = event.upcast::<Event>();
+        event.set_trusted(true);
+        event.fire(target)
+    }
+
+    #[allow(unsafe_code)]
+    pub fn handle_wheel_event(
+        &self,
+        js_runtime: *mut JSRuntime,
+        delta: WheelDelta,
+        client_point: Point2D<f32>,
+        node_address: Option<UntrustedNodeAddress>
+    ) -> bool {
+        debug!("wheel: {:?} at {:?}", delta, client_point);
+
+        let el = node_address.and_then(|address| {
+            let node = unsafe { node::from_untrusted_node_address(js_runtime, address) };
+            node.inclusive_ancestors()
+                .filter_map(DomRoot::downcast::<Element>)
+                .next()
+        });
+        let el = match el {
+            Some(el) => el,
+            None => return false,
+        };
+
+        // https://w3c.github.io/uievents/#event-type-wheel
+        let client_x = client_point.x.to_i32().unwrap_or(0);
+        let client_y = client_point.y.to_i32().unwrap_or(0);
+        let delta_mode = match delta.mode {
+            WheelMode::DeltaPixel => WheelEventConstants::DOM_DELTA_PIXEL,
+            WheelMode::DeltaLine => WheelEventConstants::DOM_DELTA_LINE,
+            WheelMode::DeltaPage => WheelEventConstants::DOM_DELTA_PAGE,
+        };
+        let event = WheelEvent::new(
+            &self.window,
+            DOMString::from("wheel"),
+            EventBubbles::Bubbles,
+            EventCancelable::Cancelable,
+            Some(&self.window),
+            0i32,
+            client_x,
+            client_y,
+            client_x,
+            client_y, // TODO: Get real screen coordinates?
+            false,
+            false,
+            false,
+            false,
+            0i16,
+            None,
+            Finite::wrap(delta.x),
+            Finite::wrap(delta.y),
+            Finite::wrap(delta.z),
+            delta_mode,
+        );
+        let status = event.upcast::<Event>().fire(el.upcast());
+
+        self.window.reflow(ReflowGoal::Full, ReflowReason::MouseEvent);
+        status == EventStatus::Canceled
+    }
+
+    /// Drops files from the embedder at the given point, as a drag-and-drop operation whose
+    /// source is outside of the document.
+    #[allow(unsafe_code)]
+    pub fn handle_drop_files_event(
+        &self,
+        js_runtime: *mut JSRuntime,
+        files: Vec<SelectedFile>,
+        client_point: Point2D<f32>,
+        node_address: Option<UntrustedNodeAddress>
+    ) {
+        debug!("drop of {} files at {:?}", files.len(), client_point);
+
+        let el = node_address.and_then(|address| {
+            let node = unsafe { node::from_untrusted_node_address(js_runtime, address) };
+            node.inclusive_ancestors()
+                .filter_map(DomRoot::downcast::<Element>)
+                .next()
+        });
+        let el = match el {
+            Some(el) => el,
+            None => return,
+        };
+
+        // https://html.spec.whatwg.org/multipage/#drag-and-drop-processing-model
+        let files = files.into_iter().map(|file| File::new_from_selected(&self.window, file)).collect();
+        let data_transfer = DataTransfer::new(&self.window, DataTransferMode::Protected, files);
+        let target = el.upcast::<EventTarget>();
+        data_transfer.reset_drop_effect();
+        self.fire_drag_event("dragenter", target, client_point, &data_transfer, EventCancelable::Cancelable);
+        data_transfer.reset_drop_effect();
+        let canceled =
+            self.fire_drag_event("dragover", target, client_point, &data_transfer, EventCancelable::Cancelable);
+        if canceled && &*data_transfer.DropEffect() != "none" {
+            data_transfer.set_mode(DataTransferMode::ReadOnly);
+            self.fire_drag_event("drop", target, client_point, &data_transfer, EventCancelable::Cancelable);
+        } else {
+            data_transfer.set_drop_effect("none");
+            self.fire_drag_event("dragleave", target, client_point, &data_transfer, EventCancelable::NotCancelable);
+        }
+
+        self.window.reflow(ReflowGoal::Full, ReflowReason::MouseEvent);
+    }
+
+    /// Fires a drag-and-drop event, returning whether it was canceled.
+    fn fire_drag_event(
+        &self,
+        event_name: &str,
+        target: &EventTarget,
+        client_point: Point2D<f32>,
+        data_transfer: &DataTransfer,
+        cancelable: EventCancelable,
+    ) -> bool {
+        let client_x = client_point.x.to_i32().unwrap_or(0);
+        let client_y = client_point.y.to_i32().unwrap_or(0);
+        let event = DragEvent::new(
+            &self.window,
+            DOMString::from(event_name),
+            EventBubbles::Bubbles,
+            cancelable,
+            Some(&self.window),
+            0i32,
+            client_x,
+            client_y,
+            client_x,
+            client_y,
+            false,
+            false,
+            false,
+            false,
+            0i16,
+            None,
+            Some(data_transfer),
+        );
+        event.upcast::<Event>().fire(target) == EventStatus::Canceled
+    }
+
+    /// Prepares a drag-and-drop operation from the nearest draggable inclusive ancestor of the
+    /// element that the mouse was pressed on, if any.
+    fn begin_drag_operation(&self, target: &Element, client_point: Point2D<f32>) {
+        let source = target.upcast::<Node>()
+            .inclusive_ancestors()
+            .filter_map(DomRoot::downcast::<HTMLElement>)
+            .find(|element| element.is_draggable());
+        *self.drag_operation.borrow_mut() = match source {
+            Some(source) => Some(DragOperation {
+                source: Dom::from_ref(source.upcast()),
+                start_point: client_point,
+                data_transfer: None,
+                current_target: None,
+                drop_allowed: false,
+            }),
+            None => None,
+        };
+    }
+
+    /// Fires the drag-and-drop events of the mouse moving to the given point and element,
+    /// starting to drag once it has moved far enough. Returns whether the mouse is dragging.
+    fn update_drag_operation(&self, client_point: Point2D<f32>, target: &Element) -> bool {
+        let (source, start_point, data_transfer, current_target) = match *self.drag_operation.borrow() {
+            Some(ref operation) => (
+                DomRoot::from_ref(&*operation.source),
+                operation.start_point,
+                operation.data_transfer.as_ref().map(|data_transfer| DomRoot::from_ref(&**data_transfer)),
+                operation.current_target.as_ref().map(|target| DomRoot::from_ref(&**target)),
+            ),
+            None => return false,
+        };
+
+        let data_transfer = match data_transfer {
+            Some(data_transfer) => data_transfer,
+            None => {
+                let distance = client_point - start_point;
+                if distance.x.abs() < DRAG_THRESHOLD && distance.y.abs() < DRAG_THRESHOLD {
+                    return false;
+                }
+                match self.fire_dragstart(&source, start_point) {
+                    Some(data_transfer) => data_transfer,
+                    None => return false,
+                }
+            },
+        };
+
+        data_transfer.set_mode(DataTransferMode::Protected);
+        if self.fire_drag_event("drag", source.upcast(), client_point, &data_transfer, EventCancelable::Cancelable) {
+            self.end_drag_operation(client_point, false);
+            return true;
+        }
+
+        if current_target != Some(DomRoot::from_ref(target)) {
+            data_transfer.reset_drop_effect();
+            let cancelable = EventCancelable::Cancelable;
+            self.fire_drag_event("dragenter", target.upcast(), client_point, &data_transfer, cancelable);
+            if let Some(ref current_target) = current_target {
+                data_transfer.set_drop_effect("none");
+                self.fire_drag_event(
+                    "dragleave",
+                    current_target.upcast(),
+                    client_point,
+                    &data_transfer,
+                    EventCancelable::NotCancelable,
+                );
+            }
+        }
+
+        data_transfer.reset_drop_effect();
+        let cancelable = EventCancelable::Cancelable;
+        let canceled = self.fire_drag_event("dragover", target.upcast(), client_point, &data_transfer, cancelable);
+        let drop_allowed = canceled && &*data_transfer.DropEffect() != "none";
+        if !canceled {
+            data_transfer.set_drop_effect("none");
+        }
+
+        if let Some(ref mut operation) = *self.drag_operation.borrow_mut() {
+            operation.current_target = Some(Dom::from_ref(target));
+            operation.drop_allowed = drop_allowed;
+        }
+        true
+    }
+
+    /// Fires `dragstart` at the source of the drag-and-drop operation, with the default data of
+    /// links and images. Returns the drag data store, unless the event canceled the operation.
+    fn fire_dragstart(&self, source: &Element, client_point: Point2D<f32>) -> Option<DomRoot<DataTransfer>> {
+        let data_transfer = DataTransfer::new(&self.window, DataTransferMode::ReadWrite, vec![]);
+        let url = if source.is::<HTMLAnchorElement>() {
+            source.get_url_attribute(&local_name!("href"))
+        } else if source.is::<HTMLImageElement>() {
+            source.get_url_attribute(&local_name!("src"))
+        } else {
+            DOMString::new()
+        };
+        if !url.is_empty() {
+            data_transfer.add_data("text/uri-list", url.clone());
+            data_transfer.add_data("text/plain", url);
+        }
+
+        let cancelable = EventCancelable::Cancelable;
+        if self.fire_drag_event("dragstart", source.upcast(), client_point, &data_transfer, cancelable) {
+            *self.drag_operation.borrow_mut() = None;
+            return None;
+        }
+        if let Some(ref mut operation) = *self.drag_operation.borrow_mut() {
+            operation.data_transfer = Some(Dom::from_ref(&*data_transfer));
+        }
+
+        // https://w3c.github.io/pointerevents/#the-pointercancel-event
+        if let Some(mouse) = self.active_pointers.borrow_mut().get_mut(&MOUSE_POINTER_ID) {
+            mouse.buttons = 0;
+            mouse.mouse_events_prevented = false;
+        }
+        self.dispatch_pointer_event(PointerEventType::Cancel, MOUSE_POINTER_ID, source, client_point, -1);
+        self.release_pointer_capture_implicitly(MOUSE_POINTER_ID, client_point);
+        Some(data_transfer)
+    }
+
+    /// Ends the drag-and-drop operation of the mouse, dropping the data at the current target
+    /// element if requested and allowed. Returns whether the mouse was dragging.
+    fn end_drag_operation(&self, client_point: Point2D<f32>, drop: bool) -> bool {
+        let (source, data_transfer, current_target, drop_allowed) = match *self.drag_operation.borrow() {
+            Some(ref operation) => (
+                DomRoot::from_ref(&*operation.source),
+                operation.data_transfer.as_ref().map(|data_transfer| DomRoot::from_ref(&**data_transfer)),
+                operation.current_target.as_ref().map(|target| DomRoot::from_ref(&**target)),
+                operation.drop_allowed,
+            ),
+            None => return false,
+        };
+        *self.drag_operation.borrow_mut() = None;
+
+        let data_transfer = match data_transfer {
+            Some(data_transfer) => data_transfer,
+            None => return false,
+        };
+
+        if let Some(target) = current_target {
+            if drop && drop_allowed {
+                data_transfer.set_mode(DataTransferMode::ReadOnly);
+                let cancelable = EventCancelable::Cancelable;
+                self.fire_drag_event("drop", target.upcast(), client_point, &data_transfer, cancelable);
+            } else {
+                data_transfer.set_drop_effect("none");
+                self.fire_drag_event(
+                    "dragleave",
+                    target.upcast(),
+                    client_point,
+                    &data_transfer,
+                    EventCancelable::NotCancelable,
+                );
+            }
+        }
+
+        data_transfer.set_mode(DataTransferMode::Protected);
+        self.fire_drag_event("dragend", source.upcast(), client_point, &data_transfer, EventCancelable::NotCancelable);
+        true
+    }
+
+    /// Fires a `copy`, `cut` or `paste` event at the focused element, and puts the data that a
+    /// canceled `copy` or `cut` event sets on the clipboard.
+    /// <https://w3c.github.io/clipboard-apis/#fire-a-clipboard-event>
+    fn fire_clipboard_event(&self, event_name: Atom, target: &EventTarget) {
+        let paste = event_name == atom!("paste");
+        let mode = if paste { DataTransferMode::ReadOnly } else { DataTransferMode::ReadWrite };
+        let data_transfer = DataTransfer::new(&self.window, mode, vec![]);
+        if paste {
+            let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+            self.send_to_embedder(EmbedderMsg::GetClipboardContents(sender));
+            if let Ok(text) = receiver.recv() {
+                data_transfer.add_data("text/plain", DOMString::from(text));
+            }
+        }
+
+        let event = ClipboardEvent::new(
+            &self.window,
+            event_name,
+            EventBubbles::Bubbles,
+            EventCancelable::Cancelable,
+            Some(&data_transfer),
+        );
+        let status = event.upcast::<Event>().fire(target);
+        if status == EventStatus::Canceled && !paste {
+            if let Some(text) = data_transfer.data("text/plain") {
+                self.send_to_embedder(EmbedderMsg::SetClipboardContents(String::from(text)));
+            }
         }
     }
 
@@ -1304,6 +2253,11 @@ impl Document {
             (&None, &None) => self.window.upcast(),
         };
 
+        // https://html.spec.whatwg.org/multipage/#activation-triggering-input-event
+        if state == KeyState::Pressed && key != Key::Escape {
+            self.window.notify_activation();
+        }
+
         let ctrl = modifiers.contains(KeyModifiers::CONTROL);
         let alt = modifiers.contains(KeyModifiers::ALT);
         let shift = modifiers.contains(KeyModifiers::SHIFT);
@@ -1373,6 +2327,16 @@ impl Document {
             let msg = EmbedderMsg::KeyEvent(ch, key, state, modifiers);
             self.send_to_embedder(msg);
 
+            // https://w3c.github.io/clipboard-apis/#clipboard-event-copy
+            if state != KeyState::Released && is_control_key(modifiers) {
+                match key {
+                    Key::C => self.fire_clipboard_event(atom!("copy"), target),
+                    Key::X => self.fire_clipboard_event(atom!("cut"), target),
+                    Key::V => self.fire_clipboard_event(atom!("paste"), target),
+                    _ => (),
+                }
+            }
+
             // This behavior is unspecced
             // We are supposed to dispatch synthetic click activation for Space and/or Return,
             // however *when* we do it is up to us.
@@ -1591,12 +2555,22 @@ impl Document {
     }
 
     pub fn fetch_async(&self, load: LoadType,
-                       request: RequestInit,
+                       mut request: RequestInit,
                        fetch_target: IpcSender<FetchResponseMsg>) {
+        self.prepare_subresource_request(&mut request);
         let mut loader = self.loader.borrow_mut();
         loader.fetch_async(load, request, fetch_target);
     }
 
+    /// Initiate a new fetch that does not block the document load event.
+    pub fn fetch_async_background(&self,
+                                  mut request: RequestInit,
+                                  fetch_target: IpcSender<FetchResponseMsg>) {
+        self.prepare_subresource_request(&mut request);
+        let mut loader = self.loader.borrow_mut();
+        loader.fetch_async_background(request, fetch_target);
+    }
+
     // https://html.spec.whatwg.org/multipage/#the-end
     // https://html.spec.whatwg.org/multipage/#delay-the-load-event
     pub fn finish_load(&self, load: LoadType) {
@@ -1710,6 +2684,14 @@ impl Document {
         // Step 2
         self.incr_ignore_opens_during_unload_counter();
         let document = Trusted::new(self);
+        // Documents with unload handlers or open websockets
+        // can't be kept in the back-forward cache.
+        let window_target = self.window.upcast::<EventTarget>();
+        if window_target.has_listeners_for(&atom!("unload")) ||
+            self.window.upcast::<GlobalScope>().has_open_websockets()
+        {
+            self.salvageable.set(false);
+        }
         // Step 3-6
         if self.page_showing.get() {
             self.page_showing.set(false);
@@ -1770,6 +2752,11 @@ impl Document {
             global_scope.close_event_sources();
             let msg = ScriptMsg::DiscardDocument;
             let _ = global_scope.script_to_constellation_chan().send(msg);
+        } else if !recycle {
+            // The document stays in the back-forward cache until it is reactivated.
+            self.frozen.set(true);
+            let msg = ScriptMsg::FrozenDocumentSize(self.dom_tree_size());
+            let _ = self.window.upcast::<GlobalScope>().script_to_constellation_chan().send(msg);
         }
         // Step 15, End
         self.decr_ignore_opens_during_unload_counter();
@@ -2329,6 +3316,49 @@ fn get_registrable_domain_suffix_of_or_is_equal_to(host_suffix_string: &str, ori
 }
 
 /// <https://url.spec.whatwg.org/#network-scheme>
+/// Returns the element that an optional `Dom<Element>` points to.
+fn root_element(element: &Option<Dom<Element>>) -> Option<DomRoot<Element>> {
+    element.as_ref().map(|element| DomRoot::from_ref(&**element))
+}
+
+/// Returns the inclusive ancestors of `element` that aren't inclusive ancestors of `other`,
+/// innermost first. These are the elements that a pointer leaves when it moves from `element`
+/// to `other`, and enters when it moves the other way.
+fn boundary_elements(element: &Element, other: Option<&Element>) -> Vec<DomRoot<Element>> {
+    element.upcast::<Node>()
+        .inclusive_ancestors()
+        .filter_map(DomRoot::downcast::<Element>)
+        .take_while(|ancestor| {
+            other.map_or(true, |other| !ancestor.upcast::<Node>().is_inclusive_ancestor_of(other.upcast()))
+        })
+        .collect()
+}
+
+/// Returns the default touch behaviors that the `touch-action` of an element and its ancestors
+/// allow.
+/// <https://w3c.github.io/pointerevents/#determining-supported-touch-behavior>
+fn allowed_touch_actions(element: &Element) -> AllowedTouchActions {
+    element.upcast::<Node>()
+        .inclusive_ancestors()
+        .filter_map(DomRoot::downcast::<Element>)
+        .filter_map(|element| element.style())
+        .fold(AllowedTouchActions::all(), |allowed_actions, style| {
+            let touch_action = style.get_box().touch_action;
+            let allowed_by_element = if touch_action.intersects(
+                TouchAction::TOUCH_ACTION_AUTO | TouchAction::TOUCH_ACTION_MANIPULATION,
+            ) {
+                AllowedTouchActions::all()
+            } else {
+                AllowedTouchActions {
+                    pan_x: touch_action.contains(TouchAction::TOUCH_ACTION_PAN_X),
+                    pan_y: touch_action.contains(TouchAction::TOUCH_ACTION_PAN_Y),
+                    zoom: false,
+                }
+            };
+            allowed_actions.intersection(&allowed_by_element)
+        })
+}
+
 fn url_has_network_scheme(url: &ServoUrl) -> bool {
     match url.scheme() {
         "ftp" | "http" | "https" => true,
@@ -2367,6 +3397,10 @@ impl Document {
 
         let interactive_time = InteractiveMetrics::new(window.time_profiler_chan().clone(), url.clone());
 
+        // The mouse is always an active pointer.
+        let mut active_pointers = HashMap::new();
+        active_pointers.insert(MOUSE_POINTER_ID, ActivePointer::new(PointerType::Mouse, true, 0));
+
         Document {
             node: Node::new_document_node(),
             window: Dom::from_ref(window),
@@ -2417,6 +3451,7 @@ impl Document {
             },
             stylesheets: DomRefCell::new(DocumentStylesheetSet::new()),
             stylesheet_list: MutNullableDom::new(None),
+            fonts: MutNullableDom::new(None),
             ready_state: Cell::new(ready_state),
             domcontentloaded_dispatched: Cell::new(domcontentloaded_dispatched),
             possibly_focused: Default::default(),
@@ -2439,6 +3474,10 @@ impl Document {
             pending_restyles: DomRefCell::new(HashMap::new()),
             needs_paint: Cell::new(false),
             active_touch_points: DomRefCell::new(Vec::new()),
+            active_pointers: DomRefCell::new(active_pointers),
+            touch_tap: Cell::new(None),
+            drag_operation: DomRefCell::new(None),
+            drag_end_point: Cell::new(None),
             dom_loading: Cell::new(Default::default()),
             dom_interactive: Cell::new(Default::default()),
             dom_content_loaded_event_start: Cell::new(Default::default()),
@@ -2448,6 +3487,8 @@ impl Document {
             load_event_start: Cell::new(Default::default()),
             load_event_end: Cell::new(Default::default()),
             https_state: Cell::new(HttpsState::None),
+            insecure_requests_policy: Cell::new(InsecureRequestsPolicy::DoNotUpgrade),
+            security_state: Cell::new(SecurityState::Insecure),
             origin: origin,
             referrer: referrer,
             referrer_policy: Cell::new(referrer_policy),
@@ -2465,7 +3506,8 @@ impl Document {
             throw_on_dynamic_markup_insertion_counter: Cell::new(0),
             page_showing: Cell::new(false),
             salvageable: Cell::new(true),
-            fired_unload: Cell::new(false)
+            fired_unload: Cell::new(false),
+            frozen: Cell::new(false),
         }
     }
 
@@ -2640,6 +3682,23 @@ impl Document {
         }
     }
 
+    /// Calls `f` with the effective `@font-face` rules of the document stylesheets, in tree
+    /// order.
+    pub fn for_each_font_face_rule<F>(&self, mut f: F)
+        where F: FnMut(&FontFaceRule)
+    {
+        let device = match self.device() {
+            Some(device) => device,
+            None => return,
+        };
+        let guard = self.style_shared_lock.read();
+        for (sheet, _origin) in self.stylesheets.borrow().iter() {
+            if sheet.enabled() && sheet.is_effective_for_device(&device, &guard) {
+                sheet.effective_font_face_rules(&device, &guard, &mut f);
+            }
+        }
+    }
+
     /// Returns the number of document stylesheets.
     pub fn stylesheet_count(&self) -> usize {
         self.stylesheets.borrow().len()
@@ -2649,6 +3708,20 @@ impl Document {
         self.salvageable.get()
     }
 
+    pub fn is_frozen(&self) -> bool {
+        self.frozen.get()
+    }
+
+    /// An estimate of the memory that the nodes of this document use, in bytes, which the
+    /// back-forward cache budgets. Other objects of its script global aren't counted.
+    #[allow(unsafe_code)]
+    fn dom_tree_size(&self) -> usize {
+        self.upcast::<Node>()
+            .traverse_preorder()
+            .map(|node| unsafe { get_dom_object_size(node.reflector().get_jsobject().get()) })
+            .sum()
+    }
+
     pub fn stylesheet_at(&self, index: usize) -> Option<DomRoot<CSSStyleSheet>> {
         let stylesheets = self.stylesheets.borrow();
 
@@ -2944,6 +4017,11 @@ impl DocumentMethods for Document {
         self.stylesheet_list.or_init(|| StyleSheetList::new(&self.window, Dom::from_ref(&self)))
     }
 
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfacesource-fonts
+    fn Fonts(&self) -> DomRoot<FontFaceSet> {
+        self.fonts.or_init(|| FontFaceSet::new(self))
+    }
+
     // https://dom.spec.whatwg.org/#dom-document-implementation
     fn Implementation(&self) -> DomRoot<DOMImplementation> {
         self.implementation.or_init(|| DOMImplementation::new(self))
@@ -3694,13 +4772,15 @@ impl DocumentMethods for Document {
         }
 
         if let Ok(cookie_header) = SetCookie::parse_header(&vec![cookie.to_string().into_bytes()]) {
-            let cookies = cookie_header.0.into_iter().filter_map(|cookie| {
+            let cookies: Vec<_> = cookie_header.0.into_iter().filter_map(|cookie| {
                 cookie_rs::Cookie::parse(cookie).ok().map(Serde)
             }).collect();
-            let _ = self.window
-                    .upcast::<GlobalScope>()
-                    .resource_threads()
-                    .send(SetCookiesForUrl(self.url(), cookies, NonHTTP));
+            let names: Vec<_> = cookies.iter().map(|cookie| cookie.name().to_owned()).collect();
+            let global = self.window.upcast::<GlobalScope>();
+            let _ = global.resource_threads().send(SetCookiesForUrl(self.url(), cookies, NonHTTP));
+            for name in names {
+                global.notify_store_changed(StoreType::Cookies, StoreChange::Changed(name));
+            }
         }
         Ok(())
     }
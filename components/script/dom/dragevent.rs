@@ -0,0 +1,101 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::DragEventBinding;
+use dom::bindings::codegen::Bindings::DragEventBinding::DragEventMethods;
+use dom::bindings::codegen::Bindings::MouseEventBinding::MouseEventMethods;
+use dom::bindings::error::Fallible;
+use dom::bindings::inheritance::Castable;
+use dom::bindings::reflector::reflect_dom_object;
+use dom::bindings::root::{DomRoot, MutNullableDom, RootedReference};
+use dom::bindings::str::DOMString;
+use dom::datatransfer::DataTransfer;
+use dom::event::{EventBubbles, EventCancelable};
+use dom::eventtarget::EventTarget;
+use dom::mouseevent::MouseEvent;
+use dom::window::Window;
+use dom_struct::dom_struct;
+
+#[dom_struct]
+pub struct DragEvent {
+    mouseevent: MouseEvent,
+    data_transfer: MutNullableDom<DataTransfer>,
+}
+
+impl DragEvent {
+    fn new_inherited(data_transfer: Option<&DataTransfer>) -> DragEvent {
+        DragEvent {
+            mouseevent: MouseEvent::new_inherited(),
+            data_transfer: MutNullableDom::new(data_transfer),
+        }
+    }
+
+    pub fn new(
+        window: &Window,
+        type_: DOMString,
+        can_bubble: EventBubbles,
+        cancelable: EventCancelable,
+        view: Option<&Window>,
+        detail: i32,
+        screen_x: i32,
+        screen_y: i32,
+        client_x: i32,
+        client_y: i32,
+        ctrl_key: bool,
+        alt_key: bool,
+        shift_key: bool,
+        meta_key: bool,
+        button: i16,
+        related_target: Option<&EventTarget>,
+        data_transfer: Option<&DataTransfer>,
+    ) -> DomRoot<DragEvent> {
+        let ev = reflect_dom_object(
+            Box::new(DragEvent::new_inherited(data_transfer)),
+            window,
+            DragEventBinding::Wrap,
+        );
+        ev.upcast::<MouseEvent>().InitMouseEvent(
+            type_, bool::from(can_bubble), bool::from(cancelable),
+            view, detail,
+            screen_x, screen_y, client_x, client_y,
+            ctrl_key, alt_key, shift_key, meta_key,
+            button, related_target,
+        );
+        ev
+    }
+
+    pub fn Constructor(window: &Window,
+                       type_: DOMString,
+                       init: &DragEventBinding::DragEventInit) -> Fallible<DomRoot<DragEvent>> {
+        let mouse_init = &init.parent;
+        let bubbles = EventBubbles::from(mouse_init.parent.parent.parent.bubbles);
+        let cancelable = EventCancelable::from(mouse_init.parent.parent.parent.cancelable);
+        let event = DragEvent::new(
+            window,
+            type_,
+            bubbles,
+            cancelable,
+            mouse_init.parent.parent.view.r(),
+            mouse_init.parent.parent.detail,
+            mouse_init.screenX, mouse_init.screenY,
+            mouse_init.clientX, mouse_init.clientY, mouse_init.parent.ctrlKey,
+            mouse_init.parent.altKey, mouse_init.parent.shiftKey, mouse_init.parent.metaKey,
+            mouse_init.button, mouse_init.relatedTarget.r(),
+            init.dataTransfer.r(),
+        );
+        Ok(event)
+    }
+}
+
+impl DragEventMethods for DragEvent {
+    // https://html.spec.whatwg.org/multipage/#dom-dragevent-datatransfer
+    fn GetDataTransfer(&self) -> Option<DomRoot<DataTransfer>> {
+        self.data_transfer.get()
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.mouseevent.IsTrusted()
+    }
+}
@@ -12,12 +12,14 @@ use dom::bindings::codegen::Bindings::AttrBinding::AttrMethods;
 use dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
 use dom::bindings::codegen::Bindings::ElementBinding;
 use dom::bindings::codegen::Bindings::ElementBinding::ElementMethods;
+use dom::bindings::codegen::Bindings::ElementBinding::ScrollLogicalPosition;
 use dom::bindings::codegen::Bindings::EventBinding::EventMethods;
 use dom::bindings::codegen::Bindings::FunctionBinding::Function;
 use dom::bindings::codegen::Bindings::HTMLTemplateElementBinding::HTMLTemplateElementMethods;
 use dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
 use dom::bindings::codegen::Bindings::WindowBinding::{ScrollBehavior, ScrollToOptions};
 use dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use dom::bindings::codegen::UnionTypes::BooleanOrScrollIntoViewOptions;
 use dom::bindings::codegen::UnionTypes::NodeOrString;
 use dom::bindings::conversions::DerivedFrom;
 use dom::bindings::error::{Error, ErrorResult, Fallible};
@@ -31,7 +33,7 @@ use dom::bindings::xmlname::XMLName::InvalidXMLName;
 use dom::characterdata::CharacterData;
 use dom::create::create_element;
 use dom::customelementregistry::{CallbackReaction, CustomElementDefinition, CustomElementReaction};
-use dom::document::{Document, LayoutDocumentHelpers};
+use dom::document::{Document, LayoutDocumentHelpers, determine_policy_for_token};
 use dom::documentfragment::DocumentFragment;
 use dom::domrect::DOMRect;
 use dom::domtokenlist::DOMTokenList;
@@ -84,6 +86,7 @@ use html5ever::serialize::TraversalScope::{ChildrenOnly, IncludeNode};
 use js::jsapi::Heap;
 use js::jsval::JSVal;
 use msg::constellation_msg::InputMethodType;
+use net_traits::ReferrerPolicy;
 use net_traits::request::CorsSettings;
 use ref_filter_map::ref_filter_map;
 use script_layout_interface::message::ReflowGoal;
@@ -94,6 +97,7 @@ use selectors::matching::{ElementSelectorFlags, MatchingContext};
 use selectors::sink::Push;
 use servo_arc::Arc;
 use servo_atoms::Atom;
+use servo_url::ServoUrl;
 use std::borrow::Cow;
 use std::cell::{Cell, Ref};
 use std::default::Default;
@@ -107,9 +111,11 @@ use style::attr::{AttrValue, LengthOrPercentageOrAuto};
 use style::context::QuirksMode;
 use style::dom_apis;
 use style::element_state::ElementState;
+use style::error_reporting::ParseErrorReporter;
 use style::invalidation::element::restyle_hints::RestyleHint;
-use style::properties::{ComputedValues, Importance, PropertyDeclaration};
-use style::properties::{PropertyDeclarationBlock, parse_style_attribute};
+use style::properties::{ComputedValues, Importance, PropertyDeclaration, PropertyId};
+use style::properties::{PropertyDeclarationBlock, SourcePropertyDeclaration};
+use style::properties::{parse_one_declaration_into, parse_style_attribute};
 use style::properties::longhands::{self, background_image, border_spacing, font_family, font_size};
 use style::properties::longhands::{overflow_x, overflow_y};
 use style::rule_tree::CascadeLevel;
@@ -119,6 +125,7 @@ use style::shared_lock::{SharedRwLock, Locked};
 use style::thread_state;
 use style::values::{CSSFloat, Either};
 use style::values::{specified, computed};
+use style_traits::ParsingMode;
 use stylesheet_loader::StylesheetOwner;
 use task::TaskOnce;
 use xml5ever::serialize as xmlSerialize;
@@ -783,6 +790,33 @@ impl LayoutElementHelpers for LayoutDom<Element> {
                 shared_lock,
                 PropertyDeclaration::BorderRightWidth(width_value)));
         }
+
+        // The language of the element is used for hyphenation.
+        let element = &*self.unsafe_get();
+        let lang = element.get_attr_val_for_layout(&ns!(xml), &local_name!("lang"))
+            .or_else(|| element.get_attr_val_for_layout(&ns!(), &local_name!("lang")));
+        if let Some(lang) = lang {
+            hints.push(from_declaration(
+                shared_lock,
+                PropertyDeclaration::XLang(specified::XLang(Atom::from(lang)))));
+        }
+
+        if (*self.unsafe_get()).namespace == ns!(svg) {
+            // Presentation attributes were parsed into declaration blocks when they were set.
+            let attrs = (*self.unsafe_get()).attrs.borrow_for_layout();
+            for attr in attrs.iter() {
+                let attr = attr.to_layout();
+                if attr.local_name_atom_forever() == local_name!("style") {
+                    continue;
+                }
+                if let AttrValue::Declaration(_, ref block) = *attr.value_forever() {
+                    hints.push(ApplicableDeclarationBlock::from_declarations(
+                        block.clone(),
+                        CascadeLevel::PresHints,
+                    ));
+                }
+            }
+        }
     }
 
     #[allow(unsafe_code)]
@@ -1531,6 +1565,88 @@ impl Element {
         win.scroll_node(node, x, y, behavior);
     }
 
+    /// <https://drafts.csswg.org/cssom-view/#scroll-an-element-into-view>
+    fn scroll_into_view(
+        &self,
+        behavior: ScrollBehavior,
+        block: ScrollLogicalPosition,
+        inline: ScrollLogicalPosition,
+    ) {
+        let node = self.upcast::<Node>();
+        let win = match node.owner_doc().GetDefaultView() {
+            None => return,
+            Some(win) => win,
+        };
+
+        // The border box of the element, as laid out before its scrolling boxes are scrolled.
+        // It is moved along with the contents of each scrolling box that is scrolled.
+        let border_box = node.bounding_content_box_or_zero();
+        let (mut left, mut top) = (border_box.origin.x.to_f64_px(), border_box.origin.y.to_f64_px());
+        let width = border_box.size.width.to_f64_px();
+        let height = border_box.size.height.to_f64_px();
+
+        // Step 1
+        let root_element = self.root_element();
+        for ancestor in node.ancestors().filter_map(DomRoot::downcast::<Element>) {
+            // The viewport is the scrolling box of the root element.
+            if *ancestor == *root_element {
+                break;
+            }
+            if !ancestor.has_scrolling_box() {
+                continue;
+            }
+
+            let ancestor_node = ancestor.upcast::<Node>();
+            let ancestor_box = ancestor_node.bounding_content_box_or_zero();
+            let client_rect = ancestor_node.client_rect();
+            let scrollport_left = ancestor_box.origin.x.to_f64_px() + client_rect.origin.x as f64;
+            let scrollport_top = ancestor_box.origin.y.to_f64_px() + client_rect.origin.y as f64;
+            let (scroll_left, scroll_top) = (ancestor.ScrollLeft(), ancestor.ScrollTop());
+
+            // Steps 1.1-1.12
+            let x = aligned_scroll_position(
+                inline,
+                left - scroll_left,
+                width,
+                scrollport_left,
+                client_rect.size.width as f64,
+                scroll_left,
+            );
+            let y = aligned_scroll_position(
+                block,
+                top - scroll_top,
+                height,
+                scrollport_top,
+                client_rect.size.height as f64,
+                scroll_top,
+            );
+
+            // Step 1.13
+            ancestor.scroll(x, y, behavior);
+            left -= x;
+            top -= y;
+        }
+
+        let (scroll_x, scroll_y) = (win.ScrollX() as f64, win.ScrollY() as f64);
+        let x = aligned_scroll_position(
+            inline,
+            left - scroll_x,
+            width,
+            0.0,
+            win.InnerWidth() as f64,
+            scroll_x,
+        );
+        let y = aligned_scroll_position(
+            block,
+            top - scroll_y,
+            height,
+            0.0,
+            win.InnerHeight() as f64,
+            scroll_y,
+        );
+        win.scroll(x, y, behavior);
+    }
+
     // https://w3c.github.io/DOM-Parsing/#parsing
     pub fn parse_fragment(&self, markup: DOMString) -> Fallible<DomRoot<DocumentFragment>> {
         // Steps 1-2.
@@ -1891,6 +2007,30 @@ impl ElementMethods for Element {
                      rect.size.height.to_f64_px())
     }
 
+    // https://drafts.csswg.org/cssom-view/#dom-element-scrollintoview
+    fn ScrollIntoView(&self, arg: BooleanOrScrollIntoViewOptions) {
+        // Steps 1-3
+        let (behavior, block, inline) = match arg {
+            BooleanOrScrollIntoViewOptions::Boolean(true) => {
+                (ScrollBehavior::Auto, ScrollLogicalPosition::Start, ScrollLogicalPosition::Nearest)
+            },
+            BooleanOrScrollIntoViewOptions::Boolean(false) => {
+                (ScrollBehavior::Auto, ScrollLogicalPosition::End, ScrollLogicalPosition::Nearest)
+            },
+            BooleanOrScrollIntoViewOptions::ScrollIntoViewOptions(options) => {
+                (options.parent.behavior, options.block, options.inline)
+            },
+        };
+
+        // Step 4
+        if !self.has_css_layout_box() {
+            return;
+        }
+
+        // Step 5
+        self.scroll_into_view(behavior, block, inline);
+    }
+
     // https://drafts.csswg.org/cssom-view/#dom-element-scroll
     fn Scroll(&self, options: &ScrollToOptions) {
         // Step 1
@@ -2417,6 +2557,21 @@ impl ElementMethods for Element {
         let doc = document_from_node(self);
         doc.enter_fullscreen(self)
     }
+
+    // https://w3c.github.io/pointerevents/#dom-element-setpointercapture
+    fn SetPointerCapture(&self, pointer_id: i32) -> ErrorResult {
+        document_from_node(self).set_pointer_capture(self, pointer_id)
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-element-releasepointercapture
+    fn ReleasePointerCapture(&self, pointer_id: i32) -> ErrorResult {
+        document_from_node(self).release_pointer_capture(self, pointer_id)
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-element-haspointercapture
+    fn HasPointerCapture(&self, pointer_id: i32) -> bool {
+        document_from_node(self).has_pointer_capture(self, pointer_id)
+    }
 }
 
 impl VirtualMethods for Element {
@@ -2431,6 +2586,16 @@ impl VirtualMethods for Element {
             return true;
         }
 
+        if attr.local_name() == &local_name!("lang") {
+            return true;
+        }
+
+        // SVG content is read from its attributes when the enclosing `svg` element is laid
+        // out, so any change needs to reach layout.
+        if self.namespace == ns!(svg) {
+            return true;
+        }
+
         self.super_type().unwrap().attribute_affects_presentational_hints(attr)
     }
 
@@ -2529,6 +2694,18 @@ impl VirtualMethods for Element {
         match name {
             &local_name!("id") => AttrValue::from_atomic(value.into()),
             &local_name!("class") => AttrValue::from_serialized_tokenlist(value.into()),
+            _ if self.namespace == ns!(svg) && is_svg_presentation_attribute(name) => {
+                let doc = document_from_node(self);
+                let win = window_from_node(self);
+                let block = parse_svg_presentation_attribute(
+                    name,
+                    &value,
+                    &doc.base_url(),
+                    win.css_error_reporter(),
+                    doc.quirks_mode(),
+                );
+                AttrValue::Declaration(value.into(), Arc::new(doc.style_shared_lock().wrap(block)))
+            },
             _ => self.super_type().unwrap().parse_plain_attribute(name, value),
         }
     }
@@ -3244,6 +3421,42 @@ impl TaskOnce for ElementPerformFullscreenExit {
     }
 }
 
+/// Returns the scroll position along an axis that aligns an element with a scrolling box, as
+/// `alignment` specifies. The element and the scrollport are given by their start edge and size
+/// while the box is scrolled to `position`.
+///
+/// <https://drafts.csswg.org/cssom-view/#scroll-an-element-into-view>
+fn aligned_scroll_position(
+    alignment: ScrollLogicalPosition,
+    element_start: f64,
+    element_size: f64,
+    scrollport_start: f64,
+    scrollport_size: f64,
+    position: f64,
+) -> f64 {
+    let element_end = element_start + element_size;
+    let scrollport_end = scrollport_start + scrollport_size;
+    let align_start = position + element_start - scrollport_start;
+    let align_end = position + element_end - scrollport_end;
+    match alignment {
+        ScrollLogicalPosition::Start => align_start,
+        ScrollLogicalPosition::End => align_end,
+        ScrollLogicalPosition::Center => (align_start + align_end) / 2.0,
+        ScrollLogicalPosition::Nearest => {
+            let start_outside = element_start < scrollport_start;
+            let end_outside = element_end > scrollport_end;
+            if start_outside == end_outside {
+                // The element either covers the scrollport or is already inside it.
+                position
+            } else if start_outside == (element_size < scrollport_size) {
+                align_start
+            } else {
+                align_end
+            }
+        },
+    }
+}
+
 pub fn reflect_cross_origin_attribute(element: &Element) -> Option<DOMString> {
     let attr = element.get_attribute(&ns!(), &local_name!("crossorigin"));
 
@@ -3266,6 +3479,32 @@ pub fn set_cross_origin_attribute(element: &Element, value: Option<DOMString>) {
     }
 }
 
+/// <https://html.spec.whatwg.org/multipage/#referrer-policy-attribute>
+fn referrer_policy_for_keyword(keyword: &str) -> Option<ReferrerPolicy> {
+    // The legacy keywords of the referrer meta element are no referrer policy keywords.
+    match_ignore_ascii_case! { keyword,
+        "never" | "default" | "always" | "" => None,
+        _ => determine_policy_for_token(keyword),
+    }
+}
+
+pub fn reflect_referrer_policy_attribute(element: &Element) -> DOMString {
+    let attr = element.get_attribute(&ns!(), &local_name!("referrerpolicy"));
+
+    if let Some(mut val) = attr.map(|v| v.Value()) {
+        val.make_ascii_lowercase();
+        if referrer_policy_for_keyword(&val).is_some() {
+            return val;
+        }
+    }
+    DOMString::new()
+}
+
+pub fn referrer_policy_for_element(element: &Element) -> Option<ReferrerPolicy> {
+    element.get_attribute(&ns!(), &local_name!("referrerpolicy"))
+        .and_then(|attr| referrer_policy_for_keyword(&attr.Value()))
+}
+
 pub fn cors_setting_for_element(element: &Element) -> Option<CorsSettings> {
     reflect_cross_origin_attribute(element).map_or(None, |attr| {
         match &*attr {
@@ -3275,3 +3514,43 @@ pub fn cors_setting_for_element(element: &Element) -> Option<CorsSettings> {
         }
     })
 }
+
+/// <https://svgwg.org/svg2-draft/styling.html#PresentationAttributes>
+fn is_svg_presentation_attribute(name: &LocalName) -> bool {
+    match &**name {
+        "color" | "display" | "fill" | "fill-opacity" | "font-family" | "font-size" |
+        "font-style" | "font-weight" | "opacity" | "stop-color" | "stop-opacity" | "stroke" |
+        "stroke-dasharray" | "stroke-linecap" | "stroke-linejoin" | "stroke-miterlimit" |
+        "stroke-opacity" | "stroke-width" | "text-anchor" | "visibility" => true,
+        _ => false,
+    }
+}
+
+/// Parses a presentation attribute as a declaration of the property of the same name.
+/// Invalid values result in an empty block.
+fn parse_svg_presentation_attribute(name: &LocalName,
+                                    value: &str,
+                                    base_url: &ServoUrl,
+                                    error_reporter: Option<&ParseErrorReporter>,
+                                    quirks_mode: QuirksMode)
+                                    -> PropertyDeclarationBlock {
+    let mut block = PropertyDeclarationBlock::new();
+    let id = match PropertyId::parse_enabled_for_all_content(name) {
+        Ok(id) => id,
+        Err(..) => return block,
+    };
+    let mut declarations = SourcePropertyDeclaration::new();
+    let result = parse_one_declaration_into(
+        &mut declarations,
+        id,
+        value,
+        base_url,
+        error_reporter,
+        ParsingMode::ALLOW_UNITLESS_LENGTH,
+        quirks_mode,
+    );
+    if result.is_ok() {
+        block.extend(declarations.drain(), Importance::Normal);
+    }
+    block
+}
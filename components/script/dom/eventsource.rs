@@ -459,7 +459,6 @@ impl EventSource {
             CorsSettings::Anonymous
         };
         // Step 8
-        // TODO: Step 9 set request's client settings
         let mut request = RequestInit {
             url: url_record,
             origin: global.origin().immutable().clone(),
@@ -474,6 +473,8 @@ impl EventSource {
             },
             ..RequestInit::default()
         };
+        // Step 9
+        global.prepare_request(&mut request);
         // Step 10
         request.headers.set(Accept(vec![qitem(mime!(Text / EventStream))]));
         // Step 11
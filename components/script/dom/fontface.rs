@@ -0,0 +1,451 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use cssparser::{Parser, ParserInput, UnicodeRange};
+use dom::bindings::cell::DomRefCell;
+use dom::bindings::codegen::Bindings::FontFaceBinding;
+use dom::bindings::codegen::Bindings::FontFaceBinding::{FontFaceLoadStatus, FontFaceMethods};
+use dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use dom::bindings::codegen::UnionTypes::StringOrArrayBufferOrArrayBufferView;
+use dom::bindings::error::{Error, ErrorResult, Fallible};
+use dom::bindings::inheritance::Castable;
+use dom::bindings::refcounted::Trusted;
+use dom::bindings::reflector::{DomObject, Reflector, reflect_dom_object};
+use dom::bindings::root::{Dom, DomRoot};
+use dom::bindings::str::DOMString;
+use dom::fontfaceset::FontFaceSet;
+use dom::promise::Promise;
+use dom::window::Window;
+use dom_struct::dom_struct;
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use script_layout_interface::message::Msg;
+use script_traits::WebFontLoadEvent;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::sync::atomic::{ATOMIC_USIZE_INIT, AtomicUsize, Ordering};
+use std::sync::mpsc::Sender;
+use style::context::QuirksMode;
+use style::font_face::{EffectiveSources, FontDisplay, FontFaceDescriptors, FontStretch, FontStyle};
+use style::font_face::{FontWeight, ScriptFontFaceSource, Source};
+use style::parser::{Parse, ParserContext};
+use style::properties::shorthands::font_variant;
+use style::stylesheets::{CssRuleType, FontFaceRule};
+use style::values::computed::font::FamilyName;
+use style::values::specified::font::{SpecifiedFontFeatureSettings, SpecifiedFontVariationSettings};
+use style_traits::{ParsingMode, ToCss};
+use task_source::{TaskSource, TaskSourceName};
+
+/// The identifier of the next font face, which tells layout which face a message is about.
+static NEXT_FONT_FACE_ID: AtomicUsize = ATOMIC_USIZE_INIT;
+
+/// <https://drafts.csswg.org/css-font-loading/#fontface-interface>
+#[dom_struct]
+pub struct FontFace {
+    reflector_: Reflector,
+    id: usize,
+    family: DomRefCell<DOMString>,
+    style: DomRefCell<DOMString>,
+    weight: DomRefCell<DOMString>,
+    stretch: DomRefCell<DOMString>,
+    unicode_range: DomRefCell<DOMString>,
+    variant: DomRefCell<DOMString>,
+    feature_settings: DomRefCell<DOMString>,
+    variation_settings: DomRefCell<DOMString>,
+    display: DomRefCell<DOMString>,
+    /// Where the data of the face comes from, `None` if the source given to the constructor
+    /// could not be parsed, or if the face is CSS-connected.
+    #[ignore_malloc_size_of = "Defined in style"]
+    source: Option<ScriptFontFaceSource>,
+    /// The identifier of the `@font-face` rule of a CSS-connected face.
+    ///
+    /// <https://drafts.csswg.org/css-font-loading/#css-connected>
+    rule_id: Option<usize>,
+    /// The channel to layout, which is told when the face is collected.
+    #[ignore_malloc_size_of = "Channels are hard"]
+    layout_chan: Sender<Msg>,
+    status: Cell<FontFaceLoadStatus>,
+    #[ignore_malloc_size_of = "Rc"]
+    loaded: Rc<Promise>,
+    /// The font face sets containing this face.
+    sets: DomRefCell<Vec<Dom<FontFaceSet>>>,
+}
+
+/// Parses `value` as the value of a descriptor of type `T`, as if it was in an `@font-face`
+/// rule of the document of `window`.
+fn parse_descriptor<T: Parse>(window: &Window, value: &str) -> Fallible<T> {
+    let url = window.Document().base_url();
+    let context = ParserContext::new_for_cssom(
+        &url,
+        Some(CssRuleType::FontFace),
+        ParsingMode::DEFAULT,
+        QuirksMode::NoQuirks,
+        None,
+    );
+    let mut input = ParserInput::new(value);
+    let mut parser = Parser::new(&mut input);
+    parser.parse_entirely(|input| T::parse(&context, input)).map_err(|_| Error::Syntax)
+}
+
+/// Parses and serializes `value` as the value of a descriptor of type `T`.
+fn normalize_descriptor<T: Parse + ToCss>(window: &Window, value: &str) -> Fallible<DOMString> {
+    parse_descriptor::<T>(window, value).map(|value| DOMString::from(value.to_css_string()))
+}
+
+impl FontFace {
+    fn new_inherited(source: Option<ScriptFontFaceSource>,
+                     rule_id: Option<usize>,
+                     layout_chan: Sender<Msg>,
+                     loaded: Rc<Promise>)
+                     -> FontFace {
+        FontFace {
+            reflector_: Reflector::new(),
+            id: NEXT_FONT_FACE_ID.fetch_add(1, Ordering::SeqCst),
+            family: DomRefCell::new(DOMString::new()),
+            style: DomRefCell::new(DOMString::new()),
+            weight: DomRefCell::new(DOMString::new()),
+            stretch: DomRefCell::new(DOMString::new()),
+            unicode_range: DomRefCell::new(DOMString::new()),
+            variant: DomRefCell::new(DOMString::new()),
+            feature_settings: DomRefCell::new(DOMString::new()),
+            variation_settings: DomRefCell::new(DOMString::new()),
+            display: DomRefCell::new(DOMString::new()),
+            source: source,
+            rule_id: rule_id,
+            layout_chan: layout_chan,
+            status: Cell::new(FontFaceLoadStatus::Unloaded),
+            loaded: loaded,
+            sets: DomRefCell::new(vec![]),
+        }
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-fontface
+    #[allow(unsafe_code)]
+    pub fn Constructor(window: &Window,
+                       family: DOMString,
+                       mut source: StringOrArrayBufferOrArrayBufferView,
+                       descriptors: &FontFaceBinding::FontFaceDescriptors)
+                       -> Fallible<DomRoot<FontFace>> {
+        let source = match source {
+            StringOrArrayBufferOrArrayBufferView::String(ref source) => {
+                parse_descriptor::<Vec<Source>>(window, source).ok().map(|sources| {
+                    ScriptFontFaceSource::Sources(EffectiveSources::new(&sources))
+                })
+            },
+            StringOrArrayBufferOrArrayBufferView::ArrayBuffer(ref mut buffer) => unsafe {
+                Some(ScriptFontFaceSource::Data(buffer.as_slice().to_vec()))
+            },
+            StringOrArrayBufferOrArrayBufferView::ArrayBufferView(ref mut view) => unsafe {
+                Some(ScriptFontFaceSource::Data(view.as_slice().to_vec()))
+            },
+        };
+        let is_data = match source {
+            Some(ScriptFontFaceSource::Data(_)) => true,
+            _ => false,
+        };
+
+        let loaded = Promise::new(window.upcast());
+        let layout_chan = window.layout_chan().clone();
+        let face = reflect_dom_object(Box::new(FontFace::new_inherited(source, None, layout_chan, loaded)),
+                                      window,
+                                      FontFaceBinding::Wrap);
+
+        let parsed = face.source.is_some() &&
+            face.SetFamily(family).is_ok() &&
+            face.SetStyle(descriptors.style.clone()).is_ok() &&
+            face.SetWeight(descriptors.weight.clone()).is_ok() &&
+            face.SetStretch(descriptors.stretch.clone()).is_ok() &&
+            face.SetUnicodeRange(descriptors.unicodeRange.clone()).is_ok() &&
+            face.SetVariant(descriptors.variant.clone()).is_ok() &&
+            face.SetFeatureSettings(descriptors.featureSettings.clone()).is_ok() &&
+            face.SetVariationSettings(descriptors.variationSettings.clone()).is_ok() &&
+            face.SetDisplay(descriptors.display.clone()).is_ok();
+        if !parsed {
+            for attribute in &[&face.family, &face.style, &face.weight, &face.stretch,
+                               &face.unicode_range, &face.variant, &face.feature_settings,
+                               &face.variation_settings, &face.display] {
+                *attribute.borrow_mut() = DOMString::new();
+            }
+            face.status.set(FontFaceLoadStatus::Error);
+            face.loaded.reject_error(Error::Syntax);
+        } else if is_data {
+            // Binary data is loaded right away.
+            face.start_loading();
+        }
+        Ok(face)
+    }
+
+    /// Creates the CSS-connected face of an `@font-face` rule which has `font-family` and `src`
+    /// descriptors. The face is unloaded until layout tells it the font cache started loading it.
+    ///
+    /// <https://drafts.csswg.org/css-font-loading/#css-connected>
+    pub fn new_css_connected(window: &Window, rule: &FontFaceRule) -> DomRoot<FontFace> {
+        let loaded = Promise::new(window.upcast());
+        let layout_chan = window.layout_chan().clone();
+        let face = FontFace::new_inherited(None, Some(rule.id()), layout_chan, loaded);
+        let face = reflect_dom_object(Box::new(face), window, FontFaceBinding::Wrap);
+
+        fn serialize<T: ToCss>(value: &Option<T>, initial: &str) -> DOMString {
+            DOMString::from(value.as_ref().map_or(initial.to_owned(), |value| value.to_css_string()))
+        }
+        *face.family.borrow_mut() = serialize(&rule.family, "");
+        *face.unicode_range.borrow_mut() = serialize(&rule.unicode_range, "U+0-10FFFF");
+        *face.feature_settings.borrow_mut() = serialize(&rule.feature_settings, "normal");
+        *face.display.borrow_mut() = serialize(&rule.display, "auto");
+        // Servo doesn't support the other descriptors in `@font-face` rules.
+        for attribute in &[&face.style, &face.weight, &face.stretch, &face.variant, &face.variation_settings] {
+            *attribute.borrow_mut() = DOMString::from("normal");
+        }
+        face
+    }
+
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn rule_id(&self) -> Option<usize> {
+        self.rule_id
+    }
+
+    /// The family name of the face, `None` if it could not be parsed.
+    pub fn family_name(&self) -> Option<FamilyName> {
+        let global = self.global();
+        parse_descriptor(global.as_window(), &self.family.borrow()).ok()
+    }
+
+    /// The descriptors of the face which decide which characters it is used for and how it is
+    /// displayed.
+    pub fn descriptors(&self) -> FontFaceDescriptors {
+        let global = self.global();
+        let window = global.as_window();
+        let unicode_range: Option<Vec<UnicodeRange>> = parse_descriptor(window, &self.unicode_range.borrow()).ok();
+        let display: Option<FontDisplay> = parse_descriptor(window, &self.display.borrow()).ok();
+        let feature_settings: Option<SpecifiedFontFeatureSettings> =
+            parse_descriptor(window, &self.feature_settings.borrow()).ok();
+        FontFaceDescriptors::new(unicode_range.as_ref().map(|ranges| &**ranges),
+                                 display,
+                                 feature_settings.as_ref())
+    }
+
+    pub fn add_to_set(&self, set: &FontFaceSet) {
+        self.sets.borrow_mut().push(Dom::from_ref(set));
+    }
+
+    pub fn remove_from_set(&self, set: &FontFaceSet) {
+        self.sets.borrow_mut().retain(|other| &**other != set);
+    }
+
+    fn set_descriptor<T>(&self, attribute: &DomRefCell<DOMString>, value: DOMString) -> ErrorResult
+        where T: Parse + ToCss
+    {
+        let global = self.global();
+        *attribute.borrow_mut() = normalize_descriptor::<T>(global.as_window(), &value)?;
+        Ok(())
+    }
+
+    /// Updates the status of a CSS-connected face when the font cache starts or finishes loading
+    /// it.
+    pub fn handle_load_event(&self, event: WebFontLoadEvent) {
+        if self.status.get() == FontFaceLoadStatus::Unloaded {
+            self.set_loading();
+        }
+        if let WebFontLoadEvent::Finished(loaded) = event {
+            if self.status.get() == FontFaceLoadStatus::Loading {
+                self.finish_loading(loaded);
+            }
+        }
+    }
+
+    fn set_loading(&self) {
+        self.status.set(FontFaceLoadStatus::Loading);
+        let sets: Vec<_> = self.sets.borrow().iter().map(|set| DomRoot::from_ref(&**set)).collect();
+        for set in sets {
+            set.font_face_started_loading(self);
+        }
+    }
+
+    /// Asks layout to load a face created by script, through the font cache.
+    ///
+    /// <https://drafts.csswg.org/css-font-loading/#font-face-load>
+    fn start_loading(&self) {
+        let (family_name, source) = match (self.family_name(), self.source.clone()) {
+            (Some(family_name), Some(source)) => (family_name, source),
+            _ => return,
+        };
+
+        self.set_loading();
+
+        let global = self.global();
+        let window = global.as_window();
+        let (sender, receiver) = ipc::channel().unwrap();
+        let face = Trusted::new(self);
+        let task_source = window.networking_task_source();
+        let canceller = window.task_canceller(TaskSourceName::Networking);
+        ROUTER.add_route(receiver.to_opaque(), Box::new(move |message| {
+            let face = face.clone();
+            let loaded = message.to().unwrap();
+            let _ = task_source.queue_with_canceller(
+                task!(finish_loading_font_face: move || {
+                    face.root().finish_loading(loaded);
+                }),
+                &canceller,
+            );
+        }));
+
+        let msg = Msg::LoadFontFace(self.id, family_name, source, self.descriptors(), sender);
+        window.layout_chan().send(msg).unwrap();
+    }
+
+    fn finish_loading(&self, loaded: bool) {
+        if loaded {
+            self.status.set(FontFaceLoadStatus::Loaded);
+            self.loaded.resolve_native(&DomRoot::from_ref(self));
+        } else {
+            self.status.set(FontFaceLoadStatus::Error);
+            self.loaded.reject_error(Error::Network);
+        }
+
+        let sets: Vec<_> = self.sets.borrow().iter().map(|set| DomRoot::from_ref(&**set)).collect();
+        for set in sets {
+            set.font_face_finished_loading(self);
+        }
+    }
+}
+
+impl FontFaceMethods for FontFace {
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-family
+    fn Family(&self) -> DOMString {
+        self.family.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-family
+    fn SetFamily(&self, value: DOMString) -> ErrorResult {
+        self.set_descriptor::<FamilyName>(&self.family, value)
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-style
+    fn Style(&self) -> DOMString {
+        self.style.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-style
+    fn SetStyle(&self, value: DOMString) -> ErrorResult {
+        self.set_descriptor::<FontStyle>(&self.style, value)
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-weight
+    fn Weight(&self) -> DOMString {
+        self.weight.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-weight
+    fn SetWeight(&self, value: DOMString) -> ErrorResult {
+        self.set_descriptor::<FontWeight>(&self.weight, value)
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-stretch
+    fn Stretch(&self) -> DOMString {
+        self.stretch.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-stretch
+    fn SetStretch(&self, value: DOMString) -> ErrorResult {
+        self.set_descriptor::<FontStretch>(&self.stretch, value)
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-unicoderange
+    fn UnicodeRange(&self) -> DOMString {
+        self.unicode_range.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-unicoderange
+    fn SetUnicodeRange(&self, value: DOMString) -> ErrorResult {
+        self.set_descriptor::<Vec<UnicodeRange>>(&self.unicode_range, value)
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-variant
+    fn Variant(&self) -> DOMString {
+        self.variant.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-variant
+    fn SetVariant(&self, value: DOMString) -> ErrorResult {
+        let url = self.global().as_window().Document().base_url();
+        let context = ParserContext::new_for_cssom(
+            &url,
+            Some(CssRuleType::FontFace),
+            ParsingMode::DEFAULT,
+            QuirksMode::NoQuirks,
+            None,
+        );
+        let mut input = ParserInput::new(&value);
+        let mut parser = Parser::new(&mut input);
+        // The descriptor takes the values of the `font-variant` property.
+        parser.parse_entirely(|input| font_variant::parse_value(&context, input)).map_err(|_| Error::Syntax)?;
+        *self.variant.borrow_mut() = value;
+        Ok(())
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-featuresettings
+    fn FeatureSettings(&self) -> DOMString {
+        self.feature_settings.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-featuresettings
+    fn SetFeatureSettings(&self, value: DOMString) -> ErrorResult {
+        self.set_descriptor::<SpecifiedFontFeatureSettings>(&self.feature_settings, value)
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-variationsettings
+    fn VariationSettings(&self) -> DOMString {
+        self.variation_settings.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-variationsettings
+    fn SetVariationSettings(&self, value: DOMString) -> ErrorResult {
+        self.set_descriptor::<SpecifiedFontVariationSettings>(&self.variation_settings, value)
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-display
+    fn Display(&self) -> DOMString {
+        self.display.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-display
+    fn SetDisplay(&self, value: DOMString) -> ErrorResult {
+        self.set_descriptor::<FontDisplay>(&self.display, value)
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-status
+    fn Status(&self) -> FontFaceLoadStatus {
+        self.status.get()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-load
+    fn Load(&self) -> Rc<Promise> {
+        if self.status.get() == FontFaceLoadStatus::Unloaded {
+            match self.rule_id {
+                Some(rule_id) => {
+                    self.set_loading();
+                    self.layout_chan.send(Msg::LoadFontFaceRule(rule_id)).unwrap();
+                },
+                None => self.start_loading(),
+            }
+        }
+        self.loaded.clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontface-loaded
+    fn Loaded(&self) -> Rc<Promise> {
+        self.loaded.clone()
+    }
+}
+
+impl Drop for FontFace {
+    fn drop(&mut self) {
+        if self.rule_id.is_none() {
+            let _ = self.layout_chan.send(Msg::RemoveFontFace(self.id));
+        }
+    }
+}
@@ -0,0 +1,359 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use cssparser::{Parser, ParserInput};
+use dom::bindings::cell::DomRefCell;
+use dom::bindings::codegen::Bindings::FontFaceBinding::{FontFaceLoadStatus, FontFaceMethods};
+use dom::bindings::codegen::Bindings::FontFaceSetBinding::{self, FontFaceSetLoadStatus, FontFaceSetMethods};
+use dom::bindings::error::{Error, Fallible};
+use dom::bindings::inheritance::Castable;
+use dom::bindings::reflector::{DomObject, reflect_dom_object};
+use dom::bindings::root::{Dom, DomRoot};
+use dom::bindings::str::DOMString;
+use dom::document::Document;
+use dom::event::Event;
+use dom::eventtarget::EventTarget;
+use dom::fontface::FontFace;
+use dom::fontfacesetloadevent::FontFaceSetLoadEvent;
+use dom::promise::Promise;
+use dom_struct::dom_struct;
+use script_layout_interface::message::Msg;
+use script_traits::WebFontLoadEvent;
+use servo_atoms::Atom;
+use std::mem;
+use std::rc::Rc;
+use style::context::QuirksMode;
+use style::parser::ParserContext;
+use style::properties::shorthands::font;
+use style::stylesheets::CssRuleType;
+use style::values::computed::font::SingleFontFamily;
+use style::values::specified::font::FontFamily;
+use style_traits::ParsingMode;
+
+/// The fonts of a document: the CSS-connected faces of its `@font-face` rules, followed by the
+/// faces added by script.
+///
+/// <https://drafts.csswg.org/css-font-loading/#FontFaceSet-interface>
+#[dom_struct]
+pub struct FontFaceSet {
+    eventtarget: EventTarget,
+    document: Dom<Document>,
+    /// The CSS-connected faces, in tree order.
+    css_faces: DomRefCell<Vec<Dom<FontFace>>>,
+    /// The faces added by script.
+    faces: DomRefCell<Vec<Dom<FontFace>>>,
+    /// The faces of the set which are loading.
+    loading_faces: DomRefCell<Vec<Dom<FontFace>>>,
+    /// The faces which loaded since the set last switched to the loaded state.
+    loaded_faces: DomRefCell<Vec<Dom<FontFace>>>,
+    /// The faces which failed to load since the set last switched to the loaded state.
+    failed_faces: DomRefCell<Vec<Dom<FontFace>>>,
+    #[ignore_malloc_size_of = "Rc"]
+    ready: DomRefCell<Rc<Promise>>,
+    /// The promises returned by `load`, with the faces they wait for.
+    #[ignore_malloc_size_of = "Rc"]
+    pending_loads: DomRefCell<Vec<(Rc<Promise>, Vec<Dom<FontFace>>)>>,
+}
+
+impl FontFaceSet {
+    fn new_inherited(document: &Document, ready: Rc<Promise>) -> FontFaceSet {
+        FontFaceSet {
+            eventtarget: EventTarget::new_inherited(),
+            document: Dom::from_ref(document),
+            css_faces: DomRefCell::new(vec![]),
+            faces: DomRefCell::new(vec![]),
+            loading_faces: DomRefCell::new(vec![]),
+            loaded_faces: DomRefCell::new(vec![]),
+            failed_faces: DomRefCell::new(vec![]),
+            ready: DomRefCell::new(ready),
+            pending_loads: DomRefCell::new(vec![]),
+        }
+    }
+
+    pub fn new(document: &Document) -> DomRoot<FontFaceSet> {
+        let window = document.window();
+        let ready = Promise::new(window.upcast());
+        let set = reflect_dom_object(Box::new(FontFaceSet::new_inherited(document, ready)),
+                                     window,
+                                     FontFaceSetBinding::Wrap);
+        set.ready.borrow().resolve_native(&set);
+        set
+    }
+
+    /// Updates the CSS-connected faces to the `@font-face` rules of the document, keeping the faces
+    /// of the rules which are still there.
+    ///
+    /// <https://drafts.csswg.org/css-font-loading/#document-font-face-set>
+    #[allow(unrooted_must_root)]
+    fn update_css_connected_faces(&self) {
+        let window = self.document.window();
+        let mut old_faces: Vec<_> =
+            self.css_faces.borrow().iter().map(|face| DomRoot::from_ref(&**face)).collect();
+        let mut faces = vec![];
+        let mut new_faces = vec![];
+        self.document.for_each_font_face_rule(|rule| {
+            if rule.font_face().is_none() {
+                return
+            }
+            let old_face = old_faces.iter().position(|face| face.rule_id() == Some(rule.id()));
+            match old_face {
+                Some(index) => faces.push(old_faces.remove(index)),
+                None => {
+                    let face = FontFace::new_css_connected(window, rule);
+                    new_faces.push(face.clone());
+                    faces.push(face);
+                },
+            }
+        });
+
+        *self.css_faces.borrow_mut() = faces.iter().map(|face| Dom::from_ref(&**face)).collect();
+        for face in new_faces {
+            face.add_to_set(self);
+        }
+        for face in old_faces {
+            self.forget_face(&face);
+        }
+    }
+
+    /// Returns the CSS-connected faces followed by the faces added by script.
+    fn all_faces(&self) -> Vec<DomRoot<FontFace>> {
+        self.update_css_connected_faces();
+        let css_faces = self.css_faces.borrow();
+        let faces = self.faces.borrow();
+        let all_faces: Vec<_> = css_faces.iter().chain(faces.iter()).map(|face| DomRoot::from_ref(&**face)).collect();
+        all_faces
+    }
+
+    /// Updates the CSS-connected face of the `@font-face` rule with the given identifier when the
+    /// font cache starts or finishes loading it.
+    pub fn handle_font_face_rule_load(&self, rule_id: usize, event: WebFontLoadEvent) {
+        self.update_css_connected_faces();
+        let face = self.css_faces.borrow().iter()
+            .find(|face| face.rule_id() == Some(rule_id))
+            .map(|face| DomRoot::from_ref(&**face));
+        if let Some(face) = face {
+            face.handle_load_event(event);
+        }
+    }
+
+    /// Removes `font` from the faces the set and its `load` promises wait for.
+    #[allow(unrooted_must_root)]
+    fn forget_face(&self, font: &FontFace) {
+        font.remove_from_set(self);
+        for &mut (_, ref mut faces) in self.pending_loads.borrow_mut().iter_mut() {
+            faces.retain(|face| &**face != font);
+        }
+        self.settle_pending_loads();
+        let was_loading = self.loading_faces.borrow().iter().any(|face| &**face == font);
+        if was_loading {
+            self.loading_faces.borrow_mut().retain(|face| &**face != font);
+            if self.loading_faces.borrow().is_empty() {
+                self.switch_to_loaded();
+            }
+        }
+    }
+
+    /// Switches the set to the loading state if `face` is the first of its faces to load.
+    ///
+    /// <https://drafts.csswg.org/css-font-loading/#fontfaceset-pending-on-the-environment>
+    pub fn font_face_started_loading(&self, face: &FontFace) {
+        if self.loading_faces.borrow().is_empty() {
+            if self.ready.borrow().is_fulfilled() {
+                *self.ready.borrow_mut() = Promise::new(&self.global());
+            }
+            self.upcast::<EventTarget>().fire_event(atom!("loading"));
+        }
+        self.loading_faces.borrow_mut().push(Dom::from_ref(face));
+    }
+
+    /// Records the outcome of the loading of `face`, and switches the set to the loaded state if
+    /// it was the last of its faces to load.
+    pub fn font_face_finished_loading(&self, face: &FontFace) {
+        self.loading_faces.borrow_mut().retain(|other| &**other != face);
+        if face.Status() == FontFaceLoadStatus::Loaded {
+            self.loaded_faces.borrow_mut().push(Dom::from_ref(face));
+        } else {
+            self.failed_faces.borrow_mut().push(Dom::from_ref(face));
+        }
+        self.settle_pending_loads();
+        if self.loading_faces.borrow().is_empty() {
+            self.switch_to_loaded();
+        }
+    }
+
+    /// <https://drafts.csswg.org/css-font-loading/#fontfaceset-switch-the-fontfaceset-to-loaded>
+    #[allow(unrooted_must_root)]
+    fn switch_to_loaded(&self) {
+        let loaded_faces =
+            self.loaded_faces.borrow_mut().drain(..).map(|face| DomRoot::from_ref(&*face)).collect();
+        let failed_faces: Vec<_> =
+            self.failed_faces.borrow_mut().drain(..).map(|face| DomRoot::from_ref(&*face)).collect();
+        self.ready.borrow().resolve_native(&DomRoot::from_ref(self));
+        self.fire_load_event(atom!("loadingdone"), loaded_faces);
+        if !failed_faces.is_empty() {
+            self.fire_load_event(atom!("loadingerror"), failed_faces);
+        }
+    }
+
+    fn fire_load_event(&self, type_: Atom, faces: Vec<DomRoot<FontFace>>) {
+        let event = FontFaceSetLoadEvent::new(self.document.window(), type_, false, false, faces);
+        event.upcast::<Event>().fire(self.upcast());
+    }
+
+    /// Settles the promises returned by `load` whose faces all loaded, or one of them failed to.
+    #[allow(unrooted_must_root)]
+    fn settle_pending_loads(&self) {
+        let pending_loads = mem::replace(&mut *self.pending_loads.borrow_mut(), vec![]);
+        for (promise, faces) in pending_loads {
+            if faces.iter().any(|face| face.Status() == FontFaceLoadStatus::Error) {
+                promise.reject_error(Error::Network);
+            } else if faces.iter().all(|face| face.Status() == FontFaceLoadStatus::Loaded) {
+                let faces: Vec<_> = faces.iter().map(|face| DomRoot::from_ref(&**face)).collect();
+                promise.resolve_native(&faces);
+            } else {
+                self.pending_loads.borrow_mut().push((promise, faces));
+            }
+        }
+    }
+
+    /// Returns the faces of the set which `font` would use to render `text`.
+    ///
+    /// <https://drafts.csswg.org/css-font-loading/#find-the-matching-font-faces>
+    fn matching_font_faces(&self, font: &str, text: &str) -> Fallible<Vec<DomRoot<FontFace>>> {
+        let url = self.document.base_url();
+        let context = ParserContext::new_for_cssom(
+            &url,
+            Some(CssRuleType::Style),
+            ParsingMode::DEFAULT,
+            QuirksMode::NoQuirks,
+            None,
+        );
+        let mut input = ParserInput::new(font);
+        let mut parser = Parser::new(&mut input);
+        let longhands = parser.parse_entirely(|input| font::parse_value(&context, input))
+            .map_err(|_| Error::Syntax)?;
+        let families = match longhands.font_family {
+            FontFamily::Values(ref families) => families,
+            FontFamily::System(_) => return Err(Error::Syntax),
+        };
+
+        let faces = self.all_faces();
+        let mut matching_faces = vec![];
+        for family in families.iter() {
+            let family = match *family {
+                SingleFontFamily::FamilyName(ref family) => family,
+                SingleFontFamily::Generic(_) => continue,
+            };
+            for face in &faces {
+                let same_family = face.family_name().map_or(false, |name| {
+                    name.name.eq_ignore_ascii_case(&*family.name)
+                });
+                if !same_family {
+                    continue
+                }
+                let descriptors = face.descriptors();
+                if text.chars().any(|character| descriptors.covers(character)) {
+                    matching_faces.push(face.clone());
+                }
+            }
+        }
+        Ok(matching_faces)
+    }
+}
+
+impl FontFaceSetMethods for FontFaceSet {
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-size
+    fn Size(&self) -> u32 {
+        self.all_faces().len() as u32
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-has
+    fn Has(&self, font: &FontFace) -> bool {
+        self.all_faces().iter().any(|face| &**face == font)
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-add
+    fn Add(&self, font: &FontFace) -> Fallible<DomRoot<FontFaceSet>> {
+        if font.rule_id().is_some() {
+            return Err(Error::InvalidModification)
+        }
+        if !self.Has(font) {
+            self.faces.borrow_mut().push(Dom::from_ref(font));
+            font.add_to_set(self);
+            self.document.window().layout_chan().send(Msg::SetFontFaceUsed(font.id(), true)).unwrap();
+            if font.Status() == FontFaceLoadStatus::Loading {
+                self.font_face_started_loading(font);
+            }
+        }
+        Ok(DomRoot::from_ref(self))
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-delete
+    fn Delete(&self, font: &FontFace) -> bool {
+        // CSS-connected faces can only be removed by removing their rule.
+        if !self.faces.borrow().iter().any(|face| &**face == font) {
+            return false
+        }
+        self.faces.borrow_mut().retain(|face| &**face != font);
+        self.document.window().layout_chan().send(Msg::SetFontFaceUsed(font.id(), false)).unwrap();
+        self.forget_face(font);
+        true
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-clear
+    fn Clear(&self) {
+        // Only the faces added by script are removed.
+        let faces: Vec<_> = self.faces.borrow().iter().map(|face| DomRoot::from_ref(&**face)).collect();
+        for face in faces {
+            self.Delete(&face);
+        }
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-onloading
+    event_handler!(loading, GetOnloading, SetOnloading);
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-onloadingdone
+    event_handler!(loadingdone, GetOnloadingdone, SetOnloadingdone);
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-onloadingerror
+    event_handler!(loadingerror, GetOnloadingerror, SetOnloadingerror);
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-load
+    #[allow(unrooted_must_root)]
+    fn Load(&self, font: DOMString, text: DOMString) -> Rc<Promise> {
+        let promise = Promise::new(&self.global());
+        match self.matching_font_faces(&font, &text) {
+            Ok(faces) => {
+                for face in &faces {
+                    face.Load();
+                }
+                let faces = faces.iter().map(|face| Dom::from_ref(&**face)).collect();
+                self.pending_loads.borrow_mut().push((promise.clone(), faces));
+                self.settle_pending_loads();
+            },
+            Err(error) => promise.reject_error(error),
+        }
+        promise
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-check
+    fn Check(&self, font: DOMString, text: DOMString) -> Fallible<bool> {
+        let faces = self.matching_font_faces(&font, &text)?;
+        Ok(faces.iter().all(|face| face.Status() == FontFaceLoadStatus::Loaded))
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-ready
+    fn Ready(&self) -> Rc<Promise> {
+        self.ready.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-status
+    fn Status(&self) -> FontFaceSetLoadStatus {
+        if self.loading_faces.borrow().is_empty() {
+            FontFaceSetLoadStatus::Loaded
+        } else {
+            FontFaceSetLoadStatus::Loading
+        }
+    }
+}
@@ -0,0 +1,79 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::EventBinding::EventMethods;
+use dom::bindings::codegen::Bindings::FontFaceSetLoadEventBinding;
+use dom::bindings::codegen::Bindings::FontFaceSetLoadEventBinding::FontFaceSetLoadEventMethods;
+use dom::bindings::error::Fallible;
+use dom::bindings::inheritance::Castable;
+use dom::bindings::reflector::reflect_dom_object;
+use dom::bindings::root::{Dom, DomRoot};
+use dom::bindings::str::DOMString;
+use dom::event::Event;
+use dom::fontface::FontFace;
+use dom::window::Window;
+use dom_struct::dom_struct;
+use js::conversions::ToJSValConvertible;
+use js::jsapi::JSContext;
+use js::jsval::{JSVal, UndefinedValue};
+use servo_atoms::Atom;
+
+// https://drafts.csswg.org/css-font-loading/#fontfacesetloadevent
+#[dom_struct]
+pub struct FontFaceSetLoadEvent {
+    event: Event,
+    fontfaces: Vec<Dom<FontFace>>,
+}
+
+impl FontFaceSetLoadEvent {
+    fn new_inherited(fontfaces: Vec<DomRoot<FontFace>>) -> FontFaceSetLoadEvent {
+        FontFaceSetLoadEvent {
+            event: Event::new_inherited(),
+            fontfaces: fontfaces.iter().map(|face| Dom::from_ref(&**face)).collect(),
+        }
+    }
+
+    pub fn new(window: &Window,
+               type_: Atom,
+               bubbles: bool,
+               cancelable: bool,
+               fontfaces: Vec<DomRoot<FontFace>>)
+               -> DomRoot<FontFaceSetLoadEvent> {
+        let ev = reflect_dom_object(Box::new(FontFaceSetLoadEvent::new_inherited(fontfaces)),
+                                    window,
+                                    FontFaceSetLoadEventBinding::Wrap);
+        {
+            let event = ev.upcast::<Event>();
+            event.init_event(type_, bubbles, cancelable);
+        }
+        ev
+    }
+
+    pub fn Constructor(window: &Window,
+                       type_: DOMString,
+                       init: &FontFaceSetLoadEventBinding::FontFaceSetLoadEventInit)
+                       -> Fallible<DomRoot<FontFaceSetLoadEvent>> {
+        Ok(FontFaceSetLoadEvent::new(window,
+                                     Atom::from(type_),
+                                     init.parent.bubbles,
+                                     init.parent.cancelable,
+                                     init.fontfaces.clone().unwrap_or(vec![])))
+    }
+}
+
+impl FontFaceSetLoadEventMethods for FontFaceSetLoadEvent {
+    #[allow(unsafe_code)]
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfacesetloadevent-fontfaces
+    unsafe fn Fontfaces(&self, cx: *mut JSContext) -> JSVal {
+        let fontfaces: Vec<_> = self.fontfaces.iter().map(|face| DomRoot::from_ref(&**face)).collect();
+        rooted!(in(cx) let mut rval = UndefinedValue());
+        fontfaces.to_jsval(cx, rval.handle_mut());
+        rval.get()
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.event.IsTrusted()
+    }
+}
@@ -2,9 +2,10 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
-use devtools_traits::{ScriptToDevtoolsControlMsg, WorkerId};
+use devtools_traits::{ScriptToDevtoolsControlMsg, StoreChange, StoreType, WorkerId};
 use dom::bindings::cell::DomRefCell;
 use dom::bindings::codegen::Bindings::EventSourceBinding::EventSourceBinding::EventSourceMethods;
+use dom::bindings::codegen::Bindings::WebSocketBinding::{WebSocketConstants, WebSocketMethods};
 use dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use dom::bindings::codegen::Bindings::WorkerGlobalScopeBinding::WorkerGlobalScopeMethods;
 use dom::bindings::conversions::root_from_object;
@@ -22,6 +23,7 @@ use dom::event::{Event, EventBubbles, EventCancelable, EventStatus};
 use dom::eventsource::EventSource;
 use dom::eventtarget::EventTarget;
 use dom::performance::Performance;
+use dom::websocket::WebSocket;
 use dom::window::Window;
 use dom::workerglobalscope::WorkerGlobalScope;
 use dom::workletglobalscope::WorkletGlobalScope;
@@ -41,6 +43,7 @@ use libc;
 use microtask::{Microtask, MicrotaskQueue};
 use msg::constellation_msg::PipelineId;
 use net_traits::{CoreResourceThread, ResourceThreads, IpcSend};
+use net_traits::request::{InsecureRequestsPolicy, RequestInit};
 use profile_traits::{mem, time};
 use script_runtime::{CommonScriptMsg, ScriptChan, ScriptPort};
 use script_thread::{MainThreadScriptChan, ScriptThread};
@@ -137,6 +140,9 @@ pub struct GlobalScope {
 
     /// Vector storing references of all eventsources.
     event_source_tracker: DOMTracker<EventSource>,
+
+    /// Vector storing references of all websockets.
+    websocket_tracker: DOMTracker<WebSocket>,
 }
 
 impl GlobalScope {
@@ -171,6 +177,7 @@ impl GlobalScope {
             microtask_queue,
             list_auto_close_worker: Default::default(),
             event_source_tracker: DOMTracker::new(),
+            websocket_tracker: DOMTracker::new(),
         }
     }
 
@@ -196,6 +203,21 @@ impl GlobalScope {
         canceled_any_fetch
     }
 
+    pub fn track_websocket(&self, websocket: &WebSocket) {
+        self.websocket_tracker.track(websocket);
+    }
+
+    /// Whether any websocket created in this global is not yet closed.
+    pub fn has_open_websockets(&self) -> bool {
+        let mut has_open_websockets = false;
+        self.websocket_tracker.for_each(|websocket: DomRoot<WebSocket>| {
+            if websocket.ReadyState() != WebSocketConstants::CLOSED {
+                has_open_websockets = true;
+            }
+        });
+        has_open_websockets
+    }
+
     /// Returns the global scope of the realm that the given DOM object's reflector
     /// was created in.
     #[allow(unsafe_code)]
@@ -287,6 +309,15 @@ impl GlobalScope {
         self.devtools_chan.as_ref()
     }
 
+    /// Tell the devtools storage inspector that a store of the origin of this global changed.
+    pub fn notify_store_changed(&self, store: StoreType, change: StoreChange) {
+        if let Some(ref chan) = self.devtools_chan {
+            let host = self.get_url().origin().ascii_serialization();
+            let msg = ScriptToDevtoolsControlMsg::StoreChanged(self.pipeline_id(), store, host, change);
+            let _ = chan.send(msg);
+        }
+    }
+
     /// Get a sender to the memory profiler thread.
     pub fn mem_profiler_chan(&self) -> &mem::ProfilerChan {
         &self.mem_profiler_chan
@@ -334,6 +365,30 @@ impl GlobalScope {
         unreachable!();
     }
 
+    /// <https://w3c.github.io/webappsec-upgrade-insecure-requests/#insecure-requests-policy>
+    pub fn insecure_requests_policy(&self) -> InsecureRequestsPolicy {
+        if let Some(window) = self.downcast::<Window>() {
+            return window.Document().insecure_requests_policy();
+        }
+        if let Some(worker) = self.downcast::<WorkerGlobalScope>() {
+            return worker.insecure_requests_policy();
+        }
+        if self.is::<WorkletGlobalScope>() {
+            // TODO: inherit the policy of the document that added the worklet.
+            return InsecureRequestsPolicy::DoNotUpgrade;
+        }
+        unreachable!();
+    }
+
+    /// Applies the fetch settings of this global to a request made on its behalf, so that the
+    /// request is upgraded or blocked as mixed content like the subresources of its document.
+    pub fn prepare_request(&self, request: &mut RequestInit) {
+        if let Some(window) = self.downcast::<Window>() {
+            return window.Document().prepare_subresource_request(request);
+        }
+        request.insecure_requests_policy = self.insecure_requests_policy();
+    }
+
     /// Get the URL for this global scope.
     pub fn get_url(&self) -> ServoUrl {
         if let Some(window) = self.downcast::<Window>() {
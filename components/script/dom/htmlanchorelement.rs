@@ -15,7 +15,7 @@ use dom::bindings::root::{DomRoot, MutNullableDom};
 use dom::bindings::str::{DOMString, USVString};
 use dom::document::Document;
 use dom::domtokenlist::DOMTokenList;
-use dom::element::Element;
+use dom::element::{Element, referrer_policy_for_element, reflect_referrer_policy_attribute};
 use dom::event::Event;
 use dom::eventtarget::EventTarget;
 use dom::htmlelement::HTMLElement;
@@ -124,6 +124,14 @@ impl HTMLAnchorElementMethods for HTMLAnchorElement {
         self.rel_list.or_init(|| DOMTokenList::new(self.upcast(), &local_name!("rel")))
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-a-referrerpolicy
+    fn ReferrerPolicy(&self) -> DOMString {
+        reflect_referrer_policy_attribute(self.upcast::<Element>())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-a-referrerpolicy
+    make_setter!(SetReferrerPolicy, "referrerpolicy");
+
     // https://html.spec.whatwg.org/multipage/#dom-a-coords
     make_getter!(Coords, "coords");
 
@@ -563,7 +571,7 @@ impl Activatable for HTMLAnchorElement {
         // https://w3c.github.io/webappsec-referrer-policy/#referrer-policy-delivery
         let referrer_policy = match self.RelList().Contains("noreferrer".into()) {
             true => Some(ReferrerPolicy::NoReferrer),
-            false => None,
+            false => referrer_policy_for_element(self.upcast::<Element>()),
         };
 
         follow_hyperlink(element, ismap_suffix, referrer_policy);
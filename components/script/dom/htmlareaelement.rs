@@ -11,7 +11,7 @@ use dom::bindings::root::{DomRoot, MutNullableDom};
 use dom::bindings::str::DOMString;
 use dom::document::Document;
 use dom::domtokenlist::DOMTokenList;
-use dom::element::Element;
+use dom::element::{Element, referrer_policy_for_element, reflect_referrer_policy_attribute};
 use dom::event::Event;
 use dom::eventtarget::EventTarget;
 use dom::htmlanchorelement::follow_hyperlink;
@@ -278,6 +278,14 @@ impl HTMLAreaElementMethods for HTMLAreaElement {
             DOMTokenList::new(self.upcast(), &local_name!("rel"))
         })
     }
+
+    // https://html.spec.whatwg.org/multipage/#dom-area-referrerpolicy
+    fn ReferrerPolicy(&self) -> DOMString {
+        reflect_referrer_policy_attribute(self.upcast::<Element>())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-area-referrerpolicy
+    make_setter!(SetReferrerPolicy, "referrerpolicy");
 }
 
 impl Activatable for HTMLAreaElement {
@@ -311,7 +319,7 @@ impl Activatable for HTMLAreaElement {
         // Step 3
         let referrer_policy = match self.RelList().Contains("noreferrer".into()) {
             true => Some(ReferrerPolicy::NoReferrer),
-            false => None,
+            false => referrer_policy_for_element(self.upcast::<Element>()),
         };
         follow_hyperlink(self.upcast::<Element>(), None, referrer_policy);
     }
@@ -141,6 +141,17 @@ impl HTMLElementMethods for HTMLElement {
     // https://html.spec.whatwg.org/multipage/#dom-hidden
     make_bool_setter!(SetHidden, "hidden");
 
+    // https://html.spec.whatwg.org/multipage/#dom-draggable
+    fn Draggable(&self) -> bool {
+        self.is_draggable()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-draggable
+    fn SetDraggable(&self, draggable: bool) {
+        let value = if draggable { "true" } else { "false" };
+        self.upcast::<Element>().set_string_attribute(&local_name!("draggable"), DOMString::from(value));
+    }
+
     // https://html.spec.whatwg.org/multipage/#globaleventhandlers
     global_event_handlers!(NoOnload);
 
@@ -568,6 +579,26 @@ impl HTMLElement {
         self.upcast::<Element>().remove_attribute(&ns!(), &local_name);
     }
 
+    // https://html.spec.whatwg.org/multipage/#the-draggable-attribute
+    pub fn is_draggable(&self) -> bool {
+        let element = self.upcast::<Element>();
+        if let Some(attr) = element.get_attribute(&ns!(), &local_name!("draggable")) {
+            if attr.value().eq_ignore_ascii_case("true") {
+                return true;
+            }
+            if attr.value().eq_ignore_ascii_case("false") {
+                return false;
+            }
+        }
+        match self.upcast::<Node>().type_id() {
+            NodeTypeId::Element(ElementTypeId::HTMLElement(HTMLElementTypeId::HTMLImageElement)) => true,
+            NodeTypeId::Element(ElementTypeId::HTMLElement(HTMLElementTypeId::HTMLAnchorElement)) => {
+                element.has_attribute(&local_name!("href"))
+            },
+            _ => false,
+        }
+    }
+
     // https://html.spec.whatwg.org/multipage/#category-label
     pub fn is_labelable_element(&self) -> bool {
         // Note: HTMLKeygenElement is omitted because Servo doesn't currently implement it
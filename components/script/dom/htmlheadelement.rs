@@ -58,8 +58,12 @@ impl HTMLHeadElement {
             if let Some(content) = meta.get_attribute(&ns!(), &local_name!("content")).r() {
                 let content = content.value();
                 let content_val = content.trim();
-                if !content_val.is_empty() {
-                    doc.set_referrer_policy(determine_policy_for_token(content_val));
+                if content_val.is_empty() {
+                    continue;
+                }
+                // Unknown tokens leave the current policy untouched.
+                if let Some(policy) = determine_policy_for_token(content_val) {
+                    doc.set_referrer_policy(Some(policy));
                     return;
                 }
             }
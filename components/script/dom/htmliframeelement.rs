@@ -16,6 +16,7 @@ use dom::bindings::str::DOMString;
 use dom::document::Document;
 use dom::domtokenlist::DOMTokenList;
 use dom::element::{AttributeMutation, Element, RawLayoutElementHelpers};
+use dom::element::{referrer_policy_for_element, reflect_referrer_policy_attribute};
 use dom::eventtarget::EventTarget;
 use dom::globalscope::GlobalScope;
 use dom::htmlelement::HTMLElement;
@@ -125,6 +126,11 @@ impl HTMLIFrameElement {
         LoadBlocker::terminate(&mut load_blocker);
 
         if let Some(ref mut load_data) = load_data {
+            // Nested navigations are upgraded and blocked as mixed content like the
+            // subresources of this document.
+            load_data.insecure_requests_policy = document.insecure_requests_policy();
+            load_data.parent_origin = Some(document.origin().immutable().clone());
+
             let is_javascript = load_data.url.scheme() == "javascript";
             if is_javascript {
                 let window_proxy = self.GetContentWindow();
@@ -233,7 +239,9 @@ impl HTMLIFrameElement {
         };
 
         let document = document_from_node(self);
-        let load_data = LoadData::new(url, creator_pipeline_id, document.get_referrer_policy(), Some(document.url()));
+        let referrer_policy = referrer_policy_for_element(self.upcast::<Element>())
+            .or_else(|| document.get_referrer_policy());
+        let load_data = LoadData::new(url, creator_pipeline_id, referrer_policy, Some(document.url()));
 
         let pipeline_id = self.pipeline_id();
         // If the initial `about:blank` page is the current page, load with replacement enabled.
@@ -453,6 +461,14 @@ impl HTMLIFrameElementMethods for HTMLIFrameElement {
     // https://html.spec.whatwg.org/multipage/#attr-iframe-allowfullscreen
     make_bool_setter!(SetAllowFullscreen, "allowfullscreen");
 
+    // https://html.spec.whatwg.org/multipage/#dom-iframe-referrerpolicy
+    fn ReferrerPolicy(&self) -> DOMString {
+        reflect_referrer_policy_attribute(self.upcast::<Element>())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-iframe-referrerpolicy
+    make_setter!(SetReferrerPolicy, "referrerpolicy");
+
     // https://html.spec.whatwg.org/multipage/#dom-dim-width
     make_getter!(Width, "width");
     // https://html.spec.whatwg.org/multipage/#dom-dim-width
@@ -23,6 +23,7 @@ use dom::bindings::root::{DomRoot, LayoutDom, MutNullableDom};
 use dom::bindings::str::DOMString;
 use dom::document::Document;
 use dom::element::{AttributeMutation, Element, RawLayoutElementHelpers};
+use dom::element::{referrer_policy_for_element, reflect_referrer_policy_attribute};
 use dom::element::{reflect_cross_origin_attribute, set_cross_origin_attribute};
 use dom::event::{Event, EventBubbles, EventCancelable};
 use dom::eventtarget::EventTarget;
@@ -50,7 +51,7 @@ use net_traits::image::base::{Image, ImageMetadata};
 use net_traits::image_cache::{CanRequestImages, ImageCache, ImageOrMetadataAvailable};
 use net_traits::image_cache::{ImageResponder, ImageResponse, ImageState, PendingImageId};
 use net_traits::image_cache::UsePlaceholder;
-use net_traits::request::RequestInit;
+use net_traits::request::{Destination, RequestInit};
 use network_listener::{NetworkListener, PreInvoke};
 use num_traits::ToPrimitive;
 use script_thread::ScriptThread;
@@ -293,14 +294,18 @@ impl HTMLImageElement {
 
         let request = RequestInit {
             url: img_url.clone(),
+            destination: Destination::Image,
             origin: document.origin().immutable().clone(),
             pipeline_id: Some(document.global().pipeline_id()),
+            referrer_url: Some(document.url()),
+            referrer_policy: referrer_policy_for_element(self.upcast::<Element>())
+                .or_else(|| document.get_referrer_policy()),
             .. RequestInit::default()
         };
 
         // This is a background load because the load blocker already fulfills the
         // purpose of delaying the document's load event.
-        document.loader_mut().fetch_async_background(request, action_sender);
+        document.fetch_async_background(request, action_sender);
     }
 
     /// Step 14 of https://html.spec.whatwg.org/multipage/#update-the-image-data
@@ -1068,6 +1073,14 @@ impl HTMLImageElementMethods for HTMLImageElement {
     // https://html.spec.whatwg.org/multipage/#dom-img-usemap
     make_setter!(SetUseMap, "usemap");
 
+    // https://html.spec.whatwg.org/multipage/#dom-img-referrerpolicy
+    fn ReferrerPolicy(&self) -> DOMString {
+        reflect_referrer_policy_attribute(self.upcast::<Element>())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-img-referrerpolicy
+    make_setter!(SetReferrerPolicy, "referrerpolicy");
+
     // https://html.spec.whatwg.org/multipage/#dom-img-ismap
     make_bool_getter!(IsMap, "ismap");
     // https://html.spec.whatwg.org/multipage/#dom-img-ismap
@@ -17,6 +17,7 @@ use dom::bindings::inheritance::Castable;
 use dom::bindings::reflector::DomObject;
 use dom::bindings::root::{Dom, DomRoot, LayoutDom, MutNullableDom, RootedReference};
 use dom::bindings::str::DOMString;
+use dom::clipboardevent::ClipboardEvent;
 use dom::document::Document;
 use dom::element::{AttributeMutation, Element, LayoutElementHelpers, RawLayoutElementHelpers};
 use dom::event::{Event, EventBubbles, EventCancelable};
@@ -1405,6 +1406,25 @@ impl VirtualMethods for HTMLInputElement {
                         Nothing => (),
                     }
                 }
+        } else if let Some(clipboard_event) = event.downcast::<ClipboardEvent>() {
+            if !self.input_type().is_textual_or_password() {
+                return;
+            }
+            if let Some(action) = clipboard_event.text_input_action() {
+                let reaction = self.textinput.borrow_mut().handle_clipboard_action(action);
+                if let DispatchInput = reaction {
+                    self.value_dirty.set(true);
+                    self.update_placeholder_shown_state();
+                    self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+                    let window = window_from_node(self);
+                    let _ = window.user_interaction_task_source()
+                                  .queue_event(&self.upcast(),
+                                               atom!("input"),
+                                               EventBubbles::Bubbles,
+                                               EventCancelable::NotCancelable,
+                                               &window);
+                }
+            }
         } else if event.type_() == atom!("keypress") && !event.DefaultPrevented() &&
             self.input_type().is_textual_or_password() {
                 if event.IsTrusted() {
@@ -16,6 +16,7 @@ use dom::document::Document;
 use dom::domtokenlist::DOMTokenList;
 use dom::element::{AttributeMutation, Element, ElementCreator};
 use dom::element::{cors_setting_for_element, reflect_cross_origin_attribute, set_cross_origin_attribute};
+use dom::element::{referrer_policy_for_element, reflect_referrer_policy_attribute};
 use dom::htmlelement::HTMLElement;
 use dom::node::{Node, UnbindContext, document_from_node, window_from_node};
 use dom::stylesheet::StyleSheet as DOMStyleSheet;
@@ -353,7 +354,7 @@ impl StylesheetOwner for HTMLLinkElement {
             return Some(ReferrerPolicy::NoReferrer)
         }
 
-        None
+        referrer_policy_for_element(self.upcast::<Element>())
     }
 
     fn set_origin_clean(&self, origin_clean: bool) {
@@ -390,6 +391,14 @@ impl HTMLLinkElementMethods for HTMLLinkElement {
     // https://html.spec.whatwg.org/multipage/#dom-link-integrity
     make_setter!(SetIntegrity, "integrity");
 
+    // https://html.spec.whatwg.org/multipage/#dom-link-referrerpolicy
+    fn ReferrerPolicy(&self) -> DOMString {
+        reflect_referrer_policy_attribute(self.upcast::<Element>())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-link-referrerpolicy
+    make_setter!(SetReferrerPolicy, "referrerpolicy");
+
     // https://html.spec.whatwg.org/multipage/#dom-link-hreflang
     make_getter!(Hreflang, "hreflang");
 
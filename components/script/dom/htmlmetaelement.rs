@@ -19,6 +19,7 @@ use dom::node::{Node, UnbindContext, document_from_node, window_from_node};
 use dom::virtualmethods::VirtualMethods;
 use dom_struct::dom_struct;
 use html5ever::{LocalName, Prefix};
+use net_traits::request::{InsecureRequestsPolicy, policy_upgrades_insecure_requests};
 use parking_lot::RwLock;
 use servo_arc::Arc;
 use servo_config::prefs::PREFS;
@@ -87,6 +88,34 @@ impl HTMLMetaElement {
                 self.apply_referrer();
             }
         }
+
+        if let Some(http_equiv) = element.get_attribute(&ns!(), &local_name!("http-equiv")).r() {
+            let http_equiv = http_equiv.value();
+            let http_equiv = http_equiv.trim_matches(HTML_SPACE_CHARACTERS);
+
+            if http_equiv.eq_ignore_ascii_case("content-security-policy") {
+                self.apply_content_security_policy();
+            }
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#attr-meta-http-equiv-content-security-policy>
+    fn apply_content_security_policy(&self) {
+        // Step 1.
+        let in_head = self.upcast::<Node>()
+            .GetParentElement()
+            .map_or(false, |parent| parent.is::<HTMLHeadElement>());
+        if !in_head {
+            return;
+        }
+
+        // Steps 2-4. Only the upgrade-insecure-requests directive is supported.
+        let element = self.upcast::<Element>();
+        if let Some(content) = element.get_attribute(&ns!(), &local_name!("content")).r() {
+            if policy_upgrades_insecure_requests(&content.value()) {
+                document_from_node(self).set_insecure_requests_policy(InsecureRequestsPolicy::Upgrade);
+            }
+        }
     }
 
     fn apply_viewport(&self) {
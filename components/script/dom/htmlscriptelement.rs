@@ -16,6 +16,7 @@ use dom::bindings::str::DOMString;
 use dom::document::Document;
 use dom::element::{AttributeMutation, Element, ElementCreator};
 use dom::element::{cors_setting_for_element, reflect_cross_origin_attribute, set_cross_origin_attribute};
+use dom::element::{referrer_policy_for_element, reflect_referrer_policy_attribute};
 use dom::event::{Event, EventBubbles, EventCancelable, EventStatus};
 use dom::globalscope::GlobalScope;
 use dom::htmlelement::HTMLElement;
@@ -253,7 +254,8 @@ fn fetch_a_classic_script(script: &HTMLScriptElement,
         origin: doc.origin().immutable().clone(),
         pipeline_id: Some(script.global().pipeline_id()),
         referrer_url: Some(doc.url()),
-        referrer_policy: doc.get_referrer_policy(),
+        referrer_policy: referrer_policy_for_element(script.upcast::<Element>())
+            .or_else(|| doc.get_referrer_policy()),
         integrity_metadata: integrity_metadata,
         .. RequestInit::default()
     };
@@ -735,6 +737,14 @@ impl HTMLScriptElementMethods for HTMLScriptElement {
     // https://html.spec.whatwg.org/multipage/#dom-script-integrity
     make_setter!(SetIntegrity, "integrity");
 
+    // https://html.spec.whatwg.org/multipage/#dom-script-referrerpolicy
+    fn ReferrerPolicy(&self) -> DOMString {
+        reflect_referrer_policy_attribute(self.upcast::<Element>())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-script-referrerpolicy
+    make_setter!(SetReferrerPolicy, "referrerpolicy");
+
     // https://html.spec.whatwg.org/multipage/#dom-script-event
     make_getter!(Event, "event");
     // https://html.spec.whatwg.org/multipage/#dom-script-event
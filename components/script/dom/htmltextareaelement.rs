@@ -13,6 +13,7 @@ use dom::bindings::error::ErrorResult;
 use dom::bindings::inheritance::Castable;
 use dom::bindings::root::{DomRoot, LayoutDom, MutNullableDom};
 use dom::bindings::str::DOMString;
+use dom::clipboardevent::ClipboardEvent;
 use dom::document::Document;
 use dom::element::{AttributeMutation, Element};
 use dom::element::RawLayoutElementHelpers;
@@ -522,6 +523,22 @@ impl VirtualMethods for HTMLTextAreaElement {
                     KeyReaction::Nothing => (),
                 }
             }
+        } else if let Some(clipboard_event) = event.downcast::<ClipboardEvent>() {
+            if let Some(action) = clipboard_event.text_input_action() {
+                let reaction = self.textinput.borrow_mut().handle_clipboard_action(action);
+                if let KeyReaction::DispatchInput = reaction {
+                    self.value_dirty.set(true);
+                    self.update_placeholder_shown_state();
+                    self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+                    let window = window_from_node(self);
+                    let _ = window.user_interaction_task_source()
+                                  .queue_event(&self.upcast(),
+                                               atom!("input"),
+                                               EventBubbles::Bubbles,
+                                               EventCancelable::NotCancelable,
+                                               &window);
+                }
+            }
         } else if event.type_() == atom!("keypress") && !event.DefaultPrevented() {
             if event.IsTrusted() {
                 let window = window_from_node(self);
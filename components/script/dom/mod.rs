@@ -242,6 +242,8 @@ pub mod canvaspattern;
 pub mod canvasrenderingcontext2d;
 pub mod characterdata;
 pub mod client;
+pub mod clipboard;
+pub mod clipboardevent;
 pub mod closeevent;
 pub mod comment;
 pub mod compositionevent;
@@ -257,6 +259,7 @@ pub mod csskeyframerule;
 pub mod csskeyframesrule;
 pub mod cssmediarule;
 pub mod cssnamespacerule;
+pub mod csspagerule;
 pub mod cssrule;
 pub mod cssrulelist;
 pub mod cssstyledeclaration;
@@ -267,6 +270,7 @@ pub mod csssupportsrule;
 pub mod cssviewportrule;
 pub mod customelementregistry;
 pub mod customevent;
+pub mod datatransfer;
 pub mod dedicatedworkerglobalscope;
 pub mod dissimilaroriginlocation;
 pub mod dissimilaroriginwindow;
@@ -285,6 +289,7 @@ pub mod domrect;
 pub mod domrectreadonly;
 pub mod domstringmap;
 pub mod domtokenlist;
+pub mod dragevent;
 pub mod element;
 pub mod errorevent;
 pub mod event;
@@ -297,6 +302,9 @@ pub mod filelist;
 pub mod filereader;
 pub mod filereadersync;
 pub mod focusevent;
+pub mod fontface;
+pub mod fontfaceset;
+pub mod fontfacesetloadevent;
 pub mod formdata;
 pub mod gainnode;
 pub mod gamepad;
@@ -420,6 +428,7 @@ pub mod permissions;
 pub mod permissionstatus;
 pub mod plugin;
 pub mod pluginarray;
+pub mod pointerevent;
 pub mod popstateevent;
 pub mod processinginstruction;
 pub mod progressevent;
@@ -495,6 +504,7 @@ pub mod webgltexture;
 pub mod webgluniformlocation;
 pub mod webglvertexarrayobjectoes;
 pub mod websocket;
+pub mod wheelevent;
 pub mod window;
 pub mod windowproxy;
 pub mod worker;
@@ -9,6 +9,7 @@ use dom::bindings::reflector::{Reflector, DomObject, reflect_dom_object};
 use dom::bindings::root::{DomRoot, MutNullableDom};
 use dom::bindings::str::DOMString;
 use dom::bluetooth::Bluetooth;
+use dom::clipboard::Clipboard;
 use dom::gamepadlist::GamepadList;
 use dom::mimetypearray::MimeTypeArray;
 use dom::navigatorinfo;
@@ -31,6 +32,7 @@ pub struct Navigator {
     vr: MutNullableDom<VR>,
     gamepads: MutNullableDom<GamepadList>,
     permissions: MutNullableDom<Permissions>,
+    clipboard: MutNullableDom<Clipboard>,
 }
 
 impl Navigator {
@@ -44,6 +46,7 @@ impl Navigator {
             vr: Default::default(),
             gamepads: Default::default(),
             permissions: Default::default(),
+            clipboard: Default::default(),
         }
     }
 
@@ -143,6 +146,11 @@ impl NavigatorMethods for Navigator {
         self.permissions.or_init(|| Permissions::new(&self.global()))
     }
 
+    // https://w3c.github.io/clipboard-apis/#dom-navigator-clipboard
+    fn Clipboard(&self) -> DomRoot<Clipboard> {
+        self.clipboard.or_init(|| Clipboard::new(&self.global()))
+    }
+
     // https://w3c.github.io/webvr/spec/1.1/#navigator-getvrdisplays-attribute
     #[allow(unrooted_must_root)]
     fn GetVRDisplays(&self) -> Rc<Promise> {
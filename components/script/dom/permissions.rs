@@ -308,6 +308,25 @@ pub fn get_descriptor_permission_state(permission_name: PermissionName,
     state
 }
 
+// https://w3c.github.io/permissions/#request-permission-to-use
+pub fn request_permission_to_use(permission_name: PermissionName, global: &GlobalScope) -> bool {
+    // Step 1 - 2.
+    let state = match get_descriptor_permission_state(permission_name, Some(global)) {
+        // Step 3 - 4.
+        PermissionState::Prompt => {
+            let state = prompt_user(&format!("{} {} ?", REQUEST_DIALOG_MESSAGE, permission_name));
+            global.as_window()
+                  .permission_state_invocation_results()
+                  .borrow_mut()
+                  .insert(permission_name.to_string(), state);
+            state
+        },
+        state => state,
+    };
+    // Step 5.
+    state == PermissionState::Granted
+}
+
 #[cfg(target_os = "linux")]
 fn prompt_user(message: &str) -> PermissionState {
     if opts::get().headless {
@@ -353,5 +372,7 @@ fn allowed_in_nonsecure_contexts(permission_name: &PermissionName) -> bool {
         PermissionName::Bluetooth => false,
         // https://storage.spec.whatwg.org/#dom-permissionname-persistent-storage
         PermissionName::Persistent_storage => false,
+        // https://w3c.github.io/clipboard-apis/#clipboard-permissions
+        PermissionName::Clipboard_read => false,
     }
 }
@@ -0,0 +1,193 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::MouseEventBinding::MouseEventMethods;
+use dom::bindings::codegen::Bindings::PointerEventBinding;
+use dom::bindings::codegen::Bindings::PointerEventBinding::PointerEventMethods;
+use dom::bindings::error::Fallible;
+use dom::bindings::inheritance::Castable;
+use dom::bindings::num::Finite;
+use dom::bindings::reflector::reflect_dom_object;
+use dom::bindings::root::{DomRoot, RootedReference};
+use dom::bindings::str::DOMString;
+use dom::event::{EventBubbles, EventCancelable};
+use dom::eventtarget::EventTarget;
+use dom::mouseevent::MouseEvent;
+use dom::window::Window;
+use dom_struct::dom_struct;
+
+#[dom_struct]
+pub struct PointerEvent {
+    mouseevent: MouseEvent,
+    pointer_id: i32,
+    width: f64,
+    height: f64,
+    pressure: f32,
+    tangential_pressure: f32,
+    tilt_x: i32,
+    tilt_y: i32,
+    twist: i32,
+    pointer_type: DOMString,
+    is_primary: bool,
+}
+
+impl PointerEvent {
+    fn new_inherited(
+        pointer_id: i32,
+        width: Finite<f64>,
+        height: Finite<f64>,
+        pressure: Finite<f32>,
+        tangential_pressure: Finite<f32>,
+        tilt_x: i32,
+        tilt_y: i32,
+        twist: i32,
+        pointer_type: DOMString,
+        is_primary: bool,
+    ) -> PointerEvent {
+        PointerEvent {
+            mouseevent: MouseEvent::new_inherited(),
+            pointer_id: pointer_id,
+            width: *width,
+            height: *height,
+            pressure: *pressure,
+            tangential_pressure: *tangential_pressure,
+            tilt_x: tilt_x,
+            tilt_y: tilt_y,
+            twist: twist,
+            pointer_type: pointer_type,
+            is_primary: is_primary,
+        }
+    }
+
+    pub fn new(
+        window: &Window,
+        type_: DOMString,
+        can_bubble: EventBubbles,
+        cancelable: EventCancelable,
+        view: Option<&Window>,
+        detail: i32,
+        screen_x: i32,
+        screen_y: i32,
+        client_x: i32,
+        client_y: i32,
+        ctrl_key: bool,
+        alt_key: bool,
+        shift_key: bool,
+        meta_key: bool,
+        button: i16,
+        related_target: Option<&EventTarget>,
+        pointer_id: i32,
+        width: Finite<f64>,
+        height: Finite<f64>,
+        pressure: Finite<f32>,
+        tangential_pressure: Finite<f32>,
+        tilt_x: i32,
+        tilt_y: i32,
+        twist: i32,
+        pointer_type: DOMString,
+        is_primary: bool,
+    ) -> DomRoot<PointerEvent> {
+        let ev = reflect_dom_object(
+            Box::new(PointerEvent::new_inherited(
+                pointer_id, width, height,
+                pressure, tangential_pressure,
+                tilt_x, tilt_y, twist,
+                pointer_type, is_primary,
+            )),
+            window,
+            PointerEventBinding::Wrap,
+        );
+        ev.upcast::<MouseEvent>().InitMouseEvent(
+            type_, bool::from(can_bubble), bool::from(cancelable),
+            view, detail,
+            screen_x, screen_y, client_x, client_y,
+            ctrl_key, alt_key, shift_key, meta_key,
+            button, related_target,
+        );
+        ev
+    }
+
+    pub fn Constructor(window: &Window,
+                       type_: DOMString,
+                       init: &PointerEventBinding::PointerEventInit) -> Fallible<DomRoot<PointerEvent>> {
+        let mouse_init = &init.parent;
+        let bubbles = EventBubbles::from(mouse_init.parent.parent.parent.bubbles);
+        let cancelable = EventCancelable::from(mouse_init.parent.parent.parent.cancelable);
+        let event = PointerEvent::new(
+            window,
+            type_,
+            bubbles,
+            cancelable,
+            mouse_init.parent.parent.view.r(),
+            mouse_init.parent.parent.detail,
+            mouse_init.screenX, mouse_init.screenY,
+            mouse_init.clientX, mouse_init.clientY, mouse_init.parent.ctrlKey,
+            mouse_init.parent.altKey, mouse_init.parent.shiftKey, mouse_init.parent.metaKey,
+            mouse_init.button, mouse_init.relatedTarget.r(),
+            init.pointerId,
+            init.width, init.height,
+            init.pressure, init.tangentialPressure,
+            init.tiltX, init.tiltY, init.twist,
+            init.pointerType.clone(), init.isPrimary,
+        );
+        Ok(event)
+    }
+}
+
+impl PointerEventMethods for PointerEvent {
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-pointerid
+    fn PointerId(&self) -> i32 {
+        self.pointer_id
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-width
+    fn Width(&self) -> Finite<f64> {
+        Finite::wrap(self.width)
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-height
+    fn Height(&self) -> Finite<f64> {
+        Finite::wrap(self.height)
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-pressure
+    fn Pressure(&self) -> Finite<f32> {
+        Finite::wrap(self.pressure)
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-tangentialpressure
+    fn TangentialPressure(&self) -> Finite<f32> {
+        Finite::wrap(self.tangential_pressure)
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-tiltx
+    fn TiltX(&self) -> i32 {
+        self.tilt_x
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-tilty
+    fn TiltY(&self) -> i32 {
+        self.tilt_y
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-twist
+    fn Twist(&self) -> i32 {
+        self.twist
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-pointertype
+    fn PointerType(&self) -> DOMString {
+        self.pointer_type.clone()
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-isprimary
+    fn IsPrimary(&self) -> bool {
+        self.is_primary
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.mouseevent.IsTrusted()
+    }
+}
@@ -168,6 +168,7 @@ impl ServiceWorkerGlobalScope {
                 referrer_url: referrer_url,
                 referrer_policy: referrer_policy,
                 origin,
+                insecure_requests_policy: init.insecure_requests_policy,
                 .. RequestInit::default()
             };
 
@@ -2,6 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+use devtools_traits::{StoreChange, StoreType};
 use dom::bindings::codegen::Bindings::StorageBinding;
 use dom::bindings::codegen::Bindings::StorageBinding::StorageMethods;
 use dom::bindings::error::{Error, ErrorResult};
@@ -152,6 +153,18 @@ impl Storage {
     fn broadcast_change_notification(&self, key: Option<String>, old_value: Option<String>,
                                      new_value: Option<String>) {
         let storage = self.storage_type;
+        let store = match storage {
+            StorageType::Local => StoreType::LocalStorage,
+            StorageType::Session => StoreType::SessionStorage,
+        };
+        let change = match (key.clone(), &old_value, &new_value) {
+            (None, _, _) => StoreChange::Cleared,
+            (Some(key), &None, _) => StoreChange::Added(key),
+            (Some(key), _, &None) => StoreChange::Deleted(key),
+            (Some(key), _, _) => StoreChange::Changed(key),
+        };
+        self.global().notify_store_changed(store, change);
+
         let url = self.get_url();
         let msg = ScriptMsg::BroadcastStorageEvent(storage, url, key, old_value, new_value);
         self.global().script_to_constellation_chan().send(msg).unwrap();
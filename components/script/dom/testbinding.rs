@@ -10,6 +10,7 @@ use dom::bindings::codegen::Bindings::FunctionBinding::Function;
 use dom::bindings::codegen::Bindings::TestBindingBinding::{self, SimpleCallback};
 use dom::bindings::codegen::Bindings::TestBindingBinding::{TestBindingMethods, TestDictionary};
 use dom::bindings::codegen::Bindings::TestBindingBinding::{TestDictionaryDefaults, TestEnum};
+use dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use dom::bindings::codegen::UnionTypes;
 use dom::bindings::codegen::UnionTypes::{BlobOrBoolean, BlobOrBlobSequence, LongOrLongSequenceSequence};
 use dom::bindings::codegen::UnionTypes::{BlobOrString, BlobOrUnsignedLong, EventOrString};
@@ -19,8 +20,9 @@ use dom::bindings::codegen::UnionTypes::{EventOrUSVString, HTMLElementOrLong, Lo
 use dom::bindings::codegen::UnionTypes::{HTMLElementOrUnsignedLongOrStringOrBoolean, LongSequenceOrBoolean};
 use dom::bindings::codegen::UnionTypes::{StringOrLongSequence, StringOrStringSequence, StringSequenceOrUnsignedLong};
 use dom::bindings::codegen::UnionTypes::{StringOrUnsignedLong, StringOrBoolean, UnsignedLongOrBoolean};
-use dom::bindings::error::{Error, Fallible};
+use dom::bindings::error::{Error, ErrorResult, Fallible};
 use dom::bindings::mozmap::MozMap;
+use dom::bindings::inheritance::Castable;
 use dom::bindings::num::Finite;
 use dom::bindings::refcounted::TrustedPromise;
 use dom::bindings::reflector::{DomObject, Reflector, reflect_dom_object};
@@ -33,14 +35,18 @@ use dom::globalscope::GlobalScope;
 use dom::promise::Promise;
 use dom::promisenativehandler::{PromiseNativeHandler, Callback};
 use dom::url::URL;
+use dom::window::Window;
 use dom_struct::dom_struct;
+use euclid::Point2D;
 use js::jsapi::{Heap, JSContext, JSObject};
 use js::jsapi::{JS_NewPlainObject, JS_NewUint8ClampedArray};
 use js::jsval::{JSVal, NullValue};
 use js::rust::{HandleObject, HandleValue};
 use js::rust::CustomAutoRooterGuard;
 use js::typedarray;
-use script_traits::MsDuration;
+use script_layout_interface::message::NodesFromPointQueryType;
+use script_traits::{CompositorEvent, MouseButton, MouseEventType, MsDuration};
+use script_traits::{TouchEventType, TouchId, UntrustedNodeAddress};
 use servo_config::prefs::PREFS;
 use std::borrow::ToOwned;
 use std::ptr;
@@ -80,6 +86,18 @@ impl TestBinding {
     pub fn Constructor__(global: &GlobalScope, num: f64) -> Fallible<DomRoot<TestBinding>> {
         Ok(TestBinding::new(global))
     }
+
+    /// Returns the window that synthesized input is sent to, and the node that input at the
+    /// given point hits.
+    fn hit_test(&self, point: &Point2D<f32>) -> Fallible<(DomRoot<Window>, Option<UntrustedNodeAddress>)> {
+        let window = DomRoot::downcast::<Window>(self.global()).ok_or(Error::NotSupported)?;
+        let node_address = window
+            .Document()
+            .nodes_from_point(point, NodesFromPointQueryType::Topmost)
+            .into_iter()
+            .next();
+        Ok((window, node_address))
+    }
 }
 
 impl TestBindingMethods for TestBinding {
@@ -804,6 +822,43 @@ impl TestBindingMethods for TestBinding {
 
     fn Panic(&self) { panic!("explicit panic from script") }
 
+    fn SendMouseEvent(&self, type_: DOMString, x: Finite<f32>, y: Finite<f32>, button: u16) -> ErrorResult {
+        let button = match button {
+            0 => MouseButton::Left,
+            1 => MouseButton::Middle,
+            2 => MouseButton::Right,
+            _ => return Err(Error::Type(format!("Unknown mouse button {}", button))),
+        };
+        let point = Point2D::new(*x, *y);
+        let (window, node_address) = self.hit_test(&point)?;
+        let event_type = match &*type_ {
+            "mousemove" => {
+                window.send_test_input_event(CompositorEvent::MouseMoveEvent(Some(point), node_address));
+                return Ok(());
+            },
+            "mousedown" => MouseEventType::MouseDown,
+            "mouseup" => MouseEventType::MouseUp,
+            "click" => MouseEventType::Click,
+            _ => return Err(Error::Type(format!("Unknown mouse event type {}", type_))),
+        };
+        window.send_test_input_event(CompositorEvent::MouseButtonEvent(event_type, button, point, node_address, None));
+        Ok(())
+    }
+
+    fn SendTouchEvent(&self, type_: DOMString, identifier: i32, x: Finite<f32>, y: Finite<f32>) -> ErrorResult {
+        let event_type = match &*type_ {
+            "touchstart" => TouchEventType::Down,
+            "touchmove" => TouchEventType::Move,
+            "touchend" => TouchEventType::Up,
+            "touchcancel" => TouchEventType::Cancel,
+            _ => return Err(Error::Type(format!("Unknown touch event type {}", type_))),
+        };
+        let point = Point2D::new(*x, *y);
+        let (window, node_address) = self.hit_test(&point)?;
+        window.send_test_input_event(CompositorEvent::TouchEvent(event_type, TouchId(identifier), point, node_address));
+        Ok(())
+    }
+
     fn EntryGlobal(&self) -> DomRoot<GlobalScope> {
         GlobalScope::entry()
     }
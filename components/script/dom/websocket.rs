@@ -184,6 +184,7 @@ impl WebSocket {
             ProfiledIpc::channel(global.time_profiler_chan().clone()).unwrap();
 
         let ws = WebSocket::new(global, url_record.clone(), dom_action_sender);
+        global.track_websocket(&ws);
         let address = Trusted::new(&*ws);
 
         // Step 8.
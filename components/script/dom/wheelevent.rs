@@ -0,0 +1,125 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::MouseEventBinding::MouseEventMethods;
+use dom::bindings::codegen::Bindings::WheelEventBinding;
+use dom::bindings::codegen::Bindings::WheelEventBinding::WheelEventMethods;
+use dom::bindings::error::Fallible;
+use dom::bindings::inheritance::Castable;
+use dom::bindings::num::Finite;
+use dom::bindings::reflector::reflect_dom_object;
+use dom::bindings::root::{DomRoot, RootedReference};
+use dom::bindings::str::DOMString;
+use dom::event::{EventBubbles, EventCancelable};
+use dom::eventtarget::EventTarget;
+use dom::mouseevent::MouseEvent;
+use dom::window::Window;
+use dom_struct::dom_struct;
+
+#[dom_struct]
+pub struct WheelEvent {
+    mouseevent: MouseEvent,
+    delta_x: f64,
+    delta_y: f64,
+    delta_z: f64,
+    delta_mode: u32,
+}
+
+impl WheelEvent {
+    fn new_inherited(delta_x: Finite<f64>, delta_y: Finite<f64>, delta_z: Finite<f64>, delta_mode: u32) -> WheelEvent {
+        WheelEvent {
+            mouseevent: MouseEvent::new_inherited(),
+            delta_x: *delta_x,
+            delta_y: *delta_y,
+            delta_z: *delta_z,
+            delta_mode: delta_mode,
+        }
+    }
+
+    pub fn new(
+        window: &Window,
+        type_: DOMString,
+        can_bubble: EventBubbles,
+        cancelable: EventCancelable,
+        view: Option<&Window>,
+        detail: i32,
+        screen_x: i32,
+        screen_y: i32,
+        client_x: i32,
+        client_y: i32,
+        ctrl_key: bool,
+        alt_key: bool,
+        shift_key: bool,
+        meta_key: bool,
+        button: i16,
+        related_target: Option<&EventTarget>,
+        delta_x: Finite<f64>,
+        delta_y: Finite<f64>,
+        delta_z: Finite<f64>,
+        delta_mode: u32,
+    ) -> DomRoot<WheelEvent> {
+        let ev = reflect_dom_object(
+            Box::new(WheelEvent::new_inherited(delta_x, delta_y, delta_z, delta_mode)),
+            window,
+            WheelEventBinding::Wrap,
+        );
+        ev.upcast::<MouseEvent>().InitMouseEvent(
+            type_, bool::from(can_bubble), bool::from(cancelable),
+            view, detail,
+            screen_x, screen_y, client_x, client_y,
+            ctrl_key, alt_key, shift_key, meta_key,
+            button, related_target,
+        );
+        ev
+    }
+
+    pub fn Constructor(window: &Window,
+                       type_: DOMString,
+                       init: &WheelEventBinding::WheelEventInit) -> Fallible<DomRoot<WheelEvent>> {
+        let mouse_init = &init.parent;
+        let bubbles = EventBubbles::from(mouse_init.parent.parent.parent.bubbles);
+        let cancelable = EventCancelable::from(mouse_init.parent.parent.parent.cancelable);
+        let event = WheelEvent::new(
+            window,
+            type_,
+            bubbles,
+            cancelable,
+            mouse_init.parent.parent.view.r(),
+            mouse_init.parent.parent.detail,
+            mouse_init.screenX, mouse_init.screenY,
+            mouse_init.clientX, mouse_init.clientY, mouse_init.parent.ctrlKey,
+            mouse_init.parent.altKey, mouse_init.parent.shiftKey, mouse_init.parent.metaKey,
+            mouse_init.button, mouse_init.relatedTarget.r(),
+            init.deltaX, init.deltaY, init.deltaZ, init.deltaMode,
+        );
+        Ok(event)
+    }
+}
+
+impl WheelEventMethods for WheelEvent {
+    // https://w3c.github.io/uievents/#dom-wheelevent-deltax
+    fn DeltaX(&self) -> Finite<f64> {
+        Finite::wrap(self.delta_x)
+    }
+
+    // https://w3c.github.io/uievents/#dom-wheelevent-deltay
+    fn DeltaY(&self) -> Finite<f64> {
+        Finite::wrap(self.delta_y)
+    }
+
+    // https://w3c.github.io/uievents/#dom-wheelevent-deltaz
+    fn DeltaZ(&self) -> Finite<f64> {
+        Finite::wrap(self.delta_z)
+    }
+
+    // https://w3c.github.io/uievents/#dom-wheelevent-deltamode
+    fn DeltaMode(&self) -> u32 {
+        self.delta_mode
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.mouseevent.IsTrusted()
+    }
+}
@@ -23,7 +23,7 @@ use dom::bindings::inheritance::Castable;
 use dom::bindings::num::Finite;
 use dom::bindings::refcounted::Trusted;
 use dom::bindings::reflector::DomObject;
-use dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use dom::bindings::root::{Dom, DomRoot, MutNullableDom, RootedReference};
 use dom::bindings::str::{DOMString, USVString};
 use dom::bindings::structuredclone::StructuredCloneData;
 use dom::bindings::trace::RootedTraceableBox;
@@ -78,12 +78,12 @@ use profile_traits::time::ProfilerChan as TimeProfilerChan;
 use script_layout_interface::{TrustedNodeAddress, PendingImageState};
 use script_layout_interface::message::{Msg, Reflow, QueryMsg, ReflowGoal, ScriptReflow};
 use script_layout_interface::reporter::CSSErrorReporter;
-use script_layout_interface::rpc::{ContentBoxResponse, ContentBoxesResponse, LayoutRPC};
+use script_layout_interface::rpc::{ContentBoxResponse, ContentBoxesResponse, LayoutPage, LayoutRPC};
 use script_layout_interface::rpc::{NodeScrollIdResponse, ResolvedStyleResponse, TextIndexResponse};
 use script_runtime::{CommonScriptMsg, ScriptChan, ScriptPort, ScriptThreadEventCategory, Runtime};
 use script_thread::{ImageCacheMsg, MainThreadScriptChan, MainThreadScriptMsg};
 use script_thread::{ScriptThread, SendableMainThreadScriptChan};
-use script_traits::{ConstellationControlMsg, DocumentState, LoadData};
+use script_traits::{CompositorEvent, ConstellationControlMsg, DocumentState, LoadData};
 use script_traits::{ScriptToConstellationChan, ScriptMsg, ScrollState, TimerEvent, TimerEventId};
 use script_traits::{TimerSchedulerMsg, UntrustedNodeAddress, WindowSizeData, WindowSizeType};
 use script_traits::webdriver_msg::{WebDriverJSError, WebDriverJSResult};
@@ -109,7 +109,7 @@ use std::sync::mpsc::TryRecvError::{Disconnected, Empty};
 use style::error_reporting::ParseErrorReporter;
 use style::media_queries;
 use style::parser::ParserContext as CssParserContext;
-use style::properties::{ComputedValues, PropertyId};
+use style::properties::{ComputedValues, LonghandId, PropertyId};
 use style::selector_parser::PseudoElement;
 use style::str::HTML_SPACE_CHARACTERS;
 use style::stylesheets::CssRuleType;
@@ -162,6 +162,12 @@ pub enum ReflowReason {
     ElementStateChanged,
 }
 
+/// How long, in nanoseconds, a user interaction lets a document use the features that require
+/// one.
+///
+/// <https://html.spec.whatwg.org/multipage/#transient-activation-duration>
+const TRANSIENT_ACTIVATION_DURATION_NS: u64 = 5_000_000_000;
+
 #[dom_struct]
 pub struct Window {
     globalscope: GlobalScope,
@@ -281,6 +287,11 @@ pub struct Window {
     /// A map for storing the previous permission state read results.
     permission_state_invocation_results: DomRefCell<HashMap<String, PermissionState>>,
 
+    /// When the user last interacted with the document, from `time::precise_time_ns`.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#last-activation-timestamp>
+    last_activation_timestamp: Cell<Option<u64>>,
+
     /// All of the elements that have an outstanding image request that was
     /// initiated by layout during a reflow. They are stored in the script thread
     /// to ensure that the element can be marked dirty when the image data becomes
@@ -1211,12 +1222,13 @@ impl Window {
         let global_scope = self.upcast::<GlobalScope>();
         let x = x.to_f32().unwrap_or(0.0f32);
         let y = y.to_f32().unwrap_or(0.0f32);
+        let document_element = self.Document().GetDocumentElement();
         self.update_viewport_for_scroll(x, y);
         self.perform_a_scroll(x,
                               y,
                               global_scope.pipeline_id().root_scroll_id(),
                               behavior,
-                              None);
+                              document_element.r());
     }
 
     /// <https://drafts.csswg.org/cssom-view/#perform-a-scroll>
@@ -1224,15 +1236,40 @@ impl Window {
                             x: f32,
                             y: f32,
                             scroll_id: ExternalScrollId,
-                            _behavior: ScrollBehavior,
-                            _element: Option<&Element>) {
+                            behavior: ScrollBehavior,
+                            element: Option<&Element>) {
         // TODO Step 1
-        // TODO(mrobinson, #18709): Add smooth scrolling support to WebRender so that we can
-        // properly process ScrollBehavior here.
-        self.layout_chan.send(Msg::UpdateScrollStateFromScript(ScrollState {
+        let scroll_state = ScrollState {
             scroll_id,
             scroll_offset: Vector2D::new(-x, -y),
-        })).unwrap();
+        };
+
+        // Step 2
+        if self.scrolls_smoothly(behavior, element) {
+            // The compositor animates the scroll, and tells layout about the new scroll offsets
+            // once it is done.
+            self.send_to_constellation(ScriptMsg::SmoothScroll(scroll_state));
+            return;
+        }
+
+        // Step 3
+        self.layout_chan.send(Msg::UpdateScrollStateFromScript(scroll_state)).unwrap();
+    }
+
+    /// Whether a scroll with the given behavior, associated with `element`, is performed in a
+    /// smooth fashion rather than instantly.
+    ///
+    /// <https://drafts.csswg.org/cssom-view/#perform-a-scroll>
+    fn scrolls_smoothly(&self, behavior: ScrollBehavior, element: Option<&Element>) -> bool {
+        match behavior {
+            ScrollBehavior::Smooth => true,
+            ScrollBehavior::Instant => false,
+            ScrollBehavior::Auto => element.map_or(false, |element| {
+                let node = element.upcast::<Node>().to_trusted_node_address();
+                let property = PropertyId::Longhand(LonghandId::ScrollBehavior);
+                &*self.resolved_style_query(node, None, property) == "smooth"
+            }),
+        }
     }
 
     pub fn update_viewport_for_scroll(&self, x: f32, y: f32) {
@@ -1261,6 +1298,13 @@ impl Window {
         self.layout_chan.send(Msg::AdvanceClockMs(delta, tick)).unwrap();
     }
 
+    /// Queues input that script synthesized for testing, which is then handled like input
+    /// from the compositor.
+    pub fn send_test_input_event(&self, event: CompositorEvent) {
+        let pipeline_id = self.upcast::<GlobalScope>().pipeline_id();
+        self.main_thread_script_chan().send(MainThreadScriptMsg::SendEvent(pipeline_id, event)).unwrap();
+    }
+
     /// Reflows the page unconditionally if possible and not suppressed. This
     /// method will wait for the layout thread to complete (but see the `TODO`
     /// below). If there is no window size yet, the page is presumed invisible
@@ -1522,7 +1566,7 @@ impl Window {
                               y_.to_f32().unwrap_or(0.0f32),
                               scroll_id,
                               behavior,
-                              None);
+                              node.downcast::<Element>());
     }
 
     pub fn resolved_style_query(&self,
@@ -1570,6 +1614,14 @@ impl Window {
         self.layout_rpc.text_index()
     }
 
+    /// Lays the document out on pages of the given size, for printing and PDF export.
+    pub fn pages_query(&self, page_size: Size2D<Au>) -> Vec<LayoutPage> {
+        if !self.layout_reflow(QueryMsg::PagesQuery(page_size)) {
+            return vec![];
+        }
+        self.layout_rpc.pages()
+    }
+
     #[allow(unsafe_code)]
     pub fn init_window_proxy(&self, window_proxy: &WindowProxy) {
         assert!(self.window_proxy.get().is_none());
@@ -1797,6 +1849,21 @@ impl Window {
         self.parent_info.is_none()
     }
 
+    /// Records that the user just interacted with the document, through an activation
+    /// triggering input event.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#activation-notification>
+    pub fn notify_activation(&self) {
+        self.last_activation_timestamp.set(Some(time::precise_time_ns()));
+    }
+
+    // https://html.spec.whatwg.org/multipage/#transient-activation
+    pub fn has_transient_activation(&self) -> bool {
+        self.last_activation_timestamp.get().map_or(false, |timestamp| {
+            time::precise_time_ns() - timestamp < TRANSIENT_ACTIVATION_DURATION_NS
+        })
+    }
+
     /// Evaluate media query lists and report changes
     /// <https://drafts.csswg.org/cssom-view/#evaluate-media-queries-and-report-changes>
     pub fn evaluate_media_queries_and_report_changes(&self) {
@@ -1959,6 +2026,7 @@ impl Window {
             webgl_chan,
             webvr_chan,
             permission_state_invocation_results: Default::default(),
+            last_activation_timestamp: Cell::new(None),
             pending_layout_images: Default::default(),
             unminified_js_dir: Default::default(),
             test_worklet: Default::default(),
@@ -2012,6 +2080,7 @@ fn debug_reflow_events(id: PipelineId, reflow_goal: &ReflowGoal, reason: &Reflow
             &QueryMsg::StyleQuery(_n) => "\tStyleQuery",
             &QueryMsg::TextIndexQuery(..) => "\tTextIndexQuery",
             &QueryMsg::ElementInnerTextQuery(_) => "\tElementInnerTextQuery",
+            &QueryMsg::PagesQuery(_) => "\tPagesQuery",
         },
     });
 
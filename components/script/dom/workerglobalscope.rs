@@ -32,7 +32,7 @@ use js::panic::maybe_resume_unwind;
 use js::rust::HandleValue;
 use msg::constellation_msg::PipelineId;
 use net_traits::{IpcSend, load_whole_resource};
-use net_traits::request::{CredentialsMode, Destination, RequestInit as NetRequestInit};
+use net_traits::request::{CredentialsMode, Destination, InsecureRequestsPolicy, RequestInit as NetRequestInit};
 use script_runtime::{CommonScriptMsg, ScriptChan, ScriptPort, get_reports, Runtime};
 use script_traits::{TimerEvent, TimerEventId};
 use script_traits::WorkerGlobalScopeInit;
@@ -63,6 +63,7 @@ pub fn prepare_workerscope_init(global: &GlobalScope,
             worker_id: global.get_next_worker_id(),
             pipeline_id: global.pipeline_id(),
             origin: global.origin().immutable().clone(),
+            insecure_requests_policy: global.insecure_requests_policy(),
         };
 
     init
@@ -94,6 +95,9 @@ pub struct WorkerGlobalScope {
 
     navigation_start_precise: u64,
     performance: MutNullableDom<Performance>,
+
+    /// <https://w3c.github.io/webappsec-upgrade-insecure-requests/#insecure-requests-policy>
+    insecure_requests_policy: InsecureRequestsPolicy,
 }
 
 impl WorkerGlobalScope {
@@ -119,6 +123,7 @@ impl WorkerGlobalScope {
                 Default::default(),
             ),
             worker_id: init.worker_id,
+            insecure_requests_policy: init.insecure_requests_policy,
             worker_url,
             closing,
             runtime,
@@ -159,6 +164,10 @@ impl WorkerGlobalScope {
         &self.worker_url
     }
 
+    pub fn insecure_requests_policy(&self) -> InsecureRequestsPolicy {
+        self.insecure_requests_policy
+    }
+
     pub fn get_worker_id(&self) -> WorkerId {
         self.worker_id.clone()
     }
@@ -204,7 +213,7 @@ impl WorkerGlobalScopeMethods for WorkerGlobalScope {
         rooted!(in(self.runtime.cx()) let mut rval = UndefinedValue());
         for url in urls {
             let global_scope = self.upcast::<GlobalScope>();
-            let request = NetRequestInit {
+            let mut request = NetRequestInit {
                 url: url.clone(),
                 destination: Destination::Script,
                 credentials_mode: CredentialsMode::Include,
@@ -215,6 +224,7 @@ impl WorkerGlobalScopeMethods for WorkerGlobalScope {
                 referrer_policy: None,
                 .. NetRequestInit::default()
             };
+            global_scope.prepare_request(&mut request);
             let (url, source) = match load_whole_resource(request,
                                                           &global_scope.resource_threads().sender()) {
                 Err(_) => return Err(Error::Network),
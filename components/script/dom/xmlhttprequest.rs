@@ -596,6 +596,7 @@ impl XMLHttpRequestMethods for XMLHttpRequest {
             pipeline_id: Some(self.global().pipeline_id()),
             .. RequestInit::default()
         };
+        self.global().prepare_request(&mut request);
 
         // step 4 (second half)
         match extracted_or_serialized {
@@ -141,6 +141,7 @@ pub fn Fetch(global: &GlobalScope, input: RequestInfo, init: RootedTraceableBox<
     if global.downcast::<ServiceWorkerGlobalScope>().is_some() {
         request_init.service_workers_mode = ServiceWorkersMode::Foreign;
     }
+    global.prepare_request(&mut request_init);
 
     // Step 4
     response.Headers().set_guard(Guard::Immutable);
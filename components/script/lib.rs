@@ -81,6 +81,8 @@ extern crate script_traits;
 extern crate selectors;
 extern crate serde;
 extern crate serde_bytes;
+#[macro_use]
+extern crate serde_json;
 extern crate servo_allocator;
 extern crate servo_arc;
 #[macro_use] extern crate servo_atoms;
@@ -109,6 +111,7 @@ extern crate xml5ever;
 mod task;
 mod body;
 pub mod clipboard_provider;
+mod debugger;
 mod devtools;
 pub mod document_loader;
 #[macro_use]
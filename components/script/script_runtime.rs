@@ -315,8 +315,9 @@ pub unsafe fn new_rt_and_cx() -> Runtime {
     Runtime(runtime)
 }
 
+/// The heap size of the DOM object that a reflector wraps, or 0 for other JS objects.
 #[allow(unsafe_code)]
-unsafe extern "C" fn get_size(obj: *mut JSObject) -> usize {
+pub unsafe extern "C" fn get_dom_object_size(obj: *mut JSObject) -> usize {
     match get_dom_class(obj) {
         Ok(v) => {
             let dom_object = private_from_object(obj) as *const c_void;
@@ -340,7 +341,7 @@ pub fn get_reports(cx: *mut JSContext, path_seg: String) -> Vec<Report> {
     unsafe {
         let rt = JS_GetRuntime(cx);
         let mut stats = ::std::mem::zeroed();
-        if CollectServoSizes(rt, &mut stats, Some(get_size)) {
+        if CollectServoSizes(rt, &mut stats, Some(get_dom_object_size)) {
             let mut report = |mut path_suffix, kind, size| {
                 let mut path = path![path_seg, "js"];
                 path.append(&mut path_suffix);
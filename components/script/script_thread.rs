@@ -19,10 +19,12 @@
 
 extern crate itertools;
 
+use app_units::Au;
 use bluetooth_traits::BluetoothRequest;
 use canvas_traits::webgl::WebGLPipeline;
+use debugger::ScriptDebugger;
 use devtools;
-use devtools_traits::{DevtoolScriptControlMsg, DevtoolsPageInfo};
+use devtools_traits::{DevtoolScriptControlMsg, DevtoolsPageInfo, PauseInfo, StepKind};
 use devtools_traits::{ScriptToDevtoolsControlMsg, WorkerId};
 use devtools_traits::CSSError;
 use document_loader::DocumentLoader;
@@ -63,7 +65,7 @@ use dom::worker::TrustedWorkerAddress;
 use dom::worklet::WorkletThreadPool;
 use dom::workletglobalscope::WorkletGlobalScopeInit;
 use embedder_traits::EmbedderMsg;
-use euclid::{Point2D, Vector2D, Rect};
+use euclid::{Point2D, Rect, Size2D, TypedSize2D, Vector2D};
 use fetch::FetchCanceller;
 use hyper::header::{ContentType, HttpDate, Headers, LastModified};
 use hyper::header::ReferrerPolicy as ReferrerPolicyHeader;
@@ -82,22 +84,24 @@ use msg::constellation_msg::{PipelineNamespace, TopLevelBrowsingContextId};
 use net_traits::{FetchMetadata, FetchResponseListener, FetchResponseMsg};
 use net_traits::{Metadata, NetworkError, ReferrerPolicy, ResourceThreads};
 use net_traits::image_cache::{ImageCache, PendingImageResponse};
-use net_traits::request::{CredentialsMode, Destination, RedirectMode, RequestInit};
+use net_traits::request::{CredentialsMode, Destination, InsecureRequestsPolicy, RedirectMode, RequestInit};
+use net_traits::request::policy_upgrades_insecure_requests;
 use net_traits::storage_thread::StorageType;
 use profile_traits::mem::{self, OpaqueSender, ReportsChan};
 use profile_traits::time::{self, ProfilerCategory, profile};
 use script_layout_interface::message::{self, Msg, NewLayoutThreadInfo, ReflowGoal};
 use script_runtime::{CommonScriptMsg, ScriptChan, ScriptThreadEventCategory};
 use script_runtime::{ScriptPort, get_reports, new_rt_and_cx, Runtime};
-use script_traits::{CompositorEvent, ConstellationControlMsg};
+use script_traits::{AllowedTouchActions, CompositorEvent, ConstellationControlMsg};
 use script_traits::{DiscardBrowsingContext, DocumentActivity, EventResult};
 use script_traits::{InitialScriptState, JsEvalResult, LayoutMsg, LoadData};
-use script_traits::{MouseButton, MouseEventType, NewLayoutInfo};
+use script_traits::{MouseButton, MouseEventType, NewLayoutInfo, PrintedPage};
 use script_traits::{ProgressiveWebMetricType, Painter, ScriptMsg, ScriptThreadFactory};
 use script_traits::{ScriptToConstellationChan, TimerEvent, TimerSchedulerMsg};
-use script_traits::{TimerSource, TouchEventType, TouchId, UntrustedNodeAddress};
+use script_traits::{TimerSource, TouchEventType, TouchId, UntrustedNodeAddress, WebFontLoadEvent};
 use script_traits::{UpdatePipelineIdReason, WindowSizeData, WindowSizeType};
-use script_traits::CompositorEvent::{KeyEvent, MouseButtonEvent, MouseMoveEvent, ResizeEvent, TouchEvent};
+use script_traits::CompositorEvent::{DropFilesEvent, KeyEvent, MouseButtonEvent, MouseMoveEvent};
+use script_traits::CompositorEvent::{ResizeEvent, TouchEvent, WheelEvent};
 use script_traits::webdriver_msg::WebDriverScriptCommand;
 use serviceworkerjob::{Job, JobQueue};
 use servo_atoms::Atom;
@@ -116,6 +120,7 @@ use std::sync::Arc;
 use std::sync::mpsc::{Receiver, Select, Sender, channel};
 use std::thread;
 use style::thread_state::{self, ThreadState};
+use style_traits::CSSPixel;
 use task_source::dom_manipulation::DOMManipulationTaskSource;
 use task_source::file_reading::FileReadingTaskSource;
 use task_source::history_traversal::HistoryTraversalTaskSource;
@@ -238,6 +243,8 @@ pub enum MainThreadScriptMsg {
     },
     /// Dispatches a job queue.
     DispatchJobQueue { scope_url: ServoUrl },
+    /// Handles input that script synthesized for testing as if it came from the compositor.
+    SendEvent(PipelineId, CompositorEvent),
 }
 
 impl OpaqueSender<CommonScriptMsg> for Box<ScriptChan + Send> {
@@ -394,6 +401,11 @@ impl<'a> Iterator for DocumentsIter<'a> {
 type IncompleteParserContexts = Vec<(PipelineId, ParserContext)>;
 unsafe_no_jsmanaged_fields!(RefCell<IncompleteParserContexts>);
 
+// Tasks held back while their document is frozen in the back-forward cache.
+// These only contain boxed tasks, which are never traced.
+type FrozenTasks = HashMap<PipelineId, Vec<CommonScriptMsg>>;
+unsafe_no_jsmanaged_fields!(RefCell<FrozenTasks>);
+
 #[derive(JSTraceable)]
 // ScriptThread instances are rooted on creation, so this is okay
 #[allow(unrooted_must_root)]
@@ -407,6 +419,8 @@ pub struct ScriptThread {
     incomplete_loads: DomRefCell<Vec<InProgressLoad>>,
     /// A vector containing parser contexts which have not yet been fully processed
     incomplete_parser_contexts: RefCell<IncompleteParserContexts>,
+    /// Tasks queued for documents in the back-forward cache, run once they are reactivated
+    frozen_tasks: RefCell<FrozenTasks>,
     /// A map to store service worker registrations for a given origin
     registration_map: DomRefCell<HashMap<ServoUrl, Dom<ServiceWorkerRegistration>>>,
     /// A job queue for Service Workers keyed by their scope url
@@ -469,6 +483,8 @@ pub struct ScriptThread {
     /// no such server exists.
     devtools_port: Receiver<DevtoolScriptControlMsg>,
     devtools_sender: IpcSender<DevtoolScriptControlMsg>,
+    /// The JavaScript debugger, created when devtools first attach to one of our pipelines.
+    debugger: DomRefCell<Option<ScriptDebugger>>,
 
     /// The JavaScript runtime.
     js_runtime: Rc<Runtime>,
@@ -706,6 +722,15 @@ impl ScriptThread {
         });
     }
 
+    /// Report that the debugger paused the scripts of the given pipeline, and handle devtools
+    /// messages until they resume it. Returns how to step from the pause, if at all.
+    pub fn debugger_paused(pipeline_id: PipelineId, info: PauseInfo) -> Option<StepKind> {
+        SCRIPT_THREAD_ROOT.with(|root| {
+            let script_thread = unsafe { &*root.get().unwrap() };
+            script_thread.handle_debugger_pause(pipeline_id, info)
+        })
+    }
+
     pub fn find_document(id: PipelineId) -> Option<DomRoot<Document>> {
         SCRIPT_THREAD_ROOT.with(|root| root.get().and_then(|script_thread| {
             let script_thread = unsafe { &*script_thread };
@@ -835,6 +860,7 @@ impl ScriptThread {
             window_proxies: DomRefCell::new(HashMap::new()),
             incomplete_loads: DomRefCell::new(vec!()),
             incomplete_parser_contexts: RefCell::new(vec!()),
+            frozen_tasks: RefCell::new(HashMap::new()),
             registration_map: DomRefCell::new(HashMap::new()),
             job_queue_map: Rc::new(JobQueue::new()),
 
@@ -865,6 +891,7 @@ impl ScriptThread {
 
             devtools_chan: state.devtools_chan,
             devtools_port: devtools_port,
+            debugger: DomRefCell::new(None),
             devtools_sender: ipc_devtools_sender,
 
             js_runtime: Rc::new(runtime),
@@ -1152,6 +1179,7 @@ impl ScriptThread {
                     MainThreadScriptMsg::RegisterPaintWorklet { .. } => {
                         ScriptThreadEventCategory::WorkletEvent
                     },
+                    MainThreadScriptMsg::SendEvent(_, _) => ScriptThreadEventCategory::DomEvent,
                     _ => ScriptThreadEventCategory::ScriptEvent,
                 }
             },
@@ -1189,10 +1217,12 @@ impl ScriptThread {
                     // FIXME https://github.com/servo/servo/issues/15079
                     TransitionEnd(..) => None,
                     WebFontLoaded(id) => Some(id),
+                    FontFaceRuleLoad(id, ..) => Some(id),
                     DispatchIFrameLoadEvent { target: _, parent: id, child: _ } => Some(id),
                     DispatchStorageEvent(id, ..) => Some(id),
                     ReportCSSError(id, ..) => Some(id),
                     Reload(id, ..) => Some(id),
+                    Print(id, ..) => Some(id),
                     WebVREvents(id, ..) => Some(id),
                     PaintMetric(..) => None,
                 }
@@ -1207,6 +1237,7 @@ impl ScriptThread {
                     MainThreadScriptMsg::WorkletLoaded(pipeline_id) => Some(pipeline_id),
                     MainThreadScriptMsg::RegisterPaintWorklet { pipeline_id, .. } => Some(pipeline_id),
                     MainThreadScriptMsg::DispatchJobQueue { .. }  => None,
+                    MainThreadScriptMsg::SendEvent(pipeline_id, _) => Some(pipeline_id),
                 }
             },
             MixedMessage::FromImageCache((pipeline_id, _)) => Some(pipeline_id),
@@ -1324,6 +1355,8 @@ impl ScriptThread {
                 self.handle_transition_event(unsafe_node, name, duration),
             ConstellationControlMsg::WebFontLoaded(pipeline_id) =>
                 self.handle_web_font_loaded(pipeline_id),
+            ConstellationControlMsg::FontFaceRuleLoad(pipeline_id, rule_id, event) =>
+                self.handle_font_face_rule_load(pipeline_id, rule_id, event),
             ConstellationControlMsg::DispatchIFrameLoadEvent {
                 target: browsing_context_id, parent: parent_id, child: child_id } =>
                 self.handle_iframe_load_event(parent_id, browsing_context_id, child_id),
@@ -1333,6 +1366,8 @@ impl ScriptThread {
                 self.handle_css_error_reporting(pipeline_id, filename, line, column, msg),
             ConstellationControlMsg::Reload(pipeline_id) =>
                 self.handle_reload(pipeline_id),
+            ConstellationControlMsg::Print(pipeline_id, page_size, response_chan) =>
+                self.handle_print(pipeline_id, page_size, response_chan),
             ConstellationControlMsg::ExitPipeline(pipeline_id, discard_browsing_context) =>
                 self.handle_exit_pipeline_msg(pipeline_id, discard_browsing_context),
             ConstellationControlMsg::WebVREvents(pipeline_id, events) =>
@@ -1353,6 +1388,14 @@ impl ScriptThread {
             MainThreadScriptMsg::Navigate(parent_pipeline_id, load_data, replace) => {
                 self.handle_navigate(parent_pipeline_id, None, load_data, replace)
             },
+            MainThreadScriptMsg::Common(CommonScriptMsg::Task(category, task, Some(pipeline_id)))
+                if self.is_frozen(pipeline_id) =>
+            {
+                self.frozen_tasks.borrow_mut()
+                    .entry(pipeline_id)
+                    .or_insert_with(Vec::new)
+                    .push(CommonScriptMsg::Task(category, task, Some(pipeline_id)));
+            },
             MainThreadScriptMsg::Common(CommonScriptMsg::Task(_, task, _)) => {
                 task.run_box()
             }
@@ -1378,6 +1421,9 @@ impl ScriptThread {
             MainThreadScriptMsg::DispatchJobQueue { scope_url } => {
                 self.job_queue_map.run_job(scope_url, self)
             }
+            MainThreadScriptMsg::SendEvent(pipeline_id, event) => {
+                self.handle_event(pipeline_id, event)
+            }
         }
     }
 
@@ -1433,7 +1479,107 @@ impl ScriptThread {
                 devtools::handle_request_animation_frame(&*documents, id, name),
             DevtoolScriptControlMsg::Reload(id) =>
                 devtools::handle_reload(&*documents, id),
+            DevtoolScriptControlMsg::GetStyleSheets(id, reply) =>
+                devtools::handle_get_style_sheets(&*documents, id, reply),
+            DevtoolScriptControlMsg::GetStyleSheetText(id, index, reply) =>
+                devtools::handle_get_style_sheet_text(&*documents, id, index, reply),
+            DevtoolScriptControlMsg::UpdateStyleSheet(id, index, text, reply) =>
+                devtools::handle_update_style_sheet(&*documents, id, index, text, reply),
+            DevtoolScriptControlMsg::GetAppliedRules(id, node_id, reply) =>
+                devtools::handle_get_applied_rules(&*documents, id, node_id, reply),
+            DevtoolScriptControlMsg::ModifyRule(id, rule, modifications, reply) =>
+                devtools::handle_modify_rule(&*documents, id, rule, modifications, reply),
+            DevtoolScriptControlMsg::SetRuleText(id, rule, text, reply) =>
+                devtools::handle_set_rule_text(&*documents, id, rule, text, reply),
+            DevtoolScriptControlMsg::GetStorageHost(id, reply) =>
+                devtools::handle_get_storage_host(&*documents, id, reply),
+            DevtoolScriptControlMsg::GetStoreEntries(id, store, reply) =>
+                devtools::handle_get_store_entries(&*documents, id, store, reply),
+            DevtoolScriptControlMsg::SetStoreEntry(id, store, entry) =>
+                devtools::handle_set_store_entry(&*documents, id, store, entry),
+            DevtoolScriptControlMsg::RemoveStoreEntry(id, store, name) =>
+                devtools::handle_remove_store_entry(&*documents, id, store, name),
+            DevtoolScriptControlMsg::ClearStore(id, store) =>
+                devtools::handle_clear_store(&*documents, id, store),
+            DevtoolScriptControlMsg::AttachDebugger(id) => {
+                let window = match documents.find_window(id) {
+                    Some(window) => window,
+                    None => return warn!("Message sent to closed pipeline {}.", id),
+                };
+                if self.debugger.borrow().is_none() {
+                    *self.debugger.borrow_mut() = Some(ScriptDebugger::new(self.get_cx()));
+                }
+                self.debugger.borrow().as_ref().unwrap().attach(window.upcast(), id);
+            },
+            DevtoolScriptControlMsg::DetachDebugger(id) => {
+                self.with_debugger(|debugger, cx| debugger.detach(cx, id));
+            },
+            DevtoolScriptControlMsg::GetSources(id, reply) => {
+                let sources = self.with_debugger(|debugger, cx| debugger.sources(cx, id));
+                reply.send(sources.unwrap_or_default()).unwrap();
+            },
+            DevtoolScriptControlMsg::GetSourceText(id, source, reply) => {
+                let text = self.with_debugger(|debugger, cx| debugger.source_text(cx, id, source));
+                reply.send(text.and_then(|text| text)).unwrap();
+            },
+            DevtoolScriptControlMsg::SetBreakpoint(id, location, reply) => {
+                let breakpoint =
+                    self.with_debugger(|debugger, cx| debugger.set_breakpoint(cx, id, location));
+                reply.send(breakpoint.and_then(|breakpoint| breakpoint)).unwrap();
+            },
+            DevtoolScriptControlMsg::RemoveBreakpoint(id, breakpoint) => {
+                self.with_debugger(|debugger, cx| debugger.remove_breakpoint(cx, id, breakpoint));
+            },
+            DevtoolScriptControlMsg::Interrupt(id) => {
+                self.with_debugger(|debugger, cx| debugger.interrupt(cx, id));
+            },
+            DevtoolScriptControlMsg::GetFrames(id, reply) => {
+                let frames = self.with_debugger(|debugger, cx| debugger.frames(cx, id));
+                reply.send(frames.unwrap_or_default()).unwrap();
+            },
+            DevtoolScriptControlMsg::GetScopes(id, depth, reply) => {
+                let scopes = self.with_debugger(|debugger, cx| debugger.scopes(cx, id, depth));
+                reply.send(scopes.and_then(|scopes| scopes)).unwrap();
+            },
+            DevtoolScriptControlMsg::GetObjectProperties(id, object, reply) => {
+                let properties =
+                    self.with_debugger(|debugger, cx| debugger.object_properties(cx, id, object));
+                reply.send(properties.and_then(|properties| properties)).unwrap();
+            },
+            // Only meaningful while paused, see `handle_debugger_pause`.
+            DevtoolScriptControlMsg::Resume(..) => {},
+        }
+    }
+
+    fn with_debugger<T, F>(&self, f: F) -> Option<T>
+        where F: FnOnce(&ScriptDebugger, *mut JSContext) -> T
+    {
+        self.debugger.borrow().as_ref().map(|debugger| f(debugger, self.get_cx()))
+    }
+
+    /// Block the event loop while the debugger is paused, so that only devtools messages
+    /// are handled until the client resumes or detaches from the paused pipeline.
+    fn handle_debugger_pause(&self, pipeline_id: PipelineId, info: PauseInfo) -> Option<StepKind> {
+        match self.devtools_chan {
+            Some(ref chan) => {
+                let msg = ScriptToDevtoolsControlMsg::DebuggerPaused(pipeline_id, info);
+                if chan.send(msg).is_err() {
+                    return None;
+                }
+            },
+            None => return None,
+        }
+        while let Ok(msg) = self.devtools_port.recv() {
+            match msg {
+                DevtoolScriptControlMsg::Resume(id, step) if id == pipeline_id => return step,
+                DevtoolScriptControlMsg::DetachDebugger(id) if id == pipeline_id => {
+                    self.handle_msg_from_devtools(msg);
+                    return None;
+                },
+                msg => self.handle_msg_from_devtools(msg),
+            }
         }
+        None
     }
 
     fn handle_msg_from_image_cache(&self, (id, response): (PipelineId, PendingImageResponse)) {
@@ -1648,6 +1794,13 @@ impl ScriptThread {
         let document = self.documents.borrow().find_document(id);
         if let Some(document) = document {
             document.set_activity(activity);
+            if activity == DocumentActivity::FullyActive {
+                // Run the tasks that were held back while the document was frozen.
+                let tasks = self.frozen_tasks.borrow_mut().remove(&id).unwrap_or_default();
+                for task in tasks {
+                    let _ = self.chan.0.send(MainThreadScriptMsg::Common(task));
+                }
+            }
             return;
         }
         let mut loads = self.incomplete_loads.borrow_mut();
@@ -1678,6 +1831,11 @@ impl ScriptThread {
         }
     }
 
+    /// Whether the document of the given pipeline is frozen in the back-forward cache.
+    fn is_frozen(&self, pipeline_id: PipelineId) -> bool {
+        self.documents.borrow().find_document(pipeline_id).map_or(false, |doc| doc.is_frozen())
+    }
+
     fn handle_unload_document(&self, pipeline_id: PipelineId) {
         let document = self.documents.borrow().find_document(pipeline_id);
         if let Some(document) = document {
@@ -1827,6 +1985,7 @@ impl ScriptThread {
         debug!("Exiting pipeline {}.", id);
 
         self.closed_pipelines.borrow_mut().insert(id);
+        self.frozen_tasks.borrow_mut().remove(&id);
 
         // Check if the exit message is for an in progress load.
         let idx = self.incomplete_loads.borrow().iter().position(|load| {
@@ -1963,6 +2122,15 @@ impl ScriptThread {
         }
     }
 
+    /// Updates the `FontFace` of an `@font-face` rule when the font cache starts or finishes
+    /// loading its face.
+    fn handle_font_face_rule_load(&self, pipeline_id: PipelineId, rule_id: usize, event: WebFontLoadEvent) {
+        let document = self.documents.borrow().find_document(pipeline_id);
+        if let Some(document) = document {
+            document.Fonts().handle_font_face_rule_load(rule_id, event);
+        }
+    }
+
     /// Handles a worklet being loaded. Does nothing if the page no longer exists.
     fn handle_worklet_loaded(&self, pipeline_id: PipelineId) {
         let document = self.documents.borrow().find_document(pipeline_id);
@@ -2214,6 +2382,19 @@ impl ScriptThread {
         let parse_input = DOMString::new();
 
         document.set_https_state(metadata.https_state);
+
+        // https://w3c.github.io/webappsec-upgrade-insecure-requests/#delivery
+        let upgrade_insecure_requests = metadata.headers
+            .as_ref()
+            .map(Serde::deref)
+            .and_then(|headers| headers.get_raw("Content-Security-Policy"))
+            .map_or(false, |values| {
+                values.iter().any(|value| policy_upgrades_insecure_requests(&String::from_utf8_lossy(value)))
+            });
+        if upgrade_insecure_requests {
+            document.set_insecure_requests_policy(InsecureRequestsPolicy::Upgrade);
+        }
+
         document.set_navigation_start(incomplete.navigation_start_precise);
 
         if is_html_document == IsHTMLDocument::NonHTMLDocument {
@@ -2338,13 +2519,8 @@ impl ScriptThread {
                     node_address
                 );
                 match (event_type, touch_result) {
-                    (TouchEventType::Down, TouchEventResult::Processed(handled)) => {
-                        let result = if handled {
-                            // TODO: Wait to see if preventDefault is called on the first touchmove event.
-                            EventResult::DefaultAllowed
-                        } else {
-                            EventResult::DefaultPrevented
-                        };
+                    (TouchEventType::Down, TouchEventResult::Processed(result)) => {
+                        // TODO: Wait to see if preventDefault is called on the first touchmove event.
                         let message = ScriptMsg::TouchEventProcessed(result);
                         self.script_sender.send((pipeline_id, message)).unwrap();
                     }
@@ -2354,6 +2530,29 @@ impl ScriptThread {
                 }
             }
 
+            WheelEvent(delta, point, node_address) => {
+                // The compositor waits for a reply before scrolling, even if the document is gone.
+                let prevented = match { self.documents.borrow().find_document(pipeline_id) } {
+                    Some(document) => {
+                        document.handle_wheel_event(self.js_runtime.rt(), delta, point, node_address)
+                    },
+                    None => {
+                        warn!("Message sent to closed pipeline {}.", pipeline_id);
+                        false
+                    },
+                };
+                let message = ScriptMsg::WheelEventProcessed(prevented);
+                self.script_sender.send((pipeline_id, message)).unwrap();
+            }
+
+            DropFilesEvent(files, point, node_address) => {
+                let document = match { self.documents.borrow().find_document(pipeline_id) } {
+                    Some(document) => document,
+                    None => return warn!("Message sent to closed pipeline {}.", pipeline_id),
+                };
+                document.handle_drop_files_event(self.js_runtime.rt(), files, point, node_address);
+            }
+
             KeyEvent(ch, key, state, modifiers) => {
                 let document = match { self.documents.borrow().find_document(pipeline_id) } {
                     Some(document) => document,
@@ -2399,7 +2598,7 @@ impl ScriptThread {
             Some(document) => document,
             None => {
                 warn!("Message sent to closed pipeline {}.", pipeline_id);
-                return TouchEventResult::Processed(true);
+                return TouchEventResult::Processed(EventResult::DefaultAllowed(AllowedTouchActions::all()));
             },
         };
         document.handle_touch_event(
@@ -2520,6 +2719,9 @@ impl ScriptThread {
             body: load_data.data,
             redirect_mode: RedirectMode::Manual,
             origin: incomplete.origin.immutable().clone(),
+            user_initiated: load_data.user_initiated,
+            insecure_requests_policy: load_data.insecure_requests_policy,
+            parent_origin: load_data.parent_origin,
             .. RequestInit::default()
         };
 
@@ -2619,6 +2821,28 @@ impl ScriptThread {
         }
     }
 
+    fn handle_print(&self,
+                    pipeline_id: PipelineId,
+                    page_size: TypedSize2D<f32, CSSPixel>,
+                    response_chan: IpcSender<Vec<PrintedPage>>) {
+        let window = match self.documents.borrow().find_window(pipeline_id) {
+            Some(window) => window,
+            None => return warn!("Print of closed pipeline {}.", pipeline_id),
+        };
+        let page_size = Size2D::new(Au::from_f32_px(page_size.width), Au::from_f32_px(page_size.height));
+        let pages = window.pages_query(page_size).into_iter().map(|page| {
+            let (display_list_data, display_list_descriptor) = page.display_list.into_data();
+            PrintedPage {
+                size: TypedSize2D::new(page.size.width.to_f32_px(), page.size.height.to_f32_px()),
+                display_list_data: display_list_data,
+                display_list_descriptor: display_list_descriptor,
+            }
+        }).collect();
+        if let Err(e) = response_chan.send(pages) {
+            warn!("Sending printed pages failed ({}).", e);
+        }
+    }
+
     fn handle_webvr_events(&self, pipeline_id: PipelineId, events: Vec<WebVREvent>) {
         let window = self.documents.borrow().find_window(pipeline_id);
         if let Some(window) = window {
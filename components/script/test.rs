@@ -62,3 +62,14 @@ pub mod size_of {
 pub mod srcset {
     pub use dom::htmlimageelement::{parse_a_srcset_attribute, ImageSource, Descriptor};
 }
+
+pub mod cssstylesheet {
+    use dom::cssstylesheet;
+    use servo_url::ServoUrl;
+    use style::context::QuirksMode;
+
+    pub fn parses_without_errors(css: &str) -> bool {
+        let url = ServoUrl::parse("http://example.com/style.css").unwrap();
+        cssstylesheet::parses_without_errors(css, url, QuirksMode::NoQuirks, None)
+    }
+}
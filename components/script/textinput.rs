@@ -119,6 +119,14 @@ impl Default for TextPoint {
     }
 }
 
+/// The default action of a clipboard event fired at a text input.
+pub enum ClipboardAction {
+    Copy,
+    Cut,
+    /// Paste the given text from the clipboard.
+    Paste(DOMString),
+}
+
 /// Control whether this control should allow multiple lines.
 #[derive(Eq, PartialEq)]
 pub enum Lines {
@@ -137,12 +145,12 @@ pub enum Direction {
 /// Was the keyboard event accompanied by the standard control modifier,
 /// i.e. cmd on Mac OS or ctrl on other platforms.
 #[cfg(target_os = "macos")]
-fn is_control_key(mods: KeyModifiers) -> bool {
+pub fn is_control_key(mods: KeyModifiers) -> bool {
     mods.contains(KeyModifiers::SUPER) && !mods.contains(KeyModifiers::CONTROL | KeyModifiers::ALT)
 }
 
 #[cfg(not(target_os = "macos"))]
-fn is_control_key(mods: KeyModifiers) -> bool {
+pub fn is_control_key(mods: KeyModifiers) -> bool {
     mods.contains(KeyModifiers::CONTROL) && !mods.contains(KeyModifiers::SUPER | KeyModifiers::ALT)
 }
 
@@ -678,6 +686,33 @@ impl<T: ClipboardProvider> TextInput<T> {
         }
     }
 
+    /// Perform the default action of a `copy`, `cut` or `paste` event and return an action for
+    /// the caller to execute.
+    pub fn handle_clipboard_action(&mut self, action: ClipboardAction) -> KeyReaction {
+        match action {
+            ClipboardAction::Copy => {
+                if let Some(text) = self.get_selection_text() {
+                    self.clipboard_provider.set_clipboard_contents(text);
+                }
+                KeyReaction::Nothing
+            },
+            ClipboardAction::Cut => {
+                match self.get_selection_text() {
+                    Some(text) => {
+                        self.clipboard_provider.set_clipboard_contents(text);
+                        self.replace_selection(DOMString::new());
+                        KeyReaction::DispatchInput
+                    },
+                    None => KeyReaction::Nothing,
+                }
+            },
+            ClipboardAction::Paste(text) => {
+                self.insert_string(text);
+                KeyReaction::DispatchInput
+            },
+        }
+    }
+
     /// Process a given `KeyboardEvent` and return an action for the caller to execute.
     pub fn handle_keydown(&mut self, event: &KeyboardEvent) -> KeyReaction {
         if let Some(key) = event.get_key() {
@@ -728,16 +763,9 @@ impl<T: ClipboardProvider> TextInput<T> {
                 self.select_all();
                 KeyReaction::RedrawSelection
             },
-            (_, Key::C) if is_control_key(mods) => {
-                if let Some(text) = self.get_selection_text() {
-                    self.clipboard_provider.set_clipboard_contents(text);
-                }
-                KeyReaction::DispatchInput
-            },
-            (_, Key::V) if is_control_key(mods) => {
-                let contents = self.clipboard_provider.clipboard_contents();
-                self.insert_string(contents);
-                KeyReaction::DispatchInput
+            (_, Key::C) | (_, Key::X) | (_, Key::V) if is_control_key(mods) => {
+                // The document fires a clipboard event instead, see `handle_clipboard_action`.
+                KeyReaction::Nothing
             },
             (Some(c), _) => {
                 self.insert_char(c);
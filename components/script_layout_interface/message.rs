@@ -4,7 +4,7 @@
 
 use {OpaqueStyleAndLayoutData, PendingImage, TrustedNodeAddress};
 use app_units::Au;
-use euclid::{Point2D, Rect};
+use euclid::{Point2D, Rect, Size2D};
 use gfx_traits::Epoch;
 use ipc_channel::ipc::{IpcReceiver, IpcSender};
 use metrics::PaintTimeMetrics;
@@ -21,9 +21,11 @@ use servo_url::ServoUrl;
 use std::sync::Arc;
 use std::sync::mpsc::{Receiver, Sender};
 use style::context::QuirksMode;
+use style::font_face::{FontFaceDescriptors, ScriptFontFaceSource};
 use style::properties::PropertyId;
 use style::selector_parser::PseudoElement;
 use style::stylesheets::Stylesheet;
+use style::values::computed::font::FamilyName;
 
 /// Asynchronous messages that script can send to layout.
 pub enum Msg {
@@ -98,6 +100,22 @@ pub enum Msg {
 
     /// Send to layout the precise time when the navigation started.
     SetNavigationStart(u64),
+
+    /// Starts loading the font face created by script with the given identifier, and sends
+    /// whether it could be loaded on the given channel.
+    LoadFontFace(usize, FamilyName, ScriptFontFaceSource, FontFaceDescriptors, IpcSender<bool>),
+
+    /// Tells layout whether the font face created by script with the given identifier belongs to
+    /// the fonts of the document, and so can be used to render it once loaded.
+    SetFontFaceUsed(usize, bool),
+
+    /// Starts loading the font face of the `@font-face` rule with the given identifier if it is
+    /// only loaded once some text needs it.
+    LoadFontFaceRule(usize),
+
+    /// Tells layout that the font face created by script with the given identifier was collected,
+    /// so the font cache can forget it.
+    RemoveFontFace(usize),
 }
 
 #[derive(Debug, PartialEq)]
@@ -119,6 +137,8 @@ pub enum QueryMsg {
     TextIndexQuery(TrustedNodeAddress, Point2D<f32>),
     NodesFromPointQuery(Point2D<f32>, NodesFromPointQueryType),
     ElementInnerTextQuery(TrustedNodeAddress),
+    /// Lays the document out on pages of the given size, for printing.
+    PagesQuery(Size2D<Au>),
 }
 
 /// Any query to perform with this reflow.
@@ -139,6 +159,7 @@ impl ReflowGoal {
                 &QueryMsg::NodesFromPointQuery(..) |
                 &QueryMsg::TextIndexQuery(..) |
                 &QueryMsg::ElementInnerTextQuery(_) => true,
+                &QueryMsg::PagesQuery(_) |
                 &QueryMsg::ContentBoxQuery(_) |
                 &QueryMsg::ContentBoxesQuery(_) |
                 &QueryMsg::NodeGeometryQuery(_) |
@@ -160,6 +181,7 @@ impl ReflowGoal {
                 &QueryMsg::NodesFromPointQuery(..) |
                 &QueryMsg::TextIndexQuery(..) |
                 &QueryMsg::ElementInnerTextQuery(_) => true,
+                &QueryMsg::PagesQuery(_) |
                 &QueryMsg::ContentBoxQuery(_) |
                 &QueryMsg::ContentBoxesQuery(_) |
                 &QueryMsg::NodeGeometryQuery(_) |
@@ -3,12 +3,12 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
 use app_units::Au;
-use euclid::{Point2D, Rect};
+use euclid::{Point2D, Rect, Size2D};
 use script_traits::UntrustedNodeAddress;
 use servo_arc::Arc;
 use style::properties::ComputedValues;
 use style::properties::longhands::overflow_x;
-use webrender_api::ExternalScrollId;
+use webrender_api::{BuiltDisplayList, ExternalScrollId};
 
 /// Synchronous messages that script can send to layout.
 ///
@@ -40,6 +40,8 @@ pub trait LayoutRPC {
     fn nodes_from_point_response(&self) -> Vec<UntrustedNodeAddress>;
     /// Query layout to get the inner text for a given element.
     fn element_inner_text(&self) -> String;
+    /// Requests the pages of the document, as laid out for printing.
+    fn pages(&self) -> Vec<LayoutPage>;
 }
 
 pub struct ContentBoxResponse(pub Option<Rect<Au>>);
@@ -76,3 +78,12 @@ pub struct StyleResponse(pub Option<Arc<ComputedValues>>);
 
 #[derive(Clone)]
 pub struct TextIndexResponse(pub Option<usize>);
+
+/// One page of a paginated layout.
+#[derive(Clone)]
+pub struct LayoutPage {
+    /// The size of the page box.
+    pub size: Size2D<Au>,
+    /// The content of the page, ready to be rendered.
+    pub display_list: BuiltDisplayList,
+}
@@ -52,9 +52,11 @@ use libc::c_void;
 use msg::constellation_msg::{BrowsingContextId, HistoryStateId, Key, KeyModifiers, KeyState, PipelineId};
 use msg::constellation_msg::{PipelineNamespaceId, TraversalDirection, TopLevelBrowsingContextId};
 use net_traits::{FetchResponseMsg, ReferrerPolicy, ResourceThreads};
+use net_traits::filemanager_thread::SelectedFile;
 use net_traits::image::base::Image;
 use net_traits::image::base::PixelFormat;
 use net_traits::image_cache::ImageCache;
+use net_traits::request::InsecureRequestsPolicy;
 use net_traits::storage_thread::StorageType;
 use profile_traits::mem;
 use profile_traits::time as profile_time;
@@ -64,16 +66,17 @@ use servo_url::ImmutableOrigin;
 use servo_url::ServoUrl;
 use std::collections::HashMap;
 use std::fmt;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::mpsc::{Receiver, Sender, RecvTimeoutError};
 use style_traits::CSSPixel;
 use style_traits::SpeculativePainter;
 use style_traits::cursor::CursorKind;
 use webdriver_msg::{LoadStatus, WebDriverScriptCommand};
-use webrender_api::{ExternalScrollId, DevicePixel, DeviceUintSize, DocumentId, ImageKey};
+use webrender_api::{BuiltDisplayListDescriptor, DevicePixel, DeviceUintSize, DocumentId, ExternalScrollId, ImageKey};
 use webvr_traits::{WebVREvent, WebVRMsg};
 
-pub use script_msg::{LayoutMsg, ScriptMsg, EventResult, LogEntry};
+pub use script_msg::{AllowedTouchActions, LayoutMsg, ScriptMsg, EventResult, LogEntry};
 pub use script_msg::{ServiceWorkerMsg, ScopeThings, SWManagerMsg, SWManagerSenders, DOMMessage};
 
 /// The address of a node. Layout sends these back. They must be validated via
@@ -123,6 +126,9 @@ pub enum LayoutControlMsg {
     GetWebFontLoadState(IpcSender<bool>),
     /// Send the paint time for a specific epoch to the layout thread.
     PaintMetric(Epoch, u64),
+    /// Tells layout whether the user prefers reduced motion, which keeps animated images on
+    /// their first frame.
+    SetPrefersReducedMotion(bool),
 }
 
 /// can be passed to `LoadUrl` to load a page with GET/POST
@@ -149,6 +155,12 @@ pub struct LoadData {
     pub referrer_policy: Option<ReferrerPolicy>,
     /// The referrer URL.
     pub referrer_url: Option<ServoUrl>,
+    /// Whether the load was explicitly requested by the user through the embedder.
+    pub user_initiated: bool,
+    /// The insecure requests policy of the parent document, if the load is in an iframe.
+    pub insecure_requests_policy: InsecureRequestsPolicy,
+    /// The origin of the parent document, if the load is in an iframe.
+    pub parent_origin: Option<ImmutableOrigin>,
 }
 
 /// The result of evaluating a javascript scheme url.
@@ -177,6 +189,9 @@ impl LoadData {
             js_eval_result: None,
             referrer_policy: referrer_policy,
             referrer_url: referrer_url,
+            user_initiated: false,
+            insecure_requests_policy: InsecureRequestsPolicy::DoNotUpgrade,
+            parent_origin: None,
         }
     }
 }
@@ -251,6 +266,16 @@ pub enum UpdatePipelineIdReason {
     Traversal,
 }
 
+/// A change in the loading of the font face of an `@font-face` rule, which layout tells script
+/// about for the `FontFace` objects of the document.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum WebFontLoadEvent {
+    /// The face started loading.
+    Started,
+    /// The face finished loading, and can be used if true.
+    Finished(bool),
+}
+
 /// Messages sent from the constellation or layout to the script thread.
 #[derive(Deserialize, Serialize)]
 pub enum ConstellationControlMsg {
@@ -308,6 +333,9 @@ pub enum ConstellationControlMsg {
     /// Notifies the script thread that a new Web font has been loaded, and thus the page should be
     /// reflowed.
     WebFontLoaded(PipelineId),
+    /// Notifies the script thread of the loading of the font face of the `@font-face` rule with
+    /// the given identifier.
+    FontFaceRuleLoad(PipelineId, usize, WebFontLoadEvent),
     /// Cause a `load` event to be dispatched at the appropriate iframe element.
     DispatchIFrameLoadEvent {
         /// The frame that has been marked as loaded.
@@ -324,6 +352,8 @@ pub enum ConstellationControlMsg {
     ReportCSSError(PipelineId, String, u32, u32, String),
     /// Reload the given page.
     Reload(PipelineId),
+    /// Lay the given page out on pages of the given size, and send them back.
+    Print(PipelineId, TypedSize2D<f32, CSSPixel>, IpcSender<Vec<PrintedPage>>),
     /// Notifies the script thread of WebVR events.
     WebVREvents(PipelineId, Vec<WebVREvent>),
     /// Notifies the script thread about a new recorded paint metric.
@@ -358,10 +388,12 @@ impl fmt::Debug for ConstellationControlMsg {
             TickAllAnimations(..) => "TickAllAnimations",
             TransitionEnd(..) => "TransitionEnd",
             WebFontLoaded(..) => "WebFontLoaded",
+            FontFaceRuleLoad(..) => "FontFaceRuleLoad",
             DispatchIFrameLoadEvent { .. } => "DispatchIFrameLoadEvent",
             DispatchStorageEvent(..) => "DispatchStorageEvent",
             ReportCSSError(..) => "ReportCSSError",
             Reload(..) => "Reload",
+            Print(..) => "Print",
             WebVREvents(..) => "WebVREvents",
             PaintMetric(..) => "PaintMetric",
         };
@@ -433,6 +465,31 @@ pub enum MouseEventType {
     MouseUp,
 }
 
+/// The unit of the deltas of a wheel event.
+#[derive(Clone, Copy, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub enum WheelMode {
+    /// The deltas are in CSS pixels.
+    DeltaPixel,
+    /// The deltas are in lines.
+    DeltaLine,
+    /// The deltas are in pages.
+    DeltaPage,
+}
+
+/// The amount that the mouse wheel was rotated by, along each axis. Positive deltas scroll
+/// towards the bottom right of the page.
+#[derive(Clone, Copy, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub struct WheelDelta {
+    /// The horizontal delta.
+    pub x: f64,
+    /// The vertical delta.
+    pub y: f64,
+    /// The delta along the z-axis.
+    pub z: f64,
+    /// The unit of the deltas.
+    pub mode: WheelMode,
+}
+
 /// Events from the compositor that the script thread needs to know about
 #[derive(Deserialize, Serialize)]
 pub enum CompositorEvent {
@@ -450,6 +507,11 @@ pub enum CompositorEvent {
     MouseMoveEvent(Option<Point2D<f32>>, Option<UntrustedNodeAddress>),
     /// A touch event was generated with a touch ID and location.
     TouchEvent(TouchEventType, TouchId, Point2D<f32>, Option<UntrustedNodeAddress>),
+    /// The mouse wheel was rotated over a point. The script thread replies with a
+    /// `WheelEventProcessed` message before the compositor scrolls.
+    WheelEvent(WheelDelta, Point2D<f32>, Option<UntrustedNodeAddress>),
+    /// Files were dropped from outside of the window onto a point.
+    DropFilesEvent(Vec<SelectedFile>, Point2D<f32>, Option<UntrustedNodeAddress>),
     /// A key was pressed.
     KeyEvent(Option<char>, Key, KeyState, KeyModifiers),
 }
@@ -625,6 +687,36 @@ pub struct ScrollState {
     pub scroll_offset: Vector2D<f32>,
 }
 
+/// How strictly a scroll container comes to rest on its snap positions along an axis.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum ScrollSnapStrictness {
+    /// The scroll container always comes to rest on a snap position.
+    Mandatory,
+    /// The scroll container only comes to rest on a snap position close to where it would
+    /// otherwise stop.
+    Proximity,
+}
+
+/// The snap positions of a scroll container along an axis.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ScrollSnapAxis {
+    /// How strictly the scroll container snaps along this axis.
+    pub strictness: ScrollSnapStrictness,
+    /// The scroll positions to snap to, in CSS pixels.
+    pub positions: Vec<f32>,
+}
+
+/// The CSS scroll snap points of a scroll container, as computed by layout.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ScrollSnapInfo {
+    /// The ID of the scroll root.
+    pub scroll_id: ExternalScrollId,
+    /// The horizontal snap positions, if the scroll container snaps horizontally.
+    pub x: Option<ScrollSnapAxis>,
+    /// The vertical snap positions, if the scroll container snaps vertically.
+    pub y: Option<ScrollSnapAxis>,
+}
+
 /// Data about the window size.
 #[derive(Clone, Copy, Deserialize, MallocSizeOf, Serialize)]
 pub struct WindowSizeData {
@@ -636,6 +728,17 @@ pub struct WindowSizeData {
     pub device_pixel_ratio: TypedScale<f32, CSSPixel, DevicePixel>,
 }
 
+/// A page of a document laid out for printing.
+#[derive(Deserialize, Serialize)]
+pub struct PrintedPage {
+    /// The size of the page box.
+    pub size: TypedSize2D<f32, CSSPixel>,
+    /// The data of the display list of the page, see `BuiltDisplayList::from_data`.
+    pub display_list_data: Vec<u8>,
+    /// The descriptor of the display list of the page.
+    pub display_list_descriptor: BuiltDisplayListDescriptor,
+}
+
 /// The type of window size change.
 #[derive(Clone, Copy, Deserialize, Eq, MallocSizeOf, PartialEq, Serialize)]
 pub enum WindowSizeType {
@@ -695,6 +798,8 @@ pub enum ConstellationMsg {
     WebDriverCommand(WebDriverCommandMsg),
     /// Reload a top-level browsing context.
     Reload(TopLevelBrowsingContextId),
+    /// Lay a top-level browsing context out on pages of the given size, for printing.
+    Print(TopLevelBrowsingContextId, TypedSize2D<f32, CSSPixel>, IpcSender<Vec<PrintedPage>>),
     /// A log entry, with the top-level browsing context id and thread name
     LogEntry(Option<TopLevelBrowsingContextId>, Option<String>, LogEntry),
     /// Dispatch WebVR events to the subscribed script threads.
@@ -709,8 +814,17 @@ pub enum ConstellationMsg {
     SelectBrowser(TopLevelBrowsingContextId),
     /// Forward an event to the script task of the given pipeline.
     ForwardEvent(PipelineId, CompositorEvent),
+    /// Register files that were dropped onto a point of the given pipeline with the file
+    /// manager, and forward a `DropFilesEvent` for them to its script task.
+    DropFiles(PipelineId, Vec<PathBuf>, Point2D<f32>, Option<UntrustedNodeAddress>),
     /// Requesting a change to the onscreen cursor.
     SetCursor(CursorKind),
+    /// Act as if a mouse button changed state at the given point, in CSS pixels.
+    MouseEvent(MouseEventType, MouseButton, Point2D<f32>),
+    /// Act as if the mouse moved to the given point, in CSS pixels.
+    MouseMoveEvent(Point2D<f32>),
+    /// Inform the constellation of whether the user prefers reduced motion.
+    SetPrefersReducedMotion(bool),
 }
 
 impl fmt::Debug for ConstellationMsg {
@@ -729,6 +843,7 @@ impl fmt::Debug for ConstellationMsg {
             TickAnimation(..) => "TickAnimation",
             WebDriverCommand(..) => "WebDriverCommand",
             Reload(..) => "Reload",
+            Print(..) => "Print",
             LogEntry(..) => "LogEntry",
             WebVREvents(..) => "WebVREvents",
             NewBrowser(..) => "NewBrowser",
@@ -736,7 +851,11 @@ impl fmt::Debug for ConstellationMsg {
             SendError(..) => "SendError",
             SelectBrowser(..) => "SelectBrowser",
             ForwardEvent(..) => "ForwardEvent",
+            DropFiles(..) => "DropFiles",
             SetCursor(..) => "SetCursor",
+            MouseEvent(..) => "MouseEvent",
+            MouseMoveEvent(..) => "MouseMoveEvent",
+            SetPrefersReducedMotion(..) => "SetPrefersReducedMotion",
         };
         write!(formatter, "ConstellationMsg::{}", variant)
     }
@@ -765,6 +884,8 @@ pub struct WorkerGlobalScopeInit {
     pub pipeline_id: PipelineId,
     /// The origin
     pub origin: ImmutableOrigin,
+    /// The insecure requests policy of the creator of the worker
+    pub insecure_requests_policy: InsecureRequestsPolicy,
 }
 
 /// Common entities representing a network load origin
@@ -8,6 +8,8 @@ use IFrameLoadInfo;
 use IFrameLoadInfoWithData;
 use LayoutControlMsg;
 use LoadData;
+use ScrollSnapInfo;
+use ScrollState;
 use WorkerGlobalScopeInit;
 use WorkerScriptLoadOrigin;
 use canvas_traits::canvas::{CanvasMsg, CanvasId};
@@ -42,6 +44,9 @@ pub enum LayoutMsg {
     SetCursor(CursorKind),
     /// Notifies the constellation that the viewport has been constrained in some manner
     ViewportConstrained(PipelineId, ViewportConstraints),
+    /// Requests that the constellation inform the compositor of the scroll snap points of the
+    /// scroll containers of a pipeline.
+    ScrollSnapInfo(PipelineId, Vec<ScrollSnapInfo>),
 }
 
 impl fmt::Debug for LayoutMsg {
@@ -53,6 +58,7 @@ impl fmt::Debug for LayoutMsg {
             PendingPaintMetric(..) => "PendingPaintMetric",
             SetCursor(..) => "SetCursor",
             ViewportConstrained(..) => "ViewportConstrained",
+            ScrollSnapInfo(..) => "ScrollSnapInfo",
         };
         write!(formatter, "LayoutMsg::{}", variant)
     }
@@ -61,12 +67,45 @@ impl fmt::Debug for LayoutMsg {
 /// Whether a DOM event was prevented by web content
 #[derive(Deserialize, Serialize)]
 pub enum EventResult {
-    /// Allowed by web content
-    DefaultAllowed,
+    /// Allowed by web content, with the default touch behaviors that the `touch-action` of the
+    /// event target allows
+    DefaultAllowed(AllowedTouchActions),
     /// Prevented by web content
     DefaultPrevented,
 }
 
+/// The default touch behaviors that the `touch-action` of the target of a touch sequence allows.
+/// <https://w3c.github.io/pointerevents/#determining-supported-touch-behavior>
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct AllowedTouchActions {
+    /// Whether the touch sequence may pan horizontally.
+    pub pan_x: bool,
+    /// Whether the touch sequence may pan vertically.
+    pub pan_y: bool,
+    /// Whether the touch sequence may zoom.
+    pub zoom: bool,
+}
+
+impl AllowedTouchActions {
+    /// All default touch behaviors, as allowed by `touch-action: auto`.
+    pub fn all() -> AllowedTouchActions {
+        AllowedTouchActions {
+            pan_x: true,
+            pan_y: true,
+            zoom: true,
+        }
+    }
+
+    /// Returns the touch behaviors that both `self` and `other` allow.
+    pub fn intersection(&self, other: &AllowedTouchActions) -> AllowedTouchActions {
+        AllowedTouchActions {
+            pan_x: self.pan_x && other.pan_x,
+            pan_y: self.pan_y && other.pan_y,
+            zoom: self.zoom && other.zoom,
+        }
+    }
+}
+
 /// A log entry reported to the constellation
 /// We don't report all log entries, just serious ones.
 /// We need a separate type for this because `LogLevel` isn't serializable.
@@ -98,8 +137,6 @@ pub enum ScriptMsg {
     CreateCanvasPaintThread(Size2D<i32>, IpcSender<(IpcSender<CanvasMsg>, CanvasId)>),
     /// Notifies the constellation that this frame has received focus.
     Focus,
-    /// Requests that the constellation retrieve the current contents of the clipboard
-    GetClipboardContents(IpcSender<String>),
     /// Get the browsing context id for a given pipeline.
     GetBrowsingContextId(PipelineId, IpcSender<Option<BrowsingContextId>>),
     /// Get the parent info for a given pipeline.
@@ -137,8 +174,6 @@ pub enum ScriptMsg {
     ScriptLoadedURLInIFrame(IFrameLoadInfoWithData),
     /// A load of the initial `about:blank` has been completed in an IFrame.
     ScriptNewIFrame(IFrameLoadInfo, IpcSender<LayoutControlMsg>),
-    /// Requests that the constellation set the contents of the clipboard
-    SetClipboardContents(String),
     /// Mark a new document as active
     ActivateDocument,
     /// Set the document state for a pipeline (used by screenshot / reftests)
@@ -147,10 +182,16 @@ pub enum ScriptMsg {
     SetFinalUrl(ServoUrl),
     /// Script has handled a touch event, and either prevented or allowed default actions.
     TouchEventProcessed(EventResult),
+    /// Script has handled a wheel event. The boolean is whether its default action was prevented.
+    WheelEventProcessed(bool),
+    /// Requests that the compositor scroll a scroll root to the given state in a smooth fashion.
+    SmoothScroll(ScrollState),
     /// A log entry, with the top-level browsing context id and thread name
     LogEntry(Option<String>, LogEntry),
     /// Discard the document.
     DiscardDocument,
+    /// The document was frozen in the back-forward cache, using an estimated number of bytes.
+    FrozenDocumentSize(usize),
     /// Discard the browsing context.
     DiscardTopLevelBrowsingContext,
     /// Notifies the constellation that this pipeline has exited.
@@ -178,7 +219,6 @@ impl fmt::Debug for ScriptMsg {
             ChangeRunningAnimationsState(..) => "ChangeRunningAnimationsState",
             CreateCanvasPaintThread(..) => "CreateCanvasPaintThread",
             Focus => "Focus",
-            GetClipboardContents(..) => "GetClipboardContents",
             GetBrowsingContextId(..) => "GetBrowsingContextId",
             GetParentInfo(..) => "GetParentInfo",
             GetChildBrowsingContextId(..) => "GetChildBrowsingContextId",
@@ -196,13 +236,15 @@ impl fmt::Debug for ScriptMsg {
             VisibilityChangeComplete(..) => "VisibilityChangeComplete",
             ScriptLoadedURLInIFrame(..) => "ScriptLoadedURLInIFrame",
             ScriptNewIFrame(..) => "ScriptNewIFrame",
-            SetClipboardContents(..) => "SetClipboardContents",
             ActivateDocument => "ActivateDocument",
             SetDocumentState(..) => "SetDocumentState",
             SetFinalUrl(..) => "SetFinalUrl",
             TouchEventProcessed(..) => "TouchEventProcessed",
+            WheelEventProcessed(..) => "WheelEventProcessed",
+            SmoothScroll(..) => "SmoothScroll",
             LogEntry(..) => "LogEntry",
             DiscardDocument => "DiscardDocument",
+            FrozenDocumentSize(..) => "FrozenDocumentSize",
             DiscardTopLevelBrowsingContext => "DiscardTopLevelBrowsingContext",
             PipelineExited => "PipelineExited",
             ForwardDOMMessage(..) => "ForwardDOMMessage",
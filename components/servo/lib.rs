@@ -281,6 +281,14 @@ impl<Window> Servo<Window> where Window: WindowMethods + 'static {
                 self.compositor.on_scroll_event(delta, cursor, phase);
             }
 
+            WindowEvent::Wheel(delta, cursor, phase) => {
+                self.compositor.on_wheel_event(delta, cursor, phase);
+            }
+
+            WindowEvent::DropFiles(paths, point) => {
+                self.compositor.on_drop_files_event(paths, point);
+            }
+
             WindowEvent::Zoom(magnification) => {
                 self.compositor.on_zoom_window_event(magnification);
             }
@@ -318,6 +326,20 @@ impl<Window> Servo<Window> where Window: WindowMethods + 'static {
                 }
             }
 
+            WindowEvent::Print(top_level_browsing_context_id, page_size, response_chan) => {
+                let msg = ConstellationMsg::Print(top_level_browsing_context_id, page_size, response_chan);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending print to constellation failed ({}).", e);
+                }
+            }
+
+            WindowEvent::SetPrefersReducedMotion(prefers_reduced_motion) => {
+                let msg = ConstellationMsg::SetPrefersReducedMotion(prefers_reduced_motion);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending reduced motion preference to constellation failed ({}).", e);
+                }
+            }
+
             WindowEvent::ToggleWebRenderDebug(option) => {
                 self.compositor.toggle_webrender_debug(option);
             }
@@ -462,9 +484,15 @@ fn create_constellation(user_agent: Cow<'static, str>,
                         -> (Sender<ConstellationMsg>, SWManagerSenders) {
     let bluetooth_thread: IpcSender<BluetoothRequest> = BluetoothThreadFactory::new(embedder_proxy.clone());
 
+    // The debugger server reports network events too, so they go through it first.
+    let network_devtools_chan = match debugger_chan {
+        Some(ref debugger_chan) => Some(debugger::network_event_chan(debugger_chan, devtools_chan.clone())),
+        None => devtools_chan.clone(),
+    };
+
     let (public_resource_threads, private_resource_threads) =
         new_resource_threads(user_agent,
-                             devtools_chan.clone(),
+                             network_devtools_chan,
                              time_profiler_chan.clone(),
                              mem_profiler_chan.clone(),
                              embedder_proxy.clone(),
@@ -512,6 +540,8 @@ fn create_constellation(user_agent: Cow<'static, str>,
         webgl_threads
     });
 
+    let debugger_sender = debugger_chan.clone();
+
     let initial_state = InitialConstellationState {
         compositor_proxy,
         embedder_proxy,
@@ -539,6 +569,11 @@ fn create_constellation(user_agent: Cow<'static, str>,
         webvr_constellation_sender.send(constellation_chan.clone()).unwrap();
     }
 
+    if let Some(debugger_sender) = debugger_sender {
+        // Set constellation channel used by the debugger server to drive pages
+        debugger::set_constellation_chan(&debugger_sender, constellation_chan.clone());
+    }
+
     // channels to communicate with Service Worker Manager
     let sw_senders = SWManagerSenders {
         swmanager_sender: from_swmanager_sender,
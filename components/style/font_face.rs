@@ -8,7 +8,6 @@
 
 use cssparser::{AtRuleParser, DeclarationListParser, DeclarationParser, Parser};
 use cssparser::{CowRcStr, SourceLocation};
-#[cfg(feature = "gecko")]
 use cssparser::UnicodeRange;
 use error_reporting::ContextualParseError;
 use parser::{Parse, ParserContext};
@@ -25,8 +24,9 @@ use values::computed::font::FamilyName;
 use values::generics::font::FontStyle as GenericFontStyle;
 use values::specified::Angle;
 use values::specified::font::{AbsoluteFontWeight, FontStretch as SpecifiedFontStretch};
+use values::specified::font::SpecifiedFontFeatureSettings;
 #[cfg(feature = "gecko")]
-use values::specified::font::{SpecifiedFontFeatureSettings, SpecifiedFontVariationSettings};
+use values::specified::font::SpecifiedFontVariationSettings;
 use values::specified::font::SpecifiedFontStyle;
 use values::specified::url::SpecifiedUrl;
 
@@ -219,14 +219,94 @@ pub struct FontFace<'a>(&'a FontFaceRuleData);
 #[cfg_attr(feature = "servo", derive(Deserialize, Serialize))]
 pub struct EffectiveSources(Vec<Source>);
 
+/// Where the data of a font face created by script through the CSS Font Loading API comes from.
+///
+/// <https://drafts.csswg.org/css-font-loading/#fontface-interface>
+#[cfg(feature = "servo")]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum ScriptFontFaceSource {
+    /// The effective sources of a `src` descriptor.
+    Sources(EffectiveSources),
+    /// The data of a font file.
+    Data(Vec<u8>),
+}
+
+/// The descriptors of a font face which decide when and how its sources are used, sent through
+/// IPC to the font cache along with them.
+#[cfg(feature = "servo")]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct FontFaceDescriptors {
+    /// The inclusive ranges of code points the font face can be used for.
+    pub unicode_ranges: Vec<(u32, u32)>,
+    /// How the font face is displayed while it loads.
+    pub display: FontDisplay,
+    /// The OpenType features set by the font face, as tags and values.
+    pub feature_settings: Vec<(u32, u32)>,
+}
+
+#[cfg(feature = "servo")]
+impl FontFaceDescriptors {
+    /// Creates the descriptors of a font face from the values of its `unicode-range`,
+    /// `font-display` and `font-feature-settings` descriptors, if they were specified.
+    pub fn new(
+        unicode_range: Option<&[UnicodeRange]>,
+        display: Option<FontDisplay>,
+        feature_settings: Option<&SpecifiedFontFeatureSettings>,
+    ) -> Self {
+        FontFaceDescriptors {
+            unicode_ranges: match unicode_range {
+                Some(ranges) => ranges.iter().map(|range| (range.start, range.end)).collect(),
+                // The initial value, U+0-10FFFF.
+                None => vec![(0, 0x10FFFF)],
+            },
+            display: display.unwrap_or(FontDisplay::Auto),
+            feature_settings: match feature_settings {
+                Some(settings) => settings.0.iter().map(|feature| {
+                    (feature.tag.0, feature.value.value() as u32)
+                }).collect(),
+                None => vec![],
+            },
+        }
+    }
+
+    /// Returns true if the font face can be used for `codepoint`.
+    pub fn covers(&self, codepoint: char) -> bool {
+        let codepoint = codepoint as u32;
+        self.unicode_ranges.iter().any(|&(start, end)| start <= codepoint && codepoint <= end)
+    }
+
+    /// Returns true if the font face can be used for every code point.
+    pub fn covers_all(&self) -> bool {
+        self.unicode_ranges.iter().any(|&(start, end)| start == 0 && end >= 0x10FFFF)
+    }
+}
+
 #[cfg(feature = "servo")]
 impl<'a> FontFace<'a> {
+    /// Returns the descriptors of this font face which matter to the font cache.
+    pub fn descriptors(&self) -> FontFaceDescriptors {
+        FontFaceDescriptors::new(
+            self.0.unicode_range.as_ref().map(|ranges| &**ranges),
+            self.0.display,
+            self.0.feature_settings.as_ref(),
+        )
+    }
+
     /// Returns the list of effective sources for that font-face, that is the
     /// sources which don't list any format hint, or the ones which list at
     /// least "truetype" or "opentype".
     pub fn effective_sources(&self) -> EffectiveSources {
+        EffectiveSources::new(self.sources())
+    }
+}
+
+#[cfg(feature = "servo")]
+impl EffectiveSources {
+    /// Returns the effective sources among `sources`, as described in
+    /// `FontFace::effective_sources`.
+    pub fn new(sources: &[Source]) -> Self {
         EffectiveSources(
-            self.sources()
+            sources
                 .iter()
                 .rev()
                 .filter(|source| {
@@ -309,6 +389,7 @@ impl Parse for Source {
     }
 }
 
+#[cfg(feature = "gecko")]
 macro_rules! is_descriptor_enabled {
     ("font-display") => {
         unsafe {
@@ -327,6 +408,13 @@ macro_rules! is_descriptor_enabled {
     };
 }
 
+#[cfg(feature = "servo")]
+macro_rules! is_descriptor_enabled {
+    ($name:tt) => {
+        true
+    };
+}
+
 macro_rules! font_face_descriptors_common {
     (
         $( #[$doc: meta] $name: tt $ident: ident / $gecko_ident: ident: $ty: ty, )*
@@ -393,6 +481,15 @@ macro_rules! font_face_descriptors_common {
     }
 }
 
+#[cfg(feature = "servo")]
+impl FontFaceRuleData {
+    /// Identifies this rule among the live ones, so that layout and script can tell each other
+    /// about the font face it defines.
+    pub fn id(&self) -> usize {
+        self as *const FontFaceRuleData as usize
+    }
+}
+
 impl ToCssWithGuard for FontFaceRuleData {
     // Serialization of FontFaceRule is not specced.
     fn to_css(&self, _guard: &SharedRwLockReadGuard, dest: &mut CssStringWriter) -> fmt::Result {
@@ -491,5 +588,13 @@ font_face_descriptors! {
         "src" sources / mSrc: Vec<Source>,
     ]
     optional descriptors = [
+        /// The display of this font face.
+        "font-display" display / mDisplay: FontDisplay,
+
+        /// The ranges of code points outside of which this font face should not be used.
+        "unicode-range" unicode_range / mUnicodeRange: Vec<UnicodeRange>,
+
+        /// The feature settings of this font face.
+        "font-feature-settings" feature_settings / mFontFeatureSettings: SpecifiedFontFeatureSettings,
     ]
 }
@@ -43,6 +43,7 @@ impl WritingMode {
     /// Return a WritingMode bitflags from the relevant CSS properties.
     pub fn new(inheritedbox_style: &style_structs::InheritedBox) -> Self {
         use properties::longhands::direction::computed_value::T as Direction;
+        use properties::longhands::text_orientation::computed_value::T as TextOrientation;
         use properties::longhands::writing_mode::computed_value::T as SpecifiedWritingMode;
 
         let mut flags = WritingMode::empty();
@@ -77,23 +78,18 @@ impl WritingMode {
             },
         }
 
-        #[cfg(feature = "gecko")]
-        {
-            use properties::longhands::text_orientation::computed_value::T as TextOrientation;
-
-            // If FLAG_SIDEWAYS is already set, this means writing-mode is
-            // either sideways-rl or sideways-lr, and for both of these values,
-            // text-orientation has no effect.
-            if !flags.intersects(WritingMode::SIDEWAYS) {
-                match inheritedbox_style.clone_text_orientation() {
-                    TextOrientation::Mixed => {},
-                    TextOrientation::Upright => {
-                        flags.insert(WritingMode::UPRIGHT);
-                    },
-                    TextOrientation::Sideways => {
-                        flags.insert(WritingMode::SIDEWAYS);
-                    },
-                }
+        // If FLAG_SIDEWAYS is already set, this means writing-mode is
+        // either sideways-rl or sideways-lr, and for both of these values,
+        // text-orientation has no effect.
+        if !flags.intersects(WritingMode::SIDEWAYS) {
+            match inheritedbox_style.clone_text_orientation() {
+                TextOrientation::Mixed => {},
+                TextOrientation::Upright => {
+                    flags.insert(WritingMode::UPRIGHT);
+                },
+                TextOrientation::Sideways => {
+                    flags.insert(WritingMode::SIDEWAYS);
+                },
             }
         }
 
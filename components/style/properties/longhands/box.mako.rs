@@ -293,7 +293,6 @@ ${helpers.predefined_type("animation-delay",
 ${helpers.predefined_type("scroll-snap-destination",
                           "Position",
                           "computed::Position::zero()",
-                          products="gecko",
                           gecko_pref="layout.css.scroll-snap.enabled",
                           boxed=True,
                           spec="Nonstandard (https://developer.mozilla.org/en-US/docs/Web/CSS/scroll-snap-destination)",
@@ -304,7 +303,6 @@ ${helpers.predefined_type(
     "Position",
     "computed::Position::zero()",
     vector=True,
-    products="gecko",
     gecko_pref="layout.css.scroll-snap.enabled",
     spec="Nonstandard (https://developer.mozilla.org/en-US/docs/Web/CSS/scroll-snap-destination)",
     animation_value_type="discrete",
@@ -361,7 +359,6 @@ ${helpers.predefined_type(
 ${helpers.single_keyword("scroll-behavior",
                          "auto smooth",
                          gecko_pref="layout.css.scroll-behavior.property-enabled",
-                         products="gecko",
                          spec="https://drafts.csswg.org/cssom-view/#propdef-scroll-behavior",
                          animation_value_type="discrete")}
 
@@ -370,7 +367,6 @@ ${helpers.single_keyword("scroll-behavior",
         "scroll-snap-type-" + axis,
         "ScrollSnapType",
         "computed::ScrollSnapType::None",
-        products="gecko",
         needs_context=False,
         gecko_pref="layout.css.scroll-snap.enabled",
         spec="Nonstandard (https://developer.mozilla.org/en-US/docs/Web/CSS/scroll-snap-type-x)",
@@ -420,6 +416,22 @@ ${helpers.single_keyword("page-break-inside",
                          spec="https://drafts.csswg.org/css2/page.html#propdef-page-break-inside",
                          animation_value_type="discrete")}
 
+% for side in ["before", "after"]:
+${helpers.single_keyword("break-" + side,
+                         "auto avoid avoid-page page left right recto verso avoid-column column",
+                         products="servo",
+                         spec="https://drafts.csswg.org/css-break/#propdef-break-" + side,
+                         animation_value_type="discrete",
+                         servo_restyle_damage="reflow")}
+% endfor
+
+${helpers.single_keyword("break-inside",
+                         "auto avoid avoid-page avoid-column",
+                         products="servo",
+                         spec="https://drafts.csswg.org/css-break/#propdef-break-inside",
+                         animation_value_type="discrete",
+                         servo_restyle_damage="reflow")}
+
 // CSS Basic User Interface Module Level 3
 // http://dev.w3.org/csswg/css-ui
 // FIXME support logical values `block` and `inline` (https://drafts.csswg.org/css-logical-props/#resize)
@@ -569,10 +581,10 @@ ${helpers.predefined_type(
 
 ${helpers.predefined_type(
     "shape-image-threshold", "Opacity", "0.0",
-    products="gecko",
     gecko_pref="layout.css.shape-outside.enabled",
     animation_value_type="ComputedValue",
     flags="APPLIES_TO_FIRST_LETTER",
+    servo_restyle_damage="reflow",
     spec="https://drafts.csswg.org/css-shapes/#shape-image-threshold-property",
 )}
 
@@ -580,10 +592,10 @@ ${helpers.predefined_type(
     "shape-margin",
     "NonNegativeLengthOrPercentage",
     "computed::NonNegativeLengthOrPercentage::zero()",
-    products="gecko",
     gecko_pref="layout.css.shape-outside.enabled",
     animation_value_type="NonNegativeLengthOrPercentage",
     flags="APPLIES_TO_FIRST_LETTER",
+    servo_restyle_damage="reflow",
     spec="https://drafts.csswg.org/css-shapes/#shape-margin-property",
 )}
 
@@ -591,11 +603,11 @@ ${helpers.predefined_type(
     "shape-outside",
     "basic_shape::FloatAreaShape",
     "generics::basic_shape::ShapeSource::None",
-    products="gecko",
     boxed=True,
     gecko_pref="layout.css.shape-outside.enabled",
     animation_value_type="ComputedValue",
     flags="APPLIES_TO_FIRST_LETTER",
+    servo_restyle_damage="reflow",
     spec="https://drafts.csswg.org/css-shapes/#shape-outside-property",
 )}
 
@@ -603,7 +615,6 @@ ${helpers.predefined_type(
     "touch-action",
     "TouchAction",
     "computed::TouchAction::auto()",
-    products="gecko",
     gecko_pref="layout.css.touch_action.enabled",
     animation_value_type="discrete",
     spec="https://compat.spec.whatwg.org/#touch-action",
@@ -32,16 +32,18 @@ ${helpers.predefined_type(
 
 
 ${helpers.single_keyword("column-fill", "balance auto", extra_prefixes="moz",
-                         products="gecko", animation_value_type="discrete",
+                         animation_value_type="discrete",
                          gecko_enum_prefix="StyleColumnFill",
-                         spec="https://drafts.csswg.org/css-multicol/#propdef-column-fill")}
+                         servo_pref="layout.columns.enabled",
+                         spec="https://drafts.csswg.org/css-multicol/#propdef-column-fill",
+                         servo_restyle_damage="reflow")}
 
 ${helpers.predefined_type("column-rule-width",
                           "BorderSideWidth",
                           "::values::computed::NonNegativeLength::new(3.)",
                           initial_specified_value="specified::BorderSideWidth::Medium",
                           computed_type="::values::computed::NonNegativeLength",
-                          products="gecko",
+                          servo_pref="layout.columns.enabled",
                           spec="https://drafts.csswg.org/css-multicol/#propdef-column-rule-width",
                           animation_value_type="NonNegativeLength",
                           extra_prefixes="moz")}
@@ -52,7 +54,7 @@ ${helpers.predefined_type(
     "Color",
     "computed_value::T::currentcolor()",
     initial_specified_value="specified::Color::currentcolor()",
-    products="gecko",
+    servo_pref="layout.columns.enabled",
     animation_value_type="AnimatedColor",
     extra_prefixes="moz",
     ignored_when_colors_disabled=True,
@@ -60,15 +62,18 @@ ${helpers.predefined_type(
 )}
 
 ${helpers.single_keyword("column-span", "none all",
-                         products="gecko", animation_value_type="discrete",
+                         animation_value_type="discrete",
                          gecko_enum_prefix="StyleColumnSpan",
                          gecko_pref="layout.css.column-span.enabled",
+                         servo_pref="layout.columns.enabled",
                          spec="https://drafts.csswg.org/css-multicol/#propdef-column-span",
-                         extra_prefixes="moz:layout.css.column-span.enabled")}
+                         extra_prefixes="moz:layout.css.column-span.enabled",
+                         servo_restyle_damage="rebuild_and_reflow")}
 
 ${helpers.single_keyword("column-rule-style",
                          "none hidden dotted dashed solid double groove ridge inset outset",
-                         products="gecko", extra_prefixes="moz",
+                         extra_prefixes="moz",
+                         servo_pref="layout.columns.enabled",
                          gecko_constant_prefix="NS_STYLE_BORDER_STYLE",
                          animation_value_type="discrete",
                          spec="https://drafts.csswg.org/css-multicol/#propdef-column-rule-style")}
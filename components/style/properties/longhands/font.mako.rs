@@ -105,12 +105,12 @@ ${helpers.predefined_type(
 ${helpers.single_keyword_system(
     "font-kerning",
     "auto none normal",
-    products="gecko",
     gecko_ffi_name="mFont.kerning",
     gecko_constant_prefix="NS_FONT_KERNING",
     spec="https://drafts.csswg.org/css-fonts/#propdef-font-kerning",
     flags="APPLIES_TO_FIRST_LETTER APPLIES_TO_FIRST_LINE APPLIES_TO_PLACEHOLDER",
     animation_value_type="discrete",
+    servo_restyle_damage="rebuild_and_reflow",
 )}
 
 ${helpers.predefined_type(
@@ -138,12 +138,12 @@ ${helpers.predefined_type(
 ${helpers.predefined_type(
     "font-variant-ligatures",
     "FontVariantLigatures",
-    products="gecko",
     initial_value="computed::FontVariantLigatures::empty()",
     initial_specified_value="specified::FontVariantLigatures::empty()",
     animation_value_type="discrete",
     flags="APPLIES_TO_FIRST_LETTER APPLIES_TO_FIRST_LINE APPLIES_TO_PLACEHOLDER",
     spec="https://drafts.csswg.org/css-fonts/#propdef-font-variant-ligatures",
+    servo_restyle_damage="rebuild_and_reflow",
 )}
 
 ${helpers.predefined_type(
@@ -171,13 +171,13 @@ ${helpers.single_keyword_system(
 ${helpers.predefined_type(
     "font-feature-settings",
     "FontFeatureSettings",
-    products="gecko",
     initial_value="computed::FontFeatureSettings::normal()",
     initial_specified_value="specified::FontFeatureSettings::normal()",
     extra_prefixes="moz:layout.css.prefixes.font-features",
     animation_value_type="discrete",
     flags="APPLIES_TO_FIRST_LETTER APPLIES_TO_FIRST_LINE APPLIES_TO_PLACEHOLDER",
     spec="https://drafts.csswg.org/css-fonts/#propdef-font-feature-settings",
+    servo_restyle_damage="rebuild_and_reflow",
 )}
 
 <%
@@ -190,13 +190,13 @@ https://drafts.csswg.org/css-fonts-4/#low-level-font-variation-settings-control-
 ${helpers.predefined_type(
     "font-variation-settings",
     "FontVariationSettings",
-    products="gecko",
     gecko_pref="layout.css.font-variations.enabled",
     initial_value="computed::FontVariationSettings::normal()",
     initial_specified_value="specified::FontVariationSettings::normal()",
     animation_value_type="ComputedValue",
     flags="APPLIES_TO_FIRST_LETTER APPLIES_TO_FIRST_LINE APPLIES_TO_PLACEHOLDER",
     spec="${variation_spec}",
+    servo_restyle_damage="rebuild_and_reflow",
 )}
 
 ${helpers.predefined_type(
@@ -226,11 +226,11 @@ ${helpers.single_keyword_system(
 ${helpers.predefined_type(
     "-x-lang",
     "XLang",
-    products="gecko",
     initial_value="computed::XLang::get_initial_value()",
     animation_value_type="none",
     enabled_in="",
     spec="Internal (not web-exposed)",
+    servo_restyle_damage="rebuild_and_reflow",
 )}
 
 ${helpers.predefined_type(
@@ -47,9 +47,10 @@ ${helpers.single_keyword(
     "text-orientation",
     "mixed upright sideways",
     extra_gecko_aliases="sideways-right=sideways",
-    products="gecko",
+    servo_pref="layout.writing-mode.enabled",
     animation_value_type="discrete",
     spec="https://drafts.csswg.org/css-writing-modes/#propdef-text-orientation",
+    servo_restyle_damage="rebuild_and_reflow",
 )}
 
 // CSS Color Module Level 4
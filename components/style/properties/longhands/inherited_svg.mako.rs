@@ -14,7 +14,6 @@
 
 ${helpers.single_keyword("text-anchor",
                          "start middle end",
-                         products="gecko",
                          animation_value_type="discrete",
                          spec="https://www.w3.org/TR/SVG/text.html#TextAnchorProperty")}
 
@@ -34,13 +33,12 @@ ${helpers.single_keyword("color-interpolation-filters", "linearrgb auto srgb",
 ${helpers.predefined_type(
     "fill", "SVGPaint",
     "::values::computed::SVGPaint::black()",
-    products="gecko",
     animation_value_type="IntermediateSVGPaint",
     boxed=True,
     spec="https://www.w3.org/TR/SVG2/painting.html#SpecifyingFillPaint")}
 
 ${helpers.predefined_type("fill-opacity", "SVGOpacity", "Default::default()",
-                          products="gecko", animation_value_type="ComputedValue",
+                          animation_value_type="ComputedValue",
                           spec="https://www.w3.org/TR/SVG11/painting.html#FillOpacityProperty")}
 
 ${helpers.single_keyword("fill-rule", "nonzero evenodd",
@@ -57,7 +55,6 @@ ${helpers.single_keyword("shape-rendering",
 ${helpers.predefined_type(
     "stroke", "SVGPaint",
     "Default::default()",
-    products="gecko",
     animation_value_type="IntermediateSVGPaint",
     boxed=True,
     spec="https://www.w3.org/TR/SVG2/painting.html#SpecifyingStrokePaint")}
@@ -65,34 +62,31 @@ ${helpers.predefined_type(
 ${helpers.predefined_type(
     "stroke-width", "SVGWidth",
     "::values::computed::NonNegativeLength::new(1.).into()",
-    products="gecko",
     animation_value_type="::values::computed::SVGWidth",
     spec="https://www.w3.org/TR/SVG2/painting.html#StrokeWidth",
 )}
 
 ${helpers.single_keyword("stroke-linecap", "butt round square",
-                         products="gecko", animation_value_type="discrete",
+                         animation_value_type="discrete",
                          spec="https://www.w3.org/TR/SVG11/painting.html#StrokeLinecapProperty")}
 
 ${helpers.single_keyword("stroke-linejoin", "miter round bevel",
-                         products="gecko", animation_value_type="discrete",
+                         animation_value_type="discrete",
                          spec="https://www.w3.org/TR/SVG11/painting.html#StrokeLinejoinProperty")}
 
 ${helpers.predefined_type("stroke-miterlimit", "GreaterThanOrEqualToOneNumber",
                           "From::from(4.0)",
-                          products="gecko",
                           animation_value_type="::values::computed::GreaterThanOrEqualToOneNumber",
                           spec="https://www.w3.org/TR/SVG11/painting.html#StrokeMiterlimitProperty")}
 
 ${helpers.predefined_type("stroke-opacity", "SVGOpacity", "Default::default()",
-                          products="gecko", animation_value_type="ComputedValue",
+                          animation_value_type="ComputedValue",
                           spec="https://www.w3.org/TR/SVG11/painting.html#StrokeOpacityProperty")}
 
 ${helpers.predefined_type(
     "stroke-dasharray",
     "SVGStrokeDashArray",
     "Default::default()",
-    products="gecko",
     animation_value_type="::values::computed::SVGStrokeDashArray",
     spec="https://www.w3.org/TR/SVG2/painting.html#StrokeDashing",
 )}
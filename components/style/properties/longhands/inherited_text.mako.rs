@@ -30,8 +30,9 @@ ${helpers.single_keyword("text-transform",
 
 ${helpers.single_keyword("hyphens", "manual none auto",
                          gecko_enum_prefix="StyleHyphens",
-                         products="gecko", animation_value_type="discrete", extra_prefixes="moz",
-                         spec="https://drafts.csswg.org/css-text/#propdef-hyphens")}
+                         animation_value_type="discrete", extra_prefixes="moz",
+                         spec="https://drafts.csswg.org/css-text/#propdef-hyphens",
+                         servo_restyle_damage="rebuild_and_reflow")}
 
 // TODO: Support <percentage>
 ${helpers.single_keyword("-moz-text-size-adjust", "auto none",
@@ -48,6 +49,16 @@ ${helpers.predefined_type("text-indent",
                           spec="https://drafts.csswg.org/css-text/#propdef-text-indent",
                           allow_quirks=True, servo_restyle_damage = "reflow")}
 
+% for name in ["orphans", "widows"]:
+${helpers.predefined_type(name,
+                          "PositiveInteger",
+                          "computed::PositiveInteger::from(2)",
+                          products="servo",
+                          animation_value_type="discrete",
+                          spec="https://drafts.csswg.org/css-break/#propdef-" + name,
+                          servo_restyle_damage="reflow")}
+% endfor
+
 // Also known as "word-wrap" (which is more popular because of IE), but this is the preferred
 // name per CSS-TEXT 6.2.
 ${helpers.single_keyword("overflow-wrap",
@@ -292,8 +303,10 @@ ${helpers.single_keyword("ruby-position", "over under",
 // https://drafts.csswg.org/css-writing-modes-3/
 
 ${helpers.single_keyword("text-combine-upright", "none all",
-                         products="gecko", animation_value_type="discrete",
-                         spec="https://drafts.csswg.org/css-writing-modes-3/#text-combine-upright")}
+                         servo_pref="layout.writing-mode.enabled",
+                         animation_value_type="discrete",
+                         spec="https://drafts.csswg.org/css-writing-modes-3/#text-combine-upright",
+                         servo_restyle_damage="rebuild_and_reflow")}
 
 // SVG 1.1: Section 11 - Painting: Filling, Stroking and Marker Symbols
 ${helpers.single_keyword("text-rendering",
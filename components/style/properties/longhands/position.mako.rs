@@ -305,13 +305,12 @@ ${helpers.single_keyword("box-sizing",
                          servo_restyle_damage = "reflow")}
 
 ${helpers.single_keyword("object-fit", "fill contain cover none scale-down",
-                         products="gecko", animation_value_type="discrete",
+                         animation_value_type="discrete",
                          spec="https://drafts.csswg.org/css-images/#propdef-object-fit")}
 
 ${helpers.predefined_type("object-position",
                           "Position",
-                          "computed::Position::zero()",
-                          products="gecko",
+                          "computed::Position::center()",
                           boxed=True,
                           spec="https://drafts.csswg.org/css-images-3/#the-object-position",
                           animation_value_type="ComputedValue")}
@@ -23,13 +23,11 @@ ${helpers.predefined_type(
     "stop-color",
     "Color",
     "RGBA::new(0, 0, 0, 255).into()",
-    products="gecko",
     animation_value_type="AnimatedRGBA",
     spec="https://www.w3.org/TR/SVGTiny12/painting.html#StopColorProperty",
 )}
 
 ${helpers.predefined_type("stop-opacity", "Opacity", "1.0",
-                          products="gecko",
                           animation_value_type="ComputedValue",
                           spec="https://www.w3.org/TR/SVGTiny12/painting.html#propdef-stop-opacity")}
 
@@ -67,7 +65,6 @@ ${helpers.predefined_type(
     "clip-path",
     "basic_shape::ClippingShape",
     "generics::basic_shape::ShapeSource::None",
-    products="gecko",
     boxed=True,
     animation_value_type="ComputedValue",
     flags="CREATES_STACKING_CONTEXT",
@@ -77,7 +74,6 @@ ${helpers.predefined_type(
 ${helpers.single_keyword("mask-mode",
                          "match-source alpha luminance",
                          vector=True,
-                         products="gecko",
                          animation_value_type="discrete",
                          spec="https://drafts.fxtf.org/css-masking/#propdef-mask-mode")}
 
@@ -86,7 +82,6 @@ ${helpers.predefined_type(
     "BackgroundRepeat",
     "computed::BackgroundRepeat::repeat()",
     initial_specified_value="specified::BackgroundRepeat::repeat()",
-    products="gecko",
     extra_prefixes="webkit",
     animation_value_type="discrete",
     spec="https://drafts.fxtf.org/css-masking/#propdef-mask-repeat",
@@ -97,7 +92,6 @@ ${helpers.predefined_type(
     ${helpers.predefined_type(
         "mask-position-" + axis,
         "position::" + direction + "Position",
-        products="gecko",
         extra_prefixes="webkit",
         initial_value="computed::LengthOrPercentage::zero()",
         initial_specified_value="specified::PositionComponent::Center",
@@ -113,7 +107,6 @@ ${helpers.single_keyword(
     "border-box content-box padding-box",
     extra_gecko_values="fill-box stroke-box view-box no-clip",
     vector=True,
-    products="gecko",
     extra_prefixes="webkit",
     gecko_enum_prefix="StyleGeometryBox",
     gecko_inexhaustive=True,
@@ -126,7 +119,6 @@ ${helpers.single_keyword(
     "border-box content-box padding-box",
     extra_gecko_values="fill-box stroke-box view-box",
     vector=True,
-    products="gecko",
     extra_prefixes="webkit",
     gecko_enum_prefix="StyleGeometryBox",
     gecko_inexhaustive=True,
@@ -139,7 +131,6 @@ ${helpers.predefined_type(
     "background::BackgroundSize",
     "computed::BackgroundSize::auto()",
     initial_specified_value="specified::BackgroundSize::auto()",
-    products="gecko",
     extra_prefixes="webkit",
     spec="https://drafts.fxtf.org/css-masking/#propdef-mask-size",
     animation_value_type="MaskSizeList",
@@ -150,7 +141,6 @@ ${helpers.predefined_type(
 ${helpers.single_keyword("mask-composite",
                          "add subtract intersect exclude",
                          vector=True,
-                         products="gecko",
                          extra_prefixes="webkit",
                          animation_value_type="discrete",
                          spec="https://drafts.fxtf.org/css-masking/#propdef-mask-composite")}
@@ -160,7 +150,6 @@ ${helpers.predefined_type("mask-image", "ImageLayer",
     initial_specified_value="Either::First(None_)",
     spec="https://drafts.fxtf.org/css-masking/#propdef-mask-image",
     vector=True,
-    products="gecko",
     extra_prefixes="webkit",
     animation_value_type="discrete",
     flags="CREATES_STACKING_CONTEXT")}
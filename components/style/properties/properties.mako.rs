@@ -3059,13 +3059,21 @@ impl ComputedValuesInner {
     /// Return true if the effects force the transform style to be Flat
     pub fn overrides_transform_style(&self) -> bool {
         use computed_values::mix_blend_mode::T as MixBlendMode;
+        use values::Either;
+        use values::generics::basic_shape::ShapeSource;
 
         let effects = self.get_effects();
-        // TODO(gw): Add clip-path, isolation, mask-image, mask-border-source when supported.
+        let svg = self.get_svg();
+        // TODO(gw): Add isolation, mask-border-source when supported.
         effects.opacity < 1.0 ||
            !effects.filter.0.is_empty() ||
            !effects.clip.is_auto() ||
-           effects.mix_blend_mode != MixBlendMode::Normal
+           effects.mix_blend_mode != MixBlendMode::Normal ||
+           svg.clip_path != ShapeSource::None ||
+           svg.mask_image.0.iter().any(|image| match *image {
+               Either::First(_none) => false,
+               Either::Second(_image) => true,
+           })
     }
 
     /// <https://drafts.csswg.org/css-transforms/#grouping-property-values>
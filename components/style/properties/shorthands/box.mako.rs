@@ -381,7 +381,7 @@ macro_rules! try_parse_one {
     }
 </%helpers:shorthand>
 
-<%helpers:shorthand name="scroll-snap-type" products="gecko"
+<%helpers:shorthand name="scroll-snap-type"
                     gecko_pref="layout.css.scroll-snap.enabled"
                     sub_properties="scroll-snap-type-x scroll-snap-type-y"
                     spec="https://drafts.csswg.org/css-scroll-snap/#propdef-scroll-snap-type">
@@ -441,3 +441,73 @@ macro_rules! try_parse_one {
         }
     }
 </%helpers:shorthand>
+
+// Servo only implements the break-* longhands, the legacy page-break-* properties are aliases of
+// them.
+% for side in ["before", "after"]:
+<%helpers:shorthand name="page-break-${side}" products="servo"
+                    sub_properties="break-${side}"
+                    spec="https://drafts.csswg.org/css-break/#page-break-properties">
+    use properties::longhands::break_${side}::SpecifiedValue;
+
+    pub fn parse_value<'i, 't>(
+        _: &ParserContext,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Longhands, ParseError<'i>> {
+        let value = try_match_ident_ignore_ascii_case! { input,
+            "auto" => SpecifiedValue::Auto,
+            "always" => SpecifiedValue::Page,
+            "avoid" => SpecifiedValue::Avoid,
+            "left" => SpecifiedValue::Left,
+            "right" => SpecifiedValue::Right,
+        };
+        Ok(expanded! {
+            break_${side}: value,
+        })
+    }
+
+    impl<'a> ToCss for LonghandsToSerialize<'a>  {
+        // Serializes into an empty string if break-${side} has no legacy equivalent.
+        fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result where W: fmt::Write {
+            match *self.break_${side} {
+                SpecifiedValue::Auto => dest.write_str("auto"),
+                SpecifiedValue::Page => dest.write_str("always"),
+                SpecifiedValue::Avoid => dest.write_str("avoid"),
+                SpecifiedValue::Left => dest.write_str("left"),
+                SpecifiedValue::Right => dest.write_str("right"),
+                _ => Ok(()),
+            }
+        }
+    }
+</%helpers:shorthand>
+% endfor
+
+<%helpers:shorthand name="page-break-inside" products="servo"
+                    sub_properties="break-inside"
+                    spec="https://drafts.csswg.org/css-break/#page-break-properties">
+    use properties::longhands::break_inside::SpecifiedValue;
+
+    pub fn parse_value<'i, 't>(
+        _: &ParserContext,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Longhands, ParseError<'i>> {
+        let value = try_match_ident_ignore_ascii_case! { input,
+            "auto" => SpecifiedValue::Auto,
+            "avoid" => SpecifiedValue::Avoid,
+        };
+        Ok(expanded! {
+            break_inside: value,
+        })
+    }
+
+    impl<'a> ToCss for LonghandsToSerialize<'a>  {
+        // Serializes into an empty string if break-inside has no legacy equivalent.
+        fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result where W: fmt::Write {
+            match *self.break_inside {
+                SpecifiedValue::Auto => dest.write_str("auto"),
+                SpecifiedValue::Avoid => dest.write_str("avoid"),
+                _ => Ok(()),
+            }
+        }
+    }
+</%helpers:shorthand>
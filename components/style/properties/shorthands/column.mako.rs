@@ -55,7 +55,8 @@
     }
 </%helpers:shorthand>
 
-<%helpers:shorthand name="column-rule" products="gecko" extra_prefixes="moz"
+<%helpers:shorthand name="column-rule" extra_prefixes="moz"
+    servo_pref="layout.columns.enabled"
     sub_properties="column-rule-width column-rule-style column-rule-color"
     derive_serialize="True"
     spec="https://drafts.csswg.org/css-multicol/#propdef-column-rule">
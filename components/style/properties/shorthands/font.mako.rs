@@ -9,16 +9,16 @@
                     sub_properties="font-style font-variant-caps font-weight font-stretch
                                     font-size line-height font-family
                                     ${'font-size-adjust' if product == 'gecko' else ''}
-                                    ${'font-kerning' if product == 'gecko' else ''}
+                                    font-kerning
                                     ${'font-optical-sizing' if product == 'gecko' else ''}
                                     ${'font-variant-alternates' if product == 'gecko' else ''}
                                     ${'font-variant-east-asian' if product == 'gecko' else ''}
-                                    ${'font-variant-ligatures' if product == 'gecko' else ''}
+                                    font-variant-ligatures
                                     ${'font-variant-numeric' if product == 'gecko' else ''}
                                     ${'font-variant-position' if product == 'gecko' else ''}
                                     ${'font-language-override' if product == 'gecko' else ''}
-                                    ${'font-feature-settings' if product == 'gecko' else ''}
-                                    ${'font-variation-settings' if product == 'gecko' else ''}"
+                                    font-feature-settings
+                                    font-variation-settings"
                     derive_value_info="False"
                     spec="https://drafts.csswg.org/css-fonts-3/#propdef-font">
     use parser::Parse;
@@ -36,12 +36,12 @@
                                 variant_ligatures variant_numeric \
                                 variant_position feature_settings \
                                 variation_settings optical_sizing".split()
+        servo_sub_properties = "kerning variant_ligatures feature_settings variation_settings".split()
+        reset_sub_properties = gecko_sub_properties if product == "gecko" else servo_sub_properties
     %>
-    % if product == "gecko":
-        % for prop in gecko_sub_properties:
-            use properties::longhands::font_${prop};
-        % endfor
-    % endif
+    % for prop in reset_sub_properties:
+        use properties::longhands::font_${prop};
+    % endfor
     use self::font_family::SpecifiedValue as FontFamily;
 
     pub fn parse_value<'i, 't>(
@@ -135,11 +135,9 @@
             font_size: size,
             line_height: line_height.unwrap_or(LineHeight::normal()),
             font_family: family,
-            % if product == "gecko":
-                % for name in gecko_sub_properties:
-                    font_${name}: font_${name}::get_initial_specified_value(),
-                % endfor
-            % endif
+            % for name in reset_sub_properties:
+                font_${name}: font_${name}::get_initial_specified_value(),
+            % endfor
         })
     }
 
@@ -172,15 +170,15 @@
                     return Ok(());
                 }
             }
+            % endif
 
-            % for name in gecko_sub_properties:
-            % if name != "optical_sizing" and name != "variation_settings":
+            % for name in reset_sub_properties:
+            % if product == "servo" or (name != "optical_sizing" and name != "variation_settings"):
             if self.font_${name} != &font_${name}::get_initial_specified_value() {
                 return Ok(());
             }
             % endif
             % endfor
-            % endif
 
             // Only font-stretch keywords are allowed as part as the font
             // shorthand.
@@ -289,21 +287,20 @@
                     sub_properties="font-variant-caps
                                     ${'font-variant-alternates' if product == 'gecko' else ''}
                                     ${'font-variant-east-asian' if product == 'gecko' else ''}
-                                    ${'font-variant-ligatures' if product == 'gecko' else ''}
+                                    font-variant-ligatures
                                     ${'font-variant-numeric' if product == 'gecko' else ''}
                                     ${'font-variant-position' if product == 'gecko' else ''}"
                     spec="https://drafts.csswg.org/css-fonts-3/#propdef-font-variant">
-    <% gecko_sub_properties = "alternates east_asian ligatures numeric position".split() %>
     <%
-        sub_properties = ["caps"]
         if product == "gecko":
-            sub_properties += gecko_sub_properties
+            sub_properties = "caps alternates east_asian ligatures numeric position".split()
+        else:
+            sub_properties = ["caps", "ligatures"]
     %>
 
 % for prop in sub_properties:
     use properties::longhands::font_variant_${prop};
 % endfor
-    #[allow(unused_imports)]
     use values::specified::FontVariantLigatures;
 
     pub fn parse_value<'i, 't>(
@@ -319,9 +316,7 @@
         } else if input.try(|input| input.expect_ident_matching("none")).is_ok() {
             // The 'none' value sets 'font-variant-ligatures' to 'none' and resets all other sub properties
             // to their initial value.
-        % if product == "gecko":
             ligatures = Some(FontVariantLigatures::none());
-        % endif
         } else {
             let mut has_custom_value: bool = false;
             loop {
@@ -358,12 +353,7 @@
         #[allow(unused_assignments)]
         fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result where W: fmt::Write {
 
-            let has_none_ligatures =
-            % if product == "gecko":
-                self.font_variant_ligatures == &FontVariantLigatures::none();
-            % else:
-                false;
-            % endif
+            let has_none_ligatures = self.font_variant_ligatures == &FontVariantLigatures::none();
 
             const TOTAL_SUBPROPS: usize = ${len(sub_properties)};
             let mut nb_normals = 0;
@@ -21,7 +21,7 @@ use malloc_size_of::{MallocShallowSizeOf, MallocSizeOf, MallocSizeOfOps};
 use malloc_size_of::MallocUnconditionalShallowSizeOf;
 use media_queries::Device;
 use properties::{self, CascadeMode, ComputedValues};
-use properties::{AnimationRules, PropertyDeclarationBlock};
+use properties::{AnimationRules, Importance, PropertyDeclarationBlock};
 use rule_cache::{RuleCache, RuleCacheConditions};
 use rule_tree::{CascadeLevel, RuleTree, ShadowCascadeOrder, StrongRuleNode, StyleSource};
 use selector_map::{PrecomputedHashMap, SelectorMap, SelectorMapEntry};
@@ -44,8 +44,8 @@ use style_traits::viewport::ViewportConstraints;
 use stylesheet_set::{DataValidity, DocumentStylesheetSet, SheetRebuildKind};
 use stylesheet_set::{DocumentStylesheetFlusher, SheetCollectionFlusher};
 #[cfg(feature = "gecko")]
-use stylesheets::{CounterStyleRule, FontFaceRule, FontFeatureValuesRule, PageRule};
-use stylesheets::{CssRule, Origin, OriginSet, PerOrigin, PerOriginIter};
+use stylesheets::{CounterStyleRule, FontFaceRule, FontFeatureValuesRule};
+use stylesheets::{CssRule, Origin, OriginSet, PageRule, PerOrigin, PerOriginIter};
 use stylesheets::StyleRule;
 use stylesheets::StylesheetInDocument;
 use stylesheets::keyframes_rule::KeyframesAnimation;
@@ -431,6 +431,16 @@ impl Stylist {
         ExtraStyleDataIterator(self.cascade_data.iter_origins_rev())
     }
 
+    /// Returns the declarations of all the effective @page rules, in cascade
+    /// order.
+    ///
+    /// Page selectors are not supported, so every rule applies to every page.
+    pub fn page_declarations(&self, guards: &StylesheetGuards) -> PropertyDeclarationBlock {
+        let origins = self.iter_extra_data_origins_rev()
+            .map(|(data, origin)| (origin, &*data.pages));
+        cascade_page_rules(origins, guards)
+    }
+
     /// Returns the number of selectors.
     pub fn num_selectors(&self) -> usize {
         self.cascade_data
@@ -1623,7 +1633,7 @@ pub struct ExtraStyleData {
     pub counter_styles: PrecomputedHashMap<Atom, Arc<Locked<CounterStyleRule>>>,
 
     /// A map of effective page rules.
-    #[cfg(feature = "gecko")]
+    #[cfg_attr(feature = "servo", ignore_malloc_size_of = "Arc")]
     pub pages: Vec<Arc<Locked<PageRule>>>,
 }
 
@@ -1653,22 +1663,22 @@ impl ExtraStyleData {
         let name = rule.read_with(guard).name().0.clone();
         self.counter_styles.insert(name, rule.clone());
     }
+}
 
+impl ExtraStyleData {
     /// Add the given @page rule.
     fn add_page(&mut self, rule: &Arc<Locked<PageRule>>) {
         self.pages.push(rule.clone());
     }
-}
 
-impl ExtraStyleData {
     fn clear(&mut self) {
         #[cfg(feature = "gecko")]
         {
             self.font_faces.clear();
             self.font_feature_values.clear();
             self.counter_styles.clear();
-            self.pages.clear();
         }
+        self.pages.clear();
     }
 }
 
@@ -2301,7 +2311,6 @@ impl CascadeData {
                 CssRule::CounterStyle(ref rule) => {
                     self.extra_data.add_counter_style(guard, rule);
                 },
-                #[cfg(feature = "gecko")]
                 CssRule::Page(ref rule) => {
                     self.extra_data.add_page(rule);
                 },
@@ -2540,3 +2549,31 @@ pub fn needs_revalidation_for_testing(s: &Selector<SelectorImpl>) -> bool {
     s.visit(&mut visitor);
     visitor.needs_revalidation
 }
+
+/// Cascades the declarations of the given @page rules, grouped by origin from
+/// the lowest precedence to the highest, into a single block.
+///
+/// Normal declarations override those from origins of lower precedence, and
+/// important ones those from origins of higher precedence.
+pub fn cascade_page_rules<'a, I>(origins: I, guards: &StylesheetGuards) -> PropertyDeclarationBlock
+where
+    I: Iterator<Item = (Origin, &'a [Arc<Locked<PageRule>>])>,
+{
+    let origins = origins.collect::<Vec<_>>();
+    let normal = origins.iter().map(|origin| (origin, Importance::Normal));
+    let important = origins.iter().rev().map(|origin| (origin, Importance::Important));
+
+    let mut block = PropertyDeclarationBlock::new();
+    for (&(origin, rules), importance) in normal.chain(important) {
+        let guard = guards.for_origin(origin);
+        for rule in rules {
+            let declarations = rule.read_with(guard).block.read_with(guard);
+            for (declaration, declaration_importance) in declarations.declaration_importance_iter() {
+                if declaration_importance == importance {
+                    block.push(declaration.clone(), Importance::Normal);
+                }
+            }
+        }
+    }
+    block
+}
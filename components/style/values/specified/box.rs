@@ -533,8 +533,7 @@ impl Parse for WillChange {
 }
 
 bitflags! {
-    #[cfg_attr(feature = "gecko", derive(MallocSizeOf))]
-    #[derive(SpecifiedValueInfo, ToComputedValue)]
+    #[derive(MallocSizeOf, SpecifiedValueInfo, ToComputedValue)]
     /// These constants match Gecko's `NS_STYLE_TOUCH_ACTION_*` constants.
     #[value_info(other_values = "auto,none,manipulation,pan-x,pan-y")]
     pub struct TouchAction: u8 {
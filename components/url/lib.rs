@@ -88,6 +88,22 @@ impl ServoUrl {
         scheme == "https" || scheme == "wss"
     }
 
+    /// <https://w3c.github.io/webappsec-secure-contexts/#is-url-trustworthy>
+    pub fn is_potentially_trustworthy(&self) -> bool {
+        if self.as_str() == "about:blank" || self.as_str() == "about:srcdoc" {
+            return true;
+        }
+        if self.is_secure_scheme() || self.scheme() == "file" || self.scheme() == "data" {
+            return true;
+        }
+        match self.0.host() {
+            Some(Host::Domain(domain)) => domain == "localhost" || domain.ends_with(".localhost"),
+            Some(Host::Ipv4(ip)) => ip.is_loopback(),
+            Some(Host::Ipv6(ip)) => ip.is_loopback(),
+            None => false,
+        }
+    }
+
     pub fn is_chrome(&self) -> bool {
         self.scheme() == "chrome"
     }
@@ -323,6 +323,9 @@ impl ServoGlue {
                     info!("Alert: {}", message);
                     let _ = sender.send(());
                 },
+                EmbedderMsg::GetClipboardContents(sender) => {
+                    let _ = sender.send(String::new());
+                },
                 EmbedderMsg::CloseBrowser |
                 EmbedderMsg::Status(..) |
                 EmbedderMsg::SelectFiles(..) |
@@ -335,6 +338,8 @@ impl ServoGlue {
                 EmbedderMsg::SetFullscreenState(..) |
                 EmbedderMsg::ShowIME(..) |
                 EmbedderMsg::HideIME |
+                EmbedderMsg::SecurityStateChanged(..) |
+                EmbedderMsg::SetClipboardContents(..) |
                 EmbedderMsg::Shutdown |
                 EmbedderMsg::Panic(..) => {},
             }
@@ -412,11 +417,15 @@ impl resources::ResourceReaderMethods for ResourceReader {
             Resource::SSLCertificates => "certs",
             Resource::BadCertHTML => "badcert.html",
             Resource::NetErrorHTML => "neterror.html",
+            Resource::CrashHTML => "crash.html",
+            Resource::DebuggerJS => "debugger.js",
             Resource::UserAgentCSS => "user-agent.css",
             Resource::ServoCSS => "servo.css",
             Resource::PresentationalHintsCSS => "presentational-hints.css",
             Resource::QuirksModeCSS => "quirks-mode.css",
             Resource::RippyPNG => "rippy.png",
+            Resource::EnglishHyphenationPatterns => "hyphenation/hyph-en-us.pat.txt",
+            Resource::GermanHyphenationPatterns => "hyphenation/hyph-de-1996.pat.txt",
         };
         debug!("ResourceReader::read({})", file);
         self.0.readfile(file)
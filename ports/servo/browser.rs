@@ -2,6 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+use clipboard::{ClipboardContext, ClipboardProvider};
 use euclid::{TypedPoint2D, TypedVector2D};
 use glutin_app::keyutils::{CMD_OR_CONTROL, CMD_OR_ALT};
 use glutin_app::window::{Window, LINE_HEIGHT};
@@ -32,6 +33,7 @@ pub struct Browser {
     loading_state: Option<LoadingState>,
     window: Rc<Window>,
     event_queue: Vec<WindowEvent>,
+    clipboard_ctx: Option<ClipboardContext>,
     shutdown_requested: bool,
 }
 
@@ -52,6 +54,13 @@ impl Browser {
             loading_state: None,
             window: window,
             event_queue: Vec::new(),
+            clipboard_ctx: match ClipboardContext::new() {
+                Ok(c) => Some(c),
+                Err(e) => {
+                    warn!("Error creating clipboard context ({})", e);
+                    None
+                },
+            },
             shutdown_requested: false,
         }
     }
@@ -342,6 +351,31 @@ impl Browser {
                 EmbedderMsg::HideIME => {
                     debug!("HideIME received");
                 }
+                EmbedderMsg::SecurityStateChanged(state) => {
+                    debug!("Security state changed to {:?}", state);
+                }
+                EmbedderMsg::GetClipboardContents(sender) => {
+                    let contents = match self.clipboard_ctx {
+                        Some(ref mut ctx) => match ctx.get_contents() {
+                            Ok(c) => c,
+                            Err(e) => {
+                                warn!("Error getting clipboard contents ({}), defaulting to empty string", e);
+                                String::new()
+                            },
+                        },
+                        None => String::new(),
+                    };
+                    if let Err(e) = sender.send(contents) {
+                        warn!("Failed to send clipboard ({})", e);
+                    }
+                }
+                EmbedderMsg::SetClipboardContents(text) => {
+                    if let Some(ref mut ctx) = self.clipboard_ctx {
+                        if let Err(e) = ctx.set_contents(text) {
+                            warn!("Error setting clipboard contents ({})", e);
+                        }
+                    }
+                }
             }
         }
     }
@@ -513,9 +513,23 @@ impl Window {
 
                 let scroll_location = ScrollLocation::Delta(TypedVector2D::new(dx, dy));
                 let phase = winit_phase_to_touch_event_type(phase);
-                let event = WindowEvent::Scroll(scroll_location, self.mouse_pos.get(), phase);
+                let event = WindowEvent::Wheel(scroll_location, self.mouse_pos.get(), phase);
                 self.event_queue.borrow_mut().push(event);
             },
+            Event::WindowEvent {
+                event: winit::WindowEvent::DroppedFile(path),
+                ..
+            } => {
+                // Files dropped together arrive one at a time, but are dropped onto the page at
+                // once.
+                let mut event_queue = self.event_queue.borrow_mut();
+                if let Some(&mut WindowEvent::DropFiles(ref mut paths, _)) = event_queue.last_mut() {
+                    paths.push(path);
+                    return;
+                }
+                let point = self.mouse_pos.get().to_f32();
+                event_queue.push(WindowEvent::DropFiles(vec![path], point));
+            },
             Event::WindowEvent {
                 event: winit::WindowEvent::Touch(touch),
                 ..
@@ -24,11 +24,15 @@ fn filename(file: Resource) -> &'static str {
         Resource::SSLCertificates => "certs",
         Resource::BadCertHTML => "badcert.html",
         Resource::NetErrorHTML => "neterror.html",
+        Resource::CrashHTML => "crash.html",
+        Resource::DebuggerJS => "debugger.js",
         Resource::UserAgentCSS => "user-agent.css",
         Resource::ServoCSS => "servo.css",
         Resource::PresentationalHintsCSS => "presentational-hints.css",
         Resource::QuirksModeCSS => "quirks-mode.css",
         Resource::RippyPNG => "rippy.png",
+        Resource::EnglishHyphenationPatterns => "hyphenation/hyph-en-us.pat.txt",
+        Resource::GermanHyphenationPatterns => "hyphenation/hyph-de-1996.pat.txt",
     }
 }
 
@@ -0,0 +1,347 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+// The JavaScript debugger of a script thread, run in a global of its own by
+// components/script/debugger.rs. Requests arrive as JSON strings through
+// `handleRequest`, and pauses are reported through the native `pause`
+// function, which only returns once the devtools client resumes.
+
+"use strict";
+
+// Debugger state per debugged pipeline, keyed by the serialized pipeline id.
+var pipelines = new Map();
+var nextSourceId = 1;
+var nextBreakpointId = 1;
+// Object ids are never reused, so that the actors of a previous pause can not refer to
+// the objects of the current one.
+var nextObjectId = 1;
+
+function handleRequest(json) {
+  var request = JSON.parse(json);
+  var key = JSON.stringify(request.pipeline);
+  if (request.type == "attach") {
+    attach(key);
+    return "null";
+  }
+  var state = pipelines.get(key);
+  if (!state) {
+    return "null";
+  }
+  var reply = null;
+  switch (request.type) {
+    case "detach":
+      clearStepping(state);
+      state.dbg.removeAllDebuggees();
+      pipelines.delete(key);
+      break;
+    case "sources":
+      reply = [];
+      state.sourcesById.forEach(function (source, id) {
+        reply.push({ id: id, url: source.url || "" });
+      });
+      break;
+    case "sourceText":
+      var source = state.sourcesById.get(request.source);
+      reply = source ? source.text : null;
+      break;
+    case "setBreakpoint":
+      reply = setBreakpoint(state, request.location);
+      break;
+    case "removeBreakpoint":
+      removeBreakpoint(state, request.breakpoint);
+      break;
+    case "interrupt":
+      state.dbg.onEnterFrame = function (frame) {
+        return pauseAt(state, frame, "interrupted", []);
+      };
+      break;
+    case "frames":
+      reply = [];
+      for (var frame = state.pausedFrame; frame; frame = frame.older) {
+        if (frame.script) {
+          reply.push(frameInfo(state, frame, reply.length));
+        }
+      }
+      break;
+    case "scopes":
+      var frame = frameAt(state, request.depth);
+      reply = frame ? scopes(state, frame) : null;
+      break;
+    case "objectProperties":
+      reply = objectProperties(state, request.object);
+      break;
+  }
+  return JSON.stringify(reply);
+}
+
+function attach(key) {
+  if (pipelines.has(key)) {
+    return;
+  }
+  var state = {
+    key: key,
+    dbg: new Debugger(debuggee),
+    scripts: [],
+    sources: new Map(),
+    sourcesById: new Map(),
+    breakpoints: new Map(),
+    pausedFrame: null,
+    steppingFrames: [],
+    objectIds: new Map(),
+    objectsById: new Map(),
+  };
+  state.dbg.findScripts().forEach(function (script) {
+    addScript(state, script);
+  });
+  state.dbg.onNewScript = function (script) {
+    addScriptTree(state, script);
+  };
+  state.dbg.onDebuggerStatement = function (frame) {
+    return pauseAt(state, frame, "debuggerStatement", []);
+  };
+  pipelines.set(key, state);
+}
+
+function addScriptTree(state, script) {
+  addScript(state, script);
+  script.getChildScripts().forEach(function (child) {
+    addScriptTree(state, child);
+  });
+}
+
+function addScript(state, script) {
+  state.scripts.push(script);
+  var source = script.source;
+  if (!state.sources.has(source)) {
+    var id = nextSourceId++;
+    state.sources.set(source, id);
+    state.sourcesById.set(id, source);
+  }
+  // Breakpoints set before a script was (re)loaded apply to the new code too.
+  var sourceId = state.sources.get(source);
+  state.breakpoints.forEach(function (breakpoint) {
+    if (breakpoint.location.source == sourceId) {
+      setBreakpointOnScript(state, breakpoint, script, breakpoint.location.line);
+    }
+  });
+}
+
+function setBreakpoint(state, location) {
+  var source = state.sourcesById.get(location.source);
+  if (!source) {
+    return null;
+  }
+  var scripts = state.scripts.filter(function (script) {
+    return script.source === source;
+  });
+  var lastLine = 0;
+  scripts.forEach(function (script) {
+    lastLine = Math.max(lastLine, script.startLine + script.lineCount);
+  });
+  // Slide the breakpoint down to the first line that has code on it.
+  for (var line = location.line; line <= lastLine; line++) {
+    var breakpoint = {
+      id: nextBreakpointId,
+      location: { source: location.source, line: line, column: 0 },
+      handlers: [],
+    };
+    scripts.forEach(function (script) {
+      setBreakpointOnScript(state, breakpoint, script, line);
+    });
+    if (breakpoint.handlers.length) {
+      nextBreakpointId++;
+      state.breakpoints.set(breakpoint.id, breakpoint);
+      return { id: breakpoint.id, location: breakpoint.location };
+    }
+  }
+  return null;
+}
+
+function setBreakpointOnScript(state, breakpoint, script, line) {
+  script.getLineOffsets(line).forEach(function (offset) {
+    if (!breakpoint.handlers.length) {
+      breakpoint.location.column = script.getOffsetLocation(offset).columnNumber;
+    }
+    var handler = {
+      hit: function (frame) {
+        return pauseAt(state, frame, "breakpoint", [breakpoint.id]);
+      },
+    };
+    script.setBreakpoint(offset, handler);
+    breakpoint.handlers.push({ script: script, handler: handler });
+  });
+}
+
+function removeBreakpoint(state, id) {
+  var breakpoint = state.breakpoints.get(id);
+  if (!breakpoint) {
+    return;
+  }
+  breakpoint.handlers.forEach(function (entry) {
+    entry.script.clearBreakpoint(entry.handler);
+  });
+  state.breakpoints.delete(id);
+}
+
+function location(state, frame) {
+  var offset = frame.script.getOffsetLocation(frame.offset);
+  return {
+    source: state.sources.get(frame.script.source) || 0,
+    line: offset.lineNumber,
+    column: offset.columnNumber,
+  };
+}
+
+function frameInfo(state, frame, depth) {
+  var callee = frame.callee;
+  return {
+    depth: depth,
+    kind: frame.type,
+    function_name: callee ? (callee.displayName || callee.name || null) : null,
+    location: location(state, frame),
+  };
+}
+
+// The paused frame at the given depth, counted as in the "frames" reply.
+function frameAt(state, depth) {
+  for (var frame = state.pausedFrame; frame; frame = frame.older) {
+    if (frame.script && depth-- == 0) {
+      return frame;
+    }
+  }
+  return null;
+}
+
+function scopes(state, frame) {
+  var reply = [];
+  for (var env = frame.environment; env; env = env.parent) {
+    var callee = env.callee;
+    var bindings = [];
+    // The global scope has too many names to list.
+    if (env.parent) {
+      env.names().forEach(function (name) {
+        bindings.push([name, valueInfo(state, env.getVariable(name))]);
+      });
+    }
+    reply.push({
+      kind: env.type,
+      function_name: callee ? (callee.displayName || callee.name || null) : null,
+      bindings: bindings,
+    });
+  }
+  return reply;
+}
+
+// Serialize a debuggee value as a `DebuggerValue`.
+function valueInfo(state, value) {
+  switch (typeof value) {
+    case "undefined":
+      return { Primitive: "VoidValue" };
+    case "boolean":
+      return { Primitive: { BooleanValue: value } };
+    case "number":
+      // JSON has no representation of the non-finite numbers, and loses the sign of zero.
+      if (!isFinite(value) || Object.is(value, -0)) {
+        return { SpecialNumber: Object.is(value, -0) ? "-0" : String(value) };
+      }
+      return { Primitive: { NumberValue: value } };
+    case "string":
+      return { Primitive: { StringValue: value } };
+    case "object":
+      if (value === null) {
+        return { Primitive: "NullValue" };
+      }
+      // Optimized out, uninitialized and missing variables.
+      if (!(value instanceof Debugger.Object)) {
+        return { Primitive: "VoidValue" };
+      }
+      if (!state.objectIds.has(value)) {
+        var id = nextObjectId++;
+        state.objectIds.set(value, id);
+        state.objectsById.set(id, value);
+      }
+      return { Object: { class: value.class, id: state.objectIds.get(value) } };
+    default:
+      return { Primitive: { StringValue: String(value) } };
+  }
+}
+
+// The prototype and own data properties of the object with the given id, which are
+// all the protocol's "prototypeAndProperties" request needs.
+function objectProperties(state, id) {
+  var object = state.objectsById.get(id);
+  if (!object) {
+    return null;
+  }
+  var properties = [];
+  object.getOwnPropertyNames().forEach(function (name) {
+    var descriptor = object.getOwnPropertyDescriptor(name);
+    if (descriptor && "value" in descriptor) {
+      properties.push([name, valueInfo(state, descriptor.value)]);
+    }
+  });
+  return { prototype: valueInfo(state, object.proto), properties: properties };
+}
+
+function pauseAt(state, frame, reason, breakpoints) {
+  clearStepping(state);
+  state.pausedFrame = frame;
+  var info = { reason: reason, breakpoints: breakpoints, frame: frameInfo(state, frame, 0) };
+  var step = pause(state.key, JSON.stringify(info));
+  state.pausedFrame = null;
+  state.objectIds.clear();
+  state.objectsById.clear();
+  // The client may have detached while we were paused.
+  if (step && pipelines.get(state.key) === state) {
+    startStepping(state, frame, step);
+  }
+  return undefined;
+}
+
+function clearStepping(state) {
+  state.steppingFrames.forEach(function (frame) {
+    if (frame.live) {
+      frame.onStep = undefined;
+      frame.onPop = undefined;
+    }
+  });
+  state.steppingFrames = [];
+  state.dbg.onEnterFrame = undefined;
+}
+
+function startStepping(state, frame, kind) {
+  var start = location(state, frame);
+  // Pause at the next statement that starts on another line, or in another frame.
+  function onStep() {
+    var offset = this.script.getOffsetLocation(this.offset);
+    if (offset.isEntryPoint && (this !== frame || offset.lineNumber != start.line)) {
+      return pauseAt(state, this, "resumeLimit", []);
+    }
+    return undefined;
+  }
+  // Once the frame returns, continue stepping in its caller.
+  function onPop() {
+    var older = this.older;
+    if (older && older.script) {
+      older.onStep = onStep;
+      older.onPop = onPop;
+      state.steppingFrames.push(older);
+    }
+    return undefined;
+  }
+
+  if (kind != "finish") {
+    frame.onStep = onStep;
+  }
+  frame.onPop = onPop;
+  state.steppingFrames.push(frame);
+  if (kind == "step") {
+    state.dbg.onEnterFrame = function (entered) {
+      entered.onStep = onStep;
+      entered.onPop = onPop;
+      state.steppingFrames.push(entered);
+      return undefined;
+    };
+  }
+}
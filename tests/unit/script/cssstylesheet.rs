@@ -0,0 +1,22 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use script::test::cssstylesheet::parses_without_errors;
+
+#[test]
+fn valid_style_sheet_text() {
+    assert!(parses_without_errors(""));
+    assert!(parses_without_errors("p { color: red }\n@media print { a { color: blue } }"));
+}
+
+#[test]
+fn style_sheet_text_with_invalid_declarations() {
+    assert!(!parses_without_errors("p { colr: red }"));
+    assert!(!parses_without_errors("p { color: 12px }"));
+}
+
+#[test]
+fn style_sheet_text_with_invalid_selectors() {
+    assert!(!parses_without_errors("p[ { color: red }"));
+}
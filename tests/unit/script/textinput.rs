@@ -10,7 +10,7 @@
 use msg::constellation_msg::{Key, KeyModifiers};
 use script::clipboard_provider::DummyClipboardContext;
 use script::test::DOMString;
-use script::textinput::{TextInput, TextPoint, Selection, Lines, Direction, SelectionDirection};
+use script::textinput::{ClipboardAction, TextInput, TextPoint, Selection, Lines, Direction, SelectionDirection};
 
 fn text_input(lines: Lines, s: &str) -> TextInput<DummyClipboardContext> {
     TextInput::new(lines,
@@ -519,10 +519,23 @@ fn test_clipboard_paste() {
                                        SelectionDirection::None);
     assert_eq!(textinput.get_content(), "defg");
     assert_eq!(textinput.edit_point().index, 0);
+    // The paste event fired by the document does the pasting.
     textinput.handle_keydown_aux(Some('v'), Key::V, MODIFIERS);
+    assert_eq!(textinput.get_content(), "defg");
+    textinput.handle_clipboard_action(ClipboardAction::Paste(DOMString::from("abc")));
     assert_eq!(textinput.get_content(), "abcdefg");
 }
 
+#[test]
+fn test_clipboard_cut() {
+    let mut textinput = text_input(Lines::Single, "abcdef");
+    textinput.adjust_horizontal(1, Selection::NotSelected);
+    textinput.adjust_horizontal(3, Selection::Selected);
+    textinput.handle_clipboard_action(ClipboardAction::Cut);
+    assert_eq!(textinput.get_content(), "aef");
+    assert_eq!(textinput.edit_point().index, 1);
+}
+
 #[test]
 fn test_textinput_cursor_position_correct_after_clearing_selection() {
     let mut textinput = text_input(Lines::Single, "abcdef");
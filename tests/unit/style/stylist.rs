@@ -8,17 +8,19 @@ use euclid::TypedSize2D;
 use selectors::parser::{AncestorHashes, Selector};
 use servo_arc::Arc;
 use servo_atoms::Atom;
+use servo_url::ServoUrl;
 use style::context::QuirksMode;
-use style::media_queries::{Device, MediaType};
+use style::media_queries::{Device, MediaList, MediaType};
 use style::properties::{PropertyDeclarationBlock, PropertyDeclaration};
 use style::properties::{longhands, Importance};
 use style::selector_map::SelectorMap;
 use style::selector_parser::{SelectorImpl, SelectorParser};
-use style::shared_lock::SharedRwLock;
-use style::stylesheets::StyleRule;
-use style::stylist::{Stylist, Rule};
+use style::shared_lock::{SharedRwLock, StylesheetGuards};
+use style::stylesheets::{CssRule, Origin, StyleRule, Stylesheet};
+use style::stylist::{Stylist, Rule, cascade_page_rules};
 use style::stylist::needs_revalidation_for_testing;
 use style::thread_state::{self, ThreadState};
+use style_traits::ToCss;
 
 /// Helper method to get some Rules from selector strings.
 /// Each sublist of the result contains the Rules for one StyleRule.
@@ -199,3 +201,36 @@ fn test_stylist_rule_tree_accessors() {
     stylist.rule_tree();
     stylist.rule_tree().root();
 }
+
+#[test]
+fn test_page_rules_cascade() {
+    let lock = SharedRwLock::new();
+    let page_rules = |css, origin| {
+        let sheet = Stylesheet::from_str(
+            css,
+            ServoUrl::parse("http://localhost").unwrap(),
+            origin,
+            Arc::new(lock.wrap(MediaList::empty())),
+            lock.clone(),
+            None,
+            None,
+            QuirksMode::NoQuirks,
+            0
+        );
+        let guard = lock.read();
+        let rules = sheet.contents.rules.read_with(&guard);
+        rules.0.iter().filter_map(|rule| match *rule {
+            CssRule::Page(ref rule) => Some(rule.clone()),
+            _ => None,
+        }).collect::<Vec<_>>()
+    };
+
+    let user_agent = page_rules("@page { margin-top: 1px !important; margin-right: 2px }", Origin::UserAgent);
+    let author = page_rules("@page { margin-top: 3px; margin-right: 4px; margin-bottom: 5px !important } \
+                             @page { margin-bottom: 6px; margin-left: 7px }", Origin::Author);
+
+    let guard = lock.read();
+    let origins = vec![(Origin::UserAgent, &*user_agent), (Origin::Author, &*author)];
+    let block = cascade_page_rules(origins.into_iter(), &StylesheetGuards::same(&guard));
+    assert_eq!(block.to_css_string(), "margin: 1px 4px 5px 7px;");
+}